@@ -0,0 +1,66 @@
+//! Config file loading for TOML and JSON
+//!
+//! Budget: docs/advanced/performance.md
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use fortified_llm_client::load_config_file;
+use std::hint::black_box;
+
+/// Production-style config: sampling, token limits and a composite input
+/// guardrail in front of a regex output guardrail
+const CONFIG_TOML: &str = r#"
+api_url = "https://api.openai.com/v1/chat/completions"
+model = "gpt-4"
+api_key_name = "OPENAI_API_KEY"
+system_prompt = "You are a compliance analyst. Summarize the document and list every obligation."
+temperature = 0.0
+max_tokens = 2000
+seed = 42
+validate_tokens = true
+context_limit = 128000
+timeout_secs = 300
+
+[guardrails.input]
+type = "composite"
+execution = "sequential"
+aggregation = "all_must_pass"
+
+[[guardrails.input.providers]]
+type = "regex"
+max_length_bytes = 1048576
+builtin_patterns = ["pii", "injection", "secrets"]
+severity_threshold = "Medium"
+
+[[guardrails.input.providers]]
+type = "llama_guard"
+api_url = "http://localhost:11434/v1/chat/completions"
+model = "llama-guard3:8b"
+timeout_secs = 30
+enabled_categories = ["S1", "S2", "S3", "S4", "S5"]
+
+[guardrails.output]
+type = "regex"
+max_length_bytes = 2097152
+builtin_patterns = ["pii", "harmful_output", "secrets"]
+"#;
+
+fn bench_config_parsing(c: &mut Criterion) {
+    let dir = tempfile::tempdir().unwrap();
+    let toml_path = dir.path().join("config.toml");
+    std::fs::write(&toml_path, CONFIG_TOML).unwrap();
+    let json_path = dir.path().join("config.json");
+    let value: serde_json::Value = toml::from_str(CONFIG_TOML).unwrap();
+    std::fs::write(&json_path, serde_json::to_string_pretty(&value).unwrap()).unwrap();
+
+    let mut group = c.benchmark_group("config_parsing");
+    group.bench_function("toml", |b| {
+        b.iter(|| load_config_file(black_box(&toml_path)).unwrap())
+    });
+    group.bench_function("json", |b| {
+        b.iter(|| load_config_file(black_box(&json_path)).unwrap())
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_config_parsing);
+criterion_main!(benches);
@@ -0,0 +1,107 @@
+//! Regex guardrail throughput on representative large documents
+//!
+//! Budget: docs/advanced/performance.md
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use fortified_llm_client::{
+    create_guardrail_provider, guardrails::builder::RegexBuilder, GuardrailConfigBuilder,
+    GuardrailProvider, OutputContext, RegexAction,
+};
+use std::hint::black_box;
+use tokio::runtime::Runtime;
+
+/// Contract-like prose of roughly `bytes` bytes that passes every built-in set
+fn clean_document(bytes: usize) -> String {
+    const PARAGRAPH: &str = "Section 4.2. The Supplier shall notify the Customer in writing \
+        within thirty (30) days of any change to the processing of personal data, including \
+        sub-processors located outside the European Economic Area. Failure to comply entitles \
+        the Customer to terminate this Agreement without penalty.\n\n";
+    PARAGRAPH.repeat(bytes / PARAGRAPH.len() + 1)
+}
+
+/// Same document with contact details sprinkled through every paragraph
+fn pii_document(bytes: usize) -> String {
+    const PARAGRAPH: &str = "Notices under Section 4.2 go to legal@supplier.example or \
+        555-867-5309, attention of the Data Protection Officer, who shall acknowledge receipt \
+        within two (2) business days.\n\n";
+    PARAGRAPH.repeat(bytes / PARAGRAPH.len() + 1)
+}
+
+fn provider(builder: RegexBuilder) -> Box<dyn GuardrailProvider> {
+    let config = builder.max_length_bytes(8 * 1024 * 1024).build().unwrap();
+    create_guardrail_provider(&config).unwrap()
+}
+
+fn bench_input(c: &mut Criterion) {
+    let rt = Runtime::new().unwrap();
+    let guardrail = provider(
+        GuardrailConfigBuilder::regex()
+            .with_pii(true)
+            .with_injection(true)
+            .with_secrets(true),
+    );
+
+    let mut group = c.benchmark_group("regex_input");
+    for size in [100 * 1024, 1024 * 1024] {
+        let document = clean_document(size);
+        group.throughput(Throughput::Bytes(document.len() as u64));
+        group.bench_with_input(BenchmarkId::new("validate", size), &document, |b, doc| {
+            b.iter(|| rt.block_on(guardrail.validate(black_box(doc))).unwrap())
+        });
+    }
+    group.finish();
+}
+
+fn bench_output(c: &mut Criterion) {
+    let rt = Runtime::new().unwrap();
+    let guardrail = provider(
+        GuardrailConfigBuilder::regex()
+            .with_pii(true)
+            .with_harmful_output(true)
+            .with_secrets(true),
+    );
+    let context = OutputContext::default();
+
+    let mut group = c.benchmark_group("regex_output");
+    for size in [100 * 1024, 1024 * 1024] {
+        let response = clean_document(size);
+        group.throughput(Throughput::Bytes(response.len() as u64));
+        group.bench_with_input(
+            BenchmarkId::new("validate_output", size),
+            &response,
+            |b, response| {
+                b.iter(|| {
+                    rt.block_on(guardrail.validate_output(black_box(response), &context))
+                        .unwrap()
+                })
+            },
+        );
+    }
+    group.finish();
+}
+
+fn bench_redaction(c: &mut Criterion) {
+    let guardrail = provider(
+        GuardrailConfigBuilder::regex()
+            .with_pii(true)
+            .action(RegexAction::Redact),
+    );
+
+    let mut group = c.benchmark_group("regex_redact");
+    for size in [100 * 1024, 1024 * 1024] {
+        let document = pii_document(size);
+        group.throughput(Throughput::Bytes(document.len() as u64));
+        group.bench_with_input(BenchmarkId::new("redact", size), &document, |b, doc| {
+            b.iter(|| guardrail.redact(black_box(doc)))
+        });
+    }
+    group.finish();
+}
+
+criterion_group! {
+    name = benches;
+    // Megabyte inputs take tens of milliseconds per iteration
+    config = Criterion::default().sample_size(20);
+    targets = bench_input, bench_output, bench_redaction
+}
+criterion_main!(benches);
@@ -0,0 +1,67 @@
+//! Token estimation cost on representative prompt sizes
+//!
+//! Budget: docs/advanced/performance.md
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use fortified_llm_client::TokenEstimator;
+use std::hint::black_box;
+
+const SYSTEM_PROMPT: &str = "You are a compliance analyst. Summarize the document, \
+    list every obligation it imposes and flag clauses that conflict with the policy.";
+
+/// Contract-like prose of roughly `bytes` bytes
+fn document(bytes: usize) -> String {
+    const PARAGRAPH: &str = "Section 4.2. The Supplier shall notify the Customer in writing \
+        within thirty (30) days of any change to the processing of personal data, including \
+        sub-processors located outside the European Economic Area. Failure to comply entitles \
+        the Customer to terminate this Agreement without penalty.\n\n";
+    PARAGRAPH.repeat(bytes / PARAGRAPH.len() + 1)
+}
+
+fn bench_token_estimation(c: &mut Criterion) {
+    let mut group = c.benchmark_group("token_estimation");
+    for size in [10 * 1024, 100 * 1024, 1024 * 1024] {
+        let user_prompt = document(size);
+        group.bench_with_input(
+            BenchmarkId::new("generic", size),
+            &user_prompt,
+            |b, text| {
+                b.iter(|| {
+                    TokenEstimator::new(SYSTEM_PROMPT, black_box(text), 4096)
+                        .total_tokens_required()
+                })
+            },
+        );
+        // Registry model: tokenizer lookup plus its backend (heuristic
+        // unless the `tiktoken` feature is enabled, then a full BPE encode)
+        group.bench_with_input(
+            BenchmarkId::new("new_for_model/gpt-4", size),
+            &user_prompt,
+            |b, text| {
+                b.iter(|| {
+                    TokenEstimator::new_for_model(SYSTEM_PROMPT, black_box(text), 4096, "gpt-4")
+                        .total_tokens_required()
+                })
+            },
+        );
+        group.bench_with_input(
+            BenchmarkId::new("new_for_model/unknown", size),
+            &user_prompt,
+            |b, text| {
+                b.iter(|| {
+                    TokenEstimator::new_for_model(
+                        SYSTEM_PROMPT,
+                        black_box(text),
+                        4096,
+                        "in-house-model",
+                    )
+                    .total_tokens_required()
+                })
+            },
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_token_estimation);
+criterion_main!(benches);
@@ -0,0 +1,32 @@
+//! Build metadata reported by `--version --json`
+
+use std::{env, process::Command};
+
+fn main() {
+    // Packaged builds (no .git) can pass the commit in FORTIFIED_GIT_SHA
+    let sha = env::var("FORTIFIED_GIT_SHA").ok().or_else(|| {
+        Command::new("git")
+            .args(["rev-parse", "--short=12", "HEAD"])
+            .output()
+            .ok()
+            .filter(|output| output.status.success())
+            .and_then(|output| String::from_utf8(output.stdout).ok())
+    });
+    println!(
+        "cargo:rustc-env=FORTIFIED_GIT_SHA={}",
+        sha.unwrap_or_default().trim()
+    );
+
+    let mut features: Vec<String> = env::vars()
+        .filter_map(|(key, _)| {
+            key.strip_prefix("CARGO_FEATURE_")
+                .map(|feature| feature.to_lowercase().replace('_', "-"))
+        })
+        .collect();
+    features.sort();
+    println!("cargo:rustc-env=FORTIFIED_FEATURES={}", features.join(","));
+
+    println!("cargo:rerun-if-env-changed=FORTIFIED_GIT_SHA");
+    println!("cargo:rerun-if-changed=.git/HEAD");
+    println!("cargo:rerun-if-changed=.git/refs");
+}
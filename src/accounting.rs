@@ -0,0 +1,390 @@
+//! Per-tenant / per-key usage accounting
+//!
+//! Tracks cumulative requests, tokens and (when pricing is configured) cost
+//! per account in memory. Accounts are arbitrary strings: tenant IDs, or API
+//! key fingerprints from [`api_key_account`] so raw keys are never stored.
+//!
+//! Optional hard quotas block further evaluations once an account's usage
+//! reaches a limit, and an export hook is called after every recorded
+//! evaluation so embedders can forward usage to billing or metrics systems.
+//! Quota-checked evaluations [`reserve`](UsageAccountant::reserve) their
+//! request before running and [`settle`](UsageAccountant::settle) the tokens
+//! afterwards, so concurrent evaluations cannot all slip past a nearly
+//! exhausted request quota.
+
+use crate::{evaluate, CliError, CliOutput, EvaluationConfig};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::{
+    collections::HashMap,
+    sync::{Mutex, RwLock},
+};
+
+/// Cumulative usage for one account
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct UsageRecord {
+    /// Evaluations that reached the pipeline (including blocked ones)
+    pub requests: u64,
+    /// Estimated tokens across recorded evaluations
+    pub tokens: u64,
+    /// Accumulated cost (only when pricing is configured)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cost_usd: Option<f64>,
+}
+
+/// Token pricing used to derive cost
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Pricing {
+    pub usd_per_1k_tokens: f64,
+}
+
+impl Pricing {
+    pub fn cost(&self, tokens: u64) -> f64 {
+        tokens as f64 / 1000.0 * self.usd_per_1k_tokens
+    }
+}
+
+/// Hard usage limits (any `None` limit is unlimited)
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Quota {
+    pub max_requests: Option<u64>,
+    pub max_tokens: Option<u64>,
+    pub max_cost_usd: Option<f64>,
+}
+
+impl Quota {
+    /// Describe the first exhausted limit, if any
+    fn exceeded_by(&self, usage: &UsageRecord) -> Option<String> {
+        if let Some(max) = self.max_requests {
+            if usage.requests >= max {
+                return Some(format!("request quota of {max} reached"));
+            }
+        }
+        if let Some(max) = self.max_tokens {
+            if usage.tokens >= max {
+                return Some(format!(
+                    "token quota of {max} reached ({} used)",
+                    usage.tokens
+                ));
+            }
+        }
+        if let (Some(max), Some(cost)) = (self.max_cost_usd, usage.cost_usd) {
+            if cost >= max {
+                return Some(format!("cost quota of ${max:.2} reached (${cost:.4} used)"));
+            }
+        }
+        None
+    }
+}
+
+/// Callback invoked with `(account, cumulative usage)` after each recorded evaluation
+pub type UsageExportHook = Box<dyn Fn(&str, &UsageRecord) + Send + Sync>;
+
+/// Derive a stable account name from an API key without retaining the key
+pub fn api_key_account(api_key: &str) -> String {
+    let digest = Sha256::digest(api_key.as_bytes());
+    let hex: String = digest[..8].iter().map(|b| format!("{b:02x}")).collect();
+    format!("key:{hex}")
+}
+
+/// In-memory usage accountant
+#[derive(Default)]
+pub struct UsageAccountant {
+    usage: Mutex<HashMap<String, UsageRecord>>,
+    quotas: RwLock<HashMap<String, Quota>>,
+    default_quota: Option<Quota>,
+    pricing: Option<Pricing>,
+    export_hook: Option<UsageExportHook>,
+}
+
+impl UsageAccountant {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Enable cost tracking with the given pricing
+    pub fn with_pricing(mut self, pricing: Pricing) -> Self {
+        self.pricing = Some(pricing);
+        self
+    }
+
+    /// Quota applied to accounts without an explicit quota
+    pub fn with_default_quota(mut self, quota: Quota) -> Self {
+        self.default_quota = Some(quota);
+        self
+    }
+
+    /// Register a callback invoked after every recorded evaluation
+    pub fn with_export_hook(
+        mut self,
+        hook: impl Fn(&str, &UsageRecord) + Send + Sync + 'static,
+    ) -> Self {
+        self.export_hook = Some(Box::new(hook));
+        self
+    }
+
+    /// Set (or replace) the quota for one account
+    pub fn set_quota(&self, account: impl Into<String>, quota: Quota) {
+        self.quotas
+            .write()
+            .expect("quota lock poisoned")
+            .insert(account.into(), quota);
+    }
+
+    /// Fail with `QuotaExceeded` if the account has exhausted any limit
+    ///
+    /// Read-only: use [`UsageAccountant::reserve`] to also count the request.
+    pub fn check_quota(&self, account: &str) -> Result<(), CliError> {
+        let usage = self.usage(account).unwrap_or_default();
+        self.ensure_within_quota(account, &usage)
+    }
+
+    /// Check the quota and count one request, atomically
+    ///
+    /// The check and the increment happen under the usage lock, so of N
+    /// concurrent reservations against a request quota only the remaining
+    /// allowance succeeds. Report the evaluation's tokens with
+    /// [`UsageAccountant::settle`] once it finishes, whatever its result.
+    pub fn reserve(&self, account: &str) -> Result<(), CliError> {
+        let mut usage = self.usage.lock().expect("usage lock poisoned");
+        let current = usage.get(account).cloned().unwrap_or_default();
+        self.ensure_within_quota(account, &current)?;
+        usage.entry(account.to_string()).or_default().requests += 1;
+        Ok(())
+    }
+
+    fn ensure_within_quota(&self, account: &str, usage: &UsageRecord) -> Result<(), CliError> {
+        let quotas = self.quotas.read().expect("quota lock poisoned");
+        let Some(quota) = quotas.get(account).or(self.default_quota.as_ref()) else {
+            return Ok(());
+        };
+        match quota.exceeded_by(usage) {
+            Some(reason) => Err(CliError::QuotaExceeded(format!(
+                "account '{account}': {reason}"
+            ))),
+            None => Ok(()),
+        }
+    }
+
+    /// Record one evaluation's token usage and return the updated totals
+    pub fn record(&self, account: &str, tokens: u64) -> UsageRecord {
+        self.add(account, 1, tokens)
+    }
+
+    /// Add the tokens of an evaluation counted by [`UsageAccountant::reserve`]
+    pub fn settle(&self, account: &str, tokens: u64) -> UsageRecord {
+        self.add(account, 0, tokens)
+    }
+
+    /// Settle a reserved evaluation from its result: the output's tokens, or
+    /// none for a hard error (the request itself stays counted)
+    pub fn settle_result(
+        &self,
+        account: &str,
+        result: &Result<CliOutput, CliError>,
+    ) -> UsageRecord {
+        self.settle(account, result.as_ref().map_or(0, output_tokens))
+    }
+
+    /// Record usage from a completed evaluation output: the provider-reported
+    /// tokens when available, the estimate otherwise
+    pub fn record_output(&self, account: &str, output: &CliOutput) -> UsageRecord {
+        self.record(account, output_tokens(output))
+    }
+
+    fn add(&self, account: &str, requests: u64, tokens: u64) -> UsageRecord {
+        let snapshot = {
+            let mut usage = self.usage.lock().expect("usage lock poisoned");
+            let record = usage.entry(account.to_string()).or_default();
+            record.requests += requests;
+            record.tokens += tokens;
+            if let Some(pricing) = self.pricing {
+                *record.cost_usd.get_or_insert(0.0) += pricing.cost(tokens);
+            }
+            record.clone()
+        };
+
+        if let Some(hook) = &self.export_hook {
+            hook(account, &snapshot);
+        }
+        snapshot
+    }
+
+    /// Current usage for one account
+    pub fn usage(&self, account: &str) -> Option<UsageRecord> {
+        self.usage
+            .lock()
+            .expect("usage lock poisoned")
+            .get(account)
+            .cloned()
+    }
+
+    /// Snapshot of all accounts' usage (for export)
+    pub fn snapshot(&self) -> HashMap<String, UsageRecord> {
+        self.usage.lock().expect("usage lock poisoned").clone()
+    }
+
+    /// Clear one account's usage (e.g. at the start of a billing period)
+    pub fn reset(&self, account: &str) {
+        self.usage
+            .lock()
+            .expect("usage lock poisoned")
+            .remove(account);
+    }
+
+    /// Quota-checked evaluation that records usage under `account`
+    pub async fn evaluate(
+        &self,
+        account: &str,
+        config: EvaluationConfig,
+    ) -> Result<CliOutput, CliError> {
+        self.reserve(account)?;
+        let result = evaluate(config).await;
+        self.settle_result(account, &result);
+        result
+    }
+}
+
+/// Provider-reported tokens when available, the estimate otherwise
+fn output_tokens(output: &CliOutput) -> u64 {
+    let metadata = &output.metadata;
+    match (
+        metadata.tokens_prompt_actual,
+        metadata.tokens_completion_actual,
+    ) {
+        (None, None) => metadata.tokens_estimated as u64,
+        (prompt, completion) => prompt.unwrap_or(0) + completion.unwrap_or(0),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    };
+
+    #[test]
+    fn test_record_accumulates_usage() {
+        let accountant = UsageAccountant::new();
+        accountant.record("tenant-a", 100);
+        let usage = accountant.record("tenant-a", 50);
+
+        assert_eq!(usage.requests, 2);
+        assert_eq!(usage.tokens, 150);
+        assert_eq!(usage.cost_usd, None);
+        assert!(accountant.usage("tenant-b").is_none());
+    }
+
+    #[test]
+    fn test_pricing_tracks_cost() {
+        let accountant = UsageAccountant::new().with_pricing(Pricing {
+            usd_per_1k_tokens: 0.5,
+        });
+        accountant.record("a", 2000);
+        let usage = accountant.record("a", 1000);
+
+        assert!((usage.cost_usd.unwrap() - 1.5).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn test_quota_blocks_when_exhausted() {
+        let accountant = UsageAccountant::new();
+        accountant.set_quota(
+            "a",
+            Quota {
+                max_tokens: Some(100),
+                ..Default::default()
+            },
+        );
+
+        assert!(accountant.check_quota("a").is_ok());
+        accountant.record("a", 100);
+        let err = accountant.check_quota("a").unwrap_err();
+        assert_eq!(err.code(), "QUOTA_EXCEEDED");
+        assert!(err.to_string().contains("token quota"));
+
+        // Other accounts are unaffected
+        assert!(accountant.check_quota("b").is_ok());
+
+        accountant.reset("a");
+        assert!(accountant.check_quota("a").is_ok());
+    }
+
+    #[test]
+    fn test_reserve_counts_requests_under_the_quota_check() {
+        let accountant = Arc::new(UsageAccountant::new());
+        accountant.set_quota(
+            "a",
+            Quota {
+                max_requests: Some(3),
+                ..Default::default()
+            },
+        );
+
+        // Concurrent reservations: only the remaining allowance succeeds
+        let granted = std::thread::scope(|scope| {
+            let handles: Vec<_> = (0..8)
+                .map(|_| scope.spawn(|| accountant.reserve("a").is_ok()))
+                .collect();
+            handles
+                .into_iter()
+                .map(|h| h.join().unwrap())
+                .filter(|ok| *ok)
+                .count()
+        });
+        assert_eq!(granted, 3);
+
+        let usage = accountant.settle("a", 40);
+        assert_eq!((usage.requests, usage.tokens), (3, 40));
+        assert_eq!(
+            accountant.reserve("a").unwrap_err().code(),
+            "QUOTA_EXCEEDED"
+        );
+        // A rejected reservation does not count
+        assert_eq!(accountant.usage("a").unwrap().requests, 3);
+    }
+
+    #[test]
+    fn test_settle_result_keeps_failed_requests_counted() {
+        let accountant = UsageAccountant::new();
+        accountant.reserve("a").unwrap();
+        let usage =
+            accountant.settle_result("a", &Err(CliError::InvalidResponse("boom".to_string())));
+        assert_eq!((usage.requests, usage.tokens), (1, 0));
+    }
+
+    #[test]
+    fn test_default_quota_applies_to_unknown_accounts() {
+        let accountant = UsageAccountant::new().with_default_quota(Quota {
+            max_requests: Some(1),
+            ..Default::default()
+        });
+        accountant.record("anyone", 1);
+        assert!(accountant.check_quota("anyone").is_err());
+    }
+
+    #[test]
+    fn test_export_hook_receives_cumulative_usage() {
+        let seen = Arc::new(AtomicU64::new(0));
+        let seen_clone = Arc::clone(&seen);
+        let accountant = UsageAccountant::new().with_export_hook(move |account, usage| {
+            assert_eq!(account, "a");
+            seen_clone.store(usage.tokens, Ordering::SeqCst);
+        });
+
+        accountant.record("a", 10);
+        accountant.record("a", 15);
+        assert_eq!(seen.load(Ordering::SeqCst), 25);
+    }
+
+    #[test]
+    fn test_api_key_account_is_stable_and_opaque() {
+        let account = api_key_account("sk-secret-123");
+        assert_eq!(account, api_key_account("sk-secret-123"));
+        assert_ne!(account, api_key_account("sk-secret-456"));
+        assert!(account.starts_with("key:"));
+        assert!(!account.contains("secret"));
+    }
+}
@@ -0,0 +1,162 @@
+//! Content-addressed blob storage for audit payloads
+//!
+//! Large payloads (prompts, extracted PDF text) are stored once under their
+//! SHA-256 digest and referenced from audit events as `sha256:<hex>`, so the
+//! same document submitted thousands of times costs one blob on disk.
+
+use crate::{
+    error::CliError,
+    storage::{FsStorage, Storage},
+};
+use sha2::{Digest, Sha256};
+use std::{path::PathBuf, sync::Arc};
+
+/// Prefix of blob references stored in audit events
+pub const BLOB_REF_PREFIX: &str = "sha256:";
+
+/// Compute the blob reference for `content` without storing it
+pub fn blob_ref(content: &[u8]) -> String {
+    let digest = Sha256::digest(content);
+    let hex: String = digest.iter().map(|b| format!("{b:02x}")).collect();
+    format!("{BLOB_REF_PREFIX}{hex}")
+}
+
+/// Content-addressed blob store over any [`Storage`] backend
+///
+/// Blobs are keyed `<hex[..2]>/<hex[2..]>`; with [`BlobStore::new`] that is a
+/// sharded directory tree under the given root.
+#[derive(Clone)]
+pub struct BlobStore {
+    storage: Arc<dyn Storage>,
+}
+
+impl std::fmt::Debug for BlobStore {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BlobStore").finish_non_exhaustive()
+    }
+}
+
+impl BlobStore {
+    /// Filesystem-backed store rooted at `root`
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self::with_storage(Arc::new(FsStorage::new(root)))
+    }
+
+    pub fn with_storage(storage: Arc<dyn Storage>) -> Self {
+        Self { storage }
+    }
+
+    /// Store `content` (no-op if already present) and return its reference
+    pub fn put(&self, content: &[u8]) -> Result<String, CliError> {
+        let reference = blob_ref(content);
+        let key = key_for(&reference)?;
+        if !self.storage.contains(&key)? {
+            self.storage.put(&key, content, None)?;
+        }
+        Ok(reference)
+    }
+
+    /// Read a blob back (None if it does not exist)
+    pub fn get(&self, reference: &str) -> Result<Option<Vec<u8>>, CliError> {
+        self.storage.get(&key_for(reference)?)
+    }
+
+    pub fn contains(&self, reference: &str) -> bool {
+        key_for(reference).is_ok_and(|key| self.storage.contains(&key).unwrap_or(false))
+    }
+
+    /// References of all stored blobs
+    pub fn list(&self) -> Result<Vec<String>, CliError> {
+        Ok(self
+            .storage
+            .list("")?
+            .into_iter()
+            .map(|key| format!("{BLOB_REF_PREFIX}{}", key.replace('/', "")))
+            .filter(|reference| is_valid_ref(reference))
+            .collect())
+    }
+
+    /// Delete a blob, returning the number of bytes reclaimed
+    pub fn remove(&self, reference: &str) -> Result<u64, CliError> {
+        let key = key_for(reference)?;
+        let Some(size) = self.storage.size(&key)? else {
+            return Ok(0);
+        };
+        self.storage.delete(&key)?;
+        Ok(size)
+    }
+}
+
+fn key_for(reference: &str) -> Result<String, CliError> {
+    if !is_valid_ref(reference) {
+        return Err(CliError::InvalidArguments(format!(
+            "Invalid blob reference '{reference}'"
+        )));
+    }
+    let hex = &reference[BLOB_REF_PREFIX.len()..];
+    Ok(format!("{}/{}", &hex[..2], &hex[2..]))
+}
+
+/// `sha256:` followed by 64 lowercase hex digits (rejects path traversal)
+fn is_valid_ref(reference: &str) -> bool {
+    reference.strip_prefix(BLOB_REF_PREFIX).is_some_and(|hex| {
+        hex.len() == 64
+            && hex
+                .bytes()
+                .all(|b| b.is_ascii_digit() || (b'a'..=b'f').contains(&b))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_put_is_idempotent_and_round_trips() {
+        let dir = TempDir::new().unwrap();
+        let store = BlobStore::new(dir.path());
+
+        let first = store.put(b"large pdf text").unwrap();
+        let second = store.put(b"large pdf text").unwrap();
+
+        assert_eq!(first, second);
+        assert_eq!(first, blob_ref(b"large pdf text"));
+        assert_eq!(store.list().unwrap(), vec![first.clone()]);
+        assert_eq!(store.get(&first).unwrap().unwrap(), b"large pdf text");
+    }
+
+    #[test]
+    fn test_remove_reports_reclaimed_bytes() {
+        let dir = TempDir::new().unwrap();
+        let store = BlobStore::new(dir.path());
+        let reference = store.put(b"12345").unwrap();
+
+        assert_eq!(store.remove(&reference).unwrap(), 5);
+        assert!(!store.contains(&reference));
+        assert_eq!(store.remove(&reference).unwrap(), 0);
+        assert!(store.get(&reference).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_invalid_reference_rejected() {
+        let store = BlobStore::new("/tmp/unused");
+        assert!(store.get("sha256:../../etc/passwd").is_err());
+        assert!(store.get("md5:abc").is_err());
+    }
+
+    #[test]
+    fn test_memory_backed_store() {
+        let store = BlobStore::with_storage(Arc::new(crate::storage::MemoryStorage::new()));
+        let reference = store.put(b"payload").unwrap();
+        assert_eq!(store.list().unwrap(), vec![reference.clone()]);
+        assert_eq!(store.remove(&reference).unwrap(), 7);
+    }
+
+    #[test]
+    fn test_list_on_missing_root_is_empty() {
+        let dir = TempDir::new().unwrap();
+        let store = BlobStore::new(dir.path().join("missing"));
+        assert!(store.list().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,235 @@
+//! Audit log compaction
+//!
+//! Rewrites a JSONL audit log (dropping events older than an optional
+//! retention cutoff) and deletes blobs no longer referenced by any remaining
+//! event. Lines that do not parse as an [`AuditEvent`] (e.g. written by a
+//! newer version) are kept verbatim, and because their blob references are
+//! unknown, blob collection is skipped while any are present. Run it while no
+//! evaluations are writing to the same log: a blob stored just before its
+//! event is appended would otherwise look orphaned.
+
+use super::{AuditEvent, BlobStore, JsonlAuditLog};
+use crate::error::CliError;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::{collections::HashSet, fs, io::Write};
+use tempfile::NamedTempFile;
+
+/// Summary of a compaction run
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct CompactionReport {
+    pub events_kept: usize,
+    /// Events dropped by the retention cutoff
+    pub events_expired: usize,
+    /// Unparseable lines kept verbatim in the log
+    pub lines_unparsed: usize,
+    /// Blob collection was skipped because of unparseable lines
+    pub blob_gc_skipped: bool,
+    pub blobs_kept: usize,
+    pub blobs_removed: usize,
+    pub bytes_reclaimed: u64,
+}
+
+/// Compact `log` and garbage-collect unreferenced blobs from `blobs`
+///
+/// Events with a timestamp before `retain_since` are removed first, so their
+/// payloads are reclaimed in the same pass.
+pub fn compact_audit_log(
+    log: &JsonlAuditLog,
+    blobs: &BlobStore,
+    retain_since: Option<DateTime<Utc>>,
+) -> Result<CompactionReport, CliError> {
+    let mut report = CompactionReport::default();
+
+    let contents = match fs::read_to_string(log.path()) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(e) => {
+            return Err(CliError::FileNotFound(format!(
+                "Failed to read audit log '{}': {e}",
+                log.path().display()
+            )))
+        }
+    };
+
+    let mut kept = Vec::new();
+    for line in contents.lines().filter(|l| !l.trim().is_empty()) {
+        match serde_json::from_str::<AuditEvent>(line) {
+            Ok(event) if retain_since.is_some_and(|since| event.timestamp < since) => {
+                report.events_expired += 1;
+            }
+            Ok(event) => {
+                report.events_kept += 1;
+                kept.push((line, Some(event)));
+            }
+            Err(_) => {
+                report.lines_unparsed += 1;
+                kept.push((line, None));
+            }
+        }
+    }
+
+    if report.events_expired > 0 {
+        rewrite_log(log, kept.iter().map(|(line, _)| *line))?;
+    }
+
+    if report.lines_unparsed > 0 {
+        log::warn!(
+            "Audit compaction: {} unparseable lines in '{}', skipping blob collection",
+            report.lines_unparsed,
+            log.path().display()
+        );
+        report.blob_gc_skipped = true;
+        report.blobs_kept = blobs.list()?.len();
+        return Ok(report);
+    }
+
+    let referenced: HashSet<&str> = kept
+        .iter()
+        .filter_map(|(_, event)| event.as_ref())
+        .flat_map(|event| event.kind.blob_refs())
+        .collect();
+    for reference in blobs.list()? {
+        if referenced.contains(reference.as_str()) {
+            report.blobs_kept += 1;
+        } else {
+            report.bytes_reclaimed += blobs.remove(&reference)?;
+            report.blobs_removed += 1;
+        }
+    }
+
+    log::info!(
+        "Audit compaction: kept {} events, expired {}, removed {} blobs ({} bytes)",
+        report.events_kept,
+        report.events_expired,
+        report.blobs_removed,
+        report.bytes_reclaimed
+    );
+    Ok(report)
+}
+
+/// Atomically replace the log with `lines`
+fn rewrite_log<'a>(
+    log: &JsonlAuditLog,
+    lines: impl Iterator<Item = &'a str>,
+) -> Result<(), CliError> {
+    let path = log.path();
+    let write_err = |e: std::io::Error| {
+        CliError::InvalidArguments(format!(
+            "Failed to rewrite audit log '{}': {e}",
+            path.display()
+        ))
+    };
+
+    let dir = path
+        .parent()
+        .filter(|p| !p.as_os_str().is_empty())
+        .unwrap_or_else(|| std::path::Path::new("."));
+    let mut temp = NamedTempFile::new_in(dir).map_err(write_err)?;
+    for line in lines {
+        writeln!(temp, "{line}").map_err(write_err)?;
+    }
+    temp.persist(path).map_err(|e| write_err(e.error))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audit::{AuditEventKind, AuditSink, PromptAuditor};
+    use chrono::Duration;
+    use std::sync::Arc;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_compaction_expires_events_and_collects_orphan_blobs() {
+        let dir = TempDir::new().unwrap();
+        let log = JsonlAuditLog::new(dir.path().join("audit.jsonl"));
+        let blobs = BlobStore::new(dir.path().join("audit.blobs"));
+        let auditor = PromptAuditor::new(Arc::new(log.clone()), blobs.clone());
+
+        // Old event whose payload is only referenced by itself
+        let old_ref = blobs.put(b"old document").unwrap();
+        let shared_ref = blobs.put(b"System").unwrap();
+        let mut old = AuditEvent::now(AuditEventKind::PromptSubmitted {
+            model: "m".to_string(),
+            system_prompt: shared_ref,
+            user_prompt: old_ref.clone(),
+        });
+        old.timestamp -= Duration::days(40);
+        log.record(&old).unwrap();
+        auditor
+            .record_prompt("m", "System", "new document")
+            .unwrap();
+        blobs.put(b"never referenced").unwrap();
+
+        let report =
+            compact_audit_log(&log, &blobs, Some(Utc::now() - Duration::days(30))).unwrap();
+
+        assert_eq!(report.events_kept, 1);
+        assert_eq!(report.events_expired, 1);
+        assert_eq!(report.lines_unparsed, 0);
+        assert!(!report.blob_gc_skipped);
+        assert_eq!(report.blobs_kept, 2);
+        assert_eq!(report.blobs_removed, 2);
+        assert!(!blobs.contains(&old_ref));
+        assert_eq!(
+            log.events_since(Utc::now() - Duration::days(365))
+                .unwrap()
+                .len(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_compaction_keeps_unknown_events_and_their_blobs() {
+        let dir = TempDir::new().unwrap();
+        let log = JsonlAuditLog::new(dir.path().join("audit.jsonl"));
+        let blobs = BlobStore::new(dir.path().join("audit.blobs"));
+
+        let payload_ref = blobs.put(b"payload of a future event").unwrap();
+        let mut old = AuditEvent::now(AuditEventKind::PromptSubmitted {
+            model: "m".to_string(),
+            system_prompt: blobs.put(b"System").unwrap(),
+            user_prompt: blobs.put(b"old document").unwrap(),
+        });
+        old.timestamp -= Duration::days(40);
+        log.record(&old).unwrap();
+        let unknown = format!(
+            r#"{{"timestamp":"{}","event":"prompt_redacted","payload":"{payload_ref}"}}"#,
+            Utc::now().to_rfc3339()
+        );
+        fs::OpenOptions::new()
+            .append(true)
+            .open(log.path())
+            .unwrap()
+            .write_all(format!("{unknown}\n").as_bytes())
+            .unwrap();
+
+        let report =
+            compact_audit_log(&log, &blobs, Some(Utc::now() - Duration::days(30))).unwrap();
+
+        assert_eq!(report.events_expired, 1);
+        assert_eq!(report.lines_unparsed, 1);
+        assert!(report.blob_gc_skipped);
+        assert_eq!(report.blobs_removed, 0);
+        assert!(blobs.contains(&payload_ref));
+        assert_eq!(
+            fs::read_to_string(log.path()).unwrap(),
+            format!("{unknown}\n")
+        );
+    }
+
+    #[test]
+    fn test_compaction_of_missing_log_removes_all_blobs() {
+        let dir = TempDir::new().unwrap();
+        let log = JsonlAuditLog::new(dir.path().join("audit.jsonl"));
+        let blobs = BlobStore::new(dir.path().join("blobs"));
+        blobs.put(b"orphan").unwrap();
+
+        let report = compact_audit_log(&log, &blobs, None).unwrap();
+        assert_eq!(report.events_kept, 0);
+        assert_eq!(report.blobs_removed, 1);
+        assert_eq!(report.bytes_reclaimed, 6);
+    }
+}
@@ -0,0 +1,459 @@
+//! Audit trail for security-relevant events
+//!
+//! Events are appended to an [`AuditSink`]: a JSONL file for deployments, or
+//! an in-memory log for embedding and tests. Sinks can be read back so that
+//! time-window policies (e.g. override rate limits) survive process restarts.
+//!
+//! Prompt payloads are kept out of the log itself: [`PromptAuditor`] stores
+//! them in a content-addressed [`BlobStore`] and events reference them by hash.
+
+mod blobs;
+mod compaction;
+
+pub use blobs::{blob_ref, BlobStore, BLOB_REF_PREFIX};
+pub use compaction::{compact_audit_log, CompactionReport};
+
+use crate::{error::CliError, output::CliOutput, storage::Storage};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::{
+    fs::{self, OpenOptions},
+    io::Write,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+
+/// A single audit record
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuditEvent {
+    pub timestamp: DateTime<Utc>,
+    #[serde(flatten)]
+    pub kind: AuditEventKind,
+}
+
+impl AuditEvent {
+    pub fn now(kind: AuditEventKind) -> Self {
+        Self {
+            timestamp: Utc::now(),
+            kind,
+        }
+    }
+}
+
+/// Audit event payloads
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum AuditEventKind {
+    /// A guardrail block was downgraded to warnings via an override token
+    OverrideApplied {
+        stage: String,
+        rules: Vec<String>,
+        token_fingerprint: String,
+    },
+    /// An override was requested but refused (bad token, rule not allowed, rate limit)
+    OverrideDenied {
+        stage: String,
+        rules: Vec<String>,
+        reason: String,
+    },
+    /// Prompts sent for evaluation (payloads are blob references)
+    PromptSubmitted {
+        model: String,
+        system_prompt: String,
+        user_prompt: String,
+    },
+    /// Full metadata of a finished evaluation (prompt texts are blob references)
+    EvaluationCompleted {
+        status: String,
+        metadata: serde_json::Value,
+    },
+}
+
+impl AuditEventKind {
+    /// Event name as serialized in the `event` field
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::OverrideApplied { .. } => "override_applied",
+            Self::OverrideDenied { .. } => "override_denied",
+            Self::PromptSubmitted { .. } => "prompt_submitted",
+            Self::EvaluationCompleted { .. } => "evaluation_completed",
+        }
+    }
+
+    /// Blob references held by this event
+    pub fn blob_refs(&self) -> Vec<&str> {
+        match self {
+            Self::PromptSubmitted {
+                system_prompt,
+                user_prompt,
+                ..
+            } => vec![system_prompt, user_prompt],
+            Self::EvaluationCompleted { metadata, .. } => PROMPT_TEXT_FIELDS
+                .iter()
+                .filter_map(|field| metadata.get(field)?.as_str())
+                .filter(|value| value.starts_with(BLOB_REF_PREFIX))
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+}
+
+/// Metadata fields holding prompt texts, stored as blobs in the log
+const PROMPT_TEXT_FIELDS: [&str; 2] = ["system_prompt_text", "user_prompt_text"];
+
+/// Destination for audit events
+pub trait AuditSink: Send + Sync {
+    /// Persist an event. Callers treat failures as fatal (fail closed).
+    fn record(&self, event: &AuditEvent) -> Result<(), CliError>;
+
+    /// Events recorded at or after `since`, oldest first
+    fn events_since(&self, since: DateTime<Utc>) -> Result<Vec<AuditEvent>, CliError>;
+}
+
+/// Append-only JSON Lines audit log
+#[derive(Debug, Clone)]
+pub struct JsonlAuditLog {
+    path: PathBuf,
+}
+
+impl JsonlAuditLog {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl AuditSink for JsonlAuditLog {
+    fn record(&self, event: &AuditEvent) -> Result<(), CliError> {
+        if let Some(parent) = self.path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            fs::create_dir_all(parent).map_err(|e| {
+                CliError::InvalidArguments(format!(
+                    "Failed to create audit log directory '{}': {e}",
+                    parent.display()
+                ))
+            })?;
+        }
+
+        let mut line = serde_json::to_string(event).map_err(|e| {
+            CliError::InvalidArguments(format!("Failed to serialize audit event: {e}"))
+        })?;
+        line.push('\n');
+
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .map_err(|e| {
+                CliError::InvalidArguments(format!(
+                    "Failed to open audit log '{}': {e}",
+                    self.path.display()
+                ))
+            })?;
+        file.write_all(line.as_bytes()).map_err(|e| {
+            CliError::InvalidArguments(format!(
+                "Failed to write audit log '{}': {e}",
+                self.path.display()
+            ))
+        })
+    }
+
+    fn events_since(&self, since: DateTime<Utc>) -> Result<Vec<AuditEvent>, CliError> {
+        let contents = match fs::read_to_string(&self.path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => {
+                return Err(CliError::FileNotFound(format!(
+                    "Failed to read audit log '{}': {e}",
+                    self.path.display()
+                )))
+            }
+        };
+
+        let mut events = Vec::new();
+        for (index, line) in contents.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            match serde_json::from_str::<AuditEvent>(line) {
+                Ok(event) if event.timestamp >= since => events.push(event),
+                Ok(_) => {}
+                Err(e) => log::warn!(
+                    "Skipping unreadable audit log line {} in '{}': {e}",
+                    index + 1,
+                    self.path.display()
+                ),
+            }
+        }
+        Ok(events)
+    }
+}
+
+/// In-memory audit log (for library embedding and tests)
+#[derive(Debug, Default)]
+pub struct MemoryAuditLog {
+    events: Mutex<Vec<AuditEvent>>,
+}
+
+impl MemoryAuditLog {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// All recorded events, oldest first
+    pub fn events(&self) -> Vec<AuditEvent> {
+        self.events.lock().expect("audit lock poisoned").clone()
+    }
+}
+
+impl AuditSink for MemoryAuditLog {
+    fn record(&self, event: &AuditEvent) -> Result<(), CliError> {
+        self.events
+            .lock()
+            .expect("audit lock poisoned")
+            .push(event.clone());
+        Ok(())
+    }
+
+    fn events_since(&self, since: DateTime<Utc>) -> Result<Vec<AuditEvent>, CliError> {
+        Ok(self
+            .events
+            .lock()
+            .expect("audit lock poisoned")
+            .iter()
+            .filter(|e| e.timestamp >= since)
+            .cloned()
+            .collect())
+    }
+}
+
+/// Audit log over a [`Storage`] backend (one key per event)
+///
+/// Keys are `events/<timestamp micros>-<content hash prefix>`, so listing
+/// returns events in chronological order.
+pub struct StorageAuditLog {
+    storage: Arc<dyn Storage>,
+}
+
+impl StorageAuditLog {
+    pub fn new(storage: Arc<dyn Storage>) -> Self {
+        Self { storage }
+    }
+}
+
+impl AuditSink for StorageAuditLog {
+    fn record(&self, event: &AuditEvent) -> Result<(), CliError> {
+        let json = serde_json::to_vec(event).map_err(|e| {
+            CliError::InvalidArguments(format!("Failed to serialize audit event: {e}"))
+        })?;
+        let micros = event.timestamp.timestamp_micros().max(0);
+        let digest = &blob_ref(&json)[BLOB_REF_PREFIX.len()..][..12];
+        self.storage
+            .put(&format!("events/{micros:020}-{digest}"), &json, None)
+    }
+
+    fn events_since(&self, since: DateTime<Utc>) -> Result<Vec<AuditEvent>, CliError> {
+        let mut events = Vec::new();
+        for key in self.storage.list("events/")? {
+            let Some(json) = self.storage.get(&key)? else {
+                continue;
+            };
+            match serde_json::from_slice::<AuditEvent>(&json) {
+                Ok(event) if event.timestamp >= since => events.push(event),
+                Ok(_) => {}
+                Err(e) => log::warn!("Skipping unreadable audit entry '{key}': {e}"),
+            }
+        }
+        Ok(events)
+    }
+}
+
+/// Records submitted prompts with deduplicated payload storage
+pub struct PromptAuditor {
+    sink: Arc<dyn AuditSink>,
+    blobs: BlobStore,
+}
+
+impl std::fmt::Debug for PromptAuditor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PromptAuditor")
+            .field("blobs", &self.blobs)
+            .finish()
+    }
+}
+
+impl PromptAuditor {
+    pub fn new(sink: Arc<dyn AuditSink>, blobs: BlobStore) -> Self {
+        Self { sink, blobs }
+    }
+
+    /// JSONL log at `log`, blobs in `blob_dir` (default: `<log>.blobs/`)
+    pub fn from_config(config: &PromptAuditConfig) -> Self {
+        let blob_dir = config
+            .blob_dir
+            .clone()
+            .unwrap_or_else(|| default_blob_dir(&config.log));
+        Self::new(
+            Arc::new(JsonlAuditLog::new(&config.log)),
+            BlobStore::new(blob_dir),
+        )
+    }
+
+    pub fn blobs(&self) -> &BlobStore {
+        &self.blobs
+    }
+
+    /// Store both prompts as blobs, then append a `prompt_submitted` event
+    pub fn record_prompt(
+        &self,
+        model: &str,
+        system_prompt: &str,
+        user_prompt: &str,
+    ) -> Result<(), CliError> {
+        let system_prompt = self.blobs.put(system_prompt.as_bytes())?;
+        let user_prompt = self.blobs.put(user_prompt.as_bytes())?;
+        self.sink
+            .record(&AuditEvent::now(AuditEventKind::PromptSubmitted {
+                model: model.to_string(),
+                system_prompt,
+                user_prompt,
+            }))
+    }
+
+    /// Append an `evaluation_completed` event with the unfiltered metadata
+    /// of `output`, prompt texts stored as blobs
+    pub fn record_completion(&self, output: &CliOutput) -> Result<(), CliError> {
+        let mut metadata = serde_json::to_value(&output.metadata).map_err(|e| {
+            CliError::InvalidArguments(format!("Failed to serialize audit event: {e}"))
+        })?;
+        for field in PROMPT_TEXT_FIELDS {
+            if let Some(text) = metadata.get(field).and_then(|v| v.as_str()) {
+                let reference = self.blobs.put(text.as_bytes())?;
+                metadata[field] = serde_json::Value::String(reference);
+            }
+        }
+        self.sink
+            .record(&AuditEvent::now(AuditEventKind::EvaluationCompleted {
+                status: output.status.clone(),
+                metadata,
+            }))
+    }
+}
+
+/// Prompt audit settings as written in config files (`[prompt_audit]`)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PromptAuditConfig {
+    /// JSONL audit log path
+    pub log: PathBuf,
+
+    /// Blob directory (default: log path with a `.blobs` extension)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub blob_dir: Option<PathBuf>,
+}
+
+/// Default blob directory for a log: `audit.jsonl` -> `audit.blobs`
+pub fn default_blob_dir(log: &Path) -> PathBuf {
+    log.with_extension("blobs")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+    use tempfile::TempDir;
+
+    fn denied(reason: &str) -> AuditEventKind {
+        AuditEventKind::OverrideDenied {
+            stage: "input".to_string(),
+            rules: vec!["PII_EMAIL".to_string()],
+            reason: reason.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_event_serializes_with_event_tag() {
+        let event = AuditEvent::now(denied("invalid token"));
+        let json = serde_json::to_value(&event).unwrap();
+
+        assert_eq!(json["event"], "override_denied");
+        assert_eq!(json["stage"], "input");
+        assert_eq!(json["reason"], "invalid token");
+        assert!(json["timestamp"].is_string());
+        assert_eq!(event.kind.name(), "override_denied");
+    }
+
+    #[test]
+    fn test_jsonl_log_round_trip() {
+        let dir = TempDir::new().unwrap();
+        let log = JsonlAuditLog::new(dir.path().join("nested/audit.jsonl"));
+
+        // Missing file reads as empty
+        assert!(log
+            .events_since(Utc::now() - Duration::hours(1))
+            .unwrap()
+            .is_empty());
+
+        let mut old = AuditEvent::now(denied("old"));
+        old.timestamp -= Duration::hours(2);
+        log.record(&old).unwrap();
+        log.record(&AuditEvent::now(denied("recent"))).unwrap();
+
+        let recent = log.events_since(Utc::now() - Duration::hours(1)).unwrap();
+        assert_eq!(recent.len(), 1);
+        assert_eq!(recent[0].kind, denied("recent"));
+    }
+
+    #[test]
+    fn test_prompt_auditor_deduplicates_payloads() {
+        let dir = TempDir::new().unwrap();
+        let log = Arc::new(MemoryAuditLog::new());
+        let auditor = PromptAuditor::new(log.clone(), BlobStore::new(dir.path()));
+
+        let pdf_text = "page ".repeat(10_000);
+        for _ in 0..3 {
+            auditor.record_prompt("m", "System", &pdf_text).unwrap();
+        }
+
+        let events = log.events();
+        assert_eq!(events.len(), 3);
+        assert_eq!(events[0].kind.name(), "prompt_submitted");
+        assert_eq!(events[0].kind.blob_refs()[1], blob_ref(pdf_text.as_bytes()));
+        // One blob per distinct payload, regardless of submissions
+        assert_eq!(auditor.blobs().list().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_storage_log_round_trip() {
+        let log = StorageAuditLog::new(Arc::new(crate::storage::MemoryStorage::new()));
+        let mut old = AuditEvent::now(denied("old"));
+        old.timestamp -= Duration::hours(2);
+        log.record(&old).unwrap();
+        log.record(&AuditEvent::now(denied("recent"))).unwrap();
+
+        let all = log.events_since(Utc::now() - Duration::hours(3)).unwrap();
+        assert_eq!(all.len(), 2);
+        assert_eq!(all[0].kind, denied("old"));
+
+        let recent = log.events_since(Utc::now() - Duration::hours(1)).unwrap();
+        assert_eq!(recent, vec![all[1].clone()]);
+    }
+
+    #[test]
+    fn test_memory_log_filters_by_time() {
+        let log = MemoryAuditLog::new();
+        let mut old = AuditEvent::now(denied("old"));
+        old.timestamp -= Duration::hours(2);
+        log.record(&old).unwrap();
+        log.record(&AuditEvent::now(denied("recent"))).unwrap();
+
+        assert_eq!(log.events().len(), 2);
+        assert_eq!(
+            log.events_since(Utc::now() - Duration::hours(1))
+                .unwrap()
+                .len(),
+            1
+        );
+    }
+}
@@ -0,0 +1,637 @@
+//! Batch evaluation
+//!
+//! Runs many evaluations with bounded parallelism and summarizes the
+//! outcomes. [`BatchItem::load`] reads prompts from a JSONL or CSV file for
+//! the CLI `--batch-file` mode:
+//!
+//! ```text
+//! {"id": "q1", "user_prompt": "Summarize the refund policy"}
+//! {"id": "q2", "user_prompt": "Translate to French", "system_prompt": "You are a translator"}
+//! {"id": "q3", "user_prompt": "Summarize the refund policy", "tags": ["variant=B"]}
+//! ```
+//!
+//! ```text
+//! id,user_prompt,system_prompt,tags
+//! q1,Summarize the refund policy,,
+//! q2,Translate to French,You are a translator,lang=fr;variant=A
+//! ```
+//!
+//! Item tags are added to the configured tags, and [`BatchSummary::by_tag`]
+//! groups the outcomes per tag for A/B comparisons.
+//!
+//! When a provider answers HTTP 429, its endpoint (API URL) is paused for the
+//! `Retry-After` window: items for that endpoint wait without holding a
+//! concurrency slot, items for other endpoints keep running, and the
+//! rate-limited item is retried once the window ends. [`BatchSummary::throttling`]
+//! records how often and how long endpoints were throttled.
+
+use crate::{
+    evaluate,
+    events::{Event, EventWriter},
+    CliError, CliOutput, EvaluationConfig, EvaluationContext,
+};
+use futures::stream::{self, StreamExt};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, HashMap},
+    path::Path,
+    sync::Mutex,
+    time::Duration,
+};
+use tokio::{sync::Semaphore, time::Instant};
+
+/// Retries of one item after rate-limited (HTTP 429) responses
+pub const MAX_RATE_LIMIT_RETRIES: u32 = 5;
+
+/// Pause of an endpoint whose 429 response has no `Retry-After` header
+pub const DEFAULT_RETRY_AFTER: Duration = Duration::from_secs(1);
+
+/// Longest honored `Retry-After` window
+pub const MAX_RETRY_AFTER: Duration = Duration::from_secs(300);
+
+/// One prompt from a batch file
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct BatchItem {
+    /// Caller-supplied identifier echoed in the result line
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    pub user_prompt: String,
+    /// Replaces the configured system prompt for this item
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub system_prompt: Option<String>,
+    /// Added to the configured tags (a `;`-separated cell in CSV files)
+    #[serde(
+        default,
+        deserialize_with = "deserialize_tags",
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub tags: Vec<String>,
+}
+
+/// A list of tags, or one string with `;`-separated tags
+fn deserialize_tags<'de, D>(deserializer: D) -> Result<Vec<String>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Tags {
+        List(Vec<String>),
+        Joined(String),
+    }
+
+    let tags = match Tags::deserialize(deserializer)? {
+        Tags::List(tags) => tags,
+        Tags::Joined(joined) => joined.split(';').map(str::to_string).collect(),
+    };
+    Ok(tags
+        .into_iter()
+        .map(|tag| tag.trim().to_string())
+        .filter(|tag| !tag.is_empty())
+        .collect())
+}
+
+impl BatchItem {
+    /// Read items from a `.jsonl`/`.ndjson` file (one object per line) or a
+    /// `.csv` file with a header row
+    pub fn load(path: &Path) -> Result<Vec<BatchItem>, CliError> {
+        let content = std::fs::read_to_string(path).map_err(|e| {
+            CliError::InvalidArguments(format!(
+                "Failed to read batch file '{}': {e}",
+                path.display()
+            ))
+        })?;
+        let items = match path.extension().and_then(|s| s.to_str()) {
+            Some("jsonl" | "ndjson") => Self::parse_jsonl(&content),
+            Some("csv") => Self::parse_csv(&content),
+            _ => Err("Batch file must have .jsonl, .ndjson or .csv extension".to_string()),
+        }
+        .map_err(|e| CliError::InvalidArguments(format!("{}: {e}", path.display())))?;
+
+        if items.is_empty() {
+            return Err(CliError::InvalidArguments(format!(
+                "Batch file '{}' contains no items",
+                path.display()
+            )));
+        }
+        Ok(items)
+    }
+
+    /// Blank lines are skipped
+    fn parse_jsonl(content: &str) -> Result<Vec<BatchItem>, String> {
+        content
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(index, line)| {
+                serde_json::from_str(line).map_err(|e| format!("line {}: {e}", index + 1))
+            })
+            .collect()
+    }
+
+    /// Empty `id`/`system_prompt` cells count as unset
+    fn parse_csv(content: &str) -> Result<Vec<BatchItem>, String> {
+        let mut reader = csv::Reader::from_reader(content.as_bytes());
+        reader
+            .deserialize::<BatchItem>()
+            .map(|row| {
+                let mut item = row.map_err(|e| e.to_string())?;
+                item.id = item.id.filter(|id| !id.is_empty());
+                item.system_prompt = item.system_prompt.filter(|p| !p.is_empty());
+                Ok(item)
+            })
+            .collect()
+    }
+
+    /// `base` with this item's prompts and tags
+    pub fn apply(&self, base: &EvaluationConfig) -> EvaluationConfig {
+        let mut config = base.clone();
+        config.user_prompt = self.user_prompt.clone();
+        if let Some(system_prompt) = &self.system_prompt {
+            config.system_prompt = system_prompt.clone();
+            config.system_prompt_file = None;
+            config.system_prompt_url = None;
+        }
+        config.user_prompt_file = None;
+        config.pdf_input = None;
+        config.url_input = None;
+        for tag in &self.tags {
+            if !config.tags.contains(tag) {
+                config.tags.push(tag.clone());
+            }
+        }
+        config
+    }
+}
+
+/// Aggregate counts for a batch run
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct BatchSummary {
+    pub total: usize,
+    /// Items with `status: "success"`
+    pub succeeded: usize,
+    /// Items stopped by the pipeline (guardrails, context limit, refusal, ...)
+    pub blocked: usize,
+    /// Items that failed with a hard error (HTTP, auth, ...)
+    pub errors: usize,
+    /// Sum of estimated tokens across items
+    pub tokens_estimated: u64,
+    /// Sum of estimated model-backed guardrail tokens across items
+    #[serde(skip_serializing_if = "is_zero")]
+    pub guard_tokens_estimated: u64,
+    /// Sum of per-item latency
+    pub total_latency_ms: u64,
+    /// Wall time of the whole batch
+    pub elapsed_ms: u64,
+    /// Outcomes grouped by each tag in the items' metadata
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub by_tag: BTreeMap<String, TagSummary>,
+    /// Rate limiting met during the batch
+    #[serde(skip_serializing_if = "ThrottleStats::is_empty")]
+    pub throttling: ThrottleStats,
+}
+
+fn is_zero(n: &u64) -> bool {
+    *n == 0
+}
+
+/// Rate-limited (HTTP 429) responses and the resulting endpoint pauses
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ThrottleStats {
+    /// Rate-limited responses received
+    pub rate_limited: usize,
+    /// Items that failed still rate limited after [`MAX_RATE_LIMIT_RETRIES`]
+    pub exhausted: usize,
+    /// Time endpoints spent paused (overlapping windows counted once)
+    pub paused_ms: u64,
+    /// Rate-limited responses per endpoint (API URL)
+    pub by_endpoint: BTreeMap<String, usize>,
+}
+
+impl ThrottleStats {
+    pub fn is_empty(&self) -> bool {
+        self.rate_limited == 0
+    }
+
+    fn count(&mut self, endpoint: &str) {
+        self.rate_limited += 1;
+        *self.by_endpoint.entry(endpoint.to_string()).or_default() += 1;
+    }
+}
+
+/// Outcome counts of the batch items carrying one tag
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct TagSummary {
+    pub total: usize,
+    pub succeeded: usize,
+    pub blocked: usize,
+    pub errors: usize,
+    pub tokens_estimated: u64,
+    pub total_latency_ms: u64,
+}
+
+impl TagSummary {
+    /// Share of the tagged items that succeeded (0.0 when empty)
+    pub fn success_rate(&self) -> f64 {
+        if self.total == 0 {
+            0.0
+        } else {
+            self.succeeded as f64 / self.total as f64
+        }
+    }
+}
+
+/// Per-item outputs (in input order) and their summary
+pub struct BatchOutput {
+    pub results: Vec<CliOutput>,
+    pub summary: BatchSummary,
+}
+
+impl BatchOutput {
+    /// Outputs whose metadata carries `tag`
+    pub fn tagged<'a>(&'a self, tag: &'a str) -> impl Iterator<Item = &'a CliOutput> + 'a {
+        self.results
+            .iter()
+            .filter(move |output| output.metadata.tags.iter().any(|t| t == tag))
+    }
+
+    /// One CSV row of key metadata per item, for spreadsheet analysis
+    ///
+    /// `items` are the batch's items in input order (for their ids). List
+    /// cells (violation rules, warning codes, tags) are `;`-separated.
+    pub fn to_summary_csv(&self, items: &[BatchItem]) -> Result<String, CliError> {
+        let csv_error = |e: &dyn std::fmt::Display| {
+            CliError::InvalidArguments(format!("Failed to write summary CSV: {e}"))
+        };
+        let mut writer = csv::Writer::from_writer(Vec::new());
+        writer
+            .write_record([
+                "index",
+                "id",
+                "status",
+                "outcome",
+                "model",
+                "tokens_estimated",
+                "latency_ms",
+                "violation_rules",
+                "warnings",
+                "quality_score",
+                "tags",
+            ])
+            .map_err(|e| csv_error(&e))?;
+        for (index, output) in self.results.iter().enumerate() {
+            let metadata = &output.metadata;
+            let warnings: Vec<&str> = metadata.warnings.iter().map(|w| w.code.as_str()).collect();
+            writer
+                .write_record([
+                    index.to_string(),
+                    items
+                        .get(index)
+                        .and_then(|item| item.id.clone())
+                        .unwrap_or_default(),
+                    output.status.clone(),
+                    output
+                        .error
+                        .as_ref()
+                        .map(|e| e.code.to_string())
+                        .unwrap_or_default(),
+                    metadata.model.clone(),
+                    metadata.tokens_estimated.to_string(),
+                    metadata.latency_ms.to_string(),
+                    metadata.violation_rules.join(";"),
+                    warnings.join(";"),
+                    metadata
+                        .quality_score
+                        .map(|s| s.to_string())
+                        .unwrap_or_default(),
+                    metadata.tags.join(";"),
+                ])
+                .map_err(|e| csv_error(&e))?;
+        }
+        let bytes = writer.into_inner().map_err(|e| csv_error(&e))?;
+        String::from_utf8(bytes).map_err(|e| csv_error(&e))
+    }
+
+    /// JUnit XML report with one test case per item, for CI servers (see
+    /// [`crate::junit`])
+    pub fn to_junit_xml(&self, items: &[BatchItem], suite_name: &str) -> String {
+        crate::junit::batch_report(self, items, suite_name)
+    }
+}
+
+/// Evaluate `configs` with at most `concurrency` evaluations in flight
+///
+/// Results keep the input order. Hard errors become error outputs (with the
+/// item's configuration in the metadata), so one failing item never aborts
+/// the batch. A `concurrency` of 0 is treated as 1.
+pub async fn evaluate_batch(configs: Vec<EvaluationConfig>, concurrency: usize) -> BatchOutput {
+    let items = configs.into_iter().map(|config| (None, config)).collect();
+    run_batch(items, concurrency, None).await
+}
+
+/// [`evaluate_batch`] of `items` applied to `base`, reporting progress to `events`
+///
+/// Emits `item_started` and `item_completed` (preceded by `guardrail_blocked`
+/// when guardrails stopped the item) as items run, then `batch_summary`.
+pub async fn evaluate_batch_with_events(
+    base: &EvaluationConfig,
+    items: &[BatchItem],
+    concurrency: usize,
+    events: &EventWriter,
+) -> BatchOutput {
+    let items = items
+        .iter()
+        .map(|item| (item.id.clone(), item.apply(base)))
+        .collect();
+    let output = run_batch(items, concurrency, Some(events)).await;
+    events.emit(&Event::BatchSummary {
+        summary: output.summary.clone(),
+    });
+    output
+}
+
+/// Pause windows of rate-limited endpoints
+#[derive(Default)]
+struct Throttle {
+    state: Mutex<ThrottleState>,
+}
+
+#[derive(Default)]
+struct ThrottleState {
+    paused_until: HashMap<String, Instant>,
+    stats: ThrottleStats,
+}
+
+impl Throttle {
+    fn lock(&self) -> std::sync::MutexGuard<'_, ThrottleState> {
+        self.state.lock().expect("throttle lock poisoned")
+    }
+
+    /// End of the current pause of `endpoint` (None when not paused)
+    fn paused_until(&self, endpoint: &str) -> Option<Instant> {
+        let until = *self.lock().paused_until.get(endpoint)?;
+        (until > Instant::now()).then_some(until)
+    }
+
+    /// Wait out any pause of `endpoint`, then take a concurrency slot
+    ///
+    /// Waiting happens before taking the slot, so a paused endpoint never
+    /// blocks items for other endpoints.
+    async fn acquire<'a>(
+        &self,
+        endpoint: &str,
+        slots: &'a Semaphore,
+    ) -> tokio::sync::SemaphorePermit<'a> {
+        loop {
+            while let Some(until) = self.paused_until(endpoint) {
+                tokio::time::sleep_until(until).await;
+            }
+            let slot = slots.acquire().await.expect("batch semaphore closed");
+            // Paused again while waiting for the slot
+            if self.paused_until(endpoint).is_none() {
+                return slot;
+            }
+        }
+    }
+
+    /// Record a rate-limited response and pause `endpoint` for `delay`
+    fn pause(&self, endpoint: &str, delay: Duration) {
+        let now = Instant::now();
+        let until = now + delay;
+        let mut state = self.lock();
+        let current = state
+            .paused_until
+            .get(endpoint)
+            .copied()
+            .unwrap_or(now)
+            .max(now);
+        if until > current {
+            state.stats.paused_ms += (until - current).as_millis() as u64;
+            state.paused_until.insert(endpoint.to_string(), until);
+        }
+        state.stats.count(endpoint);
+    }
+
+    /// Record a rate-limited response of an item out of retries
+    fn exhausted(&self, endpoint: &str) {
+        let mut state = self.lock();
+        state.stats.count(endpoint);
+        state.stats.exhausted += 1;
+    }
+
+    fn stats(&self) -> ThrottleStats {
+        self.lock().stats.clone()
+    }
+}
+
+/// Evaluate `config` in a concurrency slot, retrying it after its endpoint's
+/// pause when the provider rate limits it
+///
+/// `on_start` runs once, when the first attempt gets its slot.
+async fn evaluate_throttled(
+    config: EvaluationConfig,
+    slots: &Semaphore,
+    throttle: &Throttle,
+    on_start: impl FnOnce(),
+) -> Result<CliOutput, CliError> {
+    let endpoint = config.api_url.clone();
+    let mut on_start = Some(on_start);
+    let mut retries = 0;
+    loop {
+        let _slot = throttle.acquire(&endpoint, slots).await;
+        if let Some(on_start) = on_start.take() {
+            on_start();
+        }
+        match evaluate(config.clone()).await {
+            Err(CliError::RateLimited { retry_after, .. }) if retries < MAX_RATE_LIMIT_RETRIES => {
+                retries += 1;
+                let delay = retry_after
+                    .unwrap_or(DEFAULT_RETRY_AFTER)
+                    .min(MAX_RETRY_AFTER);
+                log::warn!(
+                    "Rate limited by {endpoint}; pausing it for {:.1}s (retry \
+                    {retries}/{MAX_RATE_LIMIT_RETRIES})",
+                    delay.as_secs_f64()
+                );
+                throttle.pause(&endpoint, delay);
+            }
+            result => {
+                if let Err(CliError::RateLimited { .. }) = &result {
+                    throttle.exhausted(&endpoint);
+                }
+                return result;
+            }
+        }
+    }
+}
+
+async fn run_batch(
+    items: Vec<(Option<String>, EvaluationConfig)>,
+    concurrency: usize,
+    events: Option<&EventWriter>,
+) -> BatchOutput {
+    let started = Instant::now();
+    let total = items.len();
+    let slots = Semaphore::new(concurrency.max(1));
+    let throttle = Throttle::default();
+    let (slots, throttle) = (&slots, &throttle);
+
+    // Every item is polled so items of a paused endpoint can wait without a
+    // slot; the semaphore bounds the evaluations in flight
+    let outcomes: Vec<(CliOutput, bool)> = stream::iter(items.into_iter().enumerate())
+        .map(|(index, (id, config))| async move {
+            let fallback = EvaluationContext::new(config.clone());
+            let on_start = || {
+                if let Some(events) = events {
+                    events.emit(&Event::ItemStarted {
+                        index,
+                        id: id.clone(),
+                    });
+                }
+            };
+            let outcome = match evaluate_throttled(config, slots, throttle, on_start).await {
+                Ok(output) => (output, false),
+                Err(e) => {
+                    log::warn!("Batch item failed: {e}");
+                    (
+                        CliOutput::error(&e, e.to_string(), fallback.metadata()),
+                        true,
+                    )
+                }
+            };
+            if let Some(events) = events {
+                events.emit_all(&Event::completed(index, id.as_deref(), &outcome.0));
+            }
+            outcome
+        })
+        .buffered(total.max(1))
+        .collect()
+        .await;
+
+    let mut summary = BatchSummary {
+        total,
+        throttling: throttle.stats(),
+        ..Default::default()
+    };
+    for (output, hard_error) in &outcomes {
+        let tokens = output.metadata.tokens_estimated as u64;
+        let latency_ms = output.metadata.latency_ms;
+        let succeeded = !*hard_error && output.status == "success";
+        if *hard_error {
+            summary.errors += 1;
+        } else if succeeded {
+            summary.succeeded += 1;
+        } else {
+            summary.blocked += 1;
+        }
+        summary.tokens_estimated += tokens;
+        summary.guard_tokens_estimated += output
+            .metadata
+            .guard_tokens
+            .iter()
+            .map(|usage| usage.tokens_estimated as u64)
+            .sum::<u64>();
+        summary.total_latency_ms += latency_ms;
+
+        for tag in &output.metadata.tags {
+            let group = summary.by_tag.entry(tag.clone()).or_default();
+            group.total += 1;
+            if *hard_error {
+                group.errors += 1;
+            } else if succeeded {
+                group.succeeded += 1;
+            } else {
+                group.blocked += 1;
+            }
+            group.tokens_estimated += tokens;
+            group.total_latency_ms += latency_ms;
+        }
+    }
+    summary.elapsed_ms = started.elapsed().as_millis() as u64;
+
+    BatchOutput {
+        results: outcomes.into_iter().map(|(output, _)| output).collect(),
+        summary,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_jsonl() {
+        let items = BatchItem::parse_jsonl(
+            "{\"id\": \"a\", \"user_prompt\": \"one\"}\n\n{\"user_prompt\": \"two\", \"system_prompt\": \"sys\"}\n",
+        )
+        .unwrap();
+        assert_eq!(
+            items,
+            vec![
+                BatchItem {
+                    id: Some("a".to_string()),
+                    user_prompt: "one".to_string(),
+                    system_prompt: None,
+                    tags: Vec::new(),
+                },
+                BatchItem {
+                    id: None,
+                    user_prompt: "two".to_string(),
+                    system_prompt: Some("sys".to_string()),
+                    tags: Vec::new(),
+                },
+            ]
+        );
+
+        let err = BatchItem::parse_jsonl("{\"user_prompt\": \"ok\"}\n{\"id\": \"x\"}").unwrap_err();
+        assert!(err.starts_with("line 2:"), "{err}");
+    }
+
+    #[test]
+    fn test_parse_csv() {
+        let items = BatchItem::parse_csv(
+            "id,user_prompt,system_prompt\nq1,\"Hello, world\",\nq2,Bonjour,Translate\n",
+        )
+        .unwrap();
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[0].user_prompt, "Hello, world");
+        assert_eq!(items[0].system_prompt, None);
+        assert_eq!(items[1].system_prompt.as_deref(), Some("Translate"));
+
+        // Only user_prompt is required
+        let items = BatchItem::parse_csv("user_prompt\nping\n").unwrap();
+        assert_eq!(items[0].id, None);
+        assert!(BatchItem::parse_csv("prompt\nping\n").is_err());
+    }
+
+    #[test]
+    fn test_throttle_counts_overlapping_pauses_once() {
+        let throttle = Throttle::default();
+        throttle.pause("a", Duration::from_secs(10));
+        // Within the first window: counted, but the pause isn't extended
+        throttle.pause("a", Duration::from_secs(4));
+        throttle.pause("b", Duration::from_secs(2));
+        throttle.exhausted("b");
+
+        let stats = throttle.stats();
+        assert_eq!(stats.rate_limited, 4);
+        assert_eq!(stats.exhausted, 1);
+        assert_eq!(stats.by_endpoint["a"], 2);
+        assert_eq!(stats.by_endpoint["b"], 2);
+        assert!((12_000..12_100).contains(&stats.paused_ms), "{stats:?}");
+        assert!(throttle.paused_until("a").is_some());
+        assert!(throttle.paused_until("c").is_none());
+    }
+
+    #[test]
+    fn test_parse_tags() {
+        let items =
+            BatchItem::parse_csv("user_prompt,tags\none,variant=A; lang=fr\ntwo,\n").unwrap();
+        assert_eq!(items[0].tags, vec!["variant=A", "lang=fr"]);
+        assert!(items[1].tags.is_empty());
+
+        let items =
+            BatchItem::parse_jsonl("{\"user_prompt\": \"x\", \"tags\": [\"variant=B\"]}").unwrap();
+        assert_eq!(items[0].tags, vec!["variant=B"]);
+    }
+}
@@ -0,0 +1,46 @@
+//! Build information for compatibility checks
+//!
+//! Orchestration tooling can run `fortified-llm-client --version --json` (or
+//! call [`build_info`]) to check the version, output schema and supported
+//! providers before invoking the binary.
+
+use crate::{guardrails::GuardrailProviderConfig, output::OUTPUT_SCHEMA_VERSION, ProviderType};
+use serde::Serialize;
+
+/// Version, build and capability information
+#[derive(Debug, Clone, Serialize)]
+pub struct BuildInfo {
+    pub name: &'static str,
+    pub version: &'static str,
+    /// Commit the binary was built from (None outside a git checkout)
+    pub git_sha: Option<&'static str>,
+    /// Enabled cargo features
+    pub features: Vec<&'static str>,
+    /// Values accepted by `--provider`
+    pub providers: Vec<&'static str>,
+    /// Guardrail `type` values accepted in configuration files
+    pub guardrail_types: Vec<&'static str>,
+    /// Version of the `CliOutput` JSON layout (see [`OUTPUT_SCHEMA_VERSION`])
+    pub output_schema_version: u32,
+}
+
+/// Information about this build
+pub fn build_info() -> BuildInfo {
+    let non_empty = |s: &'static str| (!s.is_empty()).then_some(s);
+    BuildInfo {
+        name: env!("CARGO_PKG_NAME"),
+        version: env!("CARGO_PKG_VERSION"),
+        git_sha: non_empty(env!("FORTIFIED_GIT_SHA")),
+        features: env!("FORTIFIED_FEATURES")
+            .split(',')
+            .filter(|f| !f.is_empty())
+            .collect(),
+        providers: ProviderType::NAMES.to_vec(),
+        guardrail_types: GuardrailProviderConfig::PROVIDER_TYPES
+            .iter()
+            .copied()
+            .chain(["composite"])
+            .collect(),
+        output_schema_version: OUTPUT_SCHEMA_VERSION,
+    }
+}
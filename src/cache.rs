@@ -0,0 +1,572 @@
+//! Response caching
+//!
+//! Skips the LLM call when the same request (endpoint, model, prompts and
+//! sampling parameters) was answered before. Guardrails, refusal detection
+//! and formatting still run on cached responses, so a cache hit never
+//! bypasses output validation.
+//!
+//! Only deterministic requests (temperature 0 or a fixed seed) are cached by
+//! default: replaying one sample of a stochastic request would silently
+//! collapse the distribution an evaluation is measuring.
+//!
+//! Each endpoint and model also has a build marker recording the served
+//! model version, `system_fingerprint` or Ollama digest of the last live
+//! call. When a live call reports a different build, entries stored before
+//! the change (or from another build) stop matching, so cached results never
+//! mix responses from different model builds.
+//!
+//! ```toml
+//! [cache]
+//! dir = "./cache"      # Omit for an in-memory LRU (useful in batch mode)
+//! max_entries = 1000   # In-memory capacity
+//! ttl_secs = 86400     # Disk entry lifetime (omit to keep forever)
+//! stochastic = false   # Also cache temperature > 0 requests without a seed
+//! invalidate_on_model_change = true
+//! ```
+//!
+//! Backends implement [`CacheBackend`]: [`MemoryCache`] is a bounded LRU and
+//! [`StorageCache`] keeps entries in any [`Storage`] (an [`FsStorage`]
+//! directory for `dir`).
+
+use crate::{
+    image::ImageInput,
+    models::ToolCall,
+    provider::{InvokeParams, ModelEcho, UpstreamInfo},
+    storage::{FsStorage, Storage},
+    CliError, Provider,
+};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::{
+    collections::{BTreeMap, HashMap},
+    path::PathBuf,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+const KEY_PREFIX: &str = "cache";
+
+/// Default in-memory capacity
+pub const DEFAULT_MAX_ENTRIES: usize = 1000;
+
+/// Cache settings as written in config files (`[cache]`)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CacheConfig {
+    /// Directory of the on-disk cache (None = in-memory LRU)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dir: Option<PathBuf>,
+    /// Capacity of the in-memory LRU
+    #[serde(default = "default_max_entries")]
+    pub max_entries: usize,
+    /// Lifetime of on-disk entries (None = never expire)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ttl_secs: Option<u64>,
+    /// Also cache stochastic requests (temperature > 0 without a seed)
+    #[serde(default)]
+    pub stochastic: bool,
+    /// Ignore entries stored before the model build last changed (default: true)
+    #[serde(default = "default_invalidate_on_model_change")]
+    pub invalidate_on_model_change: bool,
+}
+
+fn default_invalidate_on_model_change() -> bool {
+    true
+}
+
+fn default_max_entries() -> usize {
+    DEFAULT_MAX_ENTRIES
+}
+
+impl Default for CacheConfig {
+    fn default() -> Self {
+        Self {
+            dir: None,
+            max_entries: DEFAULT_MAX_ENTRIES,
+            ttl_secs: None,
+            stochastic: false,
+            invalidate_on_model_change: true,
+        }
+    }
+}
+
+/// LLM response as stored in the cache
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CachedResponse {
+    pub content: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub upstream: Option<UpstreamInfo>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model_echo: Option<ModelEcho>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tool_calls: Vec<ToolCall>,
+    /// When the entry was stored (Unix milliseconds, set by
+    /// [`ResponseCache::store`]); for build markers, when the build last changed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cached_at_ms: Option<u64>,
+}
+
+/// Where cached responses live
+pub trait CacheBackend: Send + Sync {
+    /// Cached response for `key` (None on a miss)
+    fn get(&self, key: &str) -> Result<Option<CachedResponse>, CliError>;
+
+    /// Store a response, replacing any existing one
+    fn put(&self, key: &str, response: &CachedResponse) -> Result<(), CliError>;
+}
+
+/// Bounded in-memory cache evicting the least recently used entry
+pub struct MemoryCache {
+    capacity: usize,
+    state: Mutex<LruState>,
+}
+
+#[derive(Default)]
+struct LruState {
+    /// Entry and its last-use tick
+    entries: HashMap<String, (CachedResponse, u64)>,
+    /// Last-use tick to key, oldest first
+    recency: BTreeMap<u64, String>,
+    tick: u64,
+}
+
+impl LruState {
+    fn touch(&mut self, key: &str) -> Option<&CachedResponse> {
+        self.tick += 1;
+        let (response, last_used) = self.entries.get_mut(key)?;
+        self.recency.remove(last_used);
+        *last_used = self.tick;
+        self.recency.insert(self.tick, key.to_string());
+        Some(response)
+    }
+}
+
+impl MemoryCache {
+    /// A capacity of 0 is treated as 1
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            state: Mutex::new(LruState::default()),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.state
+            .lock()
+            .expect("cache lock poisoned")
+            .entries
+            .len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl CacheBackend for MemoryCache {
+    fn get(&self, key: &str) -> Result<Option<CachedResponse>, CliError> {
+        let mut state = self.state.lock().expect("cache lock poisoned");
+        Ok(state.touch(key).cloned())
+    }
+
+    fn put(&self, key: &str, response: &CachedResponse) -> Result<(), CliError> {
+        let mut state = self.state.lock().expect("cache lock poisoned");
+        if let Some(existing) = state.entries.get_mut(key) {
+            existing.0 = response.clone();
+            state.touch(key);
+            return Ok(());
+        }
+        while state.entries.len() >= self.capacity {
+            let Some((_, oldest)) = state.recency.pop_first() else {
+                break;
+            };
+            state.entries.remove(&oldest);
+        }
+        state.tick += 1;
+        let tick = state.tick;
+        state
+            .entries
+            .insert(key.to_string(), (response.clone(), tick));
+        state.recency.insert(tick, key.to_string());
+        Ok(())
+    }
+}
+
+/// Cache entries kept in a [`Storage`] (JSON values under `cache/`)
+pub struct StorageCache {
+    storage: Arc<dyn Storage>,
+    ttl: Option<Duration>,
+}
+
+impl StorageCache {
+    pub fn new(storage: Arc<dyn Storage>, ttl: Option<Duration>) -> Self {
+        Self { storage, ttl }
+    }
+}
+
+impl CacheBackend for StorageCache {
+    fn get(&self, key: &str) -> Result<Option<CachedResponse>, CliError> {
+        let Some(bytes) = self.storage.get(&format!("{KEY_PREFIX}/{key}"))? else {
+            return Ok(None);
+        };
+        serde_json::from_slice(&bytes).map(Some).map_err(|e| {
+            CliError::InvalidResponse(format!("Corrupt response cache entry '{key}': {e}"))
+        })
+    }
+
+    fn put(&self, key: &str, response: &CachedResponse) -> Result<(), CliError> {
+        let bytes = serde_json::to_vec(response).map_err(|e| {
+            CliError::InvalidResponse(format!("Failed to serialize cache entry: {e}"))
+        })?;
+        self.storage
+            .put(&format!("{KEY_PREFIX}/{key}"), &bytes, self.ttl)
+    }
+}
+
+/// Response cache used by the `invoke` stage
+///
+/// Backend failures are logged and treated as misses, so a broken cache
+/// never fails an evaluation.
+pub struct ResponseCache {
+    backend: Arc<dyn CacheBackend>,
+    stochastic: bool,
+    invalidate_on_model_change: bool,
+}
+
+impl std::fmt::Debug for ResponseCache {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ResponseCache").finish_non_exhaustive()
+    }
+}
+
+impl ResponseCache {
+    /// Caches deterministic requests only (see [`ResponseCache::stochastic`])
+    pub fn new(backend: Arc<dyn CacheBackend>) -> Self {
+        Self {
+            backend,
+            stochastic: false,
+            invalidate_on_model_change: true,
+        }
+    }
+
+    /// Also cache stochastic requests (temperature > 0 without a seed)
+    pub fn stochastic(mut self, enabled: bool) -> Self {
+        self.stochastic = enabled;
+        self
+    }
+
+    /// Ignore entries stored before the model build last changed (default: on)
+    pub fn invalidate_on_model_change(mut self, enabled: bool) -> Self {
+        self.invalidate_on_model_change = enabled;
+        self
+    }
+
+    /// In-memory LRU holding at most `max_entries` responses
+    pub fn memory(max_entries: usize) -> Self {
+        Self::new(Arc::new(MemoryCache::new(max_entries)))
+    }
+
+    /// On-disk cache when `dir` is set, in-memory LRU otherwise
+    pub fn from_config(config: &CacheConfig) -> Self {
+        let cache = match &config.dir {
+            Some(dir) => Self::new(Arc::new(StorageCache::new(
+                Arc::new(FsStorage::new(dir)),
+                config.ttl_secs.map(Duration::from_secs),
+            ))),
+            None => Self::memory(config.max_entries),
+        };
+        cache
+            .stochastic(config.stochastic)
+            .invalidate_on_model_change(config.invalidate_on_model_change)
+    }
+
+    /// Whether a request always samples the same response: temperature 0 or
+    /// a fixed seed
+    pub fn is_deterministic(params: &InvokeParams) -> bool {
+        params.temperature == 0.0 || params.seed.is_some()
+    }
+
+    /// Whether this cache serves and stores `params`
+    pub fn accepts(&self, params: &InvokeParams) -> bool {
+        self.stochastic || Self::is_deterministic(params)
+    }
+
+    /// Key of a request: SHA-256 of everything that shapes the response
+    ///
+    /// API keys and timeouts are not part of the key.
+    pub fn key(api_url: &str, provider: Option<Provider>, params: &InvokeParams) -> String {
+        let mut identity = serde_json::json!({
+            "api_url": api_url,
+            "provider": provider.map(|p| format!("{p:?}")),
+            "model": params.model,
+            "system_prompt": params.system_prompt,
+            "user_prompt": params.user_prompt,
+            "temperature": params.temperature,
+            "top_p": params.top_p,
+            "max_tokens": params.max_tokens,
+            "seed": params.seed,
+            "response_format": params.response_format,
+        });
+        // Only present with tools, so keys of plain requests stay unchanged
+        if !params.tools.is_empty() {
+            identity["tools"] = serde_json::json!(params.tools);
+        }
+        if !params.images.is_empty() {
+            let digests: Vec<String> = params.images.iter().map(ImageInput::digest).collect();
+            identity["images"] = serde_json::json!(digests);
+        }
+        let digest = Sha256::digest(identity.to_string().as_bytes());
+        digest.iter().map(|b| format!("{b:02x}")).collect()
+    }
+
+    /// Key of the build marker of an endpoint and model
+    pub fn model_key(api_url: &str, provider: Option<Provider>, model: &str) -> String {
+        let identity = serde_json::json!({
+            "model_build": {
+                "api_url": api_url,
+                "provider": provider.map(|p| format!("{p:?}")),
+                "model": model,
+            }
+        });
+        let digest = Sha256::digest(identity.to_string().as_bytes());
+        digest.iter().map(|b| format!("{b:02x}")).collect()
+    }
+
+    /// Cached response for `key`, unless it predates the last build change
+    /// recorded under `model_key` or comes from another build
+    pub fn lookup_current(&self, key: &str, model_key: &str) -> Option<CachedResponse> {
+        let cached = self.lookup(key)?;
+        if !self.invalidate_on_model_change {
+            return Some(cached);
+        }
+        let Some(marker) = self.lookup(model_key) else {
+            return Some(cached);
+        };
+        let build =
+            |entry: &CachedResponse| entry.model_echo.as_ref().and_then(ModelEcho::build_id);
+        let older = cached.cached_at_ms.unwrap_or(0) < marker.cached_at_ms.unwrap_or(0);
+        let other_build = matches!(
+            (build(&cached), build(&marker)),
+            (Some(entry), Some(current)) if entry != current
+        );
+        if older || other_build {
+            log::info!("Cached response predates the current model build; ignoring it");
+            return None;
+        }
+        Some(cached)
+    }
+
+    /// Record the model build that served a live response under `model_key`
+    ///
+    /// A build differing from the recorded one invalidates the entries stored
+    /// before it (see [`ResponseCache::lookup_current`]).
+    pub fn observe_build(&self, model_key: &str, echo: Option<&ModelEcho>) {
+        if !self.invalidate_on_model_change {
+            return;
+        }
+        let Some(build) = echo.and_then(ModelEcho::build_id) else {
+            return;
+        };
+        let previous = self
+            .lookup(model_key)
+            .and_then(|marker| marker.model_echo)
+            .and_then(|echo| echo.build_id());
+        let changed_at = match previous {
+            Some(previous) if previous == build => return,
+            Some(previous) => {
+                log::info!(
+                    "Model build changed ({previous} -> {build}); cached responses of the \
+                    previous build are invalidated"
+                );
+                now_ms()
+            }
+            // First build seen: no change observed yet
+            None => 0,
+        };
+        self.store(
+            model_key,
+            &CachedResponse {
+                model_echo: echo.cloned(),
+                cached_at_ms: Some(changed_at),
+                ..Default::default()
+            },
+        );
+    }
+
+    pub fn lookup(&self, key: &str) -> Option<CachedResponse> {
+        self.backend.get(key).unwrap_or_else(|e| {
+            log::warn!("Response cache lookup failed: {e}");
+            None
+        })
+    }
+
+    /// Store a response, stamping `cached_at_ms` when unset
+    pub fn store(&self, key: &str, response: &CachedResponse) {
+        let mut entry = response.clone();
+        entry.cached_at_ms.get_or_insert_with(now_ms);
+        if let Err(e) = self.backend.put(key, &entry) {
+            log::warn!("Response cache write failed: {e}");
+        }
+    }
+}
+
+fn now_ms() -> u64 {
+    chrono::Utc::now().timestamp_millis().max(0) as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::MemoryStorage;
+
+    fn response(content: &str) -> CachedResponse {
+        CachedResponse {
+            content: content.to_string(),
+            upstream: None,
+            model_echo: None,
+            tool_calls: Vec::new(),
+            cached_at_ms: None,
+        }
+    }
+
+    #[test]
+    fn test_memory_cache_evicts_least_recently_used() {
+        let cache = MemoryCache::new(2);
+        cache.put("a", &response("A")).unwrap();
+        cache.put("b", &response("B")).unwrap();
+        assert_eq!(cache.get("a").unwrap(), Some(response("A")));
+
+        cache.put("c", &response("C")).unwrap();
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.get("b").unwrap(), None);
+        assert_eq!(cache.get("a").unwrap(), Some(response("A")));
+
+        cache.put("a", &response("A2")).unwrap();
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.get("a").unwrap(), Some(response("A2")));
+    }
+
+    #[test]
+    fn test_storage_cache_round_trip() {
+        let storage = Arc::new(MemoryStorage::new());
+        let cache = StorageCache::new(storage.clone(), None);
+        cache.put("abc123", &response("Hello")).unwrap();
+
+        assert_eq!(cache.get("abc123").unwrap(), Some(response("Hello")));
+        assert_eq!(cache.get("missing").unwrap(), None);
+        assert!(storage.contains("cache/abc123").unwrap());
+    }
+
+    #[test]
+    fn test_key_covers_prompts_and_sampling() {
+        let params = InvokeParams {
+            model: "m",
+            system_prompt: "sys",
+            user_prompt: "hello",
+            temperature: 0.0,
+            top_p: None,
+            max_tokens: None,
+            seed: Some(1),
+            api_key: Some("secret"),
+            timeout_secs: 30,
+            response_format: None,
+            tools: &[],
+            images: &[],
+        };
+        let key = ResponseCache::key("http://x", None, &params);
+        assert_eq!(key.len(), 64);
+
+        let same = InvokeParams {
+            api_key: None,
+            timeout_secs: 5,
+            ..params
+        };
+        assert_eq!(ResponseCache::key("http://x", None, &same), key);
+
+        for changed in [
+            InvokeParams {
+                user_prompt: "hi",
+                ..params
+            },
+            InvokeParams {
+                temperature: 0.5,
+                ..params
+            },
+            InvokeParams {
+                seed: Some(2),
+                ..params
+            },
+        ] {
+            assert_ne!(ResponseCache::key("http://x", None, &changed), key);
+        }
+
+        let images = [ImageInput::from_bytes(b"GIF89a".to_vec()).unwrap()];
+        let with_image = InvokeParams {
+            images: &images,
+            ..params
+        };
+        assert_ne!(ResponseCache::key("http://x", None, &with_image), key);
+    }
+
+    #[test]
+    fn test_only_deterministic_requests_accepted_by_default() {
+        let params = InvokeParams {
+            model: "m",
+            system_prompt: "sys",
+            user_prompt: "hello",
+            temperature: 0.7,
+            top_p: None,
+            max_tokens: None,
+            seed: None,
+            api_key: None,
+            timeout_secs: 30,
+            response_format: None,
+            tools: &[],
+            images: &[],
+        };
+        let cache = ResponseCache::memory(10);
+        assert!(!cache.accepts(&params));
+        assert!(cache.accepts(&InvokeParams {
+            seed: Some(7),
+            ..params
+        }));
+        assert!(cache.accepts(&InvokeParams {
+            temperature: 0.0,
+            ..params
+        }));
+
+        let config: CacheConfig = toml::from_str("stochastic = true").unwrap();
+        assert!(ResponseCache::from_config(&config).accepts(&params));
+    }
+
+    #[test]
+    fn test_build_change_invalidates_earlier_entries() {
+        let cache = ResponseCache::memory(10);
+        let echo = |fingerprint: &str| ModelEcho {
+            system_fingerprint: Some(fingerprint.to_string()),
+            ..Default::default()
+        };
+        let entry = |fingerprint: &str| CachedResponse {
+            model_echo: Some(echo(fingerprint)),
+            ..response("Paris")
+        };
+
+        // The first build seen invalidates nothing
+        cache.store("a", &response("legacy"));
+        cache.observe_build("m", Some(&echo("fp_1")));
+        cache.store("b", &entry("fp_1"));
+        assert!(cache.lookup_current("a", "m").is_some());
+        assert!(cache.lookup_current("b", "m").is_some());
+
+        std::thread::sleep(Duration::from_millis(2));
+        cache.observe_build("m", Some(&echo("fp_2")));
+        assert!(cache.lookup_current("a", "m").is_none());
+        assert!(cache.lookup_current("b", "m").is_none());
+        cache.store("c", &entry("fp_2"));
+        assert!(cache.lookup_current("c", "m").is_some());
+
+        let untracked = ResponseCache::memory(10).invalidate_on_model_change(false);
+        untracked.store("b", &entry("fp_1"));
+        untracked.observe_build("m", Some(&echo("fp_2")));
+        assert!(untracked.lookup_current("b", "m").is_some());
+    }
+}
@@ -0,0 +1,513 @@
+//! Record/replay of HTTP interactions (VCR-style cassettes)
+//!
+//! Every HTTP call the crate makes (LLM invocation, LLM-based guardrails,
+//! context probing) goes through [`send`]. With a cassette installed, calls
+//! are either recorded to a JSON file or answered from one without touching
+//! the network, which makes CI runs deterministic and lets users share exact
+//! reproductions of a bug.
+//!
+//! Secrets are scrubbed before anything is stored: headers are never
+//! recorded, known API keys are replaced wherever they appear, and
+//! secret-looking JSON fields and query parameters are redacted.
+//!
+//! The cassette is process-wide (guardrail providers create their own
+//! clients), so install one per test process or serialize tests that use it.
+
+use crate::{
+    error::CliError,
+    response_limit::{self, BodyLimit},
+};
+use reqwest::{header::HeaderMap, RequestBuilder, StatusCode};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::{
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex, RwLock},
+    time::Duration,
+};
+
+/// Replacement for scrubbed values
+pub const REDACTED: &str = "[REDACTED]";
+
+/// Cassette file format version
+const CASSETTE_VERSION: u32 = 1;
+
+/// JSON fields and query parameters whose values are always redacted
+const SECRET_FIELDS: &[&str] = &[
+    "api_key",
+    "apikey",
+    "key",
+    "token",
+    "access_token",
+    "secret",
+    "password",
+    "authorization",
+];
+
+static ACTIVE: RwLock<Option<Arc<Cassette>>> = RwLock::new(None);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CassetteMode {
+    /// Perform real requests and store them
+    Record,
+    /// Answer requests from the cassette, never touching the network
+    Replay,
+}
+
+/// Recorded request (scrubbed)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecordedRequest {
+    pub method: String,
+    pub url: String,
+    #[serde(default, skip_serializing_if = "Value::is_null")]
+    pub body: Value,
+}
+
+/// Recorded outcome: an HTTP response or a transport error
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecordedResponse {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<u16>,
+    #[serde(default)]
+    pub body: String,
+    /// `Retry-After` delay of a rate-limited response, in seconds
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retry_after_secs: Option<u64>,
+    /// Transport failure (timeout, connection refused, ...)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Interaction {
+    pub request: RecordedRequest,
+    pub response: RecordedResponse,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct CassetteFile {
+    version: u32,
+    interactions: Vec<Interaction>,
+}
+
+/// A set of recorded HTTP interactions bound to a file
+#[derive(Debug)]
+pub struct Cassette {
+    mode: CassetteMode,
+    path: PathBuf,
+    /// Interactions and whether each was already replayed
+    interactions: Mutex<Vec<(Interaction, bool)>>,
+    secrets: Mutex<Vec<String>>,
+}
+
+impl Cassette {
+    /// Empty cassette that records to `path` on [`save`](Self::save)
+    pub fn record(path: impl Into<PathBuf>) -> Self {
+        Self {
+            mode: CassetteMode::Record,
+            path: path.into(),
+            interactions: Mutex::new(Vec::new()),
+            secrets: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Cassette replaying the interactions stored at `path`
+    pub fn replay(path: impl Into<PathBuf>) -> Result<Self, CliError> {
+        let path = path.into();
+        let content = std::fs::read_to_string(&path).map_err(|e| {
+            CliError::FileNotFound(format!("Failed to read cassette '{}': {e}", path.display()))
+        })?;
+        let file: CassetteFile = serde_json::from_str(&content).map_err(|e| {
+            CliError::InvalidArguments(format!("Invalid cassette '{}': {e}", path.display()))
+        })?;
+        if file.version != CASSETTE_VERSION {
+            return Err(CliError::InvalidArguments(format!(
+                "Unsupported cassette version {} in '{}' (expected {CASSETTE_VERSION})",
+                file.version,
+                path.display()
+            )));
+        }
+        Ok(Self {
+            mode: CassetteMode::Replay,
+            path,
+            interactions: Mutex::new(file.interactions.into_iter().map(|i| (i, false)).collect()),
+            secrets: Mutex::new(Vec::new()),
+        })
+    }
+
+    pub fn mode(&self) -> CassetteMode {
+        self.mode
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Scrub `secret` wherever it appears in recorded requests and responses
+    pub fn add_secret(&self, secret: impl Into<String>) {
+        let secret = secret.into();
+        if !secret.is_empty() {
+            self.secrets
+                .lock()
+                .expect("cassette lock poisoned")
+                .push(secret);
+        }
+    }
+
+    /// Interactions recorded or loaded so far
+    pub fn interactions(&self) -> Vec<Interaction> {
+        self.interactions
+            .lock()
+            .expect("cassette lock poisoned")
+            .iter()
+            .map(|(i, _)| i.clone())
+            .collect()
+    }
+
+    /// Write recorded interactions to the cassette file (record mode)
+    pub fn save(&self) -> Result<(), CliError> {
+        self.save_to(&self.path)
+    }
+
+    /// Write the interactions recorded or loaded so far to `path`
+    pub fn save_to(&self, path: &Path) -> Result<(), CliError> {
+        let file = CassetteFile {
+            version: CASSETTE_VERSION,
+            interactions: self.interactions(),
+        };
+        let json = serde_json::to_string_pretty(&file).map_err(|e| {
+            CliError::InvalidArguments(format!("Failed to serialize cassette: {e}"))
+        })?;
+        if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir).map_err(|e| {
+                CliError::InvalidArguments(format!(
+                    "Failed to create cassette directory '{}': {e}",
+                    dir.display()
+                ))
+            })?;
+        }
+        std::fs::write(path, json).map_err(|e| {
+            CliError::InvalidArguments(format!(
+                "Failed to write cassette '{}': {e}",
+                path.display()
+            ))
+        })
+    }
+
+    /// Replace the known secrets in `text`
+    pub fn scrub_text(&self, text: &str) -> String {
+        let secrets = self.secrets.lock().expect("cassette lock poisoned");
+        secrets.iter().fold(text.to_string(), |acc, secret| {
+            acc.replace(secret, REDACTED)
+        })
+    }
+
+    /// Redact secret-looking fields and known secrets in `value`
+    pub fn scrub_value(&self, value: Value) -> Value {
+        match value {
+            Value::Object(map) => Value::Object(
+                map.into_iter()
+                    .map(|(k, v)| {
+                        if is_secret_field(&k) && !v.is_null() {
+                            (k, Value::String(REDACTED.to_string()))
+                        } else {
+                            (k, self.scrub_value(v))
+                        }
+                    })
+                    .collect(),
+            ),
+            Value::Array(items) => {
+                Value::Array(items.into_iter().map(|v| self.scrub_value(v)).collect())
+            }
+            Value::String(s) => Value::String(self.scrub_text(&s)),
+            other => other,
+        }
+    }
+
+    fn scrub_url(&self, url: &reqwest::Url) -> String {
+        let mut url = url.clone();
+        if url.query().is_some() {
+            let pairs: Vec<(String, String)> = url
+                .query_pairs()
+                .map(|(k, v)| {
+                    let v = if is_secret_field(&k) {
+                        REDACTED.to_string()
+                    } else {
+                        v.into_owned()
+                    };
+                    (k.into_owned(), v)
+                })
+                .collect();
+            url.query_pairs_mut().clear().extend_pairs(pairs);
+        }
+        self.scrub_text(url.as_str())
+    }
+
+    fn recorded_request(&self, request: &reqwest::Request) -> RecordedRequest {
+        let body = request
+            .body()
+            .and_then(|b| b.as_bytes())
+            .map(|bytes| {
+                serde_json::from_slice(bytes)
+                    .unwrap_or_else(|_| Value::String(String::from_utf8_lossy(bytes).into_owned()))
+            })
+            .unwrap_or(Value::Null);
+        RecordedRequest {
+            method: request.method().to_string(),
+            url: self.scrub_url(request.url()),
+            body: self.scrub_value(body),
+        }
+    }
+
+    /// First unreplayed interaction matching `request`
+    fn take_match(&self, request: &RecordedRequest) -> Option<RecordedResponse> {
+        let mut interactions = self.interactions.lock().expect("cassette lock poisoned");
+        let (interaction, used) = interactions
+            .iter_mut()
+            .find(|(i, used)| !*used && i.request == *request)?;
+        *used = true;
+        Some(interaction.response.clone())
+    }
+
+    fn push(&self, request: RecordedRequest, response: RecordedResponse) {
+        self.interactions
+            .lock()
+            .expect("cassette lock poisoned")
+            .push((Interaction { request, response }, false));
+    }
+}
+
+pub(crate) fn is_secret_field(name: &str) -> bool {
+    SECRET_FIELDS
+        .iter()
+        .any(|field| name.eq_ignore_ascii_case(field))
+}
+
+/// Install `cassette` for all subsequent HTTP calls, returning the previous one
+pub fn install(cassette: Arc<Cassette>) -> Option<Arc<Cassette>> {
+    ACTIVE
+        .write()
+        .expect("cassette lock poisoned")
+        .replace(cassette)
+}
+
+/// Remove the installed cassette (HTTP calls go to the network again)
+pub fn uninstall() -> Option<Arc<Cassette>> {
+    ACTIVE.write().expect("cassette lock poisoned").take()
+}
+
+/// The installed cassette, if any
+pub fn installed() -> Option<Arc<Cassette>> {
+    ACTIVE.read().expect("cassette lock poisoned").clone()
+}
+
+/// Status and body of an HTTP response (live or replayed)
+#[derive(Debug, Clone)]
+pub(crate) struct HttpReply {
+    pub status: StatusCode,
+    pub body: String,
+    /// `Retry-After` header (only kept for rate-limited responses)
+    pub retry_after: Option<Duration>,
+}
+
+impl HttpReply {
+    async fn read(response: reqwest::Response) -> Result<Self, CliError> {
+        let status = response.status();
+        let retry_after = retry_after(status, response.headers());
+        let body = response_limit::read_text(response).await?;
+        Ok(Self {
+            status,
+            body,
+            retry_after,
+        })
+    }
+}
+
+/// Delay requested by the `Retry-After` header of a 429 response: a number of
+/// seconds or an HTTP date
+fn retry_after(status: StatusCode, headers: &HeaderMap) -> Option<Duration> {
+    if status != StatusCode::TOO_MANY_REQUESTS {
+        return None;
+    }
+    let value = headers
+        .get(reqwest::header::RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .trim();
+    if let Ok(secs) = value.parse::<u64>() {
+        return Some(Duration::from_secs(secs));
+    }
+    let date = chrono::DateTime::parse_from_rfc2822(value).ok()?;
+    let delay = date.signed_duration_since(chrono::Utc::now());
+    Some(delay.to_std().unwrap_or(Duration::ZERO))
+}
+
+/// Send a request, recording or replaying it if a cassette is installed
+///
+/// `api_key` is scrubbed from recordings in addition to the cassette's secrets.
+pub(crate) async fn send(
+    request: RequestBuilder,
+    api_key: Option<&str>,
+) -> Result<HttpReply, CliError> {
+    let (client, request) = request.build_split();
+    let request = request?;
+
+    let Some(cassette) = installed() else {
+        return HttpReply::read(client.execute(request).await?).await;
+    };
+
+    if let Some(key) = api_key {
+        cassette.add_secret(key);
+    }
+    let recorded = cassette.recorded_request(&request);
+
+    match cassette.mode {
+        CassetteMode::Replay => {
+            let response = cassette.take_match(&recorded).ok_or_else(|| {
+                CliError::InvalidResponse(format!(
+                    "No recorded interaction for {} {} in cassette '{}'",
+                    recorded.method,
+                    recorded.url,
+                    cassette.path.display()
+                ))
+            })?;
+            log::debug!("Cassette replay: {} {}", recorded.method, recorded.url);
+            if let Some(error) = response.error {
+                return Err(CliError::InvalidResponse(format!(
+                    "HTTP request failed (replayed): {error}"
+                )));
+            }
+            let status = StatusCode::from_u16(response.status.unwrap_or(200)).map_err(|e| {
+                CliError::InvalidArguments(format!("Invalid status in cassette: {e}"))
+            })?;
+            Ok(HttpReply {
+                status,
+                body: response.body,
+                retry_after: response.retry_after_secs.map(Duration::from_secs),
+            })
+        }
+        CassetteMode::Record => {
+            log::debug!("Cassette record: {} {}", recorded.method, recorded.url);
+            let result = async { HttpReply::read(client.execute(request).await?).await }.await;
+            let stored = match &result {
+                Ok(reply) => RecordedResponse {
+                    status: Some(reply.status.as_u16()),
+                    body: cassette.scrub_text(&reply.body),
+                    retry_after_secs: reply.retry_after.map(|delay| delay.as_secs()),
+                    error: None,
+                },
+                Err(e) => {
+                    let error = match e {
+                        CliError::HttpError(e) => e.to_string(),
+                        other => other.to_string(),
+                    };
+                    RecordedResponse {
+                        status: None,
+                        body: String::new(),
+                        retry_after_secs: None,
+                        error: Some(cassette.scrub_text(&error)),
+                    }
+                }
+            };
+            cassette.push(recorded, stored);
+            Ok(result?)
+        }
+    }
+}
+
+/// HTTP response whose body is read incrementally
+///
+/// Live responses are read chunk by chunk as they arrive. With a cassette
+/// installed the body is recorded or replayed in full and handed out as a
+/// single chunk, so streamed interactions stay reproducible. Live bodies
+/// are counted against the response size limit (see [`crate::response_limit`]).
+pub(crate) enum StreamReply {
+    Live(reqwest::Response, BodyLimit),
+    Buffered(HttpReply),
+}
+
+impl StreamReply {
+    pub fn status(&self) -> StatusCode {
+        match self {
+            Self::Live(response, _) => response.status(),
+            Self::Buffered(reply) => reply.status,
+        }
+    }
+
+    /// `Retry-After` delay of a rate-limited response
+    pub fn retry_after(&self) -> Option<Duration> {
+        match self {
+            Self::Live(response, _) => retry_after(response.status(), response.headers()),
+            Self::Buffered(reply) => reply.retry_after,
+        }
+    }
+
+    /// Rest of the body as text (used for error responses)
+    pub async fn text(self) -> Result<String, CliError> {
+        match self {
+            Self::Live(response, _) => response_limit::read_text(response).await,
+            Self::Buffered(reply) => Ok(reply.body),
+        }
+    }
+
+    /// Next piece of the body (None = end of body)
+    pub async fn next_bytes(&mut self) -> Result<Option<Vec<u8>>, CliError> {
+        match self {
+            Self::Live(response, limit) => match response.chunk().await? {
+                Some(chunk) => {
+                    limit.add(chunk.len())?;
+                    Ok(Some(chunk.to_vec()))
+                }
+                None => Ok(None),
+            },
+            Self::Buffered(reply) if reply.body.is_empty() => Ok(None),
+            Self::Buffered(reply) => Ok(Some(std::mem::take(&mut reply.body).into_bytes())),
+        }
+    }
+}
+
+/// Send a request whose body is streamed, recording or replaying it if a
+/// cassette is installed
+pub(crate) async fn send_streaming(
+    request: RequestBuilder,
+    api_key: Option<&str>,
+) -> Result<StreamReply, CliError> {
+    if installed().is_none() {
+        let response = request.send().await?;
+        let limit = BodyLimit::start(&response)?;
+        return Ok(StreamReply::Live(response, limit));
+    }
+    send(request, api_key).await.map(StreamReply::Buffered)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_scrubbing() {
+        let cassette = Cassette::record("unused.json");
+        cassette.add_secret("sk-live-123");
+
+        let scrubbed = cassette.scrub_value(json!({
+            "model": "m",
+            "api_key": "anything",
+            "messages": [{"content": "my key is sk-live-123"}]
+        }));
+        assert_eq!(
+            scrubbed,
+            json!({
+                "model": "m",
+                "api_key": REDACTED,
+                "messages": [{"content": "my key is [REDACTED]"}]
+            })
+        );
+
+        let url = reqwest::Url::parse("https://api.example.com/v1/models?key=abc&x=1").unwrap();
+        assert_eq!(
+            cassette.scrub_url(&url),
+            "https://api.example.com/v1/models?key=%5BREDACTED%5D&x=1"
+        );
+    }
+}
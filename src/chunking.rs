@@ -0,0 +1,307 @@
+//! Chunked evaluation of documents exceeding the context window
+//!
+//! Without chunking, a prompt whose estimate exceeds `context_limit` ends the
+//! run with `CONTEXT_LIMIT_EXCEEDED`. With a `[chunking]` section, the largest
+//! part of the prompt (usually the extracted document) is split into
+//! overlapping chunks aligned to paragraphs and sentences, and each chunk is
+//! sent with the rest of the prompt:
+//!
+//! ```toml
+//! [chunking]
+//! strategy = "map_reduce"   # or "refine" (default: map_reduce)
+//! chunk_tokens = 6000       # Default: as much as fits the context window
+//! overlap_tokens = 200      # Repeated between neighbouring chunks (default)
+//! max_chunks = 16           # Larger documents still fail (default)
+//! combine_prompt = "Merge these partial summaries into one summary."
+//! ```
+//!
+//! - `map_reduce`: every chunk is answered on its own, then the partial
+//!   answers are combined by further calls (in groups when they don't fit
+//!   together).
+//! - `refine`: the first chunk is answered, and each following chunk refines
+//!   the running answer.
+//!
+//! Output guardrails and refusal detection check the combined answer; each
+//! chunk is recorded in `metadata.chunking`.
+
+use crate::{constants::token_estimation, model_registry};
+use serde::{Deserialize, Serialize};
+use std::ops::Range;
+
+fn default_overlap_tokens() -> usize {
+    200
+}
+
+fn default_max_chunks() -> usize {
+    16
+}
+
+/// How chunk answers are merged
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChunkStrategy {
+    /// Answer each chunk independently, then combine the answers
+    #[default]
+    MapReduce,
+    /// Answer the first chunk, then refine the answer with each following one
+    Refine,
+}
+
+impl std::fmt::Display for ChunkStrategy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::MapReduce => "map_reduce",
+            Self::Refine => "refine",
+        })
+    }
+}
+
+/// Chunking settings (`[chunking]`)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChunkingConfig {
+    #[serde(default)]
+    pub strategy: ChunkStrategy,
+
+    /// Document tokens per chunk (default: as much as fits the context window)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chunk_tokens: Option<usize>,
+
+    /// Tokens repeated at the start of the next chunk (default: 200)
+    #[serde(default = "default_overlap_tokens")]
+    pub overlap_tokens: usize,
+
+    /// Documents needing more chunks still fail (default: 16)
+    #[serde(default = "default_max_chunks")]
+    pub max_chunks: usize,
+
+    /// Instruction for combining (map_reduce) or refining (refine) answers
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub combine_prompt: Option<String>,
+}
+
+impl Default for ChunkingConfig {
+    fn default() -> Self {
+        Self {
+            strategy: ChunkStrategy::default(),
+            chunk_tokens: None,
+            overlap_tokens: default_overlap_tokens(),
+            max_chunks: default_max_chunks(),
+            combine_prompt: None,
+        }
+    }
+}
+
+impl ChunkingConfig {
+    pub fn new(strategy: ChunkStrategy) -> Self {
+        Self {
+            strategy,
+            ..Self::default()
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.chunk_tokens == Some(0) {
+            return Err("chunking.chunk_tokens must be >= 1".to_string());
+        }
+        if let Some(chunk_tokens) = self.chunk_tokens {
+            if self.overlap_tokens >= chunk_tokens {
+                return Err(format!(
+                    "chunking.overlap_tokens ({}) must be lower than chunk_tokens ({chunk_tokens})",
+                    self.overlap_tokens
+                ));
+            }
+        }
+        if self.max_chunks < 2 {
+            return Err("chunking.max_chunks must be >= 2".to_string());
+        }
+        Ok(())
+    }
+
+    /// User prompt combining partial answers (map_reduce)
+    pub fn combine_prompt(
+        &self,
+        answers: &[String],
+        first_part: usize,
+        total_parts: usize,
+    ) -> String {
+        let instruction = self.combine_prompt.as_deref().unwrap_or(
+            "The document was too long to process at once, so it was split into parts and \
+             each part was answered separately. Combine the partial answers below into a \
+             single answer to the original request, removing duplicates.",
+        );
+        let parts: Vec<String> = answers
+            .iter()
+            .enumerate()
+            .map(|(i, answer)| {
+                format!(
+                    "Partial answer {} of {total_parts}:\n{}",
+                    first_part + i + 1,
+                    answer.trim()
+                )
+            })
+            .collect();
+        format!("{instruction}\n\n{}", parts.join("\n\n"))
+    }
+
+    /// User prompt refining `answer` with chunk `index` (refine)
+    pub fn refine_prompt(
+        &self,
+        answer: &str,
+        chunk_prompt: &str,
+        index: usize,
+        total: usize,
+    ) -> String {
+        let instruction = self.combine_prompt.as_deref().unwrap_or(
+            "The document was too long to process at once, so it is given in parts. Refine \
+             the existing answer with the next part: keep what is still correct and add what \
+             the new part contributes.",
+        );
+        format!(
+            "{instruction}\n\nExisting answer:\n{}\n\nPart {} of {total}:\n{chunk_prompt}",
+            answer.trim(),
+            index + 1
+        )
+    }
+}
+
+/// One chunk of a chunked evaluation (`metadata.chunking.chunks`)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChunkRecord {
+    pub index: usize,
+    /// Byte range of the chunk in the document
+    pub start: usize,
+    pub end: usize,
+    /// Estimated tokens of the chunk's request
+    pub tokens_estimated: usize,
+    /// Latency of the chunk's LLM call (0 until invoked)
+    pub latency_ms: u64,
+    /// Length of the chunk's answer in bytes (0 until invoked)
+    pub response_bytes: usize,
+}
+
+/// Chunked evaluation summary (`metadata.chunking`)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChunkingRecord {
+    pub strategy: ChunkStrategy,
+    /// Source of the chunked prompt part (e.g. "pdf")
+    pub source: String,
+    pub chunks: Vec<ChunkRecord>,
+    /// LLM calls combining partial answers (map_reduce)
+    pub combine_calls: usize,
+}
+
+/// Characters per token of `model`'s tokenizer (generic estimate if unknown)
+pub fn chars_per_token(model: &str) -> f64 {
+    model_registry::lookup_model(model)
+        .map(|info| info.tokenizer.chars_per_token())
+        .unwrap_or(token_estimation::CHARS_PER_TOKEN)
+}
+
+/// Byte ranges of chunks of up to `max_bytes`, overlapping by about
+/// `overlap_bytes`
+///
+/// Chunks end at a paragraph break, line break, sentence end or whitespace in
+/// their second half when there is one; ranges are on UTF-8 boundaries.
+pub fn split(text: &str, max_bytes: usize, overlap_bytes: usize) -> Vec<Range<usize>> {
+    let max_bytes = max_bytes.max(1);
+    let mut chunks = Vec::new();
+    let mut start = 0;
+    while start < text.len() {
+        let mut end = floor_boundary(text, (start + max_bytes).min(text.len()));
+        if end <= start {
+            end = ceil_boundary(text, start + 1);
+        }
+        if end < text.len() {
+            let window = &text[start..end];
+            let half = window.len() / 2;
+            let cut = ["\n\n", "\n", ". ", " "].iter().find_map(|separator| {
+                window
+                    .rfind(separator)
+                    .map(|at| at + separator.len())
+                    .filter(|&at| at > half)
+            });
+            if let Some(cut) = cut {
+                end = start + cut;
+            }
+        }
+        chunks.push(start..end);
+        if end >= text.len() {
+            break;
+        }
+
+        // Start the next chunk about `overlap_bytes` back, at a word start
+        let mut next = end;
+        if overlap_bytes > 0 {
+            let back = ceil_boundary(text, end.saturating_sub(overlap_bytes));
+            if let Some(at) = text[back..end].find(char::is_whitespace) {
+                let space = back + at;
+                next = text[space..end]
+                    .find(|c: char| !c.is_whitespace())
+                    .map_or(end, |word| space + word);
+            }
+        }
+        start = if next > start { next } else { end };
+    }
+    chunks
+}
+
+fn floor_boundary(text: &str, mut index: usize) -> usize {
+    while !text.is_char_boundary(index) {
+        index -= 1;
+    }
+    index
+}
+
+fn ceil_boundary(text: &str, mut index: usize) -> usize {
+    while index < text.len() && !text.is_char_boundary(index) {
+        index += 1;
+    }
+    index
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_aligns_and_overlaps() {
+        let text =
+            "First paragraph here.\n\nSecond paragraph follows. It has two sentences.\n\nThird.";
+        let chunks = split(text, 40, 0);
+        assert_eq!(&text[chunks[0].clone()], "First paragraph here.\n\n");
+        assert!(chunks.iter().all(|c| c.len() <= 40));
+        assert_eq!(chunks.last().unwrap().end, text.len());
+
+        let overlapping = split(text, 40, 12);
+        assert!(overlapping.len() >= chunks.len());
+        for pair in overlapping.windows(2) {
+            assert!(pair[1].start < pair[0].end, "{overlapping:?}");
+            assert!(pair[1].start > pair[0].start);
+        }
+    }
+
+    #[test]
+    fn test_split_respects_char_boundaries() {
+        let text = "é".repeat(50);
+        let chunks = split(&text, 7, 3);
+        assert!(chunks.iter().all(|c| text.is_char_boundary(c.start)
+            && text.is_char_boundary(c.end)
+            && !c.is_empty()));
+        assert_eq!(chunks.last().unwrap().end, text.len());
+    }
+
+    #[test]
+    fn test_config_defaults_and_validation() {
+        let config: ChunkingConfig = toml::from_str("strategy = \"refine\"").unwrap();
+        assert_eq!(config.strategy, ChunkStrategy::Refine);
+        assert_eq!(config.overlap_tokens, 200);
+        assert_eq!(config.max_chunks, 16);
+        config.validate().unwrap();
+
+        let config = ChunkingConfig {
+            chunk_tokens: Some(100),
+            ..ChunkingConfig::default()
+        };
+        assert!(config.validate().unwrap_err().contains("overlap_tokens"));
+    }
+}
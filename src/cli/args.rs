@@ -0,0 +1,728 @@
+//! Command-line arguments and config file merging
+
+use super::{
+    commands::Command,
+    guardrail_config::InputGuardrailArg,
+    validators::{
+        validate_byte_size, validate_context_limit, validate_file_exists, validate_positive_u32,
+        validate_positive_u64, validate_positive_usize, validate_temperature, validate_top_p,
+    },
+};
+use crate::{
+    config::{env_provider, read_config_value},
+    CliError, DataClassification, ImageMode, LogContentPolicy, OutputFormat, ParameterPreset,
+    Provider,
+};
+use clap::Parser;
+use figment::{providers::Serialized, Figment};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::path::PathBuf;
+
+#[derive(Parser, Debug, Clone, Serialize, Deserialize)]
+#[command(name = "fortified-llm-client")]
+#[command(about = "LLM client fortified by multi-layered security guardrails and multi-provider support", long_about = None)]
+#[command(version = env!("CARGO_PKG_VERSION"), disable_version_flag = true)]
+#[serde(default)]
+pub struct Args {
+    /// Print version
+    #[arg(long, short = 'V')]
+    #[serde(skip, default)]
+    pub version: bool,
+
+    /// With --version: print build information (git sha, features, providers,
+    /// guardrail types, output schema version) as JSON
+    #[arg(long, requires = "version")]
+    #[serde(skip, default)]
+    pub json: bool,
+
+    /// Subcommand (maintenance commands run instead of an evaluation)
+    #[command(subcommand)]
+    #[serde(skip)]
+    pub command: Option<Command>,
+
+    /// Config file (JSON or TOML) with default evaluation parameters
+    /// Note: any CLI argument will override the corresponding config file value
+    #[arg(long, short = 'c', value_parser = validate_file_exists)]
+    #[serde(skip)]
+    pub config_file: Option<PathBuf>,
+
+    /// Config file profile applied over its [default] section (e.g. --profile prod
+    /// selects [profiles.prod])
+    #[arg(long, requires = "config_file", value_name = "NAME")]
+    #[serde(skip)]
+    pub profile: Option<String>,
+
+    /// LLM API endpoint URL
+    #[arg(long, short = 'a')]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub api_url: Option<String>,
+
+    /// Model name/identifier
+    #[arg(long, short = 'm')]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+
+    /// Force specific provider format (overrides auto-detection)
+    #[arg(long, value_enum)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub provider: Option<ProviderArg>,
+
+    /// System prompt from file
+    #[arg(long, short = 's', conflicts_with = "system_text", value_parser = validate_file_exists)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub system_file: Option<PathBuf>,
+
+    /// System prompt as text (no short form, use --system-text)
+    #[arg(long, conflicts_with = "system_file")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub system_text: Option<String>,
+
+    /// System prompt fetched from a prompt library URL (https, host listed in
+    /// the config file's fetch.allowed_hosts, plain text or markdown)
+    #[arg(long, value_name = "URL", conflicts_with_all = ["system_file", "system_text"])]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub system_prompt_url: Option<String>,
+
+    /// User prompt from file
+    #[arg(long, short = 'u', conflicts_with_all = ["user_text", "pdf_file", "user_prompt_url"], value_parser = validate_file_exists)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user_file: Option<PathBuf>,
+
+    /// User prompt as text (no short form, use --user-text); `-` reads it
+    /// from stdin
+    #[arg(long, conflicts_with_all = ["user_file", "pdf_file", "user_prompt_url"])]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user_text: Option<String>,
+
+    /// PDF file to extract text from (replaces user prompt)
+    #[arg(long, short = 'p', conflicts_with_all = ["user_file", "user_text"], value_parser = validate_file_exists)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pdf_file: Option<PathBuf>,
+
+    /// Web page to fetch, reduce to its main content and convert to markdown
+    /// as the user prompt (private network hosts are refused)
+    #[arg(long = "url", value_name = "URL", conflicts_with_all = ["user_file", "user_text", "pdf_file"])]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user_prompt_url: Option<String>,
+
+    /// Image (PNG, JPEG, GIF or WebP) sent with the user prompt: OCR'd into it,
+    /// or attached for vision models with --image-mode vision
+    #[arg(long, value_parser = validate_file_exists)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub image_file: Option<PathBuf>,
+
+    /// How --image-file reaches the model (default: ocr)
+    #[arg(long, value_enum)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub image_mode: Option<ImageModeArg>,
+
+    /// File appended to the user prompt as untrusted content (repeatable, e.g.
+    /// --context-file notes.md); replaces the config file's context_files
+    #[arg(long = "context-file", value_name = "FILE", value_parser = validate_file_exists)]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub context_files: Vec<PathBuf>,
+
+    /// Evaluate every prompt in a JSONL or CSV file instead of a single prompt
+    /// Items have a user_prompt and optional id/system_prompt; writes one JSON result per line
+    #[arg(long, conflicts_with_all = ["user_file", "user_text", "pdf_file", "user_prompt_url"], value_parser = validate_file_exists)]
+    #[serde(skip)]
+    pub batch_file: Option<PathBuf>,
+
+    /// Maximum evaluations in flight in batch mode (default: 4)
+    #[arg(long, requires = "batch_file", value_parser = validate_positive_usize)]
+    #[serde(skip)]
+    pub batch_concurrency: Option<usize>,
+
+    /// Also write one CSV row per batch item (status, tokens, latency, violation rules,
+    /// quality score, tags) to this file
+    #[arg(long, requires = "batch_file", conflicts_with = "experiment")]
+    #[serde(skip)]
+    pub summary_csv: Option<PathBuf>,
+
+    /// Also write a JUnit XML report with one test case per batch item (guardrail,
+    /// schema and other pipeline blocks are failures, hard errors are errors) to this file
+    #[arg(long, requires = "batch_file", conflicts_with = "experiment")]
+    #[serde(skip)]
+    pub junit_xml: Option<PathBuf>,
+
+    /// Evaluation tag echoed in output metadata (repeatable, e.g. --tag experiment=A)
+    /// Batch summaries are grouped by tag; replaces the config file's tags
+    #[arg(long = "tag", value_name = "TAG")]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+
+    /// Compare the system prompt variants of the config file's [experiment] section
+    /// on the batch file's items; writes a comparative report instead of per-item lines
+    #[arg(long, requires = "batch_file")]
+    #[serde(skip, default)]
+    pub experiment: bool,
+
+    /// Run the prompt across the sampling grid of the config file's [sweep] section
+    /// and write a per-point report instead of a single response
+    #[arg(long, conflicts_with = "batch_file")]
+    #[serde(skip, default)]
+    pub sweep: bool,
+
+    /// Format of the --sweep report
+    #[arg(long, value_enum, default_value = "json", requires = "sweep")]
+    #[serde(skip, default)]
+    pub sweep_format: SweepFormat,
+
+    /// Run the prompt on these models concurrently (comma-separated, e.g.
+    /// --compare-models llama3,mistral) and write a comparison report instead
+    /// of a single response; --model defaults to the first one
+    #[arg(long, value_delimiter = ',', value_name = "MODELS", conflicts_with_all = ["batch_file", "sweep"])]
+    #[serde(skip)]
+    pub compare_models: Vec<String>,
+
+    /// Parameter preset (fills temperature/top_p/seed not set explicitly)
+    #[arg(long, value_enum)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub preset: Option<PresetArg>,
+
+    /// Sampling temperature
+    #[arg(long, short = 't', value_parser = validate_temperature)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f32>,
+
+    /// Nucleus sampling probability mass (0.0-1.0)
+    #[arg(long, value_parser = validate_top_p)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub top_p: Option<f32>,
+
+    /// Maximum response tokens
+    #[arg(long, value_parser = validate_positive_u32)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_tokens: Option<u32>,
+
+    /// Random seed for reproducible sampling
+    #[arg(long)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub seed: Option<u64>,
+
+    /// Enable token validation
+    #[arg(long)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub validate_tokens: Option<bool>,
+
+    /// Model's context window limit
+    #[arg(long, value_parser = validate_context_limit)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub context_limit: Option<usize>,
+
+    /// Warn when estimated tokens exceed this percent of the context limit (default: 90)
+    #[arg(long, value_parser = clap::value_parser!(u8).range(1..=100))]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub context_warning_percent: Option<u8>,
+
+    /// Response format (json-object, json-schema, or text, OpenAI-compatible only)
+    #[arg(long, value_enum)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub response_format: Option<ResponseFormatArg>,
+
+    /// JSON Schema file path (required when --response-format=json-schema)
+    #[arg(long, value_parser = validate_file_exists)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub response_format_schema: Option<PathBuf>,
+
+    /// Use strict mode for JSON schema validation (default: true)
+    #[arg(long, default_value_t = true)]
+    #[serde(default = "default_response_format_schema_strict")]
+    pub response_format_schema_strict: bool,
+
+    /// API key for authentication (direct value)
+    #[arg(long, conflicts_with = "api_key_name")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub api_key: Option<String>,
+
+    /// Environment variable name containing the API key
+    #[arg(long, conflicts_with = "api_key")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub api_key_name: Option<String>,
+
+    /// Run the guardrail canary self-test before evaluating; refuse to run if it fails
+    #[arg(long)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub self_test: Option<bool>,
+
+    /// Content allowed in log lines: never, truncated, truncated:N or full (default: full)
+    #[arg(long)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub log_content_policy: Option<LogContentPolicy>,
+
+    /// Maximum size in bytes of response bodies read from providers, guardrails
+    /// and context probes (default: 20971520 = 20MB)
+    #[arg(long)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_response_bytes: Option<usize>,
+
+    /// Sensitivity of the content: public or restricted (restricted content is
+    /// only sent to local endpoints and --allow-external-host hosts)
+    #[arg(long)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data_classification: Option<DataClassification>,
+
+    /// External host trusted with restricted content (repeatable, `*.` prefix for
+    /// subdomains, e.g. --allow-external-host '*.openai.azure.com')
+    #[arg(long = "allow-external-host", value_name = "HOST")]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub allowed_external_hosts: Vec<String>,
+
+    /// Request timeout in seconds (must be > 0)
+    #[arg(long = "timeout", value_parser = validate_positive_u64)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timeout_secs: Option<u64>,
+
+    /// Enable verbose logging (DEBUG level)
+    #[arg(long, short = 'v', conflicts_with = "quiet")]
+    #[serde(skip, default)]
+    pub verbose: bool,
+
+    /// Suppress all logging output
+    #[arg(long, short = 'q', conflicts_with = "verbose")]
+    #[serde(skip, default)]
+    pub quiet: bool,
+
+    /// Color log levels: auto (only when stderr is a terminal and NO_COLOR is unset),
+    /// always or never
+    #[arg(long, value_enum, default_value = "auto")]
+    #[serde(skip, default)]
+    pub color: ColorChoice,
+
+    /// Disable log colors (same as --color never)
+    #[arg(long)]
+    #[serde(skip, default)]
+    pub no_color: bool,
+
+    /// Pipeline-safe mode: stdout carries only result JSON (--events-file - is
+    /// rejected) and log colors are off unless --color always
+    #[arg(long)]
+    #[serde(skip, default)]
+    pub pipe: bool,
+
+    /// Write output to file instead of stdout
+    /// Uses atomic writes (temp file + rename) and creates parent directories
+    #[arg(long, short = 'o')]
+    #[serde(skip)]
+    pub output: Option<PathBuf>,
+
+    /// How --output is written: replace (atomic whole-file write) or append-jsonl
+    /// (each result appended as one line under an exclusive file lock)
+    #[arg(long, value_enum, default_value = "replace")]
+    #[serde(skip, default)]
+    pub output_mode: OutputMode,
+
+    /// Output format of single evaluations: json, yaml, markdown (a
+    /// human-readable report) or raw (only the response text, for shell
+    /// pipelines; errors go to stderr)
+    #[arg(long, value_enum, default_value = "json")]
+    #[serde(skip, default)]
+    pub output_format: OutputFormatArg,
+
+    // Input Validation (regex-based pattern matching or a single LLM guard via CLI)
+    // Note: For GPT-OSS Safeguard, output guardrails and hybrid strategies,
+    //       use config files with the [guardrails] section
+    /// Enable regex-based input validation using default patterns (PII, prompt injection, etc.)
+    /// For custom patterns or fine-grained control, use config files
+    #[arg(long, conflicts_with = "input_guardrail")]
+    #[serde(skip, default)]
+    pub enable_input_validation: bool,
+
+    /// Validate input with an LLM guard instead of a [guardrails] config section
+    #[arg(long, value_enum)]
+    #[serde(skip)]
+    pub input_guardrail: Option<InputGuardrailArg>,
+
+    /// Guard endpoint (default: http://localhost:11434/api/generate)
+    #[arg(long, requires = "input_guardrail")]
+    #[serde(skip)]
+    pub guard_url: Option<String>,
+
+    /// Guard model (default: llama-guard-3:8b or llama-prompt-guard-2-22m)
+    #[arg(long, requires = "input_guardrail")]
+    #[serde(skip)]
+    pub guard_model: Option<String>,
+
+    /// Environment variable holding the guard endpoint's API key
+    #[arg(long, requires = "input_guardrail")]
+    #[serde(skip)]
+    pub guard_api_key_name: Option<String>,
+
+    /// Maximum input length (default: 1MB when validation enabled)
+    /// Accepts human-readable sizes: 100MB, 1.5GB, 500KB, or plain bytes
+    #[arg(long, requires = "enable_input_validation", value_parser = validate_byte_size)]
+    #[serde(skip)]
+    pub max_input_length: Option<usize>,
+
+    /// Maximum estimated input tokens (default: 200K when validation enabled)
+    #[arg(long, requires = "enable_input_validation", value_parser = validate_positive_usize)]
+    #[serde(skip)]
+    pub max_input_tokens: Option<usize>,
+
+    /// Override token to downgrade allowlisted guardrail blocks to warnings
+    /// Requires a [guardrail_override] policy in the config file; every use is audited
+    #[arg(long)]
+    #[serde(skip)]
+    pub override_token: Option<String>,
+
+    /// Record all HTTP interactions (LLM and guardrails) to a cassette file, secrets scrubbed
+    #[arg(long, conflicts_with = "replay")]
+    #[serde(skip)]
+    pub record: Option<PathBuf>,
+
+    /// Replay HTTP interactions from a cassette file instead of calling the network
+    #[arg(long, value_parser = validate_file_exists)]
+    #[serde(skip)]
+    pub replay: Option<PathBuf>,
+
+    /// On unexpected errors (not guardrail blocks), write a debug bundle to a new
+    /// subdirectory of DIR: scrubbed config, HTTP payloads, timings and log excerpt
+    #[arg(long, value_name = "DIR")]
+    #[serde(skip)]
+    pub debug_bundle: Option<PathBuf>,
+
+    /// Show a redacted preview of the outbound request (endpoints, model, byte counts,
+    /// detected sensitive data) on stderr and ask for confirmation before sending it
+    #[arg(long, conflicts_with_all = ["batch_file", "sweep", "compare_models"])]
+    #[serde(skip, default)]
+    pub confirm: bool,
+
+    /// Answer repeated requests (same model, prompts and sampling) from a response cache
+    /// In-memory unless --cache-dir or [cache] dir is set
+    #[arg(long)]
+    #[serde(skip, default)]
+    pub cache: bool,
+
+    /// Directory of the on-disk response cache (implies --cache)
+    #[arg(long)]
+    #[serde(skip)]
+    pub cache_dir: Option<PathBuf>,
+
+    /// Also cache stochastic requests (temperature > 0 without a seed; implies --cache)
+    #[arg(long)]
+    #[serde(skip, default)]
+    pub cache_stochastic: bool,
+
+    /// Write Prometheus metrics (requests, guardrail blocks, latency, tokens) to this file after the run
+    #[arg(long)]
+    #[serde(skip)]
+    pub metrics_file: Option<PathBuf>,
+
+    /// Export pipeline trace spans to an OTLP/HTTP collector (e.g. http://localhost:4318)
+    /// Defaults to OTEL_EXPORTER_OTLP_ENDPOINT when set
+    #[arg(long)]
+    #[serde(skip)]
+    pub otlp_endpoint: Option<String>,
+
+    /// Write NDJSON progress events (item_started, guardrail_blocked, item_completed,
+    /// batch_summary) in batch and serve modes; "-" writes to stdout
+    #[arg(long, value_name = "PATH", conflicts_with = "experiment")]
+    #[serde(skip)]
+    pub events_file: Option<PathBuf>,
+}
+
+fn default_response_format_schema_strict() -> bool {
+    true
+}
+
+impl Default for Args {
+    fn default() -> Self {
+        Self {
+            version: false,
+            json: false,
+            command: None,
+            config_file: None,
+            profile: None,
+            api_url: None,
+            model: None,
+            provider: None,
+            system_file: None,
+            system_text: None,
+            system_prompt_url: None,
+            user_file: None,
+            user_text: None,
+            pdf_file: None,
+            user_prompt_url: None,
+            image_file: None,
+            image_mode: None,
+            context_files: Vec::new(),
+            batch_file: None,
+            batch_concurrency: None,
+            summary_csv: None,
+            junit_xml: None,
+            tags: Vec::new(),
+            experiment: false,
+            sweep: false,
+            sweep_format: SweepFormat::Json,
+            compare_models: Vec::new(),
+            preset: None,
+            temperature: None,
+            top_p: None,
+            max_tokens: None,
+            seed: None,
+            validate_tokens: None,
+            context_limit: None,
+            context_warning_percent: None,
+            response_format: None,
+            response_format_schema: None,
+            response_format_schema_strict: true,
+            api_key: None,
+            api_key_name: None,
+            self_test: None,
+            log_content_policy: None,
+            max_response_bytes: None,
+            data_classification: None,
+            allowed_external_hosts: Vec::new(),
+            timeout_secs: None,
+            verbose: false,
+            quiet: false,
+            color: ColorChoice::Auto,
+            no_color: false,
+            pipe: false,
+            output: None,
+            output_mode: OutputMode::Replace,
+            output_format: OutputFormatArg::Json,
+            enable_input_validation: false,
+            input_guardrail: None,
+            guard_url: None,
+            guard_model: None,
+            guard_api_key_name: None,
+            max_input_length: None,
+            max_input_tokens: None,
+            override_token: None,
+            record: None,
+            replay: None,
+            debug_bundle: None,
+            confirm: false,
+            cache: false,
+            cache_dir: None,
+            cache_stochastic: false,
+            metrics_file: None,
+            otlp_endpoint: None,
+            events_file: None,
+        }
+    }
+}
+
+/// Merge config file, `SLLM_*` environment variables and CLI args using figment
+/// Priority: CLI args > Environment > Config file
+pub fn merge_config(args: &Args) -> Result<Args, CliError> {
+    // If no config file or variable is given, just return CLI args
+    let env = env_provider();
+    if args.config_file.is_none() && env.iter().next().is_none() {
+        return Ok(args.clone());
+    }
+
+    // Merge: config file < environment < CLI args (CLI has highest priority)
+    // The file is read like load_config_file(), with --profile applied
+    let file_values = match &args.config_file {
+        Some(config_path) => read_config_value(config_path, args.profile.as_deref())?,
+        None => Value::Object(Map::new()),
+    };
+    let file_provider = Figment::from(Serialized::defaults(file_values));
+
+    let merged: Args = file_provider
+        .merge(env)
+        .merge(Serialized::defaults(args))
+        .extract()
+        .map_err(|e| CliError::InvalidArguments(format!("Failed to merge config: {e}")))?;
+
+    // Restore CLI-only fields that shouldn't be in config files
+    //
+    // ⚠️ CRITICAL CHECKLIST: When adding new #[serde(skip)] fields to Args,
+    // you MUST add them to this restoration list below.
+    //
+    // Current CLI-only fields (39 total):
+    // 0. command - Maintenance subcommand
+    // 1. config_file - Path to config file itself
+    // 2. verbose - CLI logging flag
+    // 3. quiet - CLI logging flag
+    // 4. output - Output file path
+    // 5. enable_input_validation - Input guardrails flag
+    // 6. max_input_length - Input size limit
+    // 7. max_input_tokens - Input token limit
+    // 8. override_token - Guardrail override secret (never read from config files)
+    // 9. record - Cassette recording path
+    // 10. replay - Cassette replay path
+    // 11. input_guardrail - LLM input guard selection
+    // 12. guard_url - LLM input guard endpoint
+    // 13. guard_model - LLM input guard model
+    // 14. guard_api_key_name - LLM input guard API key variable
+    // 15. batch_file - Batch prompts file
+    // 16. batch_concurrency - Batch parallelism
+    // 17. cache - Response cache switch
+    // 18. cache_dir - Response cache directory
+    // 19. experiment - Prompt variant experiment mode
+    // 20. metrics_file - Prometheus metrics output path
+    // 21. otlp_endpoint - Trace export endpoint
+    // 22. sweep - Sampling parameter sweep mode
+    // 23. sweep_format - Sweep report format
+    // 24. summary_csv - Batch summary CSV path
+    // 25. events_file - Progress event stream path
+    // 26. output_mode - Replace or append to --output
+    // 27. color - Log color choice
+    // 28. no_color - Disable log colors
+    // 29. pipe - Pipeline-safe stdout
+    // 30. version - Print version
+    // 31. json - Version as JSON
+    // 32. cache_stochastic - Cache stochastic requests too
+    // 33. compare_models - Multi-model comparison mode
+    // 34. profile - Config file profile selection
+    // 35. debug_bundle - Debug bundle directory
+    // 36. confirm - Interactive outbound request confirmation
+    // 37. output_format - JSON, YAML, markdown or raw response text
+    // 38. junit_xml - Batch JUnit XML report path
+    Ok(Args {
+        command: args.command.clone(),
+        config_file: args.config_file.clone(),
+        profile: args.profile.clone(),
+        verbose: args.verbose,
+        quiet: args.quiet,
+        output: args.output.clone(),
+        enable_input_validation: args.enable_input_validation,
+        max_input_length: args.max_input_length,
+        max_input_tokens: args.max_input_tokens,
+        override_token: args.override_token.clone(),
+        record: args.record.clone(),
+        replay: args.replay.clone(),
+        debug_bundle: args.debug_bundle.clone(),
+        confirm: args.confirm,
+        input_guardrail: args.input_guardrail,
+        guard_url: args.guard_url.clone(),
+        guard_model: args.guard_model.clone(),
+        guard_api_key_name: args.guard_api_key_name.clone(),
+        batch_file: args.batch_file.clone(),
+        batch_concurrency: args.batch_concurrency,
+        cache: args.cache,
+        cache_dir: args.cache_dir.clone(),
+        cache_stochastic: args.cache_stochastic,
+        compare_models: args.compare_models.clone(),
+        experiment: args.experiment,
+        metrics_file: args.metrics_file.clone(),
+        otlp_endpoint: args.otlp_endpoint.clone(),
+        sweep: args.sweep,
+        sweep_format: args.sweep_format,
+        summary_csv: args.summary_csv.clone(),
+        junit_xml: args.junit_xml.clone(),
+        events_file: args.events_file.clone(),
+        output_mode: args.output_mode,
+        output_format: args.output_format,
+        color: args.color,
+        no_color: args.no_color,
+        pipe: args.pipe,
+        version: args.version,
+        json: args.json,
+        ..merged
+    })
+}
+
+#[derive(Debug, Clone, Copy, clap::ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ProviderArg {
+    Ollama,
+    #[value(name = "openai")]
+    #[serde(rename = "openai")]
+    OpenAI,
+    Anthropic,
+    Bedrock,
+    Mock,
+}
+
+#[derive(Debug, Clone, Copy, clap::ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ImageModeArg {
+    Ocr,
+    Vision,
+}
+
+impl From<ImageModeArg> for ImageMode {
+    fn from(arg: ImageModeArg) -> Self {
+        match arg {
+            ImageModeArg::Ocr => ImageMode::Ocr,
+            ImageModeArg::Vision => ImageMode::Vision,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, clap::ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PresetArg {
+    Deterministic,
+    Balanced,
+    Creative,
+}
+
+impl From<PresetArg> for ParameterPreset {
+    fn from(arg: PresetArg) -> Self {
+        match arg {
+            PresetArg::Deterministic => ParameterPreset::Deterministic,
+            PresetArg::Balanced => ParameterPreset::Balanced,
+            PresetArg::Creative => ParameterPreset::Creative,
+        }
+    }
+}
+
+impl From<ProviderArg> for Provider {
+    fn from(arg: ProviderArg) -> Self {
+        match arg {
+            ProviderArg::Ollama => Provider::Ollama,
+            ProviderArg::OpenAI => Provider::OpenAI,
+            ProviderArg::Anthropic => Provider::Anthropic,
+            ProviderArg::Bedrock => Provider::Bedrock,
+            ProviderArg::Mock => Provider::Mock,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, clap::ValueEnum)]
+pub enum SweepFormat {
+    #[default]
+    Json,
+    Csv,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, clap::ValueEnum)]
+pub enum ColorChoice {
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, clap::ValueEnum)]
+pub enum OutputMode {
+    #[default]
+    Replace,
+    AppendJsonl,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, clap::ValueEnum)]
+pub enum OutputFormatArg {
+    #[default]
+    Json,
+    Yaml,
+    Markdown,
+    Raw,
+}
+
+impl From<OutputFormatArg> for OutputFormat {
+    fn from(arg: OutputFormatArg) -> Self {
+        match arg {
+            OutputFormatArg::Json => OutputFormat::Json,
+            OutputFormatArg::Yaml => OutputFormat::Yaml,
+            OutputFormatArg::Markdown => OutputFormat::Markdown,
+            OutputFormatArg::Raw => OutputFormat::Raw,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, clap::ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ResponseFormatArg {
+    #[value(name = "json-object")]
+    JsonObject,
+    #[value(name = "json-schema")]
+    JsonSchema,
+    Text,
+}
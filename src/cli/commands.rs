@@ -0,0 +1,299 @@
+//! Subcommands
+//!
+//! Maintenance subcommands run instead of an evaluation and print a report
+//! (JSON unless a format option says otherwise) to stdout. `serve`,
+//! `validate-config` and `guardrails` use the evaluation options and are run
+//! by main.
+
+use super::{
+    args::ProviderArg,
+    validators::{validate_file_exists, validate_positive_u32, validate_positive_u64},
+};
+use crate::{
+    audit::default_blob_dir, compact_audit_log, discover_models, BlobStore, CliError,
+    ContextProber, GuardrailStage, JsonlAuditLog, PromptLibrary, TrendStatsConfig, TrendStore,
+};
+use clap::Subcommand;
+use std::{net::SocketAddr, path::PathBuf};
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum Command {
+    /// Audit log maintenance
+    #[command(subcommand)]
+    Audit(AuditCommand),
+
+    /// Compare recent refusal/violation rates and quality scores against the
+    /// preceding days, per configuration fingerprint
+    Trends {
+        /// Trend store directory ([trend_stats].dir)
+        #[arg(long, value_parser = validate_file_exists)]
+        dir: PathBuf,
+
+        /// Report only this configuration fingerprint
+        #[arg(long)]
+        fingerprint: Option<String>,
+
+        /// Report window in days, ending today
+        #[arg(long, default_value_t = 28, value_parser = validate_positive_u32)]
+        days: u32,
+
+        /// Most recent days compared against the rest of the window
+        #[arg(long, default_value_t = 7, value_parser = validate_positive_u32)]
+        recent_days: u32,
+    },
+
+    /// List the models an endpoint serves (Ollama /api/tags, OpenAI-compatible
+    /// /v1/models, Anthropic /v1/models) with their context windows from the
+    /// model registry
+    Models {
+        /// Endpoint URL, as given to --api-url
+        #[arg(long)]
+        api_url: String,
+
+        /// Force specific provider format (overrides auto-detection)
+        #[arg(long, value_enum)]
+        provider: Option<ProviderArg>,
+
+        /// Environment variable name containing the API key
+        #[arg(long)]
+        api_key_name: Option<String>,
+
+        /// Request timeout in seconds
+        #[arg(long = "timeout", default_value_t = 30, value_parser = validate_positive_u64)]
+        timeout_secs: u64,
+
+        /// Ask the provider for the context window of models missing from the
+        /// registry (one request per model)
+        #[arg(long)]
+        probe: bool,
+
+        /// Report format
+        #[arg(long, value_enum, default_value = "table")]
+        format: ModelsFormat,
+    },
+
+    /// Prompt library maintenance (a directory of prompt files with TOML
+    /// front matter: name, version, variables, models)
+    #[command(subcommand)]
+    Prompts(PromptsCommand),
+
+    /// Serve POST /evaluate, GET /health and GET /metrics over HTTP, using the
+    /// other options (config file, guardrails, ...) as the server configuration
+    Serve {
+        /// Address to listen on (use 0.0.0.0:<port> inside a container)
+        #[arg(long, default_value = "127.0.0.1:8080")]
+        listen: SocketAddr,
+    },
+
+    /// Load and validate the configuration given by the other options (config
+    /// file, profile, SLLM_* variables) without calling any endpoint, and print
+    /// it resolved; exits non-zero if it has problems
+    ValidateConfig,
+
+    /// Guardrail tuning
+    #[command(subcommand)]
+    Guardrails(GuardrailsCommand),
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum AuditCommand {
+    /// Rewrite the audit log and delete payload blobs no event references
+    /// Run while no evaluation is writing to the same log
+    Compact {
+        /// JSONL audit log ([prompt_audit].log)
+        #[arg(long, value_parser = validate_file_exists)]
+        log: PathBuf,
+
+        /// Blob directory (default: log path with a .blobs extension)
+        #[arg(long)]
+        blob_dir: Option<PathBuf>,
+
+        /// Drop events older than this many days before collecting blobs
+        #[arg(long, value_parser = validate_positive_u32)]
+        retain_days: Option<u32>,
+    },
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum PromptsCommand {
+    /// List the prompts (metadata, file and text hash), by name and version
+    List {
+        /// Prompt directory
+        #[arg(long, value_parser = validate_file_exists)]
+        dir: PathBuf,
+    },
+
+    /// Print a prompt with its metadata and text
+    Show {
+        /// Prompt name
+        name: String,
+
+        /// Version to show (default: latest)
+        #[arg(long)]
+        version: Option<String>,
+
+        /// Prompt directory
+        #[arg(long, value_parser = validate_file_exists)]
+        dir: PathBuf,
+    },
+
+    /// Check every prompt file (front matter, duplicate versions, undeclared
+    /// or unused variables); exits non-zero on errors
+    Lint {
+        /// Prompt directory
+        #[arg(long, value_parser = validate_file_exists)]
+        dir: PathBuf,
+    },
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum GuardrailsCommand {
+    /// Run the configured input or output guardrail (config file,
+    /// --input-guardrail, --enable-input-validation, ...) against text from
+    /// --file or stdin without calling the LLM, and print the full result
+    Test {
+        /// Guardrail to run
+        #[arg(long, value_enum, default_value = "input")]
+        direction: GuardrailDirection,
+
+        /// Text to check (default: stdin)
+        #[arg(long, value_parser = validate_file_exists)]
+        file: Option<PathBuf>,
+
+        /// Prompt the checked response answers (output checks only)
+        #[arg(long)]
+        prompt: Option<String>,
+    },
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, clap::ValueEnum)]
+pub enum GuardrailDirection {
+    #[default]
+    Input,
+    Output,
+}
+
+impl From<GuardrailDirection> for GuardrailStage {
+    fn from(direction: GuardrailDirection) -> Self {
+        match direction {
+            GuardrailDirection::Input => GuardrailStage::Input,
+            GuardrailDirection::Output => GuardrailStage::Output,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, clap::ValueEnum)]
+pub enum ModelsFormat {
+    #[default]
+    Table,
+    Json,
+}
+
+/// Run a subcommand, returning its report (pretty-printed JSON by default)
+pub async fn run_command(command: Command) -> Result<String, CliError> {
+    let report = match command {
+        Command::Audit(AuditCommand::Compact {
+            log,
+            blob_dir,
+            retain_days,
+        }) => {
+            let blobs = BlobStore::new(blob_dir.unwrap_or_else(|| default_blob_dir(&log)));
+            let retain_since = retain_days
+                .map(|days| chrono::Utc::now() - chrono::Duration::days(i64::from(days)));
+            let report = compact_audit_log(&JsonlAuditLog::new(log), &blobs, retain_since)?;
+            serde_json::to_value(report)
+        }
+        Command::Trends {
+            dir,
+            fingerprint,
+            days,
+            recent_days,
+        } => {
+            let store = TrendStore::from_config(&TrendStatsConfig { dir });
+            let fingerprints = match fingerprint {
+                Some(fingerprint) => vec![fingerprint],
+                None => store.fingerprints()?,
+            };
+            let reports = fingerprints
+                .iter()
+                .map(|fp| store.report(fp, days, recent_days))
+                .collect::<Result<Vec<_>, _>>()?;
+            serde_json::to_value(serde_json::json!({
+                "window_days": days,
+                "recent_days": recent_days,
+                "configs": reports,
+            }))
+        }
+        Command::Models {
+            api_url,
+            provider,
+            api_key_name,
+            timeout_secs,
+            probe,
+            format,
+        } => {
+            let api_key = match api_key_name {
+                Some(name) => Some(std::env::var(&name).map_err(|_| {
+                    CliError::InvalidArguments(format!(
+                        "Environment variable '{name}' specified by --api-key-name does not exist"
+                    ))
+                })?),
+                None => None,
+            };
+            let prober = probe.then(ContextProber::new);
+            let listing = discover_models(
+                &api_url,
+                provider.map(Into::into),
+                api_key.as_deref(),
+                timeout_secs,
+                prober.as_ref(),
+            )
+            .await?;
+            if format == ModelsFormat::Table {
+                return Ok(listing.to_table().trim_end().to_string());
+            }
+            serde_json::to_value(listing)
+        }
+        Command::Prompts(PromptsCommand::List { dir }) => {
+            let prompts = PromptLibrary::new(dir).list()?;
+            serde_json::to_value(prompts).map(|mut value| {
+                // The list shows metadata only; `prompts show` prints the text
+                for prompt in value.as_array_mut().into_iter().flatten() {
+                    if let Some(prompt) = prompt.as_object_mut() {
+                        prompt.remove("text");
+                    }
+                }
+                value
+            })
+        }
+        Command::Prompts(PromptsCommand::Show { name, version, dir }) => {
+            serde_json::to_value(PromptLibrary::new(dir).get(&name, version.as_deref())?)
+        }
+        Command::Prompts(PromptsCommand::Lint { dir }) => {
+            let report = PromptLibrary::new(dir).lint()?;
+            if report.errors > 0 {
+                let summary = report
+                    .issues
+                    .iter()
+                    .filter(|issue| issue.level == "error")
+                    .map(|issue| format!("{}: {}", issue.file.display(), issue.message))
+                    .collect::<Vec<_>>()
+                    .join("; ");
+                return Err(CliError::InvalidArguments(format!(
+                    "{} prompt problem(s): {summary}",
+                    report.errors
+                )));
+            }
+            serde_json::to_value(report)
+        }
+        Command::Serve { .. } | Command::ValidateConfig | Command::Guardrails(_) => {
+            return Err(CliError::InvalidArguments(
+                "serve, validate-config and guardrails are not maintenance commands".to_string(),
+            ))
+        }
+    };
+
+    report
+        .and_then(|value| serde_json::to_string_pretty(&value))
+        .map_err(|e| CliError::InvalidArguments(format!("Failed to serialize report: {e}")))
+}
@@ -1,13 +1,75 @@
-use fortified_llm_client::{
-    guardrails::config::RegexGuardrailConfig, ConfigFileRequest, GuardrailProviderConfig, Severity,
+use crate::{
+    guardrails::config::{RegexAction, RegexGuardrailConfig},
+    ConfigFileRequest, GuardrailProviderConfig, LlamaGuardCategory, LlamaGuardConfig,
+    LlamaPromptGuardConfig, Severity,
 };
 
+/// LLM-based input guardrail selectable with `--input-guardrail`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum InputGuardrailArg {
+    /// Llama Guard 3 (all MLCommons categories)
+    #[value(name = "llama_guard")]
+    LlamaGuard,
+    /// Llama Prompt Guard 2 (prompt injection detection)
+    #[value(name = "llama_prompt_guard", alias = "prompt_guard")]
+    LlamaPromptGuard,
+}
+
+/// Single-provider input guardrail given on the command line
+///
+/// Unset fields fall back to the provider defaults (local Ollama endpoint).
+#[derive(Debug, Clone, PartialEq)]
+pub struct CliGuardrail {
+    pub kind: InputGuardrailArg,
+    pub api_url: Option<String>,
+    pub model: Option<String>,
+    pub api_key_name: Option<String>,
+}
+
+impl CliGuardrail {
+    fn to_provider_config(&self) -> GuardrailProviderConfig {
+        match self.kind {
+            InputGuardrailArg::LlamaGuard => {
+                let defaults = LlamaGuardConfig::default();
+                GuardrailProviderConfig::LlamaGuard {
+                    api_url: self.api_url.clone().unwrap_or(defaults.api_url),
+                    model: self.model.clone().unwrap_or(defaults.model),
+                    timeout_secs: defaults.timeout_secs,
+                    enabled_categories: LlamaGuardCategory::all(),
+                    custom_categories: Vec::new(),
+                    api_key: None,
+                    api_key_name: self.api_key_name.clone(),
+                    api_key_secret: None,
+                    resolved_api_key: None,
+                }
+            }
+            InputGuardrailArg::LlamaPromptGuard => {
+                let defaults = LlamaPromptGuardConfig::default();
+                GuardrailProviderConfig::LlamaPromptGuard {
+                    api_url: self.api_url.clone().unwrap_or(defaults.api_url),
+                    model: self.model.clone().unwrap_or(defaults.model),
+                    timeout_secs: defaults.timeout_secs,
+                    threshold: defaults.threshold,
+                    api_key: None,
+                    api_key_name: self.api_key_name.clone(),
+                    api_key_secret: None,
+                    resolved_api_key: None,
+                    max_scan_bytes: defaults.max_scan_bytes,
+                    window_bytes: defaults.window_bytes,
+                    window_overlap_bytes: defaults.window_overlap_bytes,
+                }
+            }
+        }
+    }
+}
+
 /// Configure input guardrails from CLI args or config file
 ///
-/// Priority: CLI args > config file
+/// Priority: CLI guardrail > CLI regex validation > config file
 ///
 /// # Arguments
 ///
+/// * `cli_guardrail` - Optional LLM guardrail selected via `--input-guardrail`
 /// * `enable_validation` - Whether CLI-based validation is enabled
 /// * `max_input_length` - Optional max input length from CLI
 /// * `file_config` - Optional config file data
@@ -17,27 +79,31 @@ use fortified_llm_client::{
 /// - `Some(GuardrailProviderConfig)` if guardrails are configured
 /// - `None` if no guardrails configured
 pub fn configure_guardrails(
+    cli_guardrail: Option<&CliGuardrail>,
     enable_validation: bool,
     max_input_length: Option<usize>,
     file_config: Option<&ConfigFileRequest>,
 ) -> Option<GuardrailProviderConfig> {
-    if enable_validation {
+    if let Some(guardrail) = cli_guardrail {
+        log::debug!("Input guardrail {:?} selected via CLI", guardrail.kind);
+        Some(guardrail.to_provider_config())
+    } else if enable_validation {
         // CLI-based input validation
         log::debug!("Input validation enabled via CLI");
         Some(GuardrailProviderConfig::Regex(RegexGuardrailConfig {
             max_length_bytes: max_input_length
-                .unwrap_or(fortified_llm_client::constants::input_limits::MAX_INPUT_BYTES),
+                .unwrap_or(crate::constants::input_limits::MAX_INPUT_BYTES),
             patterns_file: None,
             severity_threshold: Severity::Medium,
+            builtin_patterns: Vec::new(),
+            action: RegexAction::Block,
+            preview_chars: None,
         }))
     } else if let Some(guardrail_cfg) = file_config.and_then(|c| c.guardrails.as_ref()) {
         // Config file-based guardrails (supports all provider types)
         log::debug!("Input validation enabled via config file");
-        // Use input field, or fallback to flattened provider field
-        guardrail_cfg
-            .input
-            .clone()
-            .or_else(|| guardrail_cfg.provider.clone())
+        // Use input field, then flattened provider field, then preset
+        guardrail_cfg.input_config()
     } else {
         None
     }
@@ -49,7 +115,7 @@ mod tests {
 
     #[test]
     fn test_configure_guardrails_cli_enabled() {
-        let config = configure_guardrails(true, None, None);
+        let config = configure_guardrails(None, true, None, None);
         assert!(config.is_some());
         match config.unwrap() {
             GuardrailProviderConfig::Regex(regex_config) => {
@@ -62,7 +128,7 @@ mod tests {
 
     #[test]
     fn test_configure_guardrails_cli_with_custom_limits() {
-        let config = configure_guardrails(true, Some(500_000), None);
+        let config = configure_guardrails(None, true, Some(500_000), None);
         assert!(config.is_some());
         match config.unwrap() {
             GuardrailProviderConfig::Regex(regex_config) => {
@@ -74,7 +140,53 @@ mod tests {
 
     #[test]
     fn test_configure_guardrails_disabled() {
-        let config = configure_guardrails(false, None, None);
+        let config = configure_guardrails(None, false, None, None);
         assert!(config.is_none());
     }
+
+    #[test]
+    fn test_configure_guardrails_cli_llm_guard() {
+        let guardrail = CliGuardrail {
+            kind: InputGuardrailArg::LlamaGuard,
+            api_url: Some("https://guard.example.com/v1/chat/completions".to_string()),
+            model: None,
+            api_key_name: Some("GUARD_KEY".to_string()),
+        };
+        match configure_guardrails(Some(&guardrail), false, None, None) {
+            Some(GuardrailProviderConfig::LlamaGuard {
+                api_url,
+                model,
+                api_key_name,
+                ..
+            }) => {
+                assert_eq!(api_url, "https://guard.example.com/v1/chat/completions");
+                assert_eq!(model, LlamaGuardConfig::default().model);
+                assert_eq!(api_key_name.as_deref(), Some("GUARD_KEY"));
+            }
+            other => panic!("Expected LlamaGuard variant, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_configure_guardrails_cli_prompt_guard_defaults() {
+        let guardrail = CliGuardrail {
+            kind: InputGuardrailArg::LlamaPromptGuard,
+            api_url: None,
+            model: Some("llama-prompt-guard-2-86m".to_string()),
+            api_key_name: None,
+        };
+        match configure_guardrails(Some(&guardrail), false, None, None) {
+            Some(GuardrailProviderConfig::LlamaPromptGuard {
+                api_url,
+                model,
+                threshold,
+                ..
+            }) => {
+                assert_eq!(api_url, LlamaPromptGuardConfig::default().api_url);
+                assert_eq!(model, "llama-prompt-guard-2-86m");
+                assert_eq!(threshold, 0.5);
+            }
+            other => panic!("Expected LlamaPromptGuard variant, got {other:?}"),
+        }
+    }
 }
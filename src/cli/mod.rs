@@ -1,13 +1,38 @@
+//! Command-line interface
+//!
+//! Argument parsing, config file merging and output writing used by the
+//! binary. [`run_from_args`] runs the same argument surface in-process.
+
+mod args;
+mod commands;
 mod guardrail_config;
+mod output_template;
 mod output_writer;
 mod prompt_loader;
+mod run;
 mod validators;
 
 // Re-export public items
-pub use guardrail_config::configure_guardrails;
-pub use output_writer::write_output;
-pub use prompt_loader::load_prompt;
+pub use args::{
+    merge_config, Args, ColorChoice, ImageModeArg, OutputFormatArg, OutputMode, PresetArg,
+    ProviderArg, ResponseFormatArg, SweepFormat,
+};
+pub use commands::{
+    run_command, AuditCommand, Command, GuardrailDirection, GuardrailsCommand, ModelsFormat,
+    PromptsCommand,
+};
+pub use guardrail_config::{configure_guardrails, CliGuardrail, InputGuardrailArg};
+pub use output_template::{OutputTemplate, TemplateVars};
+pub use output_writer::{
+    append_batch_output, append_jsonl, write_batch_files, write_batch_output, write_metrics_file,
+    write_output, write_rendered, write_text,
+};
+pub use prompt_loader::{load_prompt, read_stdin_prompt};
+pub use run::{
+    error_output, output_template, run, run_from_args, write_single_output, RunResult,
+    DEFAULT_BATCH_CONCURRENCY,
+};
 pub use validators::{
     validate_byte_size, validate_context_limit, validate_file_exists, validate_positive_u32,
-    validate_positive_u64, validate_positive_usize, validate_temperature,
+    validate_positive_u64, validate_positive_usize, validate_temperature, validate_top_p,
 };
@@ -0,0 +1,266 @@
+use crate::{CliError, CliOutput};
+use chrono::{DateTime, Utc};
+use sha2::{Digest, Sha256};
+use std::{
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+/// Placeholders accepted in `--output` templates
+const PLACEHOLDERS: &[&str] = &[
+    "timestamp",
+    "date",
+    "model",
+    "status",
+    "evaluation_id",
+    "index",
+    "id",
+];
+
+/// Placeholders that give each batch item its own file
+const PER_ITEM: &[&str] = &["index", "id"];
+
+/// Evaluation IDs generated by this process (makes IDs unique within a run)
+static EVALUATIONS: AtomicU64 = AtomicU64::new(0);
+
+/// Output path template with `{placeholder}` substitution
+///
+/// A path without placeholders renders to itself. Substituted values are
+/// sanitized to `[A-Za-z0-9._-]` so they can't add directories or escape
+/// the template's directory (`meta-llama/llama-3` becomes `meta-llama_llama-3`).
+#[derive(Debug, Clone)]
+pub struct OutputTemplate {
+    template: String,
+    placeholders: Vec<String>,
+}
+
+impl OutputTemplate {
+    /// Parse a template, rejecting unknown or unterminated placeholders
+    pub fn parse(path: &Path) -> Result<Self, CliError> {
+        let template = path.to_string_lossy().into_owned();
+        let mut placeholders = Vec::new();
+        let mut rest = template.as_str();
+        while let Some(start) = rest.find('{') {
+            let Some(len) = rest[start..].find('}') else {
+                return Err(invalid(&template, "unterminated '{'"));
+            };
+            let name = &rest[start + 1..start + len];
+            if !PLACEHOLDERS.contains(&name) {
+                return Err(invalid(
+                    &template,
+                    &format!(
+                        "unknown placeholder '{{{name}}}' (expected one of: {})",
+                        PLACEHOLDERS.join(", ")
+                    ),
+                ));
+            }
+            placeholders.push(name.to_string());
+            rest = &rest[start + len + 1..];
+        }
+        if rest.contains('}') {
+            return Err(invalid(&template, "unmatched '}'"));
+        }
+        Ok(Self {
+            template,
+            placeholders,
+        })
+    }
+
+    /// Whether `{index}` or `{id}` is used (one file per batch item)
+    pub fn is_per_item(&self) -> bool {
+        self.placeholders
+            .iter()
+            .any(|p| PER_ITEM.contains(&p.as_str()))
+    }
+
+    /// Reject placeholders that have no value in the current mode
+    pub fn allow_only(&self, allowed: &[&str], mode: &str) -> Result<(), CliError> {
+        match self
+            .placeholders
+            .iter()
+            .find(|p| !allowed.contains(&p.as_str()))
+        {
+            Some(name) => Err(invalid(
+                &self.template,
+                &format!("'{{{name}}}' is not available {mode}"),
+            )),
+            None => Ok(()),
+        }
+    }
+
+    pub fn render(&self, vars: &TemplateVars) -> PathBuf {
+        let mut path = self.template.clone();
+        for name in &self.placeholders {
+            let value = match name.as_str() {
+                "timestamp" => vars.timestamp.format("%Y%m%dT%H%M%SZ").to_string(),
+                "date" => vars.timestamp.format("%Y-%m-%d").to_string(),
+                "model" => vars.model.clone().unwrap_or_else(|| "unknown".to_string()),
+                "status" => vars.status.clone().unwrap_or_else(|| "unknown".to_string()),
+                "evaluation_id" => vars.evaluation_id.clone(),
+                "index" => vars.index.map(|i| i.to_string()).unwrap_or_default(),
+                // Items without an id fall back to their position
+                "id" => vars
+                    .id
+                    .clone()
+                    .or_else(|| vars.index.map(|i| i.to_string()))
+                    .unwrap_or_default(),
+                _ => unreachable!("placeholders are checked by parse()"),
+            };
+            path = path.replacen(&format!("{{{name}}}"), &sanitize(&value), 1);
+        }
+        PathBuf::from(path)
+    }
+}
+
+/// Values substituted into an [`OutputTemplate`]
+#[derive(Debug, Clone)]
+pub struct TemplateVars {
+    pub timestamp: DateTime<Utc>,
+    pub model: Option<String>,
+    pub status: Option<String>,
+    pub evaluation_id: String,
+    pub index: Option<usize>,
+    pub id: Option<String>,
+}
+
+impl TemplateVars {
+    /// Values for a run report (no model, status or item)
+    pub fn now() -> Self {
+        let timestamp = Utc::now();
+        Self {
+            evaluation_id: evaluation_id(&timestamp),
+            timestamp,
+            model: None,
+            status: None,
+            index: None,
+            id: None,
+        }
+    }
+
+    /// Values for one evaluation output (timestamp and model from its metadata)
+    pub fn for_output(output: &CliOutput) -> Self {
+        let timestamp = DateTime::parse_from_rfc3339(&output.metadata.timestamp)
+            .map(|t| t.with_timezone(&Utc))
+            .unwrap_or_else(|_| Utc::now());
+        Self {
+            evaluation_id: evaluation_id(&timestamp),
+            timestamp,
+            model: Some(output.metadata.model.clone()),
+            status: Some(output.status.clone()),
+            index: None,
+            id: None,
+        }
+    }
+
+    pub fn item(mut self, index: usize, id: Option<&str>) -> Self {
+        self.index = Some(index);
+        self.id = id.map(str::to_string);
+        self
+    }
+}
+
+/// 16 hex characters, unique per evaluation across runs
+fn evaluation_id(timestamp: &DateTime<Utc>) -> String {
+    let sequence = EVALUATIONS.fetch_add(1, Ordering::Relaxed);
+    let digest = Sha256::digest(format!(
+        "{}:{}:{sequence}",
+        timestamp.timestamp_nanos_opt().unwrap_or_default(),
+        std::process::id()
+    ));
+    digest[..8].iter().map(|b| format!("{b:02x}")).collect()
+}
+
+/// Keep `[A-Za-z0-9._-]`, replace everything else with `_`; dot-only values
+/// (`.`, `..`) become `_`
+fn sanitize(value: &str) -> String {
+    let clean: String = value
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    if clean.chars().all(|c| c == '.') {
+        "_".to_string()
+    } else {
+        clean
+    }
+}
+
+fn invalid(template: &str, reason: &str) -> CliError {
+    CliError::InvalidArguments(format!("Invalid --output template '{template}': {reason}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vars() -> TemplateVars {
+        TemplateVars {
+            timestamp: DateTime::parse_from_rfc3339("2026-03-04T05:06:07Z")
+                .unwrap()
+                .with_timezone(&Utc),
+            model: Some("meta-llama/llama-3".to_string()),
+            status: Some("success".to_string()),
+            evaluation_id: "0123456789abcdef".to_string(),
+            index: None,
+            id: None,
+        }
+    }
+
+    #[test]
+    fn test_render_substitutes_sanitized_values() {
+        let template = OutputTemplate::parse(Path::new(
+            "results/{date}/{timestamp}-{model}-{evaluation_id}.json",
+        ))
+        .unwrap();
+        assert!(!template.is_per_item());
+        assert_eq!(
+            template.render(&vars()),
+            PathBuf::from(
+                "results/2026-03-04/20260304T050607Z-meta-llama_llama-3-0123456789abcdef.json"
+            )
+        );
+
+        let template = OutputTemplate::parse(Path::new("out/{id}.json")).unwrap();
+        assert!(template.is_per_item());
+        assert_eq!(
+            template.render(&vars().item(2, Some("../../etc/passwd"))),
+            PathBuf::from("out/.._.._etc_passwd.json")
+        );
+        assert_eq!(
+            template.render(&vars().item(2, Some(".."))),
+            PathBuf::from("out/_.json")
+        );
+        assert_eq!(
+            template.render(&vars().item(2, None)),
+            PathBuf::from("out/2.json")
+        );
+
+        let plain = OutputTemplate::parse(Path::new("output.json")).unwrap();
+        assert_eq!(plain.render(&vars()), PathBuf::from("output.json"));
+    }
+
+    #[test]
+    fn test_parse_rejects_invalid_templates() {
+        for (template, reason) in [
+            ("out/{user}.json", "unknown placeholder '{user}'"),
+            ("out/{model.json", "unterminated"),
+            ("out/model}.json", "unmatched"),
+        ] {
+            let err = OutputTemplate::parse(Path::new(template)).unwrap_err();
+            assert!(err.to_string().contains(reason), "{err}");
+        }
+
+        let template = OutputTemplate::parse(Path::new("{model}-{status}.json")).unwrap();
+        let err = template
+            .allow_only(&["timestamp", "model"], "for batch files")
+            .unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("'{status}' is not available for batch files"));
+    }
+}
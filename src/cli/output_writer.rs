@@ -1,8 +1,15 @@
-use fortified_llm_client::CliOutput;
-use std::{fs, io::Write, path::PathBuf};
+use super::output_template::{OutputTemplate, TemplateVars};
+use crate::{BatchItem, CliOutput, MetricsRegistry, OutputFormat};
+use serde::Serialize;
+use std::{
+    collections::HashSet,
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
 use tempfile::NamedTempFile;
 
-/// Write CLI output to stdout or file with atomic writes
+/// Write CLI output (or another JSON report) to stdout or file with atomic writes
 ///
 /// Uses atomic writes for file output (temp file + rename) to prevent
 /// partial/corrupted files. Automatically creates parent directories.
@@ -29,8 +36,8 @@ use tempfile::NamedTempFile;
 /// // Write to file
 /// write_output(&output, Some(&PathBuf::from("output.json")))?;
 /// ```
-pub fn write_output(
-    output: &CliOutput,
+pub fn write_output<T: Serialize + ?Sized>(
+    output: &T,
     output_path: Option<&PathBuf>,
 ) -> Result<(), std::io::Error> {
     // Serialize to pretty JSON
@@ -38,38 +45,218 @@ pub fn write_output(
         .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
 
     match output_path {
-        Some(path) => {
-            // Create parent directories if they don't exist
-            if let Some(parent) = path.parent() {
-                fs::create_dir_all(parent)?;
-            }
+        Some(path) => write_atomic(path, &format!("{json}\n"))?,
+        None => print_stdout(&format!("{json}\n"))?,
+    }
 
-            // Atomic write: write to temp file in same directory, then rename
-            let temp_dir = path.parent().unwrap_or_else(|| std::path::Path::new("."));
-            let mut temp_file = NamedTempFile::new_in(temp_dir)?;
+    Ok(())
+}
 
-            temp_file.write_all(json.as_bytes())?;
-            temp_file.write_all(b"\n")?; // Add trailing newline
-            temp_file.flush()?;
+/// One batch result line: the item's position and id, then the usual output fields
+#[derive(Serialize)]
+struct BatchLine<'a> {
+    index: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    id: Option<&'a str>,
+    #[serde(flatten)]
+    output: &'a CliOutput,
+}
+
+/// Write batch results as JSON Lines (one compact object per item, input order)
+///
+/// File output uses the same atomic write as [`write_output`].
+pub fn write_batch_output(
+    items: &[BatchItem],
+    results: &[CliOutput],
+    output_path: Option<&PathBuf>,
+) -> Result<(), std::io::Error> {
+    let mut jsonl = String::new();
+    for (index, (item, output)) in items.iter().zip(results).enumerate() {
+        let line = BatchLine {
+            index,
+            id: item.id.as_deref(),
+            output,
+        };
+        let json = serde_json::to_string(&line)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+        jsonl.push_str(&json);
+        jsonl.push('\n');
+    }
 
-            // Atomically rename temp file to final path
-            temp_file.persist(path)?;
+    match output_path {
+        Some(path) => write_atomic(path, &jsonl),
+        None => print_stdout(&jsonl),
+    }
+}
 
-            log::info!("Output written to: {}", path.display());
+/// Write each batch result to its own file, named by a per-item template
+///
+/// Each file holds one pretty-printed result line (index, id and output
+/// fields), written atomically like [`write_output`].
+pub fn write_batch_files(
+    items: &[BatchItem],
+    results: &[CliOutput],
+    template: &OutputTemplate,
+) -> Result<(), std::io::Error> {
+    let mut written = HashSet::new();
+    for (index, (item, output)) in items.iter().zip(results).enumerate() {
+        let path =
+            template.render(&TemplateVars::for_output(output).item(index, item.id.as_deref()));
+        // Duplicate ids would silently overwrite earlier results
+        if !written.insert(path.clone()) {
+            return Err(std::io::Error::other(format!(
+                "batch item {index} renders to already written file {}",
+                path.display()
+            )));
         }
-        None => {
-            // Print to stdout
-            println!("{json}");
+        let line = BatchLine {
+            index,
+            id: item.id.as_deref(),
+            output,
+        };
+        write_output(&line, Some(&path))?;
+    }
+    Ok(())
+}
+
+/// Append one compact JSON line to a file (see [`append_lines`])
+pub fn append_jsonl<T: Serialize + ?Sized>(output: &T, path: &Path) -> Result<(), std::io::Error> {
+    let json = serde_json::to_string(output)
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+    append_lines(path, &format!("{json}\n"))
+}
+
+/// Append batch result lines (same fields as [`write_batch_output`])
+///
+/// A per-item template routes each line to its own file; otherwise all lines
+/// are appended to one file in a single locked write.
+pub fn append_batch_output(
+    items: &[BatchItem],
+    results: &[CliOutput],
+    template: &OutputTemplate,
+) -> Result<(), std::io::Error> {
+    let shared = TemplateVars {
+        model: results.first().map(|o| o.metadata.model.clone()),
+        ..TemplateVars::now()
+    };
+    let mut files: Vec<(PathBuf, String)> = Vec::new();
+    for (index, (item, output)) in items.iter().zip(results).enumerate() {
+        let path = if template.is_per_item() {
+            template.render(&TemplateVars::for_output(output).item(index, item.id.as_deref()))
+        } else {
+            template.render(&shared)
+        };
+        let line = BatchLine {
+            index,
+            id: item.id.as_deref(),
+            output,
+        };
+        let json = serde_json::to_string(&line)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+        let line = format!("{json}\n");
+        match files.iter_mut().find(|(p, _)| *p == path) {
+            Some((_, lines)) => lines.push_str(&line),
+            None => files.push((path, line)),
         }
     }
+    for (path, lines) in &files {
+        append_lines(path, lines)?;
+    }
+    Ok(())
+}
+
+/// Append to a file under an exclusive lock (creates the file and parent
+/// directories)
+///
+/// Concurrent writers (e.g. several watch processes sharing one results file)
+/// never interleave partial lines, and tail-based consumers only ever see
+/// complete lines appended.
+pub fn append_lines(path: &Path, lines: &str) -> Result<(), std::io::Error> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut file = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)?;
+    file.lock()?;
+    file.write_all(lines.as_bytes())?;
+    file.flush()
+    // The lock is released when the file is closed
+}
+
+/// Write an output in a `--output-format` other than JSON to stdout or file,
+/// atomically like [`write_output`]
+///
+/// Nothing is written when the rendering is empty (raw outputs of errors).
+pub fn write_rendered(
+    output: &CliOutput,
+    format: OutputFormat,
+    output_path: Option<&PathBuf>,
+) -> Result<(), std::io::Error> {
+    let text = output
+        .render(format)
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+    if text.is_empty() {
+        return Ok(());
+    }
+    write_text(&text, output_path)
+}
+
+/// Write a text report (e.g. CSV) to stdout or file, atomically like [`write_output`]
+pub fn write_text(content: &str, output_path: Option<&PathBuf>) -> Result<(), std::io::Error> {
+    match output_path {
+        Some(path) => write_atomic(path, content),
+        None => print_stdout(content),
+    }
+}
+
+/// Write to stdout in one piece
+///
+/// A closed stdout (e.g. piped into `head`) is not an error: the reader has
+/// everything it wanted.
+fn print_stdout(content: &str) -> Result<(), std::io::Error> {
+    let mut stdout = io::stdout().lock();
+    match stdout
+        .write_all(content.as_bytes())
+        .and_then(|()| stdout.flush())
+    {
+        Err(e) if e.kind() == io::ErrorKind::BrokenPipe => Ok(()),
+        result => result,
+    }
+}
+
+/// Write metrics in the Prometheus text format (e.g. for the node_exporter
+/// textfile collector), atomically like [`write_output`]
+pub fn write_metrics_file(path: &Path, metrics: &MetricsRegistry) -> Result<(), std::io::Error> {
+    write_atomic(path, &metrics.render_prometheus())
+}
+
+/// Temp file in the target directory, then rename (creates parent directories)
+fn write_atomic(path: &Path, content: &str) -> Result<(), std::io::Error> {
+    // Create parent directories if they don't exist
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    // Atomic write: write to temp file in same directory, then rename
+    let temp_dir = path.parent().unwrap_or_else(|| Path::new("."));
+    let mut temp_file = NamedTempFile::new_in(temp_dir)?;
+
+    temp_file.write_all(content.as_bytes())?;
+    temp_file.flush()?;
+
+    // Atomically rename temp file to final path
+    temp_file.persist(path)?;
 
+    log::info!("Output written to: {}", path.display());
     Ok(())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use fortified_llm_client::Metadata;
+    use crate::Metadata;
     use std::fs;
     use tempfile::TempDir;
 
@@ -77,11 +264,15 @@ mod tests {
         Metadata {
             model: "test-model".to_string(),
             tokens_estimated: 100,
+            tokens_prompt_actual: None,
+            tokens_completion_actual: None,
+            context_usage_ratio: None,
             latency_ms: 200,
             timestamp: "2025-01-01T00:00:00Z".to_string(),
             api_url: "http://test".to_string(),
             provider: None,
             temperature: 0.7,
+            top_p: None,
             max_tokens: Some(1000),
             seed: None,
             timeout_secs: 30,
@@ -93,8 +284,34 @@ mod tests {
             user_prompt_text: Some("user".to_string()),
             user_prompt_file: None,
             pdf_input: None,
+            url_input: None,
+            system_prompt_url: None,
+            prompt_pins: Vec::new(),
+            image_input: None,
+            image_mode: None,
+            chunking: None,
+            guard_tokens: Vec::new(),
+            user_prompt_prefix: None,
+            user_prompt_suffix: None,
+            spotlighting: None,
+            tags: Vec::new(),
             input_guardrails_enabled: None,
             output_guardrails_enabled: None,
+            violation_rules: Vec::new(),
+            violation_previews: Vec::new(),
+            redactions: Vec::new(),
+            guardrail_results: Vec::new(),
+            guardrail_explanations: Default::default(),
+            escalation: None,
+            quality_score: None,
+            response_chunks_aggregated: None,
+            upstream: None,
+            model_echo: None,
+            cache_hit: None,
+            stage_latency: Vec::new(),
+            warnings: Vec::new(),
+            config_warnings: Vec::new(),
+            field_filter: None,
         }
     }
 
@@ -1,5 +1,5 @@
-use fortified_llm_client::CliError;
-use std::path::PathBuf;
+use crate::CliError;
+use std::{io::Read, path::PathBuf};
 
 /// Load prompt from file or text string
 ///
@@ -41,6 +41,27 @@ pub fn load_prompt(file: Option<PathBuf>, text: Option<String>) -> Result<String
     }
 }
 
+/// Read the user prompt from stdin (`--user-text -`)
+///
+/// The trailing newline left by `echo` and most commands is dropped. An empty
+/// prompt is rejected.
+pub fn read_stdin_prompt() -> Result<String, CliError> {
+    read_prompt_from(std::io::stdin())
+}
+
+fn read_prompt_from(reader: impl Read) -> Result<String, CliError> {
+    let prompt = std::io::read_to_string(reader).map_err(|e| {
+        CliError::InvalidArguments(format!("Failed to read the user prompt from stdin: {e}"))
+    })?;
+    let prompt = prompt.trim_end_matches(['\n', '\r']);
+    if prompt.trim().is_empty() {
+        return Err(CliError::InvalidArguments(
+            "--user-text - read an empty prompt from stdin".to_string(),
+        ));
+    }
+    Ok(prompt.to_string())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -106,4 +127,13 @@ mod tests {
             .to_string()
             .contains("Failed to read file"));
     }
+
+    #[test]
+    fn test_read_prompt_from_stdin_drops_trailing_newline() {
+        let prompt = read_prompt_from("Summarize:\n\nfirst line\n".as_bytes()).unwrap();
+        assert_eq!(prompt, "Summarize:\n\nfirst line");
+
+        let err = read_prompt_from(" \n".as_bytes()).unwrap_err();
+        assert!(err.to_string().contains("empty prompt"));
+    }
 }
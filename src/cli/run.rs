@@ -0,0 +1,1090 @@
+//! Evaluation run driven by parsed [`Args`]
+//!
+//! Shared by the binary and [`run_from_args`], so both build the same
+//! [`EvaluationConfig`](crate::EvaluationConfig) from the same arguments.
+
+use super::{
+    args::{merge_config, Args, OutputMode, ResponseFormatArg},
+    commands::{Command, GuardrailsCommand},
+    guardrail_config::{configure_guardrails, CliGuardrail},
+    output_template::{OutputTemplate, TemplateVars},
+    output_writer::{append_jsonl, write_metrics_file, write_output, write_rendered},
+    prompt_loader::{load_prompt, read_stdin_prompt},
+};
+use crate::{
+    cassette::{self, Cassette, CassetteMode},
+    check_guardrail,
+    config::{env_config_value, load_config_layers, ConfigFileRequest},
+    config_builder::{self, ConfigBuilder},
+    config_check::{check_config, ConfigCheckReport},
+    constants::http::DEFAULT_MAX_RESPONSE_BYTES,
+    debug_bundle::{self, is_unexpected},
+    ensure_self_test, evaluate, evaluate_batch, evaluate_batch_with_events, evaluate_compare,
+    evaluate_with_confirmation, log_content_policy, response_limit, run_experiment, run_sweep,
+    set_log_content_policy, validate_compare_models, BatchItem, BatchOutput, BundleFailure,
+    CacheConfig, CliError, CliOutput, CompareReport, ContextProber, DebugBundle, EventWriter,
+    ExperimentReport, GuardBudget, GuardrailCheckReport, GuardrailOverride, GuardrailStage,
+    LogContentPolicy, Metadata, MetricsRegistry, OutputContext, OutputFormat, PromptAuditor,
+    RateLimiter, RequestPreview, ResponseCache, Server, SweepReport, TrendStore,
+};
+use clap::Parser;
+use serde_json::{json, Map, Value};
+use std::{path::PathBuf, sync::Arc};
+
+/// Run the CLI in-process on `args` (without the program name)
+///
+/// Parses, merges (`--config-file`) and validates the arguments exactly like
+/// the binary and runs the single evaluation they describe. The output is
+/// returned, and also written to `--output` (honoring `--output-mode`) when
+/// one is given; like the binary, a failed run writes its error output there
+/// before the error is returned. Nothing is printed to stdout.
+///
+/// Logging flags (`--verbose`, `--quiet`, `--color`, `--pipe`) are accepted
+/// but ignored: the caller owns the logger. Trace export (`--otlp-endpoint`)
+/// is not performed.
+///
+/// `--batch-file`, `--sweep`, `--compare-models`, subcommands and `--version`
+/// are rejected with `InvalidArguments`; use [`evaluate_batch`],
+/// [`run_experiment`], [`run_sweep`] or [`evaluate_compare`] for those runs. Parse errors (unknown flags, invalid values,
+/// `--help`) are `InvalidArguments` with clap's message. `--confirm` is
+/// rejected too: use [`evaluate_with_confirmation`](crate::evaluate_with_confirmation).
+///
+/// Process-wide settings the run applies (`--log-content-policy`, the
+/// `--record`/`--replay`/`--debug-bundle` cassette) are restored when it returns, so later calls are unaffected.
+///
+/// ```no_run
+/// # async fn example() -> Result<(), fortified_llm_client::CliError> {
+/// use fortified_llm_client::run_from_args;
+///
+/// let args: Vec<String> = ["--api-url", "http://localhost:11434/api/generate",
+///     "--model", "llama3", "--user-text", "Hello", "--enable-input-validation"]
+///     .map(String::from)
+///     .to_vec();
+/// let output = run_from_args(&args).await?;
+/// println!("{}", output.status);
+/// # Ok(())
+/// # }
+/// ```
+pub async fn run_from_args(args: &[String]) -> Result<CliOutput, CliError> {
+    let program = std::iter::once(env!("CARGO_PKG_NAME").to_string());
+    let args = Args::try_parse_from(program.chain(args.iter().cloned()))
+        .map_err(|e| CliError::InvalidArguments(e.to_string().trim_end().to_string()))?;
+    if args.version
+        || args.command.is_some()
+        || args.batch_file.is_some()
+        || args.sweep
+        || !args.compare_models.is_empty()
+    {
+        return Err(CliError::InvalidArguments(
+            "run_from_args runs single evaluations (--batch-file, --sweep, --compare-models, \
+             subcommands and --version are not supported)"
+                .to_string(),
+        ));
+    }
+    if args.confirm {
+        return Err(CliError::InvalidArguments(
+            "--confirm is interactive; use evaluate_with_confirmation in-process".to_string(),
+        ));
+    }
+
+    let template = output_template(&args)?;
+    let append = args.output_mode == OutputMode::AppendJsonl;
+    let format = args.output_format.into();
+    let _settings = ProcessSettings::save();
+    let output = match run(args).await {
+        Ok(RunResult::Single(output)) => *output,
+        Ok(_) => unreachable!("only single evaluations get past the checks above"),
+        Err(e) => {
+            if template.is_some() {
+                write_single_output(&error_output(&e), template.as_ref(), append, format)?;
+            }
+            return Err(e);
+        }
+    };
+    if template.is_some() {
+        write_single_output(&output, template.as_ref(), append, format)?;
+    }
+    Ok(output)
+}
+
+/// Process-wide settings [`run`] may change, restored on drop
+struct ProcessSettings {
+    log_content_policy: LogContentPolicy,
+    cassette: Option<Arc<Cassette>>,
+}
+
+impl ProcessSettings {
+    fn save() -> Self {
+        Self {
+            log_content_policy: log_content_policy(),
+            cassette: cassette::installed(),
+        }
+    }
+}
+
+impl Drop for ProcessSettings {
+    fn drop(&mut self) {
+        set_log_content_policy(self.log_content_policy);
+        match self.cassette.take() {
+            Some(cassette) => cassette::install(cassette),
+            None => cassette::uninstall(),
+        };
+    }
+}
+
+/// Write a single evaluation output to the rendered `--output` path (stdout
+/// without one), appending a line with `--output-mode append-jsonl`
+///
+/// Other formats than JSON are rendered with [`CliOutput::render`]; with
+/// `--output-format raw` an error's message goes to stderr instead.
+pub fn write_single_output(
+    output: &CliOutput,
+    template: Option<&OutputTemplate>,
+    append: bool,
+    format: OutputFormat,
+) -> Result<(), CliError> {
+    let path = template.map(|t| t.render(&TemplateVars::for_output(output)));
+    match (&path, append, format) {
+        (Some(path), true, _) => append_jsonl(output, path),
+        (_, _, OutputFormat::Json) => write_output(output, path.as_ref()),
+        _ => {
+            if let (OutputFormat::Raw, Some(error)) = (format, &output.error) {
+                eprintln!("Error: {}", error.message);
+            }
+            write_rendered(output, format, path.as_ref())
+        }
+    }
+    .map_err(|e| CliError::InvalidArguments(format!("Failed to write output: {e}")))
+}
+
+/// Output reported for a failed run (no config is available)
+pub fn error_output(error: &CliError) -> CliOutput {
+    let metadata = Metadata {
+        model: "unknown".to_string(),
+        tokens_estimated: 0,
+        tokens_prompt_actual: None,
+        tokens_completion_actual: None,
+        context_usage_ratio: None,
+        latency_ms: 0,
+        timestamp: chrono::Utc::now().to_rfc3339(),
+        api_url: "unknown".to_string(),
+        provider: None,
+        temperature: 0.0,
+        top_p: None,
+        max_tokens: None,
+        seed: None,
+        timeout_secs: 0,
+        context_limit: None,
+        response_format: None,
+        validate_tokens: false,
+        system_prompt_text: None,
+        system_prompt_file: None,
+        user_prompt_text: None,
+        user_prompt_file: None,
+        pdf_input: None,
+        url_input: None,
+        system_prompt_url: None,
+        prompt_pins: Vec::new(),
+        image_input: None,
+        image_mode: None,
+        chunking: None,
+        guard_tokens: Vec::new(),
+        user_prompt_prefix: None,
+        user_prompt_suffix: None,
+        spotlighting: None,
+        tags: Vec::new(),
+        input_guardrails_enabled: None,
+        output_guardrails_enabled: None,
+        violation_rules: Vec::new(),
+        violation_previews: Vec::new(),
+        redactions: Vec::new(),
+        guardrail_results: Vec::new(),
+        guardrail_explanations: Default::default(),
+        escalation: None,
+        quality_score: None,
+        response_chunks_aggregated: None,
+        upstream: None,
+        model_echo: None,
+        cache_hit: None,
+        stage_latency: Vec::new(),
+        warnings: Vec::new(),
+        config_warnings: Vec::new(),
+        field_filter: None,
+    };
+    CliOutput::error(error, error.to_string(), metadata)
+}
+
+/// Parse `--output` and check it (placeholders, output mode and format) suits
+/// the run mode
+pub fn output_template(args: &Args) -> Result<Option<OutputTemplate>, CliError> {
+    let append = args.output_mode == OutputMode::AppendJsonl;
+    let report = args.experiment || args.sweep || !args.compare_models.is_empty();
+    let format = OutputFormat::from(args.output_format);
+    if format != OutputFormat::Json && (append || report || args.batch_file.is_some()) {
+        return Err(CliError::InvalidArguments(format!(
+            "--output-format {format} supports single evaluations only, not --batch-file, \
+             --experiment, --sweep, --compare-models or --output-mode append-jsonl"
+        )));
+    }
+    if append && report {
+        return Err(CliError::InvalidArguments(
+            "--output-mode append-jsonl supports single evaluations and --batch-file, \
+             not --experiment, --sweep or --compare-models reports"
+                .to_string(),
+        ));
+    }
+    let Some(path) = &args.output else {
+        if append {
+            return Err(CliError::InvalidArguments(
+                "--output-mode append-jsonl requires --output".to_string(),
+            ));
+        }
+        return Ok(None);
+    };
+    let template = OutputTemplate::parse(path)?;
+    if report {
+        template.allow_only(
+            &["timestamp", "date", "evaluation_id"],
+            "for --experiment, --sweep and --compare-models reports",
+        )?;
+    } else if args.batch_file.is_some() {
+        // Appended lines can go to per-item files, but a shared file has no status
+        if !template.is_per_item() {
+            template.allow_only(
+                &["timestamp", "date", "model", "evaluation_id"],
+                "for a single batch results file (add {index} or {id} for one file per item)",
+            )?;
+        }
+    } else {
+        template.allow_only(
+            &["timestamp", "date", "model", "status", "evaluation_id"],
+            "without --batch-file",
+        )?;
+    }
+    Ok(Some(template))
+}
+
+/// Default for --batch-concurrency
+pub const DEFAULT_BATCH_CONCURRENCY: usize = 4;
+
+/// Result of a single evaluation or a batch run
+pub enum RunResult {
+    Single(Box<CliOutput>),
+    Batch(Vec<BatchItem>, BatchOutput),
+    Experiment(Box<ExperimentReport>),
+    Sweep(Box<SweepReport>),
+    Compare(Box<CompareReport>),
+    /// `serve` ran until interrupted
+    Served,
+    /// `validate-config` report
+    ConfigCheck(Box<ConfigCheckReport>),
+    /// `guardrails test` report
+    GuardrailCheck(Box<GuardrailCheckReport>),
+}
+
+pub async fn run(args: Args) -> Result<RunResult, CliError> {
+    // Merge config file and CLI args using figment (CLI args override config file)
+    let merged_args = merge_config(&args)?;
+    let self_test = merged_args.self_test.unwrap_or(false);
+    if let Some(policy) = merged_args.log_content_policy {
+        set_log_content_policy(policy);
+    }
+
+    // Load config file for guardrails configuration
+    //
+    // WHY DUAL LOADING: Figment (above) handles scalar fields (api_url, model, etc.),
+    // but guardrail configuration is complex nested structures not in Args struct.
+    // The [guardrails] section in config files requires ConfigFileRequest parsing.
+    //
+    // FUTURE: Could unify by adding guardrails field to Args, but would require
+    // making GuardrailConfig implement clap::Args (significant refactor).
+    //
+    // SLLM_* variables apply over the file; without a file they stand in for
+    // one, taking the keys it requires from the merged args.
+    let env_values = env_config_value()?;
+    let file_config = match &merged_args.config_file {
+        Some(config_path) => Some(load_config_layers(
+            Some(config_path),
+            merged_args.profile.as_deref(),
+            env_values,
+        )?),
+        None if !env_values.is_empty() => {
+            let mut values = env_values;
+            let required = [
+                ("api_url", json!(merged_args.api_url)),
+                (
+                    "model",
+                    json!(merged_args
+                        .model
+                        .as_ref()
+                        .or(merged_args.compare_models.first())),
+                ),
+                ("system_prompt", json!(merged_args.system_text)),
+                ("system_prompt_file", json!(merged_args.system_file)),
+                ("system_prompt_url", json!(merged_args.system_prompt_url)),
+            ];
+            let env_system_prompt = ["system_prompt", "system_prompt_file", "system_prompt_url"]
+                .iter()
+                .any(|key| values.contains_key(*key));
+            for (key, value) in required {
+                if key.starts_with("system_prompt") && env_system_prompt {
+                    continue;
+                }
+                if !value.is_null() {
+                    values.entry(key).or_insert(value);
+                }
+            }
+            Some(load_config_layers(None, None, values)?)
+        }
+        None => None,
+    };
+    // Effective configuration for a debug bundle (taken before prompts are moved out)
+    let bundle_config = merged_args
+        .debug_bundle
+        .is_some()
+        .then(|| json!({ "args": &merged_args, "config_file": &file_config }));
+
+    // Start building config from merged args
+    let mut builder = ConfigBuilder::new();
+
+    // Set values from merged args (config file + CLI args, with CLI taking priority)
+    if let Some(ref api_url) = merged_args.api_url {
+        builder = builder.api_url(api_url.clone());
+    }
+    // --compare-models replaces the model; the first one stands in for --model
+    if let Some(model) = merged_args
+        .model
+        .as_ref()
+        .or(merged_args.compare_models.first())
+    {
+        builder = builder.model(model.clone());
+    }
+    if let Some(provider) = merged_args.provider {
+        builder = builder.provider(provider.into());
+    }
+    if let Some(preset) = merged_args.preset {
+        builder = builder.preset(preset.into());
+    }
+    if let Some(temperature) = merged_args.temperature {
+        builder = builder.temperature(temperature);
+    }
+    if let Some(top_p) = merged_args.top_p {
+        builder = builder.top_p(top_p);
+    }
+    if let Some(max_tokens) = merged_args.max_tokens {
+        builder = builder.max_tokens(max_tokens);
+    }
+    if let Some(seed) = merged_args.seed {
+        builder = builder.seed(seed);
+    }
+    if let Some(timeout_secs) = merged_args.timeout_secs {
+        builder = builder.timeout_secs(timeout_secs);
+    }
+    if let Some(limit) = merged_args.max_response_bytes {
+        builder = builder.max_response_bytes(limit);
+    }
+    if let Some(validate_tokens) = merged_args.validate_tokens {
+        builder = builder.validate_tokens(validate_tokens);
+    }
+    if let Some(context_limit) = merged_args.context_limit {
+        builder = builder.context_limit(context_limit);
+    }
+    if let Some(percent) = merged_args.context_warning_percent {
+        builder = builder.context_warning_percent(percent);
+    }
+    builder = builder.tags(merged_args.tags.iter().cloned());
+    if let Some(classification) = merged_args.data_classification {
+        builder = builder.data_classification(classification);
+    }
+    builder = builder.allowed_external_hosts(merged_args.allowed_external_hosts.iter().cloned());
+    for path in &merged_args.context_files {
+        builder = builder.context_file(path);
+    }
+    if let Some(ref image_path) = merged_args.image_file {
+        builder = builder.image_input(image_path.clone());
+    }
+    if let Some(image_mode) = merged_args.image_mode {
+        builder = builder.image_mode(image_mode.into());
+    }
+
+    // Handle input validation and guardrails (merged args already include config file values)
+    // Must be called before load_prompt to avoid partial move of merged_args
+    let cli_guardrail = merged_args.input_guardrail.map(|kind| CliGuardrail {
+        kind,
+        api_url: merged_args.guard_url.clone(),
+        model: merged_args.guard_model.clone(),
+        api_key_name: merged_args.guard_api_key_name.clone(),
+    });
+    if let Some(guardrail_config) = configure_guardrails(
+        cli_guardrail.as_ref(),
+        merged_args.enable_input_validation,
+        merged_args.max_input_length,
+        file_config.as_ref(),
+    ) {
+        builder = builder.input_guardrails(guardrail_config);
+    }
+
+    // Handle output guardrails from config file
+    if let Some(guardrail_config) = file_config
+        .as_ref()
+        .and_then(|fc| fc.guardrails.as_ref())
+        // Prefer explicit output field, then flattened provider field, then preset
+        .and_then(|g| g.output_config())
+    {
+        builder = builder.output_guardrails(guardrail_config);
+    }
+    for rule in file_config
+        .iter()
+        .filter_map(|fc| fc.guardrails.as_ref())
+        .flat_map(|g| g.conditional.iter().cloned())
+    {
+        builder = builder.guardrail_condition(rule);
+    }
+
+    // Handle guardrail override policy (config file) and token (CLI only)
+    if let Some(policy) = file_config
+        .as_ref()
+        .and_then(|fc| fc.guardrail_override.as_ref())
+    {
+        builder = builder.guardrail_override(Arc::new(GuardrailOverride::from_config(policy)?));
+    }
+    if let Some(budget_config) = file_config.as_ref().and_then(|fc| fc.guard_budget.as_ref()) {
+        builder = builder.guard_budget(Arc::new(GuardBudget::from_config(budget_config)?));
+    }
+    if let Some(audit_config) = file_config.as_ref().and_then(|fc| fc.prompt_audit.as_ref()) {
+        builder = builder.prompt_audit(Arc::new(PromptAuditor::from_config(audit_config)));
+    }
+    if let Some(trend_config) = file_config.as_ref().and_then(|fc| fc.trend_stats.as_ref()) {
+        builder = builder.trend_stats(Arc::new(TrendStore::from_config(trend_config)));
+    }
+    // Response cache: [cache] section, --cache (in-memory default), --cache-dir
+    // or --cache-stochastic
+    let mut cache_config = file_config.as_ref().and_then(|fc| fc.cache.clone());
+    if merged_args.cache || merged_args.cache_dir.is_some() || merged_args.cache_stochastic {
+        let cache_config = cache_config.get_or_insert_with(CacheConfig::default);
+        if let Some(dir) = &merged_args.cache_dir {
+            cache_config.dir = Some(dir.clone());
+        }
+        if merged_args.cache_stochastic {
+            cache_config.stochastic = true;
+        }
+    }
+    if let Some(cache_config) = &cache_config {
+        builder = builder.response_cache(Arc::new(ResponseCache::from_config(cache_config)));
+    }
+    if let Some(limit_config) = file_config.as_ref().and_then(|fc| fc.rate_limit.as_ref()) {
+        builder = builder.rate_limiter(Arc::new(RateLimiter::from_config(limit_config)?));
+    }
+    // Address of the `serve` subcommand (the server also exposes GET /metrics)
+    let serve_addr = match merged_args.command {
+        Some(Command::Serve { listen }) => Some(listen),
+        _ => None,
+    };
+    let validate_only = matches!(merged_args.command, Some(Command::ValidateConfig));
+    if merged_args.confirm && merged_args.user_text.as_deref() == Some("-") {
+        return Err(CliError::InvalidArguments(
+            "--confirm reads its answer from stdin, which --user-text - uses for the prompt"
+                .to_string(),
+        ));
+    }
+    if merged_args.confirm && serve_addr.is_some() {
+        return Err(CliError::InvalidArguments(
+            "--confirm is not supported by serve (requests cannot be confirmed interactively)"
+                .to_string(),
+        ));
+    }
+    let metrics = (merged_args.metrics_file.is_some() || serve_addr.is_some())
+        .then(|| Arc::new(MetricsRegistry::new()));
+    if let Some(metrics) = &metrics {
+        builder = builder.metrics(Arc::clone(metrics));
+    }
+    if let Some(probe_config) = file_config
+        .as_ref()
+        .and_then(|fc| fc.context_probe.as_ref())
+    {
+        builder = builder.context_probe(Arc::new(ContextProber::from_config(probe_config)));
+    }
+    if let Some(token) = merged_args.override_token.clone() {
+        if builder.guardrail_override.is_none() {
+            builder = builder.warning(
+                "override_token",
+                "--override-token was provided but no [guardrail_override] policy is configured. \
+                 The token will be ignored.",
+            );
+        }
+        builder = builder.override_token(token);
+    }
+
+    // Handle system prompt (file > text > config file)
+    // Validation: Warn if config file has conflicting fields
+    if merged_args.system_file.is_some() && merged_args.system_text.is_some() {
+        builder = builder.warning(
+            "system_prompt",
+            "Config file contains both system_file and system_text. \
+             Using system_file (priority: file > text).",
+        );
+    }
+
+    if let Some(file_path) = merged_args.system_file {
+        let prompt = load_prompt(Some(file_path.clone()), None)?;
+        builder = builder.system_prompt(prompt).system_prompt_file(file_path);
+    } else if let Some(text) = merged_args.system_text {
+        builder = builder.system_prompt(text);
+        // No file path set - metadata will show text content
+    } else if let Some(url) = merged_args.system_prompt_url {
+        // Fetched by the extract stage
+        builder = builder.system_prompt_url(url);
+    }
+
+    // Handle user prompt (file > text > PDF > config file)
+    // Validation: Warn if config file has multiple user prompt sources
+    if [
+        merged_args.user_file.as_ref().map(|_| 1).unwrap_or(0),
+        merged_args.user_text.as_ref().map(|_| 1).unwrap_or(0),
+        merged_args.pdf_file.as_ref().map(|_| 1).unwrap_or(0),
+        merged_args.user_prompt_url.as_ref().map(|_| 1).unwrap_or(0),
+    ]
+    .iter()
+    .sum::<i32>()
+        > 1
+    {
+        builder = builder.warning(
+            "user_prompt",
+            "Config file contains multiple user prompt sources. \
+             Using first available (priority: user_file > user_text > pdf_file > user_prompt_url).",
+        );
+    }
+
+    // Batch items supply their own prompts
+    let batch_items = merged_args
+        .batch_file
+        .as_deref()
+        .map(BatchItem::load)
+        .transpose()?;
+
+    // Experiment variants come from the config file's [experiment] section
+    let experiment = if merged_args.experiment {
+        let experiment = file_config
+            .as_ref()
+            .and_then(|fc| fc.experiment.clone())
+            .ok_or_else(|| {
+                CliError::InvalidArguments(
+                    "--experiment requires an [experiment] section in the config file".to_string(),
+                )
+            })?;
+        experiment.validate()?;
+        Some(experiment)
+    } else {
+        None
+    };
+
+    // Sampling grid comes from the config file's [sweep] section
+    let sweep = if merged_args.sweep {
+        let sweep = file_config
+            .as_ref()
+            .and_then(|fc| fc.sweep.clone())
+            .ok_or_else(|| {
+                CliError::InvalidArguments(
+                    "--sweep requires a [sweep] section in the config file".to_string(),
+                )
+            })?;
+        sweep.validate()?;
+        Some(sweep)
+    } else {
+        None
+    };
+    if !merged_args.compare_models.is_empty() {
+        validate_compare_models(&merged_args.compare_models)?;
+    }
+
+    // Progress events are reported per batch item or server request
+    let events = match &merged_args.events_file {
+        Some(path) => {
+            if batch_items.is_none() && serve_addr.is_none() {
+                return Err(CliError::InvalidArguments(
+                    "--events-file requires --batch-file or the serve subcommand".to_string(),
+                ));
+            }
+            if path.as_os_str() == "-" && merged_args.pipe {
+                return Err(CliError::InvalidArguments(
+                    "--events-file - writes to stdout, which --pipe reserves for results"
+                        .to_string(),
+                ));
+            }
+            if path.as_os_str() == "-" && batch_items.is_some() && merged_args.output.is_none() {
+                return Err(CliError::InvalidArguments(
+                    "--events-file - requires --output in batch mode (result lines are written to stdout)"
+                        .to_string(),
+                ));
+            }
+            Some(Arc::new(EventWriter::create(path)?))
+        }
+        None => None,
+    };
+
+    // Batch items and server requests supply their own prompts
+    if batch_items.is_some() || serve_addr.is_some() {
+        builder = builder.user_prompt(String::new());
+    } else if let Some(file_path) = merged_args.user_file {
+        let prompt = load_prompt(Some(file_path.clone()), None)?;
+        builder = builder.user_prompt(prompt).user_prompt_file(file_path);
+    } else if let Some(text) = merged_args.user_text {
+        let text = if text == "-" {
+            read_stdin_prompt()?
+        } else {
+            text
+        };
+        builder = builder.user_prompt(text);
+        // No file path set - metadata will show text content
+    } else if let Some(pdf_path) = merged_args.pdf_file {
+        builder = builder.pdf_input(pdf_path);
+    } else if let Some(url) = merged_args.user_prompt_url {
+        builder = builder.url_input(url);
+    } else if validate_only {
+        // The user prompt is usually only given at run time
+        builder = builder.user_prompt(String::new());
+    }
+
+    // Handle API key (CLI direct > CLI env var > config file env var > config file direct)
+    if let Some(ref key) = merged_args.api_key {
+        builder = builder.api_key(key.clone());
+    } else if let Some(ref env_var_name) = merged_args.api_key_name {
+        // CLI --api-key-name takes priority over config file
+        match std::env::var(env_var_name) {
+            Ok(key) => {
+                log::debug!("API key loaded from environment variable (CLI): {env_var_name}");
+                builder = builder.api_key(key);
+            }
+            Err(_) => {
+                return Err(CliError::InvalidArguments(format!(
+                    "Environment variable '{env_var_name}' specified by --api-key-name does not exist"
+                )));
+            }
+        }
+    } else if let Some(file_cfg) = file_config.as_ref() {
+        // Check config file for api_key_name (env var name)
+        if let Some(ref env_var_name) = file_cfg.api_key_name {
+            match std::env::var(env_var_name) {
+                Ok(key) => {
+                    log::debug!(
+                        "API key loaded from environment variable (config file): {env_var_name}"
+                    );
+                    builder = builder.api_key(key);
+                }
+                Err(_) => {
+                    return Err(CliError::InvalidArguments(format!(
+                        "Environment variable '{env_var_name}' specified by config file 'api_key_name' does not exist"
+                    )));
+                }
+            }
+        } else if let Some(source) = &file_cfg.api_key_secret {
+            builder = builder.api_key(source.resolve()?);
+            log::debug!("API key loaded from {source} (config file)");
+        }
+        // Note: Direct api_key from config file is handled by merge_file_config() below
+    }
+
+    // Handle response format with schema validation
+    if merged_args.response_format_schema.is_some()
+        && merged_args.response_format != Some(ResponseFormatArg::JsonSchema)
+    {
+        return Err(CliError::InvalidArguments(format!(
+            "--response-format-schema can only be used with --response-format=json-schema\n\
+            You provided --response-format-schema but --response-format is {:?}\n\
+            Either:\n\
+            - Add --response-format json-schema to use structured output\n\
+            - Remove --response-format-schema if you don't need schema validation",
+            merged_args
+                .response_format
+                .map(|f| match f {
+                    ResponseFormatArg::Text => "text",
+                    ResponseFormatArg::JsonObject => "json-object",
+                    ResponseFormatArg::JsonSchema => "json-schema",
+                })
+                .unwrap_or("not set")
+        )));
+    }
+
+    match merged_args.response_format {
+        Some(ResponseFormatArg::Text) => {
+            builder = builder.response_format(crate::ResponseFormat::text());
+        }
+        Some(ResponseFormatArg::JsonObject) => {
+            builder = builder.response_format(crate::ResponseFormat::json());
+        }
+        Some(ResponseFormatArg::JsonSchema) => {
+            let schema_path = merged_args.response_format_schema.as_ref().ok_or_else(|| {
+                CliError::InvalidArguments(
+                    "--response-format-schema is required when using --response-format=json-schema\n\
+                    Example: --response-format json-schema --response-format-schema path/to/schema.json"
+                        .to_string(),
+                )
+            })?;
+
+            match config_builder::load_json_schema(
+                schema_path,
+                merged_args.response_format_schema_strict,
+            ) {
+                Ok(response_format) => builder = builder.response_format(response_format),
+                // Reported with the other problems
+                Err(e) if validate_only => {
+                    builder = builder.warning("response_format_schema", e.to_string());
+                }
+                Err(e) => return Err(e),
+            }
+        }
+        None => {}
+    }
+
+    // Merge config file values (lower priority than CLI args)
+    if let Some(file_cfg) = file_config.as_ref() {
+        builder = builder.merge_file_config(file_cfg);
+    }
+
+    // guardrails test only needs the guardrail configuration
+    if let Some(Command::Guardrails(GuardrailsCommand::Test {
+        direction,
+        file,
+        prompt,
+    })) = merged_args.command.clone()
+    {
+        let report = run_guardrail_check(&builder, direction.into(), file, prompt).await?;
+        return Ok(RunResult::GuardrailCheck(Box::new(report)));
+    }
+
+    // validate-config reports every problem instead of the first one, and
+    // sends no request
+    if validate_only {
+        let mut report = check_config(builder);
+        if let Some(Value::Object(config)) = &mut report.config {
+            resolve_cli_sections(
+                config,
+                file_config.as_ref(),
+                cache_config,
+                merged_args.log_content_policy,
+                self_test,
+            );
+        }
+        return Ok(RunResult::ConfigCheck(Box::new(report)));
+    }
+
+    // Build final config (applies defaults and validation)
+    let config = builder.build()?;
+
+    // Debug log: Input parameters (excluding API key for security)
+    log::debug!("=== Evaluation Parameters ===");
+    log::debug!("API URL: {}", config.api_url);
+    log::debug!("Model: {}", config.model);
+    log::debug!("Provider: {:?}", config.provider);
+    log::debug!("Temperature: {}", config.temperature);
+    log::debug!("Top-p: {:?}", config.top_p);
+    log::debug!(
+        "Max tokens: {}",
+        config
+            .max_tokens
+            .map(|t| t.to_string())
+            .unwrap_or_else(|| "unlimited (model's maximum)".to_string())
+    );
+    log::debug!("Timeout: {}s", config.timeout_secs);
+    log::debug!("Validate tokens: {}", config.validate_tokens);
+    log::debug!("Context limit: {:?}", config.context_limit);
+    log::debug!(
+        "Response format: {}",
+        config
+            .response_format
+            .as_ref()
+            .map(|f| f.to_string())
+            .unwrap_or_else(|| "not set".to_string())
+    );
+    // Log API key source (not the actual key value)
+    let api_key_source = match (
+        args.api_key.as_ref(),
+        args.api_key_name.as_ref(),
+        file_config.as_ref().and_then(|c| c.api_key_name.as_ref()),
+        file_config.as_ref().and_then(|c| c.api_key_secret.as_ref()),
+        file_config.as_ref().and_then(|c| c.api_key.as_ref()),
+    ) {
+        (Some(_), _, _, _, _) => "CLI argument (--api-key)".to_string(),
+        (None, Some(env_name), _, _, _) => {
+            format!("environment variable: {env_name} (--api-key-name)")
+        }
+        (None, None, Some(env_name), _, _) => {
+            format!("environment variable: {env_name} (config file api_key_name)")
+        }
+        (None, None, None, Some(source), _) => format!("{source} (config file api_key_secret)"),
+        (None, None, None, None, Some(_)) => "config file (api_key)".to_string(),
+        (None, None, None, None, None) => "not set".to_string(),
+    };
+
+    log::debug!(
+        "API key: {} (source: {})",
+        if config.api_key.is_some() {
+            "[REDACTED]"
+        } else {
+            "[NOT SET]"
+        },
+        api_key_source
+    );
+    log::debug!(
+        "PDF input: {:?}",
+        config.pdf_input.as_ref().map(|p| p.display())
+    );
+    log::debug!(
+        "Image input: {:?} ({:?})",
+        config.image_input.as_ref().map(|p| p.display()),
+        config.image_mode
+    );
+    log::debug!(
+        "System prompt length: {} chars",
+        config.system_prompt.chars().count()
+    );
+    log::debug!(
+        "User prompt length: {} chars",
+        config.user_prompt.chars().count()
+    );
+    log::debug!(
+        "Input validation/guardrails: {}",
+        if config.input_guardrails.is_some() {
+            "enabled"
+        } else {
+            "disabled"
+        }
+    );
+    log::debug!("=============================");
+
+    // Record or replay every HTTP interaction (main LLM and guardrails)
+    let cassette = if let Some(path) = &merged_args.replay {
+        Some(Arc::new(Cassette::replay(path)?))
+    } else if let Some(path) = &merged_args.record {
+        Some(Arc::new(Cassette::record(path)))
+    } else {
+        // Debug bundles include the HTTP interactions even without --record
+        merged_args
+            .debug_bundle
+            .as_ref()
+            .map(|dir| Arc::new(Cassette::record(dir.join("http.json"))))
+    };
+    if let Some(cassette) = &cassette {
+        if let Some(key) = &config.api_key {
+            cassette.add_secret(key);
+        }
+        cassette::install(cassette.clone());
+    }
+
+    let result = async {
+        // Refuse to evaluate if the configured guardrails fail their canaries
+        if self_test {
+            ensure_self_test(&config).await?;
+        }
+
+        // Call library function
+        let concurrency = merged_args
+            .batch_concurrency
+            .unwrap_or(DEFAULT_BATCH_CONCURRENCY);
+        if let Some(addr) = serve_addr {
+            let mut server = Server::bind(addr, config).await?;
+            if let Some(events) = events {
+                server = server.with_events(events);
+            }
+            log::info!("Serving on http://{}", server.local_addr());
+            tokio::select! {
+                _ = server.run() => {}
+                _ = tokio::signal::ctrl_c() => log::info!("Shutting down"),
+            }
+            return Ok(RunResult::Served);
+        }
+        if let Some(sweep) = sweep {
+            return run_sweep(&config, &sweep, concurrency)
+                .await
+                .map(|report| RunResult::Sweep(Box::new(report)));
+        }
+        if !merged_args.compare_models.is_empty() {
+            return evaluate_compare(&config, &merged_args.compare_models)
+                .await
+                .map(|report| RunResult::Compare(Box::new(report)));
+        }
+        match (batch_items, experiment) {
+            (Some(items), Some(experiment)) => {
+                run_experiment(&config, &items, &experiment, concurrency)
+                    .await
+                    .map(|report| RunResult::Experiment(Box::new(report)))
+            }
+            (Some(items), None) => {
+                let output = match &events {
+                    Some(events) => {
+                        evaluate_batch_with_events(&config, &items, concurrency, events).await
+                    }
+                    None => {
+                        let configs = items.iter().map(|item| item.apply(&config)).collect();
+                        evaluate_batch(configs, concurrency).await
+                    }
+                };
+                Ok(RunResult::Batch(items, output))
+            }
+            (None, _) if merged_args.confirm => {
+                evaluate_with_confirmation(config, confirm_on_stdin)
+                    .await
+                    .map(|output| RunResult::Single(Box::new(output)))
+            }
+            (None, _) => evaluate(config)
+                .await
+                .map(|output| RunResult::Single(Box::new(output))),
+        }
+    }
+    .await;
+
+    // Save even when the evaluation failed: failures are worth reproducing too
+    if let Some(cassette) = cassette
+        .as_ref()
+        .filter(|c| c.mode() == CassetteMode::Record && merged_args.record.is_some())
+    {
+        cassette.save()?;
+        log::info!(
+            "Recorded {} HTTP interaction(s) to {}",
+            cassette.interactions().len(),
+            cassette.path().display()
+        );
+    }
+    if let (Some(dir), Some(cassette)) = (&merged_args.debug_bundle, &cassette) {
+        let failures = bundle_failures(&result);
+        if !failures.is_empty() {
+            let bundle = DebugBundle {
+                failures,
+                config: bundle_config.unwrap_or_default(),
+                cassette,
+                spans: debug_bundle::collected_spans(),
+                log: debug_bundle::log_tail()
+                    .map(|tail| tail.lines())
+                    .unwrap_or_default(),
+            };
+            match bundle.write(dir) {
+                Ok(path) => log::warn!("Debug bundle written to {}", path.display()),
+                Err(e) => log::warn!("Failed to write debug bundle: {e}"),
+            }
+        }
+    }
+    if let (Some(metrics), Some(path)) = (&metrics, &merged_args.metrics_file) {
+        if let Err(e) = write_metrics_file(path, metrics) {
+            log::warn!("Failed to write metrics to {}: {e}", path.display());
+        }
+    }
+    result
+}
+
+/// Unexpected failures of a run, for its debug bundle
+fn bundle_failures(result: &Result<RunResult, CliError>) -> Vec<BundleFailure> {
+    let failure = |index, output: &CliOutput| {
+        let error = output.error.as_ref()?;
+        is_unexpected(&error.code).then(|| BundleFailure {
+            index,
+            code: error.code.to_string(),
+            message: error.message.clone(),
+        })
+    };
+    match result {
+        Err(e) if e.is_unexpected() => vec![BundleFailure {
+            index: None,
+            code: e.code().to_string(),
+            message: e.to_string(),
+        }],
+        Ok(RunResult::Single(output)) => failure(None, output).into_iter().collect(),
+        Ok(RunResult::Batch(_, batch)) => batch
+            .results
+            .iter()
+            .enumerate()
+            .filter_map(|(index, output)| failure(Some(index), output))
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Replace the runtime components of a resolved configuration with their
+/// config file sections, and add the process-wide settings
+fn resolve_cli_sections(
+    config: &mut Map<String, Value>,
+    file_config: Option<&ConfigFileRequest>,
+    cache: Option<CacheConfig>,
+    log_content_policy: Option<LogContentPolicy>,
+    self_test: bool,
+) {
+    if let Some(file_config) = file_config {
+        let sections = [
+            ("guardrail_override", json!(file_config.guardrail_override)),
+            ("guard_budget", json!(file_config.guard_budget)),
+            ("prompt_audit", json!(file_config.prompt_audit)),
+            ("trend_stats", json!(file_config.trend_stats)),
+            ("rate_limit", json!(file_config.rate_limit)),
+            ("context_probe", json!(file_config.context_probe)),
+        ];
+        for (name, section) in sections {
+            if config.contains_key(name) && !section.is_null() {
+                config.insert(name.to_string(), section);
+            }
+        }
+    }
+    if let Some(cache) = cache {
+        config.insert("cache".to_string(), json!(cache));
+    }
+    config.insert(
+        "log_content_policy".to_string(),
+        json!(log_content_policy.unwrap_or_default()),
+    );
+    config.insert("self_test".to_string(), json!(self_test));
+}
+
+/// `guardrails test`: run the configured guardrail for `stage` against the
+/// file content or stdin
+async fn run_guardrail_check(
+    builder: &ConfigBuilder,
+    stage: GuardrailStage,
+    file: Option<PathBuf>,
+    prompt: Option<String>,
+) -> Result<GuardrailCheckReport, CliError> {
+    let guardrail = match stage {
+        GuardrailStage::Input => builder.input_guardrails.as_ref(),
+        GuardrailStage::Output => builder.output_guardrails.as_ref(),
+    }
+    .ok_or_else(|| {
+        CliError::InvalidArguments(format!(
+            "No {stage} guardrails configured (use a config file with [guardrails.{stage}]{})",
+            match stage {
+                GuardrailStage::Input => ", --input-guardrail or --enable-input-validation",
+                GuardrailStage::Output => "",
+            }
+        ))
+    })?;
+
+    let content = match file {
+        Some(path) => load_prompt(Some(path), None)?,
+        None => std::io::read_to_string(std::io::stdin()).map_err(|e| {
+            CliError::InvalidArguments(format!("Failed to read text from stdin: {e}"))
+        })?,
+    };
+    let context = prompt.map(|prompt| {
+        OutputContext::new(
+            builder.system_prompt.as_deref().unwrap_or_default(),
+            prompt,
+            builder.response_format.clone(),
+        )
+    });
+
+    let limit = builder
+        .max_response_bytes
+        .unwrap_or(DEFAULT_MAX_RESPONSE_BYTES);
+    response_limit::scope(limit, check_guardrail(stage, guardrail, &content, context)).await
+}
+
+/// `--confirm`: show the preview on stderr and read the answer from stdin
+/// (anything but y/yes, including end of input, declines)
+fn confirm_on_stdin(preview: &RequestPreview) -> bool {
+    eprintln!("{preview}");
+    eprint!("Send this request? [y/N] ");
+    let mut answer = String::new();
+    match std::io::stdin().read_line(&mut answer) {
+        Ok(_) => matches!(answer.trim().to_ascii_lowercase().as_str(), "y" | "yes"),
+        Err(e) => {
+            log::warn!("Failed to read confirmation: {e}");
+            false
+        }
+    }
+}
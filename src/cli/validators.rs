@@ -1,4 +1,4 @@
-use fortified_llm_client::constants::llm_defaults;
+use crate::constants::llm_defaults;
 use std::path::PathBuf;
 
 // Validation constants
@@ -23,6 +23,23 @@ pub fn validate_temperature(s: &str) -> Result<f32, String> {
         })
 }
 
+/// Validate top_p value (nucleus sampling probability mass)
+pub fn validate_top_p(s: &str) -> Result<f32, String> {
+    let top_p: f32 = s
+        .parse()
+        .map_err(|_| format!("'{s}' is not a valid number"))?;
+    (llm_defaults::MIN_TOP_P..=llm_defaults::MAX_TOP_P)
+        .contains(&top_p)
+        .then_some(top_p)
+        .ok_or_else(|| {
+            format!(
+                "top_p must be between {} and {}, got {top_p}",
+                llm_defaults::MIN_TOP_P,
+                llm_defaults::MAX_TOP_P
+            )
+        })
+}
+
 /// Validate positive u32 value (must be >= MIN_TOKENS)
 pub fn validate_positive_u32(s: &str) -> Result<u32, String> {
     let val: u32 = s
@@ -165,6 +182,13 @@ mod tests {
         assert!(validate_temperature("abc").is_err());
     }
 
+    #[test]
+    fn test_validate_top_p() {
+        assert_eq!(validate_top_p("0.9").unwrap(), 0.9);
+        assert!(validate_top_p("1.1").is_err());
+        assert!(validate_top_p("abc").is_err());
+    }
+
     #[test]
     fn test_validate_positive_u32_valid() {
         assert_eq!(validate_positive_u32("1").unwrap(), 1);
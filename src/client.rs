@@ -1,19 +1,58 @@
 use crate::{
     error::CliError,
-    provider::{InvokeParams, LlmProvider},
-    providers::create_provider,
+    provider::{ChunkStream, InvokeParams, LlmProvider, ProviderResponse},
+    providers::{create_provider, detect_provider_type, BedrockProvider},
+    rate_limiter::RateLimiter,
+    EvaluationConfig,
 };
+use futures::StreamExt;
+use std::sync::Arc;
+use tracing::Instrument;
 
 pub use crate::provider::ProviderType as Provider;
 
 pub struct LlmClient {
     provider: Box<dyn LlmProvider>,
+    provider_type: Provider,
+    rate_limiter: Option<Arc<RateLimiter>>,
 }
 
 impl LlmClient {
     pub fn new(api_url: String, provider: Option<Provider>) -> Self {
+        let provider_type = provider.unwrap_or_else(|| detect_provider_type(&api_url));
         Self {
             provider: create_provider(api_url, provider),
+            provider_type,
+            rate_limiter: None,
+        }
+    }
+
+    /// Wait for `limiter` before each call (see [`crate::rate_limiter`])
+    pub fn with_rate_limiter(mut self, limiter: Arc<RateLimiter>) -> Self {
+        self.rate_limiter = Some(limiter);
+        self
+    }
+
+    /// Client for an evaluation, applying provider settings from the config
+    /// (e.g. Bedrock region and credentials) and its rate limiter
+    pub fn for_config(config: &EvaluationConfig) -> Self {
+        let provider = config
+            .provider
+            .unwrap_or_else(|| detect_provider_type(&config.api_url));
+        let client = match (provider, &config.bedrock) {
+            (Provider::Bedrock, Some(bedrock)) => Self {
+                provider: Box::new(BedrockProvider::with_config(
+                    config.api_url.clone(),
+                    bedrock.clone(),
+                )),
+                provider_type: provider,
+                rate_limiter: None,
+            },
+            _ => Self::new(config.api_url.clone(), config.provider),
+        };
+        match &config.rate_limiter {
+            Some(limiter) => client.with_rate_limiter(limiter.clone()),
+            None => client,
         }
     }
 
@@ -39,11 +78,14 @@ impl LlmClient {
     ///     system_prompt: "You are helpful.",
     ///     user_prompt: "Say hello",
     ///     temperature: 0.7,
+    ///     top_p: None,
     ///     max_tokens: Some(100),
     ///     seed: None,
     ///     api_key: None,
     ///     timeout_secs: 30,
     ///     response_format: None,
+    ///     tools: &[],
+    ///     images: &[],
     /// };
     ///
     /// let response = client.invoke(params).await?;
@@ -51,6 +93,81 @@ impl LlmClient {
     /// # }
     /// ```
     pub async fn invoke(&self, params: InvokeParams<'_>) -> Result<String, CliError> {
-        self.provider.invoke(params).await
+        let span = self.span(&params, false);
+        self.acquire(&params).await;
+        self.provider.invoke(params).instrument(span).await
+    }
+
+    /// Invoke the LLM and return the response with transport details
+    pub async fn invoke_detailed(
+        &self,
+        params: InvokeParams<'_>,
+    ) -> Result<ProviderResponse, CliError> {
+        let span = self.span(&params, false);
+        let reserved = self.acquire(&params).await;
+        let response = self
+            .provider
+            .invoke_detailed(params)
+            .instrument(span)
+            .await?;
+        if let (Some(limiter), Some(usage)) = (&self.rate_limiter, response.usage) {
+            limiter.settle(self.provider_type, reserved, usage.total_tokens());
+        }
+        Ok(response)
+    }
+
+    /// Invoke the LLM and stream the response as it is generated
+    ///
+    /// Providers without streaming support yield the full response as a single
+    /// chunk (see [`LlmProvider::supports_streaming`]).
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use fortified_llm_client::{LlmClient, InvokeParams};
+    /// # use futures::StreamExt;
+    /// # async fn example(params: InvokeParams<'_>) -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = LlmClient::new("http://localhost:11434/v1/chat/completions".to_string(), None);
+    ///
+    /// let mut chunks = client.invoke_stream(params).await?;
+    /// while let Some(chunk) = chunks.next().await {
+    ///     print!("{}", chunk?);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn invoke_stream(&self, params: InvokeParams<'_>) -> Result<ChunkStream, CliError> {
+        let span = self.span(&params, true);
+        self.acquire(&params).await;
+        let stream = self
+            .provider
+            .invoke_stream(params)
+            .instrument(span.clone())
+            .await?;
+        // The span closes when the stream is dropped
+        Ok(Box::pin(stream.map(move |chunk| {
+            let _entered = span.enter();
+            chunk
+        })))
+    }
+
+    /// Wait for the rate limiter, returning the tokens reserved
+    async fn acquire(&self, params: &InvokeParams<'_>) -> usize {
+        let Some(limiter) = &self.rate_limiter else {
+            return 0;
+        };
+        let tokens = RateLimiter::reserved_tokens(params);
+        limiter.acquire(self.provider_type, tokens).await;
+        tokens
+    }
+
+    /// Span around one LLM call
+    fn span(&self, params: &InvokeParams<'_>, streaming: bool) -> tracing::Span {
+        tracing::info_span!(
+            "llm.invoke",
+            model = params.model,
+            provider = self.provider.name(),
+            streaming,
+        )
     }
 }
@@ -0,0 +1,174 @@
+//! Multi-model comparison
+//!
+//! Runs one prompt against several models of the same endpoint concurrently
+//! and reports each model's response, latency, token counts and guardrail
+//! outcome side by side:
+//!
+//! ```text
+//! fortified-llm-client --config-file config.toml --compare-models llama3,mistral,qwen2
+//! ```
+//!
+//! Every other setting (prompts, guardrails, sampling parameters) is shared,
+//! so differences in the report come from the models alone.
+
+use crate::{
+    evaluate_batch, experiment::response_text, model_registry, CliError, CliOutput,
+    EvaluationConfig, EvaluationOutcome,
+};
+use serde::Serialize;
+use std::collections::HashSet;
+
+/// Outcome of the prompt on one model
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ModelComparison {
+    pub model: String,
+    /// "success", "error" or "refusal"
+    pub status: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub outcome: Option<EvaluationOutcome>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub response: Option<serde_json::Value>,
+    pub response_chars: usize,
+    pub latency_ms: u64,
+    pub tokens_estimated: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tokens_prompt_actual: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tokens_completion_actual: Option<u64>,
+    /// Direction (`input` or `output`) of the guardrail block, if any
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub blocked: Option<String>,
+    /// Rule IDs of the blocking violations
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub violation_rules: Vec<String>,
+    /// Codes of the non-blocking warnings
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub quality_score: Option<f32>,
+}
+
+impl ModelComparison {
+    fn from_output(output: &CliOutput) -> Self {
+        let metadata = &output.metadata;
+        Self {
+            model: metadata.model.clone(),
+            status: output.status.clone(),
+            outcome: output.outcome().cloned(),
+            error: output.error.as_ref().map(|e| e.message.clone()),
+            response: output.response.clone(),
+            response_chars: response_text(output).map_or(0, |text| text.chars().count()),
+            latency_ms: metadata.latency_ms,
+            tokens_estimated: metadata.tokens_estimated,
+            tokens_prompt_actual: metadata.tokens_prompt_actual,
+            tokens_completion_actual: metadata.tokens_completion_actual,
+            blocked: output.blocked_direction().map(str::to_string),
+            violation_rules: metadata.violation_rules.clone(),
+            warnings: metadata.warnings.iter().map(|w| w.code.clone()).collect(),
+            quality_score: metadata.quality_score,
+        }
+    }
+}
+
+/// Result of [`evaluate_compare`]
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CompareReport {
+    /// One entry per model, in the requested order
+    pub models: Vec<ModelComparison>,
+    /// Successful model with the lowest latency
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fastest: Option<String>,
+    /// Wall time of the comparison (models run concurrently)
+    pub elapsed_ms: u64,
+}
+
+/// At least two distinct, non-empty model names
+pub fn validate_compare_models(models: &[String]) -> Result<(), CliError> {
+    if models.len() < 2 {
+        return Err(CliError::InvalidArguments(
+            "Model comparison needs at least two models".to_string(),
+        ));
+    }
+    if models.iter().any(|m| m.trim().is_empty()) {
+        return Err(CliError::InvalidArguments(
+            "Model comparison: model names must not be empty".to_string(),
+        ));
+    }
+    let mut seen = HashSet::new();
+    if let Some(duplicate) = models.iter().find(|m| !seen.insert(m.as_str())) {
+        return Err(CliError::InvalidArguments(format!(
+            "Model comparison: '{duplicate}' is listed more than once"
+        )));
+    }
+    Ok(())
+}
+
+/// Run `base`'s prompt on each of `models` concurrently and compare them
+///
+/// Each run uses `base` with only the model replaced. A context limit
+/// auto-detected for `base.model` is looked up again for each model; an
+/// explicit limit applies to all of them. Failures of one model are reported
+/// in its entry and don't stop the others.
+pub async fn evaluate_compare(
+    base: &EvaluationConfig,
+    models: &[String],
+) -> Result<CompareReport, CliError> {
+    validate_compare_models(models)?;
+    let registry_limit =
+        |model: &str| model_registry::lookup_model(model).map(|info| info.context_window);
+    let auto_detected =
+        base.context_limit.is_some() && base.context_limit == registry_limit(&base.model);
+    let configs = models
+        .iter()
+        .map(|model| {
+            let mut config = base.clone();
+            config.model = model.clone();
+            if auto_detected {
+                config.context_limit = registry_limit(model);
+            }
+            config
+        })
+        .collect();
+
+    log::info!("Comparing {} model(s)", models.len());
+    let batch = evaluate_batch(configs, models.len()).await;
+
+    let models: Vec<ModelComparison> = batch
+        .results
+        .iter()
+        .map(ModelComparison::from_output)
+        .collect();
+    let fastest = models
+        .iter()
+        .filter(|m| m.status == "success")
+        .min_by_key(|m| m.latency_ms)
+        .map(|m| m.model.clone());
+    Ok(CompareReport {
+        models,
+        fastest,
+        elapsed_ms: batch.summary.elapsed_ms,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(models: &[&str]) -> Vec<String> {
+        models.iter().map(|m| m.to_string()).collect()
+    }
+
+    #[test]
+    fn test_validate_compare_models() {
+        assert!(validate_compare_models(&names(&["llama3", "mistral"])).is_ok());
+
+        let err = validate_compare_models(&names(&["llama3"])).unwrap_err();
+        assert!(err.to_string().contains("at least two"), "{err}");
+        let err = validate_compare_models(&names(&["llama3", " "])).unwrap_err();
+        assert!(err.to_string().contains("empty"), "{err}");
+        let err = validate_compare_models(&names(&["llama3", "mistral", "llama3"])).unwrap_err();
+        assert!(err.to_string().contains("'llama3'"), "{err}");
+    }
+}
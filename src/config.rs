@@ -1,5 +1,31 @@
-use crate::{error::CliError, guardrails::GuardrailConfig};
+use crate::{
+    audit::PromptAuditConfig,
+    cache::CacheConfig,
+    chunking::ChunkingConfig,
+    context_probe::ContextProbeConfig,
+    data_policy::DataClassification,
+    deadline::DeadlineConfig,
+    error::CliError,
+    experiment::ExperimentConfig,
+    fetch::FetchConfig,
+    guardrails::{GuardBudgetConfig, GuardrailConfig, OverridePolicyConfig},
+    image::ImageMode,
+    log_policy::LogContentPolicy,
+    metadata_fields::MetadataFields,
+    models::ToolDefinition,
+    prompt_library::{PromptLibrary, PromptLibraryConfig, PromptPin, PromptRef},
+    providers::BedrockConfig,
+    rate_limiter::RateLimitConfig,
+    refusal::RefusalConfig,
+    repetition::RepetitionConfig,
+    secrets::SecretSource,
+    spotlight::SpotlightConfig,
+    sweep::SweepConfig,
+    trends::TrendStatsConfig,
+};
+use figment::{providers::Env, Figment};
 use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
 use std::{fs, path::Path};
 
 /// Configuration file request format (supports both JSON and TOML)
@@ -11,7 +37,7 @@ pub struct ConfigFileRequest {
     /// Model name/identifier
     pub model: String,
 
-    /// Provider type (optional: "ollama" or "openai", auto-detected if not specified)
+    /// Provider type (optional: "ollama", "openai", "anthropic", "bedrock" or "mock", auto-detected if not specified)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub provider: Option<String>,
 
@@ -23,6 +49,11 @@ pub struct ConfigFileRequest {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub api_key_name: Option<String>,
 
+    /// Env, file, keyring or vault reference holding the API key (optional,
+    /// conflicts with api_key and api_key_name)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api_key_secret: Option<SecretSource>,
+
     /// System prompt inline text (conflicts with system_prompt_file)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub system_prompt: Option<String>,
@@ -31,6 +62,17 @@ pub struct ConfigFileRequest {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub system_prompt_file: Option<String>,
 
+    /// System prompt fetched from an allowlisted prompt library URL (conflicts
+    /// with system_prompt and system_prompt_file, see fetch.allowed_hosts)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub system_prompt_url: Option<String>,
+
+    /// System prompt from the prompt library: "name", "name@version" or a
+    /// table pinning its `hash` (conflicts with the other system prompt
+    /// sources, requires `[prompts]`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub system_prompt_ref: Option<PromptRef>,
+
     /// User prompt inline text (conflicts with user_prompt_file)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub user_prompt: Option<String>,
@@ -43,9 +85,105 @@ pub struct ConfigFileRequest {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub pdf_file: Option<String>,
 
-    /// Sampling temperature (optional, default: 0.0)
-    #[serde(default = "default_temperature")]
-    pub temperature: f32,
+    /// Web page to fetch as the user prompt (conflicts with the other user prompt sources)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user_prompt_url: Option<String>,
+
+    /// User prompt from the prompt library (like system_prompt_ref, conflicts
+    /// with the other user prompt sources)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user_prompt_ref: Option<PromptRef>,
+
+    /// Prompt library used by system_prompt_ref and user_prompt_ref (optional)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prompts: Option<PromptLibraryConfig>,
+
+    /// Library prompts the references resolved to (set by resolve_file_paths)
+    #[serde(skip)]
+    pub prompt_pins: Vec<PromptPin>,
+
+    /// Image sent with the user prompt (optional, see image_mode)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub image_file: Option<String>,
+
+    /// How the image reaches the model: "ocr" (default) or "vision" (optional)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub image_mode: Option<ImageMode>,
+
+    /// Files appended to the user prompt as untrusted content (optional)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub context_files: Vec<String>,
+
+    /// Text prepended to the user prompt after extraction, before guardrails (optional)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user_prompt_prefix: Option<String>,
+
+    /// Text appended to the user prompt after extraction, before guardrails (optional)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user_prompt_suffix: Option<String>,
+
+    /// Spotlighting of untrusted content (optional, e.g. PDF text)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub spotlighting: Option<SpotlightConfig>,
+
+    /// Refusal detection on responses (optional)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub refusal_detection: Option<RefusalConfig>,
+
+    /// Repetition loop detection on responses (optional)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub repetition_detection: Option<RepetitionConfig>,
+
+    /// Chunked evaluation of documents exceeding the context window (optional)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub chunking: Option<ChunkingConfig>,
+
+    /// Limits, host allowlist and SSRF policy for user_prompt_url and
+    /// system_prompt_url (optional)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fetch: Option<FetchConfig>,
+
+    /// Evaluation deadline and guardrails skippable to meet it (optional)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub deadline: Option<DeadlineConfig>,
+
+    /// Bedrock region and credentials (optional, defaults to the AWS environment)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bedrock: Option<BedrockConfig>,
+
+    /// Functions the model may call (optional, `[[tools]]` entries)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tools: Vec<ToolDefinition>,
+
+    /// Evaluation tags echoed in output metadata (optional, e.g. ["experiment=A"])
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+
+    /// Sensitivity of the content (optional: "public" or "restricted")
+    /// Restricted content is only sent to local or allowlisted endpoints
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data_classification: Option<DataClassification>,
+
+    /// External hosts trusted with restricted content (optional, `*.` for subdomains)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub allowed_external_hosts: Vec<String>,
+
+    /// Metadata fields kept in outputs (optional: `include`/`exclude` field or group names)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metadata_fields: Option<MetadataFields>,
+
+    /// Parameter preset (optional: "deterministic", "balanced", or "creative")
+    /// Expands to temperature/top_p/seed defaults; explicit values take precedence
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub preset: Option<String>,
+
+    /// Sampling temperature (optional, default: preset value or 0.0)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f32>,
+
+    /// Nucleus sampling probability mass (optional, default: preset value or provider default)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub top_p: Option<f32>,
 
     /// Maximum response tokens (optional, default: None = model's maximum)
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -67,6 +205,10 @@ pub struct ConfigFileRequest {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub context_limit: Option<usize>,
 
+    /// Context usage percent that triggers a headroom warning (optional, default: 90)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub context_warning_percent: Option<u8>,
+
     /// Response format (optional: "text", "json-object", or "json-schema")
     #[serde(skip_serializing_if = "Option::is_none")]
     pub response_format: Option<String>,
@@ -82,10 +224,55 @@ pub struct ConfigFileRequest {
     /// Guardrail configuration (optional)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub guardrails: Option<GuardrailConfig>,
-}
 
-fn default_temperature() -> f32 {
-    0.0
+    /// Run the guardrail canary self-test before evaluating (optional, default: false)
+    #[serde(default)]
+    pub self_test: bool,
+
+    /// Content allowed in log lines: never, truncated[:N] or full (optional,
+    /// process-wide, applied by main.rs via `set_log_content_policy`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub log_content_policy: Option<LogContentPolicy>,
+
+    /// Maximum response body size in bytes (optional, default 20MB)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_response_bytes: Option<usize>,
+
+    /// Guardrail override policy (optional, enables --override-token)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub guardrail_override: Option<OverridePolicyConfig>,
+
+    /// Prompt audit log with content-addressed payload storage (optional)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prompt_audit: Option<PromptAuditConfig>,
+
+    /// Token budgets of model-backed guardrails (optional)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub guard_budget: Option<GuardBudgetConfig>,
+
+    /// Query the provider for unknown models' context windows (optional)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub context_probe: Option<ContextProbeConfig>,
+
+    /// Local store of quality/refusal/violation trends across runs (optional)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub trend_stats: Option<TrendStatsConfig>,
+
+    /// Response cache for repeated requests (optional)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cache: Option<CacheConfig>,
+
+    /// Client-side request/token rate limits per provider (optional)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rate_limit: Option<RateLimitConfig>,
+
+    /// Prompt variant experiment run with --experiment (optional)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub experiment: Option<ExperimentConfig>,
+
+    /// Sampling parameter sweep run with --sweep (optional)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sweep: Option<SweepConfig>,
 }
 
 fn default_timeout() -> u64 {
@@ -103,8 +290,37 @@ impl ConfigFileRequest {
                 "Config file cannot specify both 'api_key' and 'api_key_name'".to_string(),
             ));
         }
+        if let Some(source) = &self.api_key_secret {
+            if self.api_key.is_some() || self.api_key_name.is_some() {
+                return Err(CliError::InvalidArguments(
+                    "Config file cannot specify 'api_key_secret' with 'api_key' or 'api_key_name'"
+                        .to_string(),
+                ));
+            }
+            source.validate().map_err(|reason| {
+                CliError::InvalidArguments(format!("Config file 'api_key_secret': {reason}"))
+            })?;
+        }
 
-        // Validate and resolve system prompt
+        // Validate and resolve system prompt (a URL is fetched at evaluation time)
+        if self.system_prompt_url.is_some()
+            && (self.system_prompt.is_some() || self.system_prompt_file.is_some())
+        {
+            return Err(CliError::InvalidArguments(
+                "Config file cannot specify 'system_prompt_url' with 'system_prompt' or 'system_prompt_file'"
+                    .to_string(),
+            ));
+        }
+        if self.system_prompt_ref.is_some()
+            && (self.system_prompt.is_some()
+                || self.system_prompt_file.is_some()
+                || self.system_prompt_url.is_some())
+        {
+            return Err(CliError::InvalidArguments(
+                "Config file cannot specify 'system_prompt_ref' with 'system_prompt', 'system_prompt_file' or 'system_prompt_url'"
+                    .to_string(),
+            ));
+        }
         match (&self.system_prompt, &self.system_prompt_file) {
             (Some(_), Some(_)) => {
                 return Err(CliError::InvalidArguments(
@@ -122,12 +338,17 @@ impl ConfigFileRequest {
                 self.system_prompt = Some(content);
                 // Keep file path for metadata tracking (don't clear it)
             }
-            (None, None) => {
+            (None, None)
+                if self.system_prompt_url.is_none() && self.system_prompt_ref.is_none() =>
+            {
                 return Err(CliError::InvalidArguments(
-                    "Config file must specify either 'system_prompt' or 'system_prompt_file'"
+                    "Config file must specify one of 'system_prompt', 'system_prompt_file', 'system_prompt_url' or 'system_prompt_ref'"
                         .to_string(),
                 ));
             }
+            (None, None) => {
+                // Fetched by the extract stage or resolved from the prompt library below
+            }
             (Some(_), None) => {
                 // Inline text provided, all good
             }
@@ -138,13 +359,15 @@ impl ConfigFileRequest {
             self.user_prompt.as_ref().map(|_| 1).unwrap_or(0),
             self.user_prompt_file.as_ref().map(|_| 1).unwrap_or(0),
             self.pdf_file.as_ref().map(|_| 1).unwrap_or(0),
+            self.user_prompt_url.as_ref().map(|_| 1).unwrap_or(0),
+            self.user_prompt_ref.as_ref().map(|_| 1).unwrap_or(0),
         ]
         .iter()
         .sum::<usize>();
 
         if user_prompt_count > 1 {
             return Err(CliError::InvalidArguments(
-                "Config file cannot specify more than one of: 'user_prompt', 'user_prompt_file', 'pdf_file'".to_string()
+                "Config file cannot specify more than one of: 'user_prompt', 'user_prompt_file', 'pdf_file', 'user_prompt_url', 'user_prompt_ref'".to_string()
             ));
         }
 
@@ -162,13 +385,83 @@ impl ConfigFileRequest {
         // Note: pdf_file validation happens in ConfigBuilder, not here
         // (PDF extraction is done later, we just need the path)
 
+        self.resolve_prompt_refs()
+    }
+
+    /// Load `*_prompt_ref` prompts from the `[prompts]` directory, keeping
+    /// the prompt file path for metadata like `*_prompt_file`
+    fn resolve_prompt_refs(&mut self) -> Result<(), CliError> {
+        if self.system_prompt_ref.is_none() && self.user_prompt_ref.is_none() {
+            return Ok(());
+        }
+        let Some(prompts) = &self.prompts else {
+            return Err(CliError::InvalidArguments(
+                "Config file 'system_prompt_ref' and 'user_prompt_ref' require a [prompts] section with 'dir'"
+                    .to_string(),
+            ));
+        };
+        let library = PromptLibrary::new(&prompts.dir);
+
+        if let Some(prompt_ref) = &self.system_prompt_ref {
+            let (prompt, pin) = library.resolve("system", prompt_ref)?;
+            self.system_prompt = Some(prompt.text);
+            self.system_prompt_file = Some(prompt.file.display().to_string());
+            self.prompt_pins.push(pin);
+        }
+        if let Some(prompt_ref) = &self.user_prompt_ref {
+            let (prompt, pin) = library.resolve("user", prompt_ref)?;
+            self.user_prompt = Some(prompt.text);
+            self.user_prompt_file = Some(prompt.file.display().to_string());
+            self.prompt_pins.push(pin);
+        }
         Ok(())
     }
 }
 
-/// Load config from file (auto-detects JSON vs TOML from extension)
-pub fn load_config_file<P: AsRef<Path>>(path: P) -> Result<ConfigFileRequest, CliError> {
-    let path = path.as_ref();
+/// Section whose values every profile inherits
+pub const DEFAULT_SECTION: &str = "default";
+
+/// Table of named profiles (`[profiles.<name>]`)
+pub const PROFILES_SECTION: &str = "profiles";
+
+/// Config file syntax, from the file extension
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ConfigFormat {
+    Json,
+    Toml,
+}
+
+impl ConfigFormat {
+    fn from_path(path: &Path) -> Result<Self, CliError> {
+        match path.extension().and_then(|s| s.to_str()) {
+            Some("json") => Ok(Self::Json),
+            Some("toml") => Ok(Self::Toml),
+            _ => Err(CliError::InvalidArguments(
+                "Config file must have .json or .toml extension".to_string(),
+            )),
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::Json => "JSON",
+            Self::Toml => "TOML",
+        }
+    }
+
+    fn parse<T: serde::de::DeserializeOwned>(self, contents: &str) -> Result<T, CliError> {
+        let parsed = match self {
+            Self::Json => serde_json::from_str(contents).map_err(|e| e.to_string()),
+            Self::Toml => toml::from_str(contents).map_err(|e| e.to_string()),
+        };
+        parsed.map_err(|e| {
+            CliError::InvalidArguments(format!("Failed to parse {} config: {e}", self.name()))
+        })
+    }
+}
+
+fn read_config(path: &Path) -> Result<(ConfigFormat, String), CliError> {
+    let format = ConfigFormat::from_path(path)?;
     let contents = fs::read_to_string(path).map_err(|e| {
         CliError::FileNotFound(format!(
             "Failed to read config file '{}': {}",
@@ -176,22 +469,154 @@ pub fn load_config_file<P: AsRef<Path>>(path: P) -> Result<ConfigFileRequest, Cl
             e
         ))
     })?;
+    Ok((format, contents))
+}
+
+/// Whether a parsed config file uses `[default]` or `[profiles.*]` sections
+fn has_profile_sections(value: &Value) -> bool {
+    value.as_object().is_some_and(|root| {
+        root.contains_key(DEFAULT_SECTION) || root.contains_key(PROFILES_SECTION)
+    })
+}
 
-    // Auto-detect format from extension and parse
-    let mut config: ConfigFileRequest = if path.extension().and_then(|s| s.to_str()) == Some("toml")
-    {
-        // Parse TOML
-        toml::from_str(&contents)
-            .map_err(|e| CliError::InvalidArguments(format!("Failed to parse TOML config: {e}")))?
-    } else if path.extension().and_then(|s| s.to_str()) == Some("json") {
-        // Parse JSON
-        serde_json::from_str(&contents)
-            .map_err(|e| CliError::InvalidArguments(format!("Failed to parse JSON config: {e}")))?
-    } else {
+/// Flatten the profile sections of a parsed config file
+///
+/// Values are layered top-level < `[default]` < `[profiles.<profile>]`;
+/// tables are merged key by key, other values (including arrays) replaced.
+/// Without `profile`, only `[default]` applies.
+///
+/// # Errors
+///
+/// Returns `CliError::InvalidArguments` if `profile` is not defined in the
+/// file, listing the profiles that are.
+pub fn resolve_profile(value: Value, profile: Option<&str>) -> Result<Value, CliError> {
+    let Value::Object(mut root) = value else {
         return Err(CliError::InvalidArguments(
-            "Config file must have .json or .toml extension".to_string(),
+            "Config file must contain a table of settings".to_string(),
         ));
     };
+    let default = root.remove(DEFAULT_SECTION);
+    let profiles = match root.remove(PROFILES_SECTION) {
+        None => Map::new(),
+        Some(Value::Object(profiles)) => profiles,
+        Some(_) => {
+            return Err(CliError::InvalidArguments(format!(
+                "Config file '{PROFILES_SECTION}' must be a table of named profiles"
+            )))
+        }
+    };
+
+    let mut resolved = Value::Object(root);
+    if let Some(default) = default {
+        merge_values(&mut resolved, default);
+    }
+    if let Some(name) = profile {
+        let Some(overrides) = profiles.get(name) else {
+            let available: Vec<&str> = profiles.keys().map(String::as_str).collect();
+            return Err(CliError::InvalidArguments(format!(
+                "Unknown profile '{name}'. Profiles in the config file: {}",
+                if available.is_empty() {
+                    "none".to_string()
+                } else {
+                    available.join(", ")
+                }
+            )));
+        };
+        merge_values(&mut resolved, overrides.clone());
+    }
+    Ok(resolved)
+}
+
+/// Overlay `overrides` on `base`, merging nested tables
+pub fn merge_values(base: &mut Value, overrides: Value) {
+    match (base, overrides) {
+        (Value::Object(base), Value::Object(overrides)) => {
+            for (key, value) in overrides {
+                match base.get_mut(&key) {
+                    Some(existing) => merge_values(existing, value),
+                    None => {
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        (base, overrides) => *base = overrides,
+    }
+}
+
+/// Parse a config file with `profile` applied (see [`resolve_profile`])
+///
+/// Used to merge the file with CLI arguments; the result still holds the
+/// `*_file` paths unresolved.
+pub fn read_config_value<P: AsRef<Path>>(
+    path: P,
+    profile: Option<&str>,
+) -> Result<Value, CliError> {
+    let (format, contents) = read_config(path.as_ref())?;
+    resolve_profile(format.parse(&contents)?, profile)
+}
+
+/// Load config from file (auto-detects JSON vs TOML from extension)
+///
+/// `[default]` values apply; `[profiles.*]` are ignored. See
+/// [`load_config_file_with_profile`] to select a profile.
+pub fn load_config_file<P: AsRef<Path>>(path: P) -> Result<ConfigFileRequest, CliError> {
+    load_config_file_with_profile(path, None)
+}
+
+/// Load config from file with a named profile applied over `[default]`
+pub fn load_config_file_with_profile<P: AsRef<Path>>(
+    path: P,
+    profile: Option<&str>,
+) -> Result<ConfigFileRequest, CliError> {
+    load_config_layers(Some(path.as_ref()), profile, Map::new())
+}
+
+/// Load a config file with `overrides` applied over its resolved profile
+///
+/// `overrides` are nested like the file (e.g. [`env_config_value`]); tables
+/// merge key by key. Without a file, `overrides` alone make the config and
+/// must set `api_url`, `model` and a system prompt like a file would.
+pub fn load_config_layers(
+    path: Option<&Path>,
+    profile: Option<&str>,
+    overrides: Map<String, Value>,
+) -> Result<ConfigFileRequest, CliError> {
+    let mut config: ConfigFileRequest = match path {
+        Some(path) => {
+            let (format, contents) = read_config(path)?;
+            let value: Value = format.parse(&contents)?;
+
+            // Plain files are deserialized from the text, keeping line numbers
+            // in error messages
+            if profile.is_none() && overrides.is_empty() && !has_profile_sections(&value) {
+                format.parse(&contents)?
+            } else {
+                let mut value = resolve_profile(value, profile)?;
+                let overridden = !overrides.is_empty();
+                merge_values(&mut value, Value::Object(overrides));
+                serde_json::from_value(value).map_err(|e| {
+                    CliError::InvalidArguments(format!(
+                        "Failed to parse {} config{}{}: {e}",
+                        format.name(),
+                        profile
+                            .map(|p| format!(" (profile '{p}')"))
+                            .unwrap_or_default(),
+                        if overridden {
+                            format!(" with {ENV_PREFIX}* overrides")
+                        } else {
+                            String::new()
+                        }
+                    ))
+                })?
+            }
+        }
+        None => serde_json::from_value(Value::Object(overrides)).map_err(|e| {
+            CliError::InvalidArguments(format!(
+                "Failed to parse {ENV_PREFIX}* environment config: {e}"
+            ))
+        })?,
+    };
 
     // Resolve file paths to actual content
     config.resolve_file_paths()?;
@@ -199,6 +624,30 @@ pub fn load_config_file<P: AsRef<Path>>(path: P) -> Result<ConfigFileRequest, Cl
     Ok(config)
 }
 
+/// Prefix of the environment variables setting config keys
+pub const ENV_PREFIX: &str = "SLLM_";
+
+/// Figment provider of `SLLM_*` variables
+///
+/// Names are lowercased and `__` separates nested keys: `SLLM_MODEL` sets
+/// `model`, `SLLM_GUARDRAILS__INPUT__TYPE` sets `guardrails.input.type`.
+/// Values are parsed loosely (`0.5`, `true`, `[a, b]`, `{k = v}`); quote
+/// them to keep a string (`SLLM_MODEL='"7"'`).
+pub fn env_provider() -> Env {
+    Env::prefixed(ENV_PREFIX).split("__")
+}
+
+/// Config values set by `SLLM_*` variables, nested like a config file
+pub fn env_config_value() -> Result<Map<String, Value>, CliError> {
+    match Figment::from(env_provider()).extract::<Value>() {
+        Ok(Value::Object(values)) => Ok(values),
+        Ok(_) => Ok(Map::new()),
+        Err(e) => Err(CliError::InvalidArguments(format!(
+            "Invalid {ENV_PREFIX}* environment variable: {e}"
+        ))),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -222,7 +671,7 @@ mod tests {
         let config = load_config_file(&path).unwrap();
         assert_eq!(config.api_url, "http://localhost:11434/api/generate");
         assert_eq!(config.model, "llama3");
-        assert_eq!(config.temperature, 0.5);
+        assert_eq!(config.temperature, Some(0.5));
         assert_eq!(config.max_tokens, Some(1000));
 
         std::fs::remove_file(&path).ok();
@@ -246,7 +695,7 @@ mod tests {
         let config = load_config_file(&path).unwrap();
         assert_eq!(config.api_url, "http://localhost:11434/api/generate");
         assert_eq!(config.model, "llama3");
-        assert_eq!(config.temperature, 0.5);
+        assert_eq!(config.temperature, Some(0.5));
         assert_eq!(config.max_tokens, Some(1000));
 
         std::fs::remove_file(&path).ok();
@@ -282,7 +731,7 @@ mod tests {
         std::fs::write(&path, json).unwrap();
 
         let config = load_config_file(&path).unwrap();
-        assert_eq!(config.temperature, 0.0); // default
+        assert_eq!(config.temperature, None); // default applied by ConfigBuilder
         assert_eq!(config.max_tokens, None); // default (use model's maximum)
         assert_eq!(config.timeout_secs, 300); // default
         assert!(!config.validate_tokens); // default
@@ -310,4 +759,72 @@ mod tests {
 
         std::fs::remove_file(&path).ok();
     }
+
+    const PROFILES_TOML: &str = r#"
+        system_prompt = "You are helpful."
+        user_prompt = "Hello"
+
+        [default]
+        api_url = "http://localhost:11434/api/generate"
+        model = "llama3"
+        temperature = 0.5
+        tags = ["env=dev"]
+
+        [default.fetch]
+        timeout_secs = 10
+        max_bytes = 1000
+
+        [profiles.prod]
+        api_url = "https://llm.example.com/v1/chat/completions"
+        model = "gpt-4"
+        tags = ["env=prod"]
+
+        [profiles.prod.fetch]
+        max_bytes = 5000
+    "#;
+
+    #[test]
+    fn test_profile_inherits_default() {
+        let file = NamedTempFile::new().unwrap();
+        let path = file.path().with_extension("toml");
+        std::fs::write(&path, PROFILES_TOML).unwrap();
+
+        // Without a profile, [default] applies over the top-level values
+        let config = load_config_file(&path).unwrap();
+        assert_eq!(config.model, "llama3");
+        assert_eq!(config.tags, vec!["env=dev"]);
+
+        let config = load_config_file_with_profile(&path, Some("prod")).unwrap();
+        assert_eq!(
+            config.api_url,
+            "https://llm.example.com/v1/chat/completions"
+        );
+        assert_eq!(config.model, "gpt-4");
+        assert_eq!(config.temperature, Some(0.5));
+        assert_eq!(config.system_prompt, Some("You are helpful.".to_string()));
+        assert_eq!(config.tags, vec!["env=prod"]);
+        // Tables merge key by key
+        let fetch = config.fetch.unwrap();
+        assert_eq!(fetch.timeout_secs, 10);
+        assert_eq!(fetch.max_bytes, 5000);
+
+        let err = load_config_file_with_profile(&path, Some("staging")).unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("Unknown profile 'staging'. Profiles in the config file: prod"),
+            "{err}"
+        );
+
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn test_profile_in_file_without_profiles() {
+        let value = serde_json::json!({"model": "llama3"});
+        let err = resolve_profile(value.clone(), Some("prod")).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("Profiles in the config file: none"));
+        assert_eq!(resolve_profile(value.clone(), None).unwrap(), value);
+    }
 }
@@ -4,16 +4,125 @@
 //! Follows the Builder pattern for testability and reusability.
 
 use crate::{
-    config::ConfigFileRequest, constants::llm_defaults, error::CliError, model_registry,
-    schema_validator, EvaluationConfig, Provider, ResponseFormat,
+    audit::{blob_ref, PromptAuditor},
+    cache::ResponseCache,
+    chunking::ChunkingConfig,
+    config::ConfigFileRequest,
+    constants::{http, llm_defaults},
+    context_probe::ContextProber,
+    data_policy::{DataClassification, DataPolicy},
+    deadline::DeadlineConfig,
+    error::CliError,
+    fetch::{self, FetchConfig},
+    image::ImageMode,
+    metadata_fields::MetadataFields,
+    metrics::MetricsRegistry,
+    model_registry,
+    prompt_library::PromptPin,
+    providers::BedrockConfig,
+    rate_limiter::RateLimiter,
+    refusal::RefusalConfig,
+    repetition::RepetitionConfig,
+    schema_validator,
+    spotlight::SpotlightConfig,
+    trends::TrendStore,
+    EvaluationConfig, GuardBudget, GuardrailOverride, Provider, ResponseFormat, ToolDefinition,
 };
-use std::path::PathBuf;
+use serde::{Deserialize, Serialize};
+use std::{fmt, path::PathBuf, str::FromStr, sync::Arc};
 
 /// Minimum values for validation
 const MIN_TOKENS: u32 = 1;
 const MIN_TIMEOUT: u64 = 1;
 const MIN_CONTEXT_LIMIT: usize = 100;
 
+/// Non-fatal configuration problem detected while merging CLI args and config files
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ConfigWarning {
+    /// Config field the warning is about (e.g. "provider", "response_format")
+    pub field: String,
+    pub message: String,
+}
+
+/// Named sampling presets, standardizing settings across config files
+///
+/// A preset only fills in values that were not set explicitly (CLI or config).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ParameterPreset {
+    /// temperature 0.0, top_p 1.0, fixed seed (reproducible outputs)
+    Deterministic,
+    /// temperature 0.7, top_p 0.9
+    Balanced,
+    /// temperature 1.0, top_p 0.95
+    Creative,
+}
+
+impl ParameterPreset {
+    /// Seed used by the deterministic preset
+    pub const DETERMINISTIC_SEED: u64 = 42;
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Deterministic => "deterministic",
+            Self::Balanced => "balanced",
+            Self::Creative => "creative",
+        }
+    }
+
+    pub fn temperature(self) -> f32 {
+        match self {
+            Self::Deterministic => 0.0,
+            Self::Balanced => 0.7,
+            Self::Creative => 1.0,
+        }
+    }
+
+    pub fn top_p(self) -> f32 {
+        match self {
+            Self::Deterministic => 1.0,
+            Self::Balanced => 0.9,
+            Self::Creative => 0.95,
+        }
+    }
+
+    pub fn seed(self) -> Option<u64> {
+        match self {
+            Self::Deterministic => Some(Self::DETERMINISTIC_SEED),
+            Self::Balanced | Self::Creative => None,
+        }
+    }
+}
+
+impl fmt::Display for ParameterPreset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for ParameterPreset {
+    type Err = CliError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "deterministic" => Ok(Self::Deterministic),
+            "balanced" => Ok(Self::Balanced),
+            "creative" => Ok(Self::Creative),
+            _ => Err(CliError::InvalidArguments(format!(
+                "Unknown preset '{s}'. Valid values: 'deterministic', 'balanced', 'creative'"
+            ))),
+        }
+    }
+}
+
+/// Problem that would make [`ConfigBuilder::build`] fail
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ValidationIssue {
+    /// Config field the problem is about (e.g. "model", "temperature")
+    pub field: String,
+    pub message: String,
+}
+
 /// Builder for constructing EvaluationConfig from CLI args and config files
 ///
 /// Handles merging of CLI arguments (highest priority), config file values (medium priority),
@@ -28,21 +137,81 @@ pub struct ConfigBuilder {
 
     // Optional fields (CLI > config > default)
     pub provider: Option<Provider>,
+    pub preset: Option<ParameterPreset>,
     pub temperature: Option<f32>,
+    pub top_p: Option<f32>,
     pub max_tokens: Option<u32>,
     pub seed: Option<u64>,
     pub api_key: Option<String>,
     pub timeout_secs: Option<u64>,
+    pub max_response_bytes: Option<usize>,
     pub validate_tokens: Option<bool>,
     pub context_limit: Option<usize>,
+    pub context_probe: Option<Arc<ContextProber>>,
+    pub context_warning_percent: Option<u8>,
     pub response_format: Option<ResponseFormat>,
+    pub tools: Vec<ToolDefinition>,
     pub pdf_input: Option<PathBuf>,
+    pub url_input: Option<String>,
+    pub system_prompt_url: Option<String>,
+    pub fetch: Option<FetchConfig>,
+    pub image_input: Option<PathBuf>,
+    pub image_mode: Option<ImageMode>,
+    pub user_prompt_prefix: Option<String>,
+    pub user_prompt_suffix: Option<String>,
+    pub context_files: Vec<PathBuf>,
+    pub untrusted_content: Vec<String>,
+    pub spotlight: Option<SpotlightConfig>,
     pub input_guardrails: Option<crate::GuardrailProviderConfig>,
     pub output_guardrails: Option<crate::GuardrailProviderConfig>,
+    pub guardrail_conditions: Vec<crate::ConditionalGuardrail>,
+    pub input_sources: Option<crate::InputSourcesConfig>,
+    pub escalation: Option<crate::EscalationConfig>,
+    pub refusal_detection: Option<RefusalConfig>,
+    pub repetition_detection: Option<RepetitionConfig>,
+    pub chunking: Option<ChunkingConfig>,
+    pub deadline: Option<DeadlineConfig>,
+    pub bedrock: Option<BedrockConfig>,
 
     // Source tracking (for metadata reproducibility)
     pub system_prompt_file: Option<PathBuf>,
     pub user_prompt_file: Option<PathBuf>,
+    pub prompt_pins: Vec<PromptPin>,
+
+    // Guardrail override escape hatch
+    pub guardrail_override: Option<Arc<GuardrailOverride>>,
+    pub override_token: Option<String>,
+
+    // Model-backed guardrail token budget
+    pub guard_budget: Option<Arc<GuardBudget>>,
+
+    // Prompt audit trail
+    pub prompt_audit: Option<Arc<PromptAuditor>>,
+
+    // Cross-run trend statistics
+    pub trend_stats: Option<Arc<TrendStore>>,
+
+    // Response cache
+    pub response_cache: Option<Arc<ResponseCache>>,
+
+    // Client-side rate limits
+    pub rate_limiter: Option<Arc<RateLimiter>>,
+
+    // Shared evaluation metrics
+    pub metrics: Option<Arc<MetricsRegistry>>,
+
+    // Labels echoed in output metadata
+    pub tags: Vec<String>,
+
+    // Pre-flight endpoint policy
+    pub data_classification: Option<DataClassification>,
+    pub allowed_external_hosts: Vec<String>,
+
+    // Metadata fields kept in serialized outputs
+    pub metadata_fields: Option<MetadataFields>,
+
+    // Merge conflicts and ignored values, surfaced in output metadata
+    pub config_warnings: Vec<ConfigWarning>,
 }
 
 impl ConfigBuilder {
@@ -62,16 +231,20 @@ impl ConfigBuilder {
         }
         if self.provider.is_none() {
             if let Some(provider_str) = &file_config.provider {
-                // Parse provider string ("ollama", "openai")
-                match provider_str.to_lowercase().as_str() {
-                    "ollama" => self.provider = Some(Provider::Ollama),
-                    "openai" => self.provider = Some(Provider::OpenAI),
-                    _ => log::warn!("Unknown provider '{provider_str}' in config file. Valid values: 'ollama', 'openai'"),
+                // Parse provider string ("ollama", "openai", "anthropic", "bedrock", "mock")
+                match Provider::from_name(provider_str) {
+                    Some(provider) => self.provider = Some(provider),
+                    None => self.push_warning(
+                        "provider",
+                        format!("Unknown provider '{provider_str}' in config file. Valid values: 'ollama', 'openai', 'anthropic', 'bedrock', 'mock'"),
+                    ),
                 }
             }
         }
-        if self.system_prompt.is_none() {
+        // A system prompt given on the command line (text, file or URL) wins
+        if self.system_prompt.is_none() && self.system_prompt_url.is_none() {
             self.system_prompt = file_config.system_prompt.clone();
+            self.system_prompt_url = file_config.system_prompt_url.clone();
         }
         // Track if system prompt came from file (for metadata)
         if self.system_prompt_file.is_none() {
@@ -88,13 +261,107 @@ impl ConfigBuilder {
                 self.user_prompt_file = Some(PathBuf::from(file_path));
             }
         }
+        // Record the library prompts that are used (not overridden on the
+        // command line), warning when one changed since its hash was pinned
+        for pin in &file_config.prompt_pins {
+            let used = match pin.role {
+                "system" => self.system_prompt.as_deref(),
+                _ => self.user_prompt.as_deref(),
+            };
+            if used.map(|text| blob_ref(text.as_bytes())).as_deref() != Some(pin.hash.as_str()) {
+                continue;
+            }
+            if let Some(pinned) = &pin.pinned_hash {
+                let version = pin.version.as_deref().unwrap_or("(none)");
+                self.push_warning(
+                    format!("{}_prompt_ref", pin.role),
+                    format!(
+                        "Prompt '{}' version {version} ({}) no longer matches its pinned hash: pinned {pinned}, found {}",
+                        pin.name,
+                        pin.file.display(),
+                        pin.hash
+                    ),
+                );
+            }
+            self.prompt_pins.push(pin.clone());
+        }
         if self.pdf_input.is_none() {
             if let Some(pdf_path) = &file_config.pdf_file {
                 self.pdf_input = Some(PathBuf::from(pdf_path));
             }
         }
+        // A prompt given on the command line takes precedence over the file's URL
+        if self.url_input.is_none() && self.user_prompt.is_none() && self.pdf_input.is_none() {
+            self.url_input = file_config.user_prompt_url.clone();
+        }
+        if self.fetch.is_none() {
+            self.fetch = file_config.fetch.clone();
+        }
+        if self.image_input.is_none() {
+            self.image_input = file_config.image_file.as_ref().map(PathBuf::from);
+        }
+        if self.image_mode.is_none() {
+            self.image_mode = file_config.image_mode;
+        }
+        if self.context_files.is_empty() {
+            self.context_files = file_config
+                .context_files
+                .iter()
+                .map(PathBuf::from)
+                .collect();
+        }
+        if self.user_prompt_prefix.is_none() {
+            self.user_prompt_prefix = file_config.user_prompt_prefix.clone();
+        }
+        if self.user_prompt_suffix.is_none() {
+            self.user_prompt_suffix = file_config.user_prompt_suffix.clone();
+        }
+        if self.spotlight.is_none() {
+            self.spotlight = file_config.spotlighting.clone();
+        }
+        if self.refusal_detection.is_none() {
+            self.refusal_detection = file_config.refusal_detection.clone();
+        }
+        if self.repetition_detection.is_none() {
+            self.repetition_detection = file_config.repetition_detection.clone();
+        }
+        if self.chunking.is_none() {
+            self.chunking = file_config.chunking.clone();
+        }
+        if self.deadline.is_none() {
+            self.deadline = file_config.deadline.clone();
+        }
+        if self.bedrock.is_none() {
+            self.bedrock = file_config.bedrock.clone();
+        }
+        if self.tools.is_empty() {
+            self.tools = file_config.tools.clone();
+        }
+        if self.tags.is_empty() {
+            self.tags = file_config.tags.clone();
+        }
+        if self.data_classification.is_none() {
+            self.data_classification = file_config.data_classification;
+        }
+        if self.allowed_external_hosts.is_empty() {
+            self.allowed_external_hosts = file_config.allowed_external_hosts.clone();
+        }
+        if self.metadata_fields.is_none() {
+            self.metadata_fields = file_config.metadata_fields.clone();
+        }
+        if self.preset.is_none() {
+            if let Some(preset_str) = &file_config.preset {
+                match preset_str.parse() {
+                    Ok(preset) => self.preset = Some(preset),
+                    Err(e) => self.push_warning("preset", format!("{e} (in config file)")),
+                }
+            }
+        }
         if self.temperature.is_none() {
-            self.temperature = Some(file_config.temperature);
+            self.temperature = file_config.temperature;
+        }
+        if self.top_p.is_none() {
+            self.top_p = file_config.top_p;
         }
         if self.max_tokens.is_none() {
             self.max_tokens = file_config.max_tokens;
@@ -105,26 +372,51 @@ impl ConfigBuilder {
         if self.timeout_secs.is_none() {
             self.timeout_secs = Some(file_config.timeout_secs);
         }
+        if self.max_response_bytes.is_none() {
+            self.max_response_bytes = file_config.max_response_bytes;
+        }
         if self.validate_tokens.is_none() {
             self.validate_tokens = Some(file_config.validate_tokens);
         }
         if self.context_limit.is_none() {
             self.context_limit = file_config.context_limit;
         }
+        if self.context_warning_percent.is_none() {
+            self.context_warning_percent = file_config.context_warning_percent;
+        }
         if self.api_key.is_none() {
             self.api_key = file_config.api_key.clone();
         }
         if self.input_guardrails.is_none() {
-            self.input_guardrails = file_config.guardrails.as_ref().and_then(|g| {
-                // Prefer explicit input field, fallback to flattened provider field
-                g.input.clone().or_else(|| g.provider.clone())
-            });
+            // Prefer explicit input field, then flattened provider field, then preset
+            self.input_guardrails = file_config
+                .guardrails
+                .as_ref()
+                .and_then(|g| g.input_config());
         }
         if self.output_guardrails.is_none() {
-            self.output_guardrails = file_config.guardrails.as_ref().and_then(|g| {
-                // Prefer explicit output field, fallback to flattened provider field
-                g.output.clone().or_else(|| g.provider.clone())
-            });
+            // Prefer explicit output field, then flattened provider field, then preset
+            self.output_guardrails = file_config
+                .guardrails
+                .as_ref()
+                .and_then(|g| g.output_config());
+        }
+        if self.guardrail_conditions.is_empty() {
+            if let Some(guardrails) = &file_config.guardrails {
+                self.guardrail_conditions = guardrails.conditional.clone();
+            }
+        }
+        if self.input_sources.is_none() {
+            self.input_sources = file_config
+                .guardrails
+                .as_ref()
+                .and_then(|g| g.input_sources.clone());
+        }
+        if self.escalation.is_none() {
+            self.escalation = file_config
+                .guardrails
+                .as_ref()
+                .and_then(|g| g.escalation.clone());
         }
 
         // Handle response_format from config file (only if not set via CLI)
@@ -149,22 +441,29 @@ impl ConfigBuilder {
                                     self.response_format = Some(response_format);
                                 }
                                 Err(e) => {
-                                    log::warn!(
-                                        "Failed to load JSON schema from config file '{schema_path}': {e}"
+                                    self.push_warning(
+                                        "response_format_schema",
+                                        format!(
+                                            "Failed to load JSON schema from config file '{schema_path}': {e}"
+                                        ),
                                     );
                                 }
                             }
                         } else {
-                            log::warn!(
+                            self.push_warning(
+                                "response_format",
                                 "Config file specifies response_format='json-schema' but \
-                                response_format_schema is not set. Ignoring response_format."
+                                response_format_schema is not set. Ignoring response_format.",
                             );
                         }
                     }
                     _ => {
-                        log::warn!(
-                            "Unknown response_format '{format_str}' in config file. \
-                            Valid values: 'text', 'json-object', 'json-schema'"
+                        self.push_warning(
+                            "response_format",
+                            format!(
+                                "Unknown response_format '{format_str}' in config file. \
+                                Valid values: 'text', 'json-object', 'json-schema'"
+                            ),
                         );
                     }
                 }
@@ -174,6 +473,21 @@ impl ConfigBuilder {
         self
     }
 
+    /// Record a configuration warning (logged and included in output metadata)
+    pub fn warning(mut self, field: impl Into<String>, message: impl Into<String>) -> Self {
+        self.push_warning(field, message);
+        self
+    }
+
+    fn push_warning(&mut self, field: impl Into<String>, message: impl Into<String>) {
+        let warning = ConfigWarning {
+            field: field.into(),
+            message: message.into(),
+        };
+        log::warn!("{}", warning.message);
+        self.config_warnings.push(warning);
+    }
+
     /// Set API URL (highest priority - typically from CLI)
     pub fn api_url(mut self, api_url: impl Into<String>) -> Self {
         self.api_url = Some(api_url.into());
@@ -216,12 +530,24 @@ impl ConfigBuilder {
         self
     }
 
+    /// Set parameter preset (fills temperature/top_p/seed not set explicitly)
+    pub fn preset(mut self, preset: ParameterPreset) -> Self {
+        self.preset = Some(preset);
+        self
+    }
+
     /// Set temperature (validates range)
     pub fn temperature(mut self, temperature: f32) -> Self {
         self.temperature = Some(temperature);
         self
     }
 
+    /// Set nucleus sampling probability mass (validates range)
+    pub fn top_p(mut self, top_p: f32) -> Self {
+        self.top_p = Some(top_p);
+        self
+    }
+
     /// Set max tokens
     pub fn max_tokens(mut self, max_tokens: u32) -> Self {
         self.max_tokens = Some(max_tokens);
@@ -246,6 +572,12 @@ impl ConfigBuilder {
         self
     }
 
+    /// Set the maximum size in bytes of response bodies (default: 20MB)
+    pub fn max_response_bytes(mut self, limit: usize) -> Self {
+        self.max_response_bytes = Some(limit);
+        self
+    }
+
     /// Set whether to validate tokens
     pub fn validate_tokens(mut self, validate_tokens: bool) -> Self {
         self.validate_tokens = Some(validate_tokens);
@@ -258,18 +590,62 @@ impl ConfigBuilder {
         self
     }
 
+    /// Set context usage percent that triggers a headroom warning
+    pub fn context_warning_percent(mut self, percent: u8) -> Self {
+        self.context_warning_percent = Some(percent);
+        self
+    }
+
     /// Set response format
     pub fn response_format(mut self, response_format: ResponseFormat) -> Self {
         self.response_format = Some(response_format);
         self
     }
 
+    /// Offer a function the model may call (OpenAI-compatible and Ollama)
+    pub fn tool(mut self, tool: ToolDefinition) -> Self {
+        self.tools.push(tool);
+        self
+    }
+
     /// Set PDF input path (mutually exclusive with user_prompt text)
     pub fn pdf_input(mut self, pdf_path: PathBuf) -> Self {
         self.pdf_input = Some(pdf_path);
         self
     }
 
+    /// Fetch a web page as the user prompt (mutually exclusive with user_prompt text)
+    pub fn url_input(mut self, url: impl Into<String>) -> Self {
+        self.url_input = Some(url.into());
+        self
+    }
+
+    /// Fetch the system prompt from a prompt library URL (the host must be in
+    /// the fetch config's `allowed_hosts`)
+    pub fn system_prompt_url(mut self, url: impl Into<String>) -> Self {
+        self.system_prompt_url = Some(url.into());
+        self
+    }
+
+    /// Set size, redirect, host allowlist and private network limits for
+    /// `url_input` and `system_prompt_url`
+    pub fn fetch(mut self, config: FetchConfig) -> Self {
+        self.fetch = Some(config);
+        self
+    }
+
+    /// Send an image with the user prompt (OCR'd into it unless `image_mode` is vision)
+    pub fn image_input(mut self, image_path: impl Into<PathBuf>) -> Self {
+        self.image_input = Some(image_path.into());
+        self
+    }
+
+    /// Set how `image_input` reaches the model (default: OCR)
+    pub fn image_mode(mut self, mode: ImageMode) -> Self {
+        self.image_mode = Some(mode);
+        self
+    }
+
     /// Set input guardrails configuration
     pub fn input_guardrails(mut self, guardrails: crate::GuardrailProviderConfig) -> Self {
         self.input_guardrails = Some(guardrails);
@@ -282,6 +658,426 @@ impl ConfigBuilder {
         self
     }
 
+    /// Add a rule skipping or adding guardrail providers depending on the input
+    pub fn guardrail_condition(mut self, rule: crate::ConditionalGuardrail) -> Self {
+        self.guardrail_conditions.push(rule);
+        self
+    }
+
+    /// Add an evaluation tag (e.g. "experiment=A"); duplicates are ignored
+    pub fn tag(mut self, tag: impl Into<String>) -> Self {
+        let tag = tag.into();
+        if !self.tags.contains(&tag) {
+            self.tags.push(tag);
+        }
+        self
+    }
+
+    /// Add several evaluation tags
+    pub fn tags<I, S>(self, tags: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        tags.into_iter().fold(self, |builder, tag| builder.tag(tag))
+    }
+
+    /// Set the data classification (restricted content only goes to local or
+    /// allowlisted endpoints)
+    pub fn data_classification(mut self, classification: DataClassification) -> Self {
+        self.data_classification = Some(classification);
+        self
+    }
+
+    /// Trust an external host with restricted content (`*.` for subdomains)
+    pub fn allow_external_host(mut self, host: impl Into<String>) -> Self {
+        let host = host.into();
+        if !self.allowed_external_hosts.contains(&host) {
+            self.allowed_external_hosts.push(host);
+        }
+        self
+    }
+
+    /// Trust several external hosts with restricted content
+    pub fn allowed_external_hosts<I, S>(self, hosts: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        hosts
+            .into_iter()
+            .fold(self, |builder, host| builder.allow_external_host(host))
+    }
+
+    /// Select the metadata fields kept in serialized outputs
+    pub fn metadata_fields(mut self, fields: MetadataFields) -> Self {
+        self.metadata_fields = Some(fields);
+        self
+    }
+
+    /// Set the guardrail override controller
+    pub fn guardrail_override(mut self, controller: Arc<GuardrailOverride>) -> Self {
+        self.guardrail_override = Some(controller);
+        self
+    }
+
+    /// Set the token budget of model-backed guardrails
+    pub fn guard_budget(mut self, budget: Arc<GuardBudget>) -> Self {
+        self.guard_budget = Some(budget);
+        self
+    }
+
+    /// Set the caller's override token
+    pub fn override_token(mut self, token: impl Into<String>) -> Self {
+        self.override_token = Some(token.into());
+        self
+    }
+
+    /// Append a file's text to the user prompt as untrusted content
+    pub fn context_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.context_files.push(path.into());
+        self
+    }
+
+    /// Validate each prompt source separately with the input guardrails
+    pub fn input_sources(mut self, config: crate::InputSourcesConfig) -> Self {
+        self.input_sources = Some(config);
+        self
+    }
+
+    /// Re-run inputs the input guardrails warn about through a stricter guardrail
+    pub fn escalation(mut self, config: crate::EscalationConfig) -> Self {
+        self.escalation = Some(config);
+        self
+    }
+
+    /// Append untrusted data (e.g. a retrieved chunk) to the user prompt
+    pub fn untrusted_content(mut self, content: impl Into<String>) -> Self {
+        self.untrusted_content.push(content.into());
+        self
+    }
+
+    /// Spotlight untrusted content (PDF text, `untrusted_content`)
+    pub fn spotlight(mut self, config: SpotlightConfig) -> Self {
+        self.spotlight = Some(config);
+        self
+    }
+
+    /// Detect model refusals in the response
+    pub fn refusal_detection(mut self, config: RefusalConfig) -> Self {
+        self.refusal_detection = Some(config);
+        self
+    }
+
+    /// Detect repetition loops in the response (optionally retrying)
+    pub fn repetition_detection(mut self, config: RepetitionConfig) -> Self {
+        self.repetition_detection = Some(config);
+        self
+    }
+
+    /// Split documents exceeding the context window into chunks instead of failing
+    pub fn chunking(mut self, config: ChunkingConfig) -> Self {
+        self.chunking = Some(config);
+        self
+    }
+
+    /// Limit the wall time of the evaluation (optionally skipping non-critical guardrails)
+    pub fn deadline(mut self, config: DeadlineConfig) -> Self {
+        self.deadline = Some(config);
+        self
+    }
+
+    /// Region and credentials for the Bedrock provider (default: environment)
+    pub fn bedrock(mut self, config: BedrockConfig) -> Self {
+        self.bedrock = Some(config);
+        self
+    }
+
+    /// Probe the provider for the context window when neither set nor in the registry
+    pub fn context_probe(mut self, prober: Arc<ContextProber>) -> Self {
+        self.context_probe = Some(prober);
+        self
+    }
+
+    /// Set the prompt auditor
+    pub fn prompt_audit(mut self, auditor: Arc<PromptAuditor>) -> Self {
+        self.prompt_audit = Some(auditor);
+        self
+    }
+
+    /// Record each evaluation's outcome in a trend store
+    pub fn trend_stats(mut self, store: Arc<TrendStore>) -> Self {
+        self.trend_stats = Some(store);
+        self
+    }
+
+    /// Set the response cache
+    pub fn response_cache(mut self, cache: Arc<ResponseCache>) -> Self {
+        self.response_cache = Some(cache);
+        self
+    }
+
+    /// Wait for a shared rate limiter before each LLM call
+    pub fn rate_limiter(mut self, limiter: Arc<RateLimiter>) -> Self {
+        self.rate_limiter = Some(limiter);
+        self
+    }
+
+    /// Record counters and latency histograms in a shared registry
+    pub fn metrics(mut self, registry: Arc<MetricsRegistry>) -> Self {
+        self.metrics = Some(registry);
+        self
+    }
+
+    /// Classification (default: public) and allowlisted hosts
+    fn data_policy(&self) -> DataPolicy {
+        DataPolicy {
+            classification: self.data_classification.unwrap_or_default(),
+            allowed_external_hosts: self.allowed_external_hosts.clone(),
+        }
+    }
+
+    /// Explicit temperature, else preset, else default
+    fn effective_temperature(&self) -> f32 {
+        self.temperature
+            .or(self.preset.map(ParameterPreset::temperature))
+            .unwrap_or(llm_defaults::DEFAULT_TEMPERATURE)
+    }
+
+    /// Explicit top_p, else preset (None = provider default)
+    fn effective_top_p(&self) -> Option<f32> {
+        self.top_p.or(self.preset.map(ParameterPreset::top_p))
+    }
+
+    /// Check everything build() checks, returning all problems at once
+    ///
+    /// An empty list means build() will succeed. Intended for front-ends that
+    /// show complete feedback while a config is being edited.
+    pub fn validate(&self) -> Vec<ValidationIssue> {
+        let mut issues = Vec::new();
+        let mut issue = |field: &str, message: String| {
+            issues.push(ValidationIssue {
+                field: field.to_string(),
+                message,
+            })
+        };
+
+        // Required fields
+        if self.api_url.is_none() {
+            issue(
+                "api_url",
+                "API URL must be provided via --api-url or in config file (--config-file)"
+                    .to_string(),
+            );
+        }
+        if self.model.is_none() {
+            issue(
+                "model",
+                "Model must be provided via --model or in config file (--config-file)".to_string(),
+            );
+        }
+        if self.system_prompt.is_none() && self.system_prompt_url.is_none() {
+            issue(
+                "system_prompt",
+                "System prompt must be provided via --system-file/--system-text/--system-prompt-url or in config file (--config-file)"
+                    .to_string(),
+            );
+        }
+        // User prompt required UNLESS pdf_input, url_input or image_input is provided
+        if self.pdf_input.is_none()
+            && self.url_input.is_none()
+            && self.image_input.is_none()
+            && self.user_prompt.is_none()
+        {
+            issue(
+                "user_prompt",
+                "User prompt must be provided via --user-file/--user-text/--pdf-file/--url/--image-file or in config file (--config-file)"
+                    .to_string(),
+            );
+        }
+        if let Some(Err(e)) = self.url_input.as_deref().map(fetch::parse_url) {
+            issue("url_input", e);
+        }
+        if let Some(url) = self.system_prompt_url.as_deref() {
+            let allowed = fetch::parse_url(url).and_then(|parsed| {
+                let fetch = self.fetch.clone().unwrap_or_default();
+                let host = parsed.host_str().unwrap_or_default();
+                if fetch.allowed_hosts.is_empty() || !fetch.allows_host(host) {
+                    return Err(format!(
+                        "system_prompt_url host '{host}' must be listed in fetch.allowed_hosts"
+                    ));
+                }
+                Ok(())
+            });
+            if let Err(e) = allowed {
+                issue("system_prompt_url", e);
+            }
+        }
+        if let Some(Err(e)) = self.fetch.as_ref().map(FetchConfig::validate) {
+            issue("fetch", e);
+        }
+        if self.image_input.is_some()
+            && self.image_mode == Some(ImageMode::Vision)
+            && matches!(self.provider, Some(Provider::Anthropic | Provider::Bedrock))
+        {
+            issue(
+                "image_mode",
+                "Vision image input requires an OpenAI-compatible or Ollama provider; use image_mode = \"ocr\""
+                    .to_string(),
+            );
+        }
+
+        // Optional field ranges
+        let temperature = self.effective_temperature();
+        if !(llm_defaults::MIN_TEMPERATURE..=llm_defaults::MAX_TEMPERATURE).contains(&temperature) {
+            issue(
+                "temperature",
+                format!(
+                    "temperature must be between {} and {}, got {temperature}",
+                    llm_defaults::MIN_TEMPERATURE,
+                    llm_defaults::MAX_TEMPERATURE
+                ),
+            );
+        }
+
+        if let Some(top_p) = self
+            .effective_top_p()
+            .filter(|p| !(llm_defaults::MIN_TOP_P..=llm_defaults::MAX_TOP_P).contains(p))
+        {
+            issue(
+                "top_p",
+                format!(
+                    "top_p must be between {} and {}, got {top_p}",
+                    llm_defaults::MIN_TOP_P,
+                    llm_defaults::MAX_TOP_P
+                ),
+            );
+        }
+
+        // max_tokens: None = use model's maximum
+        if let Some(max_tokens) = self.max_tokens.filter(|&t| t < MIN_TOKENS) {
+            issue(
+                "max_tokens",
+                format!("max_tokens must be >= {MIN_TOKENS}, got {max_tokens}"),
+            );
+        }
+
+        let timeout_secs = self
+            .timeout_secs
+            .unwrap_or(llm_defaults::DEFAULT_TIMEOUT_SECS);
+        if timeout_secs < MIN_TIMEOUT {
+            issue(
+                "timeout_secs",
+                format!("timeout_secs must be >= {MIN_TIMEOUT}, got {timeout_secs}"),
+            );
+        }
+
+        if self.max_response_bytes == Some(0) {
+            issue(
+                "max_response_bytes",
+                "max_response_bytes must be greater than 0".to_string(),
+            );
+        }
+
+        if let Some(limit) = self.context_limit.filter(|&l| l < MIN_CONTEXT_LIMIT) {
+            issue(
+                "context_limit",
+                format!("context_limit must be >= {MIN_CONTEXT_LIMIT}, got {limit}"),
+            );
+        }
+
+        if let Some(percent) = self
+            .context_warning_percent
+            .filter(|p| !(1..=100).contains(p))
+        {
+            issue(
+                "context_warning_percent",
+                format!("context_warning_percent must be between 1 and 100, got {percent}"),
+            );
+        }
+
+        for (index, tool) in self.tools.iter().enumerate() {
+            if let Err(e) = tool.validate() {
+                issue("tools", e);
+            } else if self.tools[..index].iter().any(|t| t.name == tool.name) {
+                issue("tools", format!("duplicate tool name '{}'", tool.name));
+            }
+        }
+
+        if self.tags.iter().any(|tag| tag.trim().is_empty()) {
+            issue("tags", "tags must not be empty".to_string());
+        }
+
+        if let Err(e) = self.data_policy().validate() {
+            issue("allowed_external_hosts", e);
+        }
+
+        if let Some(Err(e)) = self.metadata_fields.as_ref().map(MetadataFields::validate) {
+            issue("metadata_fields", e);
+        }
+
+        for path in &self.context_files {
+            if !path.is_file() {
+                issue(
+                    "context_files",
+                    format!("context file '{}' does not exist", path.display()),
+                );
+            }
+        }
+
+        for rule in &self.guardrail_conditions {
+            if let Err(e) = rule.validate() {
+                issue("guardrails.conditional", e.to_string());
+            }
+        }
+
+        if let Some(Err(e)) = self
+            .escalation
+            .as_ref()
+            .map(crate::EscalationConfig::validate)
+        {
+            issue("guardrails.escalation", e);
+        }
+
+        if let Some(repetition) = &self.repetition_detection {
+            if repetition.ngram_size == 0 {
+                issue(
+                    "repetition_detection",
+                    "repetition_detection.ngram_size must be >= 1".to_string(),
+                );
+            }
+            if !(0.0..1.0).contains(&repetition.threshold) {
+                issue(
+                    "repetition_detection",
+                    format!(
+                        "repetition_detection.threshold must be >= 0.0 and < 1.0, got {}",
+                        repetition.threshold
+                    ),
+                );
+            }
+        }
+
+        if let Some(chunking) = &self.chunking {
+            if let Err(e) = chunking.validate() {
+                issue("chunking", e);
+            }
+            if !self.tools.is_empty() {
+                issue(
+                    "chunking",
+                    "chunking cannot be combined with tools (tool calls of chunk answers \
+                     cannot be merged)"
+                        .to_string(),
+                );
+            }
+        }
+
+        if let Some(Err(e)) = self.deadline.as_ref().map(DeadlineConfig::validate) {
+            issue("deadline", e);
+        }
+
+        issues
+    }
+
     /// Build the final EvaluationConfig, applying defaults and validation
     ///
     /// # Errors
@@ -289,79 +1085,56 @@ impl ConfigBuilder {
     /// Returns `CliError::InvalidArguments` if:
     /// - Required fields are missing (api_url, model, system_prompt, user_prompt)
     /// - Values are out of valid ranges
-    pub fn build(self) -> Result<EvaluationConfig, CliError> {
-        // Validate required fields
-        let api_url = self.api_url.ok_or_else(|| {
-            CliError::InvalidArguments(
-                "API URL must be provided via --api-url or in config file (--config-file)"
-                    .to_string(),
-            )
-        })?;
+    pub fn build(mut self) -> Result<EvaluationConfig, CliError> {
+        // Report the first problem; validate() lists them in check order
+        if let Some(issue) = self.validate().into_iter().next() {
+            return Err(CliError::InvalidArguments(issue.message));
+        }
 
-        let model = self.model.ok_or_else(|| {
-            CliError::InvalidArguments(
-                "Model must be provided via --model or in config file (--config-file)".to_string(),
-            )
-        })?;
+        // Guardrail providers are created per evaluation: read their secrets
+        // (Vault, keyring, ...) once here
+        let guardrails = self
+            .input_guardrails
+            .iter_mut()
+            .chain(self.output_guardrails.iter_mut())
+            .chain(self.escalation.iter_mut().map(|e| &mut e.provider))
+            .chain(
+                self.guardrail_conditions
+                    .iter_mut()
+                    .filter_map(|c| c.add.as_mut()),
+            );
+        for guardrail in guardrails {
+            guardrail.resolve_secrets()?;
+        }
 
-        let system_prompt = self.system_prompt.ok_or_else(|| {
-            CliError::InvalidArguments(
-                "System prompt must be provided via --system-file/--system-text or in config file (--config-file)"
-                    .to_string(),
-            )
-        })?;
+        let data_policy = (self.data_classification.is_some()
+            || !self.allowed_external_hosts.is_empty())
+        .then(|| self.data_policy());
 
-        // User prompt required UNLESS pdf_input is provided
-        let user_prompt = if self.pdf_input.is_some() {
-            String::new() // PDF will be extracted later
-        } else {
-            self.user_prompt.ok_or_else(|| {
-                CliError::InvalidArguments(
-                    "User prompt must be provided via --user-file/--user-text/--pdf-file or in config file (--config-file)"
-                        .to_string(),
-                )
-            })?
+        // Apply preset and defaults (ranges checked by validate())
+        let temperature = self.effective_temperature();
+        let top_p = self.effective_top_p();
+        let seed = self.seed.or(self.preset.and_then(ParameterPreset::seed));
+
+        let (Some(api_url), Some(model)) = (self.api_url, self.model) else {
+            unreachable!("required fields checked by validate()");
+        };
+        // A prompt URL is fetched by the extract stage
+        let (system_prompt, system_prompt_url) = match self.system_prompt {
+            Some(prompt) => (prompt, None),
+            None => (String::new(), self.system_prompt_url),
         };
 
-        // Apply defaults and validate optional fields
-        let temperature = self
-            .temperature
-            .unwrap_or(llm_defaults::DEFAULT_TEMPERATURE);
-        if !(llm_defaults::MIN_TEMPERATURE..=llm_defaults::MAX_TEMPERATURE).contains(&temperature) {
-            return Err(CliError::InvalidArguments(format!(
-                "temperature must be between {} and {}, got {temperature}",
-                llm_defaults::MIN_TEMPERATURE,
-                llm_defaults::MAX_TEMPERATURE
-            )));
-        }
-
-        // Validate max_tokens if provided (None = use model's maximum)
-        if let Some(max_tokens) = self.max_tokens {
-            if max_tokens < MIN_TOKENS {
-                return Err(CliError::InvalidArguments(format!(
-                    "max_tokens must be >= {MIN_TOKENS}, got {max_tokens}"
-                )));
-            }
-        }
+        // User prompt required UNLESS pdf_input, url_input or image_input is provided
+        let user_prompt = if self.pdf_input.is_some() || self.url_input.is_some() {
+            String::new() // PDF or page will be extracted later
+        } else {
+            self.user_prompt.unwrap_or_default()
+        };
 
         let timeout_secs = self
             .timeout_secs
             .unwrap_or(llm_defaults::DEFAULT_TIMEOUT_SECS);
-        if timeout_secs < MIN_TIMEOUT {
-            return Err(CliError::InvalidArguments(format!(
-                "timeout_secs must be >= {MIN_TIMEOUT}, got {timeout_secs}"
-            )));
-        }
-
-        // Auto-detect context limit from model registry if not explicitly set
-        // Validate user-provided limit first (early return on error)
-        if let Some(limit) = self.context_limit {
-            if limit < MIN_CONTEXT_LIMIT {
-                return Err(CliError::InvalidArguments(format!(
-                    "context_limit must be >= {MIN_CONTEXT_LIMIT}, got {limit}"
-                )));
-            }
-        }
 
         // Use user-provided limit or auto-detect from registry
         let context_limit = self.context_limit.or_else(|| {
@@ -376,7 +1149,7 @@ impl ConfigBuilder {
         });
 
         // Log if not auto-detected
-        if context_limit.is_none() && self.context_limit.is_none() {
+        if context_limit.is_none() && self.context_limit.is_none() && self.context_probe.is_none() {
             log::debug!(
                 "Model '{model}' not in registry - context limit not auto-detected. \
                 Use --context-limit to set manually."
@@ -392,18 +1165,56 @@ impl ConfigBuilder {
             user_prompt,
             provider: self.provider,
             temperature,
+            top_p,
             max_tokens: self.max_tokens, // None = use model's maximum
-            seed: self.seed,
+            seed,
             api_key: self.api_key,
             timeout_secs,
+            max_response_bytes: self
+                .max_response_bytes
+                .unwrap_or(http::DEFAULT_MAX_RESPONSE_BYTES),
             validate_tokens,
             context_limit, // Use auto-detected or user-provided value
+            context_probe: self.context_probe,
+            context_warning_percent: self.context_warning_percent,
             response_format: self.response_format,
+            tools: self.tools,
             pdf_input: self.pdf_input,
+            url_input: self.url_input,
+            system_prompt_url,
+            fetch: self.fetch,
+            image_input: self.image_input,
+            image_mode: self.image_mode.unwrap_or_default(),
+            user_prompt_prefix: self.user_prompt_prefix,
+            user_prompt_suffix: self.user_prompt_suffix,
+            context_files: self.context_files,
+            untrusted_content: self.untrusted_content,
+            spotlight: self.spotlight,
             input_guardrails: self.input_guardrails,
             output_guardrails: self.output_guardrails,
+            guardrail_conditions: self.guardrail_conditions,
+            input_sources: self.input_sources,
+            escalation: self.escalation,
+            refusal_detection: self.refusal_detection,
+            repetition_detection: self.repetition_detection,
+            chunking: self.chunking,
+            deadline: self.deadline,
+            bedrock: self.bedrock,
             system_prompt_file: self.system_prompt_file,
             user_prompt_file: self.user_prompt_file,
+            prompt_pins: self.prompt_pins,
+            guardrail_override: self.guardrail_override,
+            guard_budget: self.guard_budget,
+            override_token: self.override_token,
+            prompt_audit: self.prompt_audit,
+            trend_stats: self.trend_stats,
+            response_cache: self.response_cache,
+            rate_limiter: self.rate_limiter,
+            data_policy,
+            metadata_fields: self.metadata_fields,
+            metrics: self.metrics,
+            tags: self.tags,
+            config_warnings: self.config_warnings,
         })
     }
 }
@@ -574,4 +1385,138 @@ mod tests {
         assert_eq!(config.user_prompt, "");
         assert_eq!(config.pdf_input, Some(PathBuf::from("/tmp/test.pdf")));
     }
+
+    #[test]
+    fn test_validate_reports_all_problems() {
+        let builder = ConfigBuilder::new()
+            .api_url("http://localhost:11434")
+            .temperature(3.0)
+            .timeout_secs(0)
+            .max_response_bytes(0)
+            .context_limit(10);
+
+        let fields: Vec<String> = builder.validate().into_iter().map(|i| i.field).collect();
+        assert_eq!(
+            fields,
+            vec![
+                "model",
+                "system_prompt",
+                "user_prompt",
+                "temperature",
+                "timeout_secs",
+                "max_response_bytes",
+                "context_limit"
+            ]
+        );
+
+        // build() fails with the first problem
+        let err = builder.build().unwrap_err();
+        assert!(err.to_string().contains("Model must be provided"));
+    }
+
+    #[test]
+    fn test_validate_empty_for_valid_config() {
+        let builder = ConfigBuilder::new()
+            .api_url("http://localhost:11434")
+            .model("llama3")
+            .system_prompt("You are helpful")
+            .pdf_input(PathBuf::from("/tmp/test.pdf"));
+        assert!(builder.validate().is_empty());
+    }
+
+    #[test]
+    fn test_preset_fills_unset_parameters() {
+        let base = || {
+            ConfigBuilder::new()
+                .api_url("http://localhost:11434")
+                .model("llama3")
+                .system_prompt("You are helpful")
+                .user_prompt("Say hello")
+        };
+
+        let config = base().preset(ParameterPreset::Creative).build().unwrap();
+        assert_eq!(config.temperature, 1.0);
+        assert_eq!(config.top_p, Some(0.95));
+        assert_eq!(config.seed, None);
+
+        let config = base()
+            .preset(ParameterPreset::Deterministic)
+            .temperature(0.2)
+            .build()
+            .unwrap();
+        assert_eq!(config.temperature, 0.2);
+        assert_eq!(config.top_p, Some(1.0));
+        assert_eq!(config.seed, Some(ParameterPreset::DETERMINISTIC_SEED));
+
+        // No preset: top_p left to the provider
+        assert_eq!(base().build().unwrap().top_p, None);
+    }
+
+    #[test]
+    fn test_preset_parsing() {
+        assert_eq!(
+            "Balanced".parse::<ParameterPreset>().unwrap(),
+            ParameterPreset::Balanced
+        );
+        assert!("wild".parse::<ParameterPreset>().is_err());
+    }
+
+    #[test]
+    fn test_merge_warnings_collected() {
+        let file_config: ConfigFileRequest = toml::from_str(
+            r#"
+            api_url = "http://localhost:11434"
+            model = "llama3"
+            provider = "vertex"
+            response_format = "yaml"
+        "#,
+        )
+        .unwrap();
+
+        let config = ConfigBuilder::new()
+            .merge_file_config(&file_config)
+            .warning("system_prompt", "both set")
+            .system_prompt("You are helpful")
+            .user_prompt("Say hello")
+            .build()
+            .unwrap();
+
+        let fields: Vec<&str> = config
+            .config_warnings
+            .iter()
+            .map(|w| w.field.as_str())
+            .collect();
+        assert_eq!(fields, vec!["provider", "response_format", "system_prompt"]);
+        assert!(config.config_warnings[0].message.contains("vertex"));
+    }
+
+    #[test]
+    fn test_tags_deduplicated_and_cli_over_file() {
+        let file_config: ConfigFileRequest = toml::from_str(
+            r#"
+            api_url = "http://localhost:11434"
+            model = "llama3"
+            tags = ["experiment=A"]
+        "#,
+        )
+        .unwrap();
+        let base = || {
+            ConfigBuilder::new()
+                .system_prompt("You are helpful")
+                .user_prompt("Say hello")
+        };
+
+        let config = base().merge_file_config(&file_config).build().unwrap();
+        assert_eq!(config.tags, vec!["experiment=A"]);
+
+        let config = base()
+            .tags(["experiment=B", "model=llama3", "experiment=B"])
+            .merge_file_config(&file_config)
+            .build()
+            .unwrap();
+        assert_eq!(config.tags, vec!["experiment=B", "model=llama3"]);
+
+        let issues = base().merge_file_config(&file_config).tag(" ").validate();
+        assert_eq!(issues[0].field, "tags");
+    }
 }
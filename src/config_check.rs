@@ -0,0 +1,321 @@
+//! Offline validation of an evaluation configuration
+//!
+//! [`check_config`] runs every check an evaluation runs before its first
+//! request, without sending one: builder validation, file paths, the
+//! guardrail configurations (API key environment variables and pattern or
+//! hash files included) and the response format schema. It reports every
+//! problem found along with the resolved configuration, defaults applied and
+//! secrets redacted:
+//!
+//! ```text
+//! fortified-llm-client --config-file config.toml --profile prod validate-config
+//! ```
+
+use crate::{
+    cassette::{is_secret_field, REDACTED},
+    config_builder::{ConfigBuilder, ConfigWarning, ValidationIssue},
+    detect_provider_type,
+    guardrails::{create_guardrail_provider, GuardrailProviderConfig},
+    EvaluationConfig,
+};
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+use std::collections::HashSet;
+
+/// Warning fields meaning a configured setting was dropped: the response
+/// format is not applied when its schema fails to load
+const DROPPED_SETTING_WARNINGS: &[&str] = &["response_format", "response_format_schema"];
+
+/// Result of [`check_config`]
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ConfigCheckReport {
+    /// No issues found
+    pub valid: bool,
+    /// Problems that make evaluations fail or drop a configured setting
+    pub issues: Vec<ValidationIssue>,
+    /// Non-fatal merge problems
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<ConfigWarning>,
+    /// Resolved configuration with secrets redacted (None if it does not build)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub config: Option<Value>,
+}
+
+/// Validate `builder` without network calls, collecting all problems
+pub fn check_config(builder: ConfigBuilder) -> ConfigCheckReport {
+    let (dropped, warnings): (Vec<_>, Vec<_>) = builder
+        .config_warnings
+        .iter()
+        .cloned()
+        .partition(|w| DROPPED_SETTING_WARNINGS.contains(&w.field.as_str()));
+    // A setting dropped by several layers (e.g. CLI and config file) is
+    // reported once
+    let mut seen = HashSet::new();
+    let mut issues: Vec<ValidationIssue> = dropped
+        .into_iter()
+        .filter(|w| seen.insert(w.field.clone()))
+        .map(|w| ValidationIssue {
+            field: w.field,
+            message: w.message,
+        })
+        .collect();
+    issues.extend(builder.validate());
+    if !issues.is_empty() {
+        return ConfigCheckReport {
+            valid: false,
+            issues,
+            warnings,
+            config: None,
+        };
+    }
+
+    let config = match builder.build() {
+        Ok(config) => config,
+        Err(e) => {
+            issues.push(ValidationIssue {
+                field: String::new(),
+                message: e.to_string(),
+            });
+            return ConfigCheckReport {
+                valid: false,
+                issues,
+                warnings,
+                config: None,
+            };
+        }
+    };
+    issues.extend(offline_issues(&config));
+    ConfigCheckReport {
+        valid: issues.is_empty(),
+        issues,
+        warnings,
+        config: Some(resolved_config(&config)),
+    }
+}
+
+/// Checks left to evaluation time by `build()`: input files and guardrail
+/// providers (created, but never called)
+fn offline_issues(config: &EvaluationConfig) -> Vec<ValidationIssue> {
+    let mut issues = Vec::new();
+    let mut issue = |field: &str, message: String| {
+        issues.push(ValidationIssue {
+            field: field.to_string(),
+            message,
+        })
+    };
+
+    for (field, path) in [
+        ("pdf_file", &config.pdf_input),
+        ("image_file", &config.image_input),
+    ] {
+        if let Some(path) = path.as_ref().filter(|p| !p.is_file()) {
+            issue(field, format!("file '{}' does not exist", path.display()));
+        }
+    }
+
+    let guardrails = [
+        ("guardrails.input", config.input_guardrails.as_ref()),
+        ("guardrails.output", config.output_guardrails.as_ref()),
+        (
+            "guardrails.escalation",
+            config.escalation.as_ref().map(|e| &e.provider),
+        ),
+    ]
+    .into_iter()
+    .chain(
+        config
+            .guardrail_conditions
+            .iter()
+            .map(|c| ("guardrails.conditional", c.add.as_ref())),
+    );
+    for (field, guardrail) in guardrails {
+        if let Some(Err(e)) = guardrail.map(check_guardrail) {
+            issue(field, e.to_string());
+        }
+    }
+    issues
+}
+
+/// Validate a guardrail and create its provider (resolving API keys and
+/// loading pattern and hash files)
+fn check_guardrail(config: &GuardrailProviderConfig) -> Result<(), crate::CliError> {
+    config.validate()?;
+    create_guardrail_provider(config).map(drop)
+}
+
+/// `config` in config file layout, with defaults applied and secrets redacted
+///
+/// Shared runtime components (audit log, cache, rate limiter, ...) are
+/// listed as `true` under their config file section name.
+pub fn resolved_config(config: &EvaluationConfig) -> Value {
+    let provider = config
+        .provider
+        .unwrap_or_else(|| detect_provider_type(&config.api_url));
+    let guardrails = json!({
+        "input": config.input_guardrails,
+        "output": config.output_guardrails,
+        "conditional": config.guardrail_conditions,
+        "input_sources": config.input_sources,
+        "escalation": config.escalation,
+    });
+    let mut value = json!({
+        "api_url": config.api_url,
+        "model": config.model,
+        "provider": format!("{provider:?}"),
+        "api_key": config.api_key,
+        "system_prompt": config.system_prompt,
+        "system_prompt_file": config.system_prompt_file,
+        "system_prompt_url": config.system_prompt_url,
+        "prompt_pins": config.prompt_pins,
+        "user_prompt": Some(&config.user_prompt).filter(|p| !p.is_empty()),
+        "user_prompt_file": config.user_prompt_file,
+        "pdf_file": config.pdf_input,
+        "user_prompt_url": config.url_input,
+        "image_file": config.image_input,
+        "image_mode": config.image_mode,
+        "context_files": config.context_files,
+        "untrusted_content": config.untrusted_content,
+        "user_prompt_prefix": config.user_prompt_prefix,
+        "user_prompt_suffix": config.user_prompt_suffix,
+        "spotlighting": config.spotlight,
+        "refusal_detection": config.refusal_detection,
+        "repetition_detection": config.repetition_detection,
+        "chunking": config.chunking,
+        "fetch": config.fetch,
+        "deadline": config.deadline,
+        "bedrock": config.bedrock,
+        "tools": config.tools,
+        "tags": config.tags,
+        "data_classification": config.data_policy.as_ref().map(|p| p.classification),
+        "allowed_external_hosts": config
+            .data_policy
+            .as_ref()
+            .map(|p| &p.allowed_external_hosts),
+        "metadata_fields": config.metadata_fields,
+        "temperature": config.temperature,
+        "top_p": config.top_p,
+        "max_tokens": config.max_tokens,
+        "seed": config.seed,
+        "timeout_secs": config.timeout_secs,
+        "max_response_bytes": config.max_response_bytes,
+        "validate_tokens": config.validate_tokens,
+        "context_limit": config.context_limit,
+        "context_warning_percent": config.context_warning_percent,
+        "response_format": config.response_format,
+        "guardrails": prune(guardrails),
+    });
+    let components = [
+        ("guardrail_override", config.guardrail_override.is_some()),
+        ("guard_budget", config.guard_budget.is_some()),
+        ("prompt_audit", config.prompt_audit.is_some()),
+        ("trend_stats", config.trend_stats.is_some()),
+        ("cache", config.response_cache.is_some()),
+        ("rate_limit", config.rate_limiter.is_some()),
+        ("context_probe", config.context_probe.is_some()),
+    ];
+    if let Value::Object(map) = &mut value {
+        for (section, enabled) in components {
+            if enabled {
+                map.insert(section.to_string(), Value::Bool(true));
+            }
+        }
+    }
+    redact(prune(value))
+}
+
+/// Drop the unset fields of a table: nulls, empty lists and empty tables
+fn prune(mut value: Value) -> Value {
+    if let Value::Object(map) = &mut value {
+        map.retain(|_, v| match v {
+            Value::Null => false,
+            Value::Array(items) => !items.is_empty(),
+            Value::Object(fields) => !fields.is_empty(),
+            _ => true,
+        });
+    }
+    value
+}
+
+fn redact(value: Value) -> Value {
+    match value {
+        Value::Object(map) => Value::Object(
+            map.into_iter()
+                .map(|(k, v)| {
+                    let secret = is_secret_field(&k)
+                        || matches!(k.as_str(), "secret_access_key" | "session_token");
+                    if secret && !v.is_null() {
+                        (k, Value::String(REDACTED.to_string()))
+                    } else {
+                        (k, redact(v))
+                    }
+                })
+                .collect::<Map<_, _>>(),
+        ),
+        Value::Array(items) => Value::Array(items.into_iter().map(redact).collect()),
+        other => other,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{guardrails::GuardrailConfigBuilder, Provider};
+
+    fn builder() -> ConfigBuilder {
+        ConfigBuilder::new()
+            .api_url("http://localhost:11434/api/generate")
+            .model("llama3")
+            .system_prompt("Be brief")
+            .user_prompt("Hello")
+    }
+
+    #[test]
+    fn test_resolved_config_applies_defaults_and_redacts() {
+        let report = check_config(
+            builder()
+                .api_key("sk-secret")
+                .preset(crate::config_builder::ParameterPreset::Deterministic),
+        );
+        assert!(report.valid, "{:?}", report.issues);
+        let config = report.config.unwrap();
+        assert_eq!(config["api_key"], REDACTED);
+        assert_eq!(config["provider"], format!("{:?}", Provider::Ollama));
+        assert_eq!(config["temperature"], 0.0);
+        assert!(config["timeout_secs"].as_u64().unwrap() > 0);
+        assert!(config.get("guardrails").is_none(), "{config}");
+        assert!(config.get("cache").is_none(), "{config}");
+    }
+
+    #[test]
+    fn test_all_problems_reported() {
+        let report = check_config(builder().temperature(5.0).max_tokens(0).tag(" "));
+        assert!(!report.valid);
+        assert!(report.config.is_none());
+        let fields: Vec<&str> = report.issues.iter().map(|i| i.field.as_str()).collect();
+        assert_eq!(fields, ["temperature", "max_tokens", "tags"]);
+    }
+
+    #[test]
+    fn test_guardrail_and_file_problems_reported() {
+        let guard = GuardrailConfigBuilder::llama_guard("http://localhost:11434", "llama-guard3")
+            .api_key_name("CONFIG_CHECK_TEST_UNSET_KEY")
+            .build()
+            .unwrap();
+        let report = check_config(
+            builder()
+                .pdf_input("/nonexistent/report.pdf".into())
+                .output_guardrails(guard),
+        );
+        assert!(!report.valid);
+        let fields: Vec<&str> = report.issues.iter().map(|i| i.field.as_str()).collect();
+        assert_eq!(fields, ["pdf_file", "guardrails.output"]);
+        assert!(
+            report.issues[1]
+                .message
+                .contains("CONFIG_CHECK_TEST_UNSET_KEY"),
+            "{:?}",
+            report.issues
+        );
+        assert!(report.config.is_some());
+    }
+}
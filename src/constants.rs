@@ -11,6 +11,10 @@ pub mod token_estimation {
     /// Safety margin multiplier for token estimates (10%)
     /// Helps account for tokenizer variations and special characters
     pub const SAFETY_MARGIN: f64 = 1.1;
+
+    /// Chat template overhead added to exact token counts instead of the
+    /// safety margin (3 per message plus 3 priming the reply)
+    pub const CHAT_TEMPLATE_TOKENS: usize = 9;
 }
 
 /// Input validation limits
@@ -42,6 +46,17 @@ pub mod pdf_limits {
     pub const MAX_EXTRACTION_TIMEOUT_SECS: u64 = 60;
 }
 
+/// Image processing limits
+pub mod image_limits {
+    /// Maximum image file size in bytes (20MB)
+    /// Matches the per-image limit of common vision APIs
+    pub const MAX_IMAGE_SIZE_BYTES: u64 = 20_971_520;
+
+    /// Maximum OCR timeout in seconds (60s)
+    /// The OCR process is killed when exceeded
+    pub const MAX_OCR_TIMEOUT_SECS: u64 = 60;
+}
+
 /// LLM invocation defaults
 pub mod llm_defaults {
     /// Default temperature for LLM sampling (0.0 = deterministic)
@@ -53,11 +68,20 @@ pub mod llm_defaults {
     /// Maximum allowed temperature
     pub const MAX_TEMPERATURE: f32 = 2.0;
 
+    /// Minimum allowed nucleus sampling probability (top_p)
+    pub const MIN_TOP_P: f32 = 0.0;
+
+    /// Maximum allowed nucleus sampling probability (top_p)
+    pub const MAX_TOP_P: f32 = 1.0;
+
     /// Default maximum tokens for LLM response
     pub const DEFAULT_MAX_TOKENS: u32 = 4000;
 
     /// Default timeout for LLM API calls in seconds
     pub const DEFAULT_TIMEOUT_SECS: u64 = 300;
+
+    /// Default context usage (percent of context_limit) that triggers a headroom warning
+    pub const DEFAULT_CONTEXT_WARNING_PERCENT: u8 = 90;
 }
 
 /// HTTP client configuration
@@ -67,6 +91,17 @@ pub mod http {
 
     /// Maximum number of redirects to follow
     pub const MAX_REDIRECTS: usize = 10;
+
+    /// Default maximum response body size in bytes (20MB)
+    /// Bounds memory use when an endpoint returns unbounded data
+    pub const DEFAULT_MAX_RESPONSE_BYTES: usize = 20_971_520;
+}
+
+/// HTTP server (`serve` subcommand) limits
+pub mod server {
+    /// Maximum request body size in bytes (2MB)
+    /// Leaves room for JSON escaping of a maximum-size prompt
+    pub const MAX_REQUEST_BYTES: usize = 2_097_152;
 }
 
 /// Guardrails defaults
@@ -79,4 +114,10 @@ pub mod guardrails {
 
     /// Minimum quality score (floor)
     pub const MIN_QUALITY_SCORE: f32 = 0.0;
+
+    /// Content size from which regex scans run in parallel (feature `parallel`)
+    pub const PARALLEL_SCAN_MIN_BYTES: usize = 256 * 1024;
+
+    /// Chunk size of parallel regex scans
+    pub const PARALLEL_SCAN_CHUNK_BYTES: usize = 64 * 1024;
 }
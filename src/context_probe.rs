@@ -0,0 +1,383 @@
+//! Runtime context window discovery
+//!
+//! For models missing from the static [`model_registry`](crate::model_registry),
+//! the provider can be asked for the context window:
+//!
+//! - Ollama: `POST /api/show` (`num_ctx` parameter, else `<arch>.context_length`)
+//! - OpenAI-compatible: `GET <base>/models/<model>`, then the `<base>/models`
+//!   list (`context_length`, `context_window`, or `max_model_len`)
+//!
+//! Results are cached per process and, optionally, in a [`Storage`] backend so
+//! CLI runs don't re-probe. Probing is best-effort: failures are logged and the
+//! evaluation continues without a context limit.
+
+use crate::{
+    cassette,
+    error::CliError,
+    provider::ProviderType,
+    providers::detect_provider_type,
+    storage::{FsStorage, Storage},
+};
+use reqwest::{Client, Url};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::{
+    collections::HashMap,
+    path::PathBuf,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+/// Default lifetime of persisted probe results (7 days)
+pub const DEFAULT_PROBE_CACHE_TTL_SECS: u64 = 7 * 24 * 3600;
+
+/// Context probe settings as written in config files (`[context_probe]`)
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ContextProbeConfig {
+    /// Directory for persisted probe results (default: in-process cache only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache_dir: Option<PathBuf>,
+
+    /// Lifetime of persisted results in seconds (default: 7 days)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache_ttl_secs: Option<u64>,
+}
+
+/// Queries providers for a model's context window, with caching
+pub struct ContextProber {
+    client: Client,
+    storage: Option<Arc<dyn Storage>>,
+    ttl: Duration,
+    cache: Mutex<HashMap<String, usize>>,
+}
+
+impl std::fmt::Debug for ContextProber {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ContextProber")
+            .field("persistent", &self.storage.is_some())
+            .field("ttl", &self.ttl)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Default for ContextProber {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ContextProber {
+    /// Prober with an in-process cache only
+    pub fn new() -> Self {
+        Self {
+            client: Client::new(),
+            storage: None,
+            ttl: Duration::from_secs(DEFAULT_PROBE_CACHE_TTL_SECS),
+            cache: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Also persist results in `storage` for `ttl`
+    pub fn with_storage(mut self, storage: Arc<dyn Storage>, ttl: Duration) -> Self {
+        self.storage = Some(storage);
+        self.ttl = ttl;
+        self
+    }
+
+    pub fn from_config(config: &ContextProbeConfig) -> Self {
+        let prober = Self::new();
+        match &config.cache_dir {
+            Some(dir) => prober.with_storage(
+                Arc::new(FsStorage::new(dir)),
+                Duration::from_secs(
+                    config
+                        .cache_ttl_secs
+                        .unwrap_or(DEFAULT_PROBE_CACHE_TTL_SECS),
+                ),
+            ),
+            None => prober,
+        }
+    }
+
+    /// Discover the context window of `model` served at `api_url`
+    ///
+    /// Returns None (after logging why) if the provider doesn't report one.
+    pub async fn probe(
+        &self,
+        api_url: &str,
+        model: &str,
+        provider: Option<ProviderType>,
+        api_key: Option<&str>,
+        timeout_secs: u64,
+    ) -> Option<usize> {
+        let key = cache_key(api_url, model);
+        if let Some(limit) = self.cached(&key) {
+            log::debug!("Context window for '{model}' from probe cache: {limit} tokens");
+            return Some(limit);
+        }
+
+        let provider = provider.unwrap_or_else(|| detect_provider_type(api_url));
+        let timeout = Duration::from_secs(timeout_secs);
+        let result = match provider {
+            ProviderType::Ollama => self.probe_ollama(api_url, model, api_key, timeout).await,
+            ProviderType::OpenAI => self.probe_openai(api_url, model, api_key, timeout).await,
+            // Neither the Anthropic Models API nor Bedrock reports a context window
+            ProviderType::Anthropic | ProviderType::Bedrock | ProviderType::Mock => Ok(None),
+        };
+
+        match result {
+            Ok(Some(limit)) => {
+                log::info!("Probed context window for {model}: {limit} tokens");
+                self.store(&key, limit);
+                Some(limit)
+            }
+            Ok(None) => {
+                log::debug!("Provider did not report a context window for '{model}'");
+                None
+            }
+            Err(e) => {
+                log::warn!("Context window probe for '{model}' failed: {e}");
+                None
+            }
+        }
+    }
+
+    fn cached(&self, key: &str) -> Option<usize> {
+        if let Some(limit) = self.cache.lock().expect("probe cache poisoned").get(key) {
+            return Some(*limit);
+        }
+        let storage = self.storage.as_ref()?;
+        let value = storage.get(key).ok().flatten()?;
+        let limit = String::from_utf8(value).ok()?.trim().parse().ok()?;
+        self.cache
+            .lock()
+            .expect("probe cache poisoned")
+            .insert(key.to_string(), limit);
+        Some(limit)
+    }
+
+    fn store(&self, key: &str, limit: usize) {
+        self.cache
+            .lock()
+            .expect("probe cache poisoned")
+            .insert(key.to_string(), limit);
+        if let Some(storage) = &self.storage {
+            if let Err(e) = storage.put(key, limit.to_string().as_bytes(), Some(self.ttl)) {
+                log::warn!("Failed to persist context probe result: {e}");
+            }
+        }
+    }
+
+    async fn get_json(
+        &self,
+        request: reqwest::RequestBuilder,
+        api_key: Option<&str>,
+        timeout: Duration,
+    ) -> Result<Option<Value>, CliError> {
+        let mut request = request.timeout(timeout);
+        if let Some(key) = api_key {
+            request = request.header("Authorization", format!("Bearer {key}"));
+        }
+        let response = cassette::send(request, api_key).await?;
+        if !response.status.is_success() {
+            log::debug!("Context probe got HTTP {}", response.status.as_u16());
+            return Ok(None);
+        }
+        serde_json::from_str(&response.body)
+            .map(Some)
+            .map_err(|e| CliError::InvalidResponse(format!("Failed to parse probe response: {e}")))
+    }
+
+    async fn probe_ollama(
+        &self,
+        api_url: &str,
+        model: &str,
+        api_key: Option<&str>,
+        timeout: Duration,
+    ) -> Result<Option<usize>, CliError> {
+        let url = origin(api_url)?.join("api/show").map_err(invalid_url)?;
+        let request = self
+            .client
+            .post(url)
+            .json(&serde_json::json!({ "model": model }));
+        Ok(self
+            .get_json(request, api_key, timeout)
+            .await?
+            .as_ref()
+            .and_then(ollama_context_length))
+    }
+
+    async fn probe_openai(
+        &self,
+        api_url: &str,
+        model: &str,
+        api_key: Option<&str>,
+        timeout: Duration,
+    ) -> Result<Option<usize>, CliError> {
+        let base = openai_base(api_url)?;
+
+        let mut model_url = base.clone();
+        model_url
+            .path_segments_mut()
+            .map_err(|_| invalid_url(api_url))?
+            .pop_if_empty()
+            .push("models")
+            .push(model);
+        let request = self.client.get(model_url);
+        if let Some(limit) = self
+            .get_json(request, api_key, timeout)
+            .await?
+            .as_ref()
+            .and_then(openai_context_length)
+        {
+            return Ok(Some(limit));
+        }
+
+        // Gateways like OpenRouter only report context in the model list
+        let mut list_url = base;
+        list_url
+            .path_segments_mut()
+            .map_err(|_| invalid_url(api_url))?
+            .pop_if_empty()
+            .push("models");
+        let request = self.client.get(list_url);
+        Ok(self
+            .get_json(request, api_key, timeout)
+            .await?
+            .as_ref()
+            .and_then(|list| list.get("data")?.as_array().cloned())
+            .and_then(|models| {
+                models
+                    .iter()
+                    .find(|m| m.get("id").and_then(Value::as_str) == Some(model))
+                    .and_then(openai_context_length)
+            }))
+    }
+}
+
+fn invalid_url(e: impl std::fmt::Display) -> CliError {
+    CliError::InvalidArguments(format!("Invalid API URL for context probe: {e}"))
+}
+
+pub(crate) fn origin(api_url: &str) -> Result<Url, CliError> {
+    let url = Url::parse(api_url).map_err(invalid_url)?;
+    Url::parse(&url.origin().ascii_serialization())
+        .and_then(|origin| origin.join("/"))
+        .map_err(invalid_url)
+}
+
+/// API base of an OpenAI-compatible endpoint (`.../v1/chat/completions` -> `.../v1`)
+pub(crate) fn openai_base(api_url: &str) -> Result<Url, CliError> {
+    let mut url = Url::parse(api_url).map_err(invalid_url)?;
+    let path = url.path().trim_end_matches('/');
+    let base = if let Some(base) = path.strip_suffix("/chat/completions") {
+        base.to_string()
+    } else if let Some(index) = path.find("/v1") {
+        path[..index + 3].to_string()
+    } else {
+        "/v1".to_string()
+    };
+    url.set_path(&base);
+    url.set_query(None);
+    Ok(url)
+}
+
+/// Storage key for a probe result (hash keeps arbitrary URLs/model names valid)
+fn cache_key(api_url: &str, model: &str) -> String {
+    let digest = Sha256::digest(format!("{api_url}\n{model}").as_bytes());
+    let hex: String = digest.iter().map(|b| format!("{b:02x}")).collect();
+    format!("context-probe/{hex}")
+}
+
+fn as_limit(value: &Value) -> Option<usize> {
+    value
+        .as_u64()
+        .filter(|&n| n > 0)
+        .and_then(|n| usize::try_from(n).ok())
+}
+
+/// Effective Ollama context: explicit `num_ctx`, else the model's trained length
+fn ollama_context_length(show: &Value) -> Option<usize> {
+    let num_ctx = show
+        .get("parameters")
+        .and_then(Value::as_str)
+        .and_then(|params| {
+            params.lines().find_map(|line| {
+                let mut parts = line.split_whitespace();
+                (parts.next() == Some("num_ctx"))
+                    .then(|| parts.next()?.parse().ok())
+                    .flatten()
+            })
+        });
+    num_ctx.or_else(|| {
+        show.get("model_info")?
+            .as_object()?
+            .iter()
+            .find(|(key, _)| key.ends_with(".context_length"))
+            .and_then(|(_, value)| as_limit(value))
+    })
+}
+
+pub(crate) fn openai_context_length(model: &Value) -> Option<usize> {
+    ["context_length", "context_window", "max_model_len"]
+        .iter()
+        .find_map(|field| model.get(field).and_then(as_limit))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_openai_base() {
+        assert_eq!(
+            openai_base("https://api.example.com/v1/chat/completions")
+                .unwrap()
+                .as_str(),
+            "https://api.example.com/v1"
+        );
+        assert_eq!(
+            openai_base("https://gw.example.com/proxy/v1/chat/completions")
+                .unwrap()
+                .as_str(),
+            "https://gw.example.com/proxy/v1"
+        );
+        assert_eq!(
+            openai_base("https://api.example.com").unwrap().as_str(),
+            "https://api.example.com/v1"
+        );
+    }
+
+    #[test]
+    fn test_ollama_context_prefers_num_ctx() {
+        let show = json!({
+            "parameters": "stop \"<|eot_id|>\"\nnum_ctx 8192",
+            "model_info": { "llama.context_length": 131072 }
+        });
+        assert_eq!(ollama_context_length(&show), Some(8192));
+
+        let show = json!({ "model_info": { "qwen2.context_length": 32768 } });
+        assert_eq!(ollama_context_length(&show), Some(32768));
+        assert_eq!(ollama_context_length(&json!({})), None);
+    }
+
+    #[test]
+    fn test_openai_context_fields() {
+        assert_eq!(
+            openai_context_length(&json!({ "max_model_len": 4096 })),
+            Some(4096)
+        );
+        assert_eq!(
+            openai_context_length(&json!({ "id": "gpt-4o", "object": "model" })),
+            None
+        );
+    }
+
+    #[test]
+    fn test_cache_key_is_valid_storage_key() {
+        let key = cache_key("http://localhost:11434/api/generate", "llama3:8b");
+        assert!(crate::storage::validate_key(&key).is_ok());
+    }
+}
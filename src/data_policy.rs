@@ -0,0 +1,311 @@
+//! Data classification pre-flight check
+//!
+//! Evaluations marked `restricted` only send content to local endpoints or to
+//! allowlisted hosts, whatever the content: the check runs before any stage,
+//! independently of the guardrails scanning the content.
+//!
+//! ```toml
+//! data_classification = "restricted"     # Default: "public" (no check)
+//! allowed_external_hosts = ["llm.corp.example.com", "*.openai.azure.com"]
+//! ```
+//!
+//! An endpoint is local when its host is `localhost` (or `*.localhost`) or a
+//! non-public IP address (loopback, private, link-local, CGNAT, see
+//! [`crate::fetch::is_public`]); `mock://` is local too. Other host names are
+//! external whatever they resolve to now: the request resolves them again,
+//! and a rebinding or round-robin name could then reach a public address.
+//! With an HTTP proxy configured in the environment (`HTTP_PROXY`,
+//! `HTTPS_PROXY`, `ALL_PROXY`, which reqwest honors) every network endpoint
+//! is external, since content leaves through the proxy.
+//!
+//! Checked endpoints are the LLM `api_url` and the endpoints of model-backed
+//! guardrails (input, output, escalation, conditional additions) and image
+//! scanners, which all receive content.
+
+use crate::{error::CliError, fetch::is_public, EvaluationConfig};
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+use std::{fmt, net::IpAddr, str::FromStr};
+
+/// Sensitivity of the evaluated content (`data_classification`)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DataClassification {
+    /// May be sent to any endpoint
+    #[default]
+    Public,
+    /// Only sent to local endpoints and `allowed_external_hosts`
+    Restricted,
+}
+
+impl fmt::Display for DataClassification {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Public => "public",
+            Self::Restricted => "restricted",
+        })
+    }
+}
+
+impl FromStr for DataClassification {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "public" => Ok(Self::Public),
+            "restricted" => Ok(Self::Restricted),
+            other => Err(format!(
+                "Invalid data classification '{other}'. Valid values: public, restricted"
+            )),
+        }
+    }
+}
+
+/// Where an endpoint sends content
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EndpointClass {
+    Local,
+    External,
+}
+
+/// Classification of an evaluation and the external hosts it may use
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DataPolicy {
+    pub classification: DataClassification,
+    /// Host names (`*.` prefix for subdomains) trusted with restricted data
+    pub allowed_external_hosts: Vec<String>,
+}
+
+impl DataPolicy {
+    /// Restricted data without external hosts
+    pub fn restricted() -> Self {
+        Self {
+            classification: DataClassification::Restricted,
+            allowed_external_hosts: Vec::new(),
+        }
+    }
+
+    /// Trust `host` (e.g. `llm.corp.example.com` or `*.openai.azure.com`)
+    pub fn allow_host(mut self, host: impl Into<String>) -> Self {
+        self.allowed_external_hosts.push(host.into());
+        self
+    }
+
+    /// Allowlist entries are bare host names, optionally `*.`-prefixed
+    pub fn validate(&self) -> Result<(), String> {
+        for host in &self.allowed_external_hosts {
+            let name = host.strip_prefix("*.").unwrap_or(host);
+            if name.is_empty()
+                || name.contains(['/', ':', '*'])
+                || name.contains(char::is_whitespace)
+            {
+                return Err(format!(
+                    "allowed_external_hosts: '{host}' is not a host name \
+                     (e.g. 'llm.example.com' or '*.example.com')"
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether `host` matches the allowlist (case-insensitive)
+    pub fn allows_host(&self, host: &str) -> bool {
+        let host = host.to_ascii_lowercase();
+        self.allowed_external_hosts.iter().any(|allowed| {
+            let allowed = allowed.to_ascii_lowercase();
+            match allowed.strip_prefix("*.") {
+                Some(domain) => host
+                    .strip_suffix(domain)
+                    .is_some_and(|sub| sub.len() > 1 && sub.ends_with('.')),
+                None => host == allowed,
+            }
+        })
+    }
+
+    /// Refuse restricted data for external endpoints missing from the
+    /// allowlist
+    ///
+    /// # Errors
+    ///
+    /// Returns `CliError::EndpointNotAllowed` naming the first refused
+    /// endpoint (public data is never refused).
+    pub fn check(&self, endpoints: &[&str]) -> Result<(), CliError> {
+        if self.classification != DataClassification::Restricted {
+            return Ok(());
+        }
+        for endpoint in endpoints {
+            if classify_endpoint(endpoint) == EndpointClass::Local {
+                continue;
+            }
+            let host = Url::parse(endpoint)
+                .ok()
+                .and_then(|url| url.host_str().map(str::to_string))
+                .unwrap_or_default();
+            if !self.allows_host(&host) {
+                return Err(CliError::EndpointNotAllowed(format!(
+                    "'{endpoint}' is external and '{host}' is not in allowed_external_hosts; \
+                     restricted data is only sent to local or allowlisted endpoints"
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Whether `url` is served from this machine or a private network
+pub fn classify_endpoint(url: &str) -> EndpointClass {
+    let Ok(parsed) = Url::parse(url) else {
+        return EndpointClass::External;
+    };
+    if parsed.scheme() == "mock" {
+        return EndpointClass::Local;
+    }
+    let Some(host) = parsed.host_str() else {
+        return EndpointClass::External;
+    };
+    if let Some(var) = proxy_variable(parsed.scheme()) {
+        log::debug!("'{url}' is sent through the proxy in {var}, treating it as external");
+        return EndpointClass::External;
+    }
+    let host = host.to_ascii_lowercase();
+    let literal = host.trim_start_matches('[').trim_end_matches(']');
+    let local = match literal.parse::<IpAddr>() {
+        Ok(ip) => !is_public(ip),
+        Err(_) => host == "localhost" || host.ends_with(".localhost"),
+    };
+    if local {
+        EndpointClass::Local
+    } else {
+        EndpointClass::External
+    }
+}
+
+/// Proxy environment variable reqwest would use for `scheme`, if set
+fn proxy_variable(scheme: &str) -> Option<&'static str> {
+    proxy_variable_in(scheme, |var| std::env::var(var).ok())
+}
+
+fn proxy_variable_in(scheme: &str, env: impl Fn(&str) -> Option<String>) -> Option<&'static str> {
+    let candidates: &[&'static str] = match scheme {
+        "http" => &["HTTP_PROXY", "http_proxy", "ALL_PROXY", "all_proxy"],
+        "https" => &["HTTPS_PROXY", "https_proxy", "ALL_PROXY", "all_proxy"],
+        _ => &["ALL_PROXY", "all_proxy"],
+    };
+    candidates
+        .iter()
+        .copied()
+        .find(|var| env(var).is_some_and(|value| !value.trim().is_empty()))
+}
+
+/// Endpoints an evaluation of `config` sends content to
+pub fn content_endpoints(config: &EvaluationConfig) -> Vec<&str> {
+    let mut endpoints = vec![config.api_url.as_str()];
+    let guardrails = [&config.input_guardrails, &config.output_guardrails]
+        .into_iter()
+        .flatten()
+        .chain(config.escalation.as_ref().map(|e| &e.provider))
+        .chain(
+            config
+                .guardrail_conditions
+                .iter()
+                .filter_map(|c| c.add.as_ref()),
+        );
+    for guardrail in guardrails {
+        endpoints.extend(guardrail.endpoints());
+    }
+    endpoints.dedup();
+    endpoints
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify_endpoint() {
+        for url in [
+            "http://localhost:11434/api/generate",
+            "http://127.0.0.1:8080/v1/chat/completions",
+            "http://10.0.3.7/v1/chat/completions",
+            "http://[::1]:11434/api/chat",
+            "http://ollama.localhost/api/chat",
+            "mock://",
+        ] {
+            assert_eq!(classify_endpoint(url), EndpointClass::Local, "{url}");
+        }
+        for url in [
+            "https://8.8.8.8/v1/chat/completions",
+            "https://unresolvable.invalid/v1/chat/completions",
+            // Resolved again by the request: never trusted by name
+            "http://localtest.me:11434/api/chat",
+            "not a url",
+        ] {
+            assert_eq!(classify_endpoint(url), EndpointClass::External, "{url}");
+        }
+    }
+
+    #[test]
+    fn test_proxy_variable_by_scheme() {
+        let env = |vars: &'static [(&'static str, &'static str)]| {
+            move |name: &str| {
+                vars.iter()
+                    .find(|(var, _)| *var == name)
+                    .map(|(_, value)| value.to_string())
+            }
+        };
+        let https_only = env(&[("HTTPS_PROXY", "http://proxy:3128")]);
+        assert_eq!(proxy_variable_in("https", https_only), Some("HTTPS_PROXY"));
+        assert_eq!(proxy_variable_in("http", https_only), None);
+
+        let all = env(&[("all_proxy", "socks5://proxy:1080"), ("http_proxy", " ")]);
+        assert_eq!(proxy_variable_in("http", all), Some("all_proxy"));
+        assert_eq!(proxy_variable_in("https", env(&[])), None);
+    }
+
+    #[test]
+    fn test_allowlist_matching() {
+        let policy = DataPolicy::restricted()
+            .allow_host("llm.corp.example.com")
+            .allow_host("*.openai.azure.com");
+        assert!(policy.allows_host("LLM.corp.example.com"));
+        assert!(policy.allows_host("myorg.openai.azure.com"));
+        assert!(!policy.allows_host("openai.azure.com"));
+        assert!(!policy.allows_host("evilopenai.azure.com"));
+        assert!(!policy.allows_host("corp.example.com"));
+        assert!(policy.validate().is_ok());
+
+        for host in [
+            "https://llm.example.com",
+            "llm.example.com:443",
+            "*.",
+            "a*.b",
+        ] {
+            let policy = DataPolicy::restricted().allow_host(host);
+            assert!(policy.validate().is_err(), "{host}");
+        }
+    }
+
+    #[test]
+    fn test_check_refuses_only_restricted_external() {
+        let external = ["https://8.8.8.8/v1/chat/completions"];
+        assert!(DataPolicy::default().check(&external).is_ok());
+
+        let err = DataPolicy::restricted().check(&external).unwrap_err();
+        assert!(matches!(err, CliError::EndpointNotAllowed(_)), "{err}");
+        assert!(err.to_string().contains("'8.8.8.8'"), "{err}");
+
+        let allowed = DataPolicy::restricted().allow_host("8.8.8.8");
+        assert!(allowed.check(&external).is_ok());
+        assert!(DataPolicy::restricted()
+            .check(&["http://localhost:11434/api/generate"])
+            .is_ok());
+        // Host names are only trusted through the allowlist
+        let named = ["http://ollama.internal:11434/api/chat"];
+        assert!(DataPolicy::restricted().check(&named).is_err());
+        assert!(DataPolicy::restricted()
+            .allow_host("ollama.internal")
+            .check(&named)
+            .is_ok());
+    }
+}
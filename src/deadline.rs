@@ -0,0 +1,169 @@
+//! Evaluation deadline
+//!
+//! Bounds the wall time of a whole evaluation and lets operators trade
+//! guardrail coverage for latency explicitly:
+//!
+//! ```toml
+//! [deadline]
+//! total_ms = 8000                    # Whole evaluation, checked before each stage
+//! guardrail_reserve_ms = 2000        # Time a guard stage needs (default: 1000)
+//! skippable = ["llama_guard"]        # Non-critical guardrail types (default: none)
+//! ```
+//!
+//! Once `total_ms` has elapsed, the next stage ends the evaluation with a
+//! `DEADLINE_EXCEEDED` outcome. A guard stage starting with less than
+//! `guardrail_reserve_ms` left runs without the `skippable` provider types
+//! (including composite members) and records a `GUARDRAIL_SKIPPED_DEADLINE`
+//! warning naming them; the other guardrails always run.
+
+use crate::guardrails::{conditions::without, GuardrailProviderConfig};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// Warning code recorded for guardrails skipped to meet the deadline
+pub const GUARDRAIL_SKIPPED_DEADLINE: &str = "GUARDRAIL_SKIPPED_DEADLINE";
+
+fn default_guardrail_reserve_ms() -> u64 {
+    1000
+}
+
+/// Evaluation deadline settings (`[deadline]`)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DeadlineConfig {
+    /// Wall time allowed for the whole evaluation
+    pub total_ms: u64,
+
+    /// Skip `skippable` guardrails when less than this is left (default: 1000)
+    #[serde(default = "default_guardrail_reserve_ms")]
+    pub guardrail_reserve_ms: u64,
+
+    /// Guardrail provider types that may be skipped (e.g. "llama_guard")
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub skippable: Vec<String>,
+}
+
+impl DeadlineConfig {
+    pub fn new(total_ms: u64) -> Self {
+        Self {
+            total_ms,
+            guardrail_reserve_ms: default_guardrail_reserve_ms(),
+            skippable: Vec::new(),
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.total_ms == 0 {
+            return Err("deadline.total_ms must be >= 1".to_string());
+        }
+        if self.guardrail_reserve_ms >= self.total_ms {
+            return Err(format!(
+                "deadline.guardrail_reserve_ms ({}) must be lower than total_ms ({})",
+                self.guardrail_reserve_ms, self.total_ms
+            ));
+        }
+        if let Some(kind) = self
+            .skippable
+            .iter()
+            .find(|kind| !GuardrailProviderConfig::PROVIDER_TYPES.contains(&kind.as_str()))
+        {
+            return Err(format!(
+                "unknown provider type '{kind}' in deadline.skippable (expected one of: {})",
+                GuardrailProviderConfig::PROVIDER_TYPES.join(", ")
+            ));
+        }
+        Ok(())
+    }
+
+    pub fn total(&self) -> Duration {
+        Duration::from_millis(self.total_ms)
+    }
+
+    /// `config` without the skippable providers when only `remaining` is left,
+    /// with the provider types actually removed
+    pub fn apply(
+        &self,
+        config: GuardrailProviderConfig,
+        remaining: Duration,
+    ) -> (Option<GuardrailProviderConfig>, Vec<String>) {
+        if remaining >= Duration::from_millis(self.guardrail_reserve_ms) {
+            return (Some(config), Vec::new());
+        }
+        let mut config = Some(config);
+        let mut skipped = Vec::new();
+        for kind in &self.skippable {
+            let Some(current) = config.take() else {
+                break;
+            };
+            if contains(&current, kind) {
+                skipped.push(kind.clone());
+                config = without(current, kind);
+            } else {
+                config = Some(current);
+            }
+        }
+        (config, skipped)
+    }
+}
+
+/// Whether `config` has a provider of type `kind`, including composite members
+fn contains(config: &GuardrailProviderConfig, kind: &str) -> bool {
+    match config {
+        GuardrailProviderConfig::Composite { providers, .. } => {
+            providers.iter().any(|provider| contains(provider, kind))
+        }
+        other => other.provider_type() == kind,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::guardrails::{AggregationMode, ExecutionMode, GuardrailConfigBuilder};
+
+    #[test]
+    fn test_skippable_guardrails_dropped_within_reserve() {
+        let composite = GuardrailProviderConfig::Composite {
+            providers: vec![
+                GuardrailConfigBuilder::regex()
+                    .max_length_bytes(100)
+                    .build()
+                    .unwrap(),
+                GuardrailConfigBuilder::llama_guard(
+                    "http://localhost:11434/v1/chat/completions",
+                    "llama-guard3:8b",
+                )
+                .build()
+                .unwrap(),
+            ],
+            execution: ExecutionMode::Parallel,
+            aggregation: AggregationMode::AllMustPass,
+        };
+        let deadline = DeadlineConfig {
+            skippable: vec!["llama_guard".to_string(), "nemo".to_string()],
+            ..DeadlineConfig::new(5000)
+        };
+
+        let (config, skipped) = deadline.apply(composite.clone(), Duration::from_millis(1500));
+        assert!(skipped.is_empty());
+        assert!(contains(&config.unwrap(), "llama_guard"));
+
+        let (config, skipped) = deadline.apply(composite, Duration::from_millis(400));
+        assert_eq!(skipped, vec!["llama_guard"]);
+        let config = config.unwrap();
+        assert!(!contains(&config, "llama_guard"));
+        assert!(contains(&config, "regex"));
+    }
+
+    #[test]
+    fn test_validate() {
+        assert!(DeadlineConfig::new(5000).validate().is_ok());
+        assert!(DeadlineConfig::new(500).validate().is_err());
+        let err = DeadlineConfig {
+            skippable: vec!["llamaguard".to_string()],
+            ..DeadlineConfig::new(5000)
+        }
+        .validate()
+        .unwrap_err();
+        assert!(err.contains("unknown provider type 'llamaguard'"), "{err}");
+    }
+}
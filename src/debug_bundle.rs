@@ -0,0 +1,344 @@
+//! Debug bundles for bug reports
+//!
+//! With `--debug-bundle <DIR>`, a run that fails unexpectedly (HTTP errors,
+//! invalid provider responses, crashes of a stage; not guardrail blocks,
+//! refusals, policy refusals or argument and configuration errors) writes
+//! everything needed to reproduce it to a new `DIR/debug-<timestamp>/`
+//! directory:
+//!
+//! | File | Content |
+//! |------|---------|
+//! | `error.json` | Failures, version and build information |
+//! | `config.json` | Effective arguments and config file sections |
+//! | `http.json` | Request payloads and raw responses, as a cassette (`--replay` ready) |
+//! | `spans.json` | Stage, guardrail and LLM call timings |
+//! | `log.txt` | Last log lines, debug level included |
+//!
+//! Secrets are scrubbed like in cassettes: API keys and secret-looking fields
+//! are redacted. Prompts and responses are kept, since they are usually what
+//! reproduces the bug; review the bundle before sharing it.
+
+use crate::{
+    build_info, cassette::Cassette, error::CliError, output::EvaluationOutcome,
+    telemetry::SpanCollector,
+};
+use chrono::Utc;
+use log::{LevelFilter, Log, Metadata, Record};
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::{
+    collections::VecDeque,
+    fs,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex, OnceLock},
+};
+
+/// Log lines kept for the bundle
+pub const LOG_TAIL_LINES: usize = 500;
+
+static LOG_TAIL: OnceLock<Arc<LogTail>> = OnceLock::new();
+
+/// Whether a failure deserves a bug report
+///
+/// Guardrail blocks, refusals, context limits, deadlines and content filters
+/// are expected outcomes; errors are classified by
+/// [`CliError::is_unexpected_code`].
+pub fn is_unexpected(outcome: &EvaluationOutcome) -> bool {
+    match outcome {
+        EvaluationOutcome::Other(code) => CliError::is_unexpected_code(code),
+        _ => false,
+    }
+}
+
+/// Ring buffer of the last formatted log lines
+#[derive(Debug)]
+pub struct LogTail {
+    capacity: usize,
+    lines: Mutex<VecDeque<String>>,
+}
+
+impl LogTail {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            lines: Mutex::new(VecDeque::with_capacity(capacity)),
+        }
+    }
+
+    pub fn push(&self, line: String) {
+        let mut lines = self.lines.lock().unwrap_or_else(|e| e.into_inner());
+        if lines.len() == self.capacity {
+            lines.pop_front();
+        }
+        lines.push_back(line);
+    }
+
+    /// Lines kept, oldest first
+    pub fn lines(&self) -> Vec<String> {
+        let lines = self.lines.lock().unwrap_or_else(|e| e.into_inner());
+        lines.iter().cloned().collect()
+    }
+}
+
+/// Logger passing records to `console` and keeping this crate's debug+
+/// records in a [`LogTail`]
+struct TailLogger<L> {
+    console: L,
+    tail: Arc<LogTail>,
+}
+
+impl<L: Log> Log for TailLogger<L> {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        self.console.enabled(metadata) || captured(metadata)
+    }
+
+    fn log(&self, record: &Record) {
+        if self.console.enabled(record.metadata()) {
+            self.console.log(record);
+        }
+        if captured(record.metadata()) {
+            self.tail.push(format!(
+                "{} [{}] {} - {}",
+                Utc::now().format("%Y-%m-%dT%H:%M:%S%.3fZ"),
+                record.level(),
+                record.target(),
+                record.args()
+            ));
+        }
+    }
+
+    fn flush(&self) {
+        self.console.flush();
+    }
+}
+
+fn captured(metadata: &Metadata) -> bool {
+    metadata.level() <= log::Level::Debug && metadata.target().starts_with(env!("CARGO_CRATE_NAME"))
+}
+
+/// Install `console` as the global logger, also keeping the last log lines
+/// for debug bundles (see [`log_tail`])
+///
+/// The maximum log level is raised to `Debug` so the tail has debug records
+/// even when `console` shows less.
+pub fn install_logger(
+    console: impl Log + 'static,
+    console_level: LevelFilter,
+) -> Result<(), log::SetLoggerError> {
+    let tail = LOG_TAIL
+        .get_or_init(|| Arc::new(LogTail::new(LOG_TAIL_LINES)))
+        .clone();
+    log::set_boxed_logger(Box::new(TailLogger { console, tail }))?;
+    log::set_max_level(console_level.max(LevelFilter::Debug));
+    Ok(())
+}
+
+/// Log lines kept by [`install_logger`] (None if it was not called)
+pub fn log_tail() -> Option<Arc<LogTail>> {
+    LOG_TAIL.get().cloned()
+}
+
+/// Spans finished so far, if the global tracing subscriber is a
+/// [`SpanCollector`]
+pub fn collected_spans() -> Vec<Value> {
+    tracing::dispatcher::get_default(|dispatch| {
+        dispatch
+            .downcast_ref::<SpanCollector>()
+            .map(SpanCollector::finished_spans)
+    })
+    .unwrap_or_default()
+    .iter()
+    .map(|span| {
+        json!({
+            "name": span.name,
+            "duration_ms": span.duration().as_millis() as u64,
+        })
+    })
+    .collect()
+}
+
+/// A failure reported in `error.json`
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BundleFailure {
+    /// Batch item index (None for single evaluations)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub index: Option<usize>,
+    pub code: String,
+    pub message: String,
+}
+
+/// Everything written to a debug bundle
+pub struct DebugBundle<'a> {
+    pub failures: Vec<BundleFailure>,
+    /// Effective configuration (scrubbed when written)
+    pub config: Value,
+    /// HTTP interactions of the run (already scrubbed); also scrubs the
+    /// config and log with its known secrets
+    pub cassette: &'a Cassette,
+    pub spans: Vec<Value>,
+    pub log: Vec<String>,
+}
+
+impl DebugBundle<'_> {
+    /// Write the bundle to a new `debug-<timestamp>` directory in `parent`
+    /// and return its path
+    pub fn write(&self, parent: &Path) -> Result<PathBuf, CliError> {
+        let dir = parent.join(format!(
+            "debug-{}-{}",
+            Utc::now().format("%Y%m%dT%H%M%S%.3fZ"),
+            std::process::id()
+        ));
+        fs::create_dir_all(&dir).map_err(|e| {
+            CliError::InvalidArguments(format!(
+                "Failed to create debug bundle directory '{}': {e}",
+                dir.display()
+            ))
+        })?;
+
+        let error = json!({
+            "timestamp": Utc::now().to_rfc3339(),
+            "failures": self.failures,
+            "build": build_info(),
+        });
+        write_json(&dir.join("error.json"), &error)?;
+        write_json(
+            &dir.join("config.json"),
+            &self.cassette.scrub_value(self.config.clone()),
+        )?;
+        self.cassette.save_to(&dir.join("http.json"))?;
+        write_json(&dir.join("spans.json"), &Value::from(self.spans.clone()))?;
+        let log: String = self
+            .log
+            .iter()
+            .map(|line| self.cassette.scrub_text(line) + "\n")
+            .collect();
+        write_file(&dir.join("log.txt"), &log)?;
+        Ok(dir)
+    }
+}
+
+fn write_json(path: &Path, value: &Value) -> Result<(), CliError> {
+    let json = serde_json::to_string_pretty(value).map_err(|e| {
+        CliError::InvalidArguments(format!("Failed to serialize debug bundle: {e}"))
+    })?;
+    write_file(path, &json)
+}
+
+fn write_file(path: &Path, content: &str) -> Result<(), CliError> {
+    fs::write(path, content).map_err(|e| {
+        CliError::InvalidArguments(format!(
+            "Failed to write debug bundle file '{}': {e}",
+            path.display()
+        ))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_unexpected() {
+        for code in ["HTTP_ERROR", "INVALID_RESPONSE", "PDF_PROCESSING_FAILED"] {
+            assert!(is_unexpected(&EvaluationOutcome::from(code)), "{code}");
+        }
+        for code in [
+            "INPUT_VALIDATION_FAILED",
+            "OUTPUT_VALIDATION_FAILED",
+            "REFUSAL",
+            "CONTEXT_LIMIT_EXCEEDED",
+            "PROVIDER_CONTENT_FILTERED",
+            "ENDPOINT_NOT_ALLOWED",
+            "REQUEST_DECLINED",
+            "RATE_LIMITED",
+            "INVALID_ARGUMENTS",
+            "SELF_TEST_FAILED",
+            "SOME_FUTURE_CODE",
+        ] {
+            assert!(!is_unexpected(&EvaluationOutcome::from(code)), "{code}");
+        }
+    }
+
+    #[test]
+    fn test_unexpected_code_matches_error_classification() {
+        let s = || "x".to_string();
+        let errors = [
+            CliError::ContextLimitExceeded {
+                required: 2,
+                limit: 1,
+                excess: 1,
+            },
+            CliError::InvalidResponse(s()),
+            CliError::FileNotFound(s()),
+            CliError::InvalidArguments(s()),
+            CliError::AuthenticationFailed(s()),
+            CliError::PdfProcessingFailed(s()),
+            CliError::QuotaExceeded(s()),
+            CliError::SelfTestFailed(s()),
+            CliError::FetchFailed(s()),
+            CliError::ImageProcessingFailed(s()),
+            CliError::EndpointNotAllowed(s()),
+            CliError::ResponseTooLarge(s()),
+            CliError::RequestDeclined(s()),
+            CliError::ContentFiltered {
+                message: s(),
+                direction: s(),
+                categories: Vec::new(),
+            },
+            CliError::RateLimited {
+                message: s(),
+                retry_after: None,
+            },
+        ];
+        for error in &errors {
+            assert_eq!(
+                CliError::is_unexpected_code(error.code()),
+                error.is_unexpected(),
+                "{}",
+                error.code()
+            );
+            if error.is_policy_refusal() {
+                assert!(!error.is_unexpected(), "{}", error.code());
+            }
+        }
+    }
+
+    #[test]
+    fn test_log_tail_keeps_last_lines() {
+        let tail = LogTail::new(2);
+        for line in ["a", "b", "c"] {
+            tail.push(line.to_string());
+        }
+        assert_eq!(tail.lines(), vec!["b", "c"]);
+    }
+
+    #[test]
+    fn test_bundle_is_scrubbed() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let cassette = Cassette::record(dir.path().join("unused.json"));
+        cassette.add_secret("sk-secret");
+        let bundle = DebugBundle {
+            failures: vec![BundleFailure {
+                index: None,
+                code: "HTTP_ERROR".to_string(),
+                message: "connection refused".to_string(),
+            }],
+            config: json!({"args": {"api_key": "sk-secret", "model": "llama3"}}),
+            cassette: &cassette,
+            spans: vec![json!({"name": "stage invoke", "duration_ms": 3})],
+            log: vec!["Using key sk-secret".to_string()],
+        };
+        let path = bundle.write(dir.path()).unwrap();
+
+        let config = fs::read_to_string(path.join("config.json")).unwrap();
+        assert!(!config.contains("sk-secret"), "{config}");
+        assert!(config.contains("llama3"), "{config}");
+        let log = fs::read_to_string(path.join("log.txt")).unwrap();
+        assert_eq!(log, "Using key [REDACTED]\n");
+        let error: Value =
+            serde_json::from_str(&fs::read_to_string(path.join("error.json")).unwrap()).unwrap();
+        assert_eq!(error["failures"][0]["code"], "HTTP_ERROR");
+        assert!(Cassette::replay(path.join("http.json")).is_ok());
+        assert!(path.join("spans.json").is_file());
+    }
+}
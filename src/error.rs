@@ -1,3 +1,4 @@
+use std::time::Duration;
 use thiserror::Error;
 
 #[derive(Debug, Error)]
@@ -26,6 +27,55 @@ pub enum CliError {
 
     #[error("PDF processing failed: {0}")]
     PdfProcessingFailed(String),
+
+    #[error("Usage quota exceeded: {0}")]
+    QuotaExceeded(String),
+
+    #[error("Guardrail self-test failed: {0}")]
+    SelfTestFailed(String),
+
+    /// `--url` or `--system-prompt-url` could not be fetched: invalid,
+    /// non-public or non-allowlisted URL, HTTP error, unsupported content type
+    /// or size limit exceeded
+    #[error("URL fetch failed: {0}")]
+    FetchFailed(String),
+
+    /// `--image-file` could not be used: unsupported format, OCR tool missing
+    /// or failed
+    #[error("Image processing failed: {0}")]
+    ImageProcessingFailed(String),
+
+    /// `data_classification = "restricted"` and an endpoint receiving the
+    /// content is external and not in `allowed_external_hosts`
+    #[error("Endpoint not allowed: {0}")]
+    EndpointNotAllowed(String),
+
+    /// A provider, guardrail or context probe response body exceeded
+    /// `max_response_bytes`
+    #[error("Response too large: {0}")]
+    ResponseTooLarge(String),
+
+    /// The confirmation callback (`--confirm`) declined the previewed request
+    #[error("Request declined: {0}")]
+    RequestDeclined(String),
+
+    /// The provider's own content filter rejected the prompt (`direction`
+    /// "input") or stopped the response ("output")
+    #[error("Provider content filter: {message}")]
+    ContentFiltered {
+        message: String,
+        direction: String,
+        /// Filtered categories as reported (e.g. `hate`, `violence:high`)
+        categories: Vec<String>,
+    },
+
+    /// The provider answered HTTP 429; `retry_after` is its `Retry-After`
+    /// delay, when sent
+    #[error("Rate limited by provider: {message}")]
+    RateLimited {
+        message: String,
+        retry_after: Option<Duration>,
+    },
 }
 
 impl CliError {
@@ -39,9 +89,70 @@ impl CliError {
             Self::InvalidArguments(_) => "INVALID_ARGUMENTS",
             Self::AuthenticationFailed(_) => "AUTH_FAILED",
             Self::PdfProcessingFailed(_) => "PDF_PROCESSING_FAILED",
+            Self::QuotaExceeded(_) => "QUOTA_EXCEEDED",
+            Self::SelfTestFailed(_) => "SELF_TEST_FAILED",
+            Self::FetchFailed(_) => "FETCH_FAILED",
+            Self::ImageProcessingFailed(_) => "IMAGE_PROCESSING_FAILED",
+            Self::EndpointNotAllowed(_) => "ENDPOINT_NOT_ALLOWED",
+            Self::ResponseTooLarge(_) => "RESPONSE_TOO_LARGE",
+            Self::RequestDeclined(_) => "REQUEST_DECLINED",
+            Self::ContentFiltered { .. } => "PROVIDER_CONTENT_FILTERED",
+            Self::RateLimited { .. } => "RATE_LIMITED",
         }
     }
 
+    /// Refusal by a configured policy (quota, rate limit, endpoint allowlist)
+    /// or by the user (declined confirmation)
+    pub fn is_policy_refusal(&self) -> bool {
+        matches!(
+            self,
+            Self::QuotaExceeded(_)
+                | Self::RateLimited { .. }
+                | Self::EndpointNotAllowed(_)
+                | Self::RequestDeclined(_)
+        )
+    }
+
+    /// Whether this failure points at a bug or an outage worth a debug bundle
+    ///
+    /// Argument and configuration errors, expected pipeline outcomes and
+    /// policy refusals are not. Every variant is listed, so a new one needs an
+    /// explicit decision.
+    pub fn is_unexpected(&self) -> bool {
+        match self {
+            Self::HttpError(_)
+            | Self::InvalidResponse(_)
+            | Self::PdfProcessingFailed(_)
+            | Self::FetchFailed(_)
+            | Self::ImageProcessingFailed(_)
+            | Self::ResponseTooLarge(_) => true,
+            Self::InvalidArguments(_)
+            | Self::FileNotFound(_)
+            | Self::AuthenticationFailed(_)
+            | Self::SelfTestFailed(_)
+            | Self::ContextLimitExceeded { .. }
+            | Self::ContentFiltered { .. } => false,
+            Self::QuotaExceeded(_)
+            | Self::RateLimited { .. }
+            | Self::EndpointNotAllowed(_)
+            | Self::RequestDeclined(_) => false,
+        }
+    }
+
+    /// [`CliError::is_unexpected`] for an error known only by its code (e.g.
+    /// a batch item's output); unknown codes are not unexpected
+    pub fn is_unexpected_code(code: &str) -> bool {
+        matches!(
+            code,
+            "HTTP_ERROR"
+                | "INVALID_RESPONSE"
+                | "PDF_PROCESSING_FAILED"
+                | "FETCH_FAILED"
+                | "IMAGE_PROCESSING_FAILED"
+                | "RESPONSE_TOO_LARGE"
+        )
+    }
+
     /// Get the exit code for this error
     pub fn exit_code(&self) -> i32 {
         match self {
@@ -52,6 +163,15 @@ impl CliError {
             Self::InvalidArguments(_) => 6,
             Self::AuthenticationFailed(_) => 7,
             Self::PdfProcessingFailed(_) => 8,
+            Self::QuotaExceeded(_) => 9,
+            Self::SelfTestFailed(_) => 10,
+            Self::ContentFiltered { .. } => 11,
+            Self::RateLimited { .. } => 12,
+            Self::FetchFailed(_) => 13,
+            Self::ImageProcessingFailed(_) => 14,
+            Self::EndpointNotAllowed(_) => 15,
+            Self::ResponseTooLarge(_) => 16,
+            Self::RequestDeclined(_) => 17,
         }
     }
 }
@@ -0,0 +1,210 @@
+//! Progress event stream
+//!
+//! [`EventWriter`] writes one JSON object per line (NDJSON) as batch items and
+//! server requests start and finish, so orchestration systems can follow a
+//! run without polling output files. Lines are flushed as they are written.
+//!
+//! ```text
+//! {"timestamp":"...","event":"item_started","index":0,"id":"q1"}
+//! {"timestamp":"...","event":"guardrail_blocked","index":0,"id":"q1","direction":"input","outcome":"INPUT_VALIDATION_FAILED","rules":["regex.MAX_LENGTH"]}
+//! {"timestamp":"...","event":"item_completed","index":0,"id":"q1","status":"error","outcome":"INPUT_VALIDATION_FAILED","latency_ms":2,"tokens_estimated":12}
+//! {"timestamp":"...","event":"batch_summary","total":1,"succeeded":0,"blocked":1,"errors":0,...}
+//! ```
+
+use crate::{BatchSummary, CliError, CliOutput};
+use serde::Serialize;
+use std::{
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::Path,
+    sync::Mutex,
+};
+
+/// One line of the event stream (`event` holds the snake_case variant name)
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum Event {
+    /// An evaluation was handed to the pipeline
+    ItemStarted {
+        index: usize,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        id: Option<String>,
+    },
+    /// Input or output guardrails stopped an evaluation
+    GuardrailBlocked {
+        index: usize,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        id: Option<String>,
+        /// "input" or "output"
+        direction: String,
+        outcome: String,
+        /// Namespaced rule IDs (e.g. `regex.MAX_LENGTH`)
+        rules: Vec<String>,
+    },
+    /// An evaluation finished (successfully or not)
+    ItemCompleted {
+        index: usize,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        id: Option<String>,
+        /// "success", "error" or "refusal"
+        status: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        outcome: Option<String>,
+        latency_ms: u64,
+        tokens_estimated: usize,
+    },
+    /// All batch items finished
+    BatchSummary {
+        #[serde(flatten)]
+        summary: BatchSummary,
+    },
+}
+
+impl Event {
+    /// Events reporting a finished evaluation: `guardrail_blocked` (when
+    /// guardrails stopped it) followed by `item_completed`
+    pub fn completed(index: usize, id: Option<&str>, output: &CliOutput) -> Vec<Event> {
+        let id = id.map(str::to_string);
+        let outcome = output.error.as_ref().map(|e| &e.code);
+        let mut events = Vec::new();
+        if let (Some(direction), Some(outcome)) = (output.blocked_direction(), outcome) {
+            events.push(Event::GuardrailBlocked {
+                index,
+                id: id.clone(),
+                direction: direction.to_string(),
+                outcome: outcome.to_string(),
+                rules: output.metadata.violation_rules.clone(),
+            });
+        }
+        events.push(Event::ItemCompleted {
+            index,
+            id,
+            status: output.status.clone(),
+            outcome: outcome.map(ToString::to_string),
+            latency_ms: output.metadata.latency_ms,
+            tokens_estimated: output.metadata.tokens_estimated,
+        });
+        events
+    }
+}
+
+#[derive(Serialize)]
+struct EventLine<'a> {
+    timestamp: String,
+    #[serde(flatten)]
+    event: &'a Event,
+}
+
+/// Thread-safe NDJSON event sink
+pub struct EventWriter {
+    out: Mutex<Box<dyn Write + Send>>,
+}
+
+impl std::fmt::Debug for EventWriter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EventWriter").finish_non_exhaustive()
+    }
+}
+
+impl EventWriter {
+    pub fn new(out: impl Write + Send + 'static) -> Self {
+        Self {
+            out: Mutex::new(Box::new(out)),
+        }
+    }
+
+    /// Create (or truncate) an events file; `-` writes to stdout
+    pub fn create(path: &Path) -> Result<Self, CliError> {
+        if path == Path::new("-") {
+            return Ok(Self::new(io::stdout()));
+        }
+        let file = File::create(path).map_err(|e| {
+            CliError::InvalidArguments(format!(
+                "Failed to create events file {}: {e}",
+                path.display()
+            ))
+        })?;
+        Ok(Self::new(BufWriter::new(file)))
+    }
+
+    /// Write one event line
+    ///
+    /// Write errors are logged, never returned: a broken event consumer must
+    /// not fail the evaluations it observes.
+    pub fn emit(&self, event: &Event) {
+        let line = EventLine {
+            timestamp: chrono::Utc::now().to_rfc3339(),
+            event,
+        };
+        let mut out = self.out.lock().expect("event writer lock poisoned");
+        let written = serde_json::to_string(&line)
+            .map_err(io::Error::other)
+            .and_then(|json| writeln!(out, "{json}"))
+            .and_then(|()| out.flush());
+        if let Err(e) = written {
+            log::warn!("Failed to write event: {e}");
+        }
+    }
+
+    pub fn emit_all(&self, events: &[Event]) {
+        for event in events {
+            self.emit(event);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{config_builder::ConfigBuilder, EvaluationContext, EvaluationOutcome};
+    use std::sync::Arc;
+
+    #[derive(Clone, Default)]
+    struct Buffer(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Buffer {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_blocked_output_emits_guardrail_event_then_completion() {
+        let config = ConfigBuilder::new()
+            .api_url("mock://")
+            .model("mock-model")
+            .system_prompt("sys")
+            .user_prompt("a very long prompt")
+            .build()
+            .unwrap();
+        let mut metadata = EvaluationContext::new(config).metadata();
+        metadata.violation_rules = vec!["regex.MAX_LENGTH".to_string()];
+        let output = CliOutput::error(
+            EvaluationOutcome::InputValidationFailed,
+            "too long".to_string(),
+            metadata,
+        );
+
+        let buffer = Buffer::default();
+        let writer = EventWriter::new(buffer.clone());
+        writer.emit_all(&Event::completed(3, Some("q4"), &output));
+
+        let text = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+        let lines: Vec<serde_json::Value> = text
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0]["event"], "guardrail_blocked");
+        assert_eq!(lines[0]["direction"], "input");
+        assert_eq!(lines[0]["rules"], serde_json::json!(["regex.MAX_LENGTH"]));
+        assert_eq!(lines[1]["event"], "item_completed");
+        assert_eq!(lines[1]["index"], 3);
+        assert_eq!(lines[1]["id"], "q4");
+        assert_eq!(lines[1]["outcome"], "INPUT_VALIDATION_FAILED");
+        assert!(lines[1]["timestamp"].is_string());
+    }
+}
@@ -0,0 +1,758 @@
+//! A/B experiments over system prompt variants
+//!
+//! Runs a dataset of [`BatchItem`]s against two or more system prompts,
+//! scores every response with assertions or an LLM judge, and compares the
+//! variants pairwise with win rates and a significance hint:
+//!
+//! ```toml
+//! [experiment]
+//! assignment = "all"   # "all": every item on every variant; "split": round-robin
+//! variants = [
+//!   { name = "concise", system_prompt = "Answer in one sentence." },
+//!   { name = "thorough", system_prompt_file = "prompts/thorough.txt" },
+//! ]
+//!
+//! [[experiment.assertions]]
+//! type = "contains"
+//! value = "refund"
+//!
+//! [experiment.judge]   # Optional: scores replace the assertion pass ratio
+//! api_url = "http://localhost:11434/v1/chat/completions"
+//! model = "llama3.1:70b"
+//! criteria = "Accurate, complete and polite"
+//! ```
+//!
+//! Each run is tagged `variant=<name>`, so per-variant counts come from the
+//! batch summary.
+
+use crate::{
+    evaluate_batch, log_policy::loggable, response_limit, BatchItem, CliError, CliOutput,
+    EvaluationConfig, InvokeParams, LlmClient, SecretSource,
+};
+use futures::stream::{self, StreamExt};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::{collections::HashSet, fs, path::PathBuf};
+
+/// p-value below which a difference is reported as significant
+const SIGNIFICANCE_LEVEL: f64 = 0.05;
+
+/// Decisive comparisons needed before a significance hint is given
+const MIN_SAMPLES: usize = 5;
+
+/// Experiment settings as written in config files (`[experiment]`)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExperimentConfig {
+    pub variants: Vec<PromptVariant>,
+    #[serde(default)]
+    pub assignment: Assignment,
+    /// Checks applied to every successful response
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub assertions: Vec<Assertion>,
+    /// LLM grading responses from 0 to 10
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub judge: Option<JudgeConfig>,
+}
+
+/// How dataset items are distributed over the variants
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Assignment {
+    /// Every item runs on every variant (paired comparison)
+    #[default]
+    All,
+    /// Item `i` runs on variant `i % variants` (independent samples)
+    Split,
+}
+
+/// One system prompt under test
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PromptVariant {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub system_prompt: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub system_prompt_file: Option<PathBuf>,
+}
+
+impl PromptVariant {
+    fn load_system_prompt(&self) -> Result<String, CliError> {
+        match (&self.system_prompt, &self.system_prompt_file) {
+            (Some(prompt), None) => Ok(prompt.clone()),
+            (None, Some(path)) => fs::read_to_string(path).map_err(|e| {
+                CliError::FileNotFound(format!(
+                    "Failed to read system prompt of variant '{}' from '{}': {e}",
+                    self.name,
+                    path.display()
+                ))
+            }),
+            _ => Err(CliError::InvalidArguments(format!(
+                "Variant '{}' must set exactly one of 'system_prompt' and 'system_prompt_file'",
+                self.name
+            ))),
+        }
+    }
+}
+
+/// Check on a response's text
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Assertion {
+    Contains {
+        value: String,
+        #[serde(default)]
+        case_sensitive: bool,
+    },
+    NotContains {
+        value: String,
+        #[serde(default)]
+        case_sensitive: bool,
+    },
+    Regex {
+        pattern: String,
+    },
+    /// At most `max` characters
+    MaxChars {
+        max: usize,
+    },
+    /// The response parses as JSON
+    JsonValid,
+}
+
+impl Assertion {
+    pub(crate) fn validate(&self) -> Result<(), CliError> {
+        if let Assertion::Regex { pattern } = self {
+            Regex::new(pattern).map_err(|e| {
+                CliError::InvalidArguments(format!(
+                    "Invalid experiment assertion regex '{pattern}': {e}"
+                ))
+            })?;
+        }
+        Ok(())
+    }
+
+    /// Whether `text` satisfies the assertion
+    pub fn check(&self, text: &str) -> bool {
+        let contains = |value: &str, case_sensitive: bool| {
+            if case_sensitive {
+                text.contains(value)
+            } else {
+                text.to_lowercase().contains(&value.to_lowercase())
+            }
+        };
+        match self {
+            Assertion::Contains {
+                value,
+                case_sensitive,
+            } => contains(value, *case_sensitive),
+            Assertion::NotContains {
+                value,
+                case_sensitive,
+            } => !contains(value, *case_sensitive),
+            Assertion::Regex { pattern } => Regex::new(pattern).is_ok_and(|re| re.is_match(text)),
+            Assertion::MaxChars { max } => text.chars().count() <= *max,
+            Assertion::JsonValid => serde_json::from_str::<serde_json::Value>(text).is_ok(),
+        }
+    }
+}
+
+/// LLM grading each response against `criteria`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JudgeConfig {
+    pub api_url: String,
+    pub model: String,
+    /// What a good response looks like
+    pub criteria: String,
+    #[serde(default = "default_judge_timeout")]
+    pub timeout_secs: u64,
+    /// Environment variable holding the judge's API key
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api_key_name: Option<String>,
+    /// Env, file, keyring or vault reference holding the judge's API key
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api_key_secret: Option<SecretSource>,
+}
+
+fn default_judge_timeout() -> u64 {
+    60
+}
+
+const JUDGE_SYSTEM_PROMPT: &str = "You are an impartial evaluator. Rate how well the response \
+    meets the criteria on a scale from 0 (not at all) to 10 (perfectly). Reply with the number only.";
+
+static SCORE: Lazy<Regex> = Lazy::new(|| Regex::new(r"\d+(?:\.\d+)?").expect("valid regex"));
+
+impl ExperimentConfig {
+    /// At least two uniquely named variants, each with one prompt source,
+    /// and valid assertions
+    pub fn validate(&self) -> Result<(), CliError> {
+        if self.variants.len() < 2 {
+            return Err(CliError::InvalidArguments(
+                "An experiment needs at least 2 variants".to_string(),
+            ));
+        }
+        let mut names = HashSet::new();
+        for variant in &self.variants {
+            if variant.name.trim().is_empty() {
+                return Err(CliError::InvalidArguments(
+                    "Experiment variant names must not be empty".to_string(),
+                ));
+            }
+            if !names.insert(variant.name.as_str()) {
+                return Err(CliError::InvalidArguments(format!(
+                    "Duplicate experiment variant '{}'",
+                    variant.name
+                )));
+            }
+            if variant.system_prompt.is_some() == variant.system_prompt_file.is_some() {
+                return Err(CliError::InvalidArguments(format!(
+                    "Variant '{}' must set exactly one of 'system_prompt' and 'system_prompt_file'",
+                    variant.name
+                )));
+            }
+        }
+        self.assertions.iter().try_for_each(Assertion::validate)
+    }
+}
+
+/// Outcome of one item on one variant
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ExperimentRun {
+    /// Position of the item in the dataset
+    pub item: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    pub variant: String,
+    pub status: String,
+    /// 0.0-1.0: judge score, else share of assertions passed (0 unless successful)
+    pub score: f64,
+    /// Successful and every assertion passed
+    pub passed: bool,
+}
+
+/// Aggregates for one variant
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct VariantReport {
+    pub name: String,
+    pub runs: usize,
+    pub succeeded: usize,
+    pub blocked: usize,
+    pub errors: usize,
+    pub pass_rate: f64,
+    pub mean_score: f64,
+    pub mean_latency_ms: u64,
+    pub tokens_estimated: u64,
+}
+
+/// Pairwise comparison of two variants
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct VariantComparison {
+    pub a: String,
+    pub b: String,
+    /// Items where `a` scored higher / lower / the same (`all` assignment only)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub a_wins: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub b_wins: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ties: Option<usize>,
+    /// Share of decisive items won by `a` (`all` assignment only)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub win_rate: Option<f64>,
+    /// Mean score of `a` minus mean score of `b`
+    pub score_delta: f64,
+    /// Sign test (`all`) or two-proportion z-test on pass rates (`split`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub p_value: Option<f64>,
+    /// Human-readable significance hint
+    pub hint: String,
+}
+
+/// Result of [`run_experiment`]
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ExperimentReport {
+    pub assignment: Assignment,
+    pub items: usize,
+    pub variants: Vec<VariantReport>,
+    pub comparisons: Vec<VariantComparison>,
+    pub runs: Vec<ExperimentRun>,
+}
+
+/// Run `items` on the experiment's variants and compare them
+///
+/// Items are applied to `base` (see [`BatchItem::apply`]), then the variant's
+/// system prompt replaces any item or base system prompt. Evaluations and
+/// judge calls run with at most `concurrency` in flight.
+pub async fn run_experiment(
+    base: &EvaluationConfig,
+    items: &[BatchItem],
+    experiment: &ExperimentConfig,
+    concurrency: usize,
+) -> Result<ExperimentReport, CliError> {
+    experiment.validate()?;
+    let prompts = experiment
+        .variants
+        .iter()
+        .map(PromptVariant::load_system_prompt)
+        .collect::<Result<Vec<_>, _>>()?;
+    let judge = prepare_judge(experiment.judge.as_ref(), base)?;
+
+    let variant_count = experiment.variants.len();
+    let plan: Vec<(usize, usize)> = (0..items.len())
+        .flat_map(|item| match experiment.assignment {
+            Assignment::All => (0..variant_count).map(|v| (item, v)).collect::<Vec<_>>(),
+            Assignment::Split => vec![(item, item % variant_count)],
+        })
+        .collect();
+    let configs = plan
+        .iter()
+        .map(|&(item, variant)| {
+            let mut config = items[item].apply(base);
+            config.system_prompt = prompts[variant].clone();
+            config.system_prompt_file = None;
+            config
+                .tags
+                .push(variant_tag(&experiment.variants[variant].name));
+            config
+        })
+        .collect();
+
+    log::info!(
+        "Experiment: {} item(s) x {} variant(s), {} run(s)",
+        items.len(),
+        variant_count,
+        plan.len()
+    );
+    let batch = evaluate_batch(configs, concurrency).await;
+
+    let judge = judge.as_ref();
+    let scores: Vec<(f64, bool)> = response_limit::scope(
+        base.max_response_bytes,
+        stream::iter(plan.iter().zip(&batch.results))
+            .map(|(&(item, _), output)| async move {
+                score(
+                    output,
+                    &items[item].user_prompt,
+                    &experiment.assertions,
+                    judge,
+                )
+                .await
+            })
+            .buffered(concurrency.max(1))
+            .collect(),
+    )
+    .await;
+
+    let runs: Vec<ExperimentRun> = plan
+        .iter()
+        .zip(&batch.results)
+        .zip(scores)
+        .map(
+            |((&(item, variant), output), (score, passed))| ExperimentRun {
+                item,
+                id: items[item].id.clone(),
+                variant: experiment.variants[variant].name.clone(),
+                status: output.status.clone(),
+                score,
+                passed,
+            },
+        )
+        .collect();
+
+    let variants = experiment
+        .variants
+        .iter()
+        .map(|variant| {
+            let counts = batch
+                .summary
+                .by_tag
+                .get(&variant_tag(&variant.name))
+                .cloned()
+                .unwrap_or_default();
+            let variant_runs: Vec<&ExperimentRun> =
+                runs.iter().filter(|r| r.variant == variant.name).collect();
+            VariantReport {
+                name: variant.name.clone(),
+                runs: counts.total,
+                succeeded: counts.succeeded,
+                blocked: counts.blocked,
+                errors: counts.errors,
+                pass_rate: round(ratio(
+                    variant_runs.iter().filter(|r| r.passed).count(),
+                    variant_runs.len(),
+                )),
+                mean_score: round(mean(variant_runs.iter().map(|r| r.score))),
+                mean_latency_ms: counts
+                    .total_latency_ms
+                    .checked_div(counts.total as u64)
+                    .unwrap_or(0),
+                tokens_estimated: counts.tokens_estimated,
+            }
+        })
+        .collect();
+
+    let mut comparisons = Vec::new();
+    for a in 0..variant_count {
+        for b in a + 1..variant_count {
+            comparisons.push(compare(
+                &experiment.variants[a].name,
+                &experiment.variants[b].name,
+                &runs,
+                experiment.assignment,
+            ));
+        }
+    }
+
+    Ok(ExperimentReport {
+        assignment: experiment.assignment,
+        items: items.len(),
+        variants,
+        comparisons,
+        runs,
+    })
+}
+
+/// Judge settings with the resolved API key and a client
+pub(crate) type PreparedJudge<'a> = (&'a JudgeConfig, Option<String>, LlmClient);
+
+/// Resolve the judge's API key from its environment variable or secret source
+///
+/// The judge sees every prompt and response, so its endpoint must pass the
+/// base configuration's data policy before anything is evaluated.
+pub(crate) fn prepare_judge<'a>(
+    judge: Option<&'a JudgeConfig>,
+    base: &EvaluationConfig,
+) -> Result<Option<PreparedJudge<'a>>, CliError> {
+    judge
+        .map(|judge| {
+            if let Some(policy) = &base.data_policy {
+                policy.check(&[judge.api_url.as_str()])?;
+            }
+            let api_key = match (&judge.api_key_name, &judge.api_key_secret) {
+                (Some(_), Some(_)) => {
+                    return Err(CliError::InvalidArguments(
+                        "Judge cannot specify both 'api_key_name' and 'api_key_secret'"
+                            .to_string(),
+                    ))
+                }
+                (Some(name), None) => Some(std::env::var(name).map_err(|_| {
+                    CliError::InvalidArguments(format!(
+                        "Environment variable '{name}' specified by judge 'api_key_name' does not exist"
+                    ))
+                })?),
+                (None, Some(source)) => Some(source.resolve()?),
+                (None, None) => None,
+            };
+            Ok((judge, api_key, LlmClient::new(judge.api_url.clone(), None)))
+        })
+        .transpose()
+}
+
+fn variant_tag(name: &str) -> String {
+    format!("variant={name}")
+}
+
+/// Response text for assertions and the judge (JSON responses re-serialized)
+pub(crate) fn response_text(output: &CliOutput) -> Option<String> {
+    output.response.as_ref().map(|response| match response {
+        serde_json::Value::String(text) => text.clone(),
+        other => other.to_string(),
+    })
+}
+
+/// Score of a run (0.0-1.0) and whether it passed every assertion
+pub(crate) async fn score(
+    output: &CliOutput,
+    user_prompt: &str,
+    assertions: &[Assertion],
+    judge: Option<&PreparedJudge<'_>>,
+) -> (f64, bool) {
+    let Some(text) = response_text(output).filter(|_| output.status == "success") else {
+        return (0.0, false);
+    };
+    let passed_assertions = assertions.iter().filter(|a| a.check(&text)).count();
+    let passed = passed_assertions == assertions.len();
+
+    let score = match judge {
+        Some((config, api_key, client)) => {
+            judge_score(client, config, api_key.as_deref(), user_prompt, &text)
+                .await
+                .unwrap_or(0.0)
+        }
+        None if assertions.is_empty() => 1.0,
+        None => ratio(passed_assertions, assertions.len()),
+    };
+    (score, passed)
+}
+
+/// Judge score scaled to 0.0-1.0 (None if the call fails or no number is found)
+async fn judge_score(
+    client: &LlmClient,
+    judge: &JudgeConfig,
+    api_key: Option<&str>,
+    user_prompt: &str,
+    response: &str,
+) -> Option<f64> {
+    let prompt = format!(
+        "Criteria: {}\n\nPrompt:\n{user_prompt}\n\nResponse:\n{response}",
+        judge.criteria
+    );
+    let reply = client
+        .invoke(InvokeParams {
+            model: &judge.model,
+            system_prompt: JUDGE_SYSTEM_PROMPT,
+            user_prompt: &prompt,
+            temperature: 0.0,
+            top_p: None,
+            max_tokens: Some(16),
+            seed: None,
+            api_key,
+            timeout_secs: judge.timeout_secs,
+            response_format: None,
+            tools: &[],
+            images: &[],
+        })
+        .await
+        .map_err(|e| log::warn!("Experiment judge call failed: {e}"))
+        .ok()?;
+    let Some(number) = SCORE.find(&reply) else {
+        log::warn!("Experiment judge reply has no score: {}", loggable(&reply));
+        return None;
+    };
+    let value: f64 = number.as_str().parse().ok()?;
+    Some(value.clamp(0.0, 10.0) / 10.0)
+}
+
+fn compare(a: &str, b: &str, runs: &[ExperimentRun], assignment: Assignment) -> VariantComparison {
+    let of =
+        |name: &str| -> Vec<&ExperimentRun> { runs.iter().filter(|r| r.variant == name).collect() };
+    let (runs_a, runs_b) = (of(a), of(b));
+    let score_delta =
+        round(mean(runs_a.iter().map(|r| r.score)) - mean(runs_b.iter().map(|r| r.score)));
+
+    let mut comparison = VariantComparison {
+        a: a.to_string(),
+        b: b.to_string(),
+        a_wins: None,
+        b_wins: None,
+        ties: None,
+        win_rate: None,
+        score_delta,
+        p_value: None,
+        hint: String::new(),
+    };
+
+    let samples = match assignment {
+        Assignment::All => {
+            // Runs are in item order, so the same index is the same item
+            let (mut wins, mut losses, mut ties) = (0, 0, 0);
+            for (run_a, run_b) in runs_a.iter().zip(&runs_b) {
+                match run_a.score.partial_cmp(&run_b.score) {
+                    Some(std::cmp::Ordering::Greater) => wins += 1,
+                    Some(std::cmp::Ordering::Less) => losses += 1,
+                    _ => ties += 1,
+                }
+            }
+            comparison.a_wins = Some(wins);
+            comparison.b_wins = Some(losses);
+            comparison.ties = Some(ties);
+            comparison.win_rate = (wins + losses > 0).then(|| round(ratio(wins, wins + losses)));
+            comparison.p_value = sign_test(wins, losses);
+            wins + losses
+        }
+        Assignment::Split => {
+            let passed = |runs: &[&ExperimentRun]| runs.iter().filter(|r| r.passed).count();
+            comparison.p_value =
+                two_proportion_test(passed(&runs_a), runs_a.len(), passed(&runs_b), runs_b.len());
+            runs_a.len().min(runs_b.len())
+        }
+    };
+    comparison.p_value = comparison.p_value.map(round);
+
+    comparison.hint = match comparison.p_value {
+        _ if samples < MIN_SAMPLES => {
+            format!("Not enough data ({samples} decisive sample(s), need {MIN_SAMPLES})")
+        }
+        Some(p) if p < SIGNIFICANCE_LEVEL => {
+            let (better, worse) = if score_delta >= 0.0 { (a, b) } else { (b, a) };
+            format!("'{better}' outperforms '{worse}' (p = {p:.3})")
+        }
+        Some(p) => format!("No significant difference (p = {p:.3})"),
+        None => "No significant difference".to_string(),
+    };
+    comparison
+}
+
+/// Two-sided sign test on paired wins and losses (ties excluded)
+fn sign_test(wins: usize, losses: usize) -> Option<f64> {
+    let n = wins + losses;
+    if n == 0 {
+        return None;
+    }
+    let k = wins.min(losses);
+    let p = if n <= 100 {
+        // Exact binomial tail: sum of C(n, i) / 2^n for i <= k
+        let mut term = 0.5f64.powi(n as i32);
+        let mut tail = 0.0;
+        for i in 0..=k {
+            tail += term;
+            term *= (n - i) as f64 / (i + 1) as f64;
+        }
+        2.0 * tail
+    } else {
+        // Normal approximation with continuity correction
+        let z = ((wins as f64 - losses as f64).abs() - 1.0).max(0.0) / (n as f64).sqrt();
+        2.0 * (1.0 - normal_cdf(z))
+    };
+    Some(p.min(1.0))
+}
+
+/// Two-sided two-proportion z-test
+fn two_proportion_test(x1: usize, n1: usize, x2: usize, n2: usize) -> Option<f64> {
+    if n1 == 0 || n2 == 0 {
+        return None;
+    }
+    let (n1, n2) = (n1 as f64, n2 as f64);
+    let pooled = (x1 + x2) as f64 / (n1 + n2);
+    let se = (pooled * (1.0 - pooled) * (1.0 / n1 + 1.0 / n2)).sqrt();
+    if se == 0.0 {
+        return Some(1.0);
+    }
+    let z = (x1 as f64 / n1 - x2 as f64 / n2).abs() / se;
+    Some((2.0 * (1.0 - normal_cdf(z))).min(1.0))
+}
+
+fn normal_cdf(z: f64) -> f64 {
+    0.5 * (1.0 + erf(z / std::f64::consts::SQRT_2))
+}
+
+/// Abramowitz & Stegun 7.1.26 (max error 1.5e-7)
+fn erf(x: f64) -> f64 {
+    let sign = x.signum();
+    let x = x.abs();
+    let t = 1.0 / (1.0 + 0.3275911 * x);
+    let poly = t
+        * (0.254829592
+            + t * (-0.284496736 + t * (1.421413741 + t * (-1.453152027 + t * 1.061405429))));
+    sign * (1.0 - poly * (-x * x).exp())
+}
+
+pub(crate) fn ratio(count: usize, total: usize) -> f64 {
+    if total == 0 {
+        0.0
+    } else {
+        count as f64 / total as f64
+    }
+}
+
+pub(crate) fn mean(values: impl Iterator<Item = f64>) -> f64 {
+    let (sum, count) = values.fold((0.0, 0), |(sum, count), v| (sum + v, count + 1));
+    if count == 0 {
+        0.0
+    } else {
+        sum / count as f64
+    }
+}
+
+pub(crate) fn round(value: f64) -> f64 {
+    (value * 1000.0).round() / 1000.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(item: usize, variant: &str, score: f64) -> ExperimentRun {
+        ExperimentRun {
+            item,
+            id: None,
+            variant: variant.to_string(),
+            status: "success".to_string(),
+            score,
+            passed: score >= 1.0,
+        }
+    }
+
+    #[test]
+    fn test_assertions() {
+        let contains = Assertion::Contains {
+            value: "Refund".to_string(),
+            case_sensitive: false,
+        };
+        assert!(contains.check("Our refund policy"));
+        assert!(!Assertion::NotContains {
+            value: "sorry".to_string(),
+            case_sensitive: true,
+        }
+        .check("I'm sorry"));
+        assert!(Assertion::MaxChars { max: 5 }.check("héllo"));
+        assert!(Assertion::JsonValid.check("{\"a\": 1}"));
+        assert!(!Assertion::JsonValid.check("a: 1"));
+        assert!(Assertion::Regex {
+            pattern: r"^\d+ days$".to_string()
+        }
+        .check("30 days"));
+    }
+
+    #[test]
+    fn test_validate() {
+        let config: ExperimentConfig = toml::from_str(
+            r#"
+            variants = [
+              { name = "a", system_prompt = "A" },
+              { name = "a", system_prompt = "B" },
+            ]
+            "#,
+        )
+        .unwrap();
+        assert!(config
+            .validate()
+            .unwrap_err()
+            .to_string()
+            .contains("Duplicate"));
+
+        let config: ExperimentConfig = toml::from_str(
+            r#"
+            variants = [{ name = "a", system_prompt = "A" }, { name = "b" }]
+            [[assertions]]
+            type = "regex"
+            pattern = "("
+            "#,
+        )
+        .unwrap();
+        assert!(config.validate().unwrap_err().to_string().contains("'b'"));
+    }
+
+    #[test]
+    fn test_paired_comparison() {
+        let mut runs = Vec::new();
+        for item in 0..10 {
+            runs.push(run(item, "a", 1.0));
+            runs.push(run(item, "b", if item < 9 { 0.0 } else { 1.0 }));
+        }
+        let comparison = compare("a", "b", &runs, Assignment::All);
+        assert_eq!(comparison.a_wins, Some(9));
+        assert_eq!(comparison.b_wins, Some(0));
+        assert_eq!(comparison.ties, Some(1));
+        assert_eq!(comparison.win_rate, Some(1.0));
+        assert_eq!(comparison.score_delta, 0.9);
+        assert!(comparison.p_value.unwrap() < 0.01);
+        assert!(comparison.hint.starts_with("'a' outperforms 'b'"));
+
+        let comparison = compare("a", "b", &runs[..4], Assignment::All);
+        assert!(comparison.hint.starts_with("Not enough data"));
+    }
+
+    #[test]
+    fn test_significance_tests() {
+        // 8 of 10: p = 2 * (1 + 10 + 45) / 1024
+        assert!((sign_test(8, 2).unwrap() - 0.109375).abs() < 1e-9);
+        assert_eq!(sign_test(5, 5), Some(1.0));
+        assert!(sign_test(300, 200).unwrap() < 0.001);
+
+        assert!(two_proportion_test(45, 50, 25, 50).unwrap() < 0.001);
+        assert!(two_proportion_test(26, 50, 25, 50).unwrap() > 0.5);
+        assert_eq!(two_proportion_test(0, 0, 1, 2), None);
+    }
+}
@@ -0,0 +1,716 @@
+//! Web page ingestion (`--url`) and prompt library URLs
+//!
+//! Fetches a page, keeps its main content (readability-style: the `<article>`,
+//! `<main>` or `<body>` element without scripts, navigation, headers, footers,
+//! asides and forms) and converts it to markdown for use as the user prompt.
+//! [`fetch_prompt`] fetches a system prompt (`--system-prompt-url`) as-is.
+//!
+//! Fetching is locked down by default:
+//! - Only `http` and `https` URLs are accepted
+//! - Hosts resolving to loopback, private, link-local, CGNAT or other
+//!   non-public addresses are refused (SSRF protection); the vetted addresses
+//!   are pinned for the request, so DNS cannot be rebound in between
+//! - Proxy environment variables (`HTTP_PROXY`, `HTTPS_PROXY`, `ALL_PROXY`) are
+//!   ignored, so requests go directly to the vetted addresses
+//! - Redirects are followed manually, at most `max_redirects`, and every hop
+//!   is checked again
+//! - Only HTML, XHTML, plain text and markdown responses are accepted
+//! - Bodies over `max_bytes` are rejected while streaming
+//! - With `allowed_hosts`, only those hosts are fetched (every hop); they may
+//!   resolve to private addresses, so intranet artifact stores work
+//! - With `https_only`, plain http URLs are refused
+//!
+//! Prompt URLs are stricter: the host must be in `allowed_hosts`, the URL must
+//! be https (except on loopback hosts), and only plain text and markdown are
+//! accepted.
+//!
+//! ```toml
+//! user_prompt_url = "https://example.com/article"
+//! system_prompt_url = "https://artifacts.corp.example/prompts/support/v3.md"
+//!
+//! [fetch]
+//! max_bytes = 2097152               # Response body limit (default: 2 MiB)
+//! timeout_secs = 30                 # Per request (default: 30)
+//! max_redirects = 5                 # Default: 5
+//! allow_private_networks = false    # Allow intranet hosts (default: false)
+//! allowed_hosts = ["artifacts.corp.example", "*.example.com"]  # Default: any public host
+//! https_only = true                 # Default: false
+//! ```
+
+use crate::error::CliError;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use reqwest::{
+    header::{ACCEPT, CONTENT_TYPE, LOCATION},
+    redirect::Policy,
+    Client, Url,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr},
+    time::Duration,
+};
+
+const ACCEPTED_TYPES: &str = "text/html,application/xhtml+xml,text/markdown;q=0.9,text/plain;q=0.8";
+const ACCEPTED_PROMPT_TYPES: &str = "text/markdown,text/plain;q=0.9";
+
+fn default_max_bytes() -> usize {
+    2 * 1024 * 1024
+}
+
+fn default_timeout_secs() -> u64 {
+    30
+}
+
+fn default_max_redirects() -> usize {
+    5
+}
+
+/// URL fetch limits (`[fetch]`)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FetchConfig {
+    /// Maximum response body size in bytes (default: 2 MiB)
+    #[serde(default = "default_max_bytes")]
+    pub max_bytes: usize,
+
+    /// Timeout of each request in seconds (default: 30)
+    #[serde(default = "default_timeout_secs")]
+    pub timeout_secs: u64,
+
+    /// Redirects followed before giving up (default: 5)
+    #[serde(default = "default_max_redirects")]
+    pub max_redirects: usize,
+
+    /// Allow hosts on loopback, private and link-local networks (default: false)
+    #[serde(default)]
+    pub allow_private_networks: bool,
+
+    /// Only fetch these hosts: exact names, or `*.example.com` for subdomains
+    /// (default: any public host). Listed hosts may resolve to private addresses
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub allowed_hosts: Vec<String>,
+
+    /// Refuse plain http URLs and redirects (default: false)
+    #[serde(default)]
+    pub https_only: bool,
+}
+
+impl Default for FetchConfig {
+    fn default() -> Self {
+        Self {
+            max_bytes: default_max_bytes(),
+            timeout_secs: default_timeout_secs(),
+            max_redirects: default_max_redirects(),
+            allow_private_networks: false,
+            allowed_hosts: Vec::new(),
+            https_only: false,
+        }
+    }
+}
+
+impl FetchConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.max_bytes == 0 {
+            return Err("fetch.max_bytes must be >= 1".to_string());
+        }
+        if self.timeout_secs == 0 {
+            return Err("fetch.timeout_secs must be >= 1".to_string());
+        }
+        for host in &self.allowed_hosts {
+            let name = host.strip_prefix("*.").unwrap_or(host);
+            if name.is_empty() || name.contains(['/', '*']) {
+                return Err(format!(
+                    "fetch.allowed_hosts entry '{host}' must be a host name or *.domain"
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether `host` may be fetched (always true without `allowed_hosts`)
+    pub fn allows_host(&self, host: &str) -> bool {
+        let host = host.to_ascii_lowercase();
+        self.allowed_hosts.is_empty()
+            || self.allowed_hosts.iter().any(|entry| {
+                let entry = entry.to_ascii_lowercase();
+                match entry.strip_prefix("*.") {
+                    Some(domain) => host
+                        .strip_suffix(domain)
+                        .is_some_and(|sub| sub.ends_with('.') && sub.len() > 1),
+                    None => host == entry,
+                }
+            })
+    }
+}
+
+/// What a fetch is for: a page reduced to markdown, or a prompt taken as-is
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FetchKind {
+    Page,
+    Prompt,
+}
+
+/// Page fetched for `--url`
+#[derive(Debug, Clone)]
+pub struct FetchedPage {
+    /// URL the content came from, after redirects
+    pub url: String,
+    /// Document `<title>`, for HTML pages
+    pub title: Option<String>,
+    /// Media type of the response (e.g. `text/html`)
+    pub content_type: String,
+    /// Size of the response body in bytes
+    pub bytes: usize,
+    /// Main content as markdown (plain text and markdown are kept as-is)
+    pub markdown: String,
+}
+
+/// Check that `url` is an absolute http(s) URL
+pub fn parse_url(url: &str) -> Result<Url, String> {
+    let parsed = Url::parse(url).map_err(|e| format!("invalid URL '{url}': {e}"))?;
+    if !matches!(parsed.scheme(), "http" | "https") {
+        return Err(format!(
+            "unsupported URL scheme '{}' in '{url}' (expected http or https)",
+            parsed.scheme()
+        ));
+    }
+    if parsed.host_str().is_none() {
+        return Err(format!("URL '{url}' has no host"));
+    }
+    Ok(parsed)
+}
+
+/// Fetch `url` and extract its main content as markdown
+///
+/// # Errors
+///
+/// Returns `CliError::FetchFailed` for invalid URLs, non-public hosts (unless
+/// allowed), redirect loops, HTTP errors, unsupported content types, bodies
+/// over `max_bytes` and pages without readable content.
+pub async fn fetch_url(url: &str, config: &FetchConfig) -> Result<FetchedPage, CliError> {
+    fetch(url, config, FetchKind::Page).await
+}
+
+/// Fetch a prompt from a prompt library URL
+///
+/// The host must be in `allowed_hosts`, the URL https (plain http is only
+/// accepted on loopback hosts), and the response plain text or markdown. The
+/// body is returned trimmed but otherwise unchanged in `markdown`.
+///
+/// # Errors
+///
+/// Returns `CliError::FetchFailed` for the same reasons as [`fetch_url`], and
+/// for hosts missing from `allowed_hosts` and non-https URLs.
+pub async fn fetch_prompt(url: &str, config: &FetchConfig) -> Result<FetchedPage, CliError> {
+    fetch(url, config, FetchKind::Prompt).await
+}
+
+async fn fetch(url: &str, config: &FetchConfig, kind: FetchKind) -> Result<FetchedPage, CliError> {
+    let mut current = parse_url(url).map_err(CliError::FetchFailed)?;
+    let accept = match kind {
+        FetchKind::Page => ACCEPTED_TYPES,
+        FetchKind::Prompt => ACCEPTED_PROMPT_TYPES,
+    };
+    for _ in 0..=config.max_redirects {
+        check_url(&current, config, kind)?;
+        let client = pinned_client(&current, config).await?;
+        let mut response = client
+            .get(current.clone())
+            .header(ACCEPT, accept)
+            .send()
+            .await
+            .map_err(|e| CliError::FetchFailed(format!("request to {current} failed: {e}")))?;
+
+        let status = response.status();
+        if status.is_redirection() {
+            let location = response
+                .headers()
+                .get(LOCATION)
+                .and_then(|v| v.to_str().ok())
+                .ok_or_else(|| {
+                    CliError::FetchFailed(format!("{current} redirected without a Location"))
+                })?;
+            let next = current.join(location).map_err(|e| {
+                CliError::FetchFailed(format!("invalid redirect from {current}: {e}"))
+            })?;
+            current = parse_url(next.as_str()).map_err(CliError::FetchFailed)?;
+            log::debug!("Following redirect to {current}");
+            continue;
+        }
+        if !status.is_success() {
+            return Err(CliError::FetchFailed(format!(
+                "{current} returned HTTP {status}"
+            )));
+        }
+
+        let content_type = response
+            .headers()
+            .get(CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.split(';').next())
+            .map(|v| v.trim().to_ascii_lowercase())
+            .unwrap_or_default();
+        let html = match content_type.as_str() {
+            "text/html" | "application/xhtml+xml" if kind == FetchKind::Page => true,
+            "text/plain" | "text/markdown" => false,
+            other if kind == FetchKind::Prompt => {
+                return Err(CliError::FetchFailed(format!(
+                    "{current} has unsupported content type '{other}' for a prompt \
+                     (expected plain text or markdown)"
+                )))
+            }
+            other => {
+                return Err(CliError::FetchFailed(format!(
+                    "{current} has unsupported content type '{other}' \
+                     (expected HTML, plain text or markdown)"
+                )))
+            }
+        };
+        let too_large = |size: usize| {
+            CliError::FetchFailed(format!(
+                "{current} exceeds the {} byte limit (fetch.max_bytes) at {size} bytes",
+                config.max_bytes
+            ))
+        };
+        if let Some(length) = response.content_length() {
+            if length > config.max_bytes as u64 {
+                return Err(too_large(length as usize));
+            }
+        }
+
+        let mut body = Vec::new();
+        while let Some(chunk) = response
+            .chunk()
+            .await
+            .map_err(|e| CliError::FetchFailed(format!("reading {current} failed: {e}")))?
+        {
+            if body.len() + chunk.len() > config.max_bytes {
+                return Err(too_large(body.len() + chunk.len()));
+            }
+            body.extend_from_slice(&chunk);
+        }
+
+        let text = String::from_utf8_lossy(&body);
+        let (title, markdown) = if html {
+            html_to_markdown(&text)
+        } else {
+            (None, text.trim().to_string())
+        };
+        if markdown.is_empty() {
+            return Err(CliError::FetchFailed(format!(
+                "{current} has no readable content"
+            )));
+        }
+        log::debug!(
+            "Fetched {} bytes of {content_type} from {current} ({} characters of markdown)",
+            body.len(),
+            markdown.len()
+        );
+        return Ok(FetchedPage {
+            url: current.to_string(),
+            title,
+            content_type,
+            bytes: body.len(),
+            markdown,
+        });
+    }
+    Err(CliError::FetchFailed(format!(
+        "{url} redirected more than {} times (fetch.max_redirects)",
+        config.max_redirects
+    )))
+}
+
+/// Scheme and host policy of one request (checked again on every redirect)
+fn check_url(url: &Url, config: &FetchConfig, kind: FetchKind) -> Result<(), CliError> {
+    let host = url.host_str().unwrap_or_default();
+    if kind == FetchKind::Prompt && config.allowed_hosts.is_empty() {
+        return Err(CliError::FetchFailed(format!(
+            "prompt URL {url} requires fetch.allowed_hosts listing its host"
+        )));
+    }
+    if !config.allows_host(host) {
+        return Err(CliError::FetchFailed(format!(
+            "host '{host}' of {url} is not in fetch.allowed_hosts"
+        )));
+    }
+    let https_required =
+        config.https_only || (kind == FetchKind::Prompt && !is_loopback_host(host));
+    if https_required && url.scheme() != "https" {
+        let reason = if config.https_only {
+            "fetch.https_only is set"
+        } else {
+            "prompts are only fetched over https"
+        };
+        return Err(CliError::FetchFailed(format!(
+            "{url} is not an https URL ({reason})"
+        )));
+    }
+    Ok(())
+}
+
+/// `localhost` or a loopback address literal
+fn is_loopback_host(host: &str) -> bool {
+    host.eq_ignore_ascii_case("localhost")
+        || host
+            .trim_start_matches('[')
+            .trim_end_matches(']')
+            .parse::<IpAddr>()
+            .is_ok_and(|ip| ip.is_loopback())
+}
+
+/// Client for one request to `url`, with its host's addresses vetted and pinned
+async fn pinned_client(url: &Url, config: &FetchConfig) -> Result<Client, CliError> {
+    let host = url.host_str().unwrap_or_default();
+    let port = url.port_or_known_default().unwrap_or(80);
+    let literal = host.trim_start_matches('[').trim_end_matches(']');
+    let addrs: Vec<SocketAddr> = match literal.parse::<IpAddr>() {
+        Ok(ip) => vec![SocketAddr::new(ip, port)],
+        Err(_) => tokio::net::lookup_host((host, port))
+            .await
+            .map_err(|e| CliError::FetchFailed(format!("cannot resolve '{host}': {e}")))?
+            .collect(),
+    };
+    // Hosts in allowed_hosts were vetted by check_url
+    if !config.allow_private_networks && config.allowed_hosts.is_empty() {
+        if let Some(addr) = addrs.iter().find(|addr| !is_public(addr.ip())) {
+            return Err(CliError::FetchFailed(format!(
+                "'{host}' resolves to non-public address {} \
+                 (set fetch.allow_private_networks or fetch.allowed_hosts to allow it)",
+                addr.ip()
+            )));
+        }
+    }
+
+    // A proxy would resolve the host itself, bypassing the checks above
+    let mut builder = Client::builder()
+        .no_proxy()
+        .redirect(Policy::none())
+        .timeout(Duration::from_secs(config.timeout_secs));
+    if literal.parse::<IpAddr>().is_err() {
+        builder = builder.resolve_to_addrs(host, &addrs);
+    }
+    Ok(builder.build()?)
+}
+
+/// Whether `ip` is a globally routable address
+pub fn is_public(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(v4) => {
+            let [a, b, ..] = v4.octets();
+            !(v4.is_private()
+                || v4.is_loopback()
+                || v4.is_link_local()
+                || v4.is_unspecified()
+                || v4.is_broadcast()
+                || v4.is_multicast()
+                || v4.is_documentation()
+                || a == 0
+                || a >= 240
+                // Shared address space (CGNAT, 100.64.0.0/10)
+                || (a == 100 && (b & 0xc0) == 64)
+                // Benchmarking (198.18.0.0/15)
+                || (a == 198 && (b & 0xfe) == 18))
+        }
+        IpAddr::V6(v6) => {
+            if let Some(v4) = v6.to_ipv4_mapped().or_else(|| embedded_ipv4(v6)) {
+                return is_public(IpAddr::V4(v4));
+            }
+            let [first, second, ..] = v6.segments();
+            !(v6.is_loopback()
+                || v6.is_unspecified()
+                || v6.is_multicast()
+                || v6.is_unique_local()
+                || v6.is_unicast_link_local()
+                // Documentation (2001:db8::/32)
+                || (first == 0x2001 && second == 0x0db8))
+        }
+    }
+}
+
+/// IPv4 address embedded by NAT64 (`64:ff9b::/96`, `64:ff9b:1::/48`) or
+/// 6to4 (`2002::/16`), which translators route to the IPv4 network
+fn embedded_ipv4(v6: Ipv6Addr) -> Option<Ipv4Addr> {
+    let o = v6.octets();
+    match v6.segments() {
+        // Well-known and local-use (RFC 8215) prefixes: the last 32 bits
+        [0x0064, 0xff9b, 0, 0, 0, 0, ..] | [0x0064, 0xff9b, 0x0001, ..] => {
+            Some(Ipv4Addr::new(o[12], o[13], o[14], o[15]))
+        }
+        // 6to4: the 32 bits after the prefix
+        [0x2002, ..] => Some(Ipv4Addr::new(o[2], o[3], o[4], o[5])),
+        _ => None,
+    }
+}
+
+/// Elements removed before extraction (scripts and page chrome)
+const BOILERPLATE_TAGS: &[&str] = &[
+    "script", "style", "noscript", "template", "svg", "iframe", "nav", "header", "footer", "aside",
+    "form", "button",
+];
+
+static COMMENT: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?s)<!--.*?-->").unwrap());
+static TITLE: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?is)<title[^>]*>(.*?)</title\s*>").unwrap());
+static BOILERPLATE: Lazy<Vec<Regex>> = Lazy::new(|| {
+    BOILERPLATE_TAGS
+        .iter()
+        .map(|tag| Regex::new(&format!(r"(?is)<{tag}\b[^>]*>.*?</{tag}\s*>")).unwrap())
+        .collect()
+});
+static CONTAINERS: Lazy<Vec<Regex>> = Lazy::new(|| {
+    ["article", "main", "body"]
+        .iter()
+        .map(|tag| Regex::new(&format!(r"(?is)<{tag}\b[^>]*>(.*)</{tag}\s*>")).unwrap())
+        .collect()
+});
+static PRE: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?is)<pre\b[^>]*>(.*?)</pre\s*>").unwrap());
+static WHITESPACE: Lazy<Regex> = Lazy::new(|| Regex::new(r"\s+").unwrap());
+static HEADINGS: Lazy<Vec<Regex>> = Lazy::new(|| {
+    (1..=6)
+        .map(|level| Regex::new(&format!(r"(?is)<h{level}\b[^>]*>(.*?)</h{level}\s*>")).unwrap())
+        .collect()
+});
+static LINK: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"(?is)<a\b[^>]*?\bhref\s*=\s*["']([^"']*)["'][^>]*>(.*?)</a\s*>"#).unwrap()
+});
+static STRONG: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(?is)<(?:strong|b)\b[^>]*>(.*?)</(?:strong|b)\s*>").unwrap());
+static EMPHASIS: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(?is)<(?:em|i)\b[^>]*>(.*?)</(?:em|i)\s*>").unwrap());
+static CODE: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?is)<code\b[^>]*>(.*?)</code\s*>").unwrap());
+static LIST_ITEM: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?i)<li\b[^>]*>").unwrap());
+static LINE_BREAK: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?i)<br\s*/?>").unwrap());
+static BLOCK: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"(?i)</?(?:p|div|section|ul|ol|dl|dt|dd|table|tr|blockquote|figure|figcaption|hr)\b[^>]*>",
+    )
+    .unwrap()
+});
+static TAG: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?s)<[^>]*>").unwrap());
+static ENTITY: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"&(#[0-9]+|#[xX][0-9a-fA-F]+|[a-zA-Z]+);").unwrap());
+static BLANK_LINES: Lazy<Regex> = Lazy::new(|| Regex::new(r"\n{3,}").unwrap());
+
+/// Extract the title and main content of an HTML page as markdown
+pub fn html_to_markdown(html: &str) -> (Option<String>, String) {
+    let title = TITLE
+        .captures(html)
+        .map(|c| decode_entities(WHITESPACE.replace_all(&c[1], " ").trim()))
+        .filter(|t| !t.is_empty());
+
+    let mut html = COMMENT.replace_all(html, "").into_owned();
+    for pattern in BOILERPLATE.iter() {
+        html = pattern.replace_all(&html, "").into_owned();
+    }
+    let content = CONTAINERS
+        .iter()
+        .find_map(|pattern| pattern.captures(&html).map(|c| c[1].to_string()))
+        .unwrap_or(html);
+
+    // Preformatted blocks keep their whitespace; they are set aside as
+    // placeholders while the rest is reflowed
+    let mut blocks = Vec::new();
+    let content = PRE.replace_all(&content, |c: &regex::Captures| {
+        blocks.push(decode_entities(
+            TAG.replace_all(&c[1], "").trim_matches('\n'),
+        ));
+        format!("<p>\u{0}{}\u{0}</p>", blocks.len() - 1)
+    });
+    let mut text = WHITESPACE.replace_all(&content, " ").into_owned();
+
+    for (level, pattern) in HEADINGS.iter().enumerate() {
+        let hashes = "#".repeat(level + 1);
+        text = pattern
+            .replace_all(&text, |c: &regex::Captures| {
+                format!("\n\n{hashes} {}\n\n", c[1].trim())
+            })
+            .into_owned();
+    }
+    text = LINK
+        .replace_all(&text, |c: &regex::Captures| {
+            let (href, label) = (&c[1], c[2].trim());
+            if href.is_empty() || href.starts_with('#') || href.starts_with("javascript:") {
+                label.to_string()
+            } else {
+                format!("[{label}]({href})")
+            }
+        })
+        .into_owned();
+    text = STRONG.replace_all(&text, "**$1**").into_owned();
+    text = EMPHASIS.replace_all(&text, "*$1*").into_owned();
+    text = CODE.replace_all(&text, "`$1`").into_owned();
+    text = LIST_ITEM.replace_all(&text, "\n- ").into_owned();
+    text = LINE_BREAK.replace_all(&text, "\n").into_owned();
+    text = BLOCK.replace_all(&text, "\n\n").into_owned();
+    text = TAG.replace_all(&text, "").into_owned();
+    let text = decode_entities(&text);
+
+    let lines: Vec<&str> = text.lines().map(str::trim).collect();
+    let mut markdown = BLANK_LINES
+        .replace_all(&lines.join("\n"), "\n\n")
+        .trim()
+        .to_string();
+    for (index, block) in blocks.iter().enumerate() {
+        markdown = markdown.replace(&format!("\u{0}{index}\u{0}"), &format!("```\n{block}\n```"));
+    }
+    (title, markdown)
+}
+
+/// Decode named (common) and numeric character references
+fn decode_entities(text: &str) -> String {
+    ENTITY
+        .replace_all(text, |c: &regex::Captures| {
+            let entity = &c[1];
+            let decoded = match entity {
+                "amp" => Some('&'),
+                "lt" => Some('<'),
+                "gt" => Some('>'),
+                "quot" => Some('"'),
+                "apos" => Some('\''),
+                "nbsp" => Some(' '),
+                "ndash" => Some('\u{2013}'),
+                "mdash" => Some('\u{2014}'),
+                "hellip" => Some('\u{2026}'),
+                "copy" => Some('\u{a9}'),
+                _ if entity.starts_with("#x") || entity.starts_with("#X") => {
+                    u32::from_str_radix(&entity[2..], 16)
+                        .ok()
+                        .and_then(char::from_u32)
+                }
+                _ if entity.starts_with('#') => entity[1..].parse().ok().and_then(char::from_u32),
+                _ => None,
+            };
+            decoded.map_or_else(|| c[0].to_string(), String::from)
+        })
+        .into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_html_to_markdown_keeps_main_content() {
+        let html = r#"<!DOCTYPE html>
+<html><head><title>Quarterly &amp; Annual Report</title>
+<style>body { color: red; }</style><script>track("view");</script></head>
+<body>
+  <header><nav><a href="/">Home</a> <a href="/about">About</a></nav></header>
+  <article>
+    <h1>Results</h1>
+    <p>Revenue grew by <strong>4%</strong>, see
+       <a href="https://example.com/q3">the Q3 summary</a>.</p>
+    <!-- editor note -->
+    <ul><li>North: up</li><li>South: <em>flat</em></li></ul>
+    <pre>let x = 1;
+  x + 1</pre>
+    <p>Costs &lt; budget&#33;</p>
+  </article>
+  <aside>Related posts</aside>
+  <footer>&copy; Example Corp</footer>
+</body></html>"#;
+
+        let (title, markdown) = html_to_markdown(html);
+        assert_eq!(title.as_deref(), Some("Quarterly & Annual Report"));
+        assert_eq!(
+            markdown,
+            "# Results\n\n\
+             Revenue grew by **4%**, see [the Q3 summary](https://example.com/q3).\n\n\
+             - North: up\n\
+             - South: *flat*\n\n\
+             ```\nlet x = 1;\n  x + 1\n```\n\n\
+             Costs < budget!"
+        );
+    }
+
+    #[test]
+    fn test_html_to_markdown_falls_back_to_body() {
+        let (title, markdown) =
+            html_to_markdown("<body><nav>Menu</nav><div>Hello<br>world</div></body>");
+        assert_eq!(title, None);
+        assert_eq!(markdown, "Hello\nworld");
+    }
+
+    #[test]
+    fn test_public_addresses() {
+        for ip in [
+            "93.184.216.34",
+            "2606:4700::1111",
+            "64:ff9b::5db8:d822",
+            "2002:5db8:d822::1",
+        ] {
+            assert!(is_public(ip.parse().unwrap()), "{ip}");
+        }
+        for ip in [
+            "127.0.0.1",
+            "10.1.2.3",
+            "172.16.0.1",
+            "192.168.1.1",
+            "169.254.169.254",
+            "100.64.0.1",
+            "0.0.0.0",
+            "::1",
+            "fd00::1",
+            "fe80::1",
+            "::ffff:10.0.0.1",
+            // NAT64 and 6to4 addresses embedding private IPv4 addresses
+            "64:ff9b::a9fe:a9fe",
+            "64:ff9b::7f00:1",
+            "64:ff9b:1::a00:1",
+            "2002:a9fe:a9fe::1",
+            "2002:c0a8:101::",
+        ] {
+            assert!(!is_public(ip.parse().unwrap()), "{ip}");
+        }
+    }
+
+    #[test]
+    fn test_allowed_hosts_matching() {
+        let config = FetchConfig {
+            allowed_hosts: vec!["artifacts.corp.example".into(), "*.Example.com".into()],
+            ..FetchConfig::default()
+        };
+        assert!(config.allows_host("ARTIFACTS.corp.example"));
+        assert!(config.allows_host("prompts.example.com"));
+        assert!(!config.allows_host("example.com"));
+        assert!(!config.allows_host("evilexample.com"));
+        assert!(!config.allows_host("artifacts.corp.example.evil"));
+        assert!(FetchConfig::default().allows_host("anything.test"));
+
+        let invalid = FetchConfig {
+            allowed_hosts: vec!["https://artifacts.corp.example/".into()],
+            ..FetchConfig::default()
+        };
+        assert!(invalid.validate().is_err());
+    }
+
+    #[test]
+    fn test_prompt_urls_need_allowlist_and_https() {
+        let url = |u: &str| Url::parse(u).unwrap();
+        let open = FetchConfig::default();
+        let err = check_url(&url("https://a.example/p.md"), &open, FetchKind::Prompt).unwrap_err();
+        assert!(err.to_string().contains("fetch.allowed_hosts"), "{err}");
+
+        let config = FetchConfig {
+            allowed_hosts: vec!["a.example".into(), "localhost".into()],
+            ..FetchConfig::default()
+        };
+        assert!(check_url(&url("https://a.example/p.md"), &config, FetchKind::Prompt).is_ok());
+        assert!(check_url(&url("http://a.example/p.md"), &config, FetchKind::Prompt).is_err());
+        assert!(check_url(&url("http://a.example/p.md"), &config, FetchKind::Page).is_ok());
+        assert!(check_url(
+            &url("http://localhost:8080/p.md"),
+            &config,
+            FetchKind::Prompt
+        )
+        .is_ok());
+        assert!(check_url(&url("https://b.example/p.md"), &config, FetchKind::Page).is_err());
+    }
+
+    #[test]
+    fn test_parse_url_rejects_other_schemes() {
+        assert!(parse_url("https://example.com/a").is_ok());
+        let err = parse_url("file:///etc/passwd").unwrap_err();
+        assert!(err.contains("unsupported URL scheme 'file'"), "{err}");
+        assert!(parse_url("not a url").is_err());
+    }
+}
@@ -0,0 +1,84 @@
+//! Standalone guardrail checks (`guardrails test`)
+//!
+//! Runs one configured guardrail against a piece of text without calling the
+//! LLM, and reports the full [`GuardrailResult`] with its latency. Input
+//! checks redact first and validate the redacted text, like the `input_guard`
+//! stage; output checks pass an [`OutputContext`] with the prompt the text is
+//! supposed to answer, when given.
+//!
+//! Useful for tuning patterns, thresholds and composite votes on sample inputs.
+
+use crate::{
+    guardrails::{
+        create_guardrail_provider,
+        provider::{GuardrailResult, OutputContext, Redaction},
+        GuardrailProviderConfig, GuardrailStage,
+    },
+    CliError,
+};
+use serde::Serialize;
+use std::time::Instant;
+
+/// Result of a standalone guardrail check
+#[derive(Debug, Clone, Serialize)]
+pub struct GuardrailCheckReport {
+    pub direction: GuardrailStage,
+    /// Provider name (e.g. `regex`, `composite`)
+    pub provider: String,
+    /// Size of the checked text, before redaction
+    pub content_bytes: usize,
+    /// Matches replaced before validation (input only)
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub redactions: Vec<Redaction>,
+    /// Text that was validated, when redaction changed it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub redacted_content: Option<String>,
+    pub latency_ms: u64,
+    pub result: GuardrailResult,
+}
+
+/// Run the guardrail configured by `config` against `content`
+///
+/// `context` is only used for output checks.
+///
+/// # Errors
+///
+/// Returns the guardrail's construction error (missing pattern file, API key
+/// variable, ...) or its validation error (unreachable endpoint, ...).
+pub async fn check_guardrail(
+    direction: GuardrailStage,
+    config: &GuardrailProviderConfig,
+    content: &str,
+    context: Option<OutputContext>,
+) -> Result<GuardrailCheckReport, CliError> {
+    let guardrail = create_guardrail_provider(config)?;
+
+    let (redacted_content, redactions) = match direction {
+        GuardrailStage::Input => match guardrail.redact(content) {
+            Some((text, redactions)) => (Some(text), redactions),
+            None => (None, Vec::new()),
+        },
+        GuardrailStage::Output => (None, Vec::new()),
+    };
+    let text = redacted_content.as_deref().unwrap_or(content);
+
+    let start = Instant::now();
+    let result = match direction {
+        GuardrailStage::Input => guardrail.validate(text).await?,
+        GuardrailStage::Output => {
+            guardrail
+                .validate_output(text, &context.unwrap_or_default())
+                .await?
+        }
+    };
+
+    Ok(GuardrailCheckReport {
+        direction,
+        provider: guardrail.name().to_string(),
+        content_bytes: content.len(),
+        redactions,
+        redacted_content,
+        latency_ms: start.elapsed().as_millis() as u64,
+        result,
+    })
+}
@@ -0,0 +1,537 @@
+//! Token accounting and budgets for model-backed guardrails
+//!
+//! Model-backed guardrails (Llama Guard, Prompt Guard, GPT-OSS-Safeguard,
+//! NeMo Guardrails and semantic similarity embeddings) can consume more tokens
+//! than the main call, especially in composites. Every call they make is
+//! estimated and recorded per provider and direction in
+//! `metadata.guard_tokens`. A `[guard_budget]` section caps them:
+//!
+//! ```toml
+//! [guard_budget]
+//! max_tokens_per_evaluation = 4000   # Guard tokens one evaluation may use
+//! max_tokens_per_batch = 500000      # Across the evaluations sharing the budget
+//! on_exhausted = "block"             # or "skip" (default: block)
+//!
+//! [[guard_budget.endpoints]]
+//! api_url = "http://guard:11434/api/generate"
+//! tokens_per_minute = 60000          # Token bucket refill rate
+//! burst_tokens = 8000                # Bucket capacity (default: tokens_per_minute)
+//! ```
+//!
+//! A call that would exceed a budget is not made: with `block` the guardrail
+//! fails with `guard_budget.BUDGET_EXHAUSTED`, with `skip` it passes with that
+//! rule as a warning.
+
+use super::{
+    catalog::namespace,
+    config::GuardrailProviderConfig,
+    provider::{GuardrailProvider, GuardrailResult, OutputContext, Redaction, Severity, Violation},
+};
+use crate::{chunking::chars_per_token, error::CliError};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::Instant,
+};
+
+pub mod rules {
+    pub const BUDGET_EXHAUSTED: &str = "BUDGET_EXHAUSTED";
+}
+
+/// Severity of `guard_budget.BUDGET_EXHAUSTED` when blocking
+pub const RULE_SEVERITY: Severity = Severity::High;
+
+/// What happens to a guardrail call that would exceed the budget
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BudgetAction {
+    /// Fail the guardrail (fail closed)
+    #[default]
+    Block,
+    /// Pass without running the guardrail, with a warning
+    Skip,
+}
+
+/// Token bucket for one guardrail endpoint
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EndpointBudgetConfig {
+    /// Guardrail `api_url` the bucket applies to
+    pub api_url: String,
+    /// Refill rate
+    pub tokens_per_minute: usize,
+    /// Bucket capacity (default: `tokens_per_minute`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub burst_tokens: Option<usize>,
+}
+
+/// Guardrail token budgets (`[guard_budget]`)
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct GuardBudgetConfig {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_tokens_per_evaluation: Option<usize>,
+
+    /// Across all evaluations sharing the budget: a batch run, or the whole
+    /// process for `serve`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_tokens_per_batch: Option<usize>,
+
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub endpoints: Vec<EndpointBudgetConfig>,
+
+    #[serde(default)]
+    pub on_exhausted: BudgetAction,
+}
+
+impl GuardBudgetConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.max_tokens_per_evaluation == Some(0) || self.max_tokens_per_batch == Some(0) {
+            return Err("guard_budget limits must be >= 1".to_string());
+        }
+        let mut seen = std::collections::HashSet::new();
+        for endpoint in &self.endpoints {
+            if endpoint.tokens_per_minute == 0 || endpoint.burst_tokens == Some(0) {
+                return Err(format!(
+                    "guard_budget.endpoints: tokens_per_minute and burst_tokens of '{}' must be >= 1",
+                    endpoint.api_url
+                ));
+            }
+            if !seen.insert(endpoint.api_url.as_str()) {
+                return Err(format!(
+                    "guard_budget.endpoints: duplicate api_url '{}'",
+                    endpoint.api_url
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+struct TokenBucket {
+    capacity: f64,
+    per_second: f64,
+    tokens: f64,
+    updated: Instant,
+}
+
+impl TokenBucket {
+    fn new(config: &EndpointBudgetConfig) -> Self {
+        let capacity = config.burst_tokens.unwrap_or(config.tokens_per_minute) as f64;
+        Self {
+            capacity,
+            per_second: config.tokens_per_minute as f64 / 60.0,
+            tokens: capacity,
+            updated: Instant::now(),
+        }
+    }
+
+    fn refill(&mut self) {
+        let now = Instant::now();
+        let elapsed = now.duration_since(self.updated).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.per_second).min(self.capacity);
+        self.updated = now;
+    }
+}
+
+#[derive(Default)]
+struct BudgetState {
+    used: usize,
+    buckets: HashMap<String, TokenBucket>,
+}
+
+/// Runtime guardrail token budget shared across evaluations
+pub struct GuardBudget {
+    config: GuardBudgetConfig,
+    state: Mutex<BudgetState>,
+}
+
+impl std::fmt::Debug for GuardBudget {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("GuardBudget")
+            .field("config", &self.config)
+            .field("tokens_used", &self.tokens_used())
+            .finish()
+    }
+}
+
+impl GuardBudget {
+    pub fn from_config(config: &GuardBudgetConfig) -> Result<Self, CliError> {
+        config.validate().map_err(CliError::InvalidArguments)?;
+        let buckets = config
+            .endpoints
+            .iter()
+            .map(|endpoint| (endpoint.api_url.clone(), TokenBucket::new(endpoint)))
+            .collect();
+        Ok(Self {
+            config: config.clone(),
+            state: Mutex::new(BudgetState { used: 0, buckets }),
+        })
+    }
+
+    pub fn config(&self) -> &GuardBudgetConfig {
+        &self.config
+    }
+
+    /// Estimated guard tokens charged across all evaluations
+    pub fn tokens_used(&self) -> usize {
+        self.state.lock().unwrap_or_else(|e| e.into_inner()).used
+    }
+
+    /// Charge `tokens` for a call to `endpoint`, or explain which budget
+    /// would be exceeded
+    fn try_charge(
+        &self,
+        endpoint: &str,
+        tokens: usize,
+        evaluation_used: usize,
+    ) -> Result<(), String> {
+        if let Some(max) = self.config.max_tokens_per_evaluation {
+            if evaluation_used + tokens > max {
+                return Err(format!(
+                    "per-evaluation guard budget of {max} tokens exhausted \
+                    ({evaluation_used} used, call needs {tokens})"
+                ));
+            }
+        }
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(max) = self.config.max_tokens_per_batch {
+            if state.used + tokens > max {
+                return Err(format!(
+                    "batch guard budget of {max} tokens exhausted ({} used, call needs {tokens})",
+                    state.used
+                ));
+            }
+        }
+        if let Some(bucket) = state.buckets.get_mut(endpoint) {
+            bucket.refill();
+            if bucket.tokens < tokens as f64 {
+                return Err(format!(
+                    "token bucket of {endpoint} has {:.0} tokens, call needs {tokens}",
+                    bucket.tokens
+                ));
+            }
+            bucket.tokens -= tokens as f64;
+        }
+        state.used += tokens;
+        Ok(())
+    }
+}
+
+/// Estimated guard tokens of one provider in one direction
+/// (`metadata.guard_tokens`)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GuardTokenUsage {
+    /// Provider type (e.g. `llama_guard`)
+    pub provider: String,
+    /// `input` or `output`
+    pub direction: String,
+    pub endpoint: String,
+    pub calls: usize,
+    /// Prompt and response buffer of the calls made
+    pub tokens_estimated: usize,
+    /// Calls not made because a budget was exhausted
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub denied: usize,
+}
+
+fn is_zero(n: &usize) -> bool {
+    *n == 0
+}
+
+/// Per-evaluation guard token meter
+///
+/// Passed to [`super::create_metered_guardrail_provider`], which wraps the
+/// model-backed providers so their calls are recorded and checked against the
+/// budget. Clones share the recorded usage.
+#[derive(Debug, Clone, Default)]
+pub struct GuardMeter {
+    budget: Option<Arc<GuardBudget>>,
+    direction: String,
+    usage: Arc<Mutex<Vec<GuardTokenUsage>>>,
+}
+
+impl GuardMeter {
+    pub fn new(budget: Option<Arc<GuardBudget>>) -> Self {
+        Self {
+            budget,
+            direction: "input".to_string(),
+            usage: Arc::default(),
+        }
+    }
+
+    /// Meter recording calls under `direction`, sharing this meter's usage
+    pub fn for_direction(&self, direction: &str) -> Self {
+        Self {
+            direction: direction.to_string(),
+            ..self.clone()
+        }
+    }
+
+    /// Usage recorded so far, per provider and direction
+    pub fn usage(&self) -> Vec<GuardTokenUsage> {
+        self.usage.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// Estimated guard tokens of the calls made so far
+    pub fn tokens_used(&self) -> usize {
+        self.usage().iter().map(|u| u.tokens_estimated).sum()
+    }
+
+    /// Wrap `provider` if `config` is model-backed
+    pub(crate) fn wrap(
+        &self,
+        config: &GuardrailProviderConfig,
+        provider: Box<dyn GuardrailProvider>,
+    ) -> Box<dyn GuardrailProvider> {
+        match MeteredCalls::of(config) {
+            Some(calls) => Box::new(MeteredGuardrail {
+                inner: provider,
+                provider_type: config.provider_type().to_string(),
+                calls,
+                meter: self.clone(),
+            }),
+            None => provider,
+        }
+    }
+
+    /// Record a call of `tokens`, or return why the budget refuses it
+    fn charge(&self, provider: &str, endpoint: &str, tokens: usize) -> Result<(), String> {
+        let mut usage = self.usage.lock().unwrap_or_else(|e| e.into_inner());
+        let charged = match &self.budget {
+            Some(budget) => {
+                let used = usage.iter().map(|u| u.tokens_estimated).sum();
+                budget.try_charge(endpoint, tokens, used)
+            }
+            None => Ok(()),
+        };
+        let index = usage
+            .iter()
+            .position(|u| {
+                u.provider == provider && u.direction == self.direction && u.endpoint == endpoint
+            })
+            .unwrap_or_else(|| {
+                usage.push(GuardTokenUsage {
+                    provider: provider.to_string(),
+                    direction: self.direction.clone(),
+                    endpoint: endpoint.to_string(),
+                    calls: 0,
+                    tokens_estimated: 0,
+                    denied: 0,
+                });
+                usage.len() - 1
+            });
+        let entry = &mut usage[index];
+        match charged {
+            Ok(()) => {
+                entry.calls += 1;
+                entry.tokens_estimated += tokens;
+            }
+            Err(_) => entry.denied += 1,
+        }
+        charged
+    }
+
+    fn action(&self) -> BudgetAction {
+        self.budget
+            .as_ref()
+            .map(|budget| budget.config.on_exhausted)
+            .unwrap_or_default()
+    }
+}
+
+/// Token cost model of a model-backed guardrail's calls
+struct MeteredCalls {
+    endpoint: String,
+    chars_per_token: f64,
+    /// Fixed prompt text sent with every call (e.g. a policy)
+    prompt_tokens: usize,
+    /// Response buffer requested per call
+    response_tokens: usize,
+}
+
+impl MeteredCalls {
+    fn of(config: &GuardrailProviderConfig) -> Option<Self> {
+        let (api_url, model, prompt_tokens, response_tokens) = match config {
+            GuardrailProviderConfig::LlamaGuard { api_url, model, .. } => (api_url, model, 0, 100),
+            GuardrailProviderConfig::GptOssSafeguard {
+                api_url,
+                model,
+                policy,
+                ..
+            } => {
+                let tokens = (policy.len() as f64 / chars_per_token(model)).ceil() as usize;
+                (api_url, model, tokens, 300)
+            }
+            GuardrailProviderConfig::LlamaPromptGuard { api_url, model, .. } => {
+                (api_url, model, 0, 50)
+            }
+            GuardrailProviderConfig::SemanticSimilarity { api_url, model, .. } => {
+                (api_url, model, 0, 0)
+            }
+            GuardrailProviderConfig::NemoGuardrails { api_url, .. } => {
+                return Some(Self {
+                    endpoint: api_url.clone(),
+                    chars_per_token: chars_per_token(""),
+                    prompt_tokens: 0,
+                    response_tokens: 0,
+                })
+            }
+            _ => return None,
+        };
+        Some(Self {
+            endpoint: api_url.clone(),
+            chars_per_token: chars_per_token(model),
+            prompt_tokens,
+            response_tokens,
+        })
+    }
+
+    fn tokens(&self, texts: &[&str]) -> usize {
+        let chars: usize = texts.iter().map(|text| text.len()).sum();
+        (chars as f64 / self.chars_per_token).ceil() as usize
+            + self.prompt_tokens
+            + self.response_tokens
+    }
+}
+
+/// Model-backed guardrail whose calls are metered
+struct MeteredGuardrail {
+    inner: Box<dyn GuardrailProvider>,
+    provider_type: String,
+    calls: MeteredCalls,
+    meter: GuardMeter,
+}
+
+impl MeteredGuardrail {
+    fn exhausted(&self, reason: String) -> GuardrailResult {
+        log::warn!("{} not run: {reason}", self.provider_type);
+        let finding = Violation {
+            namespace: namespace::GUARD_BUDGET.to_string(),
+            rule: rules::BUDGET_EXHAUSTED.to_string(),
+            severity: RULE_SEVERITY,
+            message: format!("{} not run: {reason}", self.provider_type),
+            location: None,
+            source: None,
+            preview: None,
+        };
+        match self.meter.action() {
+            BudgetAction::Block => {
+                GuardrailResult::without_quality_score(false, vec![finding], vec![])
+            }
+            BudgetAction::Skip => {
+                GuardrailResult::without_quality_score(true, vec![], vec![finding])
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl GuardrailProvider for MeteredGuardrail {
+    async fn validate(&self, content: &str) -> Result<GuardrailResult, CliError> {
+        let tokens = self.calls.tokens(&[content]);
+        match self
+            .meter
+            .charge(&self.provider_type, &self.calls.endpoint, tokens)
+        {
+            Ok(()) => self.inner.validate(content).await,
+            Err(reason) => Ok(self.exhausted(reason)),
+        }
+    }
+
+    async fn validate_output(
+        &self,
+        response: &str,
+        context: &OutputContext,
+    ) -> Result<GuardrailResult, CliError> {
+        let user_prompt = context.user_prompt.as_deref().unwrap_or_default();
+        let tokens = self.calls.tokens(&[response, user_prompt]);
+        match self
+            .meter
+            .charge(&self.provider_type, &self.calls.endpoint, tokens)
+        {
+            Ok(()) => self.inner.validate_output(response, context).await,
+            Err(reason) => Ok(self.exhausted(reason)),
+        }
+    }
+
+    fn redact(&self, content: &str) -> Option<(String, Vec<Redaction>)> {
+        self.inner.redact(content)
+    }
+
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn budget(config: GuardBudgetConfig) -> Arc<GuardBudget> {
+        Arc::new(GuardBudget::from_config(&config).unwrap())
+    }
+
+    #[test]
+    fn test_meter_records_and_enforces_limits() {
+        let shared = budget(GuardBudgetConfig {
+            max_tokens_per_evaluation: Some(300),
+            max_tokens_per_batch: Some(450),
+            ..Default::default()
+        });
+        let meter = GuardMeter::new(Some(Arc::clone(&shared)));
+        meter.charge("llama_guard", "http://guard", 200).unwrap();
+        let err = meter
+            .for_direction("output")
+            .charge("llama_guard", "http://guard", 200)
+            .unwrap_err();
+        assert!(err.contains("per-evaluation"), "{err}");
+
+        // A second evaluation hits the batch limit
+        let meter2 = GuardMeter::new(Some(Arc::clone(&shared)));
+        meter2.charge("llama_guard", "http://guard", 200).unwrap();
+        assert!(meter2
+            .charge("llama_guard", "http://guard", 100)
+            .unwrap_err()
+            .contains("batch"));
+        assert_eq!(shared.tokens_used(), 400);
+
+        let usage = meter.usage();
+        assert_eq!(usage.len(), 2);
+        assert_eq!((usage[0].calls, usage[0].tokens_estimated), (1, 200));
+        assert_eq!(
+            (usage[1].direction.as_str(), usage[1].denied),
+            ("output", 1)
+        );
+    }
+
+    #[test]
+    fn test_endpoint_bucket_limits_burst() {
+        let shared = budget(GuardBudgetConfig {
+            endpoints: vec![EndpointBudgetConfig {
+                api_url: "http://guard".to_string(),
+                tokens_per_minute: 60,
+                burst_tokens: Some(150),
+            }],
+            ..Default::default()
+        });
+        let meter = GuardMeter::new(Some(shared));
+        meter.charge("llama_guard", "http://guard", 100).unwrap();
+        assert!(meter
+            .charge("llama_guard", "http://guard", 100)
+            .unwrap_err()
+            .contains("token bucket"));
+        // Other endpoints are not limited
+        meter.charge("llama_guard", "http://other", 1000).unwrap();
+    }
+
+    #[test]
+    fn test_config_validation() {
+        let config: GuardBudgetConfig = toml::from_str(
+            "on_exhausted = \"skip\"\n[[endpoints]]\napi_url = \"http://guard\"\ntokens_per_minute = 0",
+        )
+        .unwrap();
+        assert_eq!(config.on_exhausted, BudgetAction::Skip);
+        assert!(config.validate().unwrap_err().contains("tokens_per_minute"));
+    }
+}
@@ -0,0 +1,989 @@
+//! Fluent builders for guardrail configurations
+//!
+//! Library users can assemble [`GuardrailProviderConfig`] values in code
+//! instead of writing the serde enum literals by hand. `build()` fills in the
+//! same defaults as config files and runs [`GuardrailProviderConfig::validate`].
+//!
+//! ```
+//! use fortified_llm_client::guardrails::GuardrailConfigBuilder;
+//!
+//! let config = GuardrailConfigBuilder::composite()
+//!     .sequential()
+//!     .with(GuardrailConfigBuilder::regex().with_pii(true).with_injection(true))
+//!     .with(GuardrailConfigBuilder::llama_guard(
+//!         "http://localhost:11434/api/generate",
+//!         "llama-guard-3:8b",
+//!     ))
+//!     .build()
+//!     .unwrap();
+//! ```
+
+use crate::{
+    error::CliError,
+    guardrails::{
+        config::{
+            AggregationMode, ExecutionMode, GuardrailProviderConfig, RegexAction,
+            RegexGuardrailConfig,
+        },
+        gpt_oss_safeguard::GptOssSafeguardConfig,
+        hash_list::HashListConfig,
+        image::{ImageGuardrailConfig, ImageScannerConfig},
+        json_schema::JsonSchemaConfig,
+        language::LanguageConfig,
+        llama_guard::{LlamaGuardCategory, LlamaGuardConfig, LlamaGuardCustomCategory},
+        llama_prompt_guard::LlamaPromptGuardConfig,
+        nemo_guardrails::NemoGuardrailsConfig,
+        numeric::NumericConsistencyConfig,
+        patterns::BuiltinPatterns,
+        provider::Severity,
+        semantic_similarity::SemanticSimilarityConfig,
+        terminology::TerminologyConfig,
+    },
+    secrets::SecretSource,
+};
+use std::path::PathBuf;
+
+/// Anything that can become a validated guardrail configuration
+///
+/// Implemented by every builder and by [`GuardrailProviderConfig`] itself, so
+/// composites accept either.
+pub trait IntoGuardrailConfig {
+    fn into_guardrail_config(self) -> Result<GuardrailProviderConfig, CliError>;
+}
+
+impl IntoGuardrailConfig for GuardrailProviderConfig {
+    fn into_guardrail_config(self) -> Result<GuardrailProviderConfig, CliError> {
+        self.validate()?;
+        Ok(self)
+    }
+}
+
+/// Entry points for the guardrail builders
+pub struct GuardrailConfigBuilder;
+
+impl GuardrailConfigBuilder {
+    /// Regex guardrail (no built-in pattern sets until enabled)
+    pub fn regex() -> RegexBuilder {
+        RegexBuilder {
+            config: RegexGuardrailConfig::default(),
+        }
+    }
+
+    /// Llama Guard 3 with all built-in categories enabled
+    pub fn llama_guard(api_url: impl Into<String>, model: impl Into<String>) -> LlamaGuardBuilder {
+        LlamaGuardBuilder {
+            api_url: api_url.into(),
+            model: model.into(),
+            timeout_secs: LlamaGuardConfig::default().timeout_secs,
+            enabled_categories: LlamaGuardCategory::all(),
+            custom_categories: Vec::new(),
+            key: KeySource::default(),
+        }
+    }
+
+    /// Llama Prompt Guard 2 (input-only prompt injection detection)
+    pub fn llama_prompt_guard(
+        api_url: impl Into<String>,
+        model: impl Into<String>,
+    ) -> LlamaPromptGuardBuilder {
+        let defaults = LlamaPromptGuardConfig::default();
+        LlamaPromptGuardBuilder {
+            api_url: api_url.into(),
+            model: model.into(),
+            timeout_secs: defaults.timeout_secs,
+            threshold: defaults.threshold,
+            max_scan_bytes: defaults.max_scan_bytes,
+            window_bytes: defaults.window_bytes,
+            window_overlap_bytes: defaults.window_overlap_bytes,
+            key: KeySource::default(),
+        }
+    }
+
+    /// GPT-OSS-Safeguard with a policy text
+    pub fn gpt_oss_safeguard(
+        api_url: impl Into<String>,
+        model: impl Into<String>,
+        policy: impl Into<String>,
+    ) -> GptOssSafeguardBuilder {
+        GptOssSafeguardBuilder {
+            api_url: api_url.into(),
+            model: model.into(),
+            policy: policy.into(),
+            timeout_secs: GptOssSafeguardConfig::default().timeout_secs,
+            key: KeySource::default(),
+        }
+    }
+
+    /// NeMo Guardrails server running the rails of `config_id`
+    pub fn nemo_guardrails(
+        api_url: impl Into<String>,
+        config_id: impl Into<String>,
+    ) -> NemoGuardrailsBuilder {
+        NemoGuardrailsBuilder {
+            api_url: api_url.into(),
+            config_id: config_id.into(),
+            timeout_secs: NemoGuardrailsConfig::default().timeout_secs,
+            key: KeySource::default(),
+        }
+    }
+
+    /// SHA-256 allowlist/denylist (needs a deny or allow file)
+    pub fn hash_list() -> HashListBuilder {
+        HashListBuilder {
+            config: HashListConfig::default(),
+        }
+    }
+
+    /// Terminology check (needs at least one required, forbidden or preferred term)
+    pub fn terminology() -> TerminologyBuilder {
+        TerminologyBuilder {
+            config: TerminologyConfig::default(),
+        }
+    }
+
+    /// Numeric consistency against the source document (output-only)
+    pub fn numeric_consistency() -> NumericConsistencyBuilder {
+        NumericConsistencyBuilder {
+            config: NumericConsistencyConfig::default(),
+        }
+    }
+
+    /// Semantic similarity of response chunks to the source (output-only)
+    pub fn semantic_similarity(
+        api_url: impl Into<String>,
+        model: impl Into<String>,
+    ) -> SemanticSimilarityBuilder {
+        SemanticSimilarityBuilder {
+            config: SemanticSimilarityConfig {
+                api_url: api_url.into(),
+                model: model.into(),
+                ..SemanticSimilarityConfig::default()
+            },
+            key: KeySource::default(),
+        }
+    }
+
+    /// Response language check (target, requested or prompt language)
+    pub fn language() -> LanguageBuilder {
+        LanguageBuilder {
+            config: LanguageConfig::default(),
+        }
+    }
+
+    /// JSON Schema conformance (default schema: the request's `response_format`)
+    pub fn json_schema() -> JsonSchemaBuilder {
+        JsonSchemaBuilder {
+            config: JsonSchemaConfig::default(),
+        }
+    }
+
+    /// Generated image checks: formats, size, dimensions, scanner (output-only)
+    pub fn image() -> ImageBuilder {
+        ImageBuilder {
+            config: ImageGuardrailConfig::default(),
+        }
+    }
+
+    /// Composite of other guardrails (parallel, all must pass by default)
+    pub fn composite() -> CompositeBuilder {
+        CompositeBuilder {
+            providers: Vec::new(),
+            execution: ExecutionMode::default(),
+            aggregation: AggregationMode::default(),
+        }
+    }
+}
+
+/// `api_key` / `api_key_name` / `api_key_secret` shared by the LLM-based builders
+#[derive(Default)]
+struct KeySource {
+    api_key: Option<String>,
+    api_key_name: Option<String>,
+    api_key_secret: Option<SecretSource>,
+}
+
+/// Builder for [`GuardrailProviderConfig::Regex`]
+pub struct RegexBuilder {
+    config: RegexGuardrailConfig,
+}
+
+impl RegexBuilder {
+    /// SSNs, credit cards, email addresses, phone numbers
+    pub fn with_pii(self, enabled: bool) -> Self {
+        self.with_builtin(BuiltinPatterns::Pii, enabled)
+    }
+
+    /// Prompt injection, jailbreak and SQL injection attempts
+    pub fn with_injection(self, enabled: bool) -> Self {
+        self.with_builtin(BuiltinPatterns::Injection, enabled)
+    }
+
+    /// Dangerous instructions in LLM responses
+    pub fn with_harmful_output(self, enabled: bool) -> Self {
+        self.with_builtin(BuiltinPatterns::HarmfulOutput, enabled)
+    }
+
+    /// AWS keys, GitHub tokens, JWTs, private key blocks and high-entropy
+    /// strings, all reported as `SECRET_LEAK`
+    pub fn with_secrets(self, enabled: bool) -> Self {
+        self.with_builtin(BuiltinPatterns::Secrets, enabled)
+    }
+
+    /// Enable or disable a built-in pattern set
+    pub fn with_builtin(mut self, set: BuiltinPatterns, enabled: bool) -> Self {
+        self.config.builtin_patterns.retain(|s| *s != set);
+        if enabled {
+            self.config.builtin_patterns.push(set);
+        }
+        self
+    }
+
+    pub fn max_length_bytes(mut self, max: usize) -> Self {
+        self.config.max_length_bytes = max;
+        self
+    }
+
+    /// User-provided patterns file (checked for existence at build time)
+    pub fn patterns_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.config.patterns_file = Some(path.into());
+        self
+    }
+
+    /// Minimum severity that blocks (lower severities become warnings)
+    pub fn severity_threshold(mut self, severity: Severity) -> Self {
+        self.config.severity_threshold = severity;
+        self
+    }
+
+    /// Block on matches (default) or replace sub-Critical matches with placeholders
+    pub fn action(mut self, action: RegexAction) -> Self {
+        self.config.action = action;
+        self
+    }
+
+    /// Attach a masked excerpt with `chars` characters of context on each
+    /// side of the match to every violation
+    pub fn preview_chars(mut self, chars: usize) -> Self {
+        self.config.preview_chars = Some(chars);
+        self
+    }
+
+    pub fn build(self) -> Result<GuardrailProviderConfig, CliError> {
+        GuardrailProviderConfig::Regex(self.config).into_guardrail_config()
+    }
+}
+
+/// Builder for [`GuardrailProviderConfig::LlamaGuard`]
+pub struct LlamaGuardBuilder {
+    api_url: String,
+    model: String,
+    timeout_secs: u64,
+    enabled_categories: Vec<LlamaGuardCategory>,
+    custom_categories: Vec<LlamaGuardCustomCategory>,
+    key: KeySource,
+}
+
+impl LlamaGuardBuilder {
+    /// Replace the enabled built-in categories
+    pub fn categories(mut self, categories: impl IntoIterator<Item = LlamaGuardCategory>) -> Self {
+        self.enabled_categories = categories.into_iter().collect();
+        self
+    }
+
+    /// Add a custom category (see `LlamaGuardCustomCategory`)
+    pub fn custom_category(
+        mut self,
+        code: impl Into<String>,
+        title: impl Into<String>,
+        description: impl Into<String>,
+    ) -> Self {
+        self.custom_categories.push(LlamaGuardCustomCategory {
+            code: code.into(),
+            title: title.into(),
+            description: description.into(),
+        });
+        self
+    }
+
+    pub fn timeout_secs(mut self, timeout_secs: u64) -> Self {
+        self.timeout_secs = timeout_secs;
+        self
+    }
+
+    pub fn api_key(mut self, key: impl Into<String>) -> Self {
+        self.key.api_key = Some(key.into());
+        self
+    }
+
+    /// Environment variable holding the API key
+    pub fn api_key_name(mut self, name: impl Into<String>) -> Self {
+        self.key.api_key_name = Some(name.into());
+        self
+    }
+
+    /// Secret source (env, file, keyring or vault) holding the API key
+    pub fn api_key_secret(mut self, source: SecretSource) -> Self {
+        self.key.api_key_secret = Some(source);
+        self
+    }
+
+    pub fn build(self) -> Result<GuardrailProviderConfig, CliError> {
+        GuardrailProviderConfig::LlamaGuard {
+            api_url: self.api_url,
+            model: self.model,
+            timeout_secs: self.timeout_secs,
+            enabled_categories: self.enabled_categories,
+            custom_categories: self.custom_categories,
+            api_key: self.key.api_key,
+            api_key_name: self.key.api_key_name,
+            api_key_secret: self.key.api_key_secret,
+            resolved_api_key: None,
+        }
+        .into_guardrail_config()
+    }
+}
+
+/// Builder for [`GuardrailProviderConfig::LlamaPromptGuard`]
+pub struct LlamaPromptGuardBuilder {
+    api_url: String,
+    model: String,
+    timeout_secs: u64,
+    threshold: f32,
+    max_scan_bytes: usize,
+    window_bytes: usize,
+    window_overlap_bytes: usize,
+    key: KeySource,
+}
+
+impl LlamaPromptGuardBuilder {
+    /// Malicious-probability threshold (0.0-1.0)
+    pub fn threshold(mut self, threshold: f32) -> Self {
+        self.threshold = threshold;
+        self
+    }
+
+    /// Leading bytes of the input that are scanned
+    pub fn max_scan_bytes(mut self, bytes: usize) -> Self {
+        self.max_scan_bytes = bytes;
+        self
+    }
+
+    /// Window size and overlap of the classifier requests, in bytes
+    pub fn window(mut self, bytes: usize, overlap_bytes: usize) -> Self {
+        self.window_bytes = bytes;
+        self.window_overlap_bytes = overlap_bytes;
+        self
+    }
+
+    pub fn timeout_secs(mut self, timeout_secs: u64) -> Self {
+        self.timeout_secs = timeout_secs;
+        self
+    }
+
+    pub fn api_key(mut self, key: impl Into<String>) -> Self {
+        self.key.api_key = Some(key.into());
+        self
+    }
+
+    /// Environment variable holding the API key
+    pub fn api_key_name(mut self, name: impl Into<String>) -> Self {
+        self.key.api_key_name = Some(name.into());
+        self
+    }
+
+    /// Secret source (env, file, keyring or vault) holding the API key
+    pub fn api_key_secret(mut self, source: SecretSource) -> Self {
+        self.key.api_key_secret = Some(source);
+        self
+    }
+
+    pub fn build(self) -> Result<GuardrailProviderConfig, CliError> {
+        GuardrailProviderConfig::LlamaPromptGuard {
+            api_url: self.api_url,
+            model: self.model,
+            timeout_secs: self.timeout_secs,
+            threshold: self.threshold,
+            api_key: self.key.api_key,
+            api_key_name: self.key.api_key_name,
+            api_key_secret: self.key.api_key_secret,
+            resolved_api_key: None,
+            max_scan_bytes: self.max_scan_bytes,
+            window_bytes: self.window_bytes,
+            window_overlap_bytes: self.window_overlap_bytes,
+        }
+        .into_guardrail_config()
+    }
+}
+
+/// Builder for [`GuardrailProviderConfig::GptOssSafeguard`]
+pub struct GptOssSafeguardBuilder {
+    api_url: String,
+    model: String,
+    policy: String,
+    timeout_secs: u64,
+    key: KeySource,
+}
+
+impl GptOssSafeguardBuilder {
+    pub fn timeout_secs(mut self, timeout_secs: u64) -> Self {
+        self.timeout_secs = timeout_secs;
+        self
+    }
+
+    pub fn api_key(mut self, key: impl Into<String>) -> Self {
+        self.key.api_key = Some(key.into());
+        self
+    }
+
+    /// Environment variable holding the API key
+    pub fn api_key_name(mut self, name: impl Into<String>) -> Self {
+        self.key.api_key_name = Some(name.into());
+        self
+    }
+
+    /// Secret source (env, file, keyring or vault) holding the API key
+    pub fn api_key_secret(mut self, source: SecretSource) -> Self {
+        self.key.api_key_secret = Some(source);
+        self
+    }
+
+    pub fn build(self) -> Result<GuardrailProviderConfig, CliError> {
+        GuardrailProviderConfig::GptOssSafeguard {
+            api_url: self.api_url,
+            model: self.model,
+            policy: self.policy,
+            timeout_secs: self.timeout_secs,
+            api_key: self.key.api_key,
+            api_key_name: self.key.api_key_name,
+            api_key_secret: self.key.api_key_secret,
+            resolved_api_key: None,
+        }
+        .into_guardrail_config()
+    }
+}
+
+/// Builder for [`GuardrailProviderConfig::NemoGuardrails`]
+pub struct NemoGuardrailsBuilder {
+    api_url: String,
+    config_id: String,
+    timeout_secs: u64,
+    key: KeySource,
+}
+
+impl NemoGuardrailsBuilder {
+    pub fn timeout_secs(mut self, timeout_secs: u64) -> Self {
+        self.timeout_secs = timeout_secs;
+        self
+    }
+
+    pub fn api_key(mut self, key: impl Into<String>) -> Self {
+        self.key.api_key = Some(key.into());
+        self
+    }
+
+    /// Environment variable holding the API key
+    pub fn api_key_name(mut self, name: impl Into<String>) -> Self {
+        self.key.api_key_name = Some(name.into());
+        self
+    }
+
+    /// Secret source (env, file, keyring or vault) holding the API key
+    pub fn api_key_secret(mut self, source: SecretSource) -> Self {
+        self.key.api_key_secret = Some(source);
+        self
+    }
+
+    pub fn build(self) -> Result<GuardrailProviderConfig, CliError> {
+        GuardrailProviderConfig::NemoGuardrails {
+            api_url: self.api_url,
+            config_id: self.config_id,
+            timeout_secs: self.timeout_secs,
+            api_key: self.key.api_key,
+            api_key_name: self.key.api_key_name,
+            api_key_secret: self.key.api_key_secret,
+            resolved_api_key: None,
+        }
+        .into_guardrail_config()
+    }
+}
+
+/// Builder for [`GuardrailProviderConfig::HashList`]
+pub struct HashListBuilder {
+    config: HashListConfig,
+}
+
+impl HashListBuilder {
+    /// File of digests to block
+    pub fn deny_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.config.deny_file = Some(path.into());
+        self
+    }
+
+    /// File of digests of pre-approved content
+    pub fn allow_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.config.allow_file = Some(path.into());
+        self
+    }
+
+    /// Also match the normalized text (lowercased, whitespace collapsed)
+    pub fn normalize(mut self, enabled: bool) -> Self {
+        self.config.normalize = enabled;
+        self
+    }
+
+    pub fn build(self) -> Result<GuardrailProviderConfig, CliError> {
+        GuardrailProviderConfig::HashList(self.config).into_guardrail_config()
+    }
+}
+
+/// Builder for [`GuardrailProviderConfig::Terminology`]
+pub struct TerminologyBuilder {
+    config: TerminologyConfig,
+}
+
+impl TerminologyBuilder {
+    /// Term that must appear; `note` is included when it is missing
+    pub fn require(mut self, term: impl Into<String>, note: impl Into<String>) -> Self {
+        self.config.required.insert(term.into(), note.into());
+        self
+    }
+
+    /// Term that must not appear
+    pub fn forbid(mut self, term: impl Into<String>) -> Self {
+        self.config.forbidden.push(term.into());
+        self
+    }
+
+    /// Flag `discouraged` and suggest `preferred` instead
+    pub fn prefer(mut self, discouraged: impl Into<String>, preferred: impl Into<String>) -> Self {
+        self.config
+            .preferred
+            .insert(discouraged.into(), preferred.into());
+        self
+    }
+
+    pub fn case_sensitive(mut self, enabled: bool) -> Self {
+        self.config.case_sensitive = enabled;
+        self
+    }
+
+    /// Minimum severity that blocks (default: high, so findings only warn)
+    pub fn severity_threshold(mut self, severity: Severity) -> Self {
+        self.config.severity_threshold = severity;
+        self
+    }
+
+    pub fn build(self) -> Result<GuardrailProviderConfig, CliError> {
+        GuardrailProviderConfig::Terminology(self.config).into_guardrail_config()
+    }
+}
+
+/// Builder for [`GuardrailProviderConfig::NumericConsistency`]
+pub struct NumericConsistencyBuilder {
+    config: NumericConsistencyConfig,
+}
+
+impl NumericConsistencyBuilder {
+    pub fn check_dates(mut self, enabled: bool) -> Self {
+        self.config.check_dates = enabled;
+        self
+    }
+
+    /// Plain integers below `value` are not checked
+    pub fn ignore_integers_below(mut self, value: u64) -> Self {
+        self.config.ignore_integers_below = value;
+        self
+    }
+
+    /// Relative difference accepted on top of rounding (0.0-1.0)
+    pub fn relative_tolerance(mut self, tolerance: f64) -> Self {
+        self.config.relative_tolerance = tolerance;
+        self
+    }
+
+    /// Minimum severity that blocks (default: high, so findings only warn)
+    pub fn severity_threshold(mut self, severity: Severity) -> Self {
+        self.config.severity_threshold = severity;
+        self
+    }
+
+    pub fn build(self) -> Result<GuardrailProviderConfig, CliError> {
+        GuardrailProviderConfig::NumericConsistency(self.config).into_guardrail_config()
+    }
+}
+
+/// Builder for [`GuardrailProviderConfig::SemanticSimilarity`]
+pub struct SemanticSimilarityBuilder {
+    config: SemanticSimilarityConfig,
+    key: KeySource,
+}
+
+impl SemanticSimilarityBuilder {
+    /// Response chunks below this similarity are reported (0.0-1.0)
+    pub fn min_similarity(mut self, similarity: f32) -> Self {
+        self.config.min_similarity = similarity;
+        self
+    }
+
+    /// Group sentences into chunks of up to `max` characters, skipping
+    /// response chunks shorter than `min`
+    pub fn chunk_chars(mut self, max: usize, min: usize) -> Self {
+        self.config.chunk_chars = max;
+        self.config.min_chunk_chars = min;
+        self
+    }
+
+    /// Minimum severity that blocks (default: high, so findings only warn)
+    pub fn severity_threshold(mut self, severity: Severity) -> Self {
+        self.config.severity_threshold = severity;
+        self
+    }
+
+    pub fn timeout_secs(mut self, timeout_secs: u64) -> Self {
+        self.config.timeout_secs = timeout_secs;
+        self
+    }
+
+    pub fn api_key(mut self, key: impl Into<String>) -> Self {
+        self.key.api_key = Some(key.into());
+        self
+    }
+
+    /// Environment variable holding the API key
+    pub fn api_key_name(mut self, name: impl Into<String>) -> Self {
+        self.key.api_key_name = Some(name.into());
+        self
+    }
+
+    /// Secret source (env, file, keyring or vault) holding the API key
+    pub fn api_key_secret(mut self, source: SecretSource) -> Self {
+        self.key.api_key_secret = Some(source);
+        self
+    }
+
+    pub fn build(self) -> Result<GuardrailProviderConfig, CliError> {
+        GuardrailProviderConfig::SemanticSimilarity {
+            api_url: self.config.api_url,
+            model: self.config.model,
+            timeout_secs: self.config.timeout_secs,
+            min_similarity: self.config.min_similarity,
+            chunk_chars: self.config.chunk_chars,
+            min_chunk_chars: self.config.min_chunk_chars,
+            severity_threshold: self.config.severity_threshold,
+            api_key: self.key.api_key,
+            api_key_name: self.key.api_key_name,
+            api_key_secret: self.key.api_key_secret,
+            resolved_api_key: None,
+        }
+        .into_guardrail_config()
+    }
+}
+
+/// Builder for [`GuardrailProviderConfig::Language`]
+pub struct LanguageBuilder {
+    config: LanguageConfig,
+}
+
+impl LanguageBuilder {
+    /// Expected language ("fr", "pt-BR", "fra" or "French")
+    pub fn target(mut self, language: impl Into<String>) -> Self {
+        self.config.target = Some(language.into());
+        self
+    }
+
+    /// Honor languages requested in the user prompt (default: true)
+    pub fn follow_requests(mut self, enabled: bool) -> Self {
+        self.config.follow_requests = enabled;
+        self
+    }
+
+    /// Detections below `confidence` (0.0-1.0) are not checked
+    pub fn min_confidence(mut self, confidence: f64) -> Self {
+        self.config.min_confidence = confidence;
+        self
+    }
+
+    /// Texts shorter than `chars` characters are not checked
+    pub fn min_chars(mut self, chars: usize) -> Self {
+        self.config.min_chars = chars;
+        self
+    }
+
+    /// Minimum severity that blocks (default: high, so mismatches only warn)
+    pub fn severity_threshold(mut self, severity: Severity) -> Self {
+        self.config.severity_threshold = severity;
+        self
+    }
+
+    pub fn build(self) -> Result<GuardrailProviderConfig, CliError> {
+        GuardrailProviderConfig::Language(self.config).into_guardrail_config()
+    }
+}
+
+/// Builder for [`GuardrailProviderConfig::JsonSchema`]
+pub struct JsonSchemaBuilder {
+    config: JsonSchemaConfig,
+}
+
+impl JsonSchemaBuilder {
+    /// JSON Schema file (instead of the `response_format` schema)
+    pub fn schema_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.config.schema_file = Some(path.into());
+        self
+    }
+
+    /// Reject properties the schema doesn't declare
+    pub fn strict(mut self, enabled: bool) -> Self {
+        self.config.strict = enabled;
+        self
+    }
+
+    pub fn build(self) -> Result<GuardrailProviderConfig, CliError> {
+        GuardrailProviderConfig::JsonSchema(self.config).into_guardrail_config()
+    }
+}
+
+/// Builder for [`GuardrailProviderConfig::Image`]
+pub struct ImageBuilder {
+    config: ImageGuardrailConfig,
+}
+
+impl ImageBuilder {
+    /// Accepted formats ("png", "jpeg", "gif", "webp"; default: all)
+    pub fn allowed_formats<I, S>(mut self, formats: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.config.allowed_formats = formats.into_iter().map(Into::into).collect();
+        self
+    }
+
+    pub fn max_bytes(mut self, bytes: u64) -> Self {
+        self.config.max_bytes = Some(bytes);
+        self
+    }
+
+    /// Maximum width and height in pixels
+    pub fn max_dimensions(mut self, width: u32, height: u32) -> Self {
+        self.config.max_width = Some(width);
+        self.config.max_height = Some(height);
+        self
+    }
+
+    /// External scanner webhook (see [`ImageScannerConfig`])
+    pub fn scanner(mut self, scanner: ImageScannerConfig) -> Self {
+        self.config.scanner = Some(scanner);
+        self
+    }
+
+    pub fn build(self) -> Result<GuardrailProviderConfig, CliError> {
+        GuardrailProviderConfig::Image(self.config).into_guardrail_config()
+    }
+}
+
+/// Builder for [`GuardrailProviderConfig::Composite`]
+///
+/// Members are built when the composite is built; the first invalid member
+/// fails the whole composite.
+pub struct CompositeBuilder {
+    providers: Vec<Result<GuardrailProviderConfig, CliError>>,
+    execution: ExecutionMode,
+    aggregation: AggregationMode,
+}
+
+impl CompositeBuilder {
+    /// Add a member (a builder or a ready-made configuration)
+    pub fn with(mut self, provider: impl IntoGuardrailConfig) -> Self {
+        self.providers.push(provider.into_guardrail_config());
+        self
+    }
+
+    /// Run members one at a time, in insertion order
+    pub fn sequential(mut self) -> Self {
+        self.execution = ExecutionMode::Sequential;
+        self
+    }
+
+    /// Run members concurrently (default)
+    pub fn parallel(mut self) -> Self {
+        self.execution = ExecutionMode::Parallel;
+        self
+    }
+
+    /// Pass if any member passes (default: all must pass)
+    pub fn any_can_pass(mut self) -> Self {
+        self.aggregation = AggregationMode::AnyCanPass;
+        self
+    }
+
+    /// Pass if more than half of the members pass
+    pub fn majority_vote(mut self) -> Self {
+        self.aggregation = AggregationMode::MajorityVote;
+        self
+    }
+
+    /// Pass if the weighted share of passing members reaches `min_score`
+    ///
+    /// Weights are keyed by provider type; unlisted members weigh 1.0.
+    pub fn weighted<'a>(
+        mut self,
+        min_score: f32,
+        weights: impl IntoIterator<Item = (&'a str, f32)>,
+    ) -> Self {
+        self.aggregation = AggregationMode::Weighted {
+            min_score,
+            weights: weights
+                .into_iter()
+                .map(|(kind, weight)| (kind.to_string(), weight))
+                .collect(),
+        };
+        self
+    }
+
+    pub fn build(self) -> Result<GuardrailProviderConfig, CliError> {
+        GuardrailProviderConfig::Composite {
+            providers: self.providers.into_iter().collect::<Result<_, _>>()?,
+            execution: self.execution,
+            aggregation: self.aggregation,
+        }
+        .into_guardrail_config()
+    }
+}
+
+macro_rules! impl_into_guardrail_config {
+    ($($builder:ty),*) => {
+        $(impl IntoGuardrailConfig for $builder {
+            fn into_guardrail_config(self) -> Result<GuardrailProviderConfig, CliError> {
+                self.build()
+            }
+        })*
+    };
+}
+
+impl_into_guardrail_config!(
+    RegexBuilder,
+    LlamaGuardBuilder,
+    LlamaPromptGuardBuilder,
+    GptOssSafeguardBuilder,
+    NemoGuardrailsBuilder,
+    HashListBuilder,
+    TerminologyBuilder,
+    NumericConsistencyBuilder,
+    SemanticSimilarityBuilder,
+    LanguageBuilder,
+    JsonSchemaBuilder,
+    ImageBuilder,
+    CompositeBuilder
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_regex_builder() {
+        let config = GuardrailConfigBuilder::regex()
+            .with_pii(true)
+            .with_injection(true)
+            .with_pii(true)
+            .with_injection(false)
+            .severity_threshold(Severity::High)
+            .max_length_bytes(4096)
+            .build()
+            .unwrap();
+
+        let regex = config.as_regex_config().unwrap();
+        assert_eq!(regex.builtin_patterns, vec![BuiltinPatterns::Pii]);
+        assert_eq!(regex.severity_threshold, Severity::High);
+        assert_eq!(regex.max_length_bytes, 4096);
+    }
+
+    #[test]
+    fn test_llama_guard_builder_defaults() {
+        let config = GuardrailConfigBuilder::llama_guard(
+            "http://localhost:11434/api/generate",
+            "llama-guard-3:1b",
+        )
+        .categories([LlamaGuardCategory::S1, LlamaGuardCategory::S10])
+        .build()
+        .unwrap();
+
+        let llama = config.to_llama_guard_config().unwrap();
+        assert_eq!(llama.timeout_secs, LlamaGuardConfig::default().timeout_secs);
+        assert_eq!(
+            llama.enabled_categories,
+            vec![LlamaGuardCategory::S1, LlamaGuardCategory::S10]
+        );
+    }
+
+    #[test]
+    fn test_build_time_validation() {
+        let url = "http://localhost:11434/api/generate";
+        let errors = [
+            GuardrailConfigBuilder::regex().max_length_bytes(0).build(),
+            GuardrailConfigBuilder::regex()
+                .patterns_file("/nonexistent/patterns.txt")
+                .build(),
+            GuardrailConfigBuilder::llama_guard("localhost:11434", "m").build(),
+            GuardrailConfigBuilder::llama_guard(url, " ").build(),
+            GuardrailConfigBuilder::llama_guard(url, "m")
+                .categories([])
+                .build(),
+            GuardrailConfigBuilder::llama_guard(url, "m")
+                .custom_category("S1", "Shadows built-in", "")
+                .build(),
+            GuardrailConfigBuilder::llama_guard(url, "m")
+                .api_key("k")
+                .api_key_name("K")
+                .build(),
+            GuardrailConfigBuilder::llama_prompt_guard(url, "m")
+                .threshold(1.5)
+                .build(),
+            GuardrailConfigBuilder::gpt_oss_safeguard(url, "m", "").build(),
+            GuardrailConfigBuilder::terminology().build(),
+            GuardrailConfigBuilder::terminology().forbid("").build(),
+            GuardrailConfigBuilder::numeric_consistency()
+                .relative_tolerance(-0.1)
+                .build(),
+            GuardrailConfigBuilder::language().target("klingon").build(),
+            GuardrailConfigBuilder::composite().build(),
+        ];
+        for (index, result) in errors.into_iter().enumerate() {
+            assert!(
+                matches!(result, Err(CliError::InvalidArguments(_))),
+                "case {index} should fail"
+            );
+        }
+    }
+
+    #[test]
+    fn test_composite_propagates_member_errors() {
+        let result = GuardrailConfigBuilder::composite()
+            .with(GuardrailConfigBuilder::regex().with_pii(true))
+            .with(GuardrailConfigBuilder::llama_prompt_guard("http://x", "m").threshold(-1.0))
+            .build();
+        let Err(err) = result else {
+            panic!("Expected invalid member to fail the composite");
+        };
+        assert!(err.to_string().contains("LlamaPromptGuard"), "{err}");
+
+        let config = GuardrailConfigBuilder::composite()
+            .sequential()
+            .any_can_pass()
+            .with(GuardrailProviderConfig::default())
+            .build()
+            .unwrap();
+        let GuardrailProviderConfig::Composite {
+            providers,
+            execution,
+            aggregation,
+        } = config
+        else {
+            panic!("Expected composite");
+        };
+        assert_eq!(providers.len(), 1);
+        assert_eq!(execution, ExecutionMode::Sequential);
+        assert_eq!(aggregation, AggregationMode::AnyCanPass);
+    }
+}
@@ -0,0 +1,272 @@
+//! Rule catalog
+//!
+//! Every violation carries a provider namespace, so rules have stable,
+//! machine-readable ids such as `llama_guard.S1`, `regex.MAX_LENGTH` or
+//! `prompt_guard.PROMPT_INJECTION`. [`rule_catalog`] lists all built-in rules
+//! with descriptions so dashboards can map ids without parsing messages.
+//!
+//! Rules from a `patterns_file`, Llama Guard custom categories,
+//! GPT-OSS-Safeguard policies and NeMo Guardrails rails are user-defined and
+//! not listed; they use the same namespaces. Blocks by the provider's own
+//! content filter use the `content_filter` namespace with the provider's
+//! category names (e.g. `content_filter.HATE`).
+
+use crate::guardrails::{
+    budget::{self, rules as budget_rules},
+    hash_list,
+    image::{self, rules as image_rules},
+    json_schema::{self, rules as json_schema_rules},
+    language::{self, rules as language_rules},
+    llama_guard::LlamaGuardCategory,
+    numeric::{self, rules as numeric_rules},
+    patterns::{BuiltinPatterns, SECRET_LEAK},
+    provider::Severity,
+    semantic_similarity::{self, rules as semantic_rules},
+    terminology::{self, rules as terminology_rules},
+};
+use serde::Serialize;
+
+/// Provider namespaces used as rule id prefixes
+pub mod namespace {
+    pub const REGEX: &str = "regex";
+    pub const LLAMA_GUARD: &str = "llama_guard";
+    pub const PROMPT_GUARD: &str = "prompt_guard";
+    pub const GPT_OSS_SAFEGUARD: &str = "gpt_oss_safeguard";
+    pub const NEMO: &str = "nemo";
+    pub const TERMINOLOGY: &str = "terminology";
+    pub const NUMERIC: &str = "numeric";
+    pub const SEMANTIC: &str = "semantic";
+    pub const LANGUAGE: &str = "language";
+    pub const HASH_LIST: &str = "hash_list";
+    pub const JSON_SCHEMA: &str = "json_schema";
+    pub const IMAGE: &str = "image";
+    /// Model-backed guardrail call refused by `[guard_budget]`
+    pub const GUARD_BUDGET: &str = "guard_budget";
+    /// Provider-side content filter (not a configurable guardrail)
+    pub const CONTENT_FILTER: &str = "content_filter";
+}
+
+/// Join a namespace and rule into a rule id
+pub fn rule_id(namespace: &str, rule: &str) -> String {
+    if namespace.is_empty() {
+        rule.to_string()
+    } else {
+        format!("{namespace}.{rule}")
+    }
+}
+
+/// One rule a built-in guardrail can report
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RuleInfo {
+    /// Namespaced id (e.g., "llama_guard.S1")
+    pub id: String,
+    pub namespace: &'static str,
+    /// Rule as reported in `Violation::rule`
+    pub rule: String,
+    pub description: String,
+    pub severity: Severity,
+}
+
+impl RuleInfo {
+    fn new(namespace: &'static str, rule: &str, description: &str, severity: Severity) -> Self {
+        Self {
+            id: rule_id(namespace, rule),
+            namespace,
+            rule: rule.to_string(),
+            description: description.to_string(),
+            severity,
+        }
+    }
+}
+
+/// All rules reported by the built-in guardrails, in a stable order
+pub fn rule_catalog() -> Vec<RuleInfo> {
+    let mut rules = vec![RuleInfo::new(
+        namespace::REGEX,
+        "MAX_LENGTH",
+        "Content exceeds the configured max length",
+        Severity::High,
+    )];
+    for set in [
+        BuiltinPatterns::Pii,
+        BuiltinPatterns::Injection,
+        BuiltinPatterns::HarmfulOutput,
+    ] {
+        for pattern in set.patterns() {
+            let rule = pattern.rule();
+            if !rules.iter().any(|r| r.rule == rule) {
+                rules.push(RuleInfo::new(
+                    namespace::REGEX,
+                    &rule,
+                    &pattern.description,
+                    pattern.severity,
+                ));
+            }
+        }
+    }
+    // One rule for every secret pattern and the entropy scan
+    rules.push(RuleInfo::new(
+        namespace::REGEX,
+        SECRET_LEAK,
+        "Leaked credential (known key format or high-entropy string)",
+        Severity::Critical,
+    ));
+
+    rules.extend(LlamaGuardCategory::all().into_iter().map(|category| {
+        RuleInfo::new(
+            namespace::LLAMA_GUARD,
+            category.as_str(),
+            category.description(),
+            Severity::Critical,
+        )
+    }));
+
+    rules.push(RuleInfo::new(
+        namespace::PROMPT_GUARD,
+        "PROMPT_INJECTION",
+        "Prompt injection or jailbreak attempt",
+        Severity::Critical,
+    ));
+    rules.push(RuleInfo::new(
+        namespace::HASH_LIST,
+        hash_list::rules::DENYLISTED,
+        "Content digest is on the denylist",
+        Severity::Critical,
+    ));
+    rules.push(RuleInfo::new(
+        namespace::GPT_OSS_SAFEGUARD,
+        "POLICY_VIOLATION",
+        "Policy violation without a category",
+        Severity::Critical,
+    ));
+    for (rule, description) in [
+        (
+            terminology_rules::REQUIRED_TERM,
+            "A required glossary term is missing",
+        ),
+        (
+            terminology_rules::FORBIDDEN_TERM,
+            "A forbidden glossary term is used",
+        ),
+        (
+            terminology_rules::PREFERRED_SPELLING,
+            "A discouraged spelling is used instead of the preferred one",
+        ),
+    ] {
+        rules.push(RuleInfo::new(
+            namespace::TERMINOLOGY,
+            rule,
+            description,
+            terminology::rule_severity(rule),
+        ));
+    }
+    for (rule, description) in [
+        (
+            numeric_rules::UNSUPPORTED_NUMBER,
+            "A number in the response does not appear in the source",
+        ),
+        (
+            numeric_rules::UNSUPPORTED_DATE,
+            "A date in the response does not appear in the source",
+        ),
+    ] {
+        rules.push(RuleInfo::new(
+            namespace::NUMERIC,
+            rule,
+            description,
+            numeric::RULE_SEVERITY,
+        ));
+    }
+    rules.push(RuleInfo::new(
+        namespace::SEMANTIC,
+        semantic_rules::LOW_SIMILARITY,
+        "A response section is semantically unlike the source",
+        semantic_similarity::RULE_SEVERITY,
+    ));
+    for (rule, description) in [
+        (
+            json_schema_rules::INVALID_JSON,
+            "The response is not valid JSON",
+        ),
+        (
+            json_schema_rules::SCHEMA_VIOLATION,
+            "The response does not conform to the JSON schema",
+        ),
+    ] {
+        rules.push(RuleInfo::new(
+            namespace::JSON_SCHEMA,
+            rule,
+            description,
+            json_schema::RULE_SEVERITY,
+        ));
+    }
+    rules.push(RuleInfo::new(
+        namespace::LANGUAGE,
+        language_rules::LANGUAGE_MISMATCH,
+        "The response is not in the expected language",
+        language::RULE_SEVERITY,
+    ));
+    for (rule, description) in [
+        (
+            image_rules::IMAGE_FORMAT_NOT_ALLOWED,
+            "A generated image is in a format that is not allowed",
+        ),
+        (
+            image_rules::IMAGE_TOO_LARGE,
+            "A generated image exceeds the byte size limit",
+        ),
+        (
+            image_rules::IMAGE_DIMENSIONS_EXCEEDED,
+            "A generated image exceeds the pixel dimension limits",
+        ),
+        (
+            image_rules::IMAGE_MALFORMED,
+            "A generated image's dimensions could not be read",
+        ),
+        (
+            image_rules::IMAGE_FLAGGED,
+            "A generated image was flagged by the external scanner",
+        ),
+    ] {
+        rules.push(RuleInfo::new(
+            namespace::IMAGE,
+            rule,
+            description,
+            image::RULE_SEVERITY,
+        ));
+    }
+    rules.push(RuleInfo::new(
+        namespace::GUARD_BUDGET,
+        budget_rules::BUDGET_EXHAUSTED,
+        "A model-backed guardrail was not run: its token budget is exhausted",
+        budget::RULE_SEVERITY,
+    ));
+    rules
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_catalog_ids_are_unique_and_namespaced() {
+        let catalog = rule_catalog();
+        let mut ids: Vec<&str> = catalog.iter().map(|r| r.id.as_str()).collect();
+        ids.sort_unstable();
+        ids.dedup();
+        assert_eq!(ids.len(), catalog.len());
+
+        assert!(ids.contains(&"llama_guard.S1"));
+        assert!(ids.contains(&"regex.MAX_LENGTH"));
+        assert!(ids.contains(&"regex.SECRET_LEAK"));
+        assert!(ids.contains(&"prompt_guard.PROMPT_INJECTION"));
+        assert!(ids.contains(&"terminology.FORBIDDEN_TERM"));
+        assert!(catalog.iter().all(|r| r.id.starts_with(r.namespace)));
+    }
+
+    #[test]
+    fn test_rule_id_without_namespace() {
+        assert_eq!(rule_id("", "TEST"), "TEST");
+        assert_eq!(rule_id(namespace::REGEX, "TEST"), "regex.TEST");
+    }
+}
@@ -0,0 +1,662 @@
+//! Conditional guardrails
+//!
+//! Rules in `[[guardrails.conditional]]` skip or add guardrail providers
+//! depending on the input, and are applied when a guard stage assembles its
+//! providers (after PDF extraction):
+//!
+//! ```toml
+//! [[guardrails.conditional]]
+//! when = "input_tokens > 50k"
+//! stage = "input"
+//! skip = ["llama_prompt_guard"]
+//!
+//! [[guardrails.conditional]]
+//! when = "input_source == pdf"
+//! stage = "output"
+//! add = { type = "numeric_consistency" }
+//!
+//! [[guardrails.conditional]]
+//! when = "response_format == json_schema"
+//! skip = ["terminology"]
+//! ```
+//!
+//! Conditions compare input facts with `==`, `!=`, `>`, `>=`, `<` and `<=`,
+//! combined with `and`, `or`, `not` and parentheses. Numbers accept `k`/`m`
+//! suffixes; strings may be quoted or bare words and compare
+//! case-insensitively. Facts:
+//!
+//! | Fact | Type | Value |
+//! |------|------|-------|
+//! | `input_tokens` | number | Estimated tokens of the user input |
+//! | `input_bytes` | number | Size of the user input |
+//! | `untrusted_content` | number | Number of untrusted content entries |
+//! | `input_source` | string | `pdf`, `file` or `text` |
+//! | `response_format` | string | `none`, `text`, `json_object` or `json_schema` |
+//! | `model` | string | Configured model |
+
+use crate::{
+    error::CliError,
+    guardrails::config::{AggregationMode, ExecutionMode, GuardrailProviderConfig},
+};
+use serde::{Deserialize, Serialize};
+use std::{fmt, str::FromStr};
+
+/// Guard stage a conditional rule applies to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GuardrailStage {
+    Input,
+    Output,
+}
+
+impl fmt::Display for GuardrailStage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Input => write!(f, "input"),
+            Self::Output => write!(f, "output"),
+        }
+    }
+}
+
+/// Input characteristics conditions are evaluated against
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct InputFacts {
+    pub input_tokens: usize,
+    pub input_bytes: usize,
+    pub untrusted_content: usize,
+    /// `pdf`, `file` or `text`
+    pub input_source: String,
+    /// `none`, `text`, `json_object` or `json_schema`
+    pub response_format: String,
+    pub model: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Fact {
+    InputTokens,
+    InputBytes,
+    UntrustedContent,
+    InputSource,
+    ResponseFormat,
+    Model,
+}
+
+impl Fact {
+    const NAMES: &'static str =
+        "input_tokens, input_bytes, untrusted_content, input_source, response_format, model";
+
+    fn parse(name: &str) -> Option<Self> {
+        Some(match name {
+            "input_tokens" => Self::InputTokens,
+            "input_bytes" => Self::InputBytes,
+            "untrusted_content" => Self::UntrustedContent,
+            "input_source" => Self::InputSource,
+            "response_format" => Self::ResponseFormat,
+            "model" => Self::Model,
+            _ => return None,
+        })
+    }
+
+    fn is_numeric(self) -> bool {
+        matches!(
+            self,
+            Self::InputTokens | Self::InputBytes | Self::UntrustedContent
+        )
+    }
+
+    fn value(self, facts: &InputFacts) -> Value {
+        match self {
+            Self::InputTokens => Value::Number(facts.input_tokens as f64),
+            Self::InputBytes => Value::Number(facts.input_bytes as f64),
+            Self::UntrustedContent => Value::Number(facts.untrusted_content as f64),
+            Self::InputSource => Value::Text(facts.input_source.clone()),
+            Self::ResponseFormat => Value::Text(facts.response_format.clone()),
+            Self::Model => Value::Text(facts.model.clone()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Value {
+    Number(f64),
+    Text(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum CmpOp {
+    Eq,
+    Ne,
+    Gt,
+    Ge,
+    Lt,
+    Le,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Expr {
+    Compare { fact: Fact, op: CmpOp, value: Value },
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Not(Box<Expr>),
+}
+
+impl Expr {
+    fn eval(&self, facts: &InputFacts) -> bool {
+        match self {
+            Self::Compare { fact, op, value } => match (fact.value(facts), value) {
+                (Value::Number(actual), Value::Number(expected)) => match op {
+                    CmpOp::Eq => actual == *expected,
+                    CmpOp::Ne => actual != *expected,
+                    CmpOp::Gt => actual > *expected,
+                    CmpOp::Ge => actual >= *expected,
+                    CmpOp::Lt => actual < *expected,
+                    CmpOp::Le => actual <= *expected,
+                },
+                (Value::Text(actual), Value::Text(expected)) => {
+                    let equal = actual.eq_ignore_ascii_case(expected);
+                    if *op == CmpOp::Eq {
+                        equal
+                    } else {
+                        !equal
+                    }
+                }
+                _ => unreachable!("operand types checked when parsing"),
+            },
+            Self::And(left, right) => left.eval(facts) && right.eval(facts),
+            Self::Or(left, right) => left.eval(facts) || right.eval(facts),
+            Self::Not(inner) => !inner.eval(facts),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Word(String),
+    Number(f64),
+    Str(String),
+    Op(CmpOp),
+    LParen,
+    RParen,
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = input.char_indices().peekable();
+    while let Some(&(start, c)) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+        } else if c == '(' || c == ')' {
+            chars.next();
+            tokens.push(if c == '(' {
+                Token::LParen
+            } else {
+                Token::RParen
+            });
+        } else if "=!<>".contains(c) {
+            chars.next();
+            let equals = chars.next_if(|&(_, next)| next == '=').is_some();
+            tokens.push(Token::Op(match (c, equals) {
+                ('=', true) => CmpOp::Eq,
+                ('!', true) => CmpOp::Ne,
+                ('>', false) => CmpOp::Gt,
+                ('>', true) => CmpOp::Ge,
+                ('<', false) => CmpOp::Lt,
+                ('<', true) => CmpOp::Le,
+                _ => return Err(format!("unexpected '{c}' at position {start}")),
+            }));
+        } else if c == '\'' || c == '"' {
+            chars.next();
+            let mut text = String::new();
+            loop {
+                match chars.next() {
+                    Some((_, ch)) if ch == c => break,
+                    Some((_, ch)) => text.push(ch),
+                    None => return Err(format!("unterminated string at position {start}")),
+                }
+            }
+            tokens.push(Token::Str(text));
+        } else if c.is_ascii_digit() {
+            let mut literal = String::new();
+            while let Some((_, ch)) =
+                chars.next_if(|&(_, ch)| ch.is_ascii_alphanumeric() || ch == '.' || ch == '_')
+            {
+                literal.push(ch);
+            }
+            tokens.push(Token::Number(parse_number(&literal)?));
+        } else if c.is_alphabetic() || c == '_' {
+            let mut word = String::new();
+            while let Some((_, ch)) = chars.next_if(|&(_, ch)| {
+                ch.is_alphanumeric() || matches!(ch, '_' | '-' | '.' | ':' | '/')
+            }) {
+                word.push(ch);
+            }
+            tokens.push(Token::Word(word));
+        } else {
+            return Err(format!("unexpected '{c}' at position {start}"));
+        }
+    }
+    Ok(tokens)
+}
+
+/// Number with optional `_` separators and `k`/`m` suffix ("50k", "1_000")
+fn parse_number(literal: &str) -> Result<f64, String> {
+    let digits = literal.replace('_', "");
+    let (digits, scale) = match digits.chars().last() {
+        Some('k' | 'K') => (&digits[..digits.len() - 1], 1e3),
+        Some('m' | 'M') => (&digits[..digits.len() - 1], 1e6),
+        _ => (digits.as_str(), 1.0),
+    };
+    digits
+        .parse::<f64>()
+        .map(|n| n * scale)
+        .map_err(|_| format!("invalid number '{literal}'"))
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn keyword(&mut self, keyword: &str) -> bool {
+        let found =
+            matches!(self.peek(), Some(Token::Word(word)) if word.eq_ignore_ascii_case(keyword));
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    fn or_expr(&mut self) -> Result<Expr, String> {
+        let mut expr = self.and_expr()?;
+        while self.keyword("or") {
+            expr = Expr::Or(Box::new(expr), Box::new(self.and_expr()?));
+        }
+        Ok(expr)
+    }
+
+    fn and_expr(&mut self) -> Result<Expr, String> {
+        let mut expr = self.unary()?;
+        while self.keyword("and") {
+            expr = Expr::And(Box::new(expr), Box::new(self.unary()?));
+        }
+        Ok(expr)
+    }
+
+    fn unary(&mut self) -> Result<Expr, String> {
+        if self.keyword("not") {
+            return Ok(Expr::Not(Box::new(self.unary()?)));
+        }
+        if self.peek() == Some(&Token::LParen) {
+            self.pos += 1;
+            let expr = self.or_expr()?;
+            if self.next() != Some(Token::RParen) {
+                return Err("missing ')'".to_string());
+            }
+            return Ok(expr);
+        }
+        self.comparison()
+    }
+
+    fn comparison(&mut self) -> Result<Expr, String> {
+        let Some(Token::Word(name)) = self.next() else {
+            return Err(format!("expected a fact ({})", Fact::NAMES));
+        };
+        let fact = Fact::parse(&name)
+            .ok_or_else(|| format!("unknown fact '{name}' (expected {})", Fact::NAMES))?;
+        let Some(Token::Op(op)) = self.next() else {
+            return Err(format!("expected a comparison operator after '{name}'"));
+        };
+        let value = match self.next() {
+            Some(Token::Number(n)) if fact.is_numeric() => Value::Number(n),
+            Some(Token::Word(text) | Token::Str(text)) if !fact.is_numeric() => {
+                if !matches!(op, CmpOp::Eq | CmpOp::Ne) {
+                    return Err(format!("'{name}' only supports == and !="));
+                }
+                // Accept the CLI spelling ("json-schema")
+                Value::Text(if fact == Fact::ResponseFormat {
+                    text.replace('-', "_")
+                } else {
+                    text
+                })
+            }
+            Some(_) if fact.is_numeric() => return Err(format!("'{name}' expects a number")),
+            Some(_) => return Err(format!("'{name}' expects a string")),
+            None => return Err(format!("missing value after '{name}'")),
+        };
+        Ok(Expr::Compare { fact, op, value })
+    }
+}
+
+/// Parsed `when` expression (serialized as its source text)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Condition {
+    source: String,
+    expr: Expr,
+}
+
+impl Condition {
+    pub fn matches(&self, facts: &InputFacts) -> bool {
+        self.expr.eval(facts)
+    }
+}
+
+impl FromStr for Condition {
+    type Err = String;
+
+    fn from_str(source: &str) -> Result<Self, Self::Err> {
+        let mut parser = Parser {
+            tokens: tokenize(source)?,
+            pos: 0,
+        };
+        let expr = parser.or_expr()?;
+        if parser.pos < parser.tokens.len() {
+            return Err("unexpected trailing input (combine comparisons with and/or)".to_string());
+        }
+        Ok(Self {
+            source: source.to_string(),
+            expr,
+        })
+    }
+}
+
+impl TryFrom<String> for Condition {
+    type Error = String;
+
+    fn try_from(source: String) -> Result<Self, Self::Error> {
+        source
+            .parse()
+            .map_err(|e| format!("invalid condition '{source}': {e}"))
+    }
+}
+
+impl From<Condition> for String {
+    fn from(condition: Condition) -> Self {
+        condition.source
+    }
+}
+
+impl fmt::Display for Condition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.source)
+    }
+}
+
+/// Skip or add guardrail providers when a condition holds
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConditionalGuardrail {
+    pub when: Condition,
+
+    /// Stage the rule applies to (None = both)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stage: Option<GuardrailStage>,
+
+    /// Provider types removed from the stage, including composite members
+    /// (e.g. "llama_prompt_guard")
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub skip: Vec<String>,
+
+    /// Provider added to the stage (all must pass with the existing ones)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub add: Option<GuardrailProviderConfig>,
+}
+
+impl ConditionalGuardrail {
+    pub fn validate(&self) -> Result<(), CliError> {
+        let invalid = |message: String| {
+            CliError::InvalidArguments(format!("Guardrail condition '{}': {message}", self.when))
+        };
+        if self.skip.is_empty() && self.add.is_none() {
+            return Err(invalid("nothing to skip or add".to_string()));
+        }
+        if let Some(kind) = self
+            .skip
+            .iter()
+            .find(|kind| !GuardrailProviderConfig::PROVIDER_TYPES.contains(&kind.as_str()))
+        {
+            return Err(invalid(format!(
+                "unknown provider type '{kind}' in skip (expected one of: {})",
+                GuardrailProviderConfig::PROVIDER_TYPES.join(", ")
+            )));
+        }
+        if let Some(add) = &self.add {
+            add.validate()?;
+        }
+        Ok(())
+    }
+
+    fn applies(&self, stage: GuardrailStage, facts: &InputFacts) -> bool {
+        self.stage.is_none_or(|s| s == stage) && self.when.matches(facts)
+    }
+}
+
+/// Guardrails for `stage` after applying the matching rules in order
+pub fn apply_conditions(
+    base: Option<GuardrailProviderConfig>,
+    rules: &[ConditionalGuardrail],
+    stage: GuardrailStage,
+    facts: &InputFacts,
+) -> Option<GuardrailProviderConfig> {
+    let mut config = base;
+    for rule in rules.iter().filter(|rule| rule.applies(stage, facts)) {
+        log::info!(
+            "Guardrail condition '{}' matched at {stage} stage",
+            rule.when
+        );
+        for kind in &rule.skip {
+            config = config.and_then(|c| without(c, kind));
+        }
+        if let Some(add) = &rule.add {
+            config = Some(with(config, add.clone()));
+        }
+    }
+    config
+}
+
+/// `config` without providers of type `kind` (None when nothing is left)
+pub(crate) fn without(
+    config: GuardrailProviderConfig,
+    kind: &str,
+) -> Option<GuardrailProviderConfig> {
+    match config {
+        GuardrailProviderConfig::Composite {
+            providers,
+            execution,
+            aggregation,
+        } => {
+            let providers: Vec<_> = providers
+                .into_iter()
+                .filter_map(|p| without(p, kind))
+                .collect();
+            (!providers.is_empty()).then_some(GuardrailProviderConfig::Composite {
+                providers,
+                execution,
+                aggregation,
+            })
+        }
+        other if other.provider_type() == kind => None,
+        other => Some(other),
+    }
+}
+
+/// `config` with `add` as an additional all-must-pass provider
+fn with(
+    config: Option<GuardrailProviderConfig>,
+    add: GuardrailProviderConfig,
+) -> GuardrailProviderConfig {
+    match config {
+        None => add,
+        Some(GuardrailProviderConfig::Composite {
+            mut providers,
+            execution,
+            aggregation: AggregationMode::AllMustPass,
+        }) => {
+            providers.push(add);
+            GuardrailProviderConfig::Composite {
+                providers,
+                execution,
+                aggregation: AggregationMode::AllMustPass,
+            }
+        }
+        Some(other) => GuardrailProviderConfig::Composite {
+            providers: vec![other, add],
+            execution: ExecutionMode::default(),
+            aggregation: AggregationMode::AllMustPass,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::guardrails::{
+        config::RegexGuardrailConfig, numeric::NumericConsistencyConfig,
+        terminology::TerminologyConfig,
+    };
+
+    fn facts() -> InputFacts {
+        InputFacts {
+            input_tokens: 60_000,
+            input_bytes: 240_000,
+            untrusted_content: 0,
+            input_source: "pdf".to_string(),
+            response_format: "json_schema".to_string(),
+            model: "gpt-4o".to_string(),
+        }
+    }
+
+    fn eval(source: &str) -> bool {
+        source.parse::<Condition>().unwrap().matches(&facts())
+    }
+
+    #[test]
+    fn test_condition_evaluation() {
+        assert!(eval("input_tokens > 50k"));
+        assert!(!eval("input_tokens <= 50_000"));
+        assert!(eval(
+            "input_source == 'PDF' and response_format == json-schema"
+        ));
+        assert!(eval("model != \"gpt-4\" or input_bytes < 10"));
+        assert!(eval("not (untrusted_content > 0 or input_source == text)"));
+        assert!(!eval(
+            "input_source == text or input_tokens < 1k and model == gpt-4o"
+        ));
+    }
+
+    #[test]
+    fn test_condition_parse_errors() {
+        for (source, expected) in [
+            ("tokens > 5", "unknown fact 'tokens'"),
+            ("input_tokens > big", "expects a number"),
+            ("model > gpt", "only supports == and !="),
+            ("input_source == pdf model == x", "trailing input"),
+            ("(input_tokens > 1", "missing ')'"),
+            ("model == 'gpt", "unterminated string"),
+        ] {
+            let err = source.parse::<Condition>().unwrap_err();
+            assert!(err.contains(expected), "{source}: {err}");
+        }
+    }
+
+    #[test]
+    fn test_apply_skips_and_adds_providers() {
+        let terminology = GuardrailProviderConfig::Terminology(TerminologyConfig {
+            forbidden: vec!["risk-free".to_string()],
+            ..Default::default()
+        });
+        let base = GuardrailProviderConfig::Composite {
+            providers: vec![
+                GuardrailProviderConfig::Regex(RegexGuardrailConfig::default()),
+                terminology,
+            ],
+            execution: ExecutionMode::Sequential,
+            aggregation: AggregationMode::AllMustPass,
+        };
+        let rules: Vec<ConditionalGuardrail> = toml::from_str::<toml::Table>(
+            r#"
+            [[rules]]
+            when = "response_format == json_schema"
+            stage = "output"
+            skip = ["terminology"]
+
+            [[rules]]
+            when = "input_source == pdf"
+            add = { type = "numeric_consistency" }
+
+            [[rules]]
+            when = "input_tokens < 100"
+            skip = ["regex"]
+            "#,
+        )
+        .unwrap()["rules"]
+            .clone()
+            .try_into()
+            .unwrap();
+
+        let output = apply_conditions(Some(base.clone()), &rules, GuardrailStage::Output, &facts());
+        let Some(GuardrailProviderConfig::Composite { providers, .. }) = output else {
+            panic!("expected composite, got {output:?}");
+        };
+        let types: Vec<&str> = providers.iter().map(|p| p.provider_type()).collect();
+        assert_eq!(types, vec!["regex", "numeric_consistency"]);
+
+        // Stage-specific rules do not apply elsewhere; a single provider is wrapped
+        let input = apply_conditions(
+            Some(GuardrailProviderConfig::Regex(
+                RegexGuardrailConfig::default(),
+            )),
+            &rules,
+            GuardrailStage::Input,
+            &facts(),
+        );
+        assert!(matches!(
+            input,
+            Some(GuardrailProviderConfig::Composite { providers, aggregation: AggregationMode::AllMustPass, .. })
+                if providers.len() == 2
+        ));
+
+        // Skipping the only provider disables the stage; adding enables it
+        let small = InputFacts {
+            input_tokens: 10,
+            input_source: "text".to_string(),
+            ..facts()
+        };
+        let regex_only = Some(GuardrailProviderConfig::Regex(
+            RegexGuardrailConfig::default(),
+        ));
+        assert!(apply_conditions(regex_only, &rules, GuardrailStage::Input, &small).is_none());
+        assert!(matches!(
+            apply_conditions(None, &rules, GuardrailStage::Input, &facts()),
+            Some(GuardrailProviderConfig::NumericConsistency(
+                NumericConsistencyConfig { .. }
+            ))
+        ));
+    }
+
+    #[test]
+    fn test_validate() {
+        let rule = |skip: &[&str]| ConditionalGuardrail {
+            when: "input_tokens > 1".parse().unwrap(),
+            stage: None,
+            skip: skip.iter().map(|s| s.to_string()).collect(),
+            add: None,
+        };
+        assert!(rule(&["llama_prompt_guard"]).validate().is_ok());
+        assert!(rule(&[]).validate().is_err());
+        let err = rule(&["prompt_guard"]).validate().unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("unknown provider type 'prompt_guard'"));
+    }
+}
@@ -1,13 +1,28 @@
 use crate::{
     error::CliError,
     guardrails::{
+        budget::GuardMeter,
+        conditions::ConditionalGuardrail,
+        escalation::EscalationConfig,
         gpt_oss_safeguard::GptOssSafeguardConfig,
-        llama_guard::{LlamaGuardCategory, LlamaGuardConfig},
+        hash_list::HashListConfig,
+        image::ImageGuardrailConfig,
+        json_schema::JsonSchemaConfig,
+        language::LanguageConfig,
+        llama_guard::{LlamaGuardCategory, LlamaGuardConfig, LlamaGuardCustomCategory},
+        llama_prompt_guard,
+        numeric::NumericConsistencyConfig,
+        patterns::BuiltinPatterns,
+        presets::GuardrailPreset,
         provider::Severity,
+        semantic_similarity,
+        terminology::TerminologyConfig,
     },
+    secrets::SecretSource,
+    spotlight::PromptSource,
 };
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::{collections::HashMap, path::PathBuf};
 
 /// Regex guardrail configuration (unified for both input and output)
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -22,6 +37,31 @@ pub struct RegexGuardrailConfig {
     /// Minimum severity to report (violations below this become warnings)
     #[serde(default = "default_severity_threshold")]
     pub severity_threshold: Severity,
+
+    /// Built-in pattern sets checked in addition to `patterns_file`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub builtin_patterns: Vec<BuiltinPatterns>,
+
+    /// What to do with pattern matches in input (default: block)
+    #[serde(default)]
+    pub action: RegexAction,
+
+    /// Characters of context on each side of a match in violation previews
+    /// (None = no previews)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub preview_chars: Option<usize>,
+}
+
+/// Handling of regex pattern matches in user input
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RegexAction {
+    /// Reject the input
+    #[default]
+    Block,
+    /// Replace matches with placeholders (e.g. `[EMAIL]`) and send the
+    /// sanitized prompt; Critical-severity matches still block
+    Redact,
 }
 
 fn default_severity_threshold() -> Severity {
@@ -34,6 +74,9 @@ impl Default for RegexGuardrailConfig {
             max_length_bytes: 1048576, // 1MB
             patterns_file: None,
             severity_threshold: Severity::Medium,
+            builtin_patterns: Vec::new(),
+            action: RegexAction::Block,
+            preview_chars: None,
         }
     }
 }
@@ -48,22 +91,22 @@ fn default_prompt_guard_threshold() -> f32 {
     0.5
 }
 
-/// Helper function to resolve API key from either direct value or environment variable
-/// Returns the resolved API key value, or None if neither is specified
+/// Helper function to resolve API key from a direct value, an environment
+/// variable or a secret source
+/// Returns the resolved API key value, or None if none is specified
 fn resolve_api_key(
     api_key: &Option<String>,
     api_key_name: &Option<String>,
+    api_key_secret: &Option<SecretSource>,
     provider_name: &str,
 ) -> Result<Option<String>, CliError> {
-    match (api_key, api_key_name) {
-        (Some(_), Some(_)) => Err(CliError::InvalidArguments(format!(
-            "Guardrail provider '{provider_name}' cannot specify both 'api_key' and 'api_key_name'"
-        ))),
-        (Some(key), None) => {
+    validate_key_source(provider_name, api_key, api_key_name, api_key_secret)?;
+    match (api_key, api_key_name, api_key_secret) {
+        (Some(key), _, _) => {
             log::debug!("Guardrail provider '{provider_name}': Using direct API key value");
             Ok(Some(key.clone()))
         }
-        (None, Some(env_var_name)) => {
+        (None, Some(env_var_name), _) => {
             log::debug!(
                 "Guardrail provider '{provider_name}': Loading API key from environment variable '{env_var_name}'"
             );
@@ -74,7 +117,16 @@ fn resolve_api_key(
                 ))),
             }
         }
-        (None, None) => {
+        (None, None, Some(source)) => {
+            log::debug!("Guardrail provider '{provider_name}': Loading API key from {source}");
+            source.resolve().map(Some).map_err(|e| match e {
+                CliError::InvalidArguments(reason) => CliError::InvalidArguments(format!(
+                    "Guardrail provider '{provider_name}': {reason}"
+                )),
+                e => e,
+            })
+        }
+        (None, None, None) => {
             log::debug!(
                 "Guardrail provider '{provider_name}': No API key configured (using unauthenticated endpoint)"
             );
@@ -83,6 +135,20 @@ fn resolve_api_key(
     }
 }
 
+/// Key already read by `resolve_secrets()`, else [`resolve_api_key`]
+fn resolved_key(
+    api_key: &Option<String>,
+    api_key_name: &Option<String>,
+    api_key_secret: &Option<SecretSource>,
+    resolved_api_key: &Option<String>,
+    provider_name: &str,
+) -> Result<Option<String>, CliError> {
+    match resolved_api_key {
+        Some(key) => Ok(Some(key.clone())),
+        None => resolve_api_key(api_key, api_key_name, api_key_secret, provider_name),
+    }
+}
+
 /// Execution mode for composite guardrails
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
 #[serde(rename_all = "snake_case")]
@@ -96,7 +162,7 @@ pub enum ExecutionMode {
 }
 
 /// Aggregation mode for composite guardrails
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
 #[serde(rename_all = "snake_case")]
 pub enum AggregationMode {
     /// All providers must say "safe" for overall "safe" (conservative)
@@ -105,6 +171,54 @@ pub enum AggregationMode {
 
     /// Any provider can say "safe" for overall "safe" (permissive)
     AnyCanPass,
+
+    /// More than half of the providers must say "safe" (ties fail)
+    MajorityVote,
+
+    /// The weighted share of providers saying "safe" must reach `min_score`
+    ///
+    /// Weights are keyed by provider `type` (e.g. `llama_guard`); providers
+    /// without a weight count 1.0. The score is the passing weight divided by
+    /// the total weight of the providers that ran (0.0-1.0).
+    Weighted {
+        min_score: f32,
+        #[serde(default)]
+        weights: HashMap<String, f32>,
+    },
+}
+
+impl AggregationMode {
+    /// Whether the verdict is a vote over all providers (no short-circuit)
+    pub fn is_vote(&self) -> bool {
+        matches!(self, Self::MajorityVote | Self::Weighted { .. })
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        let Self::Weighted { min_score, weights } = self else {
+            return Ok(());
+        };
+        if !(*min_score > 0.0 && *min_score <= 1.0) {
+            return Err(format!(
+                "weighted min_score must be in (0.0, 1.0], got {min_score}"
+            ));
+        }
+        for (kind, weight) in weights {
+            if kind != "composite"
+                && !GuardrailProviderConfig::PROVIDER_TYPES.contains(&kind.as_str())
+            {
+                return Err(format!(
+                    "weighted weights: unknown provider type '{kind}' (expected one of: {}, composite)",
+                    GuardrailProviderConfig::PROVIDER_TYPES.join(", ")
+                ));
+            }
+            if !weight.is_finite() || *weight < 0.0 {
+                return Err(format!(
+                    "weighted weights: weight for '{kind}' must be a non-negative number, got {weight}"
+                ));
+            }
+        }
+        Ok(())
+    }
 }
 
 /// Top-level guardrail configuration
@@ -124,6 +238,104 @@ pub struct GuardrailConfig {
     #[serde(flatten)]
     #[serde(skip_serializing_if = "Option::is_none")]
     pub provider: Option<GuardrailProviderConfig>,
+
+    /// Curated preset used when neither explicit nor flattened providers are set
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub preset: Option<GuardrailPreset>,
+
+    /// Rules skipping or adding providers depending on the input
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub conditional: Vec<ConditionalGuardrail>,
+
+    /// Validate each prompt source on its own instead of the merged prompt
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub input_sources: Option<InputSourcesConfig>,
+
+    /// Stricter input guardrail consulted when the input guardrails warn
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub escalation: Option<EscalationConfig>,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// Per-source input validation (`[guardrails.input_sources]`)
+///
+/// Input guardrails run once per prompt source (user prompt, PDF, page or
+/// image text, each context file and untrusted content entry, prefix/suffix
+/// wrappers), and violations name the source they were found in. Disabled
+/// sources are not validated.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InputSourcesConfig {
+    /// User prompt text or file (default: true)
+    #[serde(default = "default_true")]
+    pub user_prompt: bool,
+    /// Text extracted from the PDF (default: true)
+    #[serde(default = "default_true")]
+    pub pdf: bool,
+    /// Page fetched from `user_prompt_url` (default: true)
+    #[serde(default = "default_true")]
+    pub url: bool,
+    /// Text OCR'd from `image_file` (default: true)
+    #[serde(default = "default_true")]
+    pub image: bool,
+    /// Each `context_files` entry (default: true)
+    #[serde(default = "default_true")]
+    pub context_files: bool,
+    /// Each `untrusted_content` entry, e.g. retrieved chunks (default: true)
+    #[serde(default = "default_true")]
+    pub untrusted_content: bool,
+    /// `user_prompt_prefix` and `user_prompt_suffix` (default: true)
+    #[serde(default = "default_true")]
+    pub wrappers: bool,
+}
+
+impl Default for InputSourcesConfig {
+    fn default() -> Self {
+        Self {
+            user_prompt: true,
+            pdf: true,
+            url: true,
+            image: true,
+            context_files: true,
+            untrusted_content: true,
+            wrappers: true,
+        }
+    }
+}
+
+impl InputSourcesConfig {
+    /// Whether input guardrails validate `source`
+    pub fn validates(&self, source: &PromptSource) -> bool {
+        match source {
+            PromptSource::UserPrompt => self.user_prompt,
+            PromptSource::Pdf => self.pdf,
+            PromptSource::Url => self.url,
+            PromptSource::Image => self.image,
+            PromptSource::ContextFile(_) => self.context_files,
+            PromptSource::UntrustedContent(_) => self.untrusted_content,
+            PromptSource::Wrapper => self.wrappers,
+        }
+    }
+}
+
+impl GuardrailConfig {
+    /// Effective input guardrails (explicit input > flattened provider > preset)
+    pub fn input_config(&self) -> Option<GuardrailProviderConfig> {
+        self.input
+            .clone()
+            .or_else(|| self.provider.clone())
+            .or_else(|| self.preset.and_then(GuardrailPreset::input_config))
+    }
+
+    /// Effective output guardrails (explicit output > flattened provider > preset)
+    pub fn output_config(&self) -> Option<GuardrailProviderConfig> {
+        self.output
+            .clone()
+            .or_else(|| self.provider.clone())
+            .or_else(|| self.preset.and_then(GuardrailPreset::output_config))
+    }
 }
 
 /// Unified provider-specific configuration (works for both input and output)
@@ -140,10 +352,17 @@ pub enum GuardrailProviderConfig {
         timeout_secs: u64,
         #[serde(default = "default_llama_guard_categories")]
         enabled_categories: Vec<LlamaGuardCategory>,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        custom_categories: Vec<LlamaGuardCustomCategory>,
         #[serde(skip_serializing_if = "Option::is_none")]
         api_key: Option<String>,
         #[serde(skip_serializing_if = "Option::is_none")]
         api_key_name: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        api_key_secret: Option<SecretSource>,
+        /// Key read from `api_key_secret` by `resolve_secrets()` (never serialized)
+        #[serde(skip)]
+        resolved_api_key: Option<String>,
     },
 
     /// GPT-OSS-Safeguard (policy-driven reasoning model)
@@ -156,6 +375,11 @@ pub enum GuardrailProviderConfig {
         api_key: Option<String>,
         #[serde(skip_serializing_if = "Option::is_none")]
         api_key_name: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        api_key_secret: Option<SecretSource>,
+        /// Key read from `api_key_secret` by `resolve_secrets()` (never serialized)
+        #[serde(skip)]
+        resolved_api_key: Option<String>,
     },
 
     /// Llama Prompt Guard 2 (prompt injection detection, input-only)
@@ -170,8 +394,81 @@ pub enum GuardrailProviderConfig {
         api_key: Option<String>,
         #[serde(skip_serializing_if = "Option::is_none")]
         api_key_name: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        api_key_secret: Option<SecretSource>,
+        /// Key read from `api_key_secret` by `resolve_secrets()` (never serialized)
+        #[serde(skip)]
+        resolved_api_key: Option<String>,
+        /// Leading bytes of the input that are scanned
+        #[serde(default = "llama_prompt_guard::default_max_scan_bytes")]
+        max_scan_bytes: usize,
+        /// Bytes sent per classifier request
+        #[serde(default = "llama_prompt_guard::default_window_bytes")]
+        window_bytes: usize,
+        /// Bytes repeated at the start of the next window
+        #[serde(default = "llama_prompt_guard::default_window_overlap_bytes")]
+        window_overlap_bytes: usize,
+    },
+
+    /// NVIDIA NeMo Guardrails server (rails defined by a server-side config)
+    NemoGuardrails {
+        api_url: String,
+        config_id: String,
+        timeout_secs: u64,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        api_key: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        api_key_name: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        api_key_secret: Option<SecretSource>,
+        /// Key read from `api_key_secret` by `resolve_secrets()` (never serialized)
+        #[serde(skip)]
+        resolved_api_key: Option<String>,
+    },
+
+    /// SHA-256 allowlist/denylist of known prompts
+    HashList(HashListConfig),
+
+    /// Glossary check: required, forbidden and preferred terms
+    Terminology(TerminologyConfig),
+
+    /// Numbers and dates in the response must appear in the source (output-only)
+    NumericConsistency(NumericConsistencyConfig),
+
+    /// Response chunks must be semantically similar to the source (output-only)
+    SemanticSimilarity {
+        /// Embeddings endpoint (OpenAI-compatible or Ollama `/api/embed`)
+        api_url: String,
+        model: String,
+        timeout_secs: u64,
+        #[serde(default = "semantic_similarity::default_min_similarity")]
+        min_similarity: f32,
+        #[serde(default = "semantic_similarity::default_chunk_chars")]
+        chunk_chars: usize,
+        #[serde(default = "semantic_similarity::default_min_chunk_chars")]
+        min_chunk_chars: usize,
+        #[serde(default = "semantic_similarity::default_severity_threshold")]
+        severity_threshold: Severity,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        api_key: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        api_key_name: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        api_key_secret: Option<SecretSource>,
+        /// Key read from `api_key_secret` by `resolve_secrets()` (never serialized)
+        #[serde(skip)]
+        resolved_api_key: Option<String>,
     },
 
+    /// Response language must match the target, requested or prompt language
+    Language(LanguageConfig),
+
+    /// Response must be JSON conforming to a JSON Schema
+    JsonSchema(JsonSchemaConfig),
+
+    /// Generated images: formats, size, dimensions and scanner (output-only)
+    Image(ImageGuardrailConfig),
+
     /// Composite guardrail (combines multiple providers)
     Composite {
         providers: Vec<GuardrailProviderConfig>,
@@ -188,6 +485,281 @@ impl Default for GuardrailProviderConfig {
 }
 
 impl GuardrailProviderConfig {
+    /// Values of the `type` field, excluding `composite`
+    pub const PROVIDER_TYPES: &'static [&'static str] = &[
+        "regex",
+        "llama_guard",
+        "gpt_oss_safeguard",
+        "llama_prompt_guard",
+        "nemo_guardrails",
+        "hash_list",
+        "terminology",
+        "numeric_consistency",
+        "semantic_similarity",
+        "language",
+        "json_schema",
+        "image",
+    ];
+
+    /// Value of the `type` field
+    pub fn provider_type(&self) -> &'static str {
+        match self {
+            Self::Regex(_) => "regex",
+            Self::LlamaGuard { .. } => "llama_guard",
+            Self::GptOssSafeguard { .. } => "gpt_oss_safeguard",
+            Self::LlamaPromptGuard { .. } => "llama_prompt_guard",
+            Self::NemoGuardrails { .. } => "nemo_guardrails",
+            Self::HashList(_) => "hash_list",
+            Self::Terminology(_) => "terminology",
+            Self::NumericConsistency(_) => "numeric_consistency",
+            Self::SemanticSimilarity { .. } => "semantic_similarity",
+            Self::Language(_) => "language",
+            Self::JsonSchema(_) => "json_schema",
+            Self::Image(_) => "image",
+            Self::Composite { .. } => "composite",
+        }
+    }
+
+    /// URLs this provider sends content to (composite members included)
+    pub fn endpoints(&self) -> Vec<&str> {
+        match self {
+            Self::LlamaGuard { api_url, .. }
+            | Self::GptOssSafeguard { api_url, .. }
+            | Self::LlamaPromptGuard { api_url, .. }
+            | Self::NemoGuardrails { api_url, .. }
+            | Self::SemanticSimilarity { api_url, .. } => vec![api_url.as_str()],
+            Self::Image(config) => config.scanner.iter().map(|s| s.url.as_str()).collect(),
+            Self::Composite { providers, .. } => {
+                providers.iter().flat_map(|p| p.endpoints()).collect()
+            }
+            Self::Regex(_)
+            | Self::HashList(_)
+            | Self::Terminology(_)
+            | Self::NumericConsistency(_)
+            | Self::Language(_)
+            | Self::JsonSchema(_) => Vec::new(),
+        }
+    }
+
+    /// Check the configuration without contacting any guardrail endpoint
+    ///
+    /// Catches empty URLs/models/policies, out-of-range thresholds, missing
+    /// pattern files, conflicting API key settings and empty composites
+    /// (recursively). Environment variables named by `api_key_name` are only
+    /// resolved when the provider is created.
+    pub fn validate(&self) -> Result<(), CliError> {
+        match self {
+            Self::Regex(config) => {
+                if config.max_length_bytes == 0 {
+                    return Err(invalid("Regex", "max_length_bytes must be positive"));
+                }
+                if let Some(path) = &config.patterns_file {
+                    if !path.is_file() {
+                        return Err(invalid(
+                            "Regex",
+                            &format!("patterns file '{}' does not exist", path.display()),
+                        ));
+                    }
+                }
+                Ok(())
+            }
+            Self::LlamaGuard {
+                api_url,
+                model,
+                timeout_secs,
+                enabled_categories,
+                custom_categories,
+                api_key,
+                api_key_name,
+                api_key_secret,
+                ..
+            } => {
+                validate_endpoint("LlamaGuard", api_url, model, *timeout_secs)?;
+                validate_key_source("LlamaGuard", api_key, api_key_name, api_key_secret)?;
+                if enabled_categories.is_empty() && custom_categories.is_empty() {
+                    return Err(invalid("LlamaGuard", "no categories enabled"));
+                }
+                LlamaGuardCustomCategory::validate_all(custom_categories)
+            }
+            Self::GptOssSafeguard {
+                api_url,
+                model,
+                policy,
+                timeout_secs,
+                api_key,
+                api_key_name,
+                api_key_secret,
+                ..
+            } => {
+                validate_endpoint("GptOssSafeguard", api_url, model, *timeout_secs)?;
+                validate_key_source("GptOssSafeguard", api_key, api_key_name, api_key_secret)?;
+                if policy.trim().is_empty() {
+                    return Err(invalid("GptOssSafeguard", "policy must not be empty"));
+                }
+                Ok(())
+            }
+            Self::LlamaPromptGuard {
+                api_url,
+                model,
+                timeout_secs,
+                threshold,
+                api_key,
+                api_key_name,
+                api_key_secret,
+                max_scan_bytes,
+                window_bytes,
+                window_overlap_bytes,
+                ..
+            } => {
+                validate_endpoint("LlamaPromptGuard", api_url, model, *timeout_secs)?;
+                validate_key_source("LlamaPromptGuard", api_key, api_key_name, api_key_secret)?;
+                if !(0.0..=1.0).contains(threshold) {
+                    return Err(invalid(
+                        "LlamaPromptGuard",
+                        &format!("threshold {threshold} must be between 0.0 and 1.0"),
+                    ));
+                }
+                if *max_scan_bytes == 0 || *window_bytes == 0 {
+                    return Err(invalid(
+                        "LlamaPromptGuard",
+                        "max_scan_bytes and window_bytes must be >= 1",
+                    ));
+                }
+                if window_overlap_bytes >= window_bytes {
+                    return Err(invalid(
+                        "LlamaPromptGuard",
+                        &format!(
+                            "window_overlap_bytes ({window_overlap_bytes}) must be lower than \
+                             window_bytes ({window_bytes})"
+                        ),
+                    ));
+                }
+                Ok(())
+            }
+            Self::NemoGuardrails {
+                api_url,
+                config_id,
+                timeout_secs,
+                api_key,
+                api_key_name,
+                api_key_secret,
+                ..
+            } => {
+                if config_id.trim().is_empty() {
+                    return Err(invalid("NemoGuardrails", "config_id must not be empty"));
+                }
+                validate_endpoint("NemoGuardrails", api_url, config_id, *timeout_secs)?;
+                validate_key_source("NemoGuardrails", api_key, api_key_name, api_key_secret)
+            }
+            Self::HashList(config) => config
+                .validate()
+                .map_err(|reason| invalid("HashList", &reason)),
+            Self::Terminology(config) => config
+                .validate()
+                .map_err(|reason| invalid("Terminology", &reason)),
+            Self::NumericConsistency(config) => config
+                .validate()
+                .map_err(|reason| invalid("NumericConsistency", &reason)),
+            Self::SemanticSimilarity {
+                api_url,
+                model,
+                timeout_secs,
+                min_similarity,
+                chunk_chars,
+                min_chunk_chars,
+                api_key,
+                api_key_name,
+                api_key_secret,
+                ..
+            } => {
+                validate_endpoint("SemanticSimilarity", api_url, model, *timeout_secs)?;
+                validate_key_source("SemanticSimilarity", api_key, api_key_name, api_key_secret)?;
+                if !(0.0..=1.0).contains(min_similarity) {
+                    return Err(invalid(
+                        "SemanticSimilarity",
+                        &format!("min_similarity {min_similarity} must be between 0.0 and 1.0"),
+                    ));
+                }
+                if *chunk_chars == 0 {
+                    return Err(invalid("SemanticSimilarity", "chunk_chars must be >= 1"));
+                }
+                if min_chunk_chars > chunk_chars {
+                    return Err(invalid(
+                        "SemanticSimilarity",
+                        &format!(
+                            "min_chunk_chars ({min_chunk_chars}) must not exceed chunk_chars \
+                             ({chunk_chars})"
+                        ),
+                    ));
+                }
+                Ok(())
+            }
+            Self::Language(config) => config
+                .validate()
+                .map_err(|reason| invalid("Language", &reason)),
+            Self::JsonSchema(config) => config
+                .validate()
+                .map_err(|reason| invalid("JsonSchema", &reason)),
+            Self::Image(config) => config
+                .validate()
+                .map_err(|reason| invalid("Image", &reason)),
+            Self::Composite {
+                providers,
+                aggregation,
+                ..
+            } => {
+                if providers.is_empty() {
+                    return Err(invalid("Composite", "at least one provider is required"));
+                }
+                aggregation
+                    .validate()
+                    .map_err(|reason| invalid("Composite", &reason))?;
+                providers.iter().try_for_each(Self::validate)
+            }
+        }
+    }
+
+    /// Read every `api_key_secret` (composite members included) into
+    /// `resolved_api_key`, so providers created per evaluation don't fetch it
+    /// again. The source stays in place: serialized configs never hold the key.
+    pub fn resolve_secrets(&mut self) -> Result<(), CliError> {
+        let (name, api_key_secret, resolved_api_key) = match self {
+            Self::LlamaGuard {
+                api_key_secret,
+                resolved_api_key,
+                ..
+            } => ("LlamaGuard", api_key_secret, resolved_api_key),
+            Self::GptOssSafeguard {
+                api_key_secret,
+                resolved_api_key,
+                ..
+            } => ("GptOssSafeguard", api_key_secret, resolved_api_key),
+            Self::LlamaPromptGuard {
+                api_key_secret,
+                resolved_api_key,
+                ..
+            } => ("LlamaPromptGuard", api_key_secret, resolved_api_key),
+            Self::NemoGuardrails {
+                api_key_secret,
+                resolved_api_key,
+                ..
+            } => ("NemoGuardrails", api_key_secret, resolved_api_key),
+            Self::SemanticSimilarity {
+                api_key_secret,
+                resolved_api_key,
+                ..
+            } => ("SemanticSimilarity", api_key_secret, resolved_api_key),
+            Self::Composite { providers, .. } => {
+                return providers.iter_mut().try_for_each(Self::resolve_secrets);
+            }
+            _ => return Ok(()),
+        };
+        if api_key_secret.is_some() {
+            *resolved_api_key = resolve_api_key(&None, &None, api_key_secret, name)?;
+        }
+        Ok(())
+    }
+
     /// Get the RegexGuardrailConfig (if this is a Regex variant)
     pub fn as_regex_config(&self) -> Option<&RegexGuardrailConfig> {
         match self {
@@ -196,6 +768,35 @@ impl GuardrailProviderConfig {
         }
     }
 
+    /// Response size limit this configuration always enforces, if any
+    ///
+    /// An `all_must_pass` composite enforces its smallest regex limit; with
+    /// `any_can_pass` or a vote a limit only holds when every provider has one
+    /// (the largest applies). Streaming uses this to abort oversized responses.
+    pub fn max_length_bytes(&self) -> Option<usize> {
+        match self {
+            Self::Regex(config) => Some(config.max_length_bytes),
+            Self::Composite {
+                providers,
+                aggregation,
+                ..
+            } => {
+                let limits = providers.iter().map(Self::max_length_bytes);
+                match aggregation {
+                    AggregationMode::AllMustPass => limits.flatten().min(),
+                    // Every provider fails oversized content: no vote passes it
+                    // (weighted min_score is positive)
+                    AggregationMode::AnyCanPass
+                    | AggregationMode::MajorityVote
+                    | AggregationMode::Weighted { .. } => limits
+                        .collect::<Option<Vec<_>>>()
+                        .and_then(|limits| limits.into_iter().max()),
+                }
+            }
+            _ => None,
+        }
+    }
+
     /// Convert to LlamaGuardConfig (if this is a LlamaGuard config)
     pub fn to_llama_guard_config(&self) -> Option<LlamaGuardConfig> {
         match self {
@@ -204,12 +805,14 @@ impl GuardrailProviderConfig {
                 model,
                 timeout_secs,
                 enabled_categories,
+                custom_categories,
                 api_key,
                 ..
             } => Some(LlamaGuardConfig {
                 api_url: api_url.clone(),
                 model: model.clone(),
                 enabled_categories: enabled_categories.clone(),
+                custom_categories: custom_categories.clone(),
                 timeout_secs: *timeout_secs,
                 api_key: api_key.clone(),
             }),
@@ -239,13 +842,104 @@ impl GuardrailProviderConfig {
     }
 }
 
+fn invalid(provider_name: &str, message: &str) -> CliError {
+    CliError::InvalidArguments(format!("Guardrail provider '{provider_name}': {message}"))
+}
+
+fn validate_endpoint(
+    provider_name: &str,
+    api_url: &str,
+    model: &str,
+    timeout_secs: u64,
+) -> Result<(), CliError> {
+    if !(api_url.starts_with("http://") || api_url.starts_with("https://")) {
+        return Err(invalid(
+            provider_name,
+            &format!("api_url '{api_url}' must be an http(s) URL"),
+        ));
+    }
+    if model.trim().is_empty() {
+        return Err(invalid(provider_name, "model must not be empty"));
+    }
+    if timeout_secs == 0 {
+        return Err(invalid(provider_name, "timeout_secs must be positive"));
+    }
+    Ok(())
+}
+
+fn validate_key_source(
+    provider_name: &str,
+    api_key: &Option<String>,
+    api_key_name: &Option<String>,
+    api_key_secret: &Option<SecretSource>,
+) -> Result<(), CliError> {
+    let sources: Vec<&str> = [
+        ("api_key", api_key.is_some()),
+        ("api_key_name", api_key_name.is_some()),
+        ("api_key_secret", api_key_secret.is_some()),
+    ]
+    .into_iter()
+    .filter_map(|(field, set)| set.then_some(field))
+    .collect();
+    if let [first, second, ..] = sources[..] {
+        return Err(invalid(
+            provider_name,
+            &format!("cannot specify both '{first}' and '{second}'"),
+        ));
+    }
+    if let Some(source) = api_key_secret {
+        source
+            .validate()
+            .map_err(|reason| invalid(provider_name, &reason))?;
+    }
+    Ok(())
+}
+
 /// Factory function to create GuardrailProvider from configuration
 pub fn create_guardrail_provider(
     config: &GuardrailProviderConfig,
+) -> Result<Box<dyn crate::guardrails::provider::GuardrailProvider>, crate::error::CliError> {
+    build_provider(config, None)
+}
+
+/// Create a GuardrailProvider whose model-backed providers (including
+/// composite members) record their token usage in `meter` and respect its
+/// budget
+pub fn create_metered_guardrail_provider(
+    config: &GuardrailProviderConfig,
+    meter: &GuardMeter,
+) -> Result<Box<dyn crate::guardrails::provider::GuardrailProvider>, crate::error::CliError> {
+    build_provider(config, Some(meter))
+}
+
+fn build_provider(
+    config: &GuardrailProviderConfig,
+    meter: Option<&GuardMeter>,
+) -> Result<Box<dyn crate::guardrails::provider::GuardrailProvider>, crate::error::CliError> {
+    let provider = new_provider(config, meter)?;
+    Ok(match meter {
+        Some(meter) => meter.wrap(config, provider),
+        None => provider,
+    })
+}
+
+fn new_provider(
+    config: &GuardrailProviderConfig,
+    meter: Option<&GuardMeter>,
 ) -> Result<Box<dyn crate::guardrails::provider::GuardrailProvider>, crate::error::CliError> {
     use crate::guardrails::{
-        gpt_oss_safeguard::GptOssSafeguardProvider, hybrid::HybridGuardrail,
-        llama_guard::LlamaGuardProvider, regex::RegexGuardrail,
+        gpt_oss_safeguard::GptOssSafeguardProvider,
+        hash_list::HashListGuardrail,
+        hybrid::HybridGuardrail,
+        image::ImageGuardrail,
+        json_schema::JsonSchemaGuardrail,
+        language::LanguageGuardrail,
+        llama_guard::LlamaGuardProvider,
+        nemo_guardrails::{NemoGuardrailsConfig, NemoGuardrailsProvider},
+        numeric::NumericConsistencyGuardrail,
+        regex::RegexGuardrail,
+        semantic_similarity::{SemanticSimilarityConfig, SemanticSimilarityGuardrail},
+        terminology::TerminologyGuardrail,
     };
 
     match config {
@@ -258,14 +952,25 @@ pub fn create_guardrail_provider(
             model,
             timeout_secs,
             enabled_categories,
+            custom_categories,
             api_key,
             api_key_name,
+            api_key_secret,
+            resolved_api_key,
         } => {
-            let resolved_api_key = resolve_api_key(api_key, api_key_name, "LlamaGuard")?;
+            LlamaGuardCustomCategory::validate_all(custom_categories)?;
+            let resolved_api_key = resolved_key(
+                api_key,
+                api_key_name,
+                api_key_secret,
+                resolved_api_key,
+                "LlamaGuard",
+            )?;
             let llama_config = LlamaGuardConfig {
                 api_url: api_url.clone(),
                 model: model.clone(),
                 enabled_categories: enabled_categories.clone(),
+                custom_categories: custom_categories.clone(),
                 timeout_secs: *timeout_secs,
                 api_key: resolved_api_key,
             };
@@ -279,8 +984,16 @@ pub fn create_guardrail_provider(
             timeout_secs,
             api_key,
             api_key_name,
+            api_key_secret,
+            resolved_api_key,
         } => {
-            let resolved_api_key = resolve_api_key(api_key, api_key_name, "GptOssSafeguard")?;
+            let resolved_api_key = resolved_key(
+                api_key,
+                api_key_name,
+                api_key_secret,
+                resolved_api_key,
+                "GptOssSafeguard",
+            )?;
             let gpt_oss_config = GptOssSafeguardConfig {
                 api_url: api_url.clone(),
                 model: model.clone(),
@@ -298,8 +1011,19 @@ pub fn create_guardrail_provider(
             threshold,
             api_key,
             api_key_name,
+            api_key_secret,
+            resolved_api_key,
+            max_scan_bytes,
+            window_bytes,
+            window_overlap_bytes,
         } => {
-            let resolved_api_key = resolve_api_key(api_key, api_key_name, "LlamaPromptGuard")?;
+            let resolved_api_key = resolved_key(
+                api_key,
+                api_key_name,
+                api_key_secret,
+                resolved_api_key,
+                "LlamaPromptGuard",
+            )?;
             let prompt_guard_config =
                 crate::guardrails::llama_prompt_guard::LlamaPromptGuardConfig {
                     api_url: api_url.clone(),
@@ -308,6 +1032,9 @@ pub fn create_guardrail_provider(
                     threshold: *threshold,
                     api_key: resolved_api_key,
                     api_key_name: None, // Already resolved to api_key
+                    max_scan_bytes: *max_scan_bytes,
+                    window_bytes: *window_bytes,
+                    window_overlap_bytes: *window_overlap_bytes,
                 };
             Ok(Box::new(
                 crate::guardrails::llama_prompt_guard::LlamaPromptGuardProvider::new(
@@ -316,20 +1043,118 @@ pub fn create_guardrail_provider(
             ))
         }
 
+        GuardrailProviderConfig::NemoGuardrails {
+            api_url,
+            config_id,
+            timeout_secs,
+            api_key,
+            api_key_name,
+            api_key_secret,
+            resolved_api_key,
+        } => {
+            let resolved_api_key = resolved_key(
+                api_key,
+                api_key_name,
+                api_key_secret,
+                resolved_api_key,
+                "NemoGuardrails",
+            )?;
+            Ok(Box::new(NemoGuardrailsProvider::new(
+                NemoGuardrailsConfig {
+                    api_url: api_url.clone(),
+                    config_id: config_id.clone(),
+                    timeout_secs: *timeout_secs,
+                    api_key: resolved_api_key,
+                },
+            )))
+        }
+
+        GuardrailProviderConfig::HashList(hash_list_config) => {
+            Ok(Box::new(HashListGuardrail::new(hash_list_config)?))
+        }
+
+        GuardrailProviderConfig::Terminology(terminology_config) => Ok(Box::new(
+            TerminologyGuardrail::new(terminology_config.clone()),
+        )),
+
+        GuardrailProviderConfig::NumericConsistency(numeric_config) => Ok(Box::new(
+            NumericConsistencyGuardrail::new(numeric_config.clone()),
+        )),
+
+        GuardrailProviderConfig::SemanticSimilarity {
+            api_url,
+            model,
+            timeout_secs,
+            min_similarity,
+            chunk_chars,
+            min_chunk_chars,
+            severity_threshold,
+            api_key,
+            api_key_name,
+            api_key_secret,
+            resolved_api_key,
+        } => {
+            let resolved_api_key = resolved_key(
+                api_key,
+                api_key_name,
+                api_key_secret,
+                resolved_api_key,
+                "SemanticSimilarity",
+            )?;
+            Ok(Box::new(SemanticSimilarityGuardrail::new(
+                SemanticSimilarityConfig {
+                    api_url: api_url.clone(),
+                    model: model.clone(),
+                    timeout_secs: *timeout_secs,
+                    api_key: resolved_api_key,
+                    min_similarity: *min_similarity,
+                    chunk_chars: *chunk_chars,
+                    min_chunk_chars: *min_chunk_chars,
+                    severity_threshold: *severity_threshold,
+                },
+            )))
+        }
+
+        GuardrailProviderConfig::Language(language_config) => {
+            Ok(Box::new(LanguageGuardrail::new(language_config.clone())))
+        }
+
+        GuardrailProviderConfig::JsonSchema(json_schema_config) => {
+            Ok(Box::new(JsonSchemaGuardrail::new(json_schema_config)?))
+        }
+
+        GuardrailProviderConfig::Image(image_config) => {
+            let api_key_name = image_config
+                .scanner
+                .as_ref()
+                .and_then(|scanner| scanner.api_key_name.clone());
+            let resolved_api_key = resolve_api_key(&None, &api_key_name, &None, "Image")?;
+            Ok(Box::new(ImageGuardrail::new(
+                image_config.clone(),
+                resolved_api_key,
+            )))
+        }
+
         GuardrailProviderConfig::Composite {
             providers,
             execution,
             aggregation,
         } => {
             // Recursively create all providers
-            let provider_instances: Result<Vec<_>, _> =
-                providers.iter().map(create_guardrail_provider).collect();
+            let provider_instances: Result<Vec<_>, _> = providers
+                .iter()
+                .map(|provider| build_provider(provider, meter))
+                .collect();
 
-            Ok(Box::new(HybridGuardrail::new(
-                provider_instances?,
-                *execution,
-                *aggregation,
-            )))
+            Ok(Box::new(
+                HybridGuardrail::new(provider_instances?, *execution, aggregation.clone())
+                    .with_provider_types(
+                        providers
+                            .iter()
+                            .map(|p| p.provider_type().to_string())
+                            .collect(),
+                    ),
+            ))
         }
     }
 }
@@ -366,6 +1191,9 @@ mod tests {
             max_length_bytes: 1024,
             patterns_file: None,
             severity_threshold: Severity::High,
+            builtin_patterns: Vec::new(),
+            action: RegexAction::Block,
+            preview_chars: None,
         });
 
         let regex_config = config.as_regex_config().unwrap();
@@ -380,8 +1208,11 @@ mod tests {
             model: "test-model".to_string(),
             timeout_secs: 60,
             enabled_categories: vec![LlamaGuardCategory::S1, LlamaGuardCategory::S9],
+            custom_categories: Vec::new(),
             api_key: None,
             api_key_name: None,
+            api_key_secret: None,
+            resolved_api_key: None,
         };
 
         let llama_config = config.to_llama_guard_config().unwrap();
@@ -398,6 +1229,9 @@ mod tests {
             max_length_bytes: 1024,
             patterns_file: None,
             severity_threshold: Severity::High,
+            builtin_patterns: Vec::new(),
+            action: RegexAction::Block,
+            preview_chars: None,
         });
 
         let json = serde_json::to_string(&config).unwrap();
@@ -420,8 +1254,11 @@ mod tests {
             model: "llama-guard3:8b".to_string(),
             timeout_secs: 30,
             enabled_categories: vec![LlamaGuardCategory::S1],
+            custom_categories: Vec::new(),
             api_key: None,
             api_key_name: None,
+            api_key_secret: None,
+            resolved_api_key: None,
         };
 
         let json = serde_json::to_string(&config).unwrap();
@@ -437,14 +1274,20 @@ mod tests {
                     max_length_bytes: 1024,
                     patterns_file: None,
                     severity_threshold: Severity::Medium,
+                    builtin_patterns: Vec::new(),
+                    action: RegexAction::Block,
+                    preview_chars: None,
                 }),
                 GuardrailProviderConfig::LlamaGuard {
                     api_url: "http://localhost:11434".to_string(),
                     model: "llama-guard3:8b".to_string(),
                     timeout_secs: 30,
                     enabled_categories: vec![LlamaGuardCategory::S1],
+                    custom_categories: Vec::new(),
                     api_key: None,
                     api_key_name: None,
+                    api_key_secret: None,
+                    resolved_api_key: None,
                 },
             ],
             execution: ExecutionMode::Parallel,
@@ -457,9 +1300,77 @@ mod tests {
         assert!(json.contains("\"aggregation\":\"all_must_pass\""));
     }
 
+    #[test]
+    fn test_weighted_aggregation_config() {
+        let config: GuardrailProviderConfig = toml::from_str(
+            r#"
+            type = "composite"
+            execution = "parallel"
+            aggregation = { weighted = { min_score = 0.6, weights = { llama_guard = 2.0 } } }
+
+            [[providers]]
+            type = "regex"
+            max_length_bytes = 1024
+
+            [[providers]]
+            type = "llama_guard"
+            api_url = "http://localhost:11434"
+            model = "llama-guard3:8b"
+            timeout_secs = 30
+            "#,
+        )
+        .unwrap();
+        let GuardrailProviderConfig::Composite { aggregation, .. } = &config else {
+            panic!("Expected composite config");
+        };
+        assert_eq!(
+            aggregation,
+            &AggregationMode::Weighted {
+                min_score: 0.6,
+                weights: [("llama_guard".to_string(), 2.0)].into(),
+            }
+        );
+        assert!(config.validate().is_ok());
+
+        let with_aggregation = |aggregation| GuardrailProviderConfig::Composite {
+            providers: vec![GuardrailProviderConfig::default()],
+            execution: ExecutionMode::Parallel,
+            aggregation,
+        };
+        assert!(with_aggregation(AggregationMode::MajorityVote)
+            .validate()
+            .is_ok());
+        for (aggregation, reason) in [
+            (
+                AggregationMode::Weighted {
+                    min_score: 0.0,
+                    weights: HashMap::new(),
+                },
+                "min_score must be in (0.0, 1.0]",
+            ),
+            (
+                AggregationMode::Weighted {
+                    min_score: 0.5,
+                    weights: [("llama".to_string(), 1.0)].into(),
+                },
+                "unknown provider type 'llama'",
+            ),
+            (
+                AggregationMode::Weighted {
+                    min_score: 0.5,
+                    weights: [("regex".to_string(), -1.0)].into(),
+                },
+                "must be a non-negative number",
+            ),
+        ] {
+            let err = with_aggregation(aggregation).validate().unwrap_err();
+            assert!(err.to_string().contains(reason), "{err}");
+        }
+    }
+
     #[test]
     fn test_resolve_api_key_direct_value() {
-        let result = resolve_api_key(&Some("test-key".to_string()), &None, "TestProvider");
+        let result = resolve_api_key(&Some("test-key".to_string()), &None, &None, "TestProvider");
         assert!(result.is_ok());
         assert_eq!(result.unwrap(), Some("test-key".to_string()));
     }
@@ -470,6 +1381,7 @@ mod tests {
         let result = resolve_api_key(
             &None,
             &Some("TEST_GUARDRAIL_KEY".to_string()),
+            &None,
             "TestProvider",
         );
         assert!(result.is_ok());
@@ -479,7 +1391,7 @@ mod tests {
 
     #[test]
     fn test_resolve_api_key_none() {
-        let result = resolve_api_key(&None, &None, "TestProvider");
+        let result = resolve_api_key(&None, &None, &None, "TestProvider");
         assert!(result.is_ok());
         assert_eq!(result.unwrap(), None);
     }
@@ -489,6 +1401,7 @@ mod tests {
         let result = resolve_api_key(
             &Some("test-key".to_string()),
             &Some("TEST_ENV_VAR".to_string()),
+            &None,
             "TestProvider",
         );
         assert!(result.is_err());
@@ -503,9 +1416,98 @@ mod tests {
         let result = resolve_api_key(
             &None,
             &Some("NONEXISTENT_GUARDRAIL_KEY".to_string()),
+            &None,
             "TestProvider",
         );
         assert!(result.is_err());
         assert!(result.unwrap_err().to_string().contains("does not exist"));
     }
+
+    #[test]
+    fn test_resolve_secrets_reads_composite_member_keys_once() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let key_file = dir.path().join("key");
+        std::fs::write(&key_file, "guard-key\n").unwrap();
+        let mut config = GuardrailProviderConfig::Composite {
+            providers: vec![GuardrailProviderConfig::NemoGuardrails {
+                api_url: "http://localhost:8000/v1/chat/completions".to_string(),
+                config_id: "default".to_string(),
+                timeout_secs: 5,
+                api_key: None,
+                api_key_name: None,
+                api_key_secret: Some(SecretSource::File(key_file.clone())),
+                resolved_api_key: None,
+            }],
+            execution: ExecutionMode::Sequential,
+            aggregation: AggregationMode::AllMustPass,
+        };
+
+        config.resolve_secrets().unwrap();
+        let GuardrailProviderConfig::Composite { providers, .. } = &config else {
+            unreachable!()
+        };
+        assert!(matches!(
+            &providers[0],
+            GuardrailProviderConfig::NemoGuardrails {
+                api_key: None,
+                api_key_secret: Some(_),
+                resolved_api_key: Some(key),
+                ..
+            } if key == "guard-key"
+        ));
+        // The resolved key is never serialized
+        assert!(!serde_json::to_string(&config)
+            .unwrap()
+            .contains("guard-key"));
+        // Providers no longer read the secret
+        std::fs::remove_file(&key_file).unwrap();
+        assert!(create_guardrail_provider(&config).is_ok());
+    }
+
+    #[test]
+    fn test_max_length_bytes_composite() {
+        let regex = |max_length_bytes| {
+            GuardrailProviderConfig::Regex(RegexGuardrailConfig {
+                max_length_bytes,
+                ..Default::default()
+            })
+        };
+        let llama_guard = GuardrailProviderConfig::LlamaGuard {
+            api_url: "http://localhost:11434/api/generate".to_string(),
+            model: "llama-guard3:8b".to_string(),
+            timeout_secs: 60,
+            enabled_categories: LlamaGuardCategory::all(),
+            custom_categories: Vec::new(),
+            api_key: None,
+            api_key_name: None,
+            api_key_secret: None,
+            resolved_api_key: None,
+        };
+        let composite = |aggregation, providers| GuardrailProviderConfig::Composite {
+            providers,
+            execution: ExecutionMode::Sequential,
+            aggregation,
+        };
+
+        assert_eq!(regex(100).max_length_bytes(), Some(100));
+        assert_eq!(llama_guard.max_length_bytes(), None);
+        assert_eq!(
+            composite(
+                AggregationMode::AllMustPass,
+                vec![regex(100), llama_guard.clone(), regex(50)]
+            )
+            .max_length_bytes(),
+            Some(50)
+        );
+        assert_eq!(
+            composite(AggregationMode::AnyCanPass, vec![regex(100), regex(50)]).max_length_bytes(),
+            Some(100)
+        );
+        // Llama Guard may pass an oversized response on its own
+        assert_eq!(
+            composite(AggregationMode::AnyCanPass, vec![regex(100), llama_guard])
+                .max_length_bytes(),
+            None
+        );
+    }
 }
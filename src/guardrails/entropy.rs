@@ -0,0 +1,142 @@
+//! Shannon-entropy scan for generic secrets
+//!
+//! Used by the `secrets` built-in pattern set to catch credentials without a
+//! recognizable prefix. Content is split into tokens of base64/hex-like
+//! characters (`A-Z a-z 0-9 + / = _ -`); a token is reported when it is long
+//! enough, mixes letters and digits, and its entropy (bits per character)
+//! reaches the threshold of its alphabet:
+//!
+//! | Alphabet | Min length | Min entropy |
+//! |----------|------------|-------------|
+//! | hex      | 32         | 3.0         |
+//! | other    | 20         | 4.0         |
+//!
+//! Random keys land well above these thresholds, while words, identifiers
+//! and UUIDs stay below. Hex digests (e.g. git commit hashes) do match; lower
+//! the finding to a warning with `severity_threshold = "critical"` when that
+//! is expected content.
+
+/// Minimum length of a base64-like token
+pub const MIN_LENGTH: usize = 20;
+/// Minimum length of a hex token
+pub const MIN_HEX_LENGTH: usize = 32;
+/// Entropy threshold of base64-like tokens (bits per character)
+pub const MIN_ENTROPY: f64 = 4.0;
+/// Entropy threshold of hex tokens (bits per character)
+pub const MIN_HEX_ENTROPY: f64 = 3.0;
+
+/// A token that looks like a random secret
+#[derive(Debug, Clone, PartialEq)]
+pub struct HighEntropyToken {
+    /// Byte offset in the scanned content
+    pub start: usize,
+    pub end: usize,
+    /// Shannon entropy in bits per character
+    pub entropy: f64,
+}
+
+/// Shannon entropy of `text` in bits per character
+pub fn shannon_entropy(text: &str) -> f64 {
+    let mut counts = [0usize; 256];
+    let mut total = 0usize;
+    for byte in text.bytes() {
+        counts[byte as usize] += 1;
+        total += 1;
+    }
+    if total == 0 {
+        return 0.0;
+    }
+    counts
+        .iter()
+        .filter(|&&count| count > 0)
+        .map(|&count| {
+            let p = count as f64 / total as f64;
+            -p * p.log2()
+        })
+        .sum()
+}
+
+/// Tokens of `content` that look like random secrets, in order
+pub fn high_entropy_tokens(content: &str) -> Vec<HighEntropyToken> {
+    let is_token_byte =
+        |b: u8| b.is_ascii_alphanumeric() || matches!(b, b'+' | b'/' | b'=' | b'_' | b'-');
+    let bytes = content.as_bytes();
+    let mut tokens = Vec::new();
+    let mut start = 0;
+    while start < bytes.len() {
+        if !is_token_byte(bytes[start]) {
+            start += 1;
+            continue;
+        }
+        let mut end = start;
+        while end < bytes.len() && is_token_byte(bytes[end]) {
+            end += 1;
+        }
+        // Token bytes are ASCII, so both ends are char boundaries
+        let token = &content[start..end];
+        if let Some(entropy) = secret_entropy(token) {
+            tokens.push(HighEntropyToken {
+                start,
+                end,
+                entropy,
+            });
+        }
+        start = end;
+    }
+    tokens
+}
+
+/// Entropy of `token` when it passes the thresholds of its alphabet
+fn secret_entropy(token: &str) -> Option<f64> {
+    let has_digit = token.bytes().any(|b| b.is_ascii_digit());
+    let has_letter = token.bytes().any(|b| b.is_ascii_alphabetic());
+    if !has_digit || !has_letter {
+        return None;
+    }
+    let (min_length, min_entropy) = if token.bytes().all(|b| b.is_ascii_hexdigit()) {
+        (MIN_HEX_LENGTH, MIN_HEX_ENTROPY)
+    } else {
+        (MIN_LENGTH, MIN_ENTROPY)
+    };
+    if token.len() < min_length {
+        return None;
+    }
+    let entropy = shannon_entropy(token);
+    (entropy >= min_entropy).then_some(entropy)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shannon_entropy() {
+        assert_eq!(shannon_entropy(""), 0.0);
+        assert_eq!(shannon_entropy("aaaa"), 0.0);
+        assert!((shannon_entropy("abcd") - 2.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_high_entropy_tokens() {
+        let content = "key: wJalrXUtnFEMI/K7MDENG/bPxRfiCYzEXAMPLEKEY, \
+                       commit 3f786850e387550fdab836ed7e6dc881de23001b";
+        let tokens = high_entropy_tokens(content);
+        assert_eq!(tokens.len(), 2);
+        assert_eq!(
+            &content[tokens[0].start..tokens[0].end],
+            "wJalrXUtnFEMI/K7MDENG/bPxRfiCYzEXAMPLEKEY"
+        );
+        assert!(tokens[0].entropy >= MIN_ENTROPY);
+
+        // Words, identifiers, UUIDs and short or repetitive tokens pass
+        for clean in [
+            "internationalization_and_localization_settings",
+            "validate_request_payload_v2",
+            "550e8400-e29b-41d4-a716-446655440000",
+            "abc123",
+            "a1a1a1a1a1a1a1a1a1a1a1a1a1a1a1a1a1a1",
+        ] {
+            assert!(high_entropy_tokens(clean).is_empty(), "{clean}");
+        }
+    }
+}
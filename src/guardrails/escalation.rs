@@ -0,0 +1,125 @@
+//! Escalation of suspicious inputs to a stricter guardrail
+//!
+//! Fast input guardrails (regex, presets) keep latency low but only warn on
+//! borderline findings. An escalation policy re-runs such inputs through a
+//! stricter, usually LLM-based, guardrail before deciding:
+//!
+//! ```toml
+//! [guardrails.input]
+//! type = "regex"
+//! max_length_bytes = 1048576
+//! severity_threshold = "high"       # Medium findings only warn
+//!
+//! [guardrails.escalation]
+//! min_severity = "medium"           # Warnings that escalate (default: medium)
+//!
+//! [guardrails.escalation.provider]
+//! type = "llama_guard"
+//! api_url = "http://localhost:11434/api/generate"
+//! model = "llama-guard3:8b"
+//! timeout_secs = 30
+//! ```
+//!
+//! Inputs that pass without warnings at or above `min_severity` (and inputs
+//! that are blocked outright) never reach the escalation provider. When it
+//! runs, its verdict decides, and both verdicts are recorded in
+//! `metadata.escalation`.
+
+use super::{GuardrailProviderConfig, NamedProviderResult, Severity, Violation};
+use serde::{Deserialize, Serialize};
+
+fn default_min_severity() -> Severity {
+    Severity::Medium
+}
+
+/// Escalation policy (`[guardrails.escalation]`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EscalationConfig {
+    /// Stricter guardrail consulted on suspicious inputs
+    pub provider: GuardrailProviderConfig,
+
+    /// Lowest warning severity that escalates (default: medium)
+    #[serde(default = "default_min_severity")]
+    pub min_severity: Severity,
+}
+
+impl EscalationConfig {
+    pub fn new(provider: GuardrailProviderConfig) -> Self {
+        Self {
+            provider,
+            min_severity: default_min_severity(),
+        }
+    }
+
+    pub fn min_severity(mut self, severity: Severity) -> Self {
+        self.min_severity = severity;
+        self
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        self.provider
+            .validate()
+            .map_err(|e| format!("guardrails.escalation.provider: {e}"))
+    }
+
+    /// Rule IDs of the warnings that call for escalation (empty = none)
+    pub fn triggers(&self, warnings: &[Violation]) -> Vec<String> {
+        warnings
+            .iter()
+            .filter(|w| w.severity >= self.min_severity)
+            .map(Violation::id)
+            .collect()
+    }
+}
+
+/// Both verdicts of an escalated input (`metadata.escalation`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EscalationRecord {
+    /// Warning rules of the fast guardrails that triggered the escalation
+    pub triggered_by: Vec<String>,
+    /// Verdict of the fast input guardrails
+    pub initial: NamedProviderResult,
+    /// Verdict of the escalation provider, which decided the outcome
+    pub escalated: NamedProviderResult,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn warning(rule: &str, severity: Severity) -> Violation {
+        Violation {
+            namespace: "regex".to_string(),
+            rule: rule.to_string(),
+            severity,
+            message: "test".to_string(),
+            location: None,
+            source: None,
+            preview: None,
+        }
+    }
+
+    #[test]
+    fn test_triggers_respect_min_severity() {
+        let config: EscalationConfig = toml::from_str(
+            r#"
+[provider]
+type = "llama_guard"
+api_url = "http://localhost:11434/api/generate"
+model = "llama-guard3:8b"
+timeout_secs = 30
+"#,
+        )
+        .unwrap();
+        assert_eq!(config.min_severity, Severity::Medium);
+        config.validate().unwrap();
+
+        let warnings = [
+            warning("LOW_RULE", Severity::Low),
+            warning("MEDIUM_RULE", Severity::Medium),
+        ];
+        assert_eq!(config.triggers(&warnings), vec!["regex.MEDIUM_RULE"]);
+        let config = config.min_severity(Severity::High);
+        assert!(config.triggers(&warnings).is_empty());
+    }
+}
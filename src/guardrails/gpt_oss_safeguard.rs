@@ -1,8 +1,11 @@
 use crate::{
     client::LlmClient,
     error::CliError,
-    guardrails::provider::{
-        GuardrailProvider, GuardrailResult, ProviderSpecificResult, Severity, Violation,
+    guardrails::{
+        catalog::namespace,
+        provider::{
+            GuardrailProvider, GuardrailResult, ProviderSpecificResult, Severity, Violation,
+        },
     },
     provider::InvokeParams,
 };
@@ -78,6 +81,7 @@ impl GptOssSafeguardProvider {
 
         let violations = if !passed {
             vec![Violation {
+                namespace: namespace::GPT_OSS_SAFEGUARD.to_string(),
                 rule: json
                     .category
                     .clone()
@@ -88,6 +92,8 @@ impl GptOssSafeguardProvider {
                     .clone()
                     .unwrap_or_else(|| "Policy violation detected".to_string()),
                 location: None,
+                source: None,
+                preview: None,
             }]
         } else {
             vec![]
@@ -107,6 +113,8 @@ impl GptOssSafeguardProvider {
             warnings: vec![],
             quality_score: None, // GPT-OSS-Safeguard is binary (no confidence scores)
             provider_specific: Some(ProviderSpecificResult::GptOssSafeguard(gpt_oss_result)),
+            provider_results: Vec::new(),
+            explanation: None,
         })
     }
 }
@@ -122,11 +130,14 @@ impl GuardrailProvider for GptOssSafeguardProvider {
                 system_prompt: &self.config.policy, // Policy in system prompt
                 user_prompt: content,               // Content to evaluate in user prompt
                 temperature: 0.0, // Temperature 0 for deterministic classification
+                top_p: None,      // Provider default
                 max_tokens: Some(300), // Longer than Llama Guard (JSON output needs more tokens)
                 seed: None,       // No seed needed for guardrails
                 api_key: self.config.api_key.as_deref(),
                 timeout_secs: self.config.timeout_secs,
                 response_format: None, // No response_format needed for guardrails
+                tools: &[],
+                images: &[],
             })
             .await?;
 
@@ -2,16 +2,42 @@ use crate::{
     error::CliError,
     guardrails::{
         config::{AggregationMode, ExecutionMode},
-        provider::{GuardrailProvider, GuardrailResult},
+        provider::{
+            merge_redactions, GuardrailProvider, GuardrailResult, NamedProviderResult,
+            OutputContext, ProviderSpecificResult, Redaction, Violation,
+        },
     },
+    telemetry::{guardrail_span, record_guardrail},
 };
 use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::time::Instant;
+use tracing::Instrument;
 
 /// Composite guardrail combining multiple providers
 pub struct HybridGuardrail {
     providers: Vec<Box<dyn GuardrailProvider>>,
     execution: ExecutionMode,
     aggregation: AggregationMode,
+    /// Keys for weighted aggregation and provider results, one per provider
+    provider_types: Vec<String>,
+}
+
+/// Outcome of a `majority_vote` or `weighted` aggregation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VoteResult {
+    /// Passing weight divided by the total weight (0.0-1.0)
+    pub score: f32,
+    /// One vote per provider that returned a result, in provider order
+    pub votes: Vec<ProviderVote>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProviderVote {
+    /// Provider type (e.g. `llama_guard`), or provider name when unknown
+    pub provider: String,
+    pub weight: f32,
+    pub passed: bool,
 }
 
 impl HybridGuardrail {
@@ -20,15 +46,31 @@ impl HybridGuardrail {
         execution: ExecutionMode,
         aggregation: AggregationMode,
     ) -> Self {
+        let provider_types = providers.iter().map(|p| p.name().to_string()).collect();
         Self {
             providers,
             execution,
             aggregation,
+            provider_types,
         }
     }
 
+    /// Provider types used to look up weights and to name provider results
+    /// (default: provider names)
+    pub fn with_provider_types(mut self, provider_types: Vec<String>) -> Self {
+        if provider_types.len() == self.providers.len() {
+            self.provider_types = provider_types;
+        }
+        self
+    }
+
     /// Aggregate results from multiple providers based on aggregation mode
     fn aggregate_results(&self, results: Vec<GuardrailResult>) -> GuardrailResult {
+        self.aggregate_indexed(results.into_iter().enumerate().collect())
+    }
+
+    /// Aggregate results tagged with the index of the provider that produced them
+    fn aggregate_indexed(&self, results: Vec<(usize, GuardrailResult)>) -> GuardrailResult {
         if results.is_empty() {
             return GuardrailResult {
                 passed: true,
@@ -36,34 +78,56 @@ impl HybridGuardrail {
                 warnings: vec![],
                 quality_score: None,
                 provider_specific: None,
+                provider_results: Vec::new(),
+                explanation: None,
             };
         }
 
-        let passed = match self.aggregation {
-            AggregationMode::AllMustPass => {
-                // All must say "safe" for overall "safe" (conservative)
-                results.iter().all(|r| r.passed)
-            }
-            AggregationMode::AnyCanPass => {
-                // Any can say "safe" for overall "safe" (permissive)
-                results.iter().any(|r| r.passed)
-            }
-        };
+        let vote = self.vote(&results);
+        // Hash-allowlisted content passes whatever the other providers say
+        let preapproved = results.iter().position(|(_, r)| r.is_preapproved());
+        let passed = preapproved.is_some()
+            || match &self.aggregation {
+                AggregationMode::AllMustPass => {
+                    // All must say "safe" for overall "safe" (conservative)
+                    results.iter().all(|(_, r)| r.passed)
+                }
+                AggregationMode::AnyCanPass => {
+                    // Any can say "safe" for overall "safe" (permissive)
+                    results.iter().any(|(_, r)| r.passed)
+                }
+                AggregationMode::MajorityVote => {
+                    let passing = results.iter().filter(|(_, r)| r.passed).count();
+                    passing * 2 > results.len()
+                }
+                AggregationMode::Weighted { min_score, .. } => {
+                    vote.as_ref().is_some_and(|v| v.score >= *min_score)
+                }
+            };
 
-        // Merge all violations and warnings
+        // Merge all violations, warnings and member results
         let mut violations = vec![];
         let mut warnings = vec![];
+        let mut provider_results = vec![];
 
-        for result in &results {
+        for (_, result) in &results {
             violations.extend(result.violations.clone());
             warnings.extend(result.warnings.clone());
+            provider_results.extend(result.provider_results.clone());
         }
 
         // Use first available quality_score
-        let quality_score = results.iter().find_map(|r| r.quality_score);
-
-        // Use first available provider_specific data
-        let provider_specific = results.iter().find_map(|r| r.provider_specific.clone());
+        let quality_score = results.iter().find_map(|(_, r)| r.quality_score);
+        let explanation = self.explain(&results, passed, preapproved, vote.as_ref());
+
+        // Votes replace provider data (each vote records its provider's verdict);
+        // otherwise use first available provider_specific data
+        let provider_specific = match vote {
+            Some(vote) => Some(ProviderSpecificResult::Vote(vote)),
+            None => results
+                .iter()
+                .find_map(|(_, r)| r.provider_specific.clone()),
+        };
 
         GuardrailResult {
             passed,
@@ -71,30 +135,189 @@ impl HybridGuardrail {
             warnings,
             quality_score,
             provider_specific,
+            provider_results,
+            explanation: Some(explanation),
+        }
+    }
+
+    /// Type of the provider at `idx` ("provider" for results without a member)
+    fn provider_type(&self, idx: usize) -> &str {
+        self.provider_types
+            .get(idx)
+            .map_or("provider", String::as_str)
+    }
+
+    /// How the verdict was reached, e.g. "2/3 providers passed; blocked by
+    /// llama_guard.S9 under all_must_pass"
+    fn explain(
+        &self,
+        results: &[(usize, GuardrailResult)],
+        passed: bool,
+        preapproved: Option<usize>,
+        vote: Option<&VoteResult>,
+    ) -> String {
+        let passing = results.iter().filter(|(_, r)| r.passed).count();
+        let mut explanation = format!("{passing}/{} providers passed", results.len());
+        let not_run = self.providers.len().saturating_sub(results.len());
+        if not_run > 0 {
+            explanation.push_str(&format!(" ({not_run} not run or failed)"));
+        }
+
+        let mode = match &self.aggregation {
+            AggregationMode::AllMustPass => "all_must_pass",
+            AggregationMode::AnyCanPass => "any_can_pass",
+            AggregationMode::MajorityVote => "majority_vote",
+            AggregationMode::Weighted { .. } => "weighted",
+        };
+        if let Some(position) = preapproved {
+            let provider = self.provider_type(results[position].0);
+            explanation.push_str(&format!("; allowlisted by {provider}, overriding {mode}"));
+            return explanation;
+        }
+        if let (AggregationMode::Weighted { min_score, .. }, Some(vote)) = (&self.aggregation, vote)
+        {
+            let comparison = if passed { ">=" } else { "<" };
+            explanation.push_str(&format!(
+                "; score {:.2} {comparison} min_score {min_score:.2}",
+                vote.score
+            ));
+        }
+
+        // Name the failing members: they blocked the content, or were
+        // overruled (by the passing ones under any_can_pass)
+        let deciding: Vec<String> = results
+            .iter()
+            .filter(|(_, r)| !r.passed)
+            .flat_map(|(idx, r)| {
+                let rules: Vec<String> = r.violations.iter().map(Violation::id).collect();
+                if rules.is_empty() {
+                    vec![self.provider_type(*idx).to_string()]
+                } else {
+                    rules
+                }
+            })
+            .collect();
+        if !passed {
+            explanation.push_str(&format!("; blocked by {}", deciding.join(", ")));
+        } else if self.aggregation == AggregationMode::AnyCanPass && passing < results.len() {
+            let allowing: Vec<&str> = results
+                .iter()
+                .filter(|(_, r)| r.passed)
+                .map(|(idx, _)| self.provider_type(*idx))
+                .collect();
+            explanation.push_str(&format!("; allowed by {}", allowing.join(", ")));
+        } else if passing < results.len() {
+            explanation.push_str(&format!("; overruled {}", deciding.join(", ")));
         }
+        explanation.push_str(&format!(" under {mode}"));
+        explanation
+    }
+
+    /// Per-provider votes and score (vote aggregation modes only)
+    fn vote(&self, results: &[(usize, GuardrailResult)]) -> Option<VoteResult> {
+        let weights = match &self.aggregation {
+            AggregationMode::Weighted { weights, .. } => Some(weights),
+            AggregationMode::MajorityVote => None,
+            _ => return None,
+        };
+        let votes: Vec<ProviderVote> = results
+            .iter()
+            .map(|(idx, result)| {
+                let provider = self.provider_types[*idx].clone();
+                let weight = weights
+                    .and_then(|w| w.get(&provider).copied())
+                    .unwrap_or(1.0);
+                ProviderVote {
+                    provider,
+                    weight,
+                    passed: result.passed,
+                }
+            })
+            .collect();
+        let total: f32 = votes.iter().map(|v| v.weight).sum();
+        let passing: f32 = votes.iter().filter(|v| v.passed).map(|v| v.weight).sum();
+        Some(VoteResult {
+            score: if total > 0.0 { passing / total } else { 0.0 },
+            votes,
+        })
     }
 
     /// Validate content using the configured execution and aggregation strategy
-    async fn validate_with_strategy(&self, content: &str) -> Result<GuardrailResult, CliError> {
+    ///
+    /// With an output context, providers are called through `validate_output`.
+    async fn validate_with_strategy(
+        &self,
+        content: &str,
+        context: Option<&OutputContext>,
+    ) -> Result<GuardrailResult, CliError> {
         match self.execution {
-            ExecutionMode::Sequential => self.validate_sequential(content).await,
-            ExecutionMode::Parallel => self.validate_parallel(content).await,
+            ExecutionMode::Sequential => self.validate_sequential(content, context).await,
+            ExecutionMode::Parallel => self.validate_parallel(content, context).await,
         }
     }
 
-    /// Sequential execution (can short-circuit based on aggregation mode)
-    async fn validate_sequential(&self, content: &str) -> Result<GuardrailResult, CliError> {
+    /// Run one provider, forwarding the output context if any
+    ///
+    /// The result lists this provider in `provider_results` (a nested
+    /// composite keeps its own members instead).
+    async fn validate_one(
+        &self,
+        idx: usize,
+        content: &str,
+        context: Option<&OutputContext>,
+    ) -> Result<GuardrailResult, CliError> {
+        let provider = self.providers[idx].as_ref();
+        let direction = if context.is_some() { "output" } else { "input" };
+        let span = guardrail_span(provider.name(), direction);
+        let start = Instant::now();
+        let result = match context {
+            Some(context) => {
+                provider
+                    .validate_output(content, context)
+                    .instrument(span.clone())
+                    .await
+            }
+            None => provider.validate(content).instrument(span.clone()).await,
+        };
+        record_guardrail(&span, &result);
+        let mut result = result?;
+        if result.provider_results.is_empty() {
+            result.provider_results.push(NamedProviderResult {
+                provider: self.provider_types[idx].clone(),
+                direction: direction.to_string(),
+                passed: result.passed,
+                violation_rules: result.violations.iter().map(Violation::id).collect(),
+                warning_rules: result.warnings.iter().map(Violation::id).collect(),
+                latency_ms: start.elapsed().as_millis() as u64,
+                raw_response: result
+                    .provider_specific
+                    .as_ref()
+                    .and_then(|p| p.raw_response())
+                    .map(str::to_string),
+            });
+        }
+        Ok(result)
+    }
+
+    /// Sequential execution (can short-circuit based on aggregation mode;
+    /// votes always run every provider; allowlisted content stops at once)
+    async fn validate_sequential(
+        &self,
+        content: &str,
+        context: Option<&OutputContext>,
+    ) -> Result<GuardrailResult, CliError> {
         let mut results = Vec::new();
 
-        for provider in &self.providers {
-            let result = provider.validate(content).await?;
+        for idx in 0..self.providers.len() {
+            let result = self.validate_one(idx, content, context).await?;
 
             let can_short_circuit = match self.aggregation {
                 // AllMustPass: short-circuit on first failure
                 AggregationMode::AllMustPass => !result.passed,
                 // AnyCanPass: short-circuit on first success
                 AggregationMode::AnyCanPass => result.passed,
-            };
+                AggregationMode::MajorityVote | AggregationMode::Weighted { .. } => false,
+            } || result.is_preapproved();
 
             results.push(result);
 
@@ -112,27 +335,29 @@ impl HybridGuardrail {
     }
 
     /// Parallel execution (all providers run simultaneously)
-    async fn validate_parallel(&self, content: &str) -> Result<GuardrailResult, CliError> {
+    async fn validate_parallel(
+        &self,
+        content: &str,
+        context: Option<&OutputContext>,
+    ) -> Result<GuardrailResult, CliError> {
         // Handle empty providers gracefully
         if self.providers.is_empty() {
             return Ok(self.aggregate_results(vec![]));
         }
 
         // Execute all providers in parallel
-        let futures: Vec<_> = self
-            .providers
-            .iter()
-            .map(|provider| provider.validate(content))
+        let futures: Vec<_> = (0..self.providers.len())
+            .map(|idx| self.validate_one(idx, content, context))
             .collect();
 
         // Wait for all to complete
         let results = futures::future::join_all(futures).await;
 
-        // Collect successes, log failures
+        // Collect successes, log failures (failed providers don't vote)
         let mut successes = Vec::new();
         for (idx, result) in results.into_iter().enumerate() {
             match result {
-                Ok(r) => successes.push(r),
+                Ok(r) => successes.push((idx, r)),
                 Err(e) => {
                     log::warn!(
                         "Provider {} failed during parallel execution: {}",
@@ -149,14 +374,35 @@ impl HybridGuardrail {
             ));
         }
 
-        Ok(self.aggregate_results(successes))
+        Ok(self.aggregate_indexed(successes))
     }
 }
 
 #[async_trait]
 impl GuardrailProvider for HybridGuardrail {
     async fn validate(&self, content: &str) -> Result<GuardrailResult, CliError> {
-        self.validate_with_strategy(content).await
+        self.validate_with_strategy(content, None).await
+    }
+
+    async fn validate_output(
+        &self,
+        response: &str,
+        context: &OutputContext,
+    ) -> Result<GuardrailResult, CliError> {
+        self.validate_with_strategy(response, Some(context)).await
+    }
+
+    /// Each provider redacts the previous provider's output
+    fn redact(&self, content: &str) -> Option<(String, Vec<Redaction>)> {
+        let mut text = None;
+        let mut redactions = Vec::new();
+        for provider in &self.providers {
+            if let Some((redacted, made)) = provider.redact(text.as_deref().unwrap_or(content)) {
+                text = Some(redacted);
+                merge_redactions(&mut redactions, made);
+            }
+        }
+        text.map(|text| (text, redactions))
     }
 
     fn name(&self) -> &str {
@@ -168,7 +414,7 @@ impl GuardrailProvider for HybridGuardrail {
 mod tests {
     use super::*;
     use crate::guardrails::{
-        config::RegexGuardrailConfig,
+        config::{RegexAction, RegexGuardrailConfig},
         provider::{Severity, Violation},
         RegexGuardrail,
     };
@@ -198,6 +444,9 @@ mod tests {
                 max_length_bytes: 10,
                 patterns_file: None,
                 severity_threshold: Severity::Medium,
+                builtin_patterns: Vec::new(),
+                action: RegexAction::Block,
+                preview_chars: None,
             })),
             Box::new(RegexGuardrail::new(RegexGuardrailConfig::default())),
         ];
@@ -242,11 +491,17 @@ mod tests {
                 max_length_bytes: 10,
                 patterns_file: None,
                 severity_threshold: Severity::Medium,
+                builtin_patterns: Vec::new(),
+                action: RegexAction::Block,
+                preview_chars: None,
             })),
             Box::new(RegexGuardrail::new(RegexGuardrailConfig {
                 max_length_bytes: 10,
                 patterns_file: None,
                 severity_threshold: Severity::Medium,
+                builtin_patterns: Vec::new(),
+                action: RegexAction::Block,
+                preview_chars: None,
             })),
         ];
 
@@ -285,6 +540,8 @@ mod tests {
                 warnings: vec![],
                 quality_score: None,
                 provider_specific: None,
+                provider_results: Vec::new(),
+                explanation: None,
             },
             GuardrailResult {
                 passed: true,
@@ -292,6 +549,8 @@ mod tests {
                 warnings: vec![],
                 quality_score: None,
                 provider_specific: None,
+                provider_results: Vec::new(),
+                explanation: None,
             },
         ];
 
@@ -318,18 +577,25 @@ mod tests {
                 warnings: vec![],
                 quality_score: None,
                 provider_specific: None,
+                provider_results: Vec::new(),
+                explanation: None,
             },
             GuardrailResult {
                 passed: false,
                 violations: vec![Violation {
+                    namespace: String::new(),
                     rule: "TEST".to_string(),
                     severity: Severity::Critical,
                     message: "Test violation".to_string(),
                     location: None,
+                    source: None,
+                    preview: None,
                 }],
                 warnings: vec![],
                 quality_score: None,
                 provider_specific: None,
+                provider_results: Vec::new(),
+                explanation: None,
             },
         ];
 
@@ -354,14 +620,19 @@ mod tests {
             GuardrailResult {
                 passed: false,
                 violations: vec![Violation {
+                    namespace: String::new(),
                     rule: "TEST".to_string(),
                     severity: Severity::Critical,
                     message: "Test violation".to_string(),
                     location: None,
+                    source: None,
+                    preview: None,
                 }],
                 warnings: vec![],
                 quality_score: None,
                 provider_specific: None,
+                provider_results: Vec::new(),
+                explanation: None,
             },
             GuardrailResult {
                 passed: true,
@@ -369,6 +640,8 @@ mod tests {
                 warnings: vec![],
                 quality_score: None,
                 provider_specific: None,
+                provider_results: Vec::new(),
+                explanation: None,
             },
         ];
 
@@ -377,6 +650,204 @@ mod tests {
         assert_eq!(aggregated.violations.len(), 1); // But violations still aggregated
     }
 
+    fn verdict(passed: bool) -> GuardrailResult {
+        let violations = if passed {
+            vec![]
+        } else {
+            vec![Violation {
+                namespace: String::new(),
+                rule: "TEST".to_string(),
+                severity: Severity::Critical,
+                message: "Test violation".to_string(),
+                location: None,
+                source: None,
+                preview: None,
+            }]
+        };
+        GuardrailResult::without_quality_score(passed, violations, vec![])
+    }
+
+    fn composite(aggregation: AggregationMode, providers: usize) -> HybridGuardrail {
+        let providers: Vec<Box<dyn GuardrailProvider>> = (0..providers)
+            .map(|_| {
+                Box::new(RegexGuardrail::new(RegexGuardrailConfig::default()))
+                    as Box<dyn GuardrailProvider>
+            })
+            .collect();
+        HybridGuardrail::new(providers, ExecutionMode::Parallel, aggregation)
+    }
+
+    #[test]
+    fn test_explanation_describes_verdict() {
+        let mut blocked = verdict(false);
+        blocked.violations[0].namespace = "llama_guard".to_string();
+        blocked.violations[0].rule = "S9".to_string();
+        let typed = |aggregation| {
+            composite(aggregation, 3).with_provider_types(vec![
+                "regex".to_string(),
+                "llama_guard".to_string(),
+                "language".to_string(),
+            ])
+        };
+
+        let composite = typed(AggregationMode::AllMustPass);
+        let aggregated =
+            composite.aggregate_results(vec![verdict(true), blocked.clone(), verdict(true)]);
+        assert_eq!(
+            aggregated.explanation.as_deref(),
+            Some("2/3 providers passed; blocked by llama_guard.S9 under all_must_pass")
+        );
+
+        // Sequential short-circuit leaves providers out
+        let aggregated = composite.aggregate_results(vec![verdict(true), blocked.clone()]);
+        assert_eq!(
+            aggregated.explanation.as_deref(),
+            Some(
+                "1/2 providers passed (1 not run or failed); blocked by llama_guard.S9 under \
+                 all_must_pass"
+            )
+        );
+
+        let composite = typed(AggregationMode::AnyCanPass);
+        let aggregated =
+            composite.aggregate_indexed(vec![(1, blocked.clone()), (2, verdict(true))]);
+        assert_eq!(
+            aggregated.explanation.as_deref(),
+            Some("1/2 providers passed (1 not run or failed); allowed by language under any_can_pass")
+        );
+
+        let composite = typed(AggregationMode::MajorityVote);
+        let aggregated = composite.aggregate_results(vec![verdict(true), blocked, verdict(true)]);
+        assert_eq!(
+            aggregated.explanation.as_deref(),
+            Some("2/3 providers passed; overruled llama_guard.S9 under majority_vote")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_aggregate_majority_vote() {
+        let composite = composite(AggregationMode::MajorityVote, 3);
+
+        let aggregated =
+            composite.aggregate_results(vec![verdict(true), verdict(false), verdict(true)]);
+        assert!(aggregated.passed); // 2 of 3 passed
+        assert_eq!(aggregated.violations.len(), 1);
+        match aggregated.provider_specific {
+            Some(ProviderSpecificResult::Vote(vote)) => {
+                assert_eq!(vote.votes.len(), 3);
+                assert_eq!(vote.votes[0].provider, "RegexGuardrail");
+                assert!(!vote.votes[1].passed);
+                assert!((vote.score - 2.0 / 3.0).abs() < f32::EPSILON);
+            }
+            other => panic!("Expected vote result, got {other:?}"),
+        }
+
+        // Ties fail
+        let aggregated = composite.aggregate_results(vec![verdict(true), verdict(false)]);
+        assert!(!aggregated.passed);
+    }
+
+    #[tokio::test]
+    async fn test_aggregate_weighted() {
+        let aggregation = AggregationMode::Weighted {
+            min_score: 0.6,
+            weights: [("llama_guard".to_string(), 3.0), ("regex".to_string(), 1.0)].into(),
+        };
+        let composite = composite(aggregation, 3).with_provider_types(vec![
+            "llama_guard".to_string(),
+            "regex".to_string(),
+            "terminology".to_string(),
+        ]);
+
+        // Llama Guard alone outweighs the others: 3 / 5 = 0.6
+        let aggregated =
+            composite.aggregate_results(vec![verdict(true), verdict(false), verdict(false)]);
+        assert!(aggregated.passed);
+        let Some(ProviderSpecificResult::Vote(vote)) = aggregated.provider_specific else {
+            panic!("Expected vote result");
+        };
+        assert!((vote.score - 0.6).abs() < f32::EPSILON);
+        // Unlisted providers weigh 1.0
+        assert_eq!(vote.votes[2].provider, "terminology");
+        assert_eq!(vote.votes[2].weight, 1.0);
+
+        // 2 / 5 = 0.4
+        let aggregated =
+            composite.aggregate_results(vec![verdict(false), verdict(true), verdict(true)]);
+        assert!(!aggregated.passed);
+    }
+
+    #[tokio::test]
+    async fn test_sequential_vote_runs_all_providers() {
+        let providers: Vec<Box<dyn GuardrailProvider>> = vec![
+            Box::new(RegexGuardrail::new(RegexGuardrailConfig {
+                max_length_bytes: 10,
+                patterns_file: None,
+                severity_threshold: Severity::Medium,
+                builtin_patterns: Vec::new(),
+                action: RegexAction::Block,
+                preview_chars: None,
+            })),
+            Box::new(RegexGuardrail::new(RegexGuardrailConfig::default())),
+            Box::new(RegexGuardrail::new(RegexGuardrailConfig::default())),
+        ];
+        let composite = HybridGuardrail::new(
+            providers,
+            ExecutionMode::Sequential,
+            AggregationMode::MajorityVote,
+        );
+
+        // The first provider fails but is outvoted
+        let result = composite
+            .validate("This is a long input that exceeds 10 bytes")
+            .await
+            .unwrap();
+        assert!(result.passed);
+        assert!(!result.violations.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_provider_results_name_each_member() {
+        let providers: Vec<Box<dyn GuardrailProvider>> = vec![
+            Box::new(RegexGuardrail::new(RegexGuardrailConfig {
+                max_length_bytes: 10,
+                patterns_file: None,
+                severity_threshold: Severity::Medium,
+                builtin_patterns: Vec::new(),
+                action: RegexAction::Block,
+                preview_chars: None,
+            })),
+            Box::new(HybridGuardrail::new(
+                vec![Box::new(RegexGuardrail::new(
+                    RegexGuardrailConfig::default(),
+                ))],
+                ExecutionMode::Parallel,
+                AggregationMode::AllMustPass,
+            )),
+        ];
+        let composite = HybridGuardrail::new(
+            providers,
+            ExecutionMode::Parallel,
+            AggregationMode::AnyCanPass,
+        )
+        .with_provider_types(vec!["regex".to_string(), "composite".to_string()]);
+
+        let result = composite
+            .validate("This is a long input that exceeds 10 bytes")
+            .await
+            .unwrap();
+        assert!(result.passed);
+        // The nested composite reports its own member instead of itself
+        let results = &result.provider_results;
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].provider, "regex");
+        assert!(!results[0].passed);
+        assert_eq!(results[0].violation_rules, vec!["regex.MAX_LENGTH"]);
+        assert_eq!(results[1].provider, "RegexGuardrail");
+        assert!(results[1].passed);
+        assert!(results.iter().all(|r| r.direction == "input"));
+    }
+
     #[tokio::test]
     async fn test_three_providers_composite() {
         let providers: Vec<Box<dyn GuardrailProvider>> = vec![
@@ -0,0 +1,402 @@
+//! Image output guardrail
+//!
+//! Checks images returned by multimodal models: format allowlist, byte size,
+//! pixel dimensions and, optionally, an external scanner webhook (NSFW
+//! classifiers, malware or steganography scanners):
+//!
+//! ```toml
+//! [guardrails.output]
+//! type = "image"
+//! allowed_formats = ["png", "jpeg"]  # Default: png, jpeg, gif and webp
+//! max_bytes = 5242880
+//! max_width = 2048
+//! max_height = 2048
+//!
+//! [guardrails.output.scanner]
+//! url = "https://scanner.internal/v1/scan"
+//! timeout_secs = 30
+//! api_key_name = "SCANNER_API_KEY"  # Sent as a Bearer token (optional)
+//! ```
+//!
+//! The scanner receives one POST per image with
+//! `{"media_type", "width", "height", "bytes", "data"}` (`data` is base64) and
+//! answers `{"flagged": bool, "categories": [...], "reason": "..."}`.
+//!
+//! - `image.IMAGE_FORMAT_NOT_ALLOWED`: format outside `allowed_formats`
+//! - `image.IMAGE_TOO_LARGE`: more than `max_bytes`
+//! - `image.IMAGE_DIMENSIONS_EXCEEDED`: wider or taller than the limits
+//! - `image.IMAGE_MALFORMED`: dimensions unreadable while limits are set
+//! - `image.IMAGE_FLAGGED`: rejected by the scanner
+//!
+//! Responses without images pass; text is left to the other guardrails (use a
+//! composite to check both).
+
+use crate::{
+    cassette,
+    error::CliError,
+    guardrails::{
+        catalog::namespace,
+        provider::{GuardrailProvider, GuardrailResult, OutputContext, Severity, Violation},
+    },
+    image::ImageInput,
+};
+use async_trait::async_trait;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::time::Duration;
+
+/// Rule names reported in `Violation::rule`
+pub mod rules {
+    pub const IMAGE_FORMAT_NOT_ALLOWED: &str = "IMAGE_FORMAT_NOT_ALLOWED";
+    pub const IMAGE_TOO_LARGE: &str = "IMAGE_TOO_LARGE";
+    pub const IMAGE_DIMENSIONS_EXCEEDED: &str = "IMAGE_DIMENSIONS_EXCEEDED";
+    pub const IMAGE_MALFORMED: &str = "IMAGE_MALFORMED";
+    pub const IMAGE_FLAGGED: &str = "IMAGE_FLAGGED";
+}
+
+/// Every rule rejects an image the caller asked not to receive
+pub(crate) const RULE_SEVERITY: Severity = Severity::High;
+
+/// Formats accepted in `allowed_formats`
+const FORMATS: &[&str] = &["png", "jpeg", "gif", "webp"];
+
+fn default_scanner_timeout() -> u64 {
+    30
+}
+
+/// External scanner webhook
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImageScannerConfig {
+    pub url: String,
+    #[serde(default = "default_scanner_timeout")]
+    pub timeout_secs: u64,
+    /// Environment variable holding the scanner's Bearer token
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api_key_name: Option<String>,
+}
+
+impl ImageScannerConfig {
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            timeout_secs: default_scanner_timeout(),
+            api_key_name: None,
+        }
+    }
+}
+
+/// Image guardrail configuration
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ImageGuardrailConfig {
+    /// Accepted formats: "png", "jpeg", "gif", "webp" (default: all)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub allowed_formats: Vec<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_bytes: Option<u64>,
+
+    /// Maximum width in pixels
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_width: Option<u32>,
+
+    /// Maximum height in pixels
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_height: Option<u32>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scanner: Option<ImageScannerConfig>,
+}
+
+impl ImageGuardrailConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if let Some(format) = self
+            .allowed_formats
+            .iter()
+            .find(|f| !FORMATS.contains(&f.as_str()))
+        {
+            return Err(format!(
+                "unknown image format '{format}' (expected one of: {})",
+                FORMATS.join(", ")
+            ));
+        }
+        if self.max_bytes == Some(0) || self.max_width == Some(0) || self.max_height == Some(0) {
+            return Err("max_bytes, max_width and max_height must be positive".to_string());
+        }
+        if let Some(scanner) = &self.scanner {
+            if !(scanner.url.starts_with("http://") || scanner.url.starts_with("https://")) {
+                return Err(format!(
+                    "scanner url '{}' must be an http(s) URL",
+                    scanner.url
+                ));
+            }
+            if scanner.timeout_secs == 0 {
+                return Err("scanner timeout_secs must be positive".to_string());
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Verdict of the scanner webhook
+#[derive(Debug, Deserialize)]
+struct ScanVerdict {
+    flagged: bool,
+    #[serde(default)]
+    categories: Vec<String>,
+    #[serde(default)]
+    reason: Option<String>,
+}
+
+/// Guardrail checking images returned with the response
+pub struct ImageGuardrail {
+    client: Client,
+    config: ImageGuardrailConfig,
+    /// Resolved scanner token
+    api_key: Option<String>,
+}
+
+impl ImageGuardrail {
+    pub fn new(config: ImageGuardrailConfig, api_key: Option<String>) -> Self {
+        Self {
+            client: Client::new(),
+            config,
+            api_key,
+        }
+    }
+
+    /// Local checks of image `index` (format, size, dimensions)
+    fn check(&self, index: usize, image: &ImageInput) -> Vec<Violation> {
+        let config = &self.config;
+        let mut violations = Vec::new();
+        let format = image.media_type.trim_start_matches("image/");
+        if !config.allowed_formats.is_empty() && !config.allowed_formats.iter().any(|f| f == format)
+        {
+            violations.push(violation(
+                index,
+                rules::IMAGE_FORMAT_NOT_ALLOWED,
+                format!("Image format '{format}' is not allowed"),
+            ));
+        }
+        let bytes = image.data.len() as u64;
+        if let Some(max_bytes) = config.max_bytes.filter(|max| bytes > *max) {
+            violations.push(violation(
+                index,
+                rules::IMAGE_TOO_LARGE,
+                format!("Image is {bytes} bytes (limit: {max_bytes})"),
+            ));
+        }
+        if config.max_width.is_some() || config.max_height.is_some() {
+            match image.dimensions() {
+                Some((width, height)) => {
+                    let too_wide = config.max_width.is_some_and(|max| width > max);
+                    let too_tall = config.max_height.is_some_and(|max| height > max);
+                    if too_wide || too_tall {
+                        violations.push(violation(
+                            index,
+                            rules::IMAGE_DIMENSIONS_EXCEEDED,
+                            format!(
+                                "Image is {width}x{height} pixels (limit: {}x{})",
+                                limit(config.max_width),
+                                limit(config.max_height)
+                            ),
+                        ));
+                    }
+                }
+                None => violations.push(violation(
+                    index,
+                    rules::IMAGE_MALFORMED,
+                    "Image dimensions could not be read".to_string(),
+                )),
+            }
+        }
+        violations
+    }
+
+    /// Ask the scanner webhook about image `index`
+    async fn scan(
+        &self,
+        scanner: &ImageScannerConfig,
+        index: usize,
+        image: &ImageInput,
+    ) -> Result<Option<Violation>, CliError> {
+        let (width, height) = image.dimensions().unzip();
+        let body = json!({
+            "media_type": image.media_type,
+            "width": width,
+            "height": height,
+            "bytes": image.data.len(),
+            "data": image.base64(),
+        });
+        let mut request = self
+            .client
+            .post(&scanner.url)
+            .timeout(Duration::from_secs(scanner.timeout_secs))
+            .json(&body);
+        if let Some(key) = &self.api_key {
+            request = request.header("Authorization", format!("Bearer {key}"));
+        }
+
+        let response = cassette::send(request, self.api_key.as_deref()).await?;
+        if response.status == 401 {
+            return Err(CliError::AuthenticationFailed(
+                "Image scanner rejected the API key".to_string(),
+            ));
+        }
+        if !response.status.is_success() {
+            return Err(CliError::InvalidResponse(format!(
+                "Image scanner returned HTTP {}: {}",
+                response.status.as_u16(),
+                response.body
+            )));
+        }
+        let verdict: ScanVerdict = serde_json::from_str(&response.body).map_err(|e| {
+            CliError::InvalidResponse(format!("Failed to parse image scanner response: {e}"))
+        })?;
+        if !verdict.flagged {
+            return Ok(None);
+        }
+        let mut message = "Image flagged by scanner".to_string();
+        if !verdict.categories.is_empty() {
+            message.push_str(&format!(" ({})", verdict.categories.join(", ")));
+        }
+        if let Some(reason) = verdict.reason {
+            message.push_str(&format!(": {reason}"));
+        }
+        Ok(Some(violation(index, rules::IMAGE_FLAGGED, message)))
+    }
+}
+
+fn limit(max: Option<u32>) -> String {
+    max.map_or_else(|| "any".to_string(), |max| max.to_string())
+}
+
+fn violation(index: usize, rule: &str, message: String) -> Violation {
+    Violation {
+        namespace: namespace::IMAGE.to_string(),
+        rule: rule.to_string(),
+        severity: RULE_SEVERITY,
+        message,
+        location: Some(format!("image {}", index + 1)),
+        source: None,
+        preview: None,
+    }
+}
+
+#[async_trait]
+impl GuardrailProvider for ImageGuardrail {
+    async fn validate(&self, _content: &str) -> Result<GuardrailResult, CliError> {
+        // Text only: nothing to check
+        Ok(GuardrailResult::without_quality_score(
+            true,
+            Vec::new(),
+            Vec::new(),
+        ))
+    }
+
+    async fn validate_output(
+        &self,
+        _response: &str,
+        context: &OutputContext,
+    ) -> Result<GuardrailResult, CliError> {
+        let mut violations = Vec::new();
+        for (index, image) in context.images.iter().enumerate() {
+            let local = self.check(index, image);
+            // Images failing local checks are not sent to the scanner
+            if local.is_empty() {
+                if let Some(scanner) = &self.config.scanner {
+                    violations.extend(self.scan(scanner, index, image).await?);
+                }
+            }
+            violations.extend(local);
+        }
+        Ok(GuardrailResult::without_quality_score(
+            violations.is_empty(),
+            violations,
+            Vec::new(),
+        ))
+    }
+
+    fn name(&self) -> &str {
+        "ImageGuardrail"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 1x1 GIF
+    fn pixel() -> ImageInput {
+        ImageInput::from_bytes(b"GIF89a\x01\x00\x01\x00\x80\x00\x00".to_vec()).unwrap()
+    }
+
+    fn rules(guardrail: &ImageGuardrail, image: &ImageInput) -> Vec<String> {
+        guardrail
+            .check(0, image)
+            .iter()
+            .map(Violation::id)
+            .collect()
+    }
+
+    #[test]
+    fn test_local_checks() {
+        let guardrail = ImageGuardrail::new(ImageGuardrailConfig::default(), None);
+        assert!(rules(&guardrail, &pixel()).is_empty());
+
+        let guardrail = ImageGuardrail::new(
+            ImageGuardrailConfig {
+                allowed_formats: vec!["png".to_string()],
+                max_bytes: Some(8),
+                ..Default::default()
+            },
+            None,
+        );
+        assert_eq!(
+            rules(&guardrail, &pixel()),
+            vec!["image.IMAGE_FORMAT_NOT_ALLOWED", "image.IMAGE_TOO_LARGE"]
+        );
+
+        let guardrail = ImageGuardrail::new(
+            ImageGuardrailConfig {
+                max_width: Some(1),
+                max_height: Some(1),
+                ..Default::default()
+            },
+            None,
+        );
+        assert!(rules(&guardrail, &pixel()).is_empty());
+        let wide = ImageInput::from_bytes(b"GIF89a\x02\x00\x01\x00".to_vec()).unwrap();
+        assert_eq!(
+            rules(&guardrail, &wide),
+            vec!["image.IMAGE_DIMENSIONS_EXCEEDED"]
+        );
+        let truncated = ImageInput::from_bytes(b"GIF89a\x02".to_vec()).unwrap();
+        assert_eq!(rules(&guardrail, &truncated), vec!["image.IMAGE_MALFORMED"]);
+    }
+
+    #[test]
+    fn test_config_validation() {
+        let config: ImageGuardrailConfig = toml::from_str(
+            r#"
+allowed_formats = ["png", "jpeg"]
+max_width = 2048
+
+[scanner]
+url = "https://scanner.internal/v1/scan"
+"#,
+        )
+        .unwrap();
+        config.validate().unwrap();
+        assert_eq!(config.scanner.unwrap().timeout_secs, 30);
+
+        let config = ImageGuardrailConfig {
+            allowed_formats: vec!["svg".to_string()],
+            ..Default::default()
+        };
+        assert!(config
+            .validate()
+            .unwrap_err()
+            .contains("unknown image format 'svg'"));
+    }
+}
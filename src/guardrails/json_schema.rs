@@ -0,0 +1,309 @@
+//! JSON Schema conformance guardrail
+//!
+//! Parses the response as JSON and validates it against a JSON Schema
+//! (Draft 7), reporting each violation with the JSON pointer of the offending
+//! value. Providers don't always enforce `response_format = "json-schema"`, so
+//! this checks the result post-hoc:
+//!
+//! ```toml
+//! [guardrails.output]
+//! type = "json_schema"
+//! schema_file = "schemas/invoice.json"  # Default: the response_format schema
+//! strict = true                         # Reject undeclared properties (default: false)
+//! ```
+//!
+//! Without `schema_file`, output validation uses the schema of the request's
+//! `response_format` (and its `strict` flag). As an input guardrail it needs
+//! `schema_file`.
+//!
+//! - `json_schema.INVALID_JSON`: the response isn't JSON (a surrounding
+//!   Markdown code fence is tolerated)
+//! - `json_schema.SCHEMA_VIOLATION`: one per schema error, located by JSON
+//!   pointer (e.g. `/items/0/price`)
+//!
+//! With `strict = true`, objects that declare `properties` but no
+//! `additionalProperties` reject properties the schema doesn't declare, as
+//! OpenAI's strict structured outputs do.
+
+use crate::{
+    error::CliError,
+    guardrails::{
+        catalog::namespace,
+        provider::{GuardrailProvider, GuardrailResult, OutputContext, Severity, Violation},
+    },
+    models::ResponseFormat,
+};
+use async_trait::async_trait;
+use jsonschema::Validator;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::{fs, path::PathBuf};
+
+/// Rule names reported in `Violation::rule`
+pub mod rules {
+    pub const INVALID_JSON: &str = "INVALID_JSON";
+    pub const SCHEMA_VIOLATION: &str = "SCHEMA_VIOLATION";
+}
+
+/// Both rules report a response the caller can't consume
+pub(crate) const RULE_SEVERITY: Severity = Severity::High;
+
+/// JSON Schema guardrail configuration
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct JsonSchemaConfig {
+    /// JSON Schema file (default: the `response_format` schema of the request)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub schema_file: Option<PathBuf>,
+
+    /// Reject properties the schema doesn't declare (default: false)
+    #[serde(default)]
+    pub strict: bool,
+}
+
+impl JsonSchemaConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if let Some(path) = &self.schema_file {
+            if !path.is_file() {
+                return Err(format!("schema file '{}' does not exist", path.display()));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// JSON Schema conformance guardrail
+pub struct JsonSchemaGuardrail {
+    /// Compiled `schema_file`
+    validator: Option<Validator>,
+    strict: bool,
+}
+
+impl JsonSchemaGuardrail {
+    /// Load and compile `schema_file` when configured
+    pub fn new(config: &JsonSchemaConfig) -> Result<Self, CliError> {
+        let validator = match &config.schema_file {
+            Some(path) => {
+                let content = fs::read_to_string(path).map_err(|e| {
+                    CliError::FileNotFound(format!(
+                        "Failed to read JSON schema file '{}': {e}",
+                        path.display()
+                    ))
+                })?;
+                let schema: Value = serde_json::from_str(&content).map_err(|e| {
+                    CliError::InvalidArguments(format!(
+                        "Invalid JSON in schema file '{}': {e}",
+                        path.display()
+                    ))
+                })?;
+                Some(compile(schema, config.strict)?)
+            }
+            None => None,
+        };
+        Ok(Self {
+            validator,
+            strict: config.strict,
+        })
+    }
+
+    /// Violations of `content` against `validator`
+    fn check(validator: &Validator, content: &str) -> Vec<Violation> {
+        let instance: Value = match serde_json::from_str(json_body(content)) {
+            Ok(instance) => instance,
+            Err(e) => {
+                return vec![violation(
+                    rules::INVALID_JSON,
+                    format!("Response is not valid JSON: {e}"),
+                    None,
+                )]
+            }
+        };
+        validator
+            .iter_errors(&instance)
+            .map(|error| {
+                let pointer = error.instance_path().to_string();
+                let pointer = if pointer.is_empty() {
+                    "/".to_string()
+                } else {
+                    pointer
+                };
+                violation(
+                    rules::SCHEMA_VIOLATION,
+                    format!("{pointer}: {}", error.masked()),
+                    Some(pointer),
+                )
+            })
+            .collect()
+    }
+
+    fn result(violations: Vec<Violation>) -> GuardrailResult {
+        GuardrailResult::without_quality_score(violations.is_empty(), violations, Vec::new())
+    }
+}
+
+fn no_schema() -> CliError {
+    CliError::InvalidArguments(
+        "json_schema guardrail has no schema: set schema_file or use response_format = \
+         \"json-schema\""
+            .to_string(),
+    )
+}
+
+fn violation(rule: &str, message: String, location: Option<String>) -> Violation {
+    Violation {
+        namespace: namespace::JSON_SCHEMA.to_string(),
+        rule: rule.to_string(),
+        severity: RULE_SEVERITY,
+        message,
+        location,
+        source: None,
+        preview: None,
+    }
+}
+
+/// Compile `schema` (Draft 7), closing declared objects when `strict`
+fn compile(mut schema: Value, strict: bool) -> Result<Validator, CliError> {
+    if strict {
+        close_objects(&mut schema);
+    }
+    jsonschema::options()
+        .with_draft(jsonschema::Draft::Draft7)
+        .build(&schema)
+        .map_err(|e| CliError::InvalidArguments(format!("Invalid JSON schema: {e}")))
+}
+
+/// Default `additionalProperties` to false on objects declaring `properties`,
+/// recursing into subschemas
+fn close_objects(schema: &mut Value) {
+    let Some(object) = schema.as_object_mut() else {
+        return;
+    };
+    if object.get("properties").is_some_and(Value::is_object) {
+        object
+            .entry("additionalProperties")
+            .or_insert(Value::Bool(false));
+    }
+    for keyword in ["properties", "patternProperties", "definitions", "$defs"] {
+        if let Some(Value::Object(subschemas)) = object.get_mut(keyword) {
+            subschemas.values_mut().for_each(close_objects);
+        }
+    }
+    for keyword in ["anyOf", "oneOf", "allOf", "items", "prefixItems"] {
+        match object.get_mut(keyword) {
+            Some(Value::Array(subschemas)) => subschemas.iter_mut().for_each(close_objects),
+            Some(subschema) => close_objects(subschema),
+            None => {}
+        }
+    }
+    for keyword in ["additionalProperties", "not", "if", "then", "else"] {
+        if let Some(subschema) = object.get_mut(keyword) {
+            close_objects(subschema);
+        }
+    }
+}
+
+/// Response without surrounding whitespace or Markdown code fence
+fn json_body(content: &str) -> &str {
+    let trimmed = content.trim();
+    trimmed
+        .strip_prefix("```")
+        .and_then(|rest| rest.strip_suffix("```"))
+        .map(|fenced| fenced.trim_start_matches("json").trim())
+        .unwrap_or(trimmed)
+}
+
+#[async_trait]
+impl GuardrailProvider for JsonSchemaGuardrail {
+    async fn validate(&self, content: &str) -> Result<GuardrailResult, CliError> {
+        let validator = self.validator.as_ref().ok_or_else(no_schema)?;
+        Ok(Self::result(Self::check(validator, content)))
+    }
+
+    async fn validate_output(
+        &self,
+        response: &str,
+        context: &OutputContext,
+    ) -> Result<GuardrailResult, CliError> {
+        if self.validator.is_some() {
+            return self.validate(response).await;
+        }
+        let Some(ResponseFormat::JsonSchema { json_schema }) = &context.response_format else {
+            return Err(no_schema());
+        };
+        let strict = self.strict || json_schema.strict.unwrap_or(false);
+        let validator = compile(json_schema.schema.clone(), strict)?;
+        Ok(Self::result(Self::check(&validator, response)))
+    }
+
+    fn name(&self) -> &str {
+        "JsonSchemaGuardrail"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn invoice_schema() -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "total": { "type": "number" },
+                "items": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "properties": { "name": { "type": "string" } },
+                        "required": ["name"]
+                    }
+                }
+            },
+            "required": ["total"]
+        })
+    }
+
+    /// Sorted violation locations (the validator's order isn't the document's)
+    fn locations(violations: &[Violation]) -> Vec<&str> {
+        let mut locations: Vec<&str> = violations
+            .iter()
+            .map(|v| v.location.as_deref().unwrap_or_default())
+            .collect();
+        locations.sort_unstable();
+        locations
+    }
+
+    #[test]
+    fn test_violations_are_located_by_json_pointer() {
+        let validator = compile(invoice_schema(), false).unwrap();
+
+        let fenced = "```json\n{\"total\": 12.5, \"items\": [{\"name\": \"pen\"}]}\n```";
+        assert!(JsonSchemaGuardrail::check(&validator, fenced).is_empty());
+
+        let violations = JsonSchemaGuardrail::check(
+            &validator,
+            r#"{"total": "12.5", "items": [{"name": "pen"}, {"qty": 2}]}"#,
+        );
+        assert_eq!(locations(&violations), vec!["/items/1", "/total"]);
+        assert!(violations
+            .iter()
+            .all(|v| v.id() == "json_schema.SCHEMA_VIOLATION"));
+        assert!(violations.iter().any(|v| v.message.starts_with("/total: ")));
+
+        let violations = JsonSchemaGuardrail::check(&validator, "Total: 12.5");
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].id(), "json_schema.INVALID_JSON");
+    }
+
+    #[test]
+    fn test_strict_rejects_undeclared_properties() {
+        let response = r#"{"total": 1, "items": [{"name": "pen", "color": "red"}], "note": "x"}"#;
+        let lenient = compile(invoice_schema(), false).unwrap();
+        assert!(JsonSchemaGuardrail::check(&lenient, response).is_empty());
+
+        let strict = compile(invoice_schema(), true).unwrap();
+        assert_eq!(
+            locations(&JsonSchemaGuardrail::check(&strict, response)),
+            vec!["/", "/items/0"]
+        );
+    }
+}
@@ -0,0 +1,482 @@
+//! Response language guardrail
+//!
+//! Multilingual deployments often see models drift into English. This check
+//! detects the response language and compares it with the expected one:
+//!
+//! 1. `target`, when configured
+//! 2. a language the user asks for ("answer in French", "translate this
+//!    into German"), unless `follow_requests = false`
+//! 3. the language of the user prompt
+//!
+//! ```toml
+//! [guardrails.output]
+//! type = "language"
+//! target = "fr"                  # Optional: ISO 639-1/639-3 code or English name
+//! min_confidence = 0.5           # Skip uncertain detections (default)
+//! severity_threshold = "Medium"  # Block mismatches (default: High = warn)
+//! ```
+//!
+//! Texts shorter than `min_chars` and detections below `min_confidence` are
+//! not checked, so short answers and mixed-language text do not trigger
+//! findings.
+
+use crate::{
+    error::CliError,
+    guardrails::{
+        catalog::namespace,
+        provider::{GuardrailProvider, GuardrailResult, OutputContext, Severity, Violation},
+    },
+};
+use async_trait::async_trait;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use whatlang::Lang;
+
+/// Rule names reported in `Violation::rule`
+pub mod rules {
+    pub const LANGUAGE_MISMATCH: &str = "LANGUAGE_MISMATCH";
+}
+
+pub(crate) const RULE_SEVERITY: Severity = Severity::Medium;
+
+/// ISO 639-1 codes for the detectable languages
+const ISO_639_1: &[(&str, Lang)] = &[
+    ("af", Lang::Afr),
+    ("ak", Lang::Aka),
+    ("am", Lang::Amh),
+    ("ar", Lang::Ara),
+    ("az", Lang::Aze),
+    ("be", Lang::Bel),
+    ("bg", Lang::Bul),
+    ("bn", Lang::Ben),
+    ("ca", Lang::Cat),
+    ("cs", Lang::Ces),
+    ("da", Lang::Dan),
+    ("de", Lang::Deu),
+    ("el", Lang::Ell),
+    ("en", Lang::Eng),
+    ("eo", Lang::Epo),
+    ("es", Lang::Spa),
+    ("et", Lang::Est),
+    ("fa", Lang::Pes),
+    ("fi", Lang::Fin),
+    ("fr", Lang::Fra),
+    ("gu", Lang::Guj),
+    ("he", Lang::Heb),
+    ("hi", Lang::Hin),
+    ("hr", Lang::Hrv),
+    ("hu", Lang::Hun),
+    ("hy", Lang::Hye),
+    ("id", Lang::Ind),
+    ("it", Lang::Ita),
+    ("ja", Lang::Jpn),
+    ("jv", Lang::Jav),
+    ("ka", Lang::Kat),
+    ("km", Lang::Khm),
+    ("kn", Lang::Kan),
+    ("ko", Lang::Kor),
+    ("la", Lang::Lat),
+    ("lt", Lang::Lit),
+    ("lv", Lang::Lav),
+    ("mk", Lang::Mkd),
+    ("ml", Lang::Mal),
+    ("mr", Lang::Mar),
+    ("my", Lang::Mya),
+    ("nb", Lang::Nob),
+    ("ne", Lang::Nep),
+    ("nl", Lang::Nld),
+    ("no", Lang::Nob),
+    ("or", Lang::Ori),
+    ("pa", Lang::Pan),
+    ("pl", Lang::Pol),
+    ("pt", Lang::Por),
+    ("ro", Lang::Ron),
+    ("ru", Lang::Rus),
+    ("si", Lang::Sin),
+    ("sk", Lang::Slk),
+    ("sl", Lang::Slv),
+    ("sn", Lang::Sna),
+    ("sr", Lang::Srp),
+    ("sv", Lang::Swe),
+    ("ta", Lang::Tam),
+    ("te", Lang::Tel),
+    ("th", Lang::Tha),
+    ("tk", Lang::Tuk),
+    ("tl", Lang::Tgl),
+    ("tr", Lang::Tur),
+    ("uk", Lang::Ukr),
+    ("ur", Lang::Urd),
+    ("uz", Lang::Uzb),
+    ("vi", Lang::Vie),
+    ("yi", Lang::Yid),
+    ("zh", Lang::Cmn),
+    ("zu", Lang::Zul),
+];
+
+/// Common English names that differ from the detector's names
+const ALIASES: &[(&str, Lang)] = &[
+    ("chinese", Lang::Cmn),
+    ("farsi", Lang::Pes),
+    ("filipino", Lang::Tgl),
+    ("norwegian", Lang::Nob),
+    ("slovenian", Lang::Slv),
+];
+
+/// Instruction that may name a response language ("Please answer ...")
+static REQUEST: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?i)\b(?:answer|respond|reply|write|translate|summari[sz]e|explain)\b[^.?!\n]*")
+        .expect("valid request regex")
+});
+
+static LANGUAGE_NAME: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(?i)\b(?:in|into|to)\s+(\p{L}+)").expect("valid language regex"));
+
+/// Language by English or native name ("French", "Deutsch")
+fn language_by_name(name: &str) -> Option<Lang> {
+    let name = name.to_lowercase();
+    Lang::all()
+        .iter()
+        .copied()
+        .find(|lang| lang.eng_name().to_lowercase() == name || lang.name().to_lowercase() == name)
+        .or_else(|| {
+            ALIASES
+                .iter()
+                .find(|(alias, _)| *alias == name)
+                .map(|(_, lang)| *lang)
+        })
+}
+
+/// Language from an ISO 639-1 code (optionally with a region, "pt-BR"), an
+/// ISO 639-3 code or a name
+pub(crate) fn parse_language(value: &str) -> Option<Lang> {
+    let value = value.trim();
+    let code = value
+        .split(['-', '_'])
+        .next()
+        .unwrap_or_default()
+        .to_lowercase();
+    ISO_639_1
+        .iter()
+        .find(|(iso, _)| *iso == code)
+        .map(|(_, lang)| *lang)
+        .or_else(|| Lang::from_code(code.as_str()))
+        .or_else(|| language_by_name(value))
+}
+
+/// Language the prompt explicitly asks the response to use
+fn requested_language(prompt: &str) -> Option<Lang> {
+    REQUEST
+        .find_iter(prompt)
+        .flat_map(|request| LANGUAGE_NAME.captures_iter(request.as_str()))
+        .find_map(|caps| language_by_name(&caps[1]))
+}
+
+fn default_follow_requests() -> bool {
+    true
+}
+
+fn default_min_confidence() -> f64 {
+    0.5
+}
+
+fn default_min_chars() -> usize {
+    20
+}
+
+fn default_severity_threshold() -> Severity {
+    // Detection is statistical: warn unless the threshold is lowered
+    Severity::High
+}
+
+/// Language guardrail configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LanguageConfig {
+    /// Expected language: ISO 639-1 ("fr", "pt-BR"), ISO 639-3 ("fra") or
+    /// English name ("French"). Unset: follow the user prompt
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target: Option<String>,
+
+    /// Honor languages requested in the user prompt ("answer in French")
+    /// when no target is set (default: true)
+    #[serde(default = "default_follow_requests")]
+    pub follow_requests: bool,
+
+    /// Detections below this confidence (0.0-1.0) are not checked (default: 0.5)
+    #[serde(default = "default_min_confidence")]
+    pub min_confidence: f64,
+
+    /// Texts shorter than this many characters are not checked (default: 20)
+    #[serde(default = "default_min_chars")]
+    pub min_chars: usize,
+
+    /// Minimum severity that blocks; findings below it become warnings
+    /// (default: high, so mismatches are warnings)
+    #[serde(default = "default_severity_threshold")]
+    pub severity_threshold: Severity,
+}
+
+impl Default for LanguageConfig {
+    fn default() -> Self {
+        Self {
+            target: None,
+            follow_requests: default_follow_requests(),
+            min_confidence: default_min_confidence(),
+            min_chars: default_min_chars(),
+            severity_threshold: default_severity_threshold(),
+        }
+    }
+}
+
+impl LanguageConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if let Some(target) = &self.target {
+            if parse_language(target).is_none() {
+                return Err(format!("unknown target language '{target}'"));
+            }
+        }
+        if !(0.0..=1.0).contains(&self.min_confidence) {
+            return Err(format!(
+                "min_confidence {} must be between 0.0 and 1.0",
+                self.min_confidence
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Why a language is expected, for the finding message
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Expectation {
+    Target,
+    Requested,
+    Prompt,
+}
+
+impl Expectation {
+    fn describe(self) -> &'static str {
+        match self {
+            Self::Target => "target language",
+            Self::Requested => "requested in the prompt",
+            Self::Prompt => "language of the prompt",
+        }
+    }
+}
+
+/// Language-match guardrail
+pub struct LanguageGuardrail {
+    config: LanguageConfig,
+    target: Option<Lang>,
+}
+
+impl LanguageGuardrail {
+    pub fn new(config: LanguageConfig) -> Self {
+        Self {
+            target: config.target.as_deref().and_then(parse_language),
+            config,
+        }
+    }
+
+    /// Detected language, if the text is long enough and the detection confident
+    fn detect(&self, text: &str) -> Option<Lang> {
+        if text.chars().filter(|c| !c.is_whitespace()).count() < self.config.min_chars {
+            return None;
+        }
+        let info = whatlang::detect(text)?;
+        if info.confidence() < self.config.min_confidence {
+            log::debug!(
+                "Language detection inconclusive ({} at {:.2})",
+                info.lang().eng_name(),
+                info.confidence()
+            );
+            return None;
+        }
+        Some(info.lang())
+    }
+
+    fn expected(&self, user_prompt: Option<&str>) -> Option<(Lang, Expectation)> {
+        if let Some(target) = self.target {
+            return Some((target, Expectation::Target));
+        }
+        let prompt = user_prompt?;
+        if self.config.follow_requests {
+            if let Some(lang) = requested_language(prompt) {
+                return Some((lang, Expectation::Requested));
+            }
+        }
+        self.detect(prompt).map(|lang| (lang, Expectation::Prompt))
+    }
+
+    fn check(&self, content: &str, user_prompt: Option<&str>) -> GuardrailResult {
+        let mut findings = Vec::new();
+        if let Some((expected, why)) = self.expected(user_prompt) {
+            match self.detect(content) {
+                Some(actual) if actual != expected => findings.push(Violation {
+                    namespace: namespace::LANGUAGE.to_string(),
+                    rule: rules::LANGUAGE_MISMATCH.to_string(),
+                    severity: RULE_SEVERITY,
+                    message: format!(
+                        "Text is in {}, expected {} ({})",
+                        actual.eng_name(),
+                        expected.eng_name(),
+                        why.describe()
+                    ),
+                    location: None,
+                    source: None,
+                    preview: None,
+                }),
+                _ => {}
+            }
+        }
+
+        let (violations, warnings): (Vec<_>, Vec<_>) = findings
+            .into_iter()
+            .partition(|f| f.severity >= self.config.severity_threshold);
+        GuardrailResult::without_quality_score(violations.is_empty(), violations, warnings)
+    }
+}
+
+#[async_trait]
+impl GuardrailProvider for LanguageGuardrail {
+    /// Without a request context only a configured `target` can be checked
+    async fn validate(&self, content: &str) -> Result<GuardrailResult, CliError> {
+        if self.target.is_none() {
+            log::warn!(
+                "Language guardrail without a target only applies to output guardrails; skipping"
+            );
+        }
+        Ok(self.check(content, None))
+    }
+
+    async fn validate_output(
+        &self,
+        response: &str,
+        context: &OutputContext,
+    ) -> Result<GuardrailResult, CliError> {
+        Ok(self.check(response, context.user_prompt.as_deref()))
+    }
+
+    fn name(&self) -> &str {
+        "LanguageGuardrail"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ENGLISH: &str =
+        "The quarterly report shows that revenue grew steadily across all of our regions.";
+    const FRENCH: &str =
+        "Le rapport trimestriel montre que le chiffre d'affaires a progressé dans toutes nos régions.";
+
+    fn context(user_prompt: &str) -> OutputContext {
+        OutputContext {
+            user_prompt: Some(user_prompt.to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_parse_language() {
+        assert_eq!(parse_language("fr"), Some(Lang::Fra));
+        assert_eq!(parse_language("pt-BR"), Some(Lang::Por));
+        assert_eq!(parse_language("deu"), Some(Lang::Deu));
+        assert_eq!(parse_language("Japanese"), Some(Lang::Jpn));
+        assert_eq!(parse_language("chinese"), Some(Lang::Cmn));
+        assert_eq!(parse_language("klingon"), None);
+    }
+
+    #[test]
+    fn test_requested_language() {
+        assert_eq!(
+            requested_language("Summarize the attached report in French, please."),
+            Some(Lang::Fra)
+        );
+        assert_eq!(
+            requested_language("Reply to Maria. Translate the notes into German."),
+            Some(Lang::Deu)
+        );
+        assert_eq!(requested_language("What is the French word for cat?"), None);
+    }
+
+    #[tokio::test]
+    async fn test_response_must_match_prompt_language() {
+        let guardrail = LanguageGuardrail::new(LanguageConfig::default());
+
+        let result = guardrail
+            .validate_output(ENGLISH, &context(FRENCH))
+            .await
+            .unwrap();
+        assert!(result.passed);
+        assert_eq!(result.warnings.len(), 1);
+        assert_eq!(result.warnings[0].id(), "language.LANGUAGE_MISMATCH");
+        assert_eq!(
+            result.warnings[0].message,
+            "Text is in English, expected French (language of the prompt)"
+        );
+
+        let matching = guardrail
+            .validate_output(FRENCH, &context(FRENCH))
+            .await
+            .unwrap();
+        assert!(matching.warnings.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_requested_language_and_threshold() {
+        let guardrail = LanguageGuardrail::new(LanguageConfig {
+            severity_threshold: Severity::Medium,
+            ..Default::default()
+        });
+        let prompt = format!("{ENGLISH} Please answer in French.");
+
+        let blocked = guardrail
+            .validate_output(ENGLISH, &context(&prompt))
+            .await
+            .unwrap();
+        assert!(!blocked.passed);
+        assert!(blocked.violations[0]
+            .message
+            .ends_with("(requested in the prompt)"));
+
+        let allowed = guardrail
+            .validate_output(FRENCH, &context(&prompt))
+            .await
+            .unwrap();
+        assert!(allowed.passed);
+    }
+
+    #[tokio::test]
+    async fn test_target_and_short_text() {
+        let guardrail = LanguageGuardrail::new(LanguageConfig {
+            target: Some("fr".to_string()),
+            ..Default::default()
+        });
+
+        // The target wins over the prompt and also applies without context
+        let result = guardrail.validate(ENGLISH).await.unwrap();
+        assert_eq!(result.warnings.len(), 1);
+        assert!(guardrail
+            .validate("OK, done.")
+            .await
+            .unwrap()
+            .warnings
+            .is_empty());
+    }
+
+    #[test]
+    fn test_validate() {
+        assert!(LanguageConfig::default().validate().is_ok());
+        let unknown = LanguageConfig {
+            target: Some("xx".to_string()),
+            ..Default::default()
+        };
+        assert!(unknown.validate().is_err());
+        let confidence = LanguageConfig {
+            min_confidence: 1.5,
+            ..Default::default()
+        };
+        assert!(confidence.validate().is_err());
+    }
+}
@@ -1,8 +1,12 @@
 use crate::{
     client::LlmClient,
     error::CliError,
-    guardrails::provider::{
-        GuardrailProvider, GuardrailResult, ProviderSpecificResult, Severity, Violation,
+    guardrails::{
+        catalog::namespace,
+        provider::{
+            GuardrailProvider, GuardrailResult, OutputContext, ProviderSpecificResult, Severity,
+            Violation,
+        },
     },
     provider::InvokeParams,
 };
@@ -114,12 +118,54 @@ impl LlamaGuardCategory {
     }
 }
 
+/// Organization-specific category added to the MLCommons set
+///
+/// Rendered into the Llama Guard prompt's category block, e.g.
+/// `S15: Export Control.` followed by the description.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LlamaGuardCustomCategory {
+    /// Category code the model reports (e.g., "S15"); must not reuse S1-S14
+    pub code: String,
+    pub title: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub description: String,
+}
+
+impl LlamaGuardCustomCategory {
+    /// Check codes are unique and don't shadow the built-in categories
+    pub fn validate_all(categories: &[Self]) -> Result<(), CliError> {
+        let mut seen = std::collections::HashSet::new();
+        for category in categories {
+            let code = category.code.trim().to_uppercase();
+            if code.is_empty() || category.title.trim().is_empty() {
+                return Err(CliError::InvalidArguments(
+                    "Llama Guard custom categories need a code and a title".to_string(),
+                ));
+            }
+            if LlamaGuardCategory::parse(&code).is_ok() {
+                return Err(CliError::InvalidArguments(format!(
+                    "Llama Guard custom category '{code}' conflicts with a built-in category"
+                )));
+            }
+            if !seen.insert(code.clone()) {
+                return Err(CliError::InvalidArguments(format!(
+                    "Duplicate Llama Guard custom category '{code}'"
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
 /// Configuration for Llama Guard 3
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LlamaGuardConfig {
     pub api_url: String,
     pub model: String, // e.g., "llama-guard-3:8b", "llama-guard-3:1b"
     pub enabled_categories: Vec<LlamaGuardCategory>,
+    /// Extra categories; when set, the full Llama Guard prompt is sent
+    #[serde(default)]
+    pub custom_categories: Vec<LlamaGuardCustomCategory>,
     pub timeout_secs: u64,
     pub api_key: Option<String>,
 }
@@ -130,6 +176,7 @@ impl Default for LlamaGuardConfig {
             api_url: "http://localhost:11434/api/generate".to_string(),
             model: "llama-guard-3:8b".to_string(),
             enabled_categories: LlamaGuardCategory::all(),
+            custom_categories: Vec::new(),
             timeout_secs: 30,
             api_key: None,
         }
@@ -168,20 +215,32 @@ impl LlamaGuardProvider {
             vec![]
         };
 
-        // Filter violations to only enabled categories
-        let filtered_violations: Vec<LlamaGuardCategory> = violated_categories
+        // Filter violations to only enabled categories, keeping custom ones
+        let filtered_violations: Vec<(String, String)> = violated_categories
             .into_iter()
-            .filter(|cat| self.config.enabled_categories.contains(cat))
+            .filter_map(|code| match LlamaGuardCategory::parse(&code) {
+                Ok(cat) => self
+                    .config
+                    .enabled_categories
+                    .contains(&cat)
+                    .then(|| (cat.as_str().to_string(), cat.description().to_string())),
+                Err(_) => self
+                    .custom_category(&code)
+                    .map(|custom| (code.clone(), custom.title.clone())),
+            })
             .collect();
 
         // Convert to generic GuardrailResult
         let violations = filtered_violations
             .iter()
-            .map(|cat| Violation {
-                rule: cat.as_str().to_string(),
+            .map(|(code, title)| Violation {
+                namespace: namespace::LLAMA_GUARD.to_string(),
+                rule: code.clone(),
                 severity: Severity::Critical,
-                message: format!("Llama Guard violation: {}", cat.description()),
+                message: format!("Llama Guard violation: {title}"),
                 location: None,
+                source: None,
+                preview: None,
             })
             .collect();
 
@@ -190,7 +249,7 @@ impl LlamaGuardProvider {
             safe: safe || filtered_violations.is_empty(), // Safe if no enabled categories violated
             violated_categories: filtered_violations
                 .iter()
-                .map(|c| c.as_str().to_string())
+                .map(|(code, _)| code.clone())
                 .collect(),
             raw_response: response.to_string(),
         };
@@ -201,41 +260,123 @@ impl LlamaGuardProvider {
             warnings: vec![],
             quality_score: None, // Llama Guard is binary (no confidence scores)
             provider_specific: Some(ProviderSpecificResult::LlamaGuard(llama_result)),
+            provider_results: Vec::new(),
+            explanation: None,
         })
     }
 
-    /// Parse comma-separated categories from response line
-    fn parse_categories(&self, line: &str) -> Result<Vec<LlamaGuardCategory>, CliError> {
+    /// Parse comma-separated category codes from response line
+    ///
+    /// Codes must be built-in or configured custom categories.
+    fn parse_categories(&self, line: &str) -> Result<Vec<String>, CliError> {
         line.split(',')
-            .map(|s| s.trim())
+            .map(|s| s.trim().to_uppercase())
             .filter(|s| !s.is_empty())
-            .map(LlamaGuardCategory::parse)
+            .map(|code| {
+                if self.custom_category(&code).is_some() {
+                    Ok(code)
+                } else {
+                    LlamaGuardCategory::parse(&code).map(|cat| cat.as_str().to_string())
+                }
+            })
             .collect()
     }
-}
 
-#[async_trait]
-impl GuardrailProvider for LlamaGuardProvider {
-    async fn validate(&self, content: &str) -> Result<GuardrailResult, CliError> {
+    fn custom_category(&self, code: &str) -> Option<&LlamaGuardCustomCategory> {
+        self.config
+            .custom_categories
+            .iter()
+            .find(|c| c.code.trim().eq_ignore_ascii_case(code))
+    }
+
+    /// Full Llama Guard 3 task prompt with the enabled and custom categories
+    ///
+    /// Only used with custom categories: the default chat template already
+    /// embeds the MLCommons block, so plain content is sent otherwise. With a
+    /// user prompt, `content` is classified as the Agent's reply to it.
+    fn render_prompt(&self, content: &str, user_prompt: Option<&str>) -> String {
+        let mut categories: Vec<String> = self
+            .config
+            .enabled_categories
+            .iter()
+            .map(|cat| format!("{}: {}.", cat.as_str(), cat.description()))
+            .collect();
+        categories.extend(self.config.custom_categories.iter().map(|custom| {
+            let mut line = format!("{}: {}.", custom.code.trim().to_uppercase(), custom.title);
+            if !custom.description.is_empty() {
+                line.push('\n');
+                line.push_str(&custom.description);
+            }
+            line
+        }));
+
+        let (role, conversation) = match user_prompt {
+            Some(user_prompt) => ("Agent", format!("User: {user_prompt}\n\nAgent: {content}")),
+            None => ("User", format!("User: {content}")),
+        };
+
+        format!(
+            "Task: Check if there is unsafe content in '{role}' messages in conversations \
+             according our safety policy with the below categories.\n\n\
+             <BEGIN UNSAFE CONTENT CATEGORIES>\n{}\n<END UNSAFE CONTENT CATEGORIES>\n\n\
+             <BEGIN CONVERSATION>\n\n{conversation}\n\n<END CONVERSATION>\n\n\
+             Provide your safety assessment for ONLY THE LAST {role} message in the above \
+             conversation:\n - First line must read 'safe' or 'unsafe'.\n - If unsafe, a \
+             second line must include a comma-separated list of violated categories.",
+            categories.join("\n")
+        )
+    }
+
+    async fn classify(
+        &self,
+        content: &str,
+        user_prompt: Option<&str>,
+    ) -> Result<GuardrailResult, CliError> {
         // Llama Guard 3 is fine-tuned for safety classification and doesn't need
-        // elaborate prompting. Just pass the raw content with an empty system prompt.
+        // elaborate prompting. Just pass the raw content with an empty system prompt,
+        // unless custom categories must be spelled out in the category block.
+        let prompt = if self.config.custom_categories.is_empty() {
+            None
+        } else {
+            Some(self.render_prompt(content, user_prompt))
+        };
         let response = self
             .client
             .invoke(InvokeParams {
                 model: &self.config.model,
                 system_prompt: "", // Empty system prompt - model has built-in safety policy
-                user_prompt: content, // Raw content to evaluate
+                user_prompt: prompt.as_deref().unwrap_or(content), // Content to evaluate
                 temperature: 0.0,  // Temperature 0 for deterministic safety checks
+                top_p: None,       // Provider default
                 max_tokens: Some(100), // Short response: "safe" or "unsafe\nS1,S3"
                 seed: None,        // No seed needed for guardrails
                 api_key: self.config.api_key.as_deref(),
                 timeout_secs: self.config.timeout_secs,
                 response_format: None, // No response_format needed for guardrails
+                tools: &[],
+                images: &[],
             })
             .await?;
 
         self.parse_response(&response)
     }
+}
+
+#[async_trait]
+impl GuardrailProvider for LlamaGuardProvider {
+    async fn validate(&self, content: &str) -> Result<GuardrailResult, CliError> {
+        self.classify(content, None).await
+    }
+
+    /// Classifies the response as the Agent turn when the prompt is known
+    async fn validate_output(
+        &self,
+        response: &str,
+        context: &OutputContext,
+    ) -> Result<GuardrailResult, CliError> {
+        self.classify(response, context.user_prompt.as_deref())
+            .await
+    }
 
     fn name(&self) -> &str {
         "LlamaGuard3"
@@ -374,8 +515,9 @@ mod tests {
 
         let categories = provider.parse_categories("S1,S9").unwrap();
         assert_eq!(categories.len(), 2);
-        assert_eq!(categories[0], LlamaGuardCategory::S1);
-        assert_eq!(categories[1], LlamaGuardCategory::S9);
+        assert_eq!(categories[0], "S1");
+        assert_eq!(categories[1], "S9");
+        assert!(provider.parse_categories("S15").is_err());
     }
 
     #[test]
@@ -439,4 +581,66 @@ mod tests {
             .message
             .contains("Code Interpreter Abuse"));
     }
+
+    fn export_control() -> LlamaGuardCustomCategory {
+        LlamaGuardCustomCategory {
+            code: "S15".to_string(),
+            title: "Export Control".to_string(),
+            description: "Requests to ship controlled technology to embargoed countries."
+                .to_string(),
+        }
+    }
+
+    #[test]
+    fn test_custom_category_violation() {
+        let config = LlamaGuardConfig {
+            enabled_categories: vec![LlamaGuardCategory::S1],
+            custom_categories: vec![export_control()],
+            ..Default::default()
+        };
+        let provider = LlamaGuardProvider::new(config);
+
+        let result = provider.parse_response("unsafe\ns15,S1").unwrap();
+        assert!(!result.passed);
+        assert_eq!(result.violations.len(), 2);
+        assert_eq!(result.violations[0].rule, "S15");
+        assert_eq!(result.violations[0].id(), "llama_guard.S15");
+        assert!(result.violations[0].message.contains("Export Control"));
+    }
+
+    #[test]
+    fn test_render_prompt_lists_custom_categories() {
+        let config = LlamaGuardConfig {
+            enabled_categories: vec![LlamaGuardCategory::S1, LlamaGuardCategory::S9],
+            custom_categories: vec![export_control()],
+            ..Default::default()
+        };
+        let provider = LlamaGuardProvider::new(config);
+
+        let prompt = provider.render_prompt("Ship GPUs to X", None);
+        assert!(prompt.contains(
+            "S1: Violent Crimes.\nS9: Indiscriminate Weapons (CBRNE).\nS15: Export Control.\n\
+             Requests to ship"
+        ));
+        assert!(prompt.contains("User: Ship GPUs to X"));
+        assert!(!prompt.contains("S2:"));
+
+        let prompt = provider.render_prompt("Sure, here is how", Some("Ship GPUs to X"));
+        assert!(prompt.contains("User: Ship GPUs to X\n\nAgent: Sure, here is how"));
+        assert!(prompt.contains("ONLY THE LAST Agent message"));
+    }
+
+    #[test]
+    fn test_custom_category_validation() {
+        assert!(LlamaGuardCustomCategory::validate_all(&[export_control()]).is_ok());
+
+        let builtin = LlamaGuardCustomCategory {
+            code: "s3".to_string(),
+            ..export_control()
+        };
+        assert!(LlamaGuardCustomCategory::validate_all(&[builtin]).is_err());
+        assert!(
+            LlamaGuardCustomCategory::validate_all(&[export_control(), export_control()]).is_err()
+        );
+    }
 }
@@ -1,12 +1,33 @@
+//! Llama Prompt Guard 2 provider
+//!
+//! The classifier reads about 512 tokens, so longer input is scanned in
+//! overlapping windows split on UTF-8 character boundaries:
+//!
+//! ```toml
+//! [guardrails.input]
+//! type = "llama_prompt_guard"
+//! max_scan_bytes = 8192        # Input scanned (default: 2048, a single window)
+//! window_bytes = 2048          # Bytes per classifier request (default: 2048)
+//! window_overlap_bytes = 256   # Bytes shared by consecutive windows (default: 256)
+//! ```
+//!
+//! Scanning stops at the first window classified malicious. The
+//! provider-specific result reports how much of the input was scanned, since a
+//! verdict only covers the first `max_scan_bytes`.
+
 use crate::{
     client::LlmClient,
     error::CliError,
-    guardrails::provider::{GuardrailProvider, GuardrailResult, Severity, Violation},
+    guardrails::{
+        catalog::namespace,
+        provider::{GuardrailProvider, GuardrailResult, Severity, Violation},
+    },
     provider::InvokeParams,
 };
 use async_trait::async_trait;
 use regex::Regex;
 use serde::{Deserialize, Serialize};
+use std::ops::Range;
 
 /// Configuration for Llama Prompt Guard 2
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -36,12 +57,36 @@ pub struct LlamaPromptGuardConfig {
     /// Optional API key environment variable name
     #[serde(skip_serializing_if = "Option::is_none")]
     pub api_key_name: Option<String>,
+
+    /// Leading bytes of the input that are scanned (default: 2048)
+    #[serde(default = "default_max_scan_bytes")]
+    pub max_scan_bytes: usize,
+
+    /// Bytes sent per classifier request, ~512 tokens (default: 2048)
+    #[serde(default = "default_window_bytes")]
+    pub window_bytes: usize,
+
+    /// Bytes repeated at the start of the next window (default: 256)
+    #[serde(default = "default_window_overlap_bytes")]
+    pub window_overlap_bytes: usize,
 }
 
 fn default_threshold() -> f32 {
     0.5
 }
 
+pub(crate) fn default_max_scan_bytes() -> usize {
+    2048
+}
+
+pub(crate) fn default_window_bytes() -> usize {
+    2048
+}
+
+pub(crate) fn default_window_overlap_bytes() -> usize {
+    256
+}
+
 impl Default for LlamaPromptGuardConfig {
     fn default() -> Self {
         Self {
@@ -51,6 +96,9 @@ impl Default for LlamaPromptGuardConfig {
             threshold: 0.5,
             api_key: None,
             api_key_name: None,
+            max_scan_bytes: default_max_scan_bytes(),
+            window_bytes: default_window_bytes(),
+            window_overlap_bytes: default_window_overlap_bytes(),
         }
     }
 }
@@ -66,6 +114,15 @@ pub struct LlamaPromptGuardResult {
     pub label: String,
     /// Raw response from model
     pub raw_response: String,
+    /// Bytes of the input covered by the verdict
+    #[serde(default)]
+    pub scanned_bytes: usize,
+    /// Size of the input
+    #[serde(default)]
+    pub total_bytes: usize,
+    /// Classifier requests made
+    #[serde(default)]
+    pub windows_scanned: usize,
 }
 
 /// Llama Prompt Guard 2 provider for prompt injection detection
@@ -106,6 +163,7 @@ impl LlamaPromptGuardProvider {
         // Create violations
         let violations = if !passed {
             vec![Violation {
+                namespace: namespace::PROMPT_GUARD.to_string(),
                 rule: "PROMPT_INJECTION".to_string(),
                 severity: Severity::Critical,
                 message: format!(
@@ -114,6 +172,8 @@ impl LlamaPromptGuardProvider {
                     self.config.threshold
                 ),
                 location: None,
+                source: None,
+                preview: None,
             }]
         } else {
             vec![]
@@ -125,6 +185,9 @@ impl LlamaPromptGuardProvider {
             confidence,
             label: if is_malicious { "MALICIOUS" } else { "BENIGN" }.to_string(),
             raw_response: response.to_string(),
+            scanned_bytes: 0,
+            total_bytes: 0,
+            windows_scanned: 0,
         };
 
         Ok(GuardrailResult {
@@ -137,6 +200,8 @@ impl LlamaPromptGuardProvider {
                     prompt_guard_result,
                 ),
             ),
+            provider_results: Vec::new(),
+            explanation: None,
         })
     }
 
@@ -169,38 +234,132 @@ impl LlamaPromptGuardProvider {
     }
 }
 
+/// Largest char boundary of `content` at or before `index`
+fn floor_char_boundary(content: &str, index: usize) -> usize {
+    let mut index = index.min(content.len());
+    while !content.is_char_boundary(index) {
+        index -= 1;
+    }
+    index
+}
+
+/// Byte ranges of the windows covering the first `max_scan` bytes of
+/// `content`, each at most `window` bytes and overlapping the previous one by
+/// up to `overlap` bytes, all on char boundaries
+fn scan_windows(
+    content: &str,
+    max_scan: usize,
+    window: usize,
+    overlap: usize,
+) -> Vec<Range<usize>> {
+    let scan_end = floor_char_boundary(content, max_scan);
+    let mut windows = Vec::new();
+    let mut start = 0;
+    loop {
+        let mut end = floor_char_boundary(content, (start + window).min(scan_end));
+        if end <= start && start < scan_end {
+            // A window smaller than one character still advances by one
+            end = start + content[start..].chars().next().map_or(0, char::len_utf8);
+        }
+        windows.push(start..end);
+        if end >= scan_end {
+            return windows;
+        }
+        let next = floor_char_boundary(content, end.saturating_sub(overlap));
+        start = if next > start { next } else { end };
+    }
+}
+
+/// Malicious-label confidence of a window verdict (0.0 when benign)
+fn malicious_confidence(result: &GuardrailResult) -> f32 {
+    match &result.provider_specific {
+        Some(crate::guardrails::provider::ProviderSpecificResult::LlamaPromptGuard(verdict))
+            if verdict.malicious =>
+        {
+            verdict.confidence
+        }
+        _ => 0.0,
+    }
+}
+
 #[async_trait]
 impl GuardrailProvider for LlamaPromptGuardProvider {
     async fn validate(&self, content: &str) -> Result<GuardrailResult, CliError> {
-        // Truncate if exceeds 512 tokens (~2048 chars)
-        let truncated = if content.len() > 2048 {
-            log::warn!("Content exceeds 512 tokens, truncating for Prompt Guard validation");
-            &content[..2048]
-        } else {
-            content
-        };
+        let windows = scan_windows(
+            content,
+            self.config.max_scan_bytes,
+            self.config.window_bytes,
+            self.config.window_overlap_bytes,
+        );
+        let scan_end = windows.last().map_or(0, |window| window.end);
+        if scan_end < content.len() {
+            log::warn!(
+                "Prompt Guard scans {scan_end} of {} bytes (max_scan_bytes)",
+                content.len()
+            );
+        }
 
+        // Keep the first blocking window, or else the most suspicious one
+        let mut verdict: Option<GuardrailResult> = None;
+        let (mut windows_scanned, mut scanned_bytes) = (0, 0);
+        for window in windows {
+            let mut result = self.classify(&content[window.clone()]).await?;
+            windows_scanned += 1;
+            scanned_bytes = window.end;
+            if !result.passed {
+                for violation in &mut result.violations {
+                    violation.location = Some(format!("bytes {}-{}", window.start, window.end));
+                }
+                verdict = Some(result);
+                break;
+            }
+            if verdict
+                .as_ref()
+                .is_none_or(|best| malicious_confidence(&result) > malicious_confidence(best))
+            {
+                verdict = Some(result);
+            }
+        }
+
+        let mut verdict = verdict.expect("scan_windows returns at least one window");
+        if let Some(crate::guardrails::provider::ProviderSpecificResult::LlamaPromptGuard(result)) =
+            &mut verdict.provider_specific
+        {
+            result.scanned_bytes = scanned_bytes;
+            result.total_bytes = content.len();
+            result.windows_scanned = windows_scanned;
+        }
+        Ok(verdict)
+    }
+
+    fn name(&self) -> &str {
+        "LlamaPromptGuard2"
+    }
+}
+
+impl LlamaPromptGuardProvider {
+    /// Classify one window
+    async fn classify(&self, window: &str) -> Result<GuardrailResult, CliError> {
         let response = self
             .client
             .invoke(InvokeParams {
                 model: &self.config.model,
                 system_prompt: "", // Empty - classifier doesn't need system prompt
-                user_prompt: truncated,
-                temperature: 0.0,     // Deterministic classification
+                user_prompt: window,
+                temperature: 0.0, // Deterministic classification
+                top_p: None,
                 max_tokens: Some(50), // Short response
                 seed: None,
                 api_key: self.config.api_key.as_deref(),
                 timeout_secs: self.config.timeout_secs,
                 response_format: None,
+                tools: &[],
+                images: &[],
             })
             .await?;
 
         self.parse_response(&response)
     }
-
-    fn name(&self) -> &str {
-        "LlamaPromptGuard2"
-    }
 }
 
 #[cfg(test)]
@@ -226,6 +385,7 @@ mod tests {
         assert!(!result.passed);
         assert_eq!(result.violations.len(), 1);
         assert_eq!(result.violations[0].rule, "PROMPT_INJECTION");
+        assert_eq!(result.violations[0].id(), "prompt_guard.PROMPT_INJECTION");
     }
 
     #[test]
@@ -321,6 +481,24 @@ mod tests {
         assert_eq!(provider.extract_confidence("MALICIOUS"), None);
     }
 
+    #[test]
+    fn test_scan_windows_respect_char_boundaries() {
+        // "é" is 2 bytes: byte 5 falls inside the third one
+        let content = "ééééé";
+        assert_eq!(scan_windows(content, 5, 2048, 256), vec![0..4]);
+
+        let windows = scan_windows(content, 10, 5, 2);
+        assert_eq!(windows, vec![0..4, 2..6, 4..8, 6..10]);
+        for window in &windows {
+            assert!(std::str::from_utf8(&content.as_bytes()[window.clone()]).is_ok());
+        }
+
+        // Windows narrower than a character still make progress
+        assert_eq!(scan_windows("日本", 100, 1, 0), vec![0..3, 3..6]);
+        assert_eq!(scan_windows("", 2048, 2048, 256), vec![0..0]);
+        assert_eq!(scan_windows("short", 2048, 2048, 256), vec![0..5]);
+    }
+
     #[test]
     fn test_invalid_response() {
         let config = LlamaPromptGuardConfig::default();
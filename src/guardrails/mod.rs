@@ -1,30 +1,73 @@
+pub mod budget;
+pub mod builder;
+pub mod catalog;
+pub mod conditions;
 pub mod config;
+pub mod entropy;
+pub mod escalation;
 pub mod gpt_oss_safeguard;
+pub mod hash_list;
 pub mod hybrid;
+pub mod image;
+pub mod json_schema;
+pub mod language;
 pub mod llama_guard;
 pub mod llama_prompt_guard;
+pub mod nemo_guardrails;
+pub mod numeric;
+pub mod overrides;
 pub mod patterns;
+pub mod presets;
 pub mod provider;
 pub mod regex;
+pub mod semantic_similarity;
+pub mod terminology;
 
 // Re-export core trait types
 pub use provider::{
     GptOssSafeguardResult, GuardrailProvider, GuardrailResult, LlamaGuardResult,
-    ProviderSpecificResult, Severity, Violation,
+    NamedProviderResult, OutputContext, ProviderSpecificResult, Redaction, Severity, Violation,
+    ViolationPreview,
+};
+
+pub use budget::{
+    BudgetAction, EndpointBudgetConfig, GuardBudget, GuardBudgetConfig, GuardMeter, GuardTokenUsage,
+};
+pub use builder::{GuardrailConfigBuilder, IntoGuardrailConfig};
+pub use catalog::{rule_catalog, RuleInfo};
+pub use conditions::{
+    apply_conditions, Condition, ConditionalGuardrail, GuardrailStage, InputFacts,
 };
 
 // Re-export concrete implementations
 pub use config::{
-    create_guardrail_provider, AggregationMode, ExecutionMode, GuardrailConfig,
-    GuardrailProviderConfig, RegexGuardrailConfig,
+    create_guardrail_provider, create_metered_guardrail_provider, AggregationMode, ExecutionMode,
+    GuardrailConfig, GuardrailProviderConfig, InputSourcesConfig, RegexAction,
+    RegexGuardrailConfig,
 };
+pub use escalation::{EscalationConfig, EscalationRecord};
 pub use gpt_oss_safeguard::{GptOssSafeguardConfig, GptOssSafeguardProvider};
-pub use hybrid::HybridGuardrail;
-pub use llama_guard::{LlamaGuardCategory, LlamaGuardConfig, LlamaGuardProvider};
+pub use hash_list::{HashListConfig, HashListGuardrail, HashListMatch, HashListResult};
+pub use hybrid::{HybridGuardrail, ProviderVote, VoteResult};
+pub use image::{ImageGuardrail, ImageGuardrailConfig, ImageScannerConfig};
+pub use json_schema::{JsonSchemaConfig, JsonSchemaGuardrail};
+pub use language::{LanguageConfig, LanguageGuardrail};
+pub use llama_guard::{
+    LlamaGuardCategory, LlamaGuardConfig, LlamaGuardCustomCategory, LlamaGuardProvider,
+};
 pub use llama_prompt_guard::{
     LlamaPromptGuardConfig, LlamaPromptGuardProvider, LlamaPromptGuardResult,
 };
+pub use nemo_guardrails::{NemoGuardrailsConfig, NemoGuardrailsProvider, NemoGuardrailsResult};
+pub use numeric::{NumericConsistencyConfig, NumericConsistencyGuardrail};
+pub use overrides::{GuardrailOverride, OverridePolicyConfig};
+pub use patterns::BuiltinPatterns;
+pub use presets::GuardrailPreset;
 pub use regex::RegexGuardrail;
+pub use semantic_similarity::{
+    SemanticSimilarityConfig, SemanticSimilarityGuardrail, SemanticSimilarityResult,
+};
+pub use terminology::{TerminologyConfig, TerminologyGuardrail};
 
 // Type aliases
 /// Type alias for RegexGuardrail used for input validation
@@ -0,0 +1,281 @@
+//! NVIDIA NeMo Guardrails provider
+//!
+//! Delegates checks to a NeMo Guardrails server. Rails are defined in a
+//! server-side configuration selected by `config_id`; this provider only asks
+//! the server to run them:
+//!
+//! - input: the user message with only input rails enabled
+//! - output: the user message and response with only output rails enabled
+//!
+//! Requests set `options.log.activated_rails`, and content is blocked when an
+//! activated rail stopped processing (`"stop": true`). Each stopping rail is
+//! reported as a Critical violation named after the rail (e.g.
+//! `nemo.SELF_CHECK_INPUT`). Requires a server with generation options
+//! support (NeMo Guardrails 0.8 or later).
+
+use crate::{
+    cassette,
+    error::CliError,
+    guardrails::{
+        catalog::namespace,
+        provider::{
+            GuardrailProvider, GuardrailResult, OutputContext, ProviderSpecificResult, Severity,
+            Violation,
+        },
+    },
+};
+use async_trait::async_trait;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::time::Duration;
+
+/// Configuration for a NeMo Guardrails server
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NemoGuardrailsConfig {
+    /// Chat completions endpoint of the server
+    pub api_url: String,
+    /// Server-side guardrails configuration to apply
+    pub config_id: String,
+    pub timeout_secs: u64,
+    pub api_key: Option<String>,
+}
+
+impl Default for NemoGuardrailsConfig {
+    fn default() -> Self {
+        Self {
+            api_url: "http://localhost:8000/v1/chat/completions".to_string(),
+            config_id: "default".to_string(),
+            timeout_secs: 30,
+            api_key: None,
+        }
+    }
+}
+
+/// NeMo Guardrails specific result
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NemoGuardrailsResult {
+    /// Rails that stopped processing
+    pub blocking_rails: Vec<String>,
+    /// All rails the server ran
+    pub activated_rails: Vec<String>,
+    /// Message the server would have returned instead of the content
+    pub bot_message: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct NemoResponse {
+    #[serde(default)]
+    messages: Vec<NemoMessage>,
+    log: Option<NemoLog>,
+}
+
+#[derive(Debug, Deserialize)]
+struct NemoMessage {
+    #[serde(default)]
+    content: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct NemoLog {
+    #[serde(default)]
+    activated_rails: Vec<ActivatedRail>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ActivatedRail {
+    #[serde(rename = "type", default)]
+    rail_type: String,
+    name: String,
+    #[serde(default)]
+    stop: bool,
+}
+
+/// NeMo Guardrails server provider
+pub struct NemoGuardrailsProvider {
+    client: Client,
+    config: NemoGuardrailsConfig,
+}
+
+impl NemoGuardrailsProvider {
+    pub fn new(config: NemoGuardrailsConfig) -> Self {
+        Self {
+            client: Client::new(),
+            config,
+        }
+    }
+
+    /// Run the input or output rails of the configured server config
+    async fn check(
+        &self,
+        messages: serde_json::Value,
+        output: bool,
+    ) -> Result<GuardrailResult, CliError> {
+        let body = json!({
+            "config_id": self.config.config_id,
+            "messages": messages,
+            "options": {
+                "rails": {
+                    "input": !output,
+                    "output": output,
+                    "dialog": false,
+                    "retrieval": false,
+                },
+                "log": {"activated_rails": true},
+            },
+        });
+        let mut request = self
+            .client
+            .post(&self.config.api_url)
+            .timeout(Duration::from_secs(self.config.timeout_secs))
+            .json(&body);
+        if let Some(key) = &self.config.api_key {
+            request = request.header("Authorization", format!("Bearer {key}"));
+        }
+
+        let response = cassette::send(request, self.config.api_key.as_deref()).await?;
+        if response.status == 401 {
+            return Err(CliError::AuthenticationFailed(
+                "NeMo Guardrails server rejected the API key".to_string(),
+            ));
+        }
+        if !response.status.is_success() {
+            return Err(CliError::InvalidResponse(format!(
+                "NeMo Guardrails server returned HTTP {}: {}",
+                response.status.as_u16(),
+                response.body
+            )));
+        }
+        self.parse_response(&response.body, if output { "output" } else { "input" })
+    }
+
+    fn parse_response(&self, body: &str, direction: &str) -> Result<GuardrailResult, CliError> {
+        let response: NemoResponse = serde_json::from_str(body).map_err(|e| {
+            CliError::InvalidResponse(format!("Failed to parse NeMo Guardrails response: {e}"))
+        })?;
+        let log = response.log.ok_or_else(|| {
+            CliError::InvalidResponse(
+                "NeMo Guardrails response has no activated rails log \
+                 (server without generation options support?)"
+                    .to_string(),
+            )
+        })?;
+
+        let bot_message = response.messages.into_iter().last().map(|m| m.content);
+        let blocking: Vec<&ActivatedRail> = log.activated_rails.iter().filter(|r| r.stop).collect();
+        let violations = blocking
+            .iter()
+            .map(|rail| Violation {
+                namespace: namespace::NEMO.to_string(),
+                rule: rule_name(&rail.name),
+                severity: Severity::Critical,
+                message: format!(
+                    "NeMo Guardrails {} rail '{}' blocked the {direction}",
+                    rail.rail_type, rail.name
+                ),
+                location: None,
+                source: None,
+                preview: None,
+            })
+            .collect();
+
+        let result = NemoGuardrailsResult {
+            blocking_rails: blocking.iter().map(|r| r.name.clone()).collect(),
+            activated_rails: log.activated_rails.iter().map(|r| r.name.clone()).collect(),
+            bot_message,
+        };
+        Ok(GuardrailResult::with_provider_specific(
+            result.blocking_rails.is_empty(),
+            violations,
+            Vec::new(),
+            ProviderSpecificResult::NemoGuardrails(result),
+        ))
+    }
+}
+
+/// Rail name as a rule (e.g. "self check input" -> "SELF_CHECK_INPUT")
+fn rule_name(rail: &str) -> String {
+    rail.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_uppercase)
+        .collect::<Vec<_>>()
+        .join("_")
+}
+
+#[async_trait]
+impl GuardrailProvider for NemoGuardrailsProvider {
+    async fn validate(&self, content: &str) -> Result<GuardrailResult, CliError> {
+        self.check(json!([{"role": "user", "content": content}]), false)
+            .await
+    }
+
+    async fn validate_output(
+        &self,
+        response: &str,
+        context: &OutputContext,
+    ) -> Result<GuardrailResult, CliError> {
+        let user_prompt = context.user_prompt.as_deref().unwrap_or_default();
+        self.check(
+            json!([
+                {"role": "user", "content": user_prompt},
+                {"role": "assistant", "content": response},
+            ]),
+            true,
+        )
+        .await
+    }
+
+    fn name(&self) -> &str {
+        "NemoGuardrails"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_response_blocking_rail() {
+        let provider = NemoGuardrailsProvider::new(NemoGuardrailsConfig::default());
+        let body = r#"{
+            "messages": [{"role": "assistant", "content": "I'm sorry, I can't respond to that."}],
+            "log": {"activated_rails": [
+                {"type": "input", "name": "mask sensitive data on input", "stop": false},
+                {"type": "input", "name": "self check input", "stop": true}
+            ]}
+        }"#;
+
+        let result = provider.parse_response(body, "input").unwrap();
+        assert!(!result.passed);
+        assert_eq!(result.violations.len(), 1);
+        assert_eq!(result.violations[0].id(), "nemo.SELF_CHECK_INPUT");
+        assert_eq!(result.violations[0].severity, Severity::Critical);
+        match result.provider_specific {
+            Some(ProviderSpecificResult::NemoGuardrails(nemo)) => {
+                assert_eq!(nemo.activated_rails.len(), 2);
+                assert_eq!(nemo.blocking_rails, vec!["self check input"]);
+            }
+            other => panic!("Expected NemoGuardrails result, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_parse_response_requires_rails_log() {
+        let provider = NemoGuardrailsProvider::new(NemoGuardrailsConfig::default());
+        let passed = provider
+            .parse_response(
+                r#"{"messages": [], "log": {"activated_rails": []}}"#,
+                "input",
+            )
+            .unwrap();
+        assert!(passed.passed);
+
+        let err = provider
+            .parse_response(
+                r#"{"messages": [{"role": "assistant", "content": "Hi"}]}"#,
+                "input",
+            )
+            .unwrap_err();
+        assert!(err.to_string().contains("activated rails log"), "{err}");
+    }
+}
@@ -0,0 +1,421 @@
+//! Numeric consistency guardrail
+//!
+//! Cheap anti-hallucination check for document summaries: every number and
+//! date in the response must also appear in the source text (the user
+//! prompt, including extracted PDF text and untrusted content). Formatting
+//! differences are tolerated: thousands separators, currency symbols, scale
+//! words ("1.2 million", "$3bn") and rounding to the precision the response
+//! uses. Dates match across ISO and written forms ("2024-03-05" and
+//! "March 5, 2024").
+//!
+//! ```toml
+//! [guardrails.output]
+//! type = "numeric_consistency"
+//! check_dates = true             # Default
+//! ignore_integers_below = 10     # Skip small counts like "3 reasons" (default)
+//! relative_tolerance = 0.0       # Extra tolerance beyond rounding (default)
+//! severity_threshold = "Medium"  # Block invented figures (default: High = warn)
+//! ```
+//!
+//! Output-only: without a source document there is nothing to compare.
+
+use crate::{
+    error::CliError,
+    guardrails::{
+        catalog::namespace,
+        provider::{
+            line_column, GuardrailProvider, GuardrailResult, OutputContext, Severity, Violation,
+        },
+    },
+};
+use async_trait::async_trait;
+use chrono::NaiveDate;
+use once_cell::sync::Lazy;
+use regex::{Captures, Regex};
+use serde::{Deserialize, Serialize};
+use std::ops::Range;
+
+/// Rule names reported in `Violation::rule`
+pub mod rules {
+    pub const UNSUPPORTED_NUMBER: &str = "UNSUPPORTED_NUMBER";
+    pub const UNSUPPORTED_DATE: &str = "UNSUPPORTED_DATE";
+}
+
+/// Both rules report a figure the source does not contain
+pub(crate) const RULE_SEVERITY: Severity = Severity::Medium;
+
+/// Number with optional currency, decimals, percent and scale suffix
+static NUMBER: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"(?x)
+        (?P<currency>[$€£¥]\s?)?
+        (?P<int>\d{1,3}(?:,\d{3})+|\d+)
+        (?:\.(?P<frac>\d+))?
+        (?:
+            \s*(?P<word>%|(?i:percent|thousand|million|billion|trillion)\b)
+            | (?P<abbr>k|K|M|MM|mn|bn|B)\b
+        )?",
+    )
+    .expect("valid number regex")
+});
+
+const MONTHS: &str = r"(?P<month>jan(?:uary)?|feb(?:ruary)?|mar(?:ch)?|apr(?:il)?|may|june?|july?|aug(?:ust)?|sep(?:t(?:ember)?)?|oct(?:ober)?|nov(?:ember)?|dec(?:ember)?)";
+
+/// ISO and written dates ("2024-03-05", "March 5, 2024", "5 March 2024")
+static DATES: Lazy<[Regex; 3]> = Lazy::new(|| {
+    [
+        r"\b(?P<year>\d{4})-(?P<mon>\d{1,2})-(?P<day>\d{1,2})\b".to_string(),
+        format!(r"(?i)\b{MONTHS}\.?\s+(?P<day>\d{{1,2}})(?:st|nd|rd|th)?,?\s+(?P<year>\d{{4}})\b"),
+        format!(r"(?i)\b(?P<day>\d{{1,2}})(?:st|nd|rd|th)?\s+{MONTHS}\.?,?\s+(?P<year>\d{{4}})\b"),
+    ]
+    .map(|pattern| Regex::new(&pattern).expect("valid date regex"))
+});
+
+fn default_check_dates() -> bool {
+    true
+}
+
+fn default_ignore_integers_below() -> u64 {
+    10
+}
+
+fn default_severity_threshold() -> Severity {
+    // The check is heuristic: warn unless the threshold is lowered
+    Severity::High
+}
+
+/// Numeric consistency guardrail configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NumericConsistencyConfig {
+    /// Check dates as well as numbers (default: true)
+    #[serde(default = "default_check_dates")]
+    pub check_dates: bool,
+
+    /// Plain integers below this value (no decimals, unit or currency) are
+    /// not checked, so list counts do not trigger findings (default: 10)
+    #[serde(default = "default_ignore_integers_below")]
+    pub ignore_integers_below: u64,
+
+    /// Relative difference accepted on top of rounding, 0.0-1.0 (default: 0.0)
+    #[serde(default)]
+    pub relative_tolerance: f64,
+
+    /// Minimum severity that blocks; findings below it become warnings
+    /// (default: high, so findings are warnings)
+    #[serde(default = "default_severity_threshold")]
+    pub severity_threshold: Severity,
+}
+
+impl Default for NumericConsistencyConfig {
+    fn default() -> Self {
+        Self {
+            check_dates: default_check_dates(),
+            ignore_integers_below: default_ignore_integers_below(),
+            relative_tolerance: 0.0,
+            severity_threshold: default_severity_threshold(),
+        }
+    }
+}
+
+impl NumericConsistencyConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if !(0.0..1.0).contains(&self.relative_tolerance) {
+            return Err(format!(
+                "relative_tolerance {} must be >= 0.0 and < 1.0",
+                self.relative_tolerance
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// A number found in text
+#[derive(Debug, Clone, PartialEq)]
+struct Figure {
+    value: f64,
+    /// Precision the figure was written with (0.1 for "1.2", 100000 for "1.2 million")
+    step: f64,
+    /// No decimals, unit or currency
+    plain_integer: bool,
+    span: Range<usize>,
+}
+
+/// A date found in text
+#[derive(Debug, Clone, PartialEq)]
+struct DateMention {
+    date: NaiveDate,
+    span: Range<usize>,
+}
+
+fn scale(word: &str) -> f64 {
+    match word.to_lowercase().as_str() {
+        "thousand" | "k" => 1e3,
+        "million" | "m" | "mm" | "mn" => 1e6,
+        "billion" | "bn" | "b" => 1e9,
+        "trillion" => 1e12,
+        _ => 1.0, // % and percent
+    }
+}
+
+fn parse_figure(caps: &Captures) -> Option<Figure> {
+    let whole = caps.get(0)?;
+    let int = caps.name("int")?.as_str().replace(',', "");
+    let frac = caps.name("frac").map_or("", |m| m.as_str());
+    let unit = caps.name("word").or_else(|| caps.name("abbr"));
+    let scale = unit.map_or(1.0, |m| scale(m.as_str()));
+    let value: f64 = format!("{int}.{frac}0").parse().ok()?;
+    Some(Figure {
+        value: value * scale,
+        step: scale / 10f64.powi(frac.len() as i32),
+        plain_integer: frac.is_empty() && unit.is_none() && caps.name("currency").is_none(),
+        span: whole.start()..whole.end(),
+    })
+}
+
+/// Numbers in `text`, skipping digits glued to words ("GPT4") or versions ("1.2.3")
+fn figures(text: &str) -> Vec<Figure> {
+    NUMBER
+        .captures_iter(text)
+        .filter(|caps| {
+            let start = caps.name("currency").or(caps.name("int")).unwrap().start();
+            let mut before = text[..start].chars().rev();
+            match before.next() {
+                Some(c) if c.is_alphanumeric() || c == '_' => false,
+                Some('.') => !before.next().is_some_and(|c| c.is_ascii_digit()),
+                _ => true,
+            }
+        })
+        .filter_map(|caps| parse_figure(&caps))
+        .collect()
+}
+
+fn month_number(name: &str) -> Option<u32> {
+    let prefix = name.get(..3)?.to_lowercase();
+    let index = [
+        "jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec",
+    ]
+    .iter()
+    .position(|m| *m == prefix)?;
+    Some(index as u32 + 1)
+}
+
+fn dates(text: &str) -> Vec<DateMention> {
+    let mut found: Vec<DateMention> = DATES
+        .iter()
+        .flat_map(|regex| regex.captures_iter(text))
+        .filter_map(|caps| {
+            let year = caps["year"].parse().ok()?;
+            let month = match caps.name("mon") {
+                Some(m) => m.as_str().parse().ok()?,
+                None => month_number(&caps["month"])?,
+            };
+            let day = caps["day"].parse().ok()?;
+            let whole = caps.get(0)?;
+            Some(DateMention {
+                date: NaiveDate::from_ymd_opt(year, month, day)?,
+                span: whole.start()..whole.end(),
+            })
+        })
+        .collect();
+    found.sort_by_key(|d| d.span.start);
+    found
+}
+
+/// Source-grounding guardrail for numbers and dates
+pub struct NumericConsistencyGuardrail {
+    config: NumericConsistencyConfig,
+}
+
+impl NumericConsistencyGuardrail {
+    pub fn new(config: NumericConsistencyConfig) -> Self {
+        Self { config }
+    }
+
+    fn supported(&self, figure: &Figure, source: &[Figure]) -> bool {
+        source.iter().any(|s| {
+            let tolerance = figure.step / 2.0
+                + self.config.relative_tolerance * s.value.abs().max(figure.value.abs());
+            // Small epsilon absorbs float error in scaled values
+            (s.value - figure.value).abs() <= tolerance * (1.0 + 1e-9)
+        })
+    }
+
+    /// Findings for numbers and dates in `response` that `source` lacks
+    fn check(&self, response: &str, source: &str) -> Vec<Violation> {
+        let response_dates = dates(response);
+        let source_figures = figures(source);
+        let mut findings = Vec::new();
+
+        if self.config.check_dates {
+            let source_dates: Vec<NaiveDate> = dates(source).into_iter().map(|d| d.date).collect();
+            for mention in response_dates
+                .iter()
+                .filter(|d| !source_dates.contains(&d.date))
+            {
+                findings.push((
+                    mention.span.start,
+                    rules::UNSUPPORTED_DATE,
+                    format!(
+                        "Date '{}' does not appear in the source",
+                        &response[mention.span.clone()]
+                    ),
+                ));
+            }
+        }
+
+        // Numbers inside dates were handled above (or deliberately skipped)
+        let in_date = |span: &Range<usize>| {
+            response_dates
+                .iter()
+                .any(|d| span.start < d.span.end && d.span.start < span.end)
+        };
+        for figure in figures(response) {
+            let ignored =
+                figure.plain_integer && figure.value < self.config.ignore_integers_below as f64;
+            if ignored || in_date(&figure.span) || self.supported(&figure, &source_figures) {
+                continue;
+            }
+            findings.push((
+                figure.span.start,
+                rules::UNSUPPORTED_NUMBER,
+                format!(
+                    "Number '{}' does not appear in the source",
+                    response[figure.span.clone()].trim_end()
+                ),
+            ));
+        }
+
+        // Report in reading order
+        findings.sort_by_key(|(offset, ..)| *offset);
+        findings
+            .into_iter()
+            .map(|(offset, rule, message)| Violation {
+                namespace: namespace::NUMERIC.to_string(),
+                rule: rule.to_string(),
+                severity: RULE_SEVERITY,
+                message,
+                location: Some(line_column(response, offset)),
+                source: None,
+                preview: None,
+            })
+            .collect()
+    }
+}
+
+#[async_trait]
+impl GuardrailProvider for NumericConsistencyGuardrail {
+    async fn validate(&self, _content: &str) -> Result<GuardrailResult, CliError> {
+        log::warn!(
+            "Numeric consistency needs a source document; it only applies to output guardrails"
+        );
+        Ok(GuardrailResult::without_quality_score(
+            true,
+            Vec::new(),
+            Vec::new(),
+        ))
+    }
+
+    async fn validate_output(
+        &self,
+        response: &str,
+        context: &OutputContext,
+    ) -> Result<GuardrailResult, CliError> {
+        let Some(source) = context.user_prompt.as_deref() else {
+            return self.validate(response).await;
+        };
+        let (violations, warnings): (Vec<_>, Vec<_>) = self
+            .check(response, source)
+            .into_iter()
+            .partition(|f| f.severity >= self.config.severity_threshold);
+        Ok(GuardrailResult::without_quality_score(
+            violations.is_empty(),
+            violations,
+            warnings,
+        ))
+    }
+
+    fn name(&self) -> &str {
+        "NumericConsistencyGuardrail"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOURCE: &str = "Q3 revenue was $1,234,567.89, up 12.4% year over year.\n\
+        The agreement was signed on March 5, 2024 and runs for 36 months.\n\
+        Headcount: 2,150 employees across 3 regions.";
+
+    fn unsupported(response: &str) -> Vec<String> {
+        NumericConsistencyGuardrail::new(NumericConsistencyConfig::default())
+            .check(response, SOURCE)
+            .into_iter()
+            .map(|v| v.message)
+            .collect()
+    }
+
+    #[test]
+    fn test_reformatted_figures_are_supported() {
+        let response = "Revenue reached about $1.2 million (+12%), with 2150 staff in 3 regions. \
+            The contract dated 2024-03-05 lasts 36 months.";
+        assert_eq!(unsupported(response), Vec::<String>::new());
+    }
+
+    #[test]
+    fn test_invented_figures_are_flagged() {
+        let response = "Revenue was $1.5 million, up 12.45%.\nSigned on 5 April 2024.";
+        assert_eq!(
+            unsupported(response),
+            vec![
+                "Number '$1.5 million' does not appear in the source",
+                "Number '12.45%' does not appear in the source",
+                "Date '5 April 2024' does not appear in the source",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_figure_parsing() {
+        let parsed = figures("GPT4 costs $3bn, v1.2.3 and 45 percent");
+        let values: Vec<(f64, f64, bool)> = parsed
+            .iter()
+            .map(|f| (f.value, f.step, f.plain_integer))
+            .collect();
+        assert_eq!(values, vec![(3e9, 1e9, false), (45.0, 1.0, false)]);
+    }
+
+    #[tokio::test]
+    async fn test_threshold_and_context() {
+        let guardrail = NumericConsistencyGuardrail::new(NumericConsistencyConfig {
+            severity_threshold: Severity::Medium,
+            ..Default::default()
+        });
+        let context = OutputContext::new("", SOURCE, None);
+
+        let result = guardrail
+            .validate_output("Headcount is 2,500.", &context)
+            .await
+            .unwrap();
+        assert!(!result.passed);
+        assert_eq!(result.violations[0].id(), "numeric.UNSUPPORTED_NUMBER");
+        assert_eq!(
+            result.violations[0].location.as_deref(),
+            Some("Line 1, column 14")
+        );
+
+        // Without a source there is nothing to compare against
+        let result = guardrail.validate("Headcount is 2,500.").await.unwrap();
+        assert!(result.passed);
+    }
+
+    #[test]
+    fn test_validate() {
+        assert!(NumericConsistencyConfig::default().validate().is_ok());
+        let config = NumericConsistencyConfig {
+            relative_tolerance: 1.5,
+            ..Default::default()
+        };
+        assert!(config.validate().is_err());
+    }
+}
@@ -0,0 +1,346 @@
+//! Guardrail override tokens
+//!
+//! Escape hatch for false positives: a caller presenting the configured
+//! override secret can downgrade a guardrail block to warnings. Overrides are
+//! restricted to an allowlist of rules, rate-limited per rolling hour, and
+//! every attempt (applied or denied) is written to the audit log before the
+//! block is lifted, so an unauditable override never takes effect.
+
+use super::provider::GuardrailResult;
+use crate::{
+    audit::{AuditEvent, AuditEventKind, AuditSink, JsonlAuditLog},
+    error::CliError,
+};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::{
+    collections::VecDeque,
+    path::PathBuf,
+    sync::{Arc, Mutex},
+};
+
+/// Default number of overrides allowed per rolling hour
+const DEFAULT_MAX_PER_HOUR: u32 = 5;
+
+fn default_max_per_hour() -> u32 {
+    DEFAULT_MAX_PER_HOUR
+}
+
+/// Override policy as written in config files (`[guardrail_override]`)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OverridePolicyConfig {
+    /// Environment variable holding the override secret
+    pub secret_env: String,
+
+    /// Rules that may be overridden (empty = any rule), plain or namespaced
+    /// (`"MAX_LENGTH"` or `"regex.MAX_LENGTH"`). A trailing `*` matches by
+    /// prefix, e.g. `"PII_*"` or `"llama_guard.*"`.
+    #[serde(default)]
+    pub allowed_rules: Vec<String>,
+
+    /// Maximum applied overrides per rolling hour
+    #[serde(default = "default_max_per_hour")]
+    pub max_per_hour: u32,
+
+    /// JSONL audit log path (required: overrides are never unaudited)
+    pub audit_log: PathBuf,
+}
+
+/// Runtime override controller shared across evaluations
+pub struct GuardrailOverride {
+    secret: String,
+    allowed_rules: Vec<String>,
+    max_per_hour: u32,
+    applied: Mutex<VecDeque<DateTime<Utc>>>,
+    audit: Arc<dyn AuditSink>,
+}
+
+impl std::fmt::Debug for GuardrailOverride {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("GuardrailOverride")
+            .field("secret", &"[REDACTED]")
+            .field("allowed_rules", &self.allowed_rules)
+            .field("max_per_hour", &self.max_per_hour)
+            .finish()
+    }
+}
+
+impl GuardrailOverride {
+    /// Create a controller, seeding the rate-limit window from overrides
+    /// already recorded in `audit` during the last hour
+    pub fn new(
+        secret: impl Into<String>,
+        allowed_rules: Vec<String>,
+        max_per_hour: u32,
+        audit: Arc<dyn AuditSink>,
+    ) -> Result<Self, CliError> {
+        let secret = secret.into();
+        if secret.is_empty() {
+            return Err(CliError::InvalidArguments(
+                "Guardrail override secret must not be empty".to_string(),
+            ));
+        }
+
+        let applied = audit
+            .events_since(Utc::now() - Duration::hours(1))?
+            .into_iter()
+            .filter(|e| matches!(e.kind, AuditEventKind::OverrideApplied { .. }))
+            .map(|e| e.timestamp)
+            .collect();
+
+        Ok(Self {
+            secret,
+            allowed_rules,
+            max_per_hour,
+            applied: Mutex::new(applied),
+            audit,
+        })
+    }
+
+    /// Build from config file settings (reads the secret from the environment)
+    pub fn from_config(config: &OverridePolicyConfig) -> Result<Self, CliError> {
+        let secret = std::env::var(&config.secret_env).map_err(|_| {
+            CliError::InvalidArguments(format!(
+                "Environment variable '{}' specified by guardrail_override.secret_env does not exist",
+                config.secret_env
+            ))
+        })?;
+        Self::new(
+            secret,
+            config.allowed_rules.clone(),
+            config.max_per_hour,
+            Arc::new(JsonlAuditLog::new(&config.audit_log)),
+        )
+    }
+
+    /// Try to downgrade a failed guardrail result to warnings
+    ///
+    /// Returns `Ok(true)` if the override was applied (`result.passed` is now
+    /// `true`), `Ok(false)` if it was denied, and `Err` if the audit log could
+    /// not be written.
+    pub fn apply(
+        &self,
+        token: &str,
+        stage: &str,
+        result: &mut GuardrailResult,
+    ) -> Result<bool, CliError> {
+        if result.passed {
+            return Ok(false);
+        }
+        let rules: Vec<String> = result.violations.iter().map(|v| v.rule.clone()).collect();
+
+        if !constant_time_eq(token.as_bytes(), self.secret.as_bytes()) {
+            return self.deny(stage, rules, "invalid override token".to_string());
+        }
+
+        let blocked: Vec<&str> = result
+            .violations
+            .iter()
+            .filter(|v| !self.is_rule_allowed(&v.rule) && !self.is_rule_allowed(&v.id()))
+            .map(|v| v.rule.as_str())
+            .collect();
+        if !blocked.is_empty() {
+            let reason = format!("rules not overridable: {}", blocked.join(", "));
+            return self.deny(stage, rules, reason);
+        }
+
+        let now = Utc::now();
+        {
+            let mut applied = self.applied.lock().expect("override lock poisoned");
+            while applied
+                .front()
+                .is_some_and(|t| *t < now - Duration::hours(1))
+            {
+                applied.pop_front();
+            }
+            if applied.len() >= self.max_per_hour as usize {
+                drop(applied);
+                let reason = format!(
+                    "override rate limit reached ({} per hour)",
+                    self.max_per_hour
+                );
+                return self.deny(stage, rules, reason);
+            }
+            applied.push_back(now);
+        }
+
+        self.audit.record(&AuditEvent {
+            timestamp: now,
+            kind: AuditEventKind::OverrideApplied {
+                stage: stage.to_string(),
+                rules: rules.clone(),
+                token_fingerprint: fingerprint(token),
+            },
+        })?;
+
+        log::warn!(
+            "Guardrail override applied at {stage} stage for rules: {}",
+            rules.join(", ")
+        );
+        result.warnings.append(&mut result.violations);
+        result.passed = true;
+        Ok(true)
+    }
+
+    fn deny(&self, stage: &str, rules: Vec<String>, reason: String) -> Result<bool, CliError> {
+        log::warn!("Guardrail override denied at {stage} stage: {reason}");
+        self.audit
+            .record(&AuditEvent::now(AuditEventKind::OverrideDenied {
+                stage: stage.to_string(),
+                rules,
+                reason,
+            }))?;
+        Ok(false)
+    }
+
+    fn is_rule_allowed(&self, rule: &str) -> bool {
+        self.allowed_rules.is_empty()
+            || self
+                .allowed_rules
+                .iter()
+                .any(|allowed| match allowed.strip_suffix('*') {
+                    Some(prefix) => rule.starts_with(prefix),
+                    None => rule == allowed,
+                })
+    }
+}
+
+/// Short, non-reversible token fingerprint for audit records
+fn fingerprint(token: &str) -> String {
+    let digest = Sha256::digest(token.as_bytes());
+    digest[..6].iter().map(|b| format!("{b:02x}")).collect()
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        audit::MemoryAuditLog,
+        guardrails::provider::{Severity, Violation},
+    };
+
+    fn blocked(rules: &[&str]) -> GuardrailResult {
+        let violations = rules
+            .iter()
+            .map(|rule| Violation {
+                namespace: String::new(),
+                rule: rule.to_string(),
+                severity: Severity::High,
+                message: "matched".to_string(),
+                location: None,
+                source: None,
+                preview: None,
+            })
+            .collect();
+        GuardrailResult::without_quality_score(false, violations, vec![])
+    }
+
+    fn controller(allowed: &[&str], max_per_hour: u32) -> (GuardrailOverride, Arc<MemoryAuditLog>) {
+        let audit = Arc::new(MemoryAuditLog::new());
+        let ovr = GuardrailOverride::new(
+            "s3cret",
+            allowed.iter().map(|s| s.to_string()).collect(),
+            max_per_hour,
+            audit.clone(),
+        )
+        .unwrap();
+        (ovr, audit)
+    }
+
+    #[test]
+    fn test_valid_token_downgrades_violations() {
+        let (ovr, audit) = controller(&["PII_*"], 5);
+        let mut result = blocked(&["PII_EMAIL"]);
+
+        assert!(ovr.apply("s3cret", "input", &mut result).unwrap());
+        assert!(result.passed);
+        assert!(result.violations.is_empty());
+        assert_eq!(result.warnings.len(), 1);
+
+        let events = audit.events();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].kind.name(), "override_applied");
+    }
+
+    #[test]
+    fn test_invalid_token_is_denied_and_audited() {
+        let (ovr, audit) = controller(&[], 5);
+        let mut result = blocked(&["PII_EMAIL"]);
+
+        assert!(!ovr.apply("wrong", "input", &mut result).unwrap());
+        assert!(!result.passed);
+        assert_eq!(audit.events()[0].kind.name(), "override_denied");
+    }
+
+    #[test]
+    fn test_non_allowlisted_rule_keeps_block() {
+        let (ovr, _audit) = controller(&["PII_EMAIL"], 5);
+        let mut result = blocked(&["PII_EMAIL", "PROMPT_INJECTION"]);
+
+        assert!(!ovr.apply("s3cret", "input", &mut result).unwrap());
+        assert!(!result.passed);
+        assert_eq!(result.violations.len(), 2);
+    }
+
+    #[test]
+    fn test_namespaced_allowlist() {
+        let (ovr, _audit) = controller(&["llama_guard.*"], 5);
+        let mut result = blocked(&["S1"]);
+        result.violations[0].namespace = "llama_guard".to_string();
+        assert!(ovr.apply("s3cret", "input", &mut result).unwrap());
+
+        let mut result = blocked(&["S1"]);
+        result.violations[0].namespace = "gpt_oss_safeguard".to_string();
+        assert!(!ovr.apply("s3cret", "input", &mut result).unwrap());
+    }
+
+    #[test]
+    fn test_rate_limit_per_hour() {
+        let (ovr, audit) = controller(&[], 2);
+        for _ in 0..2 {
+            assert!(ovr.apply("s3cret", "output", &mut blocked(&["X"])).unwrap());
+        }
+        assert!(!ovr.apply("s3cret", "output", &mut blocked(&["X"])).unwrap());
+
+        match &audit.events()[2].kind {
+            AuditEventKind::OverrideDenied { reason, .. } => {
+                assert!(reason.contains("rate limit"))
+            }
+            other => panic!("Expected denial, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_rate_limit_window_seeded_from_audit_log() {
+        let audit = Arc::new(MemoryAuditLog::new());
+        audit
+            .record(&AuditEvent::now(AuditEventKind::OverrideApplied {
+                stage: "input".to_string(),
+                rules: vec!["X".to_string()],
+                token_fingerprint: "abc".to_string(),
+            }))
+            .unwrap();
+
+        let ovr = GuardrailOverride::new("s3cret", vec![], 1, audit).unwrap();
+        assert!(!ovr.apply("s3cret", "input", &mut blocked(&["X"])).unwrap());
+    }
+
+    #[test]
+    fn test_passed_result_is_untouched() {
+        let (ovr, audit) = controller(&[], 5);
+        let mut result = GuardrailResult::without_quality_score(true, vec![], vec![]);
+        assert!(!ovr.apply("s3cret", "input", &mut result).unwrap());
+        assert!(audit.events().is_empty());
+    }
+
+    #[test]
+    fn test_debug_redacts_secret() {
+        let (ovr, _audit) = controller(&[], 5);
+        assert!(!format!("{ovr:?}").contains("s3cret"));
+    }
+}
@@ -1,4 +1,4 @@
-use crate::error::CliError;
+use crate::{audit::blob_ref, error::CliError, image::ImageInput, models::ResponseFormat};
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 
@@ -8,12 +8,87 @@ pub trait GuardrailProvider: Send + Sync {
     /// Validate content (works for both input and output)
     async fn validate(&self, content: &str) -> Result<GuardrailResult, CliError>;
 
+    /// Validate an LLM response with the request that produced it
+    ///
+    /// Override for context-aware checks (relevance, refusals, conversation
+    /// formatting). The default ignores the context and calls [`Self::validate`].
+    async fn validate_output(
+        &self,
+        response: &str,
+        _context: &OutputContext,
+    ) -> Result<GuardrailResult, CliError> {
+        self.validate(response).await
+    }
+
+    /// Replace sanitizable matches in user input before it is validated
+    ///
+    /// Returns the redacted text and the replacements made, or `None` when
+    /// nothing was redacted. The default never redacts.
+    fn redact(&self, _content: &str) -> Option<(String, Vec<Redaction>)> {
+        None
+    }
+
     /// Provider name for logging and debugging
     fn name(&self) -> &str;
 }
 
+/// Matches of one rule replaced by a placeholder (the matched text is not kept)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Redaction {
+    /// Namespaced rule ID (e.g. `regex.EMAIL_ADDRESS`)
+    pub rule: String,
+    /// Replacement text (e.g. `[EMAIL]`)
+    pub placeholder: String,
+    pub count: usize,
+}
+
+/// Add `redactions` to `into`, summing counts per rule
+pub fn merge_redactions(into: &mut Vec<Redaction>, redactions: Vec<Redaction>) {
+    for redaction in redactions {
+        match into.iter_mut().find(|r| r.rule == redaction.rule) {
+            Some(existing) => existing.count += redaction.count,
+            None => into.push(redaction),
+        }
+    }
+}
+
+/// Request context passed to output guardrails
+#[derive(Debug, Clone, Default)]
+pub struct OutputContext {
+    /// User prompt the response answers (before spotlighting)
+    pub user_prompt: Option<String>,
+    /// Blob reference (`sha256:<hex>`) of the system prompt; the prompt itself
+    /// is not shared with guardrails
+    pub system_prompt_hash: Option<String>,
+    pub response_format: Option<ResponseFormat>,
+    /// Images returned with the response, for guardrails that inspect binary
+    /// content (text-only guardrails ignore them)
+    pub images: Vec<ImageInput>,
+}
+
+impl OutputContext {
+    pub fn new(
+        system_prompt: &str,
+        user_prompt: impl Into<String>,
+        response_format: Option<ResponseFormat>,
+    ) -> Self {
+        Self {
+            user_prompt: Some(user_prompt.into()),
+            system_prompt_hash: Some(blob_ref(system_prompt.as_bytes())),
+            response_format,
+            images: Vec::new(),
+        }
+    }
+
+    /// Attach the images returned with the response
+    pub fn with_images(mut self, images: Vec<ImageInput>) -> Self {
+        self.images = images;
+        self
+    }
+}
+
 /// Generic validation result (unified for all providers)
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct GuardrailResult {
     pub passed: bool,
     pub violations: Vec<Violation>,
@@ -26,6 +101,35 @@ pub struct GuardrailResult {
 
     /// Provider-specific metadata
     pub provider_specific: Option<ProviderSpecificResult>,
+
+    /// Results of the individual members of a composite guardrail
+    /// (empty for single providers)
+    pub provider_results: Vec<NamedProviderResult>,
+
+    /// How a composite guardrail combined its members' verdicts
+    /// (None for single providers)
+    pub explanation: Option<String>,
+}
+
+/// Result of one member of a composite guardrail
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NamedProviderResult {
+    /// Provider type from the configuration (e.g. `llama_guard`), or the
+    /// provider name for composites built in code
+    pub provider: String,
+    /// `input` or `output`
+    pub direction: String,
+    pub passed: bool,
+    /// Rule IDs of the violations this provider reported
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub violation_rules: Vec<String>,
+    /// Rule IDs of the non-blocking findings this provider reported
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub warning_rules: Vec<String>,
+    pub latency_ms: u64,
+    /// Classifier reply, for model-based providers
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub raw_response: Option<String>,
 }
 
 impl GuardrailResult {
@@ -41,6 +145,8 @@ impl GuardrailResult {
             warnings,
             quality_score: None,
             provider_specific: None,
+            provider_results: Vec::new(),
+            explanation: None,
         }
     }
 
@@ -57,9 +163,21 @@ impl GuardrailResult {
             warnings,
             quality_score: Some(quality_score),
             provider_specific: None,
+            provider_results: Vec::new(),
+            explanation: None,
         }
     }
 
+    /// Whether the content is on a hash allowlist (composites pass it without
+    /// waiting for their other providers)
+    pub fn is_preapproved(&self) -> bool {
+        matches!(
+            &self.provider_specific,
+            Some(ProviderSpecificResult::HashList(result))
+                if result.matched == Some(crate::guardrails::hash_list::HashListMatch::Allow)
+        )
+    }
+
     /// Helper: Create result with provider-specific data (for LLM providers)
     pub fn with_provider_specific(
         passed: bool,
@@ -73,20 +191,41 @@ impl GuardrailResult {
             warnings,
             quality_score: None,
             provider_specific: Some(provider_specific),
+            provider_results: Vec::new(),
+            explanation: None,
         }
     }
 }
 
 /// Provider-specific result data
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "snake_case")]
 pub enum ProviderSpecificResult {
     LlamaGuard(LlamaGuardResult),
     GptOssSafeguard(GptOssSafeguardResult),
     LlamaPromptGuard(crate::guardrails::llama_prompt_guard::LlamaPromptGuardResult),
+    NemoGuardrails(crate::guardrails::nemo_guardrails::NemoGuardrailsResult),
+    /// Composite `majority_vote`/`weighted` aggregation
+    Vote(crate::guardrails::hybrid::VoteResult),
+    HashList(crate::guardrails::hash_list::HashListResult),
+    SemanticSimilarity(crate::guardrails::semantic_similarity::SemanticSimilarityResult),
     // Future: OpenAI(OpenAIModerationResult),
     // Future: Azure(AzureContentSafetyResult),
 }
 
+impl ProviderSpecificResult {
+    /// Classifier reply as returned by the model-based provider
+    pub fn raw_response(&self) -> Option<&str> {
+        match self {
+            Self::LlamaGuard(result) => Some(&result.raw_response),
+            Self::GptOssSafeguard(result) => Some(&result.raw_response),
+            Self::LlamaPromptGuard(result) => Some(&result.raw_response),
+            Self::NemoGuardrails(result) => result.bot_message.as_deref(),
+            Self::Vote(_) | Self::HashList(_) | Self::SemanticSimilarity(_) => None,
+        }
+    }
+}
+
 /// Llama Guard 3 specific result
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LlamaGuardResult {
@@ -107,10 +246,57 @@ pub struct GptOssSafeguardResult {
 /// Violation details
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Violation {
+    /// Provider namespace (see [`crate::guardrails::catalog::namespace`])
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub namespace: String,
     pub rule: String,
     pub severity: Severity,
     pub message: String,
     pub location: Option<String>,
+    /// Prompt source the violation was found in (e.g. `pdf`,
+    /// `context_file:notes.md`), when input sources are validated separately
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    /// Excerpt around the match with the matched text masked (regex
+    /// guardrails with `preview_chars`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub preview: Option<String>,
+}
+
+impl Violation {
+    /// Namespaced rule id (e.g., "llama_guard.S1")
+    pub fn id(&self) -> String {
+        crate::guardrails::catalog::rule_id(&self.namespace, &self.rule)
+    }
+
+    /// Masked excerpt of this violation for the output metadata (None when
+    /// the provider made no preview)
+    pub fn preview_record(&self) -> Option<ViolationPreview> {
+        Some(ViolationPreview {
+            rule: self.id(),
+            location: self.location.clone(),
+            preview: self.preview.clone()?,
+        })
+    }
+}
+
+/// Masked excerpt around a blocking violation (see [`Violation::preview`])
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ViolationPreview {
+    /// Namespaced rule ID (e.g. `regex.SSN`)
+    pub rule: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub location: Option<String>,
+    pub preview: String,
+}
+
+/// "Line L, column C" (1-based, column in characters) for a byte offset
+pub(crate) fn line_column(content: &str, offset: usize) -> String {
+    let before = &content[..offset];
+    let line = before.matches('\n').count() + 1;
+    let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+    let column = content[line_start..offset].chars().count() + 1;
+    format!("Line {line}, column {column}")
 }
 
 /// Violation severity levels
@@ -0,0 +1,393 @@
+//! Semantic similarity guardrail (groundedness check)
+//!
+//! Embeds the source text (the user prompt, including extracted PDF text and
+//! untrusted content) and the response, split into sentence-aligned chunks,
+//! with an embeddings model. Each response chunk is compared with its closest
+//! source chunk by cosine similarity; chunks below `min_similarity` are
+//! reported as potential hallucinations:
+//!
+//! ```toml
+//! [guardrails.output]
+//! type = "semantic_similarity"
+//! api_url = "http://localhost:11434/v1/embeddings"
+//! model = "nomic-embed-text"
+//! timeout_secs = 30
+//! min_similarity = 0.5           # Per response chunk (default)
+//! chunk_chars = 500              # Sentences are grouped up to this size (default)
+//! min_chunk_chars = 20           # Shorter response chunks are not checked (default)
+//! severity_threshold = "Medium"  # Block low-similarity chunks (default: High = warn)
+//! ```
+//!
+//! The endpoint may be OpenAI-compatible (`/v1/embeddings`, `data[].embedding`)
+//! or Ollama's `/api/embed` (`embeddings`); both take `{"model", "input": [...]}`.
+//! All chunks are embedded in one request. Output-only: without a source
+//! document there is nothing to compare.
+
+use crate::{
+    cassette,
+    error::CliError,
+    guardrails::{
+        catalog::namespace,
+        provider::{
+            line_column, GuardrailProvider, GuardrailResult, OutputContext, ProviderSpecificResult,
+            Severity, Violation,
+        },
+    },
+};
+use async_trait::async_trait;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::{ops::Range, time::Duration};
+
+/// Rule names reported in `Violation::rule`
+pub mod rules {
+    pub const LOW_SIMILARITY: &str = "LOW_SIMILARITY";
+}
+
+/// A response chunk unlike any part of the source
+pub(crate) const RULE_SEVERITY: Severity = Severity::Medium;
+
+/// Sentence ends and line breaks (chunks never split inside a sentence)
+static SENTENCE_END: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r#"[.!?]['")\]]*\s+|\n\s*"#).expect("valid sentence regex"));
+
+pub(crate) fn default_min_similarity() -> f32 {
+    0.5
+}
+
+pub(crate) fn default_chunk_chars() -> usize {
+    500
+}
+
+pub(crate) fn default_min_chunk_chars() -> usize {
+    20
+}
+
+pub(crate) fn default_severity_threshold() -> Severity {
+    // Similarity is a heuristic: warn unless the threshold is lowered
+    Severity::High
+}
+
+/// Configuration for the semantic similarity guardrail
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SemanticSimilarityConfig {
+    /// Embeddings endpoint (OpenAI-compatible or Ollama `/api/embed`)
+    pub api_url: String,
+    /// Embeddings model (e.g. "nomic-embed-text", "text-embedding-3-small")
+    pub model: String,
+    pub timeout_secs: u64,
+    pub api_key: Option<String>,
+    /// Response chunks below this cosine similarity to every source chunk are
+    /// reported (default: 0.5)
+    pub min_similarity: f32,
+    /// Sentences are grouped into chunks of up to this many characters
+    /// (default: 500)
+    pub chunk_chars: usize,
+    /// Response chunks shorter than this are not checked (default: 20)
+    pub min_chunk_chars: usize,
+    /// Minimum severity that blocks; findings below it become warnings
+    /// (default: high, so findings are warnings)
+    pub severity_threshold: Severity,
+}
+
+impl Default for SemanticSimilarityConfig {
+    fn default() -> Self {
+        Self {
+            api_url: "http://localhost:11434/v1/embeddings".to_string(),
+            model: "nomic-embed-text".to_string(),
+            timeout_secs: 30,
+            api_key: None,
+            min_similarity: default_min_similarity(),
+            chunk_chars: default_chunk_chars(),
+            min_chunk_chars: default_min_chunk_chars(),
+            severity_threshold: default_severity_threshold(),
+        }
+    }
+}
+
+/// Similarity of one response chunk to its closest source chunk
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChunkSimilarity {
+    /// Byte range of the chunk in the response
+    pub start: usize,
+    pub end: usize,
+    pub similarity: f32,
+}
+
+/// Semantic similarity specific result
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SemanticSimilarityResult {
+    /// Checked response chunks, in reading order
+    pub chunks: Vec<ChunkSimilarity>,
+    /// Number of source chunks compared against
+    pub source_chunks: usize,
+    /// Lowest chunk similarity (1.0 when no chunk was checked)
+    pub min_similarity: f32,
+}
+
+/// Sentence-aligned chunks of `text` of up to `max_chars` characters
+///
+/// A sentence longer than `max_chars` is a chunk on its own. Ranges exclude
+/// surrounding whitespace; blank text has no chunks.
+fn chunks(text: &str, max_chars: usize) -> Vec<Range<usize>> {
+    let mut sentences = Vec::new();
+    let mut start = 0;
+    for end in SENTENCE_END.find_iter(text).map(|m| m.end()) {
+        sentences.push(start..end);
+        start = end;
+    }
+    sentences.push(start..text.len());
+
+    let trimmed = |range: Range<usize>| {
+        let slice = &text[range.clone()];
+        let start = range.start + (slice.len() - slice.trim_start().len());
+        start..range.start + slice.trim_end().len()
+    };
+    let mut chunks: Vec<Range<usize>> = Vec::new();
+    for sentence in sentences.into_iter().map(trimmed).filter(|r| !r.is_empty()) {
+        match chunks.last_mut() {
+            Some(last) if text[last.start..sentence.end].chars().count() <= max_chars => {
+                last.end = sentence.end;
+            }
+            _ => chunks.push(sentence),
+        }
+    }
+    chunks
+}
+
+fn cosine(a: &[f32], b: &[f32]) -> f32 {
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norm = |v: &[f32]| v.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let norms = norm(a) * norm(b);
+    if norms == 0.0 {
+        0.0
+    } else {
+        dot / norms
+    }
+}
+
+/// Embeddings from an OpenAI-compatible (`data[].embedding`, ordered by
+/// `index`) or Ollama (`embeddings`) response body
+fn parse_embeddings(body: &str) -> Result<Vec<Vec<f32>>, CliError> {
+    let invalid = |reason: String| {
+        CliError::InvalidResponse(format!("Failed to parse embeddings response: {reason}"))
+    };
+    let body: Value = serde_json::from_str(body).map_err(|e| invalid(e.to_string()))?;
+    let vectors = if let Some(data) = body["data"].as_array() {
+        let mut data = data.clone();
+        data.sort_by_key(|item| item["index"].as_u64());
+        data.into_iter()
+            .map(|item| item["embedding"].clone())
+            .collect()
+    } else if let Some(embeddings) = body["embeddings"].as_array() {
+        embeddings.clone()
+    } else {
+        return Err(invalid("expected 'data' or 'embeddings'".to_string()));
+    };
+    vectors
+        .into_iter()
+        .map(|vector| serde_json::from_value(vector).map_err(|e| invalid(e.to_string())))
+        .collect()
+}
+
+/// Groundedness guardrail comparing response and source embeddings
+pub struct SemanticSimilarityGuardrail {
+    client: Client,
+    config: SemanticSimilarityConfig,
+}
+
+impl SemanticSimilarityGuardrail {
+    pub fn new(config: SemanticSimilarityConfig) -> Self {
+        Self {
+            client: Client::new(),
+            config,
+        }
+    }
+
+    /// Embed `inputs` in one request
+    async fn embed(&self, inputs: &[&str]) -> Result<Vec<Vec<f32>>, CliError> {
+        let body = json!({"model": self.config.model, "input": inputs});
+        let mut request = self
+            .client
+            .post(&self.config.api_url)
+            .timeout(Duration::from_secs(self.config.timeout_secs))
+            .json(&body);
+        if let Some(key) = &self.config.api_key {
+            request = request.header("Authorization", format!("Bearer {key}"));
+        }
+
+        let response = cassette::send(request, self.config.api_key.as_deref()).await?;
+        if response.status == 401 {
+            return Err(CliError::AuthenticationFailed(
+                "Embeddings endpoint rejected the API key".to_string(),
+            ));
+        }
+        if !response.status.is_success() {
+            return Err(CliError::InvalidResponse(format!(
+                "Embeddings endpoint returned HTTP {}: {}",
+                response.status.as_u16(),
+                response.body
+            )));
+        }
+        let embeddings = parse_embeddings(&response.body)?;
+        if embeddings.len() != inputs.len() {
+            return Err(CliError::InvalidResponse(format!(
+                "Embeddings endpoint returned {} embeddings for {} inputs",
+                embeddings.len(),
+                inputs.len()
+            )));
+        }
+        Ok(embeddings)
+    }
+
+    /// Similarity of each checked response chunk to its closest source chunk
+    async fn compare(
+        &self,
+        response: &str,
+        source: &str,
+    ) -> Result<(Vec<ChunkSimilarity>, usize), CliError> {
+        let source_chunks = chunks(source, self.config.chunk_chars);
+        let response_chunks: Vec<Range<usize>> = chunks(response, self.config.chunk_chars)
+            .into_iter()
+            .filter(|r| response[r.clone()].chars().count() >= self.config.min_chunk_chars)
+            .collect();
+        if source_chunks.is_empty() || response_chunks.is_empty() {
+            return Ok((Vec::new(), source_chunks.len()));
+        }
+
+        let inputs: Vec<&str> = source_chunks
+            .iter()
+            .map(|r| &source[r.clone()])
+            .chain(response_chunks.iter().map(|r| &response[r.clone()]))
+            .collect();
+        let embeddings = self.embed(&inputs).await?;
+        let (source_vectors, response_vectors) = embeddings.split_at(source_chunks.len());
+        let similarities = response_chunks
+            .into_iter()
+            .zip(response_vectors)
+            .map(|(range, vector)| ChunkSimilarity {
+                start: range.start,
+                end: range.end,
+                similarity: source_vectors
+                    .iter()
+                    .map(|source| cosine(vector, source))
+                    .fold(f32::MIN, f32::max),
+            })
+            .collect();
+        Ok((similarities, source_chunks.len()))
+    }
+
+    fn finding(&self, response: &str, chunk: &ChunkSimilarity) -> Violation {
+        let text = &response[chunk.start..chunk.end];
+        let preview: String = text.chars().take(60).collect();
+        let ellipsis = if preview.len() < text.len() {
+            "..."
+        } else {
+            ""
+        };
+        Violation {
+            namespace: namespace::SEMANTIC.to_string(),
+            rule: rules::LOW_SIMILARITY.to_string(),
+            severity: RULE_SEVERITY,
+            message: format!(
+                "Response section '{preview}{ellipsis}' has similarity {:.2} to the source \
+                 (min {:.2}); possible hallucination",
+                chunk.similarity, self.config.min_similarity
+            ),
+            location: Some(line_column(response, chunk.start)),
+            source: None,
+            preview: None,
+        }
+    }
+}
+
+#[async_trait]
+impl GuardrailProvider for SemanticSimilarityGuardrail {
+    async fn validate(&self, _content: &str) -> Result<GuardrailResult, CliError> {
+        log::warn!(
+            "Semantic similarity needs a source document; it only applies to output guardrails"
+        );
+        Ok(GuardrailResult::without_quality_score(
+            true,
+            Vec::new(),
+            Vec::new(),
+        ))
+    }
+
+    async fn validate_output(
+        &self,
+        response: &str,
+        context: &OutputContext,
+    ) -> Result<GuardrailResult, CliError> {
+        let Some(source) = context.user_prompt.as_deref() else {
+            return self.validate(response).await;
+        };
+        let (similarities, source_chunks) = self.compare(response, source).await?;
+        let (violations, warnings): (Vec<_>, Vec<_>) = similarities
+            .iter()
+            .filter(|chunk| chunk.similarity < self.config.min_similarity)
+            .map(|chunk| self.finding(response, chunk))
+            .partition(|f| f.severity >= self.config.severity_threshold);
+
+        let result = SemanticSimilarityResult {
+            min_similarity: similarities
+                .iter()
+                .map(|c| c.similarity)
+                .fold(1.0, f32::min),
+            chunks: similarities,
+            source_chunks,
+        };
+        Ok(GuardrailResult::with_provider_specific(
+            violations.is_empty(),
+            violations,
+            warnings,
+            ProviderSpecificResult::SemanticSimilarity(result),
+        ))
+    }
+
+    fn name(&self) -> &str {
+        "SemanticSimilarityGuardrail"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chunks_group_sentences() {
+        let text = "  First sentence. Second one!\n\nThird (quoted.\") Last  ";
+        let spans: Vec<&str> = chunks(text, 30).into_iter().map(|r| &text[r]).collect();
+        assert_eq!(
+            spans,
+            vec!["First sentence. Second one!", "Third (quoted.\") Last"]
+        );
+
+        // Long sentences stay whole; blank text has no chunks
+        let spans: Vec<&str> = chunks(text, 5).into_iter().map(|r| &text[r]).collect();
+        assert_eq!(spans.len(), 4);
+        assert!(chunks(" \n ", 10).is_empty());
+    }
+
+    #[test]
+    fn test_parse_embeddings_formats() {
+        let openai = r#"{"data": [
+            {"index": 1, "embedding": [0.0, 1.0]},
+            {"index": 0, "embedding": [1.0, 0.0]}
+        ]}"#;
+        assert_eq!(
+            parse_embeddings(openai).unwrap(),
+            vec![vec![1.0, 0.0], vec![0.0, 1.0]]
+        );
+        let ollama = r#"{"model": "nomic-embed-text", "embeddings": [[0.5, 0.5]]}"#;
+        assert_eq!(parse_embeddings(ollama).unwrap(), vec![vec![0.5, 0.5]]);
+        assert!(parse_embeddings(r#"{"error": "model not found"}"#).is_err());
+
+        assert!((cosine(&[1.0, 0.0], &[1.0, 0.0]) - 1.0).abs() < f32::EPSILON);
+        assert_eq!(cosine(&[1.0, 0.0], &[0.0, 1.0]), 0.0);
+        assert_eq!(cosine(&[0.0, 0.0], &[1.0, 1.0]), 0.0);
+    }
+}
@@ -0,0 +1,326 @@
+//! Terminology guardrail
+//!
+//! Checks generated text against a glossary for brand and compliance
+//! reviews: terms that must appear, terms that must not, and discouraged
+//! spellings with their preferred form. Findings carry a line/column
+//! location. Terms match as whole words.
+//!
+//! ```toml
+//! [guardrails.output]
+//! type = "terminology"
+//! forbidden = ["guaranteed returns", "risk-free"]
+//! severity_threshold = "Medium"   # Block forbidden terms, warn on the rest
+//!
+//! [guardrails.output.required]
+//! "Acme Cloud" = "Product name must be mentioned"
+//!
+//! [guardrails.output.preferred]
+//! "e-mail" = "email"
+//! "Acme cloud" = "Acme Cloud"
+//! ```
+
+use crate::{
+    error::CliError,
+    guardrails::{
+        catalog::namespace,
+        provider::{line_column, GuardrailProvider, GuardrailResult, Severity, Violation},
+    },
+};
+use async_trait::async_trait;
+use regex::{Regex, RegexBuilder};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Rule names reported in `Violation::rule`
+pub mod rules {
+    pub const REQUIRED_TERM: &str = "REQUIRED_TERM";
+    pub const FORBIDDEN_TERM: &str = "FORBIDDEN_TERM";
+    pub const PREFERRED_SPELLING: &str = "PREFERRED_SPELLING";
+}
+
+fn default_severity_threshold() -> Severity {
+    // Terminology findings are advisory unless the threshold is lowered
+    Severity::High
+}
+
+/// Terminology guardrail configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TerminologyConfig {
+    /// Terms that must appear, with a note reported when one is missing
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub required: BTreeMap<String, String>,
+
+    /// Terms that must not appear
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub forbidden: Vec<String>,
+
+    /// Discouraged spelling → preferred spelling
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub preferred: BTreeMap<String, String>,
+
+    /// Match terms case-sensitively (default: false)
+    #[serde(default)]
+    pub case_sensitive: bool,
+
+    /// Minimum severity that blocks; findings below it become warnings
+    /// (default: high, so every finding is a warning)
+    #[serde(default = "default_severity_threshold")]
+    pub severity_threshold: Severity,
+}
+
+impl Default for TerminologyConfig {
+    fn default() -> Self {
+        Self {
+            required: BTreeMap::new(),
+            forbidden: Vec::new(),
+            preferred: BTreeMap::new(),
+            case_sensitive: false,
+            severity_threshold: default_severity_threshold(),
+        }
+    }
+}
+
+impl TerminologyConfig {
+    /// Check for empty glossaries and blank terms
+    pub fn validate(&self) -> Result<(), String> {
+        if self.required.is_empty() && self.forbidden.is_empty() && self.preferred.is_empty() {
+            return Err("no required, forbidden or preferred terms configured".to_string());
+        }
+        let terms = self
+            .required
+            .keys()
+            .chain(&self.forbidden)
+            .chain(self.preferred.iter().flat_map(|(from, to)| [from, to]));
+        if terms.into_iter().any(|term| term.trim().is_empty()) {
+            return Err("terms must not be empty".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Severity of each terminology rule
+pub(crate) fn rule_severity(rule: &str) -> Severity {
+    match rule {
+        rules::FORBIDDEN_TERM => Severity::Medium,
+        _ => Severity::Low,
+    }
+}
+
+/// Glossary-based guardrail
+pub struct TerminologyGuardrail {
+    config: TerminologyConfig,
+    required: Vec<(String, String, Regex)>,
+    forbidden: Vec<Regex>,
+    preferred: Vec<(String, String, Regex)>,
+}
+
+impl TerminologyGuardrail {
+    pub fn new(config: TerminologyConfig) -> Self {
+        let matcher = |term: &str| term_regex(term, config.case_sensitive);
+        Self {
+            required: config
+                .required
+                .iter()
+                .map(|(term, note)| (term.clone(), note.clone(), matcher(term)))
+                .collect(),
+            forbidden: config.forbidden.iter().map(|term| matcher(term)).collect(),
+            preferred: config
+                .preferred
+                .iter()
+                .map(|(from, to)| (from.clone(), to.clone(), matcher(from)))
+                .collect(),
+            config,
+        }
+    }
+
+    fn finding(&self, rule: &str, message: String, location: Option<String>) -> Violation {
+        Violation {
+            namespace: namespace::TERMINOLOGY.to_string(),
+            rule: rule.to_string(),
+            severity: rule_severity(rule),
+            message,
+            location,
+            source: None,
+            preview: None,
+        }
+    }
+}
+
+#[async_trait]
+impl GuardrailProvider for TerminologyGuardrail {
+    async fn validate(&self, content: &str) -> Result<GuardrailResult, CliError> {
+        let mut findings = Vec::new();
+
+        for (term, note, regex) in &self.required {
+            if !regex.is_match(content) {
+                let message = if note.is_empty() {
+                    format!("Required term '{term}' is missing")
+                } else {
+                    format!("Required term '{term}' is missing ({note})")
+                };
+                findings.push(self.finding(rules::REQUIRED_TERM, message, None));
+            }
+        }
+        for regex in &self.forbidden {
+            for m in regex.find_iter(content) {
+                findings.push(self.finding(
+                    rules::FORBIDDEN_TERM,
+                    format!("Forbidden term '{}'", m.as_str()),
+                    Some(line_column(content, m.start())),
+                ));
+            }
+        }
+        for (_, preferred, regex) in &self.preferred {
+            // Case-insensitive matching would otherwise flag the preferred form
+            for m in regex.find_iter(content).filter(|m| m.as_str() != preferred) {
+                findings.push(self.finding(
+                    rules::PREFERRED_SPELLING,
+                    format!("Use '{preferred}' instead of '{}'", m.as_str()),
+                    Some(line_column(content, m.start())),
+                ));
+            }
+        }
+
+        let (violations, warnings): (Vec<_>, Vec<_>) = findings
+            .into_iter()
+            .partition(|f| f.severity >= self.config.severity_threshold);
+        Ok(GuardrailResult::without_quality_score(
+            violations.is_empty(),
+            violations,
+            warnings,
+        ))
+    }
+
+    fn name(&self) -> &str {
+        "TerminologyGuardrail"
+    }
+}
+
+/// Whole-word matcher for a literal term
+///
+/// Word boundaries are only required on sides where the term starts or ends
+/// with a word character, so terms like "C++" still match.
+fn term_regex(term: &str, case_sensitive: bool) -> Regex {
+    let is_word = |c: Option<char>| c.is_some_and(|c| c.is_alphanumeric() || c == '_');
+    let start = if is_word(term.chars().next()) {
+        r"\b"
+    } else {
+        ""
+    };
+    let end = if is_word(term.chars().last()) {
+        r"\b"
+    } else {
+        ""
+    };
+    RegexBuilder::new(&format!("{start}{}{end}", regex::escape(term)))
+        .case_insensitive(!case_sensitive)
+        .build()
+        .expect("escaped term is a valid regex")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn glossary() -> TerminologyConfig {
+        TerminologyConfig {
+            required: BTreeMap::from([(
+                "Acme Cloud".to_string(),
+                "Product name must be mentioned".to_string(),
+            )]),
+            forbidden: vec!["risk-free".to_string()],
+            preferred: BTreeMap::from([
+                ("e-mail".to_string(), "email".to_string()),
+                ("Acme cloud".to_string(), "Acme Cloud".to_string()),
+            ]),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_findings_are_warnings_by_default() {
+        let guardrail = TerminologyGuardrail::new(glossary());
+        let result = guardrail
+            .validate("Our plan is Risk-Free.\nSend an E-mail to sales.")
+            .await
+            .unwrap();
+
+        assert!(result.passed);
+        let found: Vec<(String, Option<String>)> = result
+            .warnings
+            .iter()
+            .map(|w| (w.id(), w.location.clone()))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                ("terminology.REQUIRED_TERM".to_string(), None),
+                (
+                    "terminology.FORBIDDEN_TERM".to_string(),
+                    Some("Line 1, column 13".to_string())
+                ),
+                (
+                    "terminology.PREFERRED_SPELLING".to_string(),
+                    Some("Line 2, column 9".to_string())
+                ),
+            ]
+        );
+        assert!(result.warnings[0].message.contains("Product name"));
+        assert_eq!(
+            result.warnings[2].message,
+            "Use 'email' instead of 'E-mail'"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_threshold_blocks_forbidden_terms() {
+        let config = TerminologyConfig {
+            severity_threshold: Severity::Medium,
+            ..glossary()
+        };
+        let guardrail = TerminologyGuardrail::new(config);
+
+        let result = guardrail
+            .validate("Acme Cloud is risk-free.")
+            .await
+            .unwrap();
+        assert!(!result.passed);
+        assert_eq!(result.violations.len(), 1);
+        assert_eq!(result.violations[0].rule, rules::FORBIDDEN_TERM);
+
+        let clean = guardrail.validate("Acme Cloud has an SLA.").await.unwrap();
+        assert!(clean.passed);
+        assert!(
+            clean.warnings.is_empty(),
+            "preferred form must not be flagged"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_whole_word_and_case_sensitive_matching() {
+        let config = TerminologyConfig {
+            forbidden: vec!["free".to_string(), "C++".to_string()],
+            case_sensitive: true,
+            ..Default::default()
+        };
+        let guardrail = TerminologyGuardrail::new(config);
+
+        let result = guardrail
+            .validate("Carefree FREE tier, written in C++.")
+            .await
+            .unwrap();
+        let messages: Vec<&str> = result.warnings.iter().map(|w| w.message.as_str()).collect();
+        assert_eq!(messages, vec!["Forbidden term 'C++'"]);
+    }
+
+    #[test]
+    fn test_validate() {
+        assert!(glossary().validate().is_ok());
+        assert!(TerminologyConfig::default().validate().is_err());
+        let blank = TerminologyConfig {
+            forbidden: vec![" ".to_string()],
+            ..Default::default()
+        };
+        assert!(blank.validate().is_err());
+    }
+}
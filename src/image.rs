@@ -0,0 +1,390 @@
+//! Image input: OCR into the user prompt, or vision content for the model
+//!
+//! `image_mode = "ocr"` (default) extracts the image text with Docling, or
+//! Tesseract when Docling is not installed, and appends it to the user prompt
+//! as untrusted content, so input guardrails see it like any other text.
+//! `image_mode = "vision"` sends the image itself to vision-capable models
+//! (OpenAI-compatible `image_url` content parts, Ollama `images`); guardrails
+//! then only see the text prompt.
+
+use crate::{constants::image_limits, error::CliError, pdf::is_docling_available};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::{path::Path, process::Stdio, time::Duration};
+use tokio::process::Command;
+use tracing::Instrument;
+
+const DOCLING_COMMAND: &str = "docling";
+const TESSERACT_COMMAND: &str = "tesseract";
+
+/// How an image reaches the model
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ImageMode {
+    /// Extract the text and append it to the user prompt
+    #[default]
+    Ocr,
+    /// Attach the image for vision-capable models
+    Vision,
+}
+
+/// Image attached to a request
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImageInput {
+    /// MIME type detected from the file contents (e.g. "image/png")
+    pub media_type: &'static str,
+    /// Raw image bytes
+    pub data: Vec<u8>,
+}
+
+impl ImageInput {
+    /// Wrap image bytes, rejecting formats vision APIs do not accept
+    pub fn from_bytes(data: Vec<u8>) -> Result<Self, CliError> {
+        let media_type = detect_media_type(&data).ok_or_else(|| {
+            CliError::ImageProcessingFailed(
+                "Unsupported image format (expected PNG, JPEG, GIF or WebP)".to_string(),
+            )
+        })?;
+        Ok(Self { media_type, data })
+    }
+
+    /// Read an image file (size is checked by the caller)
+    pub fn load(path: &Path) -> Result<Self, CliError> {
+        let data = std::fs::read(path).map_err(|e| {
+            CliError::FileNotFound(format!(
+                "Failed to read image file '{}': {e}",
+                path.display()
+            ))
+        })?;
+        Self::from_bytes(data)
+    }
+
+    /// Decode a base64 `data:` URL (images returned by vision models)
+    pub fn from_data_url(url: &str) -> Result<Self, CliError> {
+        let encoded = url
+            .strip_prefix("data:")
+            .and_then(|rest| rest.split_once(";base64,"))
+            .map(|(_, encoded)| encoded)
+            .ok_or_else(|| {
+                CliError::ImageProcessingFailed("Expected a base64 data: URL".to_string())
+            })?;
+        let data = STANDARD.decode(encoded.trim()).map_err(|e| {
+            CliError::ImageProcessingFailed(format!("Invalid base64 image data: {e}"))
+        })?;
+        Self::from_bytes(data)
+    }
+
+    /// Width and height in pixels, when the header is readable
+    pub fn dimensions(&self) -> Option<(u32, u32)> {
+        image_dimensions(&self.data)
+    }
+
+    /// Base64-encoded bytes (Ollama `images`)
+    pub fn base64(&self) -> String {
+        STANDARD.encode(&self.data)
+    }
+
+    /// `data:` URL (OpenAI `image_url` content parts)
+    pub fn data_url(&self) -> String {
+        format!("data:{};base64,{}", self.media_type, self.base64())
+    }
+
+    /// SHA-256 of the bytes, hex-encoded (cache keys)
+    pub fn digest(&self) -> String {
+        Sha256::digest(&self.data)
+            .iter()
+            .map(|b| format!("{b:02x}"))
+            .collect()
+    }
+}
+
+/// MIME type of an image from its magic bytes
+pub fn detect_media_type(bytes: &[u8]) -> Option<&'static str> {
+    if bytes.starts_with(b"\x89PNG\r\n\x1a\n") {
+        Some("image/png")
+    } else if bytes.starts_with(&[0xFF, 0xD8, 0xFF]) {
+        Some("image/jpeg")
+    } else if bytes.starts_with(b"GIF87a") || bytes.starts_with(b"GIF89a") {
+        Some("image/gif")
+    } else if bytes.len() >= 12 && &bytes[..4] == b"RIFF" && &bytes[8..12] == b"WEBP" {
+        Some("image/webp")
+    } else {
+        None
+    }
+}
+
+/// Width and height of a PNG, JPEG, GIF or WebP image from its header
+pub fn image_dimensions(bytes: &[u8]) -> Option<(u32, u32)> {
+    let be16 = |at: usize| Some(u16::from_be_bytes(bytes.get(at..at + 2)?.try_into().ok()?));
+    let le16 = |at: usize| Some(u16::from_le_bytes(bytes.get(at..at + 2)?.try_into().ok()?));
+    let le24 = |at: usize| {
+        let b = bytes.get(at..at + 3)?;
+        Some(u32::from(b[0]) | u32::from(b[1]) << 8 | u32::from(b[2]) << 16)
+    };
+    match detect_media_type(bytes)? {
+        "image/png" => {
+            let be32 =
+                |at: usize| Some(u32::from_be_bytes(bytes.get(at..at + 4)?.try_into().ok()?));
+            Some((be32(16)?, be32(20)?))
+        }
+        "image/gif" => Some((u32::from(le16(6)?), u32::from(le16(8)?))),
+        "image/webp" => match bytes.get(12..16)? {
+            b"VP8X" => Some((le24(24)? + 1, le24(27)? + 1)),
+            b"VP8 " => Some((u32::from(le16(26)? & 0x3fff), u32::from(le16(28)? & 0x3fff))),
+            b"VP8L" => {
+                let bits = u32::from_le_bytes(bytes.get(21..25)?.try_into().ok()?);
+                Some(((bits & 0x3fff) + 1, ((bits >> 14) & 0x3fff) + 1))
+            }
+            _ => None,
+        },
+        _ => {
+            // JPEG: walk the segments up to the first start-of-frame marker
+            let mut at = 2;
+            loop {
+                if *bytes.get(at)? != 0xFF {
+                    return None;
+                }
+                let marker = *bytes.get(at + 1)?;
+                if (0xC0..=0xCF).contains(&marker) && ![0xC4, 0xC8, 0xCC].contains(&marker) {
+                    return Some((u32::from(be16(at + 7)?), u32::from(be16(at + 5)?)));
+                }
+                at += 2 + usize::from(be16(at + 2)?);
+            }
+        }
+    }
+}
+
+/// Text extracted from an image
+#[derive(Debug, Clone)]
+pub struct ImageText {
+    /// Extracted text (markdown with Docling, plain text with Tesseract)
+    pub text: String,
+
+    /// Tool used for extraction ("docling" or "tesseract")
+    pub extractor_used: &'static str,
+}
+
+/// Check if Tesseract CLI is available
+pub fn is_tesseract_available() -> bool {
+    std::process::Command::new(TESSERACT_COMMAND)
+        .arg("--version")
+        .output()
+        .map(|output| output.status.success())
+        .unwrap_or(false)
+}
+
+/// Run an OCR command, killing it after `MAX_OCR_TIMEOUT_SECS`
+async fn run_ocr(command: &mut Command, tool: &str) -> Result<Vec<u8>, CliError> {
+    command
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+    let child = command
+        .spawn()
+        .map_err(|e| CliError::ImageProcessingFailed(format!("Failed to run {tool}: {e}")))?;
+
+    let timeout = Duration::from_secs(image_limits::MAX_OCR_TIMEOUT_SECS);
+    let output = tokio::time::timeout(timeout, child.wait_with_output())
+        .await
+        .map_err(|_| {
+            CliError::ImageProcessingFailed(format!(
+                "{tool} did not finish within {}s",
+                image_limits::MAX_OCR_TIMEOUT_SECS
+            ))
+        })?
+        .map_err(|e| CliError::ImageProcessingFailed(format!("Failed to run {tool}: {e}")))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(CliError::ImageProcessingFailed(format!(
+            "{tool} OCR failed.\nStderr: {stderr}"
+        )));
+    }
+    Ok(output.stdout)
+}
+
+/// Extract markdown with Docling (same pipeline selection as PDFs)
+async fn extract_with_docling(path: &Path) -> Result<String, CliError> {
+    use std::sync::atomic::{AtomicU64, Ordering};
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+
+    let unique_id = COUNTER.fetch_add(1, Ordering::SeqCst);
+    let temp_dir = std::env::temp_dir().join(format!(
+        "docling_image_{}_{}",
+        std::process::id(),
+        unique_id
+    ));
+    std::fs::create_dir_all(&temp_dir).map_err(|e| {
+        CliError::ImageProcessingFailed(format!("Failed to create temp directory: {e}"))
+    })?;
+
+    let pipeline = std::env::var("DOCLING_PIPELINE").unwrap_or_else(|_| "standard".to_string());
+    log::debug!("Running Docling OCR with pipeline: {pipeline}");
+    let result = run_ocr(
+        Command::new(DOCLING_COMMAND)
+            .arg(path)
+            .arg("--to")
+            .arg("md")
+            .arg("--pipeline")
+            .arg(&pipeline)
+            .arg("--output")
+            .arg(&temp_dir),
+        "Docling",
+    )
+    .await
+    .and_then(|_| {
+        // Docling saves output with same base name as the input image
+        let stem = path
+            .file_stem()
+            .ok_or_else(|| CliError::ImageProcessingFailed("Invalid image filename".to_string()))?;
+        let output_path = temp_dir.join(format!("{}.md", stem.to_string_lossy()));
+        std::fs::read_to_string(&output_path).map_err(|e| {
+            CliError::ImageProcessingFailed(format!("Failed to read Docling output: {e}"))
+        })
+    });
+
+    let _ = std::fs::remove_dir_all(&temp_dir);
+    result
+}
+
+/// Extract plain text with Tesseract
+async fn extract_with_tesseract(path: &Path) -> Result<String, CliError> {
+    let stdout = run_ocr(
+        Command::new(TESSERACT_COMMAND).arg(path).arg("stdout"),
+        "Tesseract",
+    )
+    .await?;
+    Ok(String::from_utf8_lossy(&stdout).into_owned())
+}
+
+/// Extract text from an image with Docling, or Tesseract as a fallback
+///
+/// **Installation:**
+/// ```bash
+/// pip install docling          # preferred: layout-aware markdown
+/// apt install tesseract-ocr    # fallback: plain text
+/// ```
+pub async fn extract_text_from_image(path: &Path) -> Result<ImageText, CliError> {
+    let span = tracing::info_span!(
+        "image.ocr",
+        path = %path.display(),
+        chars = tracing::field::Empty,
+    );
+    let result = if is_docling_available() {
+        extract_with_docling(path)
+            .instrument(span.clone())
+            .await
+            .map(|text| ImageText {
+                text,
+                extractor_used: "docling",
+            })
+    } else if is_tesseract_available() {
+        extract_with_tesseract(path)
+            .instrument(span.clone())
+            .await
+            .map(|text| ImageText {
+                text,
+                extractor_used: "tesseract",
+            })
+    } else {
+        Err(CliError::ImageProcessingFailed(format!(
+            "Image OCR requires Docling or Tesseract.\n\n\
+                 Neither '{DOCLING_COMMAND}' nor '{TESSERACT_COMMAND}' is available in PATH.\n\n\
+                 Install with:\n  pip install docling\nor:\n  apt install tesseract-ocr\n\n\
+                 Alternatively, send the image to a vision-capable model with image_mode = \"vision\"."
+        )))
+    };
+
+    match result {
+        Ok(content) => {
+            span.record("chars", content.text.len());
+            log::info!("Image text extracted with {}", content.extractor_used);
+            Ok(content)
+        }
+        Err(e) => {
+            log::error!("Image OCR failed: {e}");
+            Err(e)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_media_type() {
+        assert_eq!(
+            detect_media_type(b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR"),
+            Some("image/png")
+        );
+        assert_eq!(
+            detect_media_type(&[0xFF, 0xD8, 0xFF, 0xE0]),
+            Some("image/jpeg")
+        );
+        assert_eq!(detect_media_type(b"GIF89a\x01\0"), Some("image/gif"));
+        assert_eq!(
+            detect_media_type(b"RIFF\x24\0\0\0WEBPVP8 "),
+            Some("image/webp")
+        );
+        assert_eq!(detect_media_type(b"%PDF-1.7"), None);
+        assert_eq!(detect_media_type(b"RIFF"), None);
+    }
+
+    #[test]
+    fn test_image_input_encoding() {
+        let image = ImageInput::from_bytes(b"GIF89a".to_vec()).unwrap();
+        assert_eq!(image.media_type, "image/gif");
+        assert_eq!(image.data_url(), "data:image/gif;base64,R0lGODlh");
+        assert_eq!(image.digest().len(), 64);
+
+        let err = ImageInput::from_bytes(b"<svg/>".to_vec()).unwrap_err();
+        assert_eq!(err.code(), "IMAGE_PROCESSING_FAILED");
+    }
+
+    #[test]
+    fn test_image_dimensions() {
+        let mut png = b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR".to_vec();
+        png.extend_from_slice(&[0, 0, 2, 128, 0, 0, 1, 224]);
+        assert_eq!(image_dimensions(&png), Some((640, 480)));
+
+        assert_eq!(
+            image_dimensions(b"GIF89a\x20\x03\x58\x02"),
+            Some((800, 600))
+        );
+
+        // APP0 segment, then a baseline SOF0 frame of 1024x768
+        let jpeg = [
+            0xFF, 0xD8, 0xFF, 0xE0, 0x00, 0x04, 0x00, 0x00, 0xFF, 0xC0, 0x00, 0x11, 0x08, 0x03,
+            0x00, 0x04, 0x00,
+        ];
+        assert_eq!(image_dimensions(&jpeg), Some((1024, 768)));
+
+        let mut webp = b"RIFF\0\0\0\0WEBPVP8X\0\0\0\0\0\0\0\0".to_vec();
+        webp.extend_from_slice(&[0x3F, 0x01, 0x00, 0xC7, 0x00, 0x00]);
+        assert_eq!(image_dimensions(&webp), Some((320, 200)));
+
+        // Truncated headers are unreadable, not a panic
+        assert_eq!(image_dimensions(b"GIF89a\x20"), None);
+        assert_eq!(image_dimensions(&jpeg[..10]), None);
+    }
+
+    #[test]
+    fn test_from_data_url() {
+        let image = ImageInput::from_data_url("data:image/gif;base64,R0lGODlhAQABAIAAAAAAAP///w==")
+            .unwrap();
+        assert_eq!(image.media_type, "image/gif");
+        assert_eq!(image.dimensions(), Some((1, 1)));
+        assert!(ImageInput::from_data_url("https://example.com/cat.png").is_err());
+        assert!(ImageInput::from_data_url("data:image/png;base64,!!").is_err());
+    }
+
+    #[test]
+    fn test_image_mode_serde() {
+        let mode: ImageMode = serde_json::from_str("\"vision\"").unwrap();
+        assert_eq!(mode, ImageMode::Vision);
+        assert_eq!(ImageMode::default(), ImageMode::Ocr);
+    }
+}
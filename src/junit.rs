@@ -0,0 +1,185 @@
+//! JUnit XML reports of batch evaluations
+//!
+//! Converts batch results into one `<testsuite>` with a `<testcase>` per
+//! batch item, so CI servers (Jenkins, GitLab) display prompt regression
+//! suites natively (`--junit-xml`):
+//!
+//! - `success` items pass; their warnings (e.g. `REFUSAL_DETECTED`) are
+//!   listed in `<system-out>`
+//! - items the pipeline stopped (guardrail and JSON schema validation
+//!   failures, refusals, context limit, deadline, provider content filter)
+//!   are `<failure>`s typed by their outcome code, with the violated rules
+//! - other errors (HTTP, authentication, ...) are `<error>`s
+//!
+//! ```text
+//! <testsuites name="fortified-llm-client" tests="2" failures="1" errors="0" time="1.204">
+//!   <testsuite name="prompts" tests="2" failures="1" errors="0" skipped="0" time="1.204">
+//!     <testcase name="q1" classname="prompts" time="0.812"/>
+//!     <testcase name="q2" classname="prompts" time="0.003">
+//!       <failure type="INPUT_VALIDATION_FAILED" message="...">...</failure>
+//!     </testcase>
+//!   </testsuite>
+//! </testsuites>
+//! ```
+
+use crate::{
+    batch::{BatchItem, BatchOutput},
+    CliOutput, EvaluationOutcome,
+};
+use std::fmt::Write;
+
+/// Name of the `<testsuites>` root element
+pub const JUNIT_ROOT_NAME: &str = "fortified-llm-client";
+
+/// How a batch item is reported
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TestCaseResult {
+    Passed,
+    /// Stopped by the pipeline (`<failure>`)
+    Failed,
+    /// Hard error (`<error>`)
+    Errored,
+}
+
+impl TestCaseResult {
+    pub fn of(output: &CliOutput) -> Self {
+        match &output.error {
+            None if output.status == "success" => Self::Passed,
+            // Codes of CliErrors and custom pipeline stages
+            Some(error) if matches!(error.code, EvaluationOutcome::Other(_)) => Self::Errored,
+            _ => Self::Failed,
+        }
+    }
+}
+
+/// JUnit XML document for `batch`
+///
+/// `items` are the batch's items in input order (for the test case names:
+/// the item id, or `item <index>` without one). `suite_name` names the test
+/// suite and is the class name of every test case (the CLI uses the batch
+/// file stem).
+pub fn batch_report(batch: &BatchOutput, items: &[BatchItem], suite_name: &str) -> String {
+    let results: Vec<TestCaseResult> = batch.results.iter().map(TestCaseResult::of).collect();
+    let count = |kind| results.iter().filter(|r| **r == kind).count();
+    let (tests, failures, errors) = (
+        results.len(),
+        count(TestCaseResult::Failed),
+        count(TestCaseResult::Errored),
+    );
+    let time = seconds(batch.summary.elapsed_ms);
+    let suite = escape(suite_name);
+
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    let _ = writeln!(
+        xml,
+        "<testsuites name=\"{JUNIT_ROOT_NAME}\" tests=\"{tests}\" failures=\"{failures}\" errors=\"{errors}\" time=\"{time}\">"
+    );
+    let _ = writeln!(
+        xml,
+        "  <testsuite name=\"{suite}\" tests=\"{tests}\" failures=\"{failures}\" errors=\"{errors}\" skipped=\"0\" time=\"{time}\">"
+    );
+    for (index, (output, result)) in batch.results.iter().zip(&results).enumerate() {
+        let name = items
+            .get(index)
+            .and_then(|item| item.id.clone())
+            .unwrap_or_else(|| format!("item {index}"));
+        let _ = write!(
+            xml,
+            "    <testcase name=\"{}\" classname=\"{suite}\" time=\"{}\"",
+            escape(&name),
+            seconds(output.metadata.latency_ms)
+        );
+        let warnings = warning_lines(output);
+        if *result == TestCaseResult::Passed && warnings.is_empty() {
+            xml.push_str("/>\n");
+            continue;
+        }
+        xml.push_str(">\n");
+        if let Some(error) = &output.error {
+            let element = match result {
+                TestCaseResult::Errored => "error",
+                _ => "failure",
+            };
+            let _ = writeln!(
+                xml,
+                "      <{element} type=\"{}\" message=\"{}\">{}</{element}>",
+                escape(error.code.as_str()),
+                escape(&error.message),
+                escape(&failure_details(output))
+            );
+        }
+        if !warnings.is_empty() {
+            let _ = writeln!(
+                xml,
+                "      <system-out>{}</system-out>",
+                escape(&warnings.join("\n"))
+            );
+        }
+        xml.push_str("    </testcase>\n");
+    }
+    xml.push_str("  </testsuite>\n</testsuites>\n");
+    xml
+}
+
+/// Failure body: the error message and the violated guardrail rules
+fn failure_details(output: &CliOutput) -> String {
+    let metadata = &output.metadata;
+    let mut details = output
+        .error
+        .as_ref()
+        .map(|e| format!("{}: {}", e.code, e.message))
+        .unwrap_or_default();
+    if !metadata.violation_rules.is_empty() {
+        let _ = write!(
+            details,
+            "\nViolated rules: {}",
+            metadata.violation_rules.join(", ")
+        );
+    }
+    let _ = write!(details, "\nModel: {}", metadata.model);
+    details
+}
+
+fn warning_lines(output: &CliOutput) -> Vec<String> {
+    output
+        .metadata
+        .warnings
+        .iter()
+        .map(|w| format!("WARNING {}: {}", w.code, w.message))
+        .collect()
+}
+
+fn seconds(ms: u64) -> String {
+    format!("{:.3}", ms as f64 / 1000.0)
+}
+
+/// Escape text and attribute values, dropping characters XML 1.0 forbids
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            '\t' | '\n' | '\r' => escaped.push(c),
+            c if c < ' ' || c == '\u{fffe}' || c == '\u{ffff}' => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_escape() {
+        assert_eq!(
+            escape("a<b> & \"c\" 'd'\u{1b}\n"),
+            "a&lt;b&gt; &amp; &quot;c&quot; &apos;d&apos;\n"
+        );
+    }
+}
@@ -2,63 +2,223 @@
 //!
 //! Provides embeddable API for LLM invocation with guardrails and validation.
 
+pub mod accounting;
+pub mod audit;
+pub mod batch;
+pub mod build_info;
+pub mod cache;
+pub mod cassette;
+pub mod chunking;
+pub mod cli;
 mod client;
+pub mod compare;
 pub mod config;
 pub mod config_builder;
+pub mod config_check;
+pub mod confirm;
 pub mod constants;
+pub mod context_probe;
+pub mod data_policy;
+pub mod deadline;
+pub mod debug_bundle;
 mod error;
+pub mod events;
+pub mod experiment;
+pub mod fetch;
+pub mod guardrail_check;
 pub mod guardrails;
+mod image;
+pub mod junit;
+pub mod log_policy;
+pub mod metadata_fields;
+pub mod metrics;
+pub mod model_discovery;
 pub mod model_registry;
 mod models;
 mod output;
 mod pdf;
+pub mod pipeline;
+pub mod prompt_library;
 mod provider;
 pub mod providers;
+pub mod rate_limiter;
+pub mod refusal;
+pub mod repetition;
+pub mod response_limit;
 pub mod schema_validator;
+pub mod secrets;
+pub mod self_test;
+pub mod server;
+pub mod spotlight;
+pub mod storage;
+pub mod sweep;
+pub mod telemetry;
+pub mod tenant;
 mod token_estimator;
+pub mod tokenizer;
+pub mod trends;
 
+pub use accounting::{api_key_account, Pricing, Quota, UsageAccountant, UsageRecord};
+pub use audit::{
+    compact_audit_log, AuditEvent, AuditEventKind, AuditSink, BlobStore, CompactionReport,
+    JsonlAuditLog, MemoryAuditLog, PromptAuditConfig, PromptAuditor, StorageAuditLog,
+};
+pub use batch::{
+    evaluate_batch, evaluate_batch_with_events, BatchItem, BatchOutput, BatchSummary, TagSummary,
+};
+pub use build_info::{build_info, BuildInfo};
+pub use cache::{
+    CacheBackend, CacheConfig, CachedResponse, MemoryCache, ResponseCache, StorageCache,
+};
+pub use cassette::Cassette;
+pub use chunking::{ChunkRecord, ChunkStrategy, ChunkingConfig, ChunkingRecord};
+pub use cli::run_from_args;
 pub use client::{LlmClient, Provider};
-pub use config::{load_config_file, ConfigFileRequest};
+pub use compare::{evaluate_compare, validate_compare_models, CompareReport, ModelComparison};
+pub use config::{load_config_file, load_config_file_with_profile, ConfigFileRequest};
+pub use config_builder::{ConfigWarning, ParameterPreset, ValidationIssue};
+pub use config_check::{check_config, resolved_config, ConfigCheckReport};
+pub use confirm::{ConfirmStage, PreviewEndpoint, RequestPreview, SensitiveCategory};
+pub use context_probe::{ContextProbeConfig, ContextProber};
+pub use data_policy::{classify_endpoint, DataClassification, DataPolicy, EndpointClass};
+pub use deadline::DeadlineConfig;
+pub use debug_bundle::{BundleFailure, DebugBundle};
 pub use error::CliError;
+pub use events::{Event, EventWriter};
+pub use experiment::{
+    run_experiment, Assertion, Assignment, ExperimentConfig, ExperimentReport, JudgeConfig,
+    PromptVariant, VariantComparison, VariantReport,
+};
+pub use fetch::{fetch_prompt, fetch_url, FetchConfig, FetchedPage};
+pub use guardrail_check::{check_guardrail, GuardrailCheckReport};
 pub use guardrails::{
     create_guardrail_provider,
+    create_metered_guardrail_provider,
+    rule_catalog,
 
     AggregationMode,
+    BudgetAction,
+    Condition,
+    ConditionalGuardrail,
+    EndpointBudgetConfig,
+    EscalationConfig,
+    EscalationRecord,
     ExecutionMode,
+    GuardBudget,
+    GuardBudgetConfig,
+    GuardMeter,
+    GuardTokenUsage,
     // Configuration
     GuardrailConfig,
+    GuardrailConfigBuilder,
+    GuardrailOverride,
     // Trait types
     GuardrailProvider,
     GuardrailProviderConfig,
     GuardrailResult,
+    GuardrailStage,
+    HashListConfig,
+    HashListGuardrail,
+    HashListMatch,
+    HashListResult,
     HybridGuardrail,
+    ImageGuardrail,
+    ImageGuardrailConfig,
+    ImageScannerConfig,
 
+    InputFacts,
     InputGuardrail,
+    InputSourcesConfig,
+    JsonSchemaConfig,
+    JsonSchemaGuardrail,
+    LanguageConfig,
+    LanguageGuardrail,
     LlamaGuardCategory,
     LlamaGuardConfig,
+    LlamaGuardCustomCategory,
     LlamaGuardProvider,
     LlamaPromptGuardConfig,
     LlamaPromptGuardProvider,
     LlamaPromptGuardResult,
+    NamedProviderResult,
+    NemoGuardrailsConfig,
+    NemoGuardrailsProvider,
+    NemoGuardrailsResult,
+    NumericConsistencyConfig,
+    NumericConsistencyGuardrail,
+    OutputContext,
     OutputGuardrail,
+    OverridePolicyConfig,
     ProviderSpecificResult,
+    ProviderVote,
+    Redaction,
+    RegexAction,
     RegexGuardrail,
     RegexGuardrailConfig,
-
+    RuleInfo,
+    SemanticSimilarityConfig,
+    SemanticSimilarityGuardrail,
+    SemanticSimilarityResult,
     // Common types
     Severity,
+    TerminologyConfig,
+    TerminologyGuardrail,
+
     Violation,
+    ViolationPreview,
+    VoteResult,
 };
+pub use image::{
+    detect_media_type, extract_text_from_image, image_dimensions, is_tesseract_available,
+    ImageInput, ImageMode, ImageText,
+};
+pub use log_policy::{log_content_policy, loggable, set_log_content_policy, LogContentPolicy};
+pub use metadata_fields::{MetadataFields, METADATA_FIELD_GROUPS};
+pub use metrics::{MetricsRegistry, MetricsSnapshot};
+pub use model_discovery::{discover_models, AvailableModel, ModelListing};
 pub use models::*;
-pub use output::{CliOutput, ErrorInfo, Metadata};
+pub use output::{
+    CliOutput, ErrorInfo, EvaluationOutcome, EvaluationWarning, Metadata, OutputFormat,
+    StageLatency, OUTPUT_SCHEMA_VERSION,
+};
 pub use pdf::{
     extract_text_from_pdf, is_docling_available, to_markdown, ContentFormat, PdfContent,
 };
-pub use provider::{InvokeParams, LlmProvider, ProviderType};
-pub use providers::{create_provider, detect_provider_type, OllamaProvider, OpenAIProvider};
+pub use pipeline::{EvaluationContext, Pipeline, Stage, StreamingInvokeStage};
+pub use prompt_library::{
+    LintIssue, LintReport, PromptLibrary, PromptLibraryConfig, PromptMetadata, PromptPin,
+    PromptRef, PromptTemplate,
+};
+pub use provider::{
+    ChunkStream, InvokeParams, LlmProvider, ModelEcho, ProviderResponse, ProviderType, TokenUsage,
+    UpstreamInfo,
+};
+pub use providers::{
+    create_provider, detect_provider_type, AnthropicProvider, BedrockConfig, BedrockProvider,
+    OllamaProvider, OpenAIProvider,
+};
+pub use rate_limiter::{ProviderRateLimit, RateLimitConfig, RateLimiter};
+pub use refusal::{RefusalAction, RefusalConfig};
+pub use repetition::RepetitionConfig;
+pub use secrets::SecretSource;
+pub use self_test::{ensure_self_test, run_self_test, SelfTestCheck, SelfTestReport};
+pub use server::{EvaluateRequest, Server};
+pub use spotlight::{PromptSegment, PromptSource, SpotlightConfig, SpotlightStrategy};
+pub use storage::{FsStorage, MemoryStorage, Storage};
+pub use sweep::{run_sweep, PointReport, SweepConfig, SweepPoint, SweepReport};
+pub use telemetry::{OtlpExporter, SpanCollector};
+pub use tenant::{TenantMetrics, TenantRegistry, TenantRequest};
 pub use token_estimator::TokenEstimator;
+#[cfg(feature = "hf-tokenizers")]
+pub use tokenizer::HuggingFaceTokenizer;
+#[cfg(feature = "tiktoken")]
+pub use tokenizer::TiktokenTokenizer;
+pub use tokenizer::{
+    register_tokenizer, tokenizer_for_model, HeuristicTokenizer, TokenizerBackend,
+};
+pub use trends::{config_fingerprint, TrendReport, TrendStatsConfig, TrendStore};
 
-use std::{path::PathBuf, time::Instant};
+use std::{path::PathBuf, sync::Arc};
 
 /// Configuration for LLM evaluation
 #[derive(Debug, Clone)]
@@ -69,338 +229,132 @@ pub struct EvaluationConfig {
     pub user_prompt: String,
     pub provider: Option<Provider>,
     pub temperature: f32,
+    pub top_p: Option<f32>,
     pub max_tokens: Option<u32>,
     pub seed: Option<u64>,
     pub api_key: Option<String>,
     pub timeout_secs: u64,
+    /// Maximum size in bytes of response bodies read from providers,
+    /// guardrails and context probes
+    pub max_response_bytes: usize,
     pub validate_tokens: bool,
     pub context_limit: Option<usize>,
+    /// Asks the provider for the context window when `context_limit` is unknown
+    pub context_probe: Option<Arc<ContextProber>>,
+    /// Context usage percent that triggers a headroom warning (None = default 90)
+    pub context_warning_percent: Option<u8>,
     pub response_format: Option<ResponseFormat>,
+    /// Functions the model may call; requested calls are returned in `CliOutput::tool_calls`
+    pub tools: Vec<ToolDefinition>,
     pub pdf_input: Option<PathBuf>,
+    /// Web page fetched and converted to markdown as the user prompt
+    pub url_input: Option<String>,
+    /// Prompt library URL fetched as the system prompt by the `extract` stage
+    /// (needs an allowlisted host, see [`FetchConfig::allowed_hosts`])
+    pub system_prompt_url: Option<String>,
+    /// Limits, host allowlist and SSRF policy for `url_input` and
+    /// `system_prompt_url` (None = defaults)
+    pub fetch: Option<FetchConfig>,
+    /// Image OCR'd into the user prompt or attached for vision models (see `image_mode`)
+    pub image_input: Option<PathBuf>,
+    pub image_mode: ImageMode,
+    /// Wrapper around the (extracted) user prompt, e.g. delimiters for untrusted content
+    pub user_prompt_prefix: Option<String>,
+    pub user_prompt_suffix: Option<String>,
+    /// Files whose text is appended to the user prompt as untrusted content
+    pub context_files: Vec<PathBuf>,
+    /// Untrusted data appended to the user prompt (e.g. retrieved chunks)
+    pub untrusted_content: Vec<String>,
+    /// Spotlighting applied to untrusted segments (PDF text, `untrusted_content`)
+    pub spotlight: Option<SpotlightConfig>,
     pub input_guardrails: Option<GuardrailProviderConfig>,
     pub output_guardrails: Option<GuardrailProviderConfig>,
+    /// Rules skipping or adding guardrail providers depending on the input
+    pub guardrail_conditions: Vec<ConditionalGuardrail>,
+    /// Validate each prompt source separately (None = one check of the merged prompt)
+    pub input_sources: Option<InputSourcesConfig>,
+    /// Stricter input guardrail consulted when the input guardrails warn
+    pub escalation: Option<EscalationConfig>,
+    /// Detects model refusals in the response (warning or `REFUSAL` outcome)
+    pub refusal_detection: Option<RefusalConfig>,
+    /// Detects repetition loops in the response (`REPETITION` warning, optional retry)
+    pub repetition_detection: Option<RepetitionConfig>,
+    /// Splits documents exceeding the context window into chunks (map-reduce or refine)
+    pub chunking: Option<ChunkingConfig>,
+    /// Wall-time limit of the evaluation, optionally skipping non-critical guardrails
+    pub deadline: Option<DeadlineConfig>,
+    /// Bedrock region and credentials (None = AWS environment variables)
+    pub bedrock: Option<BedrockConfig>,
     // Source tracking for metadata (mutually exclusive with inline text)
     pub system_prompt_file: Option<PathBuf>,
     pub user_prompt_file: Option<PathBuf>,
-}
-
-/// Helper to create Metadata from config
-fn create_metadata(
-    config: &EvaluationConfig,
-    user_prompt: &str,
-    tokens_estimated: usize,
-    latency_ms: u64,
-    output_guardrails_enabled: bool,
-) -> Metadata {
-    Metadata {
-        // Execution results
-        model: config.model.clone(),
-        tokens_estimated,
-        latency_ms,
-        timestamp: chrono::Utc::now().to_rfc3339(),
-
-        // Input configuration (for reproducibility)
-        api_url: config.api_url.clone(),
-        provider: config.provider.map(|p| format!("{p:?}")),
-        temperature: config.temperature,
-        max_tokens: config.max_tokens,
-        seed: config.seed,
-        timeout_secs: config.timeout_secs,
-        context_limit: config.context_limit,
-        response_format: config.response_format.as_ref().map(|f| f.to_string()),
-        validate_tokens: config.validate_tokens,
-
-        // Input sources (distinguish between text and file inputs)
-        system_prompt_text: if config.system_prompt_file.is_none() {
-            Some(config.system_prompt.clone())
-        } else {
-            None
-        },
-        system_prompt_file: config
-            .system_prompt_file
-            .as_ref()
-            .map(|p| p.display().to_string()),
-        user_prompt_text: if config.user_prompt_file.is_none() && config.pdf_input.is_none() {
-            Some(user_prompt.to_string())
-        } else {
-            None
-        },
-        user_prompt_file: config
-            .user_prompt_file
-            .as_ref()
-            .map(|p| p.display().to_string()),
-        pdf_input: config.pdf_input.as_ref().map(|p| p.display().to_string()),
-
-        // Guardrails
-        input_guardrails_enabled: config.input_guardrails.as_ref().map(|_| true),
-        output_guardrails_enabled: if output_guardrails_enabled {
-            Some(true)
-        } else {
-            None
-        },
-    }
+    /// Library prompts resolved from config file references (name, version, hash)
+    pub prompt_pins: Vec<PromptPin>,
+    /// Override controller (downgrades allowlisted guardrail blocks to warnings)
+    pub guardrail_override: Option<Arc<GuardrailOverride>>,
+    /// Caller-supplied override token checked against the controller's secret
+    pub override_token: Option<String>,
+    /// Token budget of model-backed guardrails, shared across evaluations
+    pub guard_budget: Option<Arc<GuardBudget>>,
+    /// Prompt audit trail (payloads stored once by content hash)
+    pub prompt_audit: Option<Arc<PromptAuditor>>,
+    /// Cross-run quality/refusal/violation statistics keyed by config fingerprint
+    pub trend_stats: Option<Arc<TrendStore>>,
+    /// Returns stored responses for repeated requests instead of calling the LLM
+    pub response_cache: Option<Arc<ResponseCache>>,
+    /// Request and token rate limits per provider, shared across evaluations
+    pub rate_limiter: Option<Arc<RateLimiter>>,
+    /// Refuses restricted data for non-allowlisted external endpoints
+    pub data_policy: Option<DataPolicy>,
+    /// Metadata fields kept in serialized outputs (None = all)
+    pub metadata_fields: Option<MetadataFields>,
+    /// Counters and latency histograms shared across evaluations
+    pub metrics: Option<Arc<MetricsRegistry>>,
+    /// Labels echoed in the metadata and grouped in batch summaries (e.g. "experiment=A")
+    pub tags: Vec<String>,
+    /// Non-fatal merge problems found while building the config
+    pub config_warnings: Vec<ConfigWarning>,
 }
 
 /// Main evaluation function with optional input and output guardrails
+///
+/// Runs the default [`Pipeline`]; use [`Pipeline::run`] for customized stages.
 pub async fn evaluate(config: EvaluationConfig) -> Result<CliOutput, CliError> {
-    evaluate_internal(config).await
+    Pipeline::default().run(config).await
 }
 
-/// Internal evaluation implementation
-async fn evaluate_internal(config: EvaluationConfig) -> Result<CliOutput, CliError> {
-    let start_time = Instant::now();
-
-    // Warn if input guardrails are disabled
-    if config.input_guardrails.is_none() {
-        log::warn!(
-            "Input guardrails are disabled. User-provided content will not be validated. \
-            This may allow malicious prompts, PII, or injection attacks. \
-            Consider enabling input guardrails for production use."
-        );
-    }
-
-    // Warn if output guardrails are disabled
-    if config.output_guardrails.is_none() {
-        log::warn!(
-            "Output guardrails are disabled. LLM responses will not be validated. \
-            This may allow unsafe, low-quality, or policy-violating content. \
-            Consider enabling output guardrails for production use."
-        );
-    }
-
-    // 1. PDF extraction (if PDF input provided)
-    let user_prompt = if let Some(pdf_path) = &config.pdf_input {
-        // Validate PDF file size before extraction (security protection)
-        let file_metadata = std::fs::metadata(pdf_path).map_err(|e| {
-            CliError::FileNotFound(format!(
-                "Failed to read PDF file metadata '{}': {e}",
-                pdf_path.display()
-            ))
-        })?;
-
-        let file_size = file_metadata.len();
-        if file_size > constants::pdf_limits::MAX_PDF_SIZE_BYTES {
-            let metadata = create_metadata(
-                &config,
-                "", // No user prompt yet
-                0,  // No tokens estimated yet
-                start_time.elapsed().as_millis() as u64,
-                config.output_guardrails.is_some(),
-            );
-            return Ok(CliOutput::error(
-                "FILE_TOO_LARGE".to_string(),
-                format!(
-                    "PDF file size ({} bytes, {:.2} MB) exceeds maximum allowed size ({} bytes, {:.2} MB). \
-                    This limit prevents resource exhaustion from large files.",
-                    file_size,
-                    file_size as f64 / 1_048_576.0,
-                    constants::pdf_limits::MAX_PDF_SIZE_BYTES,
-                    constants::pdf_limits::MAX_PDF_SIZE_BYTES as f64 / 1_048_576.0
-                ),
-                metadata,
-            ));
-        }
-
-        log::debug!(
-            "PDF file size: {} bytes ({:.2} MB)",
-            file_size,
-            file_size as f64 / 1_048_576.0
-        );
-
-        let content = extract_text_from_pdf(pdf_path).await?;
-        let char_count = content.text.len();
-        let word_count = content.text.split_whitespace().count();
-        log::debug!(
-            "Extracted {} characters ({} words) from PDF using {} (format: {:?})",
-            char_count,
-            word_count,
-            content.extractor_used,
-            content.format
-        );
-        if let Some(size) = content.file_size_bytes {
-            log::debug!(
-                "PDF file size: {} bytes ({:.2} KB)",
-                size,
-                size as f64 / 1024.0
-            );
-        }
-        for warning in &content.warnings {
-            log::debug!("PDF extraction: {warning}");
-        }
-        content.text
-    } else {
-        config.user_prompt.clone()
-    };
-
-    // 2. Input guardrails (AFTER PDF extraction)
-    // NOTE: Only validate user-provided content, NOT system prompt
-    // System prompts are trusted, developer-controlled content
-    if let Some(guardrail_config) = &config.input_guardrails {
-        log::info!("Running input guardrails validation");
-        let guardrail = create_guardrail_provider(guardrail_config)?;
-        // SECURITY: Only validate user input, not system prompt
-        let validation = guardrail.validate(&user_prompt).await?;
-
-        if !validation.passed {
-            log::error!("Input guardrails validation FAILED");
-            let metadata = create_metadata(
-                &config,
-                &user_prompt,
-                0,
-                start_time.elapsed().as_millis() as u64,
-                config.output_guardrails.is_some(),
-            );
-
-            let error_msg = validation
-                .violations
-                .iter()
-                .map(|v| format!("{}: {}", v.rule, v.message))
-                .collect::<Vec<_>>()
-                .join("; ");
-
-            log::error!("Violations: {error_msg}");
-
-            return Ok(CliOutput::error(
-                "INPUT_VALIDATION_FAILED".to_string(),
-                error_msg,
-                metadata,
-            ));
-        }
-
-        log::info!("Input guardrails validation PASSED");
-
-        // Log warnings
-        for warning in validation.warnings {
-            log::warn!("{}: {}", warning.rule, warning.message);
-        }
-    }
-
-    // 3. Token validation (if enabled)
-    let tokens_estimated = if config.validate_tokens {
-        // Use model-specific token estimation if model is recognized
-        // For estimation purposes only, use DEFAULT_MAX_TOKENS if not specified
-        let estimator = TokenEstimator::new_for_model(
-            &config.system_prompt,
-            &user_prompt,
-            config
-                .max_tokens
-                .unwrap_or(constants::llm_defaults::DEFAULT_MAX_TOKENS),
-            &config.model,
-        );
-        let required = estimator.total_tokens_required();
-
-        // Log token breakdown
-        let breakdown = estimator.breakdown();
-        log::debug!(
-            "Token estimate: system={}, user={}, response_buffer={}, total={}",
-            breakdown.system_tokens,
-            breakdown.user_tokens,
-            breakdown.response_buffer,
-            breakdown.total_required
-        );
-
-        if let Some(limit) = config.context_limit {
-            if required > limit {
-                let metadata = create_metadata(
-                    &config,
-                    &user_prompt,
-                    required,
-                    start_time.elapsed().as_millis() as u64,
-                    config.output_guardrails.is_some(),
-                );
-                return Ok(CliOutput::error(
-                    "CONTEXT_LIMIT_EXCEEDED".to_string(),
-                    format!(
-                        "Context requirement ({} tokens) exceeds model limit ({} tokens) by {} tokens",
-                        required, limit, required - limit
-                    ),
-                    metadata,
-                ));
-            }
-        }
-        required
-    } else {
-        // Even when validation is disabled, use model-specific estimation for metadata
-        // For estimation purposes only, use DEFAULT_MAX_TOKENS if not specified
-        TokenEstimator::new_for_model(
-            &config.system_prompt,
-            &user_prompt,
-            config
-                .max_tokens
-                .unwrap_or(constants::llm_defaults::DEFAULT_MAX_TOKENS),
-            &config.model,
-        )
-        .total_tokens_required()
-    };
-
-    // 4. LLM invocation
-    let client = LlmClient::new(config.api_url.clone(), config.provider);
-    let response = client
-        .invoke(InvokeParams {
-            model: &config.model,
-            system_prompt: &config.system_prompt,
-            user_prompt: &user_prompt,
-            temperature: config.temperature,
-            max_tokens: config.max_tokens,
-            seed: config.seed,
-            api_key: config.api_key.as_deref(),
-            timeout_secs: config.timeout_secs,
-            response_format: config.response_format.as_ref(),
-        })
-        .await?;
-
-    // 5. Output guardrails (if enabled)
-    let output_guardrails_enabled = config.output_guardrails.is_some();
-    if let Some(guardrail_config) = &config.output_guardrails {
-        let guardrail = create_guardrail_provider(guardrail_config)?;
-        let validation = guardrail.validate(&response).await?;
-
-        if !validation.passed {
-            let metadata = create_metadata(
-                &config,
-                &user_prompt,
-                tokens_estimated,
-                start_time.elapsed().as_millis() as u64,
-                true, // output guardrails are enabled (we're in this block)
-            );
-
-            let error_msg = validation
-                .violations
-                .iter()
-                .map(|v| format!("{}: {}", v.rule, v.message))
-                .collect::<Vec<_>>()
-                .join("; ");
-
-            return Ok(CliOutput::error(
-                "OUTPUT_VALIDATION_FAILED".to_string(),
-                error_msg,
-                metadata,
-            ));
-        }
-
-        // Log quality score and warnings
-        if let Some(score) = validation.quality_score {
-            log::info!("Response quality score: {score:.1}/10");
-        }
-        for warning in validation.warnings {
-            let rule = &warning.rule;
-            let message = &warning.message;
-            log::warn!("{rule}: {message}");
-        }
-    }
-
-    // 6. Create output
-    let metadata = create_metadata(
-        &config,
-        &user_prompt,
-        tokens_estimated,
-        start_time.elapsed().as_millis() as u64,
-        output_guardrails_enabled,
-    );
+/// Evaluation that streams the response to `on_chunk` as it is generated
+///
+/// Runs the default [`Pipeline`] with [`StreamingInvokeStage`] as the invoke
+/// stage. Output guardrails and refusal detection run on the accumulated
+/// response once the stream ends, so streamed chunks are provisional: if the
+/// returned output is not a success, discard what was already displayed.
+pub async fn evaluate_streaming(
+    config: EvaluationConfig,
+    on_chunk: impl Fn(&str) + Send + Sync + 'static,
+) -> Result<CliOutput, CliError> {
+    Pipeline::default()
+        .replace(
+            pipeline::stage_names::INVOKE,
+            StreamingInvokeStage::new(on_chunk),
+        )?
+        .run(config)
+        .await
+}
 
-    Ok(CliOutput::success(
-        response,
-        metadata,
-        config.response_format.as_ref(),
-    ))
+/// Evaluation that asks `confirm` before sending anything
+///
+/// Runs the default [`Pipeline`] with a [`ConfirmStage`] after `extract`:
+/// `confirm` gets a [`RequestPreview`] (endpoints, model, byte counts,
+/// detected sensitive categories) before any guardrail or LLM endpoint
+/// receives content. Returning false fails the evaluation with
+/// `CliError::RequestDeclined`.
+pub async fn evaluate_with_confirmation(
+    config: EvaluationConfig,
+    confirm: impl Fn(&RequestPreview) -> bool + Send + Sync + 'static,
+) -> Result<CliOutput, CliError> {
+    Pipeline::default()
+        .insert_after(pipeline::stage_names::EXTRACT, ConfirmStage::new(confirm))?
+        .run(config)
+        .await
 }
@@ -0,0 +1,157 @@
+//! Content policy for log output
+//!
+//! Prompts, responses and guardrail findings can contain user data. Every log
+//! line that embeds such content passes it through [`loggable`], which applies
+//! the process-wide [`LogContentPolicy`]:
+//!
+//! - `never`: content is replaced with its size (`[412 bytes withheld]`)
+//! - `truncated:N`: content is cut after N characters
+//! - `full`: content is logged as-is (default)
+//!
+//! Lengths, counts, rule IDs and other metadata are logged regardless.
+
+use serde::{Deserialize, Serialize};
+use std::{borrow::Cow, fmt, str::FromStr, sync::RwLock};
+
+/// Characters kept by `truncated` when no length is given
+pub const DEFAULT_TRUNCATE_CHARS: usize = 200;
+
+static POLICY: RwLock<LogContentPolicy> = RwLock::new(LogContentPolicy::Full);
+
+/// How much prompt/response content may appear in log lines
+///
+/// Written as `"never"`, `"full"`, `"truncated"` or `"truncated:N"` in config
+/// files and on the command line.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum LogContentPolicy {
+    Never,
+    Truncated(usize),
+    #[default]
+    Full,
+}
+
+impl LogContentPolicy {
+    /// Render content for a log line according to this policy
+    pub fn apply<'a>(&self, content: &'a str) -> Cow<'a, str> {
+        match *self {
+            Self::Full => Cow::Borrowed(content),
+            Self::Never => Cow::Owned(format!("[{} bytes withheld]", content.len())),
+            Self::Truncated(max) => match content.char_indices().nth(max) {
+                Some((end, _)) => Cow::Owned(format!(
+                    "{}... [{} bytes total]",
+                    &content[..end],
+                    content.len()
+                )),
+                None => Cow::Borrowed(content),
+            },
+        }
+    }
+}
+
+impl fmt::Display for LogContentPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Never => f.write_str("never"),
+            Self::Truncated(max) => write!(f, "truncated:{max}"),
+            Self::Full => f.write_str("full"),
+        }
+    }
+}
+
+impl FromStr for LogContentPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "never" => Ok(Self::Never),
+            "full" => Ok(Self::Full),
+            "truncated" => Ok(Self::Truncated(DEFAULT_TRUNCATE_CHARS)),
+            other => other
+                .strip_prefix("truncated:")
+                .and_then(|n| n.parse().ok())
+                .map(Self::Truncated)
+                .ok_or_else(|| {
+                    format!(
+                        "Invalid log content policy '{s}' \
+                         (expected never, full, truncated or truncated:N)"
+                    )
+                }),
+        }
+    }
+}
+
+impl TryFrom<String> for LogContentPolicy {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl From<LogContentPolicy> for String {
+    fn from(policy: LogContentPolicy) -> Self {
+        policy.to_string()
+    }
+}
+
+/// Set the policy used by [`loggable`] for the whole process
+pub fn set_log_content_policy(policy: LogContentPolicy) {
+    *POLICY.write().expect("log policy lock poisoned") = policy;
+}
+
+/// Current process-wide policy
+pub fn log_content_policy() -> LogContentPolicy {
+    *POLICY.read().expect("log policy lock poisoned")
+}
+
+/// Render content for a log line according to the process-wide policy
+pub fn loggable(content: &str) -> Cow<'_, str> {
+    log_content_policy().apply(content)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_policies_render_content() {
+        let content = "My email is jane@example.com";
+        assert_eq!(LogContentPolicy::Full.apply(content), content);
+        assert_eq!(
+            LogContentPolicy::Never.apply(content),
+            "[28 bytes withheld]"
+        );
+        assert_eq!(
+            LogContentPolicy::Truncated(5).apply(content),
+            "My em... [28 bytes total]"
+        );
+        assert_eq!(LogContentPolicy::Truncated(100).apply(content), content);
+        // Cuts on character boundaries
+        assert_eq!(
+            LogContentPolicy::Truncated(1).apply("éé"),
+            "é... [4 bytes total]"
+        );
+    }
+
+    #[test]
+    fn test_policy_parsing_round_trips() {
+        for text in ["never", "full", "truncated:80"] {
+            let policy: LogContentPolicy = text.parse().unwrap();
+            assert_eq!(policy.to_string(), text);
+        }
+        assert_eq!(
+            "truncated".parse(),
+            Ok(LogContentPolicy::Truncated(DEFAULT_TRUNCATE_CHARS))
+        );
+        assert!("truncated:many".parse::<LogContentPolicy>().is_err());
+        assert!("sometimes".parse::<LogContentPolicy>().is_err());
+
+        let json = serde_json::to_string(&LogContentPolicy::Truncated(10)).unwrap();
+        assert_eq!(json, r#""truncated:10""#);
+        assert_eq!(
+            serde_json::from_str::<LogContentPolicy>(r#""never""#).unwrap(),
+            LogContentPolicy::Never
+        );
+    }
+}
@@ -0,0 +1,242 @@
+//! Selection of the metadata fields written to outputs
+//!
+//! Outputs sent to third parties (batch results, server responses, uploaded
+//! reports) may need to leave out deployment details such as the `api_url`
+//! or the prompt texts. `[metadata_fields]` names fields or field groups to
+//! keep (`include`) and to drop (`exclude`):
+//!
+//! ```toml
+//! [metadata_fields]
+//! exclude = ["api_url", "prompts"]     # Drop the endpoint URL and prompt texts
+//! # include = ["model", "tokens", "timing"]   # Keep only these (default: all)
+//! ```
+//!
+//! Exclusions win over inclusions. Only the serialized
+//! [`CliOutput`](crate::CliOutput) is filtered: the [`Metadata`] struct
+//! stays complete for library callers, and with a `[prompt_audit]` log the
+//! full metadata of each evaluation is recorded there (prompt texts as blob
+//! references).
+
+use crate::output::Metadata;
+use serde::{Deserialize, Serialize, Serializer};
+
+/// Metadata field groups, by name
+///
+/// Every serialized [`Metadata`] field belongs to exactly one group.
+pub const METADATA_FIELD_GROUPS: &[(&str, &[&str])] = &[
+    ("model", &["model"]),
+    (
+        "tokens",
+        &[
+            "tokens_estimated",
+            "tokens_prompt_actual",
+            "tokens_completion_actual",
+            "context_usage_ratio",
+            "context_limit",
+            "guard_tokens",
+        ],
+    ),
+    ("timing", &["latency_ms", "timestamp", "stage_latency"]),
+    (
+        "endpoint",
+        &["api_url", "provider", "upstream", "model_echo", "cache_hit"],
+    ),
+    (
+        "sampling",
+        &[
+            "temperature",
+            "top_p",
+            "max_tokens",
+            "seed",
+            "timeout_secs",
+            "response_format",
+            "validate_tokens",
+        ],
+    ),
+    (
+        "prompts",
+        &[
+            "system_prompt_text",
+            "system_prompt_file",
+            "user_prompt_text",
+            "user_prompt_file",
+            "user_prompt_prefix",
+            "user_prompt_suffix",
+            "pdf_input",
+            "url_input",
+            "system_prompt_url",
+            "prompt_pins",
+            "image_input",
+            "image_mode",
+            "spotlighting",
+            "chunking",
+        ],
+    ),
+    (
+        "guardrails",
+        &[
+            "input_guardrails_enabled",
+            "output_guardrails_enabled",
+            "violation_rules",
+            "violation_previews",
+            "redactions",
+            "guardrail_results",
+            "guardrail_explanations",
+            "escalation",
+            "quality_score",
+        ],
+    ),
+    (
+        "diagnostics",
+        &["config_warnings", "warnings", "response_chunks_aggregated"],
+    ),
+    ("tags", &["tags"]),
+];
+
+/// Metadata fields kept in serialized outputs (`[metadata_fields]`)
+///
+/// Entries are field names (`api_url`) or group names (`prompts`, see
+/// [`METADATA_FIELD_GROUPS`]).
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MetadataFields {
+    /// Fields to keep (empty = all)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub include: Vec<String>,
+
+    /// Fields to drop, even if included
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub exclude: Vec<String>,
+}
+
+/// Group of a field, if it is a metadata field
+fn group_of(field: &str) -> Option<&'static str> {
+    METADATA_FIELD_GROUPS
+        .iter()
+        .find(|(_, fields)| fields.contains(&field))
+        .map(|(group, _)| *group)
+}
+
+fn matches(entries: &[String], field: &str) -> bool {
+    let group = group_of(field);
+    entries
+        .iter()
+        .any(|entry| entry == field || Some(entry.as_str()) == group)
+}
+
+impl MetadataFields {
+    /// Drop `fields` (names or groups)
+    pub fn exclude<I, S>(mut self, fields: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.exclude.extend(fields.into_iter().map(Into::into));
+        self
+    }
+
+    /// Keep only `fields` (names or groups)
+    pub fn include<I, S>(mut self, fields: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.include.extend(fields.into_iter().map(Into::into));
+        self
+    }
+
+    /// Entries must be known field or group names
+    pub fn validate(&self) -> Result<(), String> {
+        for (list, entries) in [("include", &self.include), ("exclude", &self.exclude)] {
+            for entry in entries {
+                let known = METADATA_FIELD_GROUPS
+                    .iter()
+                    .any(|(group, fields)| group == entry || fields.contains(&entry.as_str()));
+                if !known {
+                    let groups: Vec<&str> = METADATA_FIELD_GROUPS
+                        .iter()
+                        .map(|(group, _)| *group)
+                        .collect();
+                    return Err(format!(
+                        "metadata_fields.{list}: unknown field or group '{entry}'. Groups: {}",
+                        groups.join(", ")
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether `field` is written to outputs
+    pub fn allows(&self, field: &str) -> bool {
+        (self.include.is_empty() || matches(&self.include, field)) && !matches(&self.exclude, field)
+    }
+
+    /// Remove the fields not allowed from serialized metadata
+    pub fn filter(&self, metadata: &mut serde_json::Map<String, serde_json::Value>) {
+        metadata.retain(|field, _| self.allows(field));
+    }
+}
+
+/// Serialize `metadata` without the fields its filter drops
+pub(crate) fn serialize_filtered<S: Serializer>(
+    metadata: &Metadata,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    let Some(fields) = &metadata.field_filter else {
+        return metadata.serialize(serializer);
+    };
+    let mut value = serde_json::to_value(metadata).map_err(serde::ser::Error::custom)?;
+    if let Some(map) = value.as_object_mut() {
+        fields.filter(map);
+    }
+    value.serialize(serializer)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_include_exclude_groups_and_fields() {
+        let fields = MetadataFields::default().exclude(["api_url", "prompts"]);
+        assert!(fields.validate().is_ok());
+        assert!(fields.allows("model"));
+        assert!(fields.allows("provider"));
+        assert!(!fields.allows("api_url"));
+        assert!(!fields.allows("user_prompt_text"));
+        assert!(!fields.allows("system_prompt_file"));
+
+        let fields = MetadataFields::default()
+            .include(["model", "tokens"])
+            .exclude(["context_limit"]);
+        assert!(fields.allows("model"));
+        assert!(fields.allows("tokens_estimated"));
+        assert!(!fields.allows("context_limit"));
+        assert!(!fields.allows("latency_ms"));
+
+        let mut map = serde_json::json!({"model": "m", "latency_ms": 5, "context_limit": 10});
+        fields.filter(map.as_object_mut().unwrap());
+        assert_eq!(map, serde_json::json!({"model": "m"}));
+    }
+
+    #[test]
+    fn test_validate_rejects_unknown_names() {
+        let err = MetadataFields::default()
+            .exclude(["api_ur1"])
+            .validate()
+            .unwrap_err();
+        assert!(err.contains("metadata_fields.exclude"), "{err}");
+        assert!(err.contains("'api_ur1'"), "{err}");
+        assert!(err.contains("prompts"), "{err}");
+    }
+
+    #[test]
+    fn test_groups_are_disjoint() {
+        let mut seen = std::collections::HashSet::new();
+        for (group, fields) in METADATA_FIELD_GROUPS {
+            for field in *fields {
+                assert!(seen.insert(*field), "'{field}' listed twice ({group})");
+            }
+        }
+    }
+}
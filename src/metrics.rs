@@ -0,0 +1,390 @@
+//! Evaluation metrics
+//!
+//! [`MetricsRegistry`] aggregates counters and latency histograms across
+//! evaluations: requests by model, status and outcome, guardrail blocks by
+//! rule, estimated tokens, and end-to-end and per-stage latency. Attach it
+//! with `ConfigBuilder::metrics` and either read a [`MetricsSnapshot`] or
+//! render the Prometheus text exposition format for a `/metrics` endpoint:
+//!
+//! ```text
+//! # TYPE fortified_llm_requests_total counter
+//! fortified_llm_requests_total{model="gpt-4",status="error",outcome="INPUT_VALIDATION_FAILED"} 2
+//! fortified_llm_guardrail_blocks_total{direction="input",rule="regex.MAX_LENGTH"} 2
+//! fortified_llm_request_duration_seconds_bucket{model="gpt-4",le="0.5"} 7
+//! ```
+
+use crate::{guardrails::Violation, CliError, CliOutput};
+use serde::Serialize;
+use std::{collections::BTreeMap, fmt::Write, sync::Mutex, time::Duration};
+
+/// Upper bounds (seconds) of the latency histogram buckets
+pub const LATENCY_BUCKETS: &[f64] = &[
+    0.01, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0, 60.0, 120.0,
+];
+
+/// Outcome label of successful evaluations
+const NO_OUTCOME: &str = "none";
+
+/// Latency distribution with fixed buckets
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct HistogramSnapshot {
+    /// `(upper bound in seconds, cumulative count)`, ascending
+    pub buckets: Vec<(f64, u64)>,
+    pub sum: f64,
+    pub count: u64,
+}
+
+impl HistogramSnapshot {
+    fn new() -> Self {
+        Self {
+            buckets: LATENCY_BUCKETS.iter().map(|&le| (le, 0)).collect(),
+            sum: 0.0,
+            count: 0,
+        }
+    }
+
+    fn observe(&mut self, value: Duration) {
+        let secs = value.as_secs_f64();
+        for (le, count) in &mut self.buckets {
+            if secs <= *le {
+                *count += 1;
+            }
+        }
+        self.sum += secs;
+        self.count += 1;
+    }
+
+    /// Mean observation in seconds (None when empty)
+    pub fn mean(&self) -> Option<f64> {
+        (self.count > 0).then(|| self.sum / self.count as f64)
+    }
+}
+
+/// Evaluations with the same model, status and outcome
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RequestCount {
+    pub model: String,
+    /// "success", "error" or "refusal"
+    pub status: String,
+    /// Outcome code (e.g. `INPUT_VALIDATION_FAILED`), "none" on success
+    pub outcome: String,
+    pub count: u64,
+}
+
+/// Guardrail blocks attributed to one rule
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct GuardrailBlockCount {
+    /// "input" or "output"
+    pub direction: String,
+    /// Namespaced rule ID (e.g. `regex.MAX_LENGTH`)
+    pub rule: String,
+    pub count: u64,
+}
+
+/// Point-in-time copy of all metrics
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct MetricsSnapshot {
+    pub requests: Vec<RequestCount>,
+    pub guardrail_blocks: Vec<GuardrailBlockCount>,
+    /// Estimated prompt tokens per model
+    pub tokens_estimated: BTreeMap<String, u64>,
+    /// End-to-end evaluation latency per model
+    pub latency: BTreeMap<String, HistogramSnapshot>,
+    /// Latency per pipeline stage
+    pub stage_latency: BTreeMap<String, HistogramSnapshot>,
+}
+
+impl MetricsSnapshot {
+    /// Evaluations recorded
+    pub fn total_requests(&self) -> u64 {
+        self.requests.iter().map(|r| r.count).sum()
+    }
+
+    /// Prometheus text exposition format (version 0.0.4)
+    pub fn to_prometheus(&self) -> String {
+        let mut out = String::new();
+
+        family(
+            &mut out,
+            "fortified_llm_requests_total",
+            "counter",
+            "Evaluations by model, status and outcome",
+        );
+        for r in &self.requests {
+            sample(
+                &mut out,
+                "fortified_llm_requests_total",
+                &[
+                    ("model", &r.model),
+                    ("status", &r.status),
+                    ("outcome", &r.outcome),
+                ],
+                r.count as f64,
+            );
+        }
+
+        family(
+            &mut out,
+            "fortified_llm_guardrail_blocks_total",
+            "counter",
+            "Guardrail violations that blocked an evaluation, by rule",
+        );
+        for b in &self.guardrail_blocks {
+            sample(
+                &mut out,
+                "fortified_llm_guardrail_blocks_total",
+                &[("direction", &b.direction), ("rule", &b.rule)],
+                b.count as f64,
+            );
+        }
+
+        family(
+            &mut out,
+            "fortified_llm_tokens_estimated_total",
+            "counter",
+            "Estimated prompt tokens by model",
+        );
+        for (model, tokens) in &self.tokens_estimated {
+            sample(
+                &mut out,
+                "fortified_llm_tokens_estimated_total",
+                &[("model", model)],
+                *tokens as f64,
+            );
+        }
+
+        histogram(
+            &mut out,
+            "fortified_llm_request_duration_seconds",
+            "End-to-end evaluation latency by model",
+            "model",
+            &self.latency,
+        );
+        histogram(
+            &mut out,
+            "fortified_llm_stage_duration_seconds",
+            "Pipeline stage latency",
+            "stage",
+            &self.stage_latency,
+        );
+        out
+    }
+}
+
+fn family(out: &mut String, name: &str, kind: &str, help: &str) {
+    let _ = writeln!(out, "# HELP {name} {help}");
+    let _ = writeln!(out, "# TYPE {name} {kind}");
+}
+
+fn sample(out: &mut String, name: &str, labels: &[(&str, &str)], value: f64) {
+    let labels: Vec<String> = labels
+        .iter()
+        .map(|(k, v)| format!("{k}=\"{}\"", escape_label(v)))
+        .collect();
+    let _ = writeln!(out, "{name}{{{}}} {value}", labels.join(","));
+}
+
+fn histogram(
+    out: &mut String,
+    name: &str,
+    help: &str,
+    label: &str,
+    series: &BTreeMap<String, HistogramSnapshot>,
+) {
+    family(out, name, "histogram", help);
+    for (key, h) in series {
+        for (le, count) in &h.buckets {
+            sample(
+                out,
+                &format!("{name}_bucket"),
+                &[(label, key), ("le", &le.to_string())],
+                *count as f64,
+            );
+        }
+        sample(
+            out,
+            &format!("{name}_bucket"),
+            &[(label, key), ("le", "+Inf")],
+            h.count as f64,
+        );
+        sample(out, &format!("{name}_sum"), &[(label, key)], h.sum);
+        sample(
+            out,
+            &format!("{name}_count"),
+            &[(label, key)],
+            h.count as f64,
+        );
+    }
+}
+
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+#[derive(Default)]
+struct MetricsState {
+    requests: BTreeMap<(String, String, String), u64>,
+    guardrail_blocks: BTreeMap<(String, String), u64>,
+    tokens_estimated: BTreeMap<String, u64>,
+    latency: BTreeMap<String, HistogramSnapshot>,
+    stage_latency: BTreeMap<String, HistogramSnapshot>,
+}
+
+/// Process-wide evaluation metrics
+///
+/// Cheap to share: wrap in an `Arc` and attach the same registry to every
+/// config whose evaluations should be counted together.
+#[derive(Default)]
+pub struct MetricsRegistry {
+    state: Mutex<MetricsState>,
+}
+
+impl std::fmt::Debug for MetricsRegistry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MetricsRegistry").finish_non_exhaustive()
+    }
+}
+
+impl MetricsRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a finished evaluation
+    pub fn record_evaluation(
+        &self,
+        model: &str,
+        result: &Result<CliOutput, CliError>,
+        elapsed: Duration,
+    ) {
+        let (status, outcome) = match result {
+            Ok(output) => (
+                output.status.clone(),
+                output
+                    .error
+                    .as_ref()
+                    .map_or(NO_OUTCOME.to_string(), |e| e.code.to_string()),
+            ),
+            Err(e) => ("error".to_string(), e.code().to_string()),
+        };
+
+        let mut state = self.lock();
+        *state
+            .requests
+            .entry((model.to_string(), status, outcome))
+            .or_default() += 1;
+        state
+            .latency
+            .entry(model.to_string())
+            .or_insert_with(HistogramSnapshot::new)
+            .observe(elapsed);
+        if let Ok(output) = result {
+            *state.tokens_estimated.entry(model.to_string()).or_default() +=
+                output.metadata.tokens_estimated as u64;
+            for stage in &output.metadata.stage_latency {
+                state
+                    .stage_latency
+                    .entry(stage.stage.clone())
+                    .or_insert_with(HistogramSnapshot::new)
+                    .observe(Duration::from_millis(stage.latency_ms));
+            }
+        }
+    }
+
+    /// Record the violations of a blocking guardrail result
+    pub fn record_guardrail_block(&self, direction: &str, violations: &[Violation]) {
+        let mut state = self.lock();
+        for violation in violations {
+            *state
+                .guardrail_blocks
+                .entry((direction.to_string(), violation.id()))
+                .or_default() += 1;
+        }
+    }
+
+    pub fn snapshot(&self) -> MetricsSnapshot {
+        let state = self.lock();
+        MetricsSnapshot {
+            requests: state
+                .requests
+                .iter()
+                .map(|((model, status, outcome), count)| RequestCount {
+                    model: model.clone(),
+                    status: status.clone(),
+                    outcome: outcome.clone(),
+                    count: *count,
+                })
+                .collect(),
+            guardrail_blocks: state
+                .guardrail_blocks
+                .iter()
+                .map(|((direction, rule), count)| GuardrailBlockCount {
+                    direction: direction.clone(),
+                    rule: rule.clone(),
+                    count: *count,
+                })
+                .collect(),
+            tokens_estimated: state.tokens_estimated.clone(),
+            latency: state.latency.clone(),
+            stage_latency: state.stage_latency.clone(),
+        }
+    }
+
+    /// Prometheus text exposition of the current values
+    pub fn render_prometheus(&self) -> String {
+        self.snapshot().to_prometheus()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, MetricsState> {
+        self.state.lock().expect("metrics lock poisoned")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_histogram_buckets_are_cumulative() {
+        let mut h = HistogramSnapshot::new();
+        h.observe(Duration::from_millis(30));
+        h.observe(Duration::from_millis(700));
+        h.observe(Duration::from_secs(500));
+
+        let bucket = |le: f64| h.buckets.iter().find(|(b, _)| *b == le).unwrap().1;
+        assert_eq!(bucket(0.01), 0);
+        assert_eq!(bucket(0.05), 1);
+        assert_eq!(bucket(1.0), 2);
+        assert_eq!(bucket(120.0), 2);
+        assert_eq!(h.count, 3);
+        assert!((h.sum - 500.73).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_prometheus_rendering_escapes_labels() {
+        let registry = MetricsRegistry::new();
+        registry.record_evaluation(
+            "model \"x\"",
+            &Err(CliError::InvalidResponse("bad".to_string())),
+            Duration::from_millis(200),
+        );
+
+        let text = registry.render_prometheus();
+        assert!(text.contains("# TYPE fortified_llm_requests_total counter"));
+        assert!(text.contains(
+            r#"fortified_llm_requests_total{model="model \"x\"",status="error",outcome="INVALID_RESPONSE"} 1"#
+        ));
+        assert!(text.contains(
+            r#"fortified_llm_request_duration_seconds_bucket{model="model \"x\"",le="0.25"} 1"#
+        ));
+        assert!(text.contains(
+            r#"fortified_llm_request_duration_seconds_bucket{model="model \"x\"",le="+Inf"} 1"#
+        ));
+        assert!(
+            text.contains(r#"fortified_llm_request_duration_seconds_count{model="model \"x\""} 1"#)
+        );
+    }
+}
@@ -0,0 +1,286 @@
+//! Discovery of the models an endpoint serves
+//!
+//! [`discover_models`] asks the provider for its model list and
+//! cross-references each entry with the static
+//! [`model_registry`](crate::model_registry):
+//!
+//! - Ollama: `GET /api/tags`
+//! - OpenAI-compatible: `GET <base>/models`
+//! - Anthropic: `GET /v1/models`
+//!
+//! Context windows come from the registry, else from the list itself (some
+//! OpenAI-compatible servers report `context_length` or `max_model_len`),
+//! else, when probing is requested, from a [`ContextProber`] call per model.
+
+use crate::{
+    cassette,
+    context_probe::{openai_base, openai_context_length, origin, ContextProber},
+    error::CliError,
+    model_registry::lookup_model,
+    provider::ProviderType,
+    providers::{detect_provider_type, ANTHROPIC_VERSION},
+};
+use reqwest::Client;
+use serde::Serialize;
+use serde_json::Value;
+use std::time::Duration;
+
+/// A model listed by the endpoint
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AvailableModel {
+    /// Value to pass as `--model`
+    pub id: String,
+    /// Download size reported by Ollama
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub size_bytes: Option<u64>,
+    /// Registry entry the id matches (exact, alias or prefix match; Ollama
+    /// tags like `llama3.1:8b` also match their registry spelling)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub registry_name: Option<&'static str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub context_window: Option<usize>,
+    /// `registry`, `provider` or `probe`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub context_source: Option<&'static str>,
+    /// Recommended input budget from the registry
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_input_tokens: Option<usize>,
+}
+
+/// Models served by an endpoint
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ModelListing {
+    pub api_url: String,
+    pub provider: String,
+    pub models: Vec<AvailableModel>,
+}
+
+impl ModelListing {
+    /// Plain-text table, one model per line
+    pub fn to_table(&self) -> String {
+        let width = self
+            .models
+            .iter()
+            .map(|m| m.id.len())
+            .max()
+            .unwrap_or(0)
+            .max("MODEL".len());
+        let mut table = format!(
+            "{:<width$}  {:>9}  {:>9}  {:<8}  {:>9}  REGISTRY\n",
+            "MODEL", "CONTEXT", "MAX_INPUT", "SOURCE", "SIZE"
+        );
+        let number = |value: Option<usize>| value.map_or("-".to_string(), |n| n.to_string());
+        for model in &self.models {
+            table.push_str(&format!(
+                "{:<width$}  {:>9}  {:>9}  {:<8}  {:>9}  {}\n",
+                model.id,
+                number(model.context_window),
+                number(model.max_input_tokens),
+                model.context_source.unwrap_or("-"),
+                model.size_bytes.map_or("-".to_string(), format_size),
+                model.registry_name.unwrap_or("-"),
+            ));
+        }
+        table
+    }
+}
+
+/// List the models served at `api_url`
+///
+/// With `prober`, models missing from the registry and not reported with a
+/// context window are probed one by one (Ollama and OpenAI-compatible only).
+///
+/// # Errors
+///
+/// `AuthenticationFailed` on HTTP 401/403, `InvalidResponse` on other HTTP
+/// errors or an unexpected body, and `InvalidArguments` for Bedrock and mock
+/// endpoints, which have no model list.
+pub async fn discover_models(
+    api_url: &str,
+    provider: Option<ProviderType>,
+    api_key: Option<&str>,
+    timeout_secs: u64,
+    prober: Option<&ContextProber>,
+) -> Result<ModelListing, CliError> {
+    let provider = provider.unwrap_or_else(|| detect_provider_type(api_url));
+    let client = Client::new();
+    let timeout = Duration::from_secs(timeout_secs);
+    let (request, entries) = match provider {
+        ProviderType::Ollama => {
+            let url = origin(api_url)?.join("api/tags").map_err(invalid_url)?;
+            (client.get(url), "models")
+        }
+        ProviderType::OpenAI => {
+            let mut url = openai_base(api_url)?;
+            url.path_segments_mut()
+                .map_err(|_| invalid_url(api_url))?
+                .pop_if_empty()
+                .push("models");
+            (client.get(url), "data")
+        }
+        ProviderType::Anthropic => {
+            let url = origin(api_url)?
+                .join("v1/models?limit=1000")
+                .map_err(invalid_url)?;
+            let request = client
+                .get(url)
+                .header("anthropic-version", ANTHROPIC_VERSION);
+            (request, "data")
+        }
+        ProviderType::Bedrock | ProviderType::Mock => {
+            return Err(CliError::InvalidArguments(format!(
+                "Listing models is not supported for {provider:?} endpoints"
+            )))
+        }
+    };
+
+    let mut request = request.timeout(timeout);
+    if let Some(key) = api_key {
+        request = match provider {
+            ProviderType::Anthropic => request.header("x-api-key", key),
+            _ => request.header("Authorization", format!("Bearer {key}")),
+        };
+    }
+    let response = cassette::send(request, api_key).await?;
+    if matches!(response.status.as_u16(), 401 | 403) {
+        return Err(CliError::AuthenticationFailed(format!(
+            "HTTP {} listing models at {api_url}",
+            response.status.as_u16()
+        )));
+    }
+    if !response.status.is_success() {
+        return Err(CliError::InvalidResponse(format!(
+            "HTTP {} listing models at {api_url}: {}",
+            response.status.as_u16(),
+            response.body
+        )));
+    }
+    let body: Value = serde_json::from_str(&response.body)
+        .map_err(|e| CliError::InvalidResponse(format!("Failed to parse model list: {e}")))?;
+    let entries = body
+        .get(entries)
+        .and_then(Value::as_array)
+        .ok_or_else(|| CliError::InvalidResponse(format!("Model list has no '{entries}' array")))?;
+
+    let mut models: Vec<AvailableModel> = entries.iter().filter_map(available_model).collect();
+    models.sort_by(|a, b| a.id.cmp(&b.id));
+    if let Some(prober) = prober {
+        for model in models.iter_mut().filter(|m| m.context_window.is_none()) {
+            if let Some(limit) = prober
+                .probe(api_url, &model.id, Some(provider), api_key, timeout_secs)
+                .await
+            {
+                model.context_window = Some(limit);
+                model.context_source = Some("probe");
+            }
+        }
+    }
+
+    Ok(ModelListing {
+        api_url: api_url.to_string(),
+        provider: format!("{provider:?}"),
+        models,
+    })
+}
+
+/// Model list entry (Ollama `name`, else `id`) with its registry information
+fn available_model(entry: &Value) -> Option<AvailableModel> {
+    let id = entry
+        .get("name")
+        .or_else(|| entry.get("id"))
+        .and_then(Value::as_str)?
+        .to_string();
+    let info = lookup_model(&id).or_else(|| lookup_model(&ollama_to_registry_name(&id)?));
+    let (context_window, context_source) = match (info, openai_context_length(entry)) {
+        (Some(info), _) => (Some(info.context_window), Some("registry")),
+        (None, Some(limit)) => (Some(limit), Some("provider")),
+        (None, None) => (None, None),
+    };
+    Some(AvailableModel {
+        size_bytes: entry.get("size").and_then(Value::as_u64),
+        registry_name: info.map(|info| info.name),
+        context_window,
+        context_source,
+        max_input_tokens: info.and_then(|info| info.max_input_tokens),
+        id,
+    })
+}
+
+/// Registry spelling of an Ollama tag (`llama3.1:8b` -> `llama-3.1-8b`)
+fn ollama_to_registry_name(id: &str) -> Option<String> {
+    let (family, tag) = id.split_once(':')?;
+    let family = match family.find(|c: char| c.is_ascii_digit()) {
+        Some(index) if index > 0 && !family[..index].ends_with('-') => {
+            format!("{}-{}", &family[..index], &family[index..])
+        }
+        _ => family.to_string(),
+    };
+    Some(format!("{family}-{tag}"))
+}
+
+fn invalid_url(e: impl std::fmt::Display) -> CliError {
+    CliError::InvalidArguments(format!("Invalid API URL for model listing: {e}"))
+}
+
+/// Human-readable byte count (e.g. "4.7GB")
+fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["B", "KB", "MB", "GB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1000.0 && unit < UNITS.len() - 1 {
+        size /= 1000.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{bytes}B")
+    } else {
+        format!("{size:.1}{}", UNITS[unit])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_available_model_cross_references_registry() {
+        let model = available_model(&json!({"id": "gpt-4o", "object": "model"})).unwrap();
+        assert_eq!(model.registry_name, Some("gpt-4o"));
+        assert_eq!(model.context_window, Some(128000));
+        assert_eq!(model.context_source, Some("registry"));
+
+        let model =
+            available_model(&json!({"id": "acme/custom-13b", "max_model_len": 4096})).unwrap();
+        assert_eq!(model.registry_name, None);
+        assert_eq!(model.context_window, Some(4096));
+        assert_eq!(model.context_source, Some("provider"));
+
+        assert!(available_model(&json!({"object": "model"})).is_none());
+    }
+
+    #[test]
+    fn test_ollama_to_registry_name() {
+        assert_eq!(
+            ollama_to_registry_name("llama3.1:8b").as_deref(),
+            Some("llama-3.1-8b")
+        );
+        assert_eq!(
+            ollama_to_registry_name("mistral:7b").as_deref(),
+            Some("mistral-7b")
+        );
+        assert_eq!(ollama_to_registry_name("gpt-4o"), None);
+        assert_eq!(
+            available_model(&json!({"name": "qwen2.5:7b"}))
+                .unwrap()
+                .registry_name,
+            Some("qwen-2.5-7b")
+        );
+    }
+
+    #[test]
+    fn test_format_size() {
+        assert_eq!(format_size(512), "512B");
+        assert_eq!(format_size(4_661_224_676), "4.7GB");
+    }
+}
@@ -36,6 +36,16 @@ impl TokenizerFamily {
     }
 }
 
+/// Where a model's exact tokenizer comes from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TokenizerSource {
+    /// tiktoken BPE encoding (e.g. `cl100k_base`), feature `tiktoken`
+    Tiktoken(&'static str),
+    /// Hugging Face model repository whose `tokenizer.json` is read from the
+    /// local hub cache, feature `hf-tokenizers`
+    HuggingFace(&'static str),
+}
+
 /// Model information for token estimation and validation
 #[derive(Debug, Clone)]
 pub struct ModelInfo {
@@ -53,6 +63,10 @@ pub struct ModelInfo {
 
     /// Provider/organization
     pub provider: &'static str,
+
+    /// Exact tokenizer, used when its backend feature is enabled
+    /// (see [`crate::tokenizer`])
+    pub tokenizer_source: Option<TokenizerSource>,
 }
 
 impl ModelInfo {
@@ -75,6 +89,7 @@ static MODEL_REGISTRY: Lazy<HashMap<&'static str, ModelInfo>> = Lazy::new(|| {
             context_window: 8192,
             max_input_tokens: Some(6144), // Leave 2048 for output
             provider: "OpenAI",
+            tokenizer_source: Some(TokenizerSource::Tiktoken("cl100k_base")),
         },
     );
 
@@ -86,6 +101,7 @@ static MODEL_REGISTRY: Lazy<HashMap<&'static str, ModelInfo>> = Lazy::new(|| {
             context_window: 128000,
             max_input_tokens: Some(100000),
             provider: "OpenAI",
+            tokenizer_source: Some(TokenizerSource::Tiktoken("cl100k_base")),
         },
     );
 
@@ -97,6 +113,7 @@ static MODEL_REGISTRY: Lazy<HashMap<&'static str, ModelInfo>> = Lazy::new(|| {
             context_window: 128000,
             max_input_tokens: Some(100000),
             provider: "OpenAI",
+            tokenizer_source: Some(TokenizerSource::Tiktoken("o200k_base")),
         },
     );
 
@@ -108,6 +125,7 @@ static MODEL_REGISTRY: Lazy<HashMap<&'static str, ModelInfo>> = Lazy::new(|| {
             context_window: 16385,
             max_input_tokens: Some(12288),
             provider: "OpenAI",
+            tokenizer_source: Some(TokenizerSource::Tiktoken("cl100k_base")),
         },
     );
 
@@ -120,6 +138,9 @@ static MODEL_REGISTRY: Lazy<HashMap<&'static str, ModelInfo>> = Lazy::new(|| {
             context_window: 128000,
             max_input_tokens: Some(100000),
             provider: "Meta",
+            tokenizer_source: Some(TokenizerSource::HuggingFace(
+                "meta-llama/Llama-3.1-8B-Instruct",
+            )),
         },
     );
 
@@ -131,6 +152,9 @@ static MODEL_REGISTRY: Lazy<HashMap<&'static str, ModelInfo>> = Lazy::new(|| {
             context_window: 128000,
             max_input_tokens: Some(100000),
             provider: "Meta",
+            tokenizer_source: Some(TokenizerSource::HuggingFace(
+                "meta-llama/Llama-3.1-70B-Instruct",
+            )),
         },
     );
 
@@ -142,6 +166,9 @@ static MODEL_REGISTRY: Lazy<HashMap<&'static str, ModelInfo>> = Lazy::new(|| {
             context_window: 128000,
             max_input_tokens: Some(100000),
             provider: "Meta",
+            tokenizer_source: Some(TokenizerSource::HuggingFace(
+                "meta-llama/Llama-3.1-405B-Instruct",
+            )),
         },
     );
 
@@ -153,6 +180,9 @@ static MODEL_REGISTRY: Lazy<HashMap<&'static str, ModelInfo>> = Lazy::new(|| {
             context_window: 131072,
             max_input_tokens: Some(100000),
             provider: "Meta",
+            tokenizer_source: Some(TokenizerSource::HuggingFace(
+                "meta-llama/Llama-3.2-1B-Instruct",
+            )),
         },
     );
 
@@ -164,6 +194,9 @@ static MODEL_REGISTRY: Lazy<HashMap<&'static str, ModelInfo>> = Lazy::new(|| {
             context_window: 131072,
             max_input_tokens: Some(100000),
             provider: "Meta",
+            tokenizer_source: Some(TokenizerSource::HuggingFace(
+                "meta-llama/Llama-3.2-3B-Instruct",
+            )),
         },
     );
 
@@ -176,6 +209,7 @@ static MODEL_REGISTRY: Lazy<HashMap<&'static str, ModelInfo>> = Lazy::new(|| {
             context_window: 131072,
             max_input_tokens: Some(100000),
             provider: "Alibaba",
+            tokenizer_source: Some(TokenizerSource::HuggingFace("Qwen/Qwen2.5-72B-Instruct")),
         },
     );
 
@@ -187,6 +221,7 @@ static MODEL_REGISTRY: Lazy<HashMap<&'static str, ModelInfo>> = Lazy::new(|| {
             context_window: 131072,
             max_input_tokens: Some(100000),
             provider: "Alibaba",
+            tokenizer_source: Some(TokenizerSource::HuggingFace("Qwen/Qwen2.5-7B-Instruct")),
         },
     );
 
@@ -199,6 +234,9 @@ static MODEL_REGISTRY: Lazy<HashMap<&'static str, ModelInfo>> = Lazy::new(|| {
             context_window: 32768,
             max_input_tokens: Some(24576),
             provider: "Mistral AI",
+            tokenizer_source: Some(TokenizerSource::HuggingFace(
+                "mistralai/Mistral-7B-Instruct-v0.3",
+            )),
         },
     );
 
@@ -210,6 +248,9 @@ static MODEL_REGISTRY: Lazy<HashMap<&'static str, ModelInfo>> = Lazy::new(|| {
             context_window: 32768,
             max_input_tokens: Some(24576),
             provider: "Mistral AI",
+            tokenizer_source: Some(TokenizerSource::HuggingFace(
+                "mistralai/Mixtral-8x7B-Instruct-v0.1",
+            )),
         },
     );
 
@@ -61,18 +61,85 @@ impl fmt::Display for ResponseFormat {
     }
 }
 
+/// Function the model may call instead of answering in text
+///
+/// `parameters` is the JSON Schema of the arguments object. Tool calls are
+/// returned in `CliOutput::tool_calls`; executing them is up to the caller.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ToolDefinition {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// JSON Schema of the arguments (default: an object without properties)
+    #[serde(default = "empty_parameters")]
+    pub parameters: Value,
+}
+
+fn empty_parameters() -> Value {
+    serde_json::json!({"type": "object", "properties": {}})
+}
+
+impl ToolDefinition {
+    pub fn new(name: impl Into<String>, description: impl Into<String>, parameters: Value) -> Self {
+        Self {
+            name: name.into(),
+            description: Some(description.into()),
+            parameters,
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        let valid_name = !self.name.is_empty()
+            && self.name.len() <= 64
+            && self
+                .name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+        if !valid_name {
+            return Err(format!(
+                "tool name '{}' must be 1-64 letters, digits, '_' or '-'",
+                self.name
+            ));
+        }
+        if !self.parameters.is_object() {
+            return Err(format!(
+                "parameters of tool '{}' must be a JSON Schema object",
+                self.name
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Function call requested by the model
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ToolCall {
+    /// Provider-assigned call id (generated when the provider has none)
+    pub id: String,
+    pub name: String,
+    /// Parsed arguments object
+    pub arguments: Value,
+}
+
 // OpenAI format (standard)
 #[derive(Serialize)]
 pub struct OpenAIRequest {
     pub model: String,
-    pub messages: Vec<Message>,
+    pub messages: Vec<OpenAIMessage>,
     pub temperature: f32,
     #[serde(skip_serializing_if = "Option::is_none")]
+    pub top_p: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub max_tokens: Option<u32>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub seed: Option<u64>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub response_format: Option<ResponseFormat>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub tools: Vec<OpenAITool>,
+    /// Request server-sent events instead of a single JSON body
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stream: Option<bool>,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -81,14 +148,277 @@ pub struct Message {
     pub content: String,
 }
 
+/// Chat message whose content is text, or text and image parts for vision models
+#[derive(Serialize)]
+pub struct OpenAIMessage {
+    pub role: String,
+    pub content: OpenAIContent,
+}
+
+#[derive(Serialize)]
+#[serde(untagged)]
+pub enum OpenAIContent {
+    Text(String),
+    Parts(Vec<OpenAIContentPart>),
+}
+
+/// `{"type": "text", ...}` or `{"type": "image_url", ...}` content part
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum OpenAIContentPart {
+    Text { text: String },
+    ImageUrl { image_url: OpenAIImageUrl },
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct OpenAIImageUrl {
+    /// `data:<media type>;base64,<bytes>` URL
+    pub url: String,
+}
+
+/// `{"type": "function", "function": {...}}` entry of `tools`
+#[derive(Serialize)]
+pub struct OpenAITool {
+    #[serde(rename = "type")]
+    pub tool_type: &'static str,
+    pub function: ToolDefinition,
+}
+
+impl OpenAITool {
+    pub fn function(definition: &ToolDefinition) -> Self {
+        Self {
+            tool_type: "function",
+            function: definition.clone(),
+        }
+    }
+}
+
+/// Assistant message of a completion (`content` is null for tool calls)
+#[derive(Deserialize)]
+pub struct ResponseMessage {
+    #[serde(default)]
+    pub content: Option<String>,
+    #[serde(default)]
+    pub tool_calls: Vec<OpenAIToolCall>,
+    /// Generated images (OpenRouter extension, `image_url` parts)
+    #[serde(default)]
+    pub images: Vec<OpenAIContentPart>,
+}
+
+#[derive(Deserialize)]
+pub struct OpenAIToolCall {
+    #[serde(default)]
+    pub id: String,
+    pub function: OpenAIFunctionCall,
+}
+
+#[derive(Deserialize)]
+pub struct OpenAIFunctionCall {
+    pub name: String,
+    /// Arguments object encoded as a JSON string
+    #[serde(default)]
+    pub arguments: String,
+}
+
 #[derive(Deserialize)]
 pub struct OpenAIResponse {
     pub choices: Vec<Choice>,
+    /// Model that served the request (may differ from the requested alias)
+    #[serde(default)]
+    pub model: Option<String>,
+    /// Upstream provider chosen by a gateway (OpenRouter extension)
+    #[serde(default)]
+    pub provider: Option<String>,
+    /// Backend configuration fingerprint
+    #[serde(default)]
+    pub system_fingerprint: Option<String>,
+    #[serde(default)]
+    pub service_tier: Option<String>,
+    #[serde(default)]
+    pub usage: Option<OpenAIUsage>,
+}
+
+#[derive(Deserialize)]
+pub struct OpenAIUsage {
+    #[serde(default)]
+    pub prompt_tokens: u64,
+    #[serde(default)]
+    pub completion_tokens: u64,
 }
 
 #[derive(Deserialize)]
 pub struct Choice {
-    pub message: Message,
+    pub message: ResponseMessage,
+    #[serde(default)]
+    pub finish_reason: Option<String>,
+    /// Finish reason as reported by the upstream provider (OpenRouter extension)
+    #[serde(default)]
+    pub native_finish_reason: Option<String>,
+    /// Per-category content filter results (Azure OpenAI extension)
+    #[serde(default)]
+    pub content_filter_results: serde_json::Value,
+}
+
+/// One `data:` event of an OpenAI streaming (SSE) response
+#[derive(Deserialize)]
+pub struct OpenAIStreamChunk {
+    #[serde(default)]
+    pub choices: Vec<StreamChoice>,
+}
+
+#[derive(Deserialize)]
+pub struct StreamChoice {
+    #[serde(default)]
+    pub delta: Delta,
+    #[serde(default)]
+    pub finish_reason: Option<String>,
+    /// Per-category content filter results (Azure OpenAI extension)
+    #[serde(default)]
+    pub content_filter_results: serde_json::Value,
+}
+
+#[derive(Deserialize, Default)]
+pub struct Delta {
+    #[serde(default)]
+    pub content: Option<String>,
+}
+
+// Anthropic Messages API format
+#[derive(Serialize)]
+pub struct AnthropicRequest {
+    pub model: String,
+    /// Required by the Messages API
+    pub max_tokens: u32,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub system: String,
+    pub messages: Vec<Message>,
+    pub temperature: f32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub top_p: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stream: Option<bool>,
+}
+
+#[derive(Deserialize)]
+pub struct AnthropicResponse {
+    pub content: Vec<AnthropicContentBlock>,
+    #[serde(default)]
+    pub model: Option<String>,
+    #[serde(default)]
+    pub stop_reason: Option<String>,
+    #[serde(default)]
+    pub usage: Option<AnthropicUsage>,
+}
+
+#[derive(Deserialize)]
+pub struct AnthropicUsage {
+    #[serde(default)]
+    pub input_tokens: u64,
+    #[serde(default)]
+    pub output_tokens: u64,
+}
+
+#[derive(Deserialize)]
+pub struct AnthropicContentBlock {
+    #[serde(rename = "type")]
+    pub block_type: String,
+    #[serde(default)]
+    pub text: Option<String>,
+}
+
+/// One `data:` event of an Anthropic streaming (SSE) response
+#[derive(Deserialize)]
+pub struct AnthropicStreamEvent {
+    #[serde(rename = "type")]
+    pub event_type: String,
+    #[serde(default)]
+    pub delta: Option<AnthropicDelta>,
+    #[serde(default)]
+    pub error: Option<AnthropicError>,
+}
+
+#[derive(Deserialize)]
+pub struct AnthropicDelta {
+    #[serde(default)]
+    pub text: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct AnthropicError {
+    #[serde(rename = "type", default)]
+    pub error_type: String,
+    #[serde(default)]
+    pub message: String,
+}
+
+/// Error body: `{"type": "error", "error": {...}}`
+#[derive(Deserialize)]
+pub struct AnthropicErrorBody {
+    pub error: AnthropicError,
+}
+
+// Bedrock Converse API format
+#[derive(Serialize)]
+pub struct BedrockConverseRequest {
+    pub messages: Vec<BedrockMessage>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub system: Vec<BedrockContentBlock>,
+    #[serde(rename = "inferenceConfig")]
+    pub inference_config: BedrockInferenceConfig,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct BedrockMessage {
+    pub role: String,
+    pub content: Vec<BedrockContentBlock>,
+}
+
+/// Content block; non-text blocks (images, tool use) deserialize with no text
+#[derive(Serialize, Deserialize)]
+pub struct BedrockContentBlock {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BedrockInferenceConfig {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_tokens: Option<u32>,
+    pub temperature: f32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub top_p: Option<f32>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BedrockConverseResponse {
+    pub output: BedrockOutput,
+    #[serde(default)]
+    pub stop_reason: Option<String>,
+    #[serde(default)]
+    pub usage: Option<BedrockUsage>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BedrockUsage {
+    #[serde(default)]
+    pub input_tokens: u64,
+    #[serde(default)]
+    pub output_tokens: u64,
+}
+
+#[derive(Deserialize)]
+pub struct BedrockOutput {
+    pub message: Option<BedrockMessage>,
+}
+
+/// Error body: `{"message": "..."}` (type in the `x-amzn-ErrorType` header)
+#[derive(Deserialize)]
+pub struct BedrockErrorBody {
+    #[serde(alias = "Message")]
+    pub message: String,
 }
 
 // /api/generate format (used by local servers)
@@ -97,6 +427,9 @@ pub struct OllamaRequest {
     pub model: String,
     pub system: String,
     pub prompt: String,
+    /// Base64-encoded images for multimodal models
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub images: Vec<String>,
     pub stream: bool,
     pub options: OllamaOptions,
 }
@@ -105,10 +438,94 @@ pub struct OllamaRequest {
 pub struct OllamaOptions {
     pub temperature: f32,
     #[serde(skip_serializing_if = "Option::is_none")]
+    pub top_p: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub seed: Option<u64>,
 }
 
 #[derive(Deserialize)]
 pub struct OllamaResponse {
     pub response: String,
+    #[serde(default)]
+    pub model: Option<String>,
+    /// Prompt tokens evaluated
+    #[serde(default)]
+    pub prompt_eval_count: Option<u64>,
+    /// Tokens generated
+    #[serde(default)]
+    pub eval_count: Option<u64>,
+}
+
+/// /api/chat format, used when tools are offered
+#[derive(Serialize)]
+pub struct OllamaChatRequest {
+    pub model: String,
+    pub messages: Vec<Message>,
+    pub tools: Vec<OpenAITool>,
+    pub stream: bool,
+    pub options: OllamaOptions,
+}
+
+/// /api/tags listing of local models
+#[derive(Deserialize)]
+pub struct OllamaTagsResponse {
+    #[serde(default)]
+    pub models: Vec<OllamaModelTag>,
+}
+
+#[derive(Deserialize)]
+pub struct OllamaModelTag {
+    pub name: String,
+    #[serde(default)]
+    pub digest: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct OllamaChatResponse {
+    pub message: OllamaChatMessage,
+    #[serde(default)]
+    pub model: Option<String>,
+    #[serde(default)]
+    pub prompt_eval_count: Option<u64>,
+    #[serde(default)]
+    pub eval_count: Option<u64>,
+}
+
+#[derive(Deserialize)]
+pub struct OllamaChatMessage {
+    #[serde(default)]
+    pub content: String,
+    #[serde(default)]
+    pub tool_calls: Vec<OllamaToolCall>,
+}
+
+/// Ollama tool calls have no id and carry the arguments as an object
+#[derive(Deserialize)]
+pub struct OllamaToolCall {
+    pub function: OllamaFunctionCall,
+}
+
+#[derive(Deserialize)]
+pub struct OllamaFunctionCall {
+    pub name: String,
+    #[serde(default)]
+    pub arguments: Value,
+}
+
+/// One line of an Ollama NDJSON streaming body
+#[derive(Deserialize)]
+pub struct OllamaStreamChunk {
+    #[serde(default)]
+    pub response: String,
+    #[serde(default)]
+    pub model: Option<String>,
+    #[serde(default)]
+    pub done: bool,
+    /// Set on the final (`done`) chunk
+    #[serde(default)]
+    pub prompt_eval_count: Option<u64>,
+    #[serde(default)]
+    pub eval_count: Option<u64>,
+    #[serde(default)]
+    pub error: Option<String>,
 }
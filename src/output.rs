@@ -1,12 +1,71 @@
-use crate::models::ResponseFormat;
-use serde::Serialize;
+use crate::{
+    chunking::ChunkingRecord,
+    config_builder::ConfigWarning,
+    error::CliError,
+    guardrails::{
+        EscalationRecord, GuardTokenUsage, NamedProviderResult, Redaction, ViolationPreview,
+    },
+    image::ImageMode,
+    metadata_fields::MetadataFields,
+    models::{ResponseFormat, ToolCall},
+    prompt_library::PromptPin,
+    provider::{ModelEcho, UpstreamInfo},
+};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::Value;
+use std::{
+    collections::BTreeMap,
+    fmt::{self, Write},
+};
+
+/// Version of the [`CliOutput`] JSON layout
+///
+/// Bumped when fields are removed, renamed or change meaning; new optional
+/// fields don't change it.
+pub const OUTPUT_SCHEMA_VERSION: u32 = 1;
+
+/// How a [`CliOutput`] is rendered
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputFormat {
+    /// Pretty-printed JSON (the stable, machine-readable layout)
+    #[default]
+    Json,
+    /// The JSON layout as a YAML document
+    Yaml,
+    /// Human-readable report: response, error, metadata table and guardrail
+    /// summary
+    Markdown,
+    /// Only the response text (structured responses as compact JSON); empty
+    /// for errors
+    Raw,
+}
+
+impl fmt::Display for OutputFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Json => "json",
+            Self::Yaml => "yaml",
+            Self::Markdown => "markdown",
+            Self::Raw => "raw",
+        })
+    }
+}
 
 #[derive(Serialize)]
 pub struct CliOutput {
-    pub status: String, // "success" or "error"
+    pub status: String, // "success", "error" or "refusal"
     pub response: Option<serde_json::Value>,
+    #[serde(serialize_with = "crate::metadata_fields::serialize_filtered")]
     pub metadata: Metadata,
     pub error: Option<ErrorInfo>,
+    /// Function calls requested by the model (checked by output guardrails)
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub tool_calls: Vec<ToolCall>,
+    /// Images generated by the model, as `data:` URLs (checked by output
+    /// guardrails that inspect images)
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub images: Vec<String>,
 }
 
 #[derive(Serialize)]
@@ -14,6 +73,16 @@ pub struct Metadata {
     // Execution results
     pub model: String,
     pub tokens_estimated: usize,
+    /// Prompt tokens reported by the provider, summed over the evaluation's
+    /// LLM calls (None = not reported, e.g. cache hits and streaming)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tokens_prompt_actual: Option<u64>,
+    /// Completion tokens reported by the provider
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tokens_completion_actual: Option<u64>,
+    /// tokens_estimated / context_limit (when both are known)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub context_usage_ratio: Option<f64>,
     pub latency_ms: u64,
     pub timestamp: String, // ISO 8601
 
@@ -23,6 +92,8 @@ pub struct Metadata {
     pub provider: Option<String>,
     pub temperature: f32,
     #[serde(skip_serializing_if = "Option::is_none")]
+    pub top_p: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub max_tokens: Option<u32>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub seed: Option<u64>,
@@ -44,20 +115,224 @@ pub struct Metadata {
     pub user_prompt_file: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub pdf_input: Option<String>,
+    /// URL fetched as the user prompt
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url_input: Option<String>,
+    /// URL fetched as the system prompt
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub system_prompt_url: Option<String>,
+    /// Prompt library versions and hashes of referenced prompts
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub prompt_pins: Vec<PromptPin>,
+    /// Image file sent with the prompt
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub image_input: Option<String>,
+    /// How the image reached the model ("ocr" text or "vision" content)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub image_mode: Option<ImageMode>,
+    /// Strategy and per-chunk details, when the document was chunked
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub chunking: Option<ChunkingRecord>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user_prompt_prefix: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user_prompt_suffix: Option<String>,
+    /// Spotlighting strategy applied to untrusted content
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub spotlighting: Option<String>,
+
+    // Labels
+    /// Caller-supplied evaluation tags (e.g. "experiment=A")
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
 
     // Guardrails
     #[serde(skip_serializing_if = "Option::is_none")]
     pub input_guardrails_enabled: Option<bool>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub output_guardrails_enabled: Option<bool>,
+    /// Rule IDs of the guardrail violations that blocked the evaluation
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub violation_rules: Vec<String>,
+    /// Masked excerpts around the blocking violations that have one
+    /// (regex guardrails with `preview_chars`)
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub violation_previews: Vec<ViolationPreview>,
+    /// Input matches replaced by placeholders before the prompt was sent
+    /// (rule, placeholder and count; the matched text is not kept)
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub redactions: Vec<Redaction>,
+    /// Verdict, rules, latency and raw reply of each composite guardrail member
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub guardrail_results: Vec<NamedProviderResult>,
+    /// How each composite guardrail reached its verdict, keyed by direction
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub guardrail_explanations: BTreeMap<String, String>,
+    /// Fast and escalated input verdicts, when the input was escalated
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub escalation: Option<EscalationRecord>,
+    /// Estimated tokens of model-backed guardrail calls, per provider and
+    /// direction
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub guard_tokens: Vec<GuardTokenUsage>,
+    /// Quality score reported by the output guardrails (0-10)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub quality_score: Option<f32>,
+
+    // Transport
+    /// NDJSON chunks aggregated from a streaming body (Ollama fallback)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub response_chunks_aggregated: Option<usize>,
+    /// Serving attribution reported by an aggregating gateway (e.g. OpenRouter)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub upstream: Option<UpstreamInfo>,
+    /// Serving parameters confirmed by the provider (model version,
+    /// `system_fingerprint`, Ollama model digest)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model_echo: Option<ModelEcho>,
+    /// Whether the response came from the response cache (None = caching
+    /// disabled or bypassed for a stochastic request)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cache_hit: Option<bool>,
+
+    // Timing
+    /// Per-stage latency breakdown, in execution order
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub stage_latency: Vec<StageLatency>,
+
+    // Configuration
+    /// Merge conflicts and ignored config values
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub config_warnings: Vec<ConfigWarning>,
+
+    // Warnings
+    /// Non-blocking issues found during evaluation
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<EvaluationWarning>,
+
+    /// Fields left out of serialized outputs (`[metadata_fields]`)
+    #[serde(skip)]
+    pub field_filter: Option<MetadataFields>,
+}
+
+/// Non-blocking issue raised by a pipeline stage
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct EvaluationWarning {
+    /// Machine-readable code (e.g. "CONTEXT_HEADROOM_LOW")
+    pub code: String,
+    pub message: String,
+}
+
+/// Wall time spent in one pipeline stage
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct StageLatency {
+    pub stage: String,
+    pub latency_ms: u64,
 }
 
 #[derive(Serialize)]
 pub struct ErrorInfo {
-    pub code: String,
+    pub code: EvaluationOutcome,
     pub message: String,
 }
 
+/// Why an evaluation did not succeed
+///
+/// Serialized as the SCREAMING_SNAKE_CASE code used in JSON output
+/// (e.g. `"INPUT_VALIDATION_FAILED"`), so the wire format is unchanged.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum EvaluationOutcome {
+    /// Input guardrails blocked the user prompt
+    InputValidationFailed,
+    /// Output guardrails blocked the LLM response
+    OutputValidationFailed,
+    /// Estimated tokens exceed the configured context limit
+    ContextLimitExceeded,
+    /// PDF input exceeds the maximum allowed size
+    FileTooLarge,
+    /// The model declined the request (refusal detection)
+    Refusal,
+    /// The provider's own content filter blocked the prompt or the response
+    ProviderContentFiltered,
+    /// The evaluation ran past its `[deadline]`
+    DeadlineExceeded,
+    /// Any other code: `CliError` codes (e.g. `"HTTP_ERROR"`) or codes set by
+    /// custom pipeline stages
+    Other(String),
+}
+
+impl EvaluationOutcome {
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::InputValidationFailed => "INPUT_VALIDATION_FAILED",
+            Self::OutputValidationFailed => "OUTPUT_VALIDATION_FAILED",
+            Self::ContextLimitExceeded => "CONTEXT_LIMIT_EXCEEDED",
+            Self::FileTooLarge => "FILE_TOO_LARGE",
+            Self::Refusal => "REFUSAL",
+            Self::ProviderContentFiltered => "PROVIDER_CONTENT_FILTERED",
+            Self::DeadlineExceeded => "DEADLINE_EXCEEDED",
+            Self::Other(code) => code,
+        }
+    }
+}
+
+impl From<&str> for EvaluationOutcome {
+    fn from(code: &str) -> Self {
+        match code {
+            "INPUT_VALIDATION_FAILED" => Self::InputValidationFailed,
+            "OUTPUT_VALIDATION_FAILED" => Self::OutputValidationFailed,
+            "CONTEXT_LIMIT_EXCEEDED" => Self::ContextLimitExceeded,
+            "FILE_TOO_LARGE" => Self::FileTooLarge,
+            "REFUSAL" => Self::Refusal,
+            "PROVIDER_CONTENT_FILTERED" => Self::ProviderContentFiltered,
+            "DEADLINE_EXCEEDED" => Self::DeadlineExceeded,
+            other => Self::Other(other.to_string()),
+        }
+    }
+}
+
+impl From<String> for EvaluationOutcome {
+    fn from(code: String) -> Self {
+        Self::from(code.as_str())
+    }
+}
+
+impl From<&CliError> for EvaluationOutcome {
+    fn from(error: &CliError) -> Self {
+        Self::from(error.code())
+    }
+}
+
+impl fmt::Display for EvaluationOutcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl PartialEq<str> for EvaluationOutcome {
+    fn eq(&self, other: &str) -> bool {
+        self.as_str() == other
+    }
+}
+
+impl PartialEq<&str> for EvaluationOutcome {
+    fn eq(&self, other: &&str) -> bool {
+        self.as_str() == *other
+    }
+}
+
+impl Serialize for EvaluationOutcome {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for EvaluationOutcome {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer).map(Self::from)
+    }
+}
+
 impl CliOutput {
     /// Create a success output
     pub fn success(
@@ -93,16 +368,539 @@ impl CliOutput {
             response: Some(parsed_response),
             metadata,
             error: None,
+            tool_calls: Vec::new(),
+            images: Vec::new(),
         }
     }
 
     /// Create an error output
-    pub fn error(code: String, message: String, metadata: Metadata) -> Self {
+    pub fn error(code: impl Into<EvaluationOutcome>, message: String, metadata: Metadata) -> Self {
         Self {
             status: "error".to_string(),
             response: None,
             metadata,
-            error: Some(ErrorInfo { code, message }),
+            error: Some(ErrorInfo {
+                code: code.into(),
+                message,
+            }),
+            tool_calls: Vec::new(),
+            images: Vec::new(),
+        }
+    }
+
+    /// Response in which the model declined the request
+    ///
+    /// Keeps the response text (unlike [`Self::error`]) so refusals can be
+    /// inspected alongside answers.
+    pub fn refusal(response: String, message: String, metadata: Metadata) -> Self {
+        Self {
+            status: "refusal".to_string(),
+            response: Some(serde_json::Value::String(response)),
+            metadata,
+            error: Some(ErrorInfo {
+                code: EvaluationOutcome::Refusal,
+                message,
+            }),
+            tool_calls: Vec::new(),
+            images: Vec::new(),
+        }
+    }
+
+    /// Render the output in `format`, ending in a newline (unless empty)
+    ///
+    /// YAML and markdown are rendered from the serialized JSON layout, so
+    /// `[metadata_fields]` filtering applies to them too.
+    pub fn render(&self, format: OutputFormat) -> Result<String, serde_json::Error> {
+        Ok(match format {
+            OutputFormat::Json => format!("{}\n", serde_json::to_string_pretty(self)?),
+            OutputFormat::Yaml => to_yaml(&serde_json::to_value(self)?),
+            OutputFormat::Markdown => markdown_report(&serde_json::to_value(self)?),
+            OutputFormat::Raw => match &self.response {
+                Some(Value::String(text)) if text.ends_with('\n') => text.clone(),
+                Some(Value::String(text)) => format!("{text}\n"),
+                Some(value) => format!("{value}\n"),
+                None => String::new(),
+            },
+        })
+    }
+
+    /// Outcome of a failed evaluation (None on success)
+    pub fn outcome(&self) -> Option<&EvaluationOutcome> {
+        self.error.as_ref().map(|e| &e.code)
+    }
+
+    /// Direction (`input` or `output`) of a guardrail or provider content
+    /// filter block (None when the evaluation wasn't blocked)
+    pub fn blocked_direction(&self) -> Option<&str> {
+        match self.outcome()? {
+            EvaluationOutcome::InputValidationFailed => Some("input"),
+            EvaluationOutcome::OutputValidationFailed => Some("output"),
+            EvaluationOutcome::ProviderContentFiltered => self
+                .metadata
+                .guardrail_results
+                .iter()
+                .rev()
+                .find(|result| !result.passed)
+                .map(|result| result.direction.as_str()),
+            _ => None,
+        }
+    }
+}
+
+/// Block-style YAML for a JSON value (strings are quoted when they could be
+/// read as another type, multi-line strings use literal blocks)
+pub(crate) fn to_yaml(value: &Value) -> String {
+    let mut out = String::new();
+    match value {
+        Value::Object(map) if !map.is_empty() => write_yaml_mapping(&mut out, map, 0),
+        Value::Array(items) if !items.is_empty() => write_yaml_sequence(&mut out, items, 0),
+        scalar => {
+            let _ = writeln!(out, "{}", yaml_scalar(scalar, 0));
         }
     }
+    out
+}
+
+fn write_yaml_mapping(out: &mut String, map: &serde_json::Map<String, Value>, indent: usize) {
+    for (key, value) in map {
+        let _ = write!(out, "{:indent$}{}:", "", yaml_string(key, indent));
+        write_yaml_value(out, value, indent);
+    }
+}
+
+fn write_yaml_sequence(out: &mut String, items: &[Value], indent: usize) {
+    for item in items {
+        match item {
+            // The first line of a nested block goes after the dash
+            Value::Object(map) if !map.is_empty() => {
+                let mut nested = String::new();
+                write_yaml_mapping(&mut nested, map, indent + 2);
+                let _ = write!(out, "{:indent$}- {}", "", &nested[indent + 2..]);
+            }
+            Value::Array(items) if !items.is_empty() => {
+                let mut nested = String::new();
+                write_yaml_sequence(&mut nested, items, indent + 2);
+                let _ = write!(out, "{:indent$}- {}", "", &nested[indent + 2..]);
+            }
+            scalar => {
+                let _ = writeln!(out, "{:indent$}- {}", "", yaml_scalar(scalar, indent + 2));
+            }
+        }
+    }
+}
+
+/// Value after a `key:` at `indent`
+fn write_yaml_value(out: &mut String, value: &Value, indent: usize) {
+    match value {
+        Value::Object(map) if !map.is_empty() => {
+            out.push('\n');
+            write_yaml_mapping(out, map, indent + 2);
+        }
+        Value::Array(items) if !items.is_empty() => {
+            out.push('\n');
+            write_yaml_sequence(out, items, indent + 2);
+        }
+        scalar => {
+            let _ = writeln!(out, " {}", yaml_scalar(scalar, indent + 2));
+        }
+    }
+}
+
+/// Scalar (or empty collection) whose continuation lines are indented by
+/// `indent`
+fn yaml_scalar(value: &Value, indent: usize) -> String {
+    match value {
+        Value::Null => "null".to_string(),
+        Value::Bool(b) => b.to_string(),
+        Value::Number(n) => n.to_string(),
+        Value::String(s) => yaml_string(s, indent),
+        Value::Array(_) => "[]".to_string(),
+        Value::Object(_) => "{}".to_string(),
+    }
+}
+
+fn yaml_string(s: &str, indent: usize) -> String {
+    if s.contains('\n') && !s.contains('\r') && !s.starts_with([' ', '\t', '\n']) {
+        // `|` keeps one trailing newline, `|-` none; more need quoting
+        let (body, indicator) = match s.strip_suffix('\n') {
+            Some(body) => (body, "|"),
+            None => (s, "|-"),
+        };
+        if !body.ends_with('\n') {
+            let mut block = indicator.to_string();
+            for line in body.split('\n') {
+                block.push('\n');
+                if !line.is_empty() {
+                    let _ = write!(block, "{:indent$}{line}", "");
+                }
+            }
+            return block;
+        }
+    }
+    if is_plain_yaml(s) {
+        s.to_string()
+    } else {
+        // JSON string escapes are valid in double-quoted YAML
+        Value::String(s.to_string()).to_string()
+    }
+}
+
+/// Whether a string can be written unquoted without changing its meaning
+fn is_plain_yaml(s: &str) -> bool {
+    const RESERVED: [&str; 12] = [
+        "true", "false", "null", "~", "yes", "no", "on", "off", "y", "n", ".inf", ".nan",
+    ];
+    let Some(first) = s.chars().next() else {
+        return false;
+    };
+    !RESERVED.contains(&s.to_ascii_lowercase().as_str())
+        // Leading digits could read as numbers, dates or times
+        && !first.is_ascii_digit()
+        && !"-+.?:,[]{}#&*!|>'\"%@`".contains(first)
+        && !first.is_whitespace()
+        && !s.ends_with(char::is_whitespace)
+        && !s.ends_with(':')
+        && !s.contains(": ")
+        && !s.contains(" #")
+        && !s.chars().any(char::is_control)
+}
+
+/// Markdown report of a serialized output
+fn markdown_report(output: &Value) -> String {
+    let mut out = String::from("# Evaluation Result\n\n");
+    let status = output["status"].as_str().unwrap_or("unknown");
+    let _ = writeln!(out, "**Status**: {status}\n");
+
+    if let Some(response) = output.get("response").filter(|r| !r.is_null()) {
+        out.push_str("## Response\n\n");
+        match response {
+            Value::String(text) => {
+                let _ = writeln!(out, "{}\n", text.trim_end());
+            }
+            value => {
+                let pretty = serde_json::to_string_pretty(value).unwrap_or_default();
+                let _ = writeln!(out, "```json\n{pretty}\n```\n");
+            }
+        }
+    }
+
+    if let Some(error) = output.get("error").filter(|e| !e.is_null()) {
+        out.push_str("## Error\n\n");
+        let _ = writeln!(
+            out,
+            "**Code**: `{}`\n\n{}\n",
+            error["code"].as_str().unwrap_or_default(),
+            error["message"].as_str().unwrap_or_default()
+        );
+    }
+
+    if let Some(calls) = output["tool_calls"].as_array() {
+        out.push_str("## Tool Calls\n\n");
+        for call in calls {
+            let arguments = serde_json::to_string_pretty(&call["arguments"]).unwrap_or_default();
+            let _ = writeln!(
+                out,
+                "- `{}`\n\n  ```json\n{}\n  ```\n",
+                call["name"].as_str().unwrap_or_default(),
+                indent_lines(&arguments, 2)
+            );
+        }
+    }
+    if let Some(images) = output["images"].as_array() {
+        let _ = writeln!(
+            out,
+            "{} generated image(s), see the JSON output\n",
+            images.len()
+        );
+    }
+
+    let metadata = output["metadata"].as_object();
+    let mut rows: Vec<(&str, String)> = Vec::new();
+    for (field, value) in metadata.into_iter().flatten() {
+        if MARKDOWN_GUARDRAIL_FIELDS.contains(&field.as_str()) {
+            continue;
+        }
+        let cell = match value {
+            Value::String(s) => format!("`{}`", table_cell(s).replace('`', "'")),
+            Value::Number(n) => n.to_string(),
+            Value::Bool(b) => b.to_string(),
+            Value::Array(items) if items.iter().all(Value::is_string) => items
+                .iter()
+                .filter_map(Value::as_str)
+                .map(|item| format!("`{}`", table_cell(item).replace('`', "'")))
+                .collect::<Vec<_>>()
+                .join(", "),
+            _ => continue,
+        };
+        rows.push((field, cell));
+    }
+    if !rows.is_empty() {
+        out.push_str("## Metadata\n\n| Field | Value |\n|-------|-------|\n");
+        for (field, cell) in rows {
+            let _ = writeln!(out, "| {field} | {cell} |");
+        }
+        out.push('\n');
+    }
+
+    if let Some(metadata) = metadata {
+        write_guardrail_summary(&mut out, metadata);
+        if let Some(warnings) = metadata.get("warnings").and_then(Value::as_array) {
+            out.push_str("## Warnings\n\n");
+            for warning in warnings {
+                let _ = writeln!(
+                    out,
+                    "- `{}`: {}",
+                    warning["code"].as_str().unwrap_or_default(),
+                    warning["message"].as_str().unwrap_or_default()
+                );
+            }
+            out.push('\n');
+        }
+    }
+
+    let trimmed = out.trim_end().len();
+    out.truncate(trimmed);
+    out.push('\n');
+    out
+}
+
+/// Metadata fields shown in the guardrail summary rather than the table
+const MARKDOWN_GUARDRAIL_FIELDS: [&str; 7] = [
+    "input_guardrails_enabled",
+    "output_guardrails_enabled",
+    "violation_rules",
+    "quality_score",
+    "guardrail_explanations",
+    "redactions",
+    "warnings",
+];
+
+fn write_guardrail_summary(out: &mut String, metadata: &serde_json::Map<String, Value>) {
+    // Only set when the direction has guardrails
+    let enabled = |field: &str| match metadata.get(field).and_then(Value::as_bool) {
+        Some(true) => "enabled",
+        _ => "disabled",
+    };
+    out.push_str("## Guardrails\n\n");
+    let _ = writeln!(
+        out,
+        "- Input guardrails: {}",
+        enabled("input_guardrails_enabled")
+    );
+    let _ = writeln!(
+        out,
+        "- Output guardrails: {}",
+        enabled("output_guardrails_enabled")
+    );
+    if let Some(rules) = metadata.get("violation_rules").and_then(Value::as_array) {
+        let rules: Vec<String> = rules
+            .iter()
+            .filter_map(Value::as_str)
+            .map(|rule| format!("`{rule}`"))
+            .collect();
+        let _ = writeln!(out, "- Blocked by: {}", rules.join(", "));
+    }
+    if let Some(redactions) = metadata.get("redactions").and_then(Value::as_array) {
+        for redaction in redactions {
+            let _ = writeln!(
+                out,
+                "- Redacted: `{}` x{}",
+                redaction["rule"].as_str().unwrap_or_default(),
+                redaction["count"]
+            );
+        }
+    }
+    if let Some(score) = metadata.get("quality_score") {
+        let _ = writeln!(out, "- Quality score: {score}");
+    }
+    if let Some(explanations) = metadata
+        .get("guardrail_explanations")
+        .and_then(Value::as_object)
+    {
+        for (direction, explanation) in explanations {
+            let _ = writeln!(
+                out,
+                "- {direction} verdict: {}",
+                explanation.as_str().unwrap_or_default()
+            );
+        }
+    }
+    if let Some(results) = metadata.get("guardrail_results").and_then(Value::as_array) {
+        out.push_str("\n| Provider | Direction | Result | Rules | Latency (ms) |\n");
+        out.push_str("|----------|-----------|--------|-------|--------------|\n");
+        for result in results {
+            let rules: Vec<&str> = result["violation_rules"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(Value::as_str)
+                .collect();
+            let _ = writeln!(
+                out,
+                "| {} | {} | {} | {} | {} |",
+                table_cell(result["provider"].as_str().unwrap_or_default()),
+                result["direction"].as_str().unwrap_or_default(),
+                if result["passed"].as_bool() == Some(true) {
+                    "passed"
+                } else {
+                    "failed"
+                },
+                table_cell(&rules.join(", ")),
+                result["latency_ms"]
+            );
+        }
+    }
+    out.push('\n');
+}
+
+/// Single-line table cell text, cut at 80 characters
+fn table_cell(text: &str) -> String {
+    const MAX_CHARS: usize = 80;
+    let line = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    let mut cell: String = line.chars().take(MAX_CHARS).collect();
+    if line.chars().count() > MAX_CHARS {
+        cell.push('…');
+    }
+    cell.replace('|', "\\|")
+}
+
+fn indent_lines(text: &str, indent: usize) -> String {
+    text.lines()
+        .map(|line| format!("{:indent$}{line}", ""))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_outcome_serializes_to_legacy_codes() {
+        for (outcome, code) in [
+            (
+                EvaluationOutcome::InputValidationFailed,
+                "INPUT_VALIDATION_FAILED",
+            ),
+            (
+                EvaluationOutcome::OutputValidationFailed,
+                "OUTPUT_VALIDATION_FAILED",
+            ),
+            (
+                EvaluationOutcome::ContextLimitExceeded,
+                "CONTEXT_LIMIT_EXCEEDED",
+            ),
+            (EvaluationOutcome::FileTooLarge, "FILE_TOO_LARGE"),
+            (
+                EvaluationOutcome::Other("HTTP_ERROR".to_string()),
+                "HTTP_ERROR",
+            ),
+        ] {
+            assert_eq!(serde_json::to_value(&outcome).unwrap(), code);
+            assert_eq!(
+                serde_json::from_value::<EvaluationOutcome>(code.into()).unwrap(),
+                outcome
+            );
+            assert_eq!(outcome, code);
+        }
+    }
+
+    #[test]
+    fn test_outcome_from_cli_error() {
+        let http = CliError::InvalidResponse("bad".to_string());
+        assert_eq!(
+            EvaluationOutcome::from(&http),
+            EvaluationOutcome::Other("INVALID_RESPONSE".to_string())
+        );
+
+        let context = CliError::ContextLimitExceeded {
+            required: 10,
+            limit: 5,
+            excess: 5,
+        };
+        assert_eq!(
+            EvaluationOutcome::from(&context),
+            EvaluationOutcome::ContextLimitExceeded
+        );
+    }
+
+    #[test]
+    fn test_yaml_quotes_ambiguous_strings() {
+        let value = serde_json::json!({
+            "status": "success",
+            "response": "line one\nline two\n",
+            "error": null,
+            "metadata": {
+                "model": "gpt-4o",
+                "timestamp": "2026-03-04T05:06:07Z",
+                "tags": ["team=a", "yes", "a: b", ""],
+                "guardrail_results": [{"provider": "regex", "passed": true}],
+                "redactions": [],
+                "temperature": 0.5,
+            },
+        });
+
+        assert_eq!(
+            to_yaml(&value),
+            "error: null\n\
+             metadata:\n  \
+               guardrail_results:\n    \
+                 - passed: true\n      \
+                   provider: regex\n  \
+               model: gpt-4o\n  \
+               redactions: []\n  \
+               tags:\n    \
+                 - team=a\n    \
+                 - \"yes\"\n    \
+                 - \"a: b\"\n    \
+                 - \"\"\n  \
+               temperature: 0.5\n  \
+               timestamp: \"2026-03-04T05:06:07Z\"\n\
+             response: |\n  \
+               line one\n  \
+               line two\n\
+             status: success\n"
+        );
+        assert_eq!(
+            yaml_string("no newline\nat end", 2),
+            "|-\n  no newline\n  at end"
+        );
+        assert_eq!(
+            yaml_string("  indented\nblock", 2),
+            "\"  indented\\nblock\""
+        );
+    }
+
+    #[test]
+    fn test_markdown_report_summarizes_guardrails() {
+        let report = markdown_report(&serde_json::json!({
+            "status": "error",
+            "response": null,
+            "error": {"code": "INPUT_VALIDATION_FAILED", "message": "Prompt injection"},
+            "metadata": {
+                "model": "gpt-4o",
+                "latency_ms": 12,
+                "user_prompt_text": "a | b",
+                "input_guardrails_enabled": true,
+                "violation_rules": ["regex.PROMPT_INJECTION_ATTEMPT"],
+                "guardrail_results": [{
+                    "provider": "regex",
+                    "direction": "input",
+                    "passed": false,
+                    "violation_rules": ["regex.PROMPT_INJECTION_ATTEMPT"],
+                    "latency_ms": 1,
+                }],
+            },
+        }));
+
+        assert!(report.starts_with("# Evaluation Result\n\n**Status**: error\n"));
+        assert!(!report.contains("## Response"));
+        assert!(report.contains("**Code**: `INPUT_VALIDATION_FAILED`\n\nPrompt injection"));
+        assert!(report.contains("| model | `gpt-4o` |\n"));
+        assert!(report.contains("| user_prompt_text | `a \\| b` |\n"));
+        assert!(report.contains("- Input guardrails: enabled\n- Output guardrails: disabled\n"));
+        assert!(report.contains("- Blocked by: `regex.PROMPT_INJECTION_ATTEMPT`"));
+        assert!(report.contains("| regex | input | failed | regex.PROMPT_INJECTION_ATTEMPT | 1 |"));
+        assert!(report.ends_with("|\n"));
+    }
 }
@@ -1,6 +1,7 @@
 use crate::error::CliError;
 use std::{path::Path, process::Command};
 use tokio::task;
+use tracing::Instrument;
 
 const DOCLING_COMMAND: &str = "docling";
 
@@ -157,8 +158,14 @@ pub async fn extract_text_from_pdf(path: &Path) -> Result<PdfContent, CliError>
     }
 
     // Extract with Docling
-    match extract_with_docling(path).await {
+    let span = tracing::info_span!(
+        "pdf.extract",
+        path = %path.display(),
+        chars = tracing::field::Empty,
+    );
+    match extract_with_docling(path).instrument(span.clone()).await {
         Ok(content) => {
+            span.record("chars", content.text.len());
             log::info!("PDF extracted successfully with Docling (markdown format)");
             Ok(content)
         }
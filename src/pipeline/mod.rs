@@ -0,0 +1,648 @@
+//! Evaluation pipeline
+//!
+//! An evaluation is a sequence of named [`Stage`]s sharing an
+//! [`EvaluationContext`]. The default pipeline is
+//!
+//! ```text
+//! extract → input_guard → spotlight → estimate → invoke → repetition → postprocess
+//!         → output_guard → refusal → format
+//! ```
+//!
+//! Library users can insert, remove, or replace stages by name. A stage ends
+//! the evaluation early by returning an output (e.g. a guardrail block); the
+//! `format` stage produces the final success output. Each stage's wall time is
+//! recorded in `metadata.stage_latency`.
+
+mod stages;
+
+pub use stages::{
+    ChunkCallback, EstimateStage, ExtractStage, FormatStage, InputGuardStage, InvokeStage,
+    OutputGuardStage, PostprocessStage, RefusalStage, RepetitionStage, SpotlightStage,
+    StreamingInvokeStage,
+};
+
+use crate::{
+    chunking::ChunkingRecord,
+    deadline::GUARDRAIL_SKIPPED_DEADLINE,
+    guardrails::{
+        apply_conditions, EscalationRecord, GuardMeter, GuardrailStage, InputFacts,
+        NamedProviderResult, OutputContext, Redaction, ViolationPreview,
+    },
+    image::ImageInput,
+    log_policy::loggable,
+    output::{EvaluationWarning, StageLatency},
+    provider::TokenUsage,
+    providers::detect_provider_type,
+    response_limit,
+    spotlight::{render_segments, PromptSegment, SpotlightStrategy},
+    CliError, CliOutput, EvaluationConfig, EvaluationOutcome, GuardrailProviderConfig, Metadata,
+    ProviderResponse, ResponseFormat, TokenEstimator,
+};
+use async_trait::async_trait;
+use std::{
+    collections::BTreeMap,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use tracing::Instrument;
+
+/// Default stage names
+pub mod stage_names {
+    pub const EXTRACT: &str = "extract";
+    pub const INPUT_GUARD: &str = "input_guard";
+    pub const SPOTLIGHT: &str = "spotlight";
+    pub const ESTIMATE: &str = "estimate";
+    pub const INVOKE: &str = "invoke";
+    pub const REPETITION: &str = "repetition";
+    pub const POSTPROCESS: &str = "postprocess";
+    pub const OUTPUT_GUARD: &str = "output_guard";
+    pub const REFUSAL: &str = "refusal";
+    pub const FORMAT: &str = "format";
+}
+
+/// State threaded through the pipeline stages
+pub struct EvaluationContext {
+    pub config: EvaluationConfig,
+    /// User prompt actually sent (extracted PDF text when `pdf_input` is set)
+    pub user_prompt: String,
+    /// Trusted/untrusted pieces of `user_prompt` (set by `extract`)
+    pub segments: Vec<PromptSegment>,
+    /// Images sent to a vision model with the prompt (set by `extract`)
+    pub images: Vec<ImageInput>,
+    /// User prompts of the document chunks, when the prompt exceeds the
+    /// context window and chunking is configured (set by `estimate`)
+    pub chunk_prompts: Vec<String>,
+    /// Strategy and per-chunk details of a chunked evaluation
+    pub chunking: Option<ChunkingRecord>,
+    /// Spotlighting strategy applied to untrusted segments (set by `spotlight`)
+    pub spotlight_applied: Option<SpotlightStrategy>,
+    pub tokens_estimated: usize,
+    /// Token usage reported by the provider, summed over every LLM call
+    /// (chunks, combine calls, retries); None when no call reported usage
+    pub tokens_actual: Option<TokenUsage>,
+    /// LLM response (set by `invoke`, consumed by `format`)
+    pub response: Option<ProviderResponse>,
+    /// Timings of the stages completed so far
+    pub stage_latency: Vec<StageLatency>,
+    /// Non-blocking issues, reported in `metadata.warnings`
+    pub warnings: Vec<EvaluationWarning>,
+    /// Quality score reported by the output guardrails (set by `output_guard`)
+    pub quality_score: Option<f32>,
+    /// Rule IDs of the violations that blocked the evaluation (set by the guard stages)
+    pub violation_rules: Vec<String>,
+    /// Masked excerpts of the blocking violations (set with `violation_rules`)
+    pub violation_previews: Vec<ViolationPreview>,
+    /// Input replaced by placeholders before validation (set by `input_guard`)
+    pub redactions: Vec<Redaction>,
+    /// Composite guardrail member results (extended by the guard stages)
+    pub guardrail_results: Vec<NamedProviderResult>,
+    /// Composite verdict explanations by direction (set by the guard stages)
+    pub guardrail_explanations: BTreeMap<String, String>,
+    /// Both input verdicts when the input guard stage escalated
+    pub escalation: Option<EscalationRecord>,
+    /// Token usage of model-backed guardrails (shared by the guard stages)
+    pub guard_meter: GuardMeter,
+    /// Whether `invoke` answered from the response cache (None = no cache)
+    pub cache_hit: Option<bool>,
+    start_time: Instant,
+}
+
+impl EvaluationContext {
+    pub fn new(config: EvaluationConfig) -> Self {
+        Self {
+            user_prompt: config.user_prompt.clone(),
+            guard_meter: GuardMeter::new(config.guard_budget.clone()),
+            config,
+            segments: Vec::new(),
+            images: Vec::new(),
+            chunk_prompts: Vec::new(),
+            chunking: None,
+            spotlight_applied: None,
+            tokens_estimated: 0,
+            tokens_actual: None,
+            response: None,
+            stage_latency: Vec::new(),
+            warnings: Vec::new(),
+            quality_score: None,
+            violation_rules: Vec::new(),
+            violation_previews: Vec::new(),
+            redactions: Vec::new(),
+            guardrail_results: Vec::new(),
+            guardrail_explanations: BTreeMap::new(),
+            escalation: None,
+            cache_hit: None,
+            start_time: Instant::now(),
+        }
+    }
+
+    /// Add the provider-reported usage of an LLM call
+    pub fn record_usage(&mut self, response: &ProviderResponse) {
+        if let Some(usage) = response.usage {
+            self.tokens_actual
+                .get_or_insert_with(Default::default)
+                .add(usage);
+        }
+    }
+
+    /// Record a non-blocking warning (also logged)
+    pub fn warn(&mut self, code: impl Into<String>, message: impl Into<String>) {
+        let warning = EvaluationWarning {
+            code: code.into(),
+            message: message.into(),
+        };
+        log::warn!("{}: {}", warning.code, loggable(&warning.message));
+        self.warnings.push(warning);
+    }
+
+    /// Estimated share of the context window used (None until estimated)
+    pub fn context_usage_ratio(&self) -> Option<f64> {
+        let limit = self.config.context_limit.filter(|&l| l > 0)?;
+        (self.tokens_estimated > 0).then(|| self.tokens_estimated as f64 / limit as f64)
+    }
+
+    /// Input characteristics for conditional guardrails
+    pub fn input_facts(&self) -> InputFacts {
+        let config = &self.config;
+        let input_source = if config.pdf_input.is_some() {
+            "pdf"
+        } else if config.url_input.is_some() {
+            "url"
+        } else if config.image_input.is_some() && config.user_prompt.is_empty() {
+            "image"
+        } else if config.user_prompt_file.is_some() {
+            "file"
+        } else {
+            "text"
+        };
+        let response_format = match &config.response_format {
+            None => "none",
+            Some(ResponseFormat::Text) => "text",
+            Some(ResponseFormat::JsonObject) => "json_object",
+            Some(ResponseFormat::JsonSchema { .. }) => "json_schema",
+        };
+        let user_prompt = self.unspotlighted_prompt();
+        InputFacts {
+            input_tokens: TokenEstimator::new_for_model("", &user_prompt, 0, &config.model)
+                .breakdown()
+                .user_tokens,
+            input_bytes: user_prompt.len(),
+            untrusted_content: config.untrusted_content.len(),
+            input_source: input_source.to_string(),
+            response_format: response_format.to_string(),
+            model: config.model.clone(),
+        }
+    }
+
+    /// Time left before the `[deadline]` (None without a deadline)
+    pub fn deadline_remaining(&self) -> Option<Duration> {
+        let deadline = self.config.deadline.as_ref()?;
+        Some(deadline.total().saturating_sub(self.start_time.elapsed()))
+    }
+
+    /// `DEADLINE_EXCEEDED` output once the deadline has passed before `stage`
+    fn deadline_exceeded(&self, stage: &str) -> Option<CliOutput> {
+        if self.deadline_remaining()? > Duration::ZERO {
+            return None;
+        }
+        let total_ms = self.config.deadline.as_ref()?.total_ms;
+        log::error!("Evaluation deadline of {total_ms}ms exceeded before the '{stage}' stage");
+        Some(CliOutput::error(
+            EvaluationOutcome::DeadlineExceeded,
+            format!("Evaluation deadline of {total_ms}ms exceeded before the '{stage}' stage"),
+            self.metadata(),
+        ))
+    }
+
+    /// Guardrails for `stage` after the conditional rules and, close to the
+    /// deadline, without the skippable providers (recorded as a warning)
+    pub fn guardrails_within_deadline(
+        &mut self,
+        stage: GuardrailStage,
+    ) -> Option<GuardrailProviderConfig> {
+        let config = self.guardrails_for(stage)?;
+        self.within_deadline(&stage.to_string(), config)
+    }
+
+    /// `config` without the skippable providers when close to the deadline
+    /// (recorded as a warning naming `label` guardrails)
+    pub fn within_deadline(
+        &mut self,
+        label: &str,
+        config: GuardrailProviderConfig,
+    ) -> Option<GuardrailProviderConfig> {
+        let (Some(deadline), Some(remaining)) = (&self.config.deadline, self.deadline_remaining())
+        else {
+            return Some(config);
+        };
+        let (config, skipped) = deadline.apply(config, remaining);
+        if !skipped.is_empty() {
+            self.warn(
+                GUARDRAIL_SKIPPED_DEADLINE,
+                format!(
+                    "Skipped {label} guardrails {} with {}ms left of the {}ms deadline",
+                    skipped.join(", "),
+                    remaining.as_millis(),
+                    deadline.total_ms
+                ),
+            );
+        }
+        config
+    }
+
+    /// Guardrails for `stage` after applying the conditional rules
+    pub fn guardrails_for(&self, stage: GuardrailStage) -> Option<GuardrailProviderConfig> {
+        let config = &self.config;
+        let base = match stage {
+            GuardrailStage::Input => config.input_guardrails.clone(),
+            GuardrailStage::Output => config.output_guardrails.clone(),
+        };
+        if config.guardrail_conditions.is_empty() {
+            return base;
+        }
+        apply_conditions(
+            base,
+            &config.guardrail_conditions,
+            stage,
+            &self.input_facts(),
+        )
+    }
+
+    /// Context for output guardrails: the user prompt before spotlighting, the
+    /// system prompt's hash, the requested response format and the images
+    /// returned with the response
+    pub fn output_context(&self) -> OutputContext {
+        OutputContext::new(
+            &self.config.system_prompt,
+            self.unspotlighted_prompt(),
+            self.config.response_format.clone(),
+        )
+        .with_images(
+            self.response
+                .as_ref()
+                .map(|response| response.images.clone())
+                .unwrap_or_default(),
+        )
+    }
+
+    /// User prompt as guardrails see it (before spotlighting)
+    fn unspotlighted_prompt(&self) -> String {
+        if self.segments.is_empty() {
+            self.user_prompt.clone()
+        } else {
+            render_segments(&self.segments, None)
+        }
+    }
+
+    /// Metadata snapshot for the current state of the evaluation
+    pub fn metadata(&self) -> Metadata {
+        let config = &self.config;
+        Metadata {
+            // Execution results
+            model: config.model.clone(),
+            tokens_estimated: self.tokens_estimated,
+            tokens_prompt_actual: self.tokens_actual.map(|u| u.prompt_tokens),
+            tokens_completion_actual: self.tokens_actual.map(|u| u.completion_tokens),
+            context_usage_ratio: self
+                .context_usage_ratio()
+                .map(|r| (r * 1000.0).round() / 1000.0),
+            latency_ms: self.start_time.elapsed().as_millis() as u64,
+            timestamp: chrono::Utc::now().to_rfc3339(),
+
+            // Input configuration (for reproducibility)
+            api_url: config.api_url.clone(),
+            provider: config.provider.map(|p| format!("{p:?}")),
+            temperature: config.temperature,
+            top_p: config.top_p,
+            max_tokens: config.max_tokens,
+            seed: config.seed,
+            timeout_secs: config.timeout_secs,
+            context_limit: config.context_limit,
+            response_format: config.response_format.as_ref().map(|f| f.to_string()),
+            validate_tokens: config.validate_tokens,
+
+            // Input sources (distinguish between text and file inputs)
+            system_prompt_text: if config.system_prompt_file.is_none()
+                && config.system_prompt_url.is_none()
+            {
+                Some(config.system_prompt.clone())
+            } else {
+                None
+            },
+            system_prompt_file: config
+                .system_prompt_file
+                .as_ref()
+                .map(|p| p.display().to_string()),
+            user_prompt_text: if config.user_prompt_file.is_none()
+                && config.pdf_input.is_none()
+                && config.url_input.is_none()
+            {
+                Some(self.user_prompt.clone())
+            } else {
+                None
+            },
+            user_prompt_file: config
+                .user_prompt_file
+                .as_ref()
+                .map(|p| p.display().to_string()),
+            pdf_input: config.pdf_input.as_ref().map(|p| p.display().to_string()),
+            url_input: config.url_input.clone(),
+            system_prompt_url: config.system_prompt_url.clone(),
+            prompt_pins: config.prompt_pins.clone(),
+            image_input: config.image_input.as_ref().map(|p| p.display().to_string()),
+            image_mode: config.image_input.as_ref().map(|_| config.image_mode),
+            chunking: self.chunking.clone(),
+            user_prompt_prefix: config.user_prompt_prefix.clone(),
+            user_prompt_suffix: config.user_prompt_suffix.clone(),
+            spotlighting: self.spotlight_applied.map(|s| s.to_string()),
+            tags: config.tags.clone(),
+
+            // Guardrails
+            input_guardrails_enabled: config.input_guardrails.as_ref().map(|_| true),
+            output_guardrails_enabled: config.output_guardrails.as_ref().map(|_| true),
+            violation_rules: self.violation_rules.clone(),
+            violation_previews: self.violation_previews.clone(),
+            redactions: self.redactions.clone(),
+            guardrail_results: self.guardrail_results.clone(),
+            guardrail_explanations: self.guardrail_explanations.clone(),
+            escalation: self.escalation.clone(),
+            guard_tokens: self.guard_meter.usage(),
+            quality_score: self.quality_score,
+
+            // Transport
+            response_chunks_aggregated: self.response.as_ref().and_then(|r| r.aggregated_chunks),
+            upstream: self.response.as_ref().and_then(|r| r.upstream.clone()),
+            model_echo: self.response.as_ref().and_then(|r| r.model_echo.clone()),
+            cache_hit: self.cache_hit,
+
+            // Timing (filled in by the pipeline)
+            stage_latency: Vec::new(),
+
+            // Configuration
+            config_warnings: config.config_warnings.clone(),
+
+            // Warnings
+            warnings: self.warnings.clone(),
+
+            // Output filtering
+            field_filter: config.metadata_fields.clone(),
+        }
+    }
+}
+
+/// A single named evaluation step
+#[async_trait]
+pub trait Stage: Send + Sync {
+    /// Stage name used for lookup and timing
+    fn name(&self) -> &str;
+
+    /// Run the stage. `Ok(Some(output))` ends the evaluation with that output.
+    async fn run(&self, ctx: &mut EvaluationContext) -> Result<Option<CliOutput>, CliError>;
+}
+
+/// Ordered list of stages
+#[derive(Clone)]
+pub struct Pipeline {
+    stages: Vec<Arc<dyn Stage>>,
+}
+
+impl Default for Pipeline {
+    /// The standard evaluation pipeline
+    fn default() -> Self {
+        Self::new()
+            .with_stage(ExtractStage)
+            .with_stage(InputGuardStage)
+            .with_stage(SpotlightStage)
+            .with_stage(EstimateStage)
+            .with_stage(InvokeStage)
+            .with_stage(RepetitionStage)
+            .with_stage(PostprocessStage)
+            .with_stage(OutputGuardStage)
+            .with_stage(RefusalStage)
+            .with_stage(FormatStage)
+    }
+}
+
+impl Pipeline {
+    /// Create an empty pipeline
+    pub fn new() -> Self {
+        Self { stages: Vec::new() }
+    }
+
+    /// Names of the stages, in execution order
+    pub fn stage_names(&self) -> Vec<&str> {
+        self.stages.iter().map(|s| s.name()).collect()
+    }
+
+    /// Append a stage
+    pub fn with_stage(mut self, stage: impl Stage + 'static) -> Self {
+        self.stages.push(Arc::new(stage));
+        self
+    }
+
+    /// Insert a stage before the stage named `name`
+    pub fn insert_before(
+        mut self,
+        name: &str,
+        stage: impl Stage + 'static,
+    ) -> Result<Self, CliError> {
+        let index = self.position(name)?;
+        self.stages.insert(index, Arc::new(stage));
+        Ok(self)
+    }
+
+    /// Insert a stage after the stage named `name`
+    pub fn insert_after(
+        mut self,
+        name: &str,
+        stage: impl Stage + 'static,
+    ) -> Result<Self, CliError> {
+        let index = self.position(name)?;
+        self.stages.insert(index + 1, Arc::new(stage));
+        Ok(self)
+    }
+
+    /// Replace the stage named `name`
+    pub fn replace(mut self, name: &str, stage: impl Stage + 'static) -> Result<Self, CliError> {
+        let index = self.position(name)?;
+        self.stages[index] = Arc::new(stage);
+        Ok(self)
+    }
+
+    /// Remove the stage named `name`
+    pub fn remove(mut self, name: &str) -> Result<Self, CliError> {
+        let index = self.position(name)?;
+        self.stages.remove(index);
+        Ok(self)
+    }
+
+    fn position(&self, name: &str) -> Result<usize, CliError> {
+        self.stages
+            .iter()
+            .position(|s| s.name() == name)
+            .ok_or_else(|| {
+                CliError::InvalidArguments(format!(
+                    "Unknown pipeline stage '{name}' (stages: {})",
+                    self.stage_names().join(", ")
+                ))
+            })
+    }
+
+    /// Run all stages against `config`
+    pub async fn run(&self, config: EvaluationConfig) -> Result<CliOutput, CliError> {
+        let provider = config
+            .provider
+            .unwrap_or_else(|| detect_provider_type(&config.api_url));
+        let span = tracing::info_span!(
+            "evaluate",
+            model = %config.model,
+            provider = ?provider,
+            status = tracing::field::Empty,
+            error_code = tracing::field::Empty,
+        );
+        let metrics = config.metrics.clone().map(|m| (m, config.model.clone()));
+        let started = Instant::now();
+        let limit = config.max_response_bytes;
+        let result = response_limit::scope(limit, self.run_stages(config))
+            .instrument(span.clone())
+            .await;
+        if let Some((metrics, model)) = metrics {
+            metrics.record_evaluation(&model, &result, started.elapsed());
+        }
+        match &result {
+            Ok(output) => {
+                span.record("status", output.status.as_str());
+                if let Some(error) = &output.error {
+                    span.record("error_code", error.code.as_str());
+                }
+            }
+            Err(e) => {
+                span.record("status", "error");
+                span.record("error_code", e.code());
+            }
+        }
+        result
+    }
+
+    async fn run_stages(&self, config: EvaluationConfig) -> Result<CliOutput, CliError> {
+        // Pre-flight: restricted data never leaves for a refused endpoint
+        if let Some(policy) = &config.data_policy {
+            policy.check(&crate::data_policy::content_endpoints(&config))?;
+        }
+        let mut ctx = EvaluationContext::new(config);
+
+        for stage in &self.stages {
+            let outcome = match ctx.deadline_exceeded(stage.name()) {
+                Some(output) => Some(output),
+                None => {
+                    let started = Instant::now();
+                    let span = tracing::info_span!(
+                        "stage",
+                        otel.name = %format!("stage {}", stage.name()),
+                        stage = stage.name(),
+                    );
+                    let outcome = stage.run(&mut ctx).instrument(span).await?;
+                    ctx.stage_latency.push(StageLatency {
+                        stage: stage.name().to_string(),
+                        latency_ms: started.elapsed().as_millis() as u64,
+                    });
+                    log::debug!("Pipeline stage '{}' completed", stage.name());
+                    outcome
+                }
+            };
+
+            if let Some(mut output) = outcome {
+                output.metadata.stage_latency = ctx.stage_latency;
+                if let Some(trends) = &ctx.config.trend_stats {
+                    trends.record(&ctx.config, &output, ctx.quality_score)?;
+                }
+                // Outputs leave filtered fields out; the audit log keeps them
+                if let (Some(auditor), Some(_)) =
+                    (&ctx.config.prompt_audit, &ctx.config.metadata_fields)
+                {
+                    auditor.record_completion(&output)?;
+                }
+                return Ok(output);
+            }
+        }
+
+        Err(CliError::InvalidArguments(format!(
+            "Pipeline ended without producing output (stages: {})",
+            self.stage_names().join(", ")
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Named(&'static str);
+
+    #[async_trait]
+    impl Stage for Named {
+        fn name(&self) -> &str {
+            self.0
+        }
+
+        async fn run(&self, _ctx: &mut EvaluationContext) -> Result<Option<CliOutput>, CliError> {
+            Ok(None)
+        }
+    }
+
+    #[test]
+    fn test_default_stage_order() {
+        assert_eq!(
+            Pipeline::default().stage_names(),
+            vec![
+                "extract",
+                "input_guard",
+                "spotlight",
+                "estimate",
+                "invoke",
+                "repetition",
+                "postprocess",
+                "output_guard",
+                "refusal",
+                "format"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_stage_editing() {
+        let pipeline = Pipeline::default()
+            .insert_before(stage_names::INVOKE, Named("pre_invoke"))
+            .unwrap()
+            .insert_after(stage_names::INVOKE, Named("post_invoke"))
+            .unwrap()
+            .remove(stage_names::ESTIMATE)
+            .unwrap()
+            .replace(stage_names::POSTPROCESS, Named("redact"))
+            .unwrap();
+
+        assert_eq!(
+            pipeline.stage_names(),
+            vec![
+                "extract",
+                "input_guard",
+                "spotlight",
+                "pre_invoke",
+                "invoke",
+                "post_invoke",
+                "repetition",
+                "redact",
+                "output_guard",
+                "refusal",
+                "format"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_unknown_stage_is_an_error() {
+        let Err(err) = Pipeline::default().remove("nope") else {
+            panic!("Expected unknown stage error");
+        };
+        assert!(err.to_string().contains("Unknown pipeline stage 'nope'"));
+    }
+}
@@ -0,0 +1,1369 @@
+//! Default pipeline stages
+
+use super::{stage_names, EvaluationContext, Stage};
+use crate::{
+    cache::{CachedResponse, ResponseCache},
+    chunking::{self, ChunkRecord, ChunkStrategy, ChunkingConfig, ChunkingRecord},
+    client::LlmClient,
+    constants, create_metered_guardrail_provider, extract_text_from_pdf,
+    fetch::{fetch_prompt, fetch_url},
+    guardrails::{
+        catalog::namespace, provider::merge_redactions, EscalationConfig, EscalationRecord,
+        GuardrailProvider, GuardrailStage, InputSourcesConfig, NamedProviderResult, Severity,
+        Violation,
+    },
+    image::{extract_text_from_image, ImageInput, ImageMode},
+    log_policy::loggable,
+    refusal::RefusalAction,
+    spotlight::{render_segments, PromptSegment, PromptSource},
+    telemetry::{guardrail_span, record_guardrail},
+    CliError, CliOutput, EvaluationConfig, EvaluationOutcome, GuardrailResult, InvokeParams,
+    ProviderResponse, TokenEstimator,
+};
+use async_trait::async_trait;
+use futures::StreamExt;
+use std::{sync::Arc, time::Instant};
+use tracing::Instrument;
+
+/// Downgrade a failed guardrail result to warnings if a valid override token was supplied
+fn apply_guardrail_override(
+    config: &EvaluationConfig,
+    stage: &str,
+    validation: &mut GuardrailResult,
+) -> Result<(), CliError> {
+    if validation.passed {
+        return Ok(());
+    }
+    if let (Some(controller), Some(token)) = (&config.guardrail_override, &config.override_token) {
+        controller.apply(token, stage, validation)?;
+    }
+    Ok(())
+}
+
+/// Join violations into a single error message
+fn violation_message(validation: &GuardrailResult) -> String {
+    validation
+        .violations
+        .iter()
+        .map(|v| match &v.source {
+            Some(source) => format!("{} in {source}: {}", v.id(), v.message),
+            None => format!("{}: {}", v.id(), v.message),
+        })
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+/// System prompt and page URL fetching, PDF text extraction, image OCR or
+/// attachment, user prompt wrapping, and prompt audit
+pub struct ExtractStage;
+
+#[async_trait]
+impl Stage for ExtractStage {
+    fn name(&self) -> &str {
+        stage_names::EXTRACT
+    }
+
+    async fn run(&self, ctx: &mut EvaluationContext) -> Result<Option<CliOutput>, CliError> {
+        if let Some(url) = &ctx.config.system_prompt_url {
+            let fetch_config = ctx.config.fetch.clone().unwrap_or_default();
+            let prompt = fetch_prompt(url, &fetch_config).await?;
+            log::debug!(
+                "Fetched system prompt from {} ({} bytes of {})",
+                prompt.url,
+                prompt.bytes,
+                prompt.content_type
+            );
+            ctx.config.system_prompt = prompt.markdown;
+        }
+
+        if let Some(pdf_path) = &ctx.config.pdf_input {
+            // Validate PDF file size before extraction (security protection)
+            let file_metadata = std::fs::metadata(pdf_path).map_err(|e| {
+                CliError::FileNotFound(format!(
+                    "Failed to read PDF file metadata '{}': {e}",
+                    pdf_path.display()
+                ))
+            })?;
+
+            let file_size = file_metadata.len();
+            if file_size > constants::pdf_limits::MAX_PDF_SIZE_BYTES {
+                return Ok(Some(CliOutput::error(
+                    EvaluationOutcome::FileTooLarge,
+                    format!(
+                        "PDF file size ({} bytes, {:.2} MB) exceeds maximum allowed size ({} bytes, {:.2} MB). \
+                        This limit prevents resource exhaustion from large files.",
+                        file_size,
+                        file_size as f64 / 1_048_576.0,
+                        constants::pdf_limits::MAX_PDF_SIZE_BYTES,
+                        constants::pdf_limits::MAX_PDF_SIZE_BYTES as f64 / 1_048_576.0
+                    ),
+                    ctx.metadata(),
+                )));
+            }
+
+            log::debug!(
+                "PDF file size: {} bytes ({:.2} MB)",
+                file_size,
+                file_size as f64 / 1_048_576.0
+            );
+
+            let content = extract_text_from_pdf(pdf_path).await?;
+            let char_count = content.text.len();
+            let word_count = content.text.split_whitespace().count();
+            log::debug!(
+                "Extracted {} characters ({} words) from PDF using {} (format: {:?})",
+                char_count,
+                word_count,
+                content.extractor_used,
+                content.format
+            );
+            if let Some(size) = content.file_size_bytes {
+                log::debug!(
+                    "PDF file size: {} bytes ({:.2} KB)",
+                    size,
+                    size as f64 / 1024.0
+                );
+            }
+            for warning in &content.warnings {
+                log::debug!("PDF extraction: {warning}");
+            }
+            ctx.user_prompt = content.text;
+        } else if let Some(url) = &ctx.config.url_input {
+            let fetch_config = ctx.config.fetch.clone().unwrap_or_default();
+            let page = fetch_url(url, &fetch_config).await?;
+            log::debug!(
+                "Fetched '{}' from {} ({} bytes of {})",
+                page.title.as_deref().unwrap_or("untitled"),
+                page.url,
+                page.bytes,
+                page.content_type
+            );
+            ctx.user_prompt = page.markdown;
+        }
+
+        let image_text = match ctx.config.image_input.clone() {
+            Some(image_path) => {
+                // Validate image file size before OCR or encoding (security protection)
+                let file_size = std::fs::metadata(&image_path)
+                    .map_err(|e| {
+                        CliError::FileNotFound(format!(
+                            "Failed to read image file metadata '{}': {e}",
+                            image_path.display()
+                        ))
+                    })?
+                    .len();
+                if file_size > constants::image_limits::MAX_IMAGE_SIZE_BYTES {
+                    return Ok(Some(CliOutput::error(
+                        EvaluationOutcome::FileTooLarge,
+                        format!(
+                            "Image file size ({} bytes, {:.2} MB) exceeds maximum allowed size ({} bytes, {:.2} MB). \
+                            This limit prevents resource exhaustion from large files.",
+                            file_size,
+                            file_size as f64 / 1_048_576.0,
+                            constants::image_limits::MAX_IMAGE_SIZE_BYTES,
+                            constants::image_limits::MAX_IMAGE_SIZE_BYTES as f64 / 1_048_576.0
+                        ),
+                        ctx.metadata(),
+                    )));
+                }
+
+                // Unsupported formats are rejected before OCR tools see them
+                let image = ImageInput::load(&image_path)?;
+                match ctx.config.image_mode {
+                    ImageMode::Ocr => {
+                        let content = extract_text_from_image(&image_path).await?;
+                        log::debug!(
+                            "Extracted {} characters from {} image using {}",
+                            content.text.len(),
+                            image.media_type,
+                            content.extractor_used
+                        );
+                        Some(content.text)
+                    }
+                    ImageMode::Vision => {
+                        log::debug!(
+                            "Attaching {} image ({} bytes) to the prompt",
+                            image.media_type,
+                            image.data.len()
+                        );
+                        ctx.images.push(image);
+                        if ctx.config.input_guardrails.is_some() {
+                            ctx.warn(
+                                "IMAGE_NOT_VALIDATED",
+                                "Input guardrails validate the text prompt only, not the attached image",
+                            );
+                        }
+                        None
+                    }
+                }
+            }
+            None => None,
+        };
+
+        // Segment the prompt by trust: PDF, page and image text, context files
+        // and library-supplied content are untrusted; prefix/suffix wrappers are
+        // applied verbatim around everything
+        let config = &ctx.config;
+        let body_trusted = config.pdf_input.is_none()
+            && config.url_input.is_none()
+            && !config
+                .spotlight
+                .as_ref()
+                .is_some_and(|s| s.untrusted_user_prompt);
+        let mut segments = Vec::new();
+        if let Some(prefix) = config.user_prompt_prefix.as_deref() {
+            segments.push(PromptSegment::trusted(prefix).from_source(PromptSource::Wrapper));
+        }
+        let body = std::mem::take(&mut ctx.user_prompt);
+        let has_body = !body.is_empty() || image_text.is_none();
+        if has_body {
+            segments.push(PromptSegment {
+                text: body,
+                trusted: body_trusted,
+                source: Some(if config.pdf_input.is_some() {
+                    PromptSource::Pdf
+                } else if config.url_input.is_some() {
+                    PromptSource::Url
+                } else {
+                    PromptSource::UserPrompt
+                }),
+            });
+        }
+        if let Some(text) = image_text {
+            if has_body {
+                segments.push(PromptSegment::trusted("\n\n"));
+            }
+            segments.push(PromptSegment::untrusted(text).from_source(PromptSource::Image));
+        }
+        for path in &config.context_files {
+            let text = std::fs::read_to_string(path).map_err(|e| {
+                CliError::FileNotFound(format!(
+                    "Failed to read context file '{}': {e}",
+                    path.display()
+                ))
+            })?;
+            let name = path.file_name().map_or_else(
+                || path.display().to_string(),
+                |n| n.to_string_lossy().into(),
+            );
+            segments.push(PromptSegment::trusted("\n\n"));
+            segments
+                .push(PromptSegment::untrusted(text).from_source(PromptSource::ContextFile(name)));
+        }
+        for (index, content) in config.untrusted_content.iter().enumerate() {
+            segments.push(PromptSegment::trusted("\n\n"));
+            segments.push(
+                PromptSegment::untrusted(content.as_str())
+                    .from_source(PromptSource::UntrustedContent(index)),
+            );
+        }
+        if let Some(suffix) = config.user_prompt_suffix.as_deref() {
+            segments.push(PromptSegment::trusted(suffix).from_source(PromptSource::Wrapper));
+        }
+        // Guardrails see the raw (wrapped) prompt; spotlighting comes later
+        ctx.user_prompt = render_segments(&segments, None);
+        ctx.segments = segments;
+
+        // Audit the prompts actually sent (PDF text included), before any guardrail
+        // can short-circuit; fail closed if the audit trail cannot be written
+        if let Some(auditor) = &ctx.config.prompt_audit {
+            auditor.record_prompt(
+                &ctx.config.model,
+                &ctx.config.system_prompt,
+                &ctx.user_prompt,
+            )?;
+        }
+
+        Ok(None)
+    }
+}
+
+/// Input guardrails (run AFTER PDF extraction)
+///
+/// Only user-provided content is validated, NOT the system prompt: system
+/// prompts are trusted, developer-controlled content.
+pub struct InputGuardStage;
+
+#[async_trait]
+impl Stage for InputGuardStage {
+    fn name(&self) -> &str {
+        stage_names::INPUT_GUARD
+    }
+
+    async fn run(&self, ctx: &mut EvaluationContext) -> Result<Option<CliOutput>, CliError> {
+        let Some(guardrail_config) = ctx.guardrails_within_deadline(GuardrailStage::Input) else {
+            if ctx.config.input_guardrails.is_some() {
+                log::info!("Input guardrails skipped by conditional rules or the deadline");
+                return Ok(None);
+            }
+            log::warn!(
+                "Input guardrails are disabled. User-provided content will not be validated. \
+                This may allow malicious prompts, PII, or injection attacks. \
+                Consider enabling input guardrails for production use."
+            );
+            return Ok(None);
+        };
+
+        log::info!("Running input guardrails validation");
+        let guardrail = create_metered_guardrail_provider(
+            &guardrail_config,
+            &ctx.guard_meter.for_direction("input"),
+        )?;
+
+        // Redacting guardrails (action = "redact") sanitize each segment first;
+        // validation then blocks on whatever was left (e.g. Critical matches)
+        if ctx.segments.is_empty() {
+            if let Some((text, redactions)) = guardrail.redact(&ctx.user_prompt) {
+                ctx.user_prompt = text;
+                ctx.redactions = redactions;
+            }
+        } else {
+            let mut redactions = Vec::new();
+            for segment in &mut ctx.segments {
+                if let Some((text, made)) = guardrail.redact(&segment.text) {
+                    segment.text = text;
+                    merge_redactions(&mut redactions, made);
+                }
+            }
+            if !redactions.is_empty() {
+                ctx.user_prompt = render_segments(&ctx.segments, None);
+                ctx.redactions = redactions;
+            }
+        }
+        for redaction in &ctx.redactions {
+            log::info!(
+                "Redacted {} match(es) of {} as {}",
+                redaction.count,
+                redaction.rule,
+                redaction.placeholder
+            );
+        }
+
+        // SECURITY: Only validate user input, not system prompt
+        let start = Instant::now();
+        let mut validation = match ctx.config.input_sources.clone() {
+            Some(sources) if !ctx.segments.is_empty() => {
+                validate_sources(ctx, guardrail.as_ref(), &sources).await?
+            }
+            _ => {
+                let span = guardrail_span(guardrail.name(), "input");
+                let result = guardrail
+                    .validate(&ctx.user_prompt)
+                    .instrument(span.clone())
+                    .await;
+                record_guardrail(&span, &result);
+                let mut validation = result?;
+                ctx.guardrail_results
+                    .extend(std::mem::take(&mut validation.provider_results));
+                if let Some(explanation) = validation.explanation.take() {
+                    ctx.guardrail_explanations
+                        .insert("input".to_string(), explanation);
+                }
+                validation
+            }
+        };
+        if let Some(escalation) = ctx.config.escalation.clone().filter(|_| validation.passed) {
+            let initial = NamedProviderResult {
+                provider: guardrail_config.provider_type().to_string(),
+                direction: "input".to_string(),
+                passed: true,
+                violation_rules: Vec::new(),
+                warning_rules: validation.warnings.iter().map(Violation::id).collect(),
+                latency_ms: start.elapsed().as_millis() as u64,
+                raw_response: None,
+            };
+            escalate(ctx, &escalation, initial, &mut validation).await?;
+        }
+        apply_guardrail_override(&ctx.config, "input", &mut validation)?;
+
+        if !validation.passed {
+            if let Some(metrics) = &ctx.config.metrics {
+                metrics.record_guardrail_block("input", &validation.violations);
+            }
+            ctx.violation_rules = validation.violations.iter().map(Violation::id).collect();
+            ctx.violation_previews = validation
+                .violations
+                .iter()
+                .filter_map(Violation::preview_record)
+                .collect();
+            log::error!("Input guardrails validation FAILED");
+            let error_msg = violation_message(&validation);
+            log::error!("Violations: {}", loggable(&error_msg));
+
+            return Ok(Some(CliOutput::error(
+                EvaluationOutcome::InputValidationFailed,
+                error_msg,
+                ctx.metadata(),
+            )));
+        }
+
+        log::info!("Input guardrails validation PASSED");
+
+        // Log warnings
+        for warning in validation.warnings {
+            log::warn!("{}: {}", warning.rule, loggable(&warning.message));
+        }
+        Ok(None)
+    }
+}
+
+/// Re-run an input the guardrails warned about through the escalation
+/// provider, whose verdict decides (`[guardrails.escalation]`)
+async fn escalate(
+    ctx: &mut EvaluationContext,
+    escalation: &EscalationConfig,
+    initial: NamedProviderResult,
+    validation: &mut GuardrailResult,
+) -> Result<(), CliError> {
+    let triggered_by = escalation.triggers(&validation.warnings);
+    if triggered_by.is_empty() {
+        return Ok(());
+    }
+    let Some(config) = ctx.within_deadline("escalation", escalation.provider.clone()) else {
+        return Ok(());
+    };
+    log::info!(
+        "Escalating input to {} after {}",
+        config.provider_type(),
+        triggered_by.join(", ")
+    );
+
+    let guardrail =
+        create_metered_guardrail_provider(&config, &ctx.guard_meter.for_direction("input"))?;
+    let span = guardrail_span(guardrail.name(), "input");
+    let start = Instant::now();
+    let result = guardrail
+        .validate(&ctx.user_prompt)
+        .instrument(span.clone())
+        .await;
+    record_guardrail(&span, &result);
+    let mut result = result?;
+
+    let escalated = NamedProviderResult {
+        provider: config.provider_type().to_string(),
+        direction: "input".to_string(),
+        passed: result.passed,
+        violation_rules: result.violations.iter().map(Violation::id).collect(),
+        warning_rules: result.warnings.iter().map(Violation::id).collect(),
+        latency_ms: start.elapsed().as_millis() as u64,
+        raw_response: result
+            .provider_specific
+            .as_ref()
+            .and_then(|p| p.raw_response())
+            .map(str::to_string),
+    };
+    log::info!(
+        "Escalation verdict: {}",
+        if escalated.passed { "PASSED" } else { "FAILED" }
+    );
+    validation.passed = result.passed;
+    validation.violations.append(&mut result.violations);
+    validation.warnings.append(&mut result.warnings);
+    ctx.escalation = Some(EscalationRecord {
+        triggered_by,
+        initial,
+        escalated,
+    });
+    Ok(())
+}
+
+/// Validate each enabled prompt source on its own (`[guardrails.input_sources]`)
+///
+/// Violations and warnings are labeled with their source; composite
+/// explanations are recorded as `input:<source>`.
+async fn validate_sources(
+    ctx: &mut EvaluationContext,
+    guardrail: &dyn GuardrailProvider,
+    sources: &InputSourcesConfig,
+) -> Result<GuardrailResult, CliError> {
+    let mut combined = GuardrailResult::without_quality_score(true, Vec::new(), Vec::new());
+    for segment in &ctx.segments {
+        let Some(source) = segment.source.as_ref() else {
+            continue;
+        };
+        if !sources.validates(source) || segment.text.trim().is_empty() {
+            log::debug!("Input source {source} not validated");
+            continue;
+        }
+        let label = source.to_string();
+        let span = guardrail_span(guardrail.name(), "input");
+        let result = guardrail
+            .validate(&segment.text)
+            .instrument(span.clone())
+            .await;
+        record_guardrail(&span, &result);
+        let mut validation = result?;
+
+        for finding in validation
+            .violations
+            .iter_mut()
+            .chain(validation.warnings.iter_mut())
+        {
+            finding.source = Some(label.clone());
+        }
+        combined.passed &= validation.passed;
+        combined.violations.append(&mut validation.violations);
+        combined.warnings.append(&mut validation.warnings);
+        ctx.guardrail_results
+            .append(&mut validation.provider_results);
+        if let Some(explanation) = validation.explanation {
+            ctx.guardrail_explanations
+                .insert(format!("input:{label}"), explanation);
+        }
+    }
+    Ok(combined)
+}
+
+/// Spotlighting of untrusted segments (after input guardrails saw the raw text)
+pub struct SpotlightStage;
+
+#[async_trait]
+impl Stage for SpotlightStage {
+    fn name(&self) -> &str {
+        stage_names::SPOTLIGHT
+    }
+
+    async fn run(&self, ctx: &mut EvaluationContext) -> Result<Option<CliOutput>, CliError> {
+        let Some(spotlight) = &ctx.config.spotlight else {
+            return Ok(None);
+        };
+        if ctx.segments.iter().all(|s| s.trusted) {
+            return Ok(None);
+        }
+
+        log::debug!("Spotlighting untrusted content ({})", spotlight.strategy);
+        ctx.user_prompt = render_segments(&ctx.segments, Some(spotlight));
+        let instruction = spotlight.system_instruction();
+        ctx.spotlight_applied = Some(spotlight.strategy);
+        ctx.config.system_prompt = if ctx.config.system_prompt.is_empty() {
+            instruction
+        } else {
+            format!("{}\n\n{instruction}", ctx.config.system_prompt)
+        };
+        Ok(None)
+    }
+}
+
+/// Token estimation and context limit validation
+pub struct EstimateStage;
+
+#[async_trait]
+impl Stage for EstimateStage {
+    fn name(&self) -> &str {
+        stage_names::ESTIMATE
+    }
+
+    async fn run(&self, ctx: &mut EvaluationContext) -> Result<Option<CliOutput>, CliError> {
+        // Unknown model: ask the provider (best-effort, cached by the prober)
+        if let (None, Some(prober)) = (ctx.config.context_limit, &ctx.config.context_probe) {
+            let config = &ctx.config;
+            ctx.config.context_limit = prober
+                .probe(
+                    &config.api_url,
+                    &config.model,
+                    config.provider,
+                    config.api_key.as_deref(),
+                    config.timeout_secs,
+                )
+                .await;
+        }
+
+        let config = &ctx.config;
+        // Use model-specific token estimation if model is recognized
+        // For estimation purposes only, use DEFAULT_MAX_TOKENS if not specified
+        let estimator = TokenEstimator::new_for_model(
+            &config.system_prompt,
+            &ctx.user_prompt,
+            config
+                .max_tokens
+                .unwrap_or(constants::llm_defaults::DEFAULT_MAX_TOKENS),
+            &config.model,
+        );
+        let required = estimator.total_tokens_required();
+        let context_limit = config.context_limit;
+        let warning_percent = config
+            .context_warning_percent
+            .unwrap_or(constants::llm_defaults::DEFAULT_CONTEXT_WARNING_PERCENT);
+        let validate_tokens = config.validate_tokens;
+        ctx.tokens_estimated = required;
+
+        // Too large for the context window: split the document instead
+        if let (Some(chunking), Some(limit)) = (config.chunking.clone(), context_limit) {
+            if required > limit {
+                return Ok(plan_chunks(ctx, &chunking, limit));
+            }
+        }
+
+        // Models degrade near the limit: warn even when the request still fits
+        if let (Some(limit), Some(ratio)) = (context_limit, ctx.context_usage_ratio()) {
+            if ratio * 100.0 >= f64::from(warning_percent) && required <= limit {
+                ctx.warn(
+                    "CONTEXT_HEADROOM_LOW",
+                    format!(
+                        "Estimated {required} tokens use {:.0}% of the {limit}-token context window \
+                        (warning threshold {warning_percent}%)",
+                        ratio * 100.0
+                    ),
+                );
+            }
+        }
+
+        // Even when validation is disabled, the estimate is kept for metadata
+        if !validate_tokens {
+            return Ok(None);
+        }
+        let config = &ctx.config;
+
+        // Log token breakdown
+        let breakdown = estimator.breakdown();
+        log::debug!(
+            "Token estimate: system={}, user={}, response_buffer={}, total={}",
+            breakdown.system_tokens,
+            breakdown.user_tokens,
+            breakdown.response_buffer,
+            breakdown.total_required
+        );
+
+        if let Some(limit) = config.context_limit {
+            if required > limit {
+                return Ok(Some(CliOutput::error(
+                    EvaluationOutcome::ContextLimitExceeded,
+                    format!(
+                        "Context requirement ({} tokens) exceeds model limit ({} tokens) by {} tokens",
+                        required, limit, required - limit
+                    ),
+                    ctx.metadata(),
+                )));
+            }
+        }
+        Ok(None)
+    }
+}
+
+/// Split the largest part of the user prompt into chunks that fit `limit`
+///
+/// Fills `ctx.chunk_prompts` and `ctx.chunking`, or returns a
+/// `CONTEXT_LIMIT_EXCEEDED` output when the rest of the prompt leaves no room
+/// for the document or it needs more than `max_chunks` chunks.
+fn plan_chunks(
+    ctx: &mut EvaluationContext,
+    chunking: &ChunkingConfig,
+    limit: usize,
+) -> Option<CliOutput> {
+    let mut segments = if ctx.segments.is_empty() {
+        vec![PromptSegment {
+            text: ctx.user_prompt.clone(),
+            trusted: true,
+            source: Some(PromptSource::UserPrompt),
+        }]
+    } else {
+        ctx.segments.clone()
+    };
+    let doc_index = segments
+        .iter()
+        .enumerate()
+        .max_by_key(|(_, segment)| segment.text.len())
+        .map_or(0, |(index, _)| index);
+    let document = std::mem::take(&mut segments[doc_index].text);
+    let source = segments[doc_index]
+        .source
+        .as_ref()
+        .map_or_else(|| "user_prompt".to_string(), ToString::to_string);
+
+    let config = &ctx.config;
+    let spotlight = ctx.spotlight_applied.and(config.spotlight.as_ref());
+    let max_tokens = config
+        .max_tokens
+        .unwrap_or(constants::llm_defaults::DEFAULT_MAX_TOKENS);
+    let estimate = |prompt: &str| {
+        TokenEstimator::new_for_model(&config.system_prompt, prompt, max_tokens, &config.model)
+            .total_tokens_required()
+    };
+
+    // Tokens needed by everything but the document (and, when refining, the
+    // running answer)
+    let rest = render_segments(&segments, spotlight);
+    let overhead = match chunking.strategy {
+        ChunkStrategy::MapReduce => estimate(&rest),
+        ChunkStrategy::Refine => {
+            estimate(&chunking.refine_prompt("", &rest, 0, 2)) + max_tokens as usize
+        }
+    };
+    let available = (limit.saturating_sub(overhead) as f64
+        / constants::token_estimation::SAFETY_MARGIN) as usize;
+    let chunk_tokens = chunking
+        .chunk_tokens
+        .map_or(available, |t| t.min(available));
+    let exceeded = |message: String| {
+        Some(CliOutput::error(
+            EvaluationOutcome::ContextLimitExceeded,
+            message,
+            ctx.metadata(),
+        ))
+    };
+    if chunk_tokens == 0 {
+        return exceeded(format!(
+            "Context requirement without the document ({overhead} tokens) leaves no room for \
+            chunks within the model limit ({limit} tokens)"
+        ));
+    }
+
+    // Spotlighting (e.g. base64) grows the document when rendered
+    let expansion = (ctx.user_prompt.len().saturating_sub(rest.len()) as f64
+        / document.len().max(1) as f64)
+        .max(1.0);
+    let bytes_per_token = chunking::chars_per_token(&config.model) / expansion;
+    let to_bytes = |tokens: usize| (tokens as f64 * bytes_per_token) as usize;
+    let overlap_tokens = chunking.overlap_tokens.min(chunk_tokens / 2);
+    let ranges = chunking::split(&document, to_bytes(chunk_tokens), to_bytes(overlap_tokens));
+    if ranges.len() > chunking.max_chunks {
+        return exceeded(format!(
+            "Document needs {} chunks of {chunk_tokens} tokens, more than chunking.max_chunks ({})",
+            ranges.len(),
+            chunking.max_chunks
+        ));
+    }
+
+    let mut prompts = Vec::with_capacity(ranges.len());
+    let mut chunks = Vec::with_capacity(ranges.len());
+    for (index, range) in ranges.into_iter().enumerate() {
+        segments[doc_index].text = document[range.clone()].to_string();
+        let prompt = render_segments(&segments, spotlight);
+        chunks.push(ChunkRecord {
+            index,
+            start: range.start,
+            end: range.end,
+            tokens_estimated: estimate(&prompt),
+            latency_ms: 0,
+            response_bytes: 0,
+        });
+        prompts.push(prompt);
+    }
+    log::info!(
+        "Prompt exceeds the context window ({} > {limit} tokens); {} {} chunks of {source}",
+        ctx.tokens_estimated,
+        chunking.strategy,
+        chunks.len()
+    );
+    ctx.tokens_estimated = chunks.iter().map(|c| c.tokens_estimated).max().unwrap_or(0);
+    ctx.chunk_prompts = prompts;
+    ctx.chunking = Some(ChunkingRecord {
+        strategy: chunking.strategy,
+        source,
+        chunks,
+        combine_calls: 0,
+    });
+    None
+}
+
+/// LLM invocation
+pub struct InvokeStage;
+
+#[async_trait]
+impl Stage for InvokeStage {
+    fn name(&self) -> &str {
+        stage_names::INVOKE
+    }
+
+    async fn run(&self, ctx: &mut EvaluationContext) -> Result<Option<CliOutput>, CliError> {
+        if !ctx.chunk_prompts.is_empty() {
+            let client = LlmClient::for_config(&ctx.config);
+            return match invoke_chunks(ctx, &client).await {
+                Err(error @ CliError::ContentFiltered { .. }) => {
+                    Ok(Some(content_filtered_output(ctx, error)))
+                }
+                result => result.map(|()| None),
+            };
+        }
+
+        let cache = ctx.config.response_cache.clone().filter(|cache| {
+            let accepted = cache.accepts(&invoke_params(ctx));
+            if !accepted {
+                log::info!(
+                    "Response cache bypassed: sampling is stochastic (temperature > 0, no seed)"
+                );
+            }
+            accepted
+        });
+        let cache_key = cache.as_ref().map(|_| {
+            ResponseCache::key(
+                &ctx.config.api_url,
+                ctx.config.provider,
+                &invoke_params(ctx),
+            )
+        });
+        let model_key =
+            ResponseCache::model_key(&ctx.config.api_url, ctx.config.provider, &ctx.config.model);
+        if let (Some(cache), Some(key)) = (&cache, &cache_key) {
+            let cached = cache.lookup_current(key, &model_key);
+            ctx.cache_hit = Some(cached.is_some());
+            if let Some(cached) = cached {
+                log::info!("Response served from cache");
+                ctx.response = Some(ProviderResponse {
+                    content: cached.content,
+                    upstream: cached.upstream,
+                    model_echo: cached.model_echo,
+                    tool_calls: cached.tool_calls,
+                    ..Default::default()
+                });
+                return Ok(None);
+            }
+        }
+
+        let client = LlmClient::for_config(&ctx.config);
+        let response = match client.invoke_detailed(invoke_params(ctx)).await {
+            Err(error @ CliError::ContentFiltered { .. }) => {
+                return Ok(Some(content_filtered_output(ctx, error)))
+            }
+            result => result?,
+        };
+        ctx.record_usage(&response);
+        // Generated images are not cached
+        if let Some(cache) = &cache {
+            cache.observe_build(&model_key, response.model_echo.as_ref());
+        }
+        if let (Some(cache), Some(key), true) = (&cache, &cache_key, response.images.is_empty()) {
+            cache.store(
+                key,
+                &CachedResponse {
+                    content: response.content.clone(),
+                    upstream: response.upstream.clone(),
+                    model_echo: response.model_echo.clone(),
+                    tool_calls: response.tool_calls.clone(),
+                    cached_at_ms: None,
+                },
+            );
+        }
+        ctx.response = Some(response);
+        Ok(None)
+    }
+}
+
+/// Answer each chunk and merge the answers per the chunking strategy
+///
+/// The response of the last call (final combine or refine) becomes the
+/// evaluation response.
+async fn invoke_chunks(ctx: &mut EvaluationContext, client: &LlmClient) -> Result<(), CliError> {
+    let chunking = ctx.config.chunking.clone().unwrap_or_default();
+    let prompts = std::mem::take(&mut ctx.chunk_prompts);
+    let total = prompts.len();
+
+    let response = match chunking.strategy {
+        ChunkStrategy::MapReduce => {
+            let mut answers = Vec::with_capacity(total);
+            let mut last = None;
+            for (index, prompt) in prompts.iter().enumerate() {
+                let response = invoke_chunk(ctx, client, index, prompt).await?;
+                answers.push(response.content.clone());
+                last = Some(response);
+            }
+
+            // Combine in groups that fit the context window until one answer remains
+            let limit = ctx.config.context_limit.unwrap_or(usize::MAX);
+            let system_prompt = ctx.config.system_prompt.clone();
+            let model = ctx.config.model.clone();
+            let max_tokens = ctx
+                .config
+                .max_tokens
+                .unwrap_or(constants::llm_defaults::DEFAULT_MAX_TOKENS);
+            let fits = |answers: &[String]| {
+                TokenEstimator::new_for_model(
+                    &system_prompt,
+                    &chunking.combine_prompt(answers, 0, answers.len()),
+                    max_tokens,
+                    &model,
+                )
+                .total_tokens_required()
+                    <= limit
+            };
+            while answers.len() > 1 {
+                let mut groups: Vec<(usize, usize)> = Vec::new();
+                let mut start = 0;
+                while start < answers.len() {
+                    let mut end = (start + 2).min(answers.len());
+                    while end < answers.len() && fits(&answers[start..=end]) {
+                        end += 1;
+                    }
+                    groups.push((start, end));
+                    start = end;
+                }
+                let round_total = answers.len();
+                let mut combined = Vec::with_capacity(groups.len());
+                for (start, end) in groups {
+                    let prompt = chunking.combine_prompt(&answers[start..end], start, round_total);
+                    let response = client
+                        .invoke_detailed(InvokeParams {
+                            user_prompt: &prompt,
+                            images: &[],
+                            ..invoke_params(ctx)
+                        })
+                        .await?;
+                    ctx.record_usage(&response);
+                    if let Some(record) = &mut ctx.chunking {
+                        record.combine_calls += 1;
+                    }
+                    combined.push(response.content.clone());
+                    last = Some(response);
+                }
+                answers = combined;
+            }
+            last
+        }
+        ChunkStrategy::Refine => {
+            let mut last = None;
+            for (index, prompt) in prompts.iter().enumerate() {
+                let prompt = match &last {
+                    None => prompt.clone(),
+                    Some(ProviderResponse { content, .. }) => {
+                        chunking.refine_prompt(content, prompt, index, total)
+                    }
+                };
+                last = Some(invoke_chunk(ctx, client, index, &prompt).await?);
+            }
+            last
+        }
+    };
+    ctx.response = response;
+    Ok(())
+}
+
+/// Invoke the LLM for chunk `index`, recording its latency and answer size
+async fn invoke_chunk(
+    ctx: &mut EvaluationContext,
+    client: &LlmClient,
+    index: usize,
+    prompt: &str,
+) -> Result<ProviderResponse, CliError> {
+    log::debug!("Invoking chunk {}", index + 1);
+    let start = Instant::now();
+    let response = client
+        .invoke_detailed(InvokeParams {
+            user_prompt: prompt,
+            ..invoke_params(ctx)
+        })
+        .await?;
+    ctx.record_usage(&response);
+    if let Some(record) = ctx
+        .chunking
+        .as_mut()
+        .and_then(|record| record.chunks.get_mut(index))
+    {
+        record.latency_ms = start.elapsed().as_millis() as u64;
+        record.response_bytes = response.content.len();
+    }
+    Ok(response)
+}
+
+/// Callback receiving each streamed response chunk
+pub type ChunkCallback = Arc<dyn Fn(&str) + Send + Sync>;
+
+/// Streaming LLM invocation
+///
+/// Replaces [`InvokeStage`] in [`crate::evaluate_streaming`]: chunks are
+/// passed to the callback as they arrive and accumulated into the response,
+/// so later stages (output guardrails, refusal detection) see the full text.
+pub struct StreamingInvokeStage {
+    on_chunk: ChunkCallback,
+}
+
+impl StreamingInvokeStage {
+    pub fn new(on_chunk: impl Fn(&str) + Send + Sync + 'static) -> Self {
+        Self {
+            on_chunk: Arc::new(on_chunk),
+        }
+    }
+}
+
+#[async_trait]
+impl Stage for StreamingInvokeStage {
+    fn name(&self) -> &str {
+        stage_names::INVOKE
+    }
+
+    async fn run(&self, ctx: &mut EvaluationContext) -> Result<Option<CliOutput>, CliError> {
+        if !ctx.config.tools.is_empty() {
+            return Err(CliError::InvalidArguments(
+                "Tool calling is not supported with streaming".to_string(),
+            ));
+        }
+        if !ctx.chunk_prompts.is_empty() {
+            return Err(CliError::InvalidArguments(
+                "Chunked evaluation is not supported with streaming".to_string(),
+            ));
+        }
+        let client = LlmClient::for_config(&ctx.config);
+        let mut chunks = match client.invoke_stream(invoke_params(ctx)).await {
+            Err(error @ CliError::ContentFiltered { .. }) => {
+                return Ok(Some(content_filtered_output(ctx, error)))
+            }
+            result => result?,
+        };
+
+        let size_limit = stream_size_limit(ctx);
+        let mut content = String::new();
+        let mut count = 0;
+        while let Some(chunk) = chunks.next().await {
+            let chunk = match chunk {
+                Err(error @ CliError::ContentFiltered { .. }) => {
+                    return Ok(Some(content_filtered_output(ctx, error)))
+                }
+                chunk => chunk?,
+            };
+            if let Some(max) = size_limit {
+                let size = content.len() + chunk.len();
+                if size > max {
+                    // Dropping the stream closes the connection and stops generation
+                    log::warn!(
+                        "Aborting stream after {count} chunks: response exceeds {max} bytes"
+                    );
+                    let violation = Violation {
+                        namespace: namespace::REGEX.to_string(),
+                        rule: "MAX_LENGTH".to_string(),
+                        severity: Severity::High,
+                        message: format!(
+                            "Content exceeds max length ({size} > {max} bytes); stream aborted"
+                        ),
+                        location: None,
+                        source: None,
+                        preview: None,
+                    };
+                    return Ok(Some(CliOutput::error(
+                        EvaluationOutcome::OutputValidationFailed,
+                        format!("{}: {}", violation.id(), violation.message),
+                        ctx.metadata(),
+                    )));
+                }
+            }
+            (self.on_chunk)(&chunk);
+            content.push_str(&chunk);
+            count += 1;
+        }
+        log::debug!("Streamed response in {count} chunks");
+
+        ctx.response = Some(ProviderResponse::new(content));
+        Ok(None)
+    }
+}
+
+/// `PROVIDER_CONTENT_FILTERED` output for a block by the provider's own
+/// content filter, reported alongside guardrail blocks: one
+/// `content_filter.<CATEGORY>` violation per filtered category
+fn content_filtered_output(ctx: &mut EvaluationContext, error: CliError) -> CliOutput {
+    let CliError::ContentFiltered {
+        message,
+        direction,
+        categories,
+    } = error
+    else {
+        unreachable!("content_filtered_output called with {error:?}");
+    };
+    log::error!("Provider content filter blocked the {direction}: {message}");
+
+    let rules = if categories.is_empty() {
+        vec!["FILTERED".to_string()]
+    } else {
+        categories
+            .iter()
+            .map(|category| {
+                let name = category.split(':').next().unwrap_or(category);
+                name.to_uppercase()
+            })
+            .collect()
+    };
+    let violations: Vec<Violation> = rules
+        .into_iter()
+        .map(|rule| Violation {
+            namespace: namespace::CONTENT_FILTER.to_string(),
+            rule,
+            severity: Severity::High,
+            message: message.clone(),
+            location: None,
+            source: None,
+            preview: None,
+        })
+        .collect();
+    if let Some(metrics) = &ctx.config.metrics {
+        metrics.record_guardrail_block(&direction, &violations);
+    }
+    ctx.violation_rules = violations.iter().map(Violation::id).collect();
+    ctx.guardrail_results.push(NamedProviderResult {
+        provider: namespace::CONTENT_FILTER.to_string(),
+        direction,
+        passed: false,
+        violation_rules: ctx.violation_rules.clone(),
+        warning_rules: Vec::new(),
+        latency_ms: 0,
+        raw_response: None,
+    });
+    CliOutput::error(
+        EvaluationOutcome::ProviderContentFiltered,
+        message,
+        ctx.metadata(),
+    )
+}
+
+/// Output size limit enforced while streaming
+///
+/// Disabled when an override token is supplied, since the override may waive
+/// the violation once the complete response is validated.
+fn stream_size_limit(ctx: &EvaluationContext) -> Option<usize> {
+    let config = &ctx.config;
+    if config.guardrail_override.is_some() && config.override_token.is_some() {
+        return None;
+    }
+    ctx.guardrails_for(GuardrailStage::Output)?
+        .max_length_bytes()
+}
+
+fn invoke_params(ctx: &EvaluationContext) -> InvokeParams<'_> {
+    let config = &ctx.config;
+    InvokeParams {
+        model: &config.model,
+        system_prompt: &config.system_prompt,
+        user_prompt: &ctx.user_prompt,
+        temperature: config.temperature,
+        top_p: config.top_p,
+        max_tokens: config.max_tokens,
+        seed: config.seed,
+        api_key: config.api_key.as_deref(),
+        timeout_secs: config.timeout_secs,
+        response_format: config.response_format.as_ref(),
+        tools: &config.tools,
+        images: &ctx.images,
+    }
+}
+
+/// Repetition loop detection on the response
+///
+/// Flags degenerate responses with a `REPETITION` warning. With `max_retries`
+/// set, the model is re-invoked at a higher temperature until a response is
+/// no longer repetitive; the last response is kept either way.
+pub struct RepetitionStage;
+
+#[async_trait]
+impl Stage for RepetitionStage {
+    fn name(&self) -> &str {
+        stage_names::REPETITION
+    }
+
+    async fn run(&self, ctx: &mut EvaluationContext) -> Result<Option<CliOutput>, CliError> {
+        let Some(repetition) = ctx.config.repetition_detection.clone() else {
+            return Ok(None);
+        };
+        let Some(ratio) = ctx
+            .response
+            .as_ref()
+            .and_then(|response| repetition.detect(&response.content))
+        else {
+            return Ok(None);
+        };
+
+        let n = repetition.ngram_size;
+        let mut message = format!("{:.0}% of {n}-word sequences repeat", ratio * 100.0);
+        // Retries would resend the whole document, which did not fit
+        let max_retries = if ctx.chunking.is_some() {
+            0
+        } else {
+            repetition.max_retries
+        };
+        let client = LlmClient::for_config(&ctx.config);
+        for attempt in 1..=max_retries {
+            let temperature = repetition.retry_temperature(ctx.config.temperature, attempt);
+            log::info!(
+                "Repetitive response; retrying at temperature {temperature:.2} \
+                ({attempt}/{max_retries})"
+            );
+            let params = InvokeParams {
+                temperature,
+                ..invoke_params(ctx)
+            };
+            let response = client.invoke_detailed(params).await?;
+            ctx.record_usage(&response);
+            let retry_ratio = repetition.detect(&response.content);
+            ctx.response = Some(response);
+
+            match retry_ratio {
+                Some(ratio) => {
+                    message = format!(
+                        "{:.0}% of {n}-word sequences repeat after {attempt} retries \
+                        (temperature {temperature:.2})",
+                        ratio * 100.0
+                    );
+                }
+                None => {
+                    message.push_str(&format!(
+                        "; retry {attempt} at temperature {temperature:.2} was not repetitive"
+                    ));
+                    break;
+                }
+            }
+        }
+
+        ctx.warn("REPETITION", message);
+        Ok(None)
+    }
+}
+
+/// Response post-processing hook
+///
+/// The default stage leaves the response untouched; replace it to normalize or
+/// redact responses before output guardrails see them.
+pub struct PostprocessStage;
+
+#[async_trait]
+impl Stage for PostprocessStage {
+    fn name(&self) -> &str {
+        stage_names::POSTPROCESS
+    }
+
+    async fn run(&self, _ctx: &mut EvaluationContext) -> Result<Option<CliOutput>, CliError> {
+        Ok(None)
+    }
+}
+
+/// Output guardrails
+pub struct OutputGuardStage;
+
+#[async_trait]
+impl Stage for OutputGuardStage {
+    fn name(&self) -> &str {
+        stage_names::OUTPUT_GUARD
+    }
+
+    async fn run(&self, ctx: &mut EvaluationContext) -> Result<Option<CliOutput>, CliError> {
+        let Some(guardrail_config) = ctx.guardrails_within_deadline(GuardrailStage::Output) else {
+            if ctx.config.output_guardrails.is_some() {
+                log::info!("Output guardrails skipped by conditional rules or the deadline");
+                return Ok(None);
+            }
+            log::warn!(
+                "Output guardrails are disabled. LLM responses will not be validated. \
+                This may allow unsafe, low-quality, or policy-violating content. \
+                Consider enabling output guardrails for production use."
+            );
+            return Ok(None);
+        };
+        let Some(response) = &ctx.response else {
+            return Err(CliError::InvalidArguments(
+                "Output guardrails stage requires an LLM response (missing invoke stage?)"
+                    .to_string(),
+            ));
+        };
+
+        let guardrail = create_metered_guardrail_provider(
+            &guardrail_config,
+            &ctx.guard_meter.for_direction("output"),
+        )?;
+        let span = guardrail_span(guardrail.name(), "output");
+        let result = guardrail
+            .validate_output(&response.guarded_text(), &ctx.output_context())
+            .instrument(span.clone())
+            .await;
+        record_guardrail(&span, &result);
+        let mut validation = result?;
+        apply_guardrail_override(&ctx.config, "output", &mut validation)?;
+        ctx.guardrail_results
+            .extend(std::mem::take(&mut validation.provider_results));
+        if let Some(explanation) = validation.explanation.take() {
+            ctx.guardrail_explanations
+                .insert("output".to_string(), explanation);
+        }
+        ctx.quality_score = validation.quality_score;
+
+        if !validation.passed {
+            if let Some(metrics) = &ctx.config.metrics {
+                metrics.record_guardrail_block("output", &validation.violations);
+            }
+            ctx.violation_rules = validation.violations.iter().map(Violation::id).collect();
+            ctx.violation_previews = validation
+                .violations
+                .iter()
+                .filter_map(Violation::preview_record)
+                .collect();
+            return Ok(Some(CliOutput::error(
+                EvaluationOutcome::OutputValidationFailed,
+                violation_message(&validation),
+                ctx.metadata(),
+            )));
+        }
+
+        if let Some(score) = validation.quality_score {
+            log::info!("Response quality score: {score:.1}/10");
+        }
+        // Non-blocking findings are reported with the response
+        for warning in validation.warnings {
+            let message = match &warning.location {
+                Some(location) => format!("{} ({location})", warning.message),
+                None => warning.message.clone(),
+            };
+            ctx.warn(warning.id(), message);
+        }
+        Ok(None)
+    }
+}
+
+/// Refusal detection on the response
+///
+/// Depending on `on_refusal`, a refusal adds a warning or ends the evaluation
+/// with a `REFUSAL` outcome that keeps the response text.
+pub struct RefusalStage;
+
+#[async_trait]
+impl Stage for RefusalStage {
+    fn name(&self) -> &str {
+        stage_names::REFUSAL
+    }
+
+    async fn run(&self, ctx: &mut EvaluationContext) -> Result<Option<CliOutput>, CliError> {
+        let (Some(refusal), Some(response)) = (&ctx.config.refusal_detection, &ctx.response) else {
+            return Ok(None);
+        };
+        let Some(phrase) = refusal.detect(&response.content)? else {
+            return Ok(None);
+        };
+
+        let message = format!("Model refused the request (matched: \"{phrase}\")");
+        match refusal.on_refusal {
+            RefusalAction::Warn => {
+                ctx.warn("REFUSAL_DETECTED", message);
+                Ok(None)
+            }
+            RefusalAction::Outcome => {
+                log::warn!("{}", loggable(&message));
+                Ok(Some(CliOutput::refusal(
+                    response.content.clone(),
+                    message,
+                    ctx.metadata(),
+                )))
+            }
+        }
+    }
+}
+
+/// Success output construction
+pub struct FormatStage;
+
+#[async_trait]
+impl Stage for FormatStage {
+    fn name(&self) -> &str {
+        stage_names::FORMAT
+    }
+
+    async fn run(&self, ctx: &mut EvaluationContext) -> Result<Option<CliOutput>, CliError> {
+        let metadata = ctx.metadata();
+        let response = ctx.response.take().ok_or_else(|| {
+            CliError::InvalidArguments(
+                "Format stage requires an LLM response (missing invoke stage?)".to_string(),
+            )
+        })?;
+
+        let mut output = CliOutput::success(
+            response.content,
+            metadata,
+            ctx.config.response_format.as_ref(),
+        );
+        output.tool_calls = response.tool_calls;
+        output.images = response.images.iter().map(ImageInput::data_url).collect();
+        Ok(Some(output))
+    }
+}
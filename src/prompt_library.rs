@@ -0,0 +1,614 @@
+//! Prompt library: a directory of versioned prompt templates
+//!
+//! Each `.md`, `.txt` or `.prompt` file directly in the directory is one
+//! prompt, optionally starting with TOML front matter between `+++` lines:
+//!
+//! ```text
+//! +++
+//! name = "summarize"                 # default: file stem
+//! version = "1.2.0"
+//! description = "Summarize a document for an audience"
+//! variables = ["document", "audience"]
+//! models = ["gpt-4o", "llama3.1:8b"]
+//! +++
+//! Summarize the following for {{audience}}:
+//!
+//! {{document}}
+//! ```
+//!
+//! `variables` lists the `{{placeholders}}` a caller must fill in and `models`
+//! the models the prompt was tuned for. Several files may share a name with
+//! different versions. `fortified-llm-client prompts list|show|lint --dir
+//! ./prompts` browses and checks the library.
+//!
+//! Config files reference library prompts with `system_prompt_ref` and
+//! `user_prompt_ref` (see [`PromptRef`]). The resolved prompt's hash is
+//! recorded in the output metadata ([`PromptPin`]), and a `hash` pinned in
+//! the reference is compared with the file on disk to catch prompt drift.
+
+use crate::{
+    audit::{blob_ref, BLOB_REF_PREFIX},
+    CliError,
+};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::{
+    cmp::Ordering,
+    collections::{BTreeSet, HashMap},
+    fmt,
+    path::{Path, PathBuf},
+};
+
+/// File extensions read as prompts
+pub const PROMPT_EXTENSIONS: [&str; 3] = ["md", "txt", "prompt"];
+
+const FRONT_MATTER_DELIMITER: &str = "+++";
+
+static PLACEHOLDER: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"\{\{\s*([A-Za-z_][A-Za-z0-9_]*)\s*\}\}").unwrap());
+
+static PROMPT_NAME: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^[A-Za-z0-9][A-Za-z0-9._-]*$").unwrap());
+
+/// Front matter of a prompt file
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PromptMetadata {
+    /// Prompt name (default: file stem)
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Placeholders the caller must fill in
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub variables: Vec<String>,
+    /// Models the prompt was written and tested for
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub models: Vec<String>,
+}
+
+/// A prompt read from the library
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PromptTemplate {
+    #[serde(flatten)]
+    pub metadata: PromptMetadata,
+    pub file: PathBuf,
+    /// Blob reference (`sha256:<hex>`) of the prompt text
+    pub hash: String,
+    /// Whether the file has front matter
+    #[serde(skip)]
+    pub has_front_matter: bool,
+    /// Prompt text after the front matter
+    pub text: String,
+}
+
+impl PromptTemplate {
+    /// Parse a prompt file's content
+    ///
+    /// # Errors
+    ///
+    /// Returns a description of the problem when the front matter is not
+    /// closed or not valid TOML.
+    pub fn parse(file: &Path, content: &str) -> Result<Self, String> {
+        let (mut metadata, text, has_front_matter) = match split_front_matter(content)? {
+            Some((front_matter, text)) => {
+                let metadata: PromptMetadata = toml::from_str(front_matter)
+                    .map_err(|e| format!("invalid front matter: {}", e.message()))?;
+                (metadata, text, true)
+            }
+            None => (PromptMetadata::default(), content, false),
+        };
+        if metadata.name.is_empty() {
+            metadata.name = file
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().into_owned())
+                .unwrap_or_default();
+        }
+        Ok(Self {
+            metadata,
+            file: file.to_path_buf(),
+            hash: blob_ref(text.as_bytes()),
+            has_front_matter,
+            text: text.to_string(),
+        })
+    }
+
+    /// `{{placeholders}}` used in the text, sorted and deduplicated
+    pub fn placeholders(&self) -> BTreeSet<&str> {
+        PLACEHOLDER
+            .captures_iter(&self.text)
+            .filter_map(|captures| captures.get(1))
+            .map(|name| name.as_str())
+            .collect()
+    }
+}
+
+/// Front matter and text, or None without front matter
+fn split_front_matter(content: &str) -> Result<Option<(&str, &str)>, String> {
+    let content = content.strip_prefix('\u{feff}').unwrap_or(content);
+    let Some(rest) = content
+        .strip_prefix(FRONT_MATTER_DELIMITER)
+        .and_then(|rest| {
+            rest.strip_prefix('\n')
+                .or_else(|| rest.strip_prefix("\r\n"))
+        })
+    else {
+        return Ok(None);
+    };
+    let mut offset = 0;
+    for line in rest.split_inclusive('\n') {
+        if line.trim_end() == FRONT_MATTER_DELIMITER {
+            return Ok(Some((&rest[..offset], &rest[offset + line.len()..])));
+        }
+        offset += line.len();
+    }
+    Err(format!(
+        "front matter is not closed by a '{FRONT_MATTER_DELIMITER}' line"
+    ))
+}
+
+/// Compare versions numerically by dot-separated part (`1.10` > `1.9`),
+/// falling back to text for non-numeric parts
+pub fn compare_versions(a: &str, b: &str) -> Ordering {
+    let mut a_parts = a.trim_start_matches('v').split('.');
+    let mut b_parts = b.trim_start_matches('v').split('.');
+    loop {
+        match (a_parts.next(), b_parts.next()) {
+            (None, None) => return Ordering::Equal,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(a), Some(b)) => {
+                let ordering = match (a.parse::<u64>(), b.parse::<u64>()) {
+                    (Ok(a), Ok(b)) => a.cmp(&b),
+                    _ => a.cmp(b),
+                };
+                if ordering != Ordering::Equal {
+                    return ordering;
+                }
+            }
+        }
+    }
+}
+
+/// Problem found by [`PromptLibrary::lint`]
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LintIssue {
+    pub file: PathBuf,
+    /// `error` or `warning`
+    pub level: &'static str,
+    pub message: String,
+}
+
+/// Result of [`PromptLibrary::lint`]
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct LintReport {
+    pub prompts_checked: usize,
+    pub errors: usize,
+    pub warnings: usize,
+    pub issues: Vec<LintIssue>,
+}
+
+impl LintReport {
+    fn push(&mut self, file: &Path, level: &'static str, message: String) {
+        match level {
+            "error" => self.errors += 1,
+            _ => self.warnings += 1,
+        }
+        self.issues.push(LintIssue {
+            file: file.to_path_buf(),
+            level,
+            message,
+        });
+    }
+}
+
+/// A prompt file and its parsed prompt, or the problem reading it
+type PromptFile = (PathBuf, Result<PromptTemplate, String>);
+
+/// `[prompts]` config file section
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PromptLibraryConfig {
+    /// Prompt directory (relative to the working directory)
+    pub dir: PathBuf,
+}
+
+/// Config file reference to a library prompt
+///
+/// Written as `"name"`, `"name@version"` or a table with `name`, `version`
+/// and the `hash` (`sha256:<hex>`) the prompt text is expected to have.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "PromptRefSpec")]
+pub struct PromptRef {
+    pub name: String,
+    /// Version to use (None = latest)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    /// Expected hash of the prompt text
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hash: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum PromptRefSpec {
+    Short(String),
+    Full {
+        name: String,
+        #[serde(default)]
+        version: Option<String>,
+        #[serde(default)]
+        hash: Option<String>,
+    },
+}
+
+impl TryFrom<PromptRefSpec> for PromptRef {
+    type Error = String;
+
+    fn try_from(spec: PromptRefSpec) -> Result<Self, Self::Error> {
+        let prompt_ref = match spec {
+            PromptRefSpec::Short(spec) => match spec.split_once('@') {
+                Some((name, version)) => Self {
+                    name: name.to_string(),
+                    version: Some(version.to_string()),
+                    hash: None,
+                },
+                None => Self {
+                    name: spec,
+                    version: None,
+                    hash: None,
+                },
+            },
+            PromptRefSpec::Full {
+                name,
+                version,
+                hash,
+            } => Self {
+                name,
+                version,
+                hash,
+            },
+        };
+        if prompt_ref.name.is_empty() || prompt_ref.version.as_deref() == Some("") {
+            return Err("prompt reference needs a name and, after '@', a version".to_string());
+        }
+        if let Some(hash) = &prompt_ref.hash {
+            if !hash.starts_with(BLOB_REF_PREFIX) {
+                return Err(format!(
+                    "prompt reference hash '{hash}' must start with '{BLOB_REF_PREFIX}'"
+                ));
+            }
+        }
+        Ok(prompt_ref)
+    }
+}
+
+impl fmt::Display for PromptRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.version {
+            Some(version) => write!(f, "{}@{version}", self.name),
+            None => write!(f, "{}", self.name),
+        }
+    }
+}
+
+/// Library prompt a config reference resolved to, recorded in the metadata
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PromptPin {
+    /// `system` or `user`
+    pub role: &'static str,
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    pub file: PathBuf,
+    /// Hash of the prompt text that was used
+    pub hash: String,
+    /// Hash pinned in the reference, when it no longer matches `hash`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pinned_hash: Option<String>,
+}
+
+impl PromptPin {
+    /// Whether the prompt changed since its hash was pinned
+    pub fn drifted(&self) -> bool {
+        self.pinned_hash.is_some()
+    }
+}
+
+/// A prompt directory
+#[derive(Debug, Clone)]
+pub struct PromptLibrary {
+    dir: PathBuf,
+}
+
+impl PromptLibrary {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Prompt files in the directory, sorted by path
+    fn files(&self) -> Result<Vec<PathBuf>, CliError> {
+        let entries = std::fs::read_dir(&self.dir).map_err(|e| {
+            CliError::FileNotFound(format!(
+                "Failed to read prompt directory '{}': {e}",
+                self.dir.display()
+            ))
+        })?;
+        let mut files: Vec<PathBuf> = entries
+            .filter_map(Result::ok)
+            .map(|entry| entry.path())
+            .filter(|path| path.is_file())
+            .filter(|path| {
+                path.extension()
+                    .and_then(|ext| ext.to_str())
+                    .is_some_and(|ext| PROMPT_EXTENSIONS.contains(&ext))
+            })
+            .collect();
+        files.sort();
+        Ok(files)
+    }
+
+    /// Read every prompt file, with the problem for files that can't be parsed
+    fn read_all(&self) -> Result<Vec<PromptFile>, CliError> {
+        Ok(self
+            .files()?
+            .into_iter()
+            .map(|file| {
+                let prompt = std::fs::read_to_string(&file)
+                    .map_err(|e| format!("failed to read file: {e}"))
+                    .and_then(|content| PromptTemplate::parse(&file, &content));
+                (file, prompt)
+            })
+            .collect())
+    }
+
+    /// Every readable prompt, sorted by name then version (files that can't
+    /// be parsed are skipped with a warning; `lint` reports them)
+    pub fn list(&self) -> Result<Vec<PromptTemplate>, CliError> {
+        let mut prompts: Vec<PromptTemplate> = self
+            .read_all()?
+            .into_iter()
+            .filter_map(|(file, prompt)| {
+                prompt
+                    .map_err(|e| log::warn!("Skipping prompt file {}: {e}", file.display()))
+                    .ok()
+            })
+            .collect();
+        prompts.sort_by(|a, b| {
+            a.metadata.name.cmp(&b.metadata.name).then_with(|| {
+                compare_versions(
+                    a.metadata.version.as_deref().unwrap_or_default(),
+                    b.metadata.version.as_deref().unwrap_or_default(),
+                )
+            })
+        });
+        Ok(prompts)
+    }
+
+    /// The prompt named `name`, at `version` or else the latest version
+    ///
+    /// # Errors
+    ///
+    /// `InvalidArguments` when no prompt matches, listing the available
+    /// versions of the name.
+    pub fn get(&self, name: &str, version: Option<&str>) -> Result<PromptTemplate, CliError> {
+        let mut versions: Vec<PromptTemplate> = self
+            .list()?
+            .into_iter()
+            .filter(|prompt| prompt.metadata.name == name)
+            .collect();
+        if versions.is_empty() {
+            return Err(CliError::InvalidArguments(format!(
+                "No prompt named '{name}' in {}",
+                self.dir.display()
+            )));
+        }
+        let Some(version) = version else {
+            // Sorted by version, latest last
+            return Ok(versions.pop().expect("not empty"));
+        };
+        let available = versions
+            .iter()
+            .map(|prompt| prompt.metadata.version.as_deref().unwrap_or("(none)"))
+            .collect::<Vec<_>>()
+            .join(", ");
+        versions
+            .into_iter()
+            .find(|prompt| prompt.metadata.version.as_deref() == Some(version))
+            .ok_or_else(|| {
+                CliError::InvalidArguments(format!(
+                    "No version '{version}' of prompt '{name}' (available: {available})"
+                ))
+            })
+    }
+
+    /// Resolve a config reference for the `role` prompt
+    ///
+    /// The pin's `pinned_hash` is set when the reference pins a hash the
+    /// prompt text no longer has.
+    ///
+    /// # Errors
+    ///
+    /// `InvalidArguments` when no prompt matches or the prompt declares
+    /// variables, which a reference can't fill in.
+    pub fn resolve(
+        &self,
+        role: &'static str,
+        prompt_ref: &PromptRef,
+    ) -> Result<(PromptTemplate, PromptPin), CliError> {
+        let prompt = self.get(&prompt_ref.name, prompt_ref.version.as_deref())?;
+        let placeholders = prompt.placeholders();
+        if !prompt.metadata.variables.is_empty() || !placeholders.is_empty() {
+            return Err(CliError::InvalidArguments(format!(
+                "Prompt '{prompt_ref}' ({}) declares variables, which {role}_prompt_ref can't fill in",
+                prompt.file.display()
+            )));
+        }
+        let pin = PromptPin {
+            role,
+            name: prompt.metadata.name.clone(),
+            version: prompt.metadata.version.clone(),
+            file: prompt.file.clone(),
+            hash: prompt.hash.clone(),
+            pinned_hash: prompt_ref
+                .hash
+                .clone()
+                .filter(|pinned| *pinned != prompt.hash),
+        };
+        Ok((prompt, pin))
+    }
+
+    /// Check every prompt file
+    ///
+    /// Errors: unreadable files, invalid front matter, invalid names,
+    /// duplicate name and version pairs, empty text and placeholders missing
+    /// from `variables`. Warnings: no front matter or version, and declared
+    /// variables the text never uses.
+    pub fn lint(&self) -> Result<LintReport, CliError> {
+        let mut report = LintReport::default();
+        let mut seen: HashMap<(String, Option<String>), PathBuf> = HashMap::new();
+
+        for (file, prompt) in self.read_all()? {
+            report.prompts_checked += 1;
+            let prompt = match prompt {
+                Ok(prompt) => prompt,
+                Err(e) => {
+                    report.push(&file, "error", e);
+                    continue;
+                }
+            };
+            let metadata = &prompt.metadata;
+
+            if !PROMPT_NAME.is_match(&metadata.name) {
+                report.push(
+                    &file,
+                    "error",
+                    format!(
+                        "invalid name '{}' (letters, digits, '.', '_' and '-' only)",
+                        metadata.name
+                    ),
+                );
+            }
+            let key = (metadata.name.clone(), metadata.version.clone());
+            if let Some(first) = seen.get(&key) {
+                report.push(
+                    &file,
+                    "error",
+                    format!(
+                        "duplicate prompt '{}' version {} (also in {})",
+                        metadata.name,
+                        metadata.version.as_deref().unwrap_or("(none)"),
+                        first.display()
+                    ),
+                );
+            } else {
+                seen.insert(key, file.clone());
+            }
+            if prompt.text.trim().is_empty() {
+                report.push(&file, "error", "prompt text is empty".to_string());
+            }
+
+            let placeholders = prompt.placeholders();
+            for placeholder in &placeholders {
+                if !metadata.variables.iter().any(|v| v == placeholder) {
+                    report.push(
+                        &file,
+                        "error",
+                        format!("placeholder '{{{{{placeholder}}}}}' is not listed in variables"),
+                    );
+                }
+            }
+            for variable in &metadata.variables {
+                if !placeholders.contains(variable.as_str()) {
+                    report.push(
+                        &file,
+                        "warning",
+                        format!("variable '{variable}' is not used in the text"),
+                    );
+                }
+            }
+
+            if !prompt.has_front_matter {
+                report.push(
+                    &file,
+                    "warning",
+                    "no front matter (name defaults to the file stem)".to_string(),
+                );
+            } else if metadata.version.is_none() {
+                report.push(&file, "warning", "no version".to_string());
+            }
+        }
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_front_matter() {
+        let prompt = PromptTemplate::parse(
+            Path::new("prompts/summary.md"),
+            "+++\nname = \"summarize\"\nversion = \"1.0\"\nvariables = [\"doc\"]\n+++\nSummarize {{ doc }}\n",
+        )
+        .unwrap();
+        assert_eq!(prompt.metadata.name, "summarize");
+        assert_eq!(prompt.metadata.version.as_deref(), Some("1.0"));
+        assert_eq!(prompt.text, "Summarize {{ doc }}\n");
+        assert_eq!(
+            prompt.placeholders().into_iter().collect::<Vec<_>>(),
+            ["doc"]
+        );
+        assert_eq!(prompt.hash, blob_ref(b"Summarize {{ doc }}\n"));
+
+        let plain = PromptTemplate::parse(Path::new("greet.txt"), "Hello").unwrap();
+        assert_eq!(plain.metadata.name, "greet");
+        assert!(!plain.has_front_matter);
+
+        let err = PromptTemplate::parse(Path::new("a.md"), "+++\nname = \"a\"\nText").unwrap_err();
+        assert!(err.contains("not closed"), "{err}");
+        let err = PromptTemplate::parse(Path::new("a.md"), "+++\nauthor = \"me\"\n+++\nText")
+            .unwrap_err();
+        assert!(err.contains("invalid front matter"), "{err}");
+    }
+
+    #[test]
+    fn test_compare_versions() {
+        assert_eq!(compare_versions("1.10", "1.9"), Ordering::Greater);
+        assert_eq!(compare_versions("v2", "1.0.3"), Ordering::Greater);
+        assert_eq!(compare_versions("1.0", "1.0.1"), Ordering::Less);
+        assert_eq!(compare_versions("1.0", "1.0"), Ordering::Equal);
+    }
+
+    #[test]
+    fn test_prompt_ref_forms() {
+        #[derive(Deserialize)]
+        struct Refs {
+            a: PromptRef,
+            b: PromptRef,
+            c: PromptRef,
+        }
+        let refs: Refs = toml::from_str(
+            "a = \"reviewer\"\nb = \"reviewer@1.2\"\nc = { name = \"reviewer\", hash = \"sha256:ab\" }",
+        )
+        .unwrap();
+        assert_eq!(refs.a.to_string(), "reviewer");
+        assert_eq!(refs.b.version.as_deref(), Some("1.2"));
+        assert_eq!(refs.b.to_string(), "reviewer@1.2");
+        assert_eq!(refs.c.hash.as_deref(), Some("sha256:ab"));
+
+        assert!(toml::from_str::<Refs>("a = \"x@\"\nb = \"x\"\nc = \"x\"").is_err());
+        assert!(toml::from_str::<Refs>(
+            "a = { name = \"x\", hash = \"md5:1\" }\nb = \"x\"\nc = \"x\""
+        )
+        .is_err());
+    }
+}
@@ -1,5 +1,12 @@
-use crate::{error::CliError, models::ResponseFormat};
+use crate::{
+    error::CliError,
+    image::ImageInput,
+    models::{ResponseFormat, ToolCall, ToolDefinition},
+};
 use async_trait::async_trait;
+use futures::stream::{self, Stream};
+use serde::{Deserialize, Serialize};
+use std::{borrow::Cow, pin::Pin};
 
 /// Parameters for LLM invocation
 ///
@@ -18,11 +25,14 @@ use async_trait::async_trait;
 ///     system_prompt: "You are a helpful assistant.",
 ///     user_prompt: "What is 2+2?",
 ///     temperature: 0.7,
+///     top_p: None,
 ///     max_tokens: Some(100),
 ///     seed: Some(42),
 ///     api_key: Some("sk-..."),
 ///     timeout_secs: 30,
 ///     response_format: None,
+///     tools: &[],
+///     images: &[],
 /// };
 /// ```
 #[derive(Debug, Clone)]
@@ -39,6 +49,9 @@ pub struct InvokeParams<'a> {
     /// Sampling temperature (0.0 = deterministic, 2.0 = maximum randomness)
     pub temperature: f32,
 
+    /// Nucleus sampling probability mass (None = provider default)
+    pub top_p: Option<f32>,
+
     /// Maximum tokens to generate in response (None = use model's maximum)
     pub max_tokens: Option<u32>,
 
@@ -53,6 +66,154 @@ pub struct InvokeParams<'a> {
 
     /// Optional response format constraint (OpenAI-compatible only)
     pub response_format: Option<&'a ResponseFormat>,
+
+    /// Functions the model may call (OpenAI-compatible and Ollama only)
+    pub tools: &'a [ToolDefinition],
+
+    /// Images sent with the user prompt (vision models; OpenAI-compatible and Ollama only)
+    pub images: &'a [ImageInput],
+}
+
+/// Provider response with transport details surfaced into metadata
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ProviderResponse {
+    /// Generated text
+    pub content: String,
+
+    /// Number of NDJSON chunks aggregated when a streaming body was returned
+    /// for a non-streaming request (None = regular single-object body)
+    pub aggregated_chunks: Option<usize>,
+
+    /// Serving details reported by an aggregating gateway (e.g. OpenRouter)
+    pub upstream: Option<UpstreamInfo>,
+
+    /// Serving parameters confirmed by the provider
+    pub model_echo: Option<ModelEcho>,
+
+    /// Token counts reported by the provider (None = not reported)
+    pub usage: Option<TokenUsage>,
+
+    /// Function calls requested by the model (`content` may then be empty)
+    pub tool_calls: Vec<ToolCall>,
+
+    /// Images generated by the model (`content` may then be empty)
+    pub images: Vec<ImageInput>,
+}
+
+impl ProviderResponse {
+    pub fn new(content: impl Into<String>) -> Self {
+        Self {
+            content: content.into(),
+            ..Default::default()
+        }
+    }
+
+    /// Text checked by output guardrails: the content followed by one
+    /// `name(arguments)` line per tool call
+    pub fn guarded_text(&self) -> Cow<'_, str> {
+        if self.tool_calls.is_empty() {
+            return Cow::Borrowed(&self.content);
+        }
+        let mut text = self.content.clone();
+        for call in &self.tool_calls {
+            if !text.is_empty() {
+                text.push('\n');
+            }
+            text.push_str(&format!("{}({})", call.name, call.arguments));
+        }
+        Cow::Owned(text)
+    }
+}
+
+/// Stream of response text chunks, in generation order
+pub type ChunkStream = Pin<Box<dyn Stream<Item = Result<String, CliError>> + Send>>;
+
+/// Attribution of a response routed through a gateway to the model that
+/// actually served it
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct UpstreamInfo {
+    /// Upstream provider selected by the gateway (e.g. "Together")
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub provider: Option<String>,
+
+    /// Model id reported in the response body
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+
+    /// Normalized finish reason
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub finish_reason: Option<String>,
+
+    /// Finish reason as reported by the upstream provider
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub native_finish_reason: Option<String>,
+}
+
+/// Token counts reported by the provider for one call
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TokenUsage {
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+}
+
+impl TokenUsage {
+    pub fn new(prompt_tokens: u64, completion_tokens: u64) -> Self {
+        Self {
+            prompt_tokens,
+            completion_tokens,
+        }
+    }
+
+    pub fn total_tokens(&self) -> u64 {
+        self.prompt_tokens + self.completion_tokens
+    }
+
+    /// Add the counts of another call
+    pub fn add(&mut self, other: TokenUsage) {
+        self.prompt_tokens += other.prompt_tokens;
+        self.completion_tokens += other.completion_tokens;
+    }
+}
+
+/// Serving parameters confirmed by the provider, for attributing behavior
+/// shifts to silent backend model updates
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ModelEcho {
+    /// Model version that served the request (e.g. "gpt-4o-2024-08-06")
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+
+    /// Backend configuration fingerprint (OpenAI `system_fingerprint`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub system_fingerprint: Option<String>,
+
+    /// Service tier that processed the request (OpenAI `service_tier`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub service_tier: Option<String>,
+
+    /// Digest of the served model weights (Ollama)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model_digest: Option<String>,
+}
+
+impl ModelEcho {
+    /// None when the provider confirmed nothing
+    pub fn non_empty(self) -> Option<Self> {
+        (self != Self::default()).then_some(self)
+    }
+
+    /// Identity of the model build (served version, fingerprint and digest),
+    /// None when the provider reported none of them
+    pub fn build_id(&self) -> Option<String> {
+        let parts: Vec<&str> = [&self.model, &self.system_fingerprint, &self.model_digest]
+            .into_iter()
+            .map(|part| part.as_deref().unwrap_or(""))
+            .collect();
+        parts
+            .iter()
+            .any(|part| !part.is_empty())
+            .then(|| parts.join("|"))
+    }
 }
 
 /// LLM provider trait for extensibility
@@ -83,6 +244,8 @@ pub struct InvokeParams<'a> {
 ///     api_key: Some("sk-..."),
 ///     timeout_secs: 30,
 ///     response_format: None,
+///     tools: &[],
+///     images: &[],
 /// };
 ///
 /// let response = provider.invoke(params).await?;
@@ -100,6 +263,28 @@ pub trait LlmProvider: Send + Sync {
     /// The LLM's response as a string, or an error if the invocation failed
     async fn invoke(&self, params: InvokeParams<'_>) -> Result<String, CliError>;
 
+    /// Invoke the LLM and return the response with transport details
+    ///
+    /// Default implementation wraps `invoke()`. Providers that can report
+    /// more (e.g. aggregated streaming chunks) override this and implement
+    /// `invoke()` in terms of it.
+    async fn invoke_detailed(
+        &self,
+        params: InvokeParams<'_>,
+    ) -> Result<ProviderResponse, CliError> {
+        self.invoke(params).await.map(ProviderResponse::new)
+    }
+
+    /// Invoke the LLM and yield the response as it is generated
+    ///
+    /// Default implementation yields the full `invoke()` response as a single
+    /// chunk. Providers that support streaming override this together with
+    /// `supports_streaming()`.
+    async fn invoke_stream(&self, params: InvokeParams<'_>) -> Result<ChunkStream, CliError> {
+        let content = self.invoke(params).await?;
+        Ok(Box::pin(stream::once(async move { Ok(content) })))
+    }
+
     /// Get provider name for logging and debugging
     fn name(&self) -> &str;
 
@@ -121,10 +306,48 @@ pub trait LlmProvider: Send + Sync {
 ///
 /// - `Ollama` - For Ollama /api/generate format (local servers)
 /// - `OpenAI` - For OpenAI-compatible /v1/chat/completions format
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// - `Anthropic` - For the Anthropic Messages API (/v1/messages)
+/// - `Bedrock` - For the Amazon Bedrock Converse API (SigV4-signed)
+/// - `Mock` - Offline canned responses (API URL = fixtures path)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum ProviderType {
     /// Ollama /api/generate format (local servers)
     Ollama,
     /// OpenAI-compatible /v1/chat/completions format
     OpenAI,
+    /// Anthropic Messages API /v1/messages format
+    Anthropic,
+    /// Amazon Bedrock Converse API (/model/{model}/converse, SigV4-signed)
+    Bedrock,
+    /// Offline mock responses from a fixtures file (no network)
+    Mock,
+}
+
+impl ProviderType {
+    /// Names accepted by `--provider` and the `provider` config field
+    pub const NAMES: &'static [&'static str] =
+        &["ollama", "openai", "anthropic", "bedrock", "mock"];
+
+    /// Name as written in `--provider` and config files
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ProviderType::Ollama => "ollama",
+            ProviderType::OpenAI => "openai",
+            ProviderType::Anthropic => "anthropic",
+            ProviderType::Bedrock => "bedrock",
+            ProviderType::Mock => "mock",
+        }
+    }
+
+    /// Provider of a name in [`Self::NAMES`] (case-insensitive)
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "ollama" => Some(ProviderType::Ollama),
+            "openai" => Some(ProviderType::OpenAI),
+            "anthropic" => Some(ProviderType::Anthropic),
+            "bedrock" => Some(ProviderType::Bedrock),
+            "mock" => Some(ProviderType::Mock),
+            _ => None,
+        }
+    }
 }
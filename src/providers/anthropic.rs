@@ -0,0 +1,275 @@
+use crate::{
+    cassette,
+    constants::llm_defaults,
+    error::CliError,
+    models::{
+        AnthropicErrorBody, AnthropicRequest, AnthropicResponse, AnthropicStreamEvent, Message,
+    },
+    provider::{ChunkStream, InvokeParams, LlmProvider, ModelEcho, ProviderResponse, TokenUsage},
+};
+use async_trait::async_trait;
+use reqwest::{Client, RequestBuilder, StatusCode};
+
+use super::{
+    logging::{log_request, log_response},
+    rate_limit::rate_limited,
+    streaming::{line_stream, StreamLine},
+};
+
+/// Messages API version sent in the `anthropic-version` header
+pub const ANTHROPIC_VERSION: &str = "2023-06-01";
+
+/// Provider for the Anthropic Messages API (/v1/messages)
+pub struct AnthropicProvider {
+    client: Client,
+    api_url: String,
+}
+
+impl AnthropicProvider {
+    pub fn new(api_url: String) -> Self {
+        Self {
+            client: Client::new(),
+            api_url,
+        }
+    }
+
+    /// Build the Messages request (`stream` asks for SSE events)
+    fn request(&self, params: &InvokeParams<'_>, stream: bool) -> Result<RequestBuilder, CliError> {
+        // Note: the Messages API has no seed or response_format parameter
+        if params.seed.is_some() {
+            log::debug!("Anthropic does not support seed; ignoring it");
+        }
+        if params.response_format.is_some() {
+            log::warn!(
+                "Anthropic does not support response_format; the schema is only \
+                enforced by output validation"
+            );
+        }
+        if !params.tools.is_empty() {
+            log::warn!("Anthropic tool calling is not supported; ignoring tools");
+        }
+        // Unlike tools, images cannot be dropped: the answer would be made up
+        if !params.images.is_empty() {
+            return Err(CliError::InvalidArguments(
+                "Anthropic provider does not support image input; use image_mode = \"ocr\""
+                    .to_string(),
+            ));
+        }
+
+        let request = AnthropicRequest {
+            model: params.model.to_string(),
+            // max_tokens is required by the Messages API
+            max_tokens: params
+                .max_tokens
+                .unwrap_or(llm_defaults::DEFAULT_MAX_TOKENS),
+            // The system prompt is a top-level field, not a message
+            system: params.system_prompt.to_string(),
+            messages: vec![Message {
+                role: "user".to_string(),
+                content: params.user_prompt.to_string(),
+            }],
+            temperature: params.temperature,
+            top_p: params.top_p,
+            stream: stream.then_some(true),
+        };
+
+        log_request(&request);
+
+        let mut req = self
+            .client
+            .post(&self.api_url)
+            .header("anthropic-version", ANTHROPIC_VERSION)
+            .json(&request)
+            .timeout(std::time::Duration::from_secs(params.timeout_secs));
+
+        if let Some(key) = params.api_key {
+            req = req.header("x-api-key", key);
+            log::debug!("x-api-key header: [REDACTED]");
+        }
+        Ok(req)
+    }
+}
+
+#[async_trait]
+impl LlmProvider for AnthropicProvider {
+    async fn invoke(&self, params: InvokeParams<'_>) -> Result<String, CliError> {
+        self.invoke_detailed(params).await.map(|r| r.content)
+    }
+
+    async fn invoke_detailed(
+        &self,
+        params: InvokeParams<'_>,
+    ) -> Result<ProviderResponse, CliError> {
+        let req = self.request(&params, false)?;
+        let response = cassette::send(req, params.api_key).await?;
+
+        if !response.status.is_success() {
+            return Err(
+                rate_limited(response.status, response.retry_after, &response.body)
+                    .unwrap_or_else(|| http_error(response.status, &response.body)),
+            );
+        }
+
+        let response_text = response.body;
+        log_response(&response_text);
+
+        let anthropic_response: AnthropicResponse = serde_json::from_str(&response_text)
+            .map_err(|e| CliError::InvalidResponse(format!("Failed to parse response: {e}")))?;
+
+        // Concatenate text blocks (tool_use and other block types are skipped)
+        let texts: Vec<String> = anthropic_response
+            .content
+            .into_iter()
+            .filter(|block| block.block_type == "text")
+            .filter_map(|block| block.text)
+            .collect();
+        if texts.is_empty() {
+            return Err(CliError::InvalidResponse(format!(
+                "No text content in response (stop_reason: {})",
+                anthropic_response
+                    .stop_reason
+                    .as_deref()
+                    .unwrap_or("unknown")
+            )));
+        }
+
+        Ok(ProviderResponse {
+            content: texts.concat(),
+            model_echo: ModelEcho {
+                model: anthropic_response.model,
+                ..Default::default()
+            }
+            .non_empty(),
+            usage: anthropic_response
+                .usage
+                .map(|u| TokenUsage::new(u.input_tokens, u.output_tokens)),
+            ..Default::default()
+        })
+    }
+
+    async fn invoke_stream(&self, params: InvokeParams<'_>) -> Result<ChunkStream, CliError> {
+        let req = self.request(&params, true)?;
+        let reply = cassette::send_streaming(req, params.api_key).await?;
+
+        if !reply.status().is_success() {
+            let (status, retry_after) = (reply.status(), reply.retry_after());
+            let body = reply.text().await?;
+            return Err(rate_limited(status, retry_after, &body)
+                .unwrap_or_else(|| http_error(status, &body)));
+        }
+        Ok(line_stream(reply, decode_sse_line))
+    }
+
+    fn name(&self) -> &str {
+        "Anthropic"
+    }
+
+    fn supports_streaming(&self) -> bool {
+        true
+    }
+}
+
+/// Map a non-success HTTP status to an error, using the API's error message
+fn http_error(status: StatusCode, body: &str) -> CliError {
+    if status == 401 {
+        return CliError::AuthenticationFailed("Invalid or missing API key".to_string());
+    }
+
+    let details = match serde_json::from_str::<AnthropicErrorBody>(body) {
+        Ok(parsed) => format!("{}: {}", parsed.error.error_type, parsed.error.message),
+        Err(_) if body.is_empty() => "No details provided".to_string(),
+        Err(_) => body.to_string(),
+    };
+    CliError::InvalidResponse(format!(
+        "HTTP {} error: {}\nResponse from API: {details}",
+        status.as_u16(),
+        status.canonical_reason().unwrap_or("Unknown error"),
+    ))
+}
+
+/// Decode one line of a Messages API server-sent events body
+///
+/// Only `content_block_delta` events carry text; `message_stop` ends the
+/// stream and `error` events (e.g. `overloaded_error`) fail it.
+fn decode_sse_line(line: &str) -> Result<StreamLine, CliError> {
+    let Some(data) = line.strip_prefix("data:") else {
+        // "event: <type>" lines duplicate the data's type field
+        return Ok(StreamLine::Skip);
+    };
+
+    let event: AnthropicStreamEvent = serde_json::from_str(data.trim())
+        .map_err(|e| CliError::InvalidResponse(format!("Failed to parse streaming event: {e}")))?;
+    match event.event_type.as_str() {
+        "content_block_delta" => Ok(StreamLine::Text(
+            event.delta.and_then(|d| d.text).unwrap_or_default(),
+        )),
+        "message_stop" => Ok(StreamLine::Done),
+        "error" => {
+            let error = event
+                .error
+                .map_or_else(String::new, |e| format!("{}: {}", e.error_type, e.message));
+            Err(CliError::InvalidResponse(format!(
+                "Anthropic stream error: {error}"
+            )))
+        }
+        _ => Ok(StreamLine::Skip),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_anthropic_provider_new() {
+        let provider = AnthropicProvider::new("https://api.anthropic.com/v1/messages".to_string());
+        assert_eq!(provider.name(), "Anthropic");
+        assert!(provider.supports_streaming());
+    }
+
+    #[test]
+    fn test_http_error_uses_api_message() {
+        let err = http_error(
+            StatusCode::BAD_REQUEST,
+            r#"{"type": "error", "error": {"type": "invalid_request_error", "message": "max_tokens: too large"}}"#,
+        );
+        let message = err.to_string();
+        assert!(message.contains("HTTP 400"), "{message}");
+        assert!(message.contains("invalid_request_error: max_tokens: too large"));
+
+        assert!(matches!(
+            http_error(StatusCode::UNAUTHORIZED, ""),
+            CliError::AuthenticationFailed(_)
+        ));
+    }
+
+    #[test]
+    fn test_decode_sse_lines() {
+        let text = |line| match decode_sse_line(line).unwrap() {
+            StreamLine::Text(t) => t,
+            _ => panic!("Expected text for {line}"),
+        };
+        assert_eq!(
+            text(
+                r#"data: {"type": "content_block_delta", "index": 0, "delta": {"type": "text_delta", "text": "Hi"}}"#
+            ),
+            "Hi"
+        );
+        assert!(matches!(
+            decode_sse_line("event: message_start").unwrap(),
+            StreamLine::Skip
+        ));
+        assert!(matches!(
+            decode_sse_line(r#"data: {"type": "ping"}"#).unwrap(),
+            StreamLine::Skip
+        ));
+        assert!(matches!(
+            decode_sse_line(r#"data: {"type": "message_stop"}"#).unwrap(),
+            StreamLine::Done
+        ));
+        assert!(decode_sse_line(
+            r#"data: {"type": "error", "error": {"type": "overloaded_error", "message": "Overloaded"}}"#
+        )
+        .is_err());
+    }
+}
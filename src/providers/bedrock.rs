@@ -0,0 +1,363 @@
+use crate::{
+    cassette,
+    error::CliError,
+    models::{
+        BedrockContentBlock, BedrockConverseRequest, BedrockConverseResponse, BedrockErrorBody,
+        BedrockInferenceConfig, BedrockMessage,
+    },
+    provider::{InvokeParams, LlmProvider, ProviderResponse, TokenUsage},
+};
+use async_trait::async_trait;
+use chrono::Utc;
+use reqwest::{Client, StatusCode, Url};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+use super::{
+    logging::{log_request, log_response},
+    rate_limit::rate_limited,
+    sigv4::{sign, uri_encode, AwsCredentials},
+};
+
+/// Service name in the SigV4 credential scope
+const SIGNING_SERVICE: &str = "bedrock";
+
+/// Bedrock settings (`[bedrock]`); unset fields fall back to the standard
+/// AWS environment variables
+#[derive(Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct BedrockConfig {
+    /// AWS region (default: from the endpoint host, then `AWS_REGION` /
+    /// `AWS_DEFAULT_REGION`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub region: Option<String>,
+
+    /// Access key id (default: `AWS_ACCESS_KEY_ID`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub access_key_id: Option<String>,
+
+    /// Secret access key (default: `AWS_SECRET_ACCESS_KEY`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub secret_access_key: Option<String>,
+
+    /// Session token for temporary credentials (default: `AWS_SESSION_TOKEN`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session_token: Option<String>,
+}
+
+impl fmt::Debug for BedrockConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let redacted = |value: &Option<String>| value.as_ref().map(|_| "[REDACTED]");
+        f.debug_struct("BedrockConfig")
+            .field("region", &self.region)
+            .field("access_key_id", &self.access_key_id)
+            .field("secret_access_key", &redacted(&self.secret_access_key))
+            .field("session_token", &redacted(&self.session_token))
+            .finish()
+    }
+}
+
+impl BedrockConfig {
+    /// Region for the endpoint: explicit, from the host, or from the environment
+    fn resolve_region(&self, url: &Url) -> Result<String, CliError> {
+        if let Some(region) = &self.region {
+            return Ok(region.clone());
+        }
+        // bedrock-runtime.<region>.amazonaws.com (also bedrock-runtime-fips)
+        let from_host = url.host_str().and_then(|host| {
+            let mut labels = host.split('.');
+            let service = labels.next()?;
+            let region = labels.next()?;
+            (service.starts_with("bedrock-runtime") && host.ends_with(".amazonaws.com"))
+                .then(|| region.to_string())
+        });
+        from_host
+            .or_else(|| std::env::var("AWS_REGION").ok())
+            .or_else(|| std::env::var("AWS_DEFAULT_REGION").ok())
+            .ok_or_else(|| {
+                CliError::InvalidArguments(
+                    "Bedrock region not found: set [bedrock] region, AWS_REGION, or use a \
+                    bedrock-runtime.<region>.amazonaws.com endpoint"
+                        .to_string(),
+                )
+            })
+    }
+
+    fn resolve_credentials(&self) -> Result<AwsCredentials, CliError> {
+        let value = |configured: &Option<String>, env_var: &str| {
+            configured
+                .clone()
+                .or_else(|| std::env::var(env_var).ok())
+                .filter(|v| !v.is_empty())
+        };
+        let (Some(access_key_id), Some(secret_access_key)) = (
+            value(&self.access_key_id, "AWS_ACCESS_KEY_ID"),
+            value(&self.secret_access_key, "AWS_SECRET_ACCESS_KEY"),
+        ) else {
+            return Err(CliError::AuthenticationFailed(
+                "AWS credentials not found: set AWS_ACCESS_KEY_ID and AWS_SECRET_ACCESS_KEY \
+                (or [bedrock] access_key_id/secret_access_key)"
+                    .to_string(),
+            ));
+        };
+        Ok(AwsCredentials {
+            access_key_id,
+            secret_access_key,
+            session_token: value(&self.session_token, "AWS_SESSION_TOKEN"),
+        })
+    }
+}
+
+/// Provider for the Amazon Bedrock Converse API, signed with SigV4
+///
+/// `api_url` is the runtime endpoint (e.g.
+/// `https://bedrock-runtime.us-east-1.amazonaws.com`); requests go to
+/// `{api_url}/model/{model}/converse`.
+pub struct BedrockProvider {
+    client: Client,
+    api_url: String,
+    config: BedrockConfig,
+}
+
+impl BedrockProvider {
+    /// Provider using region and credentials from the environment
+    pub fn new(api_url: String) -> Self {
+        Self::with_config(api_url, BedrockConfig::default())
+    }
+
+    pub fn with_config(api_url: String, config: BedrockConfig) -> Self {
+        Self {
+            client: Client::new(),
+            api_url,
+            config,
+        }
+    }
+
+    /// Converse URL for a model id (ids such as `...-v1:0` are percent-encoded)
+    fn converse_url(&self, model: &str) -> Result<Url, CliError> {
+        let url = format!(
+            "{}/model/{}/converse",
+            self.api_url.trim_end_matches('/'),
+            uri_encode(model)
+        );
+        Url::parse(&url)
+            .map_err(|e| CliError::InvalidArguments(format!("Invalid Bedrock URL '{url}': {e}")))
+    }
+}
+
+#[async_trait]
+impl LlmProvider for BedrockProvider {
+    async fn invoke(&self, params: InvokeParams<'_>) -> Result<String, CliError> {
+        self.invoke_detailed(params).await.map(|r| r.content)
+    }
+
+    async fn invoke_detailed(
+        &self,
+        params: InvokeParams<'_>,
+    ) -> Result<ProviderResponse, CliError> {
+        // Note: Converse has no seed or response_format parameter, and Bedrock
+        // authenticates with SigV4 instead of an API key
+        if params.seed.is_some() {
+            log::debug!("Bedrock does not support seed; ignoring it");
+        }
+        if params.response_format.is_some() {
+            log::warn!(
+                "Bedrock does not support response_format; the schema is only \
+                enforced by output validation"
+            );
+        }
+        if !params.tools.is_empty() {
+            log::warn!("Bedrock tool calling is not supported; ignoring tools");
+        }
+        // Unlike tools, images cannot be dropped: the answer would be made up
+        if !params.images.is_empty() {
+            return Err(CliError::InvalidArguments(
+                "Bedrock provider does not support image input; use image_mode = \"ocr\""
+                    .to_string(),
+            ));
+        }
+        if params.api_key.is_some() {
+            log::debug!("Bedrock requests are signed with AWS credentials; ignoring api_key");
+        }
+
+        let url = self.converse_url(params.model)?;
+        let region = self.config.resolve_region(&url)?;
+        let credentials = self.config.resolve_credentials()?;
+
+        let request = BedrockConverseRequest {
+            messages: vec![BedrockMessage {
+                role: "user".to_string(),
+                content: vec![BedrockContentBlock {
+                    text: Some(params.user_prompt.to_string()),
+                }],
+            }],
+            // The system prompt is a top-level list of blocks, not a message
+            system: if params.system_prompt.is_empty() {
+                Vec::new()
+            } else {
+                vec![BedrockContentBlock {
+                    text: Some(params.system_prompt.to_string()),
+                }]
+            },
+            inference_config: BedrockInferenceConfig {
+                max_tokens: params.max_tokens,
+                temperature: params.temperature,
+                top_p: params.top_p,
+            },
+        };
+        log_request(&request);
+
+        // The signature covers the exact body bytes
+        let body = serde_json::to_vec(&request).map_err(|e| {
+            CliError::InvalidArguments(format!("Failed to serialize Bedrock request: {e}"))
+        })?;
+        let signed_headers = sign(
+            "POST",
+            &url,
+            &body,
+            &credentials,
+            &region,
+            SIGNING_SERVICE,
+            Utc::now(),
+        );
+        log::debug!("Signed Bedrock request for region {region}: [REDACTED]");
+
+        let mut req = self
+            .client
+            .post(url)
+            .header("content-type", "application/json")
+            .body(body)
+            .timeout(std::time::Duration::from_secs(params.timeout_secs));
+        for (name, value) in signed_headers {
+            req = req.header(name, value);
+        }
+
+        let response = cassette::send(req, None).await?;
+        if !response.status.is_success() {
+            return Err(
+                rate_limited(response.status, response.retry_after, &response.body)
+                    .unwrap_or_else(|| http_error(response.status, &response.body)),
+            );
+        }
+
+        let response_text = response.body;
+        log_response(&response_text);
+
+        let converse: BedrockConverseResponse = serde_json::from_str(&response_text)
+            .map_err(|e| CliError::InvalidResponse(format!("Failed to parse response: {e}")))?;
+
+        // Concatenate text blocks (tool use and other block types are skipped)
+        let texts: Vec<String> = converse
+            .output
+            .message
+            .into_iter()
+            .flat_map(|message| message.content)
+            .filter_map(|block| block.text)
+            .collect();
+        if texts.is_empty() {
+            return Err(CliError::InvalidResponse(format!(
+                "No text content in response (stopReason: {})",
+                converse.stop_reason.as_deref().unwrap_or("unknown")
+            )));
+        }
+
+        Ok(ProviderResponse {
+            content: texts.concat(),
+            usage: converse
+                .usage
+                .map(|u| TokenUsage::new(u.input_tokens, u.output_tokens)),
+            ..Default::default()
+        })
+    }
+
+    fn name(&self) -> &str {
+        "Bedrock"
+    }
+}
+
+/// Map a non-success HTTP status to an error, using the API's error message
+fn http_error(status: StatusCode, body: &str) -> CliError {
+    let details = match serde_json::from_str::<BedrockErrorBody>(body) {
+        Ok(parsed) => parsed.message,
+        Err(_) if body.is_empty() => "No details provided".to_string(),
+        Err(_) => body.to_string(),
+    };
+
+    // 403 covers bad signatures, expired tokens and missing model access
+    if status == 401 || status == 403 {
+        return CliError::AuthenticationFailed(format!(
+            "Bedrock rejected the request (HTTP {}): {details}",
+            status.as_u16()
+        ));
+    }
+    CliError::InvalidResponse(format!(
+        "HTTP {} error: {}\nResponse from API: {details}",
+        status.as_u16(),
+        status.canonical_reason().unwrap_or("Unknown error"),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_converse_url_encodes_model_id() {
+        let provider =
+            BedrockProvider::new("https://bedrock-runtime.us-west-2.amazonaws.com/".to_string());
+        assert_eq!(
+            provider
+                .converse_url("anthropic.claude-3-haiku-20240307-v1:0")
+                .unwrap()
+                .as_str(),
+            "https://bedrock-runtime.us-west-2.amazonaws.com/model/anthropic.claude-3-haiku-20240307-v1%3A0/converse"
+        );
+        assert_eq!(provider.name(), "Bedrock");
+        assert!(!provider.supports_streaming());
+    }
+
+    #[test]
+    fn test_region_from_config_then_host() {
+        let url = Url::parse("https://bedrock-runtime.eu-central-1.amazonaws.com/model/m/converse")
+            .unwrap();
+        assert_eq!(
+            BedrockConfig::default().resolve_region(&url).unwrap(),
+            "eu-central-1"
+        );
+
+        let config = BedrockConfig {
+            region: Some("us-east-1".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(config.resolve_region(&url).unwrap(), "us-east-1");
+    }
+
+    #[test]
+    fn test_debug_redacts_secrets() {
+        let config = BedrockConfig {
+            access_key_id: Some("AKIDEXAMPLE".to_string()),
+            secret_access_key: Some("super-secret".to_string()),
+            ..Default::default()
+        };
+        let debug = format!("{config:?}");
+        assert!(debug.contains("AKIDEXAMPLE"));
+        assert!(!debug.contains("super-secret"));
+    }
+
+    #[test]
+    fn test_http_error() {
+        let err = http_error(
+            StatusCode::FORBIDDEN,
+            r#"{"message": "The security token included in the request is invalid."}"#,
+        );
+        assert!(matches!(err, CliError::AuthenticationFailed(_)));
+        assert!(err.to_string().contains("security token"));
+
+        let err = http_error(
+            StatusCode::BAD_REQUEST,
+            r#"{"message": "The provided model identifier is invalid."}"#,
+        );
+        let message = err.to_string();
+        assert!(message.contains("HTTP 400"), "{message}");
+        assert!(message.contains("model identifier is invalid"));
+    }
+}
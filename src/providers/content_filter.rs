@@ -0,0 +1,143 @@
+//! Provider-side content filter detection
+//!
+//! Azure OpenAI (and OpenAI-compatible gateways) report their own content
+//! filters in two ways:
+//!
+//! - the prompt is rejected with HTTP 400 and an error body whose `code` is
+//!   `content_filter` (or `content_policy_violation`), with per-category
+//!   results under `innererror.content_filter_result`
+//! - the completion stops with `finish_reason: "content_filter"`, with
+//!   per-category results under the choice's `content_filter_results`
+//!
+//! Both become [`CliError::ContentFiltered`], which the pipeline reports as a
+//! `PROVIDER_CONTENT_FILTERED` outcome instead of a generic HTTP error.
+
+use crate::error::CliError;
+use serde_json::Value;
+
+/// `finish_reason` of a completion stopped by the provider's filter
+pub(crate) const FINISH_REASON: &str = "content_filter";
+
+/// Error codes (`error.code` or `error.innererror.code`) of prompts rejected
+/// by the provider's filter
+const ERROR_CODES: &[&str] = &[
+    "content_filter",
+    "content_policy_violation",
+    "ResponsibleAIPolicyViolation",
+];
+
+/// Content filter rejection in a non-success response body
+pub(crate) fn from_error_body(body: &str) -> Option<CliError> {
+    let body: Value = serde_json::from_str(body).ok()?;
+    let error = &body["error"];
+    let filtered = [&error["code"], &error["innererror"]["code"]]
+        .iter()
+        .filter_map(|code| code.as_str())
+        .any(|code| ERROR_CODES.contains(&code));
+    if !filtered {
+        return None;
+    }
+    let categories = filtered_categories(&error["innererror"]["content_filter_result"]);
+    let message = error["message"]
+        .as_str()
+        .unwrap_or("The prompt was rejected by the provider's content filter");
+    Some(content_filtered("input", message, categories))
+}
+
+/// Content filter stop of a completion (`finish_reason: "content_filter"`)
+pub(crate) fn from_finish_reason(
+    finish_reason: Option<&str>,
+    content_filter_results: &Value,
+) -> Option<CliError> {
+    (finish_reason == Some(FINISH_REASON)).then(|| {
+        content_filtered(
+            "output",
+            "The response was stopped by the provider's content filter",
+            filtered_categories(content_filter_results),
+        )
+    })
+}
+
+fn content_filtered(direction: &str, message: &str, categories: Vec<String>) -> CliError {
+    let message = if categories.is_empty() {
+        message.to_string()
+    } else {
+        format!("{message} (categories: {})", categories.join(", "))
+    };
+    CliError::ContentFiltered {
+        message,
+        direction: direction.to_string(),
+        categories,
+    }
+}
+
+/// Names of the categories marked `filtered` (e.g. `hate`, `self_harm`), in
+/// the provider's order, with the severity when reported (`violence:high`)
+fn filtered_categories(results: &Value) -> Vec<String> {
+    let Some(results) = results.as_object() else {
+        return Vec::new();
+    };
+    results
+        .iter()
+        .filter(|(_, result)| result["filtered"].as_bool() == Some(true))
+        .map(|(category, result)| match result["severity"].as_str() {
+            Some(severity) if severity != "safe" => format!("{category}:{severity}"),
+            _ => category.clone(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_azure_prompt_filter_error() {
+        let body = json!({"error": {
+            "message": "The response was filtered due to the prompt triggering Azure OpenAI's content management policy.",
+            "code": "content_filter",
+            "status": 400,
+            "innererror": {
+                "code": "ResponsibleAIPolicyViolation",
+                "content_filter_result": {
+                    "hate": {"filtered": false, "severity": "safe"},
+                    "jailbreak": {"filtered": true, "detected": true},
+                    "violence": {"filtered": true, "severity": "high"}
+                }
+            }
+        }})
+        .to_string();
+        match from_error_body(&body) {
+            Some(CliError::ContentFiltered {
+                message,
+                direction,
+                categories,
+            }) => {
+                assert_eq!(direction, "input");
+                assert_eq!(categories, vec!["jailbreak", "violence:high"]);
+                assert!(message.ends_with("(categories: jailbreak, violence:high)"));
+            }
+            other => panic!("Expected ContentFiltered, got {other:?}"),
+        }
+
+        assert!(from_error_body(r#"{"error": {"code": "invalid_api_key"}}"#).is_none());
+        assert!(from_error_body("Bad Gateway").is_none());
+    }
+
+    #[test]
+    fn test_content_filter_finish_reason() {
+        let results = json!({"self_harm": {"filtered": true, "severity": "medium"}});
+        let err = from_finish_reason(Some("content_filter"), &results).unwrap();
+        assert_eq!(err.code(), "PROVIDER_CONTENT_FILTERED");
+        assert!(err.to_string().contains("self_harm:medium"), "{err}");
+
+        // Without category details
+        let err = from_finish_reason(Some("content_filter"), &Value::Null).unwrap();
+        assert!(matches!(
+            err,
+            CliError::ContentFiltered { ref categories, .. } if categories.is_empty()
+        ));
+        assert!(from_finish_reason(Some("stop"), &results).is_none());
+    }
+}
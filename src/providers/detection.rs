@@ -1,19 +1,32 @@
 use crate::provider::{LlmProvider, ProviderType};
 
-use super::{ollama::OllamaProvider, openai::OpenAIProvider};
+use super::{
+    anthropic::AnthropicProvider,
+    bedrock::BedrockProvider,
+    mock::{MockProvider, MOCK_URL_SCHEME},
+    ollama::OllamaProvider,
+    openai::OpenAIProvider,
+};
 
 /// Detect API format from URL
 ///
 /// # Detection Strategy
 ///
+/// 0. **`mock://` scheme** → Mock (offline fixtures)
+///
 /// 1. **Path-based detection** (highest priority):
 ///    - `/api/generate` → Ollama
 ///    - `/v1/chat/completions` → OpenAI
+///    - `/v1/messages` → Anthropic
+///
+/// 2. **Host-based detection**:
+///    - `api.anthropic.com` → Anthropic
+///    - `bedrock-runtime.<region>.amazonaws.com` → Bedrock
 ///
-/// 2. **Port-based detection** (fallback):
+/// 3. **Port-based detection** (fallback):
 ///    - Port 11434 → Ollama (common local server port)
 ///
-/// 3. **Default**: OpenAI (industry standard for cloud APIs)
+/// 4. **Default**: OpenAI (industry standard for cloud APIs)
 ///
 /// # Examples
 ///
@@ -31,6 +44,17 @@ use super::{ollama::OllamaProvider, openai::OpenAIProvider};
 ///     ProviderType::OpenAI
 /// ));
 ///
+/// assert!(matches!(
+///     detect_provider_type("https://api.anthropic.com/v1/messages"),
+///     ProviderType::Anthropic
+/// ));
+///
+/// // Host-based detection
+/// assert!(matches!(
+///     detect_provider_type("https://bedrock-runtime.us-east-1.amazonaws.com"),
+///     ProviderType::Bedrock
+/// ));
+///
 /// // Port-based fallback
 /// assert!(matches!(
 ///     detect_provider_type("http://localhost:11434"),
@@ -44,6 +68,10 @@ use super::{ollama::OllamaProvider, openai::OpenAIProvider};
 /// ));
 /// ```
 pub fn detect_provider_type(url: &str) -> ProviderType {
+    if url.starts_with(MOCK_URL_SCHEME) {
+        return ProviderType::Mock;
+    }
+
     // Path-based detection (most explicit, highest priority)
     // Respect the user's explicit endpoint path choice
     if url.contains("/api/generate") {
@@ -52,6 +80,17 @@ pub fn detect_provider_type(url: &str) -> ProviderType {
     if url.contains("/v1/chat/completions") {
         return ProviderType::OpenAI;
     }
+    if url.contains("/v1/messages") {
+        return ProviderType::Anthropic;
+    }
+
+    // Host-based detection
+    if url.contains("api.anthropic.com") {
+        return ProviderType::Anthropic;
+    }
+    if url.contains("bedrock-runtime") && url.contains(".amazonaws.com") {
+        return ProviderType::Bedrock;
+    }
 
     // Port-based detection (fallback for ambiguous URLs)
     // Port 11434: Common for local servers, typically Ollama format
@@ -94,6 +133,9 @@ pub fn create_provider(
     match provider {
         ProviderType::Ollama => Box::new(OllamaProvider::new(api_url)),
         ProviderType::OpenAI => Box::new(OpenAIProvider::new(api_url)),
+        ProviderType::Anthropic => Box::new(AnthropicProvider::new(api_url)),
+        ProviderType::Bedrock => Box::new(BedrockProvider::new(api_url)),
+        ProviderType::Mock => Box::new(MockProvider::from_url(&api_url)),
     }
 }
 
@@ -135,6 +177,31 @@ mod tests {
         assert!(matches!(detect_provider_type(url), ProviderType::OpenAI));
     }
 
+    #[test]
+    fn test_detect_mock_by_scheme() {
+        assert!(matches!(
+            detect_provider_type("mock://tests/fixtures/mock.json"),
+            ProviderType::Mock
+        ));
+    }
+
+    #[test]
+    fn test_detect_anthropic() {
+        assert!(matches!(
+            detect_provider_type("https://api.anthropic.com/v1/messages"),
+            ProviderType::Anthropic
+        ));
+        assert!(matches!(
+            detect_provider_type("https://api.anthropic.com"),
+            ProviderType::Anthropic
+        ));
+        // Messages-compatible proxies are detected by path
+        assert!(matches!(
+            detect_provider_type("https://llm-proxy.internal/v1/messages"),
+            ProviderType::Anthropic
+        ));
+    }
+
     #[test]
     fn test_create_provider_auto_detect_ollama() {
         let provider = create_provider("http://localhost:11434/api/generate".to_string(), None);
@@ -1,9 +1,10 @@
+use crate::log_policy::loggable;
 use serde::Serialize;
 
 /// Log request JSON for debugging (pretty-printed if possible)
 pub fn log_request<T: Serialize>(request: &T) {
     if let Ok(request_json) = serde_json::to_string_pretty(request) {
-        log::debug!("Request JSON sent to API:\n{request_json}");
+        log::debug!("Request JSON sent to API:\n{}", loggable(&request_json));
     }
 }
 
@@ -13,7 +14,10 @@ pub fn log_response(response_text: &str) {
     let display_text = serde_json::from_str::<serde_json::Value>(response_text)
         .and_then(|v| serde_json::to_string_pretty(&v))
         .unwrap_or_else(|_| response_text.to_string());
-    log::debug!("Response JSON received from API:\n{display_text}");
+    log::debug!(
+        "Response JSON received from API:\n{}",
+        loggable(&display_text)
+    );
 }
 
 #[cfg(test)]
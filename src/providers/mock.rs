@@ -0,0 +1,416 @@
+//! Offline mock provider
+//!
+//! Returns canned or scripted responses from a fixtures file instead of
+//! calling an LLM, so embedders can run integration tests without a server.
+//! Selected with `provider = "mock"` (or a `mock://` API URL); the API URL is
+//! the fixtures path, and `mock://` alone echoes the user prompt.
+//!
+//! ```toml
+//! mode = "match"        # "match" (first matching rule) or "sequence" (in order, cycling)
+//! latency_ms = 20       # Simulated latency per call
+//! jitter_ms = 10        # Random extra latency (0..=jitter_ms)
+//! failure_rate = 0.1    # Probability of an injected HTTP 503
+//! seed = 7              # Makes jitter and failures reproducible
+//!
+//! [[responses]]
+//! when = "(?i)invoice"  # Regex on the user prompt (omit to match anything)
+//! content = "Total: {{user_prompt}}"
+//!
+//! [[responses]]
+//! error = { status = 429, message = "rate limited", retry_after_secs = 2 }
+//! ```
+
+use crate::{
+    error::CliError,
+    provider::{InvokeParams, LlmProvider, ProviderResponse},
+};
+use async_trait::async_trait;
+use regex::Regex;
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+use std::{
+    path::Path,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+    },
+    time::Duration,
+};
+
+use super::rate_limit::rate_limited;
+
+/// URL scheme selecting the mock provider
+pub const MOCK_URL_SCHEME: &str = "mock://";
+
+/// How a response is chosen for each call
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MockMode {
+    /// First response whose `when` pattern matches the user prompt
+    #[default]
+    Match,
+    /// Responses in file order, wrapping around
+    Sequence,
+}
+
+/// Simulated API error
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MockError {
+    pub status: u16,
+    #[serde(default)]
+    pub message: String,
+    /// `Retry-After` delay of a simulated 429
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retry_after_secs: Option<u64>,
+}
+
+/// One canned response
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct MockResponse {
+    /// Regex matched against the user prompt (match mode; None = always)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub when: Option<String>,
+
+    /// Response text; `{{user_prompt}}` and `{{model}}` are substituted
+    #[serde(default)]
+    pub content: String,
+
+    /// Fail with this error instead of responding
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<MockError>,
+
+    /// Latency override for this response
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub latency_ms: Option<u64>,
+}
+
+/// Mock provider fixtures (JSON or TOML file)
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct MockFixtures {
+    #[serde(default)]
+    pub mode: MockMode,
+    #[serde(default)]
+    pub responses: Vec<MockResponse>,
+    #[serde(default)]
+    pub latency_ms: u64,
+    #[serde(default)]
+    pub jitter_ms: u64,
+    /// Probability (0.0-1.0) of failing a call with HTTP 503
+    #[serde(default)]
+    pub failure_rate: f64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seed: Option<u64>,
+}
+
+impl MockFixtures {
+    /// Fixtures that echo the user prompt back
+    pub fn echo() -> Self {
+        Self {
+            responses: vec![MockResponse {
+                content: "{{user_prompt}}".to_string(),
+                ..Default::default()
+            }],
+            ..Default::default()
+        }
+    }
+
+    /// Load fixtures from a `.json` or `.toml` file
+    pub fn load(path: &Path) -> Result<Self, CliError> {
+        let content = std::fs::read_to_string(path).map_err(|e| {
+            CliError::FileNotFound(format!(
+                "Failed to read mock fixtures '{}': {e}",
+                path.display()
+            ))
+        })?;
+        let parsed = match path.extension().and_then(|e| e.to_str()) {
+            Some("toml") => toml::from_str(&content).map_err(|e| e.to_string()),
+            _ => serde_json::from_str(&content).map_err(|e| e.to_string()),
+        };
+        parsed.map_err(|e| {
+            CliError::InvalidArguments(format!("Invalid mock fixtures '{}': {e}", path.display()))
+        })
+    }
+}
+
+/// Compiled response rule
+struct Rule {
+    when: Option<Regex>,
+    response: MockResponse,
+}
+
+/// Provider serving responses from [`MockFixtures`]
+pub struct MockProvider {
+    rules: Result<Vec<Rule>, String>,
+    fixtures: MockFixtures,
+    calls: AtomicUsize,
+    rng: Mutex<u64>,
+}
+
+impl MockProvider {
+    pub fn new(fixtures: MockFixtures) -> Self {
+        let rules = fixtures
+            .responses
+            .iter()
+            .map(|response| {
+                let when = response
+                    .when
+                    .as_deref()
+                    .map(Regex::new)
+                    .transpose()
+                    .map_err(|e| format!("Invalid mock 'when' pattern: {e}"))?;
+                Ok(Rule {
+                    when,
+                    response: response.clone(),
+                })
+            })
+            .collect();
+        let seed = fixtures.seed.unwrap_or_else(|| {
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map(|d| d.as_nanos() as u64)
+                .unwrap_or_default()
+        });
+        Self {
+            rules,
+            fixtures,
+            calls: AtomicUsize::new(0),
+            rng: Mutex::new(seed),
+        }
+    }
+
+    /// Provider for an API URL: `mock://` echoes, otherwise the fixtures path
+    ///
+    /// Fixture errors are reported on the first call (provider construction is
+    /// infallible).
+    pub fn from_url(api_url: &str) -> Self {
+        let path = api_url.strip_prefix(MOCK_URL_SCHEME).unwrap_or(api_url);
+        if path.is_empty() {
+            return Self::new(MockFixtures::echo());
+        }
+        match MockFixtures::load(Path::new(path)) {
+            Ok(fixtures) => Self::new(fixtures),
+            Err(e) => Self {
+                rules: Err(e.to_string()),
+                ..Self::new(MockFixtures::default())
+            },
+        }
+    }
+
+    /// Number of calls served (including injected failures)
+    pub fn calls(&self) -> usize {
+        self.calls.load(Ordering::SeqCst)
+    }
+
+    /// Uniform sample in [0, 1) (splitmix64)
+    fn next_random(&self) -> f64 {
+        let mut state = self.rng.lock().expect("mock rng poisoned");
+        *state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = *state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^= z >> 31;
+        (z >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    fn select(&self, rules: &[Rule], call: usize, user_prompt: &str) -> Option<MockResponse> {
+        match self.fixtures.mode {
+            MockMode::Sequence => {
+                (!rules.is_empty()).then(|| rules[call % rules.len()].response.clone())
+            }
+            MockMode::Match => rules
+                .iter()
+                .find(|rule| rule.when.as_ref().is_none_or(|re| re.is_match(user_prompt)))
+                .map(|rule| rule.response.clone()),
+        }
+    }
+}
+
+fn http_error(status: u16, message: &str, retry_after: Option<Duration>) -> CliError {
+    let limited = StatusCode::from_u16(status)
+        .ok()
+        .and_then(|status| rate_limited(status, retry_after, message));
+    if let Some(limited) = limited {
+        return limited;
+    }
+    if status == 401 {
+        return CliError::AuthenticationFailed(if message.is_empty() {
+            "Invalid or missing API key".to_string()
+        } else {
+            message.to_string()
+        });
+    }
+    CliError::InvalidResponse(format!(
+        "HTTP {status} error: mock\nResponse from API: {}",
+        if message.is_empty() {
+            "No details provided"
+        } else {
+            message
+        }
+    ))
+}
+
+#[async_trait]
+impl LlmProvider for MockProvider {
+    async fn invoke(&self, params: InvokeParams<'_>) -> Result<String, CliError> {
+        self.invoke_detailed(params).await.map(|r| r.content)
+    }
+
+    async fn invoke_detailed(
+        &self,
+        params: InvokeParams<'_>,
+    ) -> Result<ProviderResponse, CliError> {
+        let rules = self
+            .rules
+            .as_ref()
+            .map_err(|e| CliError::InvalidArguments(e.clone()))?;
+        let call = self.calls.fetch_add(1, Ordering::SeqCst);
+        let response = self.select(rules, call, params.user_prompt);
+
+        let base_latency = response
+            .as_ref()
+            .and_then(|r| r.latency_ms)
+            .unwrap_or(self.fixtures.latency_ms);
+        let jitter = if self.fixtures.jitter_ms > 0 {
+            (self.next_random() * (self.fixtures.jitter_ms + 1) as f64) as u64
+        } else {
+            0
+        };
+        let latency = Duration::from_millis(base_latency + jitter);
+        if latency > Duration::from_secs(params.timeout_secs) {
+            tokio::time::sleep(Duration::from_secs(params.timeout_secs)).await;
+            return Err(CliError::InvalidResponse(format!(
+                "Mock request timed out after {}s",
+                params.timeout_secs
+            )));
+        }
+        tokio::time::sleep(latency).await;
+
+        if self.fixtures.failure_rate > 0.0 && self.next_random() < self.fixtures.failure_rate {
+            log::debug!("Mock provider: injected failure on call {call}");
+            return Err(http_error(503, "injected failure", None));
+        }
+
+        let response = response.ok_or_else(|| {
+            CliError::InvalidResponse(format!(
+                "No mock response matches user prompt (call {call})"
+            ))
+        })?;
+        if let Some(error) = &response.error {
+            let retry_after = error.retry_after_secs.map(Duration::from_secs);
+            return Err(http_error(error.status, &error.message, retry_after));
+        }
+
+        Ok(ProviderResponse::new(
+            response
+                .content
+                .replace("{{user_prompt}}", params.user_prompt)
+                .replace("{{model}}", params.model),
+        ))
+    }
+
+    fn name(&self) -> &str {
+        "Mock"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn params<'a>(user_prompt: &'a str) -> InvokeParams<'a> {
+        InvokeParams {
+            model: "mock-model",
+            system_prompt: "",
+            user_prompt,
+            temperature: 0.0,
+            top_p: None,
+            max_tokens: None,
+            seed: None,
+            api_key: None,
+            timeout_secs: 5,
+            response_format: None,
+            tools: &[],
+            images: &[],
+        }
+    }
+
+    #[tokio::test]
+    async fn test_echo() {
+        let provider = MockProvider::from_url("mock://");
+        assert_eq!(provider.invoke(params("hello")).await.unwrap(), "hello");
+    }
+
+    #[tokio::test]
+    async fn test_match_mode_and_errors() {
+        let provider = MockProvider::new(MockFixtures {
+            responses: vec![
+                MockResponse {
+                    when: Some("(?i)invoice".to_string()),
+                    content: "{{model}} saw an invoice".to_string(),
+                    ..Default::default()
+                },
+                MockResponse {
+                    when: Some("secret".to_string()),
+                    error: Some(MockError {
+                        status: 401,
+                        message: String::new(),
+                        retry_after_secs: None,
+                    }),
+                    ..Default::default()
+                },
+            ],
+            ..Default::default()
+        });
+
+        assert_eq!(
+            provider.invoke(params("An INVOICE")).await.unwrap(),
+            "mock-model saw an invoice"
+        );
+        assert!(matches!(
+            provider.invoke(params("secret")).await,
+            Err(CliError::AuthenticationFailed(_))
+        ));
+        assert!(provider.invoke(params("other")).await.is_err());
+        assert_eq!(provider.calls(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_sequence_mode_cycles() {
+        let provider = MockProvider::new(MockFixtures {
+            mode: MockMode::Sequence,
+            responses: ["a", "b"]
+                .iter()
+                .map(|c| MockResponse {
+                    content: c.to_string(),
+                    ..Default::default()
+                })
+                .collect(),
+            ..Default::default()
+        });
+        let mut seen = Vec::new();
+        for _ in 0..3 {
+            seen.push(provider.invoke(params("x")).await.unwrap());
+        }
+        assert_eq!(seen, vec!["a", "b", "a"]);
+    }
+
+    #[tokio::test]
+    async fn test_failure_injection_is_seeded() {
+        let fixtures = MockFixtures {
+            failure_rate: 0.5,
+            seed: Some(42),
+            ..MockFixtures::echo()
+        };
+        let run = || async {
+            let provider = MockProvider::new(fixtures.clone());
+            let mut outcomes = Vec::new();
+            for _ in 0..20 {
+                outcomes.push(provider.invoke(params("x")).await.is_ok());
+            }
+            outcomes
+        };
+        let first = run().await;
+        assert_eq!(first, run().await);
+        assert!(first.contains(&true) && first.contains(&false));
+    }
+}
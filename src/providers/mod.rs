@@ -1,9 +1,20 @@
+mod anthropic;
+mod bedrock;
+mod content_filter;
 mod detection;
 mod logging;
+pub mod mock;
 mod ollama;
 mod openai;
+mod rate_limit;
+mod sigv4;
+mod streaming;
 
 // Re-export public items
+pub use anthropic::AnthropicProvider;
+pub(crate) use anthropic::ANTHROPIC_VERSION;
+pub use bedrock::{BedrockConfig, BedrockProvider};
 pub use detection::{create_provider, detect_provider_type};
+pub use mock::{MockFixtures, MockProvider};
 pub use ollama::OllamaProvider;
 pub use openai::OpenAIProvider;
@@ -1,12 +1,21 @@
 use crate::{
+    cassette,
     error::CliError,
-    models::{OllamaOptions, OllamaRequest, OllamaResponse},
-    provider::{InvokeParams, LlmProvider},
+    image::ImageInput,
+    models::{
+        Message, OllamaChatRequest, OllamaChatResponse, OllamaOptions, OllamaRequest,
+        OllamaResponse, OllamaStreamChunk, OllamaTagsResponse, OpenAITool, ToolCall,
+    },
+    provider::{ChunkStream, InvokeParams, LlmProvider, ModelEcho, ProviderResponse, TokenUsage},
 };
 use async_trait::async_trait;
-use reqwest::Client;
+use reqwest::{Client, RequestBuilder};
 
-use super::logging::{log_request, log_response};
+use super::{
+    logging::{log_request, log_response},
+    rate_limit::rate_limited,
+    streaming::{line_stream, StreamLine},
+};
 
 /// Provider for Ollama /api/generate format (local servers)
 pub struct OllamaProvider {
@@ -23,51 +32,315 @@ impl OllamaProvider {
     }
 }
 
+/// Parse an Ollama /api/generate body
+///
+/// Some deployments return an NDJSON stream even for `stream: false`; in that
+/// case the chunks are aggregated and the chunk count is reported.
+fn parse_ollama_body(body: &str) -> Result<ProviderResponse, CliError> {
+    let single_error = match serde_json::from_str::<OllamaResponse>(body) {
+        Ok(response) => {
+            return Ok(ProviderResponse {
+                content: response.response,
+                model_echo: served_model(response.model),
+                usage: usage(response.prompt_eval_count, response.eval_count),
+                ..Default::default()
+            })
+        }
+        Err(e) => e,
+    };
+
+    let lines: Vec<&str> = body.lines().filter(|l| !l.trim().is_empty()).collect();
+    if lines.len() < 2 {
+        return Err(CliError::InvalidResponse(format!(
+            "Failed to parse response: {single_error}"
+        )));
+    }
+
+    let mut content = String::new();
+    let mut model = None;
+    let mut counts = None;
+    let mut chunks = 0;
+    for (index, line) in lines.iter().enumerate() {
+        let chunk: OllamaStreamChunk = serde_json::from_str(line).map_err(|e| {
+            CliError::InvalidResponse(format!(
+                "Failed to parse NDJSON response chunk {}: {e}",
+                index + 1
+            ))
+        })?;
+        if let Some(error) = chunk.error {
+            return Err(CliError::InvalidResponse(format!(
+                "Ollama stream error: {error}"
+            )));
+        }
+        content.push_str(&chunk.response);
+        model = model.or(chunk.model);
+        if chunk.done {
+            counts = usage(chunk.prompt_eval_count, chunk.eval_count);
+        }
+        chunks += 1;
+        if chunk.done {
+            break;
+        }
+    }
+
+    log::warn!(
+        "Ollama returned an NDJSON streaming body for a non-streaming request; \
+        aggregated {chunks} chunks"
+    );
+    Ok(ProviderResponse {
+        content,
+        aggregated_chunks: Some(chunks),
+        model_echo: served_model(model),
+        usage: counts,
+        ..Default::default()
+    })
+}
+
+/// Token usage from `prompt_eval_count` / `eval_count`
+///
+/// Ollama omits `prompt_eval_count` when the prompt was served from its KV
+/// cache, which counts as 0 prompt tokens.
+fn usage(prompt_eval_count: Option<u64>, eval_count: Option<u64>) -> Option<TokenUsage> {
+    if prompt_eval_count.is_none() && eval_count.is_none() {
+        return None;
+    }
+    Some(TokenUsage::new(
+        prompt_eval_count.unwrap_or(0),
+        eval_count.unwrap_or(0),
+    ))
+}
+
+fn served_model(model: Option<String>) -> Option<ModelEcho> {
+    ModelEcho {
+        model,
+        ..Default::default()
+    }
+    .non_empty()
+}
+
+/// /api/chat endpoint next to the configured /api/generate one
+///
+/// /api/generate has no tool support, so requests offering tools go to the
+/// chat endpoint of the same server.
+fn chat_url(api_url: &str) -> Result<String, CliError> {
+    let url = api_url.trim_end_matches('/');
+    if url.ends_with("/api/chat") {
+        return Ok(url.to_string());
+    }
+    url.strip_suffix("/api/generate")
+        .map(|base| format!("{base}/api/chat"))
+        .ok_or_else(|| {
+            CliError::InvalidArguments(format!(
+                "Ollama tool calling needs an /api/generate or /api/chat URL, got '{api_url}'"
+            ))
+        })
+}
+
+/// /api/tags endpoint of the server behind an /api/generate or /api/chat URL
+fn tags_url(api_url: &str) -> Option<String> {
+    let url = api_url.trim_end_matches('/');
+    url.strip_suffix("/api/generate")
+        .or_else(|| url.strip_suffix("/api/chat"))
+        .map(|base| format!("{base}/api/tags"))
+}
+
+/// Digest of `model` in an /api/tags body (untagged names mean `:latest`)
+fn tag_digest(body: &str, model: &str) -> Option<String> {
+    let tags: OllamaTagsResponse = serde_json::from_str(body).ok()?;
+    let latest = format!("{model}:latest");
+    tags.models
+        .into_iter()
+        .find(|tag| tag.name == model || tag.name == latest)
+        .and_then(|tag| tag.digest)
+}
+
+/// Parse an Ollama /api/chat body (tool calls get `call_{index}` ids)
+fn parse_chat_body(body: &str) -> Result<ProviderResponse, CliError> {
+    let response: OllamaChatResponse = serde_json::from_str(body)
+        .map_err(|e| CliError::InvalidResponse(format!("Failed to parse response: {e}")))?;
+    let tool_calls = response
+        .message
+        .tool_calls
+        .into_iter()
+        .enumerate()
+        .map(|(index, call)| ToolCall {
+            id: format!("call_{index}"),
+            name: call.function.name,
+            arguments: call.function.arguments,
+        })
+        .collect();
+    Ok(ProviderResponse {
+        content: response.message.content,
+        model_echo: served_model(response.model),
+        usage: usage(response.prompt_eval_count, response.eval_count),
+        tool_calls,
+        ..Default::default()
+    })
+}
+
 #[async_trait]
 impl LlmProvider for OllamaProvider {
     async fn invoke(&self, params: InvokeParams<'_>) -> Result<String, CliError> {
+        self.invoke_detailed(params).await.map(|r| r.content)
+    }
+
+    async fn invoke_detailed(
+        &self,
+        params: InvokeParams<'_>,
+    ) -> Result<ProviderResponse, CliError> {
+        let tools = !params.tools.is_empty();
+        let request = if tools {
+            self.chat_request(&params)?
+        } else {
+            self.request(&params, false)
+        };
+        let response = cassette::send(request, params.api_key).await?;
+
+        if !response.status.is_success() {
+            return Err(
+                rate_limited(response.status, response.retry_after, &response.body).unwrap_or_else(
+                    || CliError::InvalidResponse(format!("HTTP error: {}", response.status)),
+                ),
+            );
+        }
+
+        // Get response body as text for logging and parsing
+        let response_text = response.body;
+        log_response(&response_text);
+
+        let mut parsed = if tools {
+            parse_chat_body(&response_text)?
+        } else {
+            // Parse the response (single JSON object, or NDJSON stream fallback)
+            parse_ollama_body(&response_text)?
+        };
+        if let Some(digest) = self.model_digest(&params).await {
+            parsed
+                .model_echo
+                .get_or_insert_with(Default::default)
+                .model_digest = Some(digest);
+        }
+        Ok(parsed)
+    }
+
+    async fn invoke_stream(&self, params: InvokeParams<'_>) -> Result<ChunkStream, CliError> {
+        let request = self.request(&params, true);
+        let reply = cassette::send_streaming(request, params.api_key).await?;
+
+        if !reply.status().is_success() {
+            let (status, retry_after) = (reply.status(), reply.retry_after());
+            let body = reply.text().await?;
+            return Err(rate_limited(status, retry_after, &body)
+                .unwrap_or_else(|| CliError::InvalidResponse(format!("HTTP error: {status}"))));
+        }
+        Ok(line_stream(reply, decode_ndjson_line))
+    }
+
+    fn name(&self) -> &str {
+        "Ollama"
+    }
+
+    fn supports_streaming(&self) -> bool {
+        true
+    }
+}
+
+impl OllamaProvider {
+    /// Digest of the served model from /api/tags (None when unavailable)
+    async fn model_digest(&self, params: &InvokeParams<'_>) -> Option<String> {
+        let url = tags_url(&self.api_url)?;
+        let request = self
+            .client
+            .get(url)
+            .timeout(std::time::Duration::from_secs(params.timeout_secs));
+        match cassette::send(request, params.api_key).await {
+            Ok(reply) if reply.status.is_success() => tag_digest(&reply.body, params.model),
+            Ok(reply) => {
+                log::debug!("Ollama model digest lookup got HTTP {}", reply.status);
+                None
+            }
+            Err(e) => {
+                log::debug!("Ollama model digest lookup failed: {e}");
+                None
+            }
+        }
+    }
+
+    fn request(&self, params: &InvokeParams<'_>, stream: bool) -> RequestBuilder {
         // Note: Ollama's /api/generate format doesn't use max_tokens, api_key, or response_format
         let request = OllamaRequest {
             model: params.model.to_string(),
             system: params.system_prompt.to_string(),
             prompt: params.user_prompt.to_string(),
-            stream: false,
+            images: params.images.iter().map(ImageInput::base64).collect(),
+            stream,
             options: OllamaOptions {
                 temperature: params.temperature,
+                top_p: params.top_p,
                 seed: params.seed,
             },
         };
 
         log_request(&request);
 
-        let response = self
-            .client
+        self.client
             .post(&self.api_url)
             .json(&request)
             .timeout(std::time::Duration::from_secs(params.timeout_secs))
-            .send()
-            .await?;
+    }
 
-        if !response.status().is_success() {
-            return Err(CliError::InvalidResponse(format!(
-                "HTTP error: {}",
-                response.status()
-            )));
+    /// Build an /api/chat request offering `params.tools`
+    fn chat_request(&self, params: &InvokeParams<'_>) -> Result<RequestBuilder, CliError> {
+        if !params.images.is_empty() {
+            return Err(CliError::InvalidArguments(
+                "Ollama tool calling does not support image input".to_string(),
+            ));
         }
+        let request = OllamaChatRequest {
+            model: params.model.to_string(),
+            messages: vec![
+                Message {
+                    role: "system".to_string(),
+                    content: params.system_prompt.to_string(),
+                },
+                Message {
+                    role: "user".to_string(),
+                    content: params.user_prompt.to_string(),
+                },
+            ],
+            tools: params.tools.iter().map(OpenAITool::function).collect(),
+            stream: false,
+            options: OllamaOptions {
+                temperature: params.temperature,
+                top_p: params.top_p,
+                seed: params.seed,
+            },
+        };
 
-        // Get response body as text for logging and parsing
-        let response_text = response.text().await?;
-        log_response(&response_text);
+        log_request(&request);
 
-        // Parse the response
-        let ollama_response: OllamaResponse = serde_json::from_str(&response_text)
-            .map_err(|e| CliError::InvalidResponse(format!("Failed to parse response: {e}")))?;
-        Ok(ollama_response.response)
+        Ok(self
+            .client
+            .post(chat_url(&self.api_url)?)
+            .json(&request)
+            .timeout(std::time::Duration::from_secs(params.timeout_secs)))
     }
+}
 
-    fn name(&self) -> &str {
-        "Ollama"
+/// Decode one line of an NDJSON streaming body
+fn decode_ndjson_line(line: &str) -> Result<StreamLine, CliError> {
+    let chunk: OllamaStreamChunk = serde_json::from_str(line).map_err(|e| {
+        CliError::InvalidResponse(format!("Failed to parse NDJSON response chunk: {e}"))
+    })?;
+    if let Some(error) = chunk.error {
+        return Err(CliError::InvalidResponse(format!(
+            "Ollama stream error: {error}"
+        )));
     }
+    if chunk.done && chunk.response.is_empty() {
+        return Ok(StreamLine::Done);
+    }
+    Ok(StreamLine::Text(chunk.response))
 }
 
 #[cfg(test)]
@@ -90,6 +363,93 @@ mod tests {
     #[test]
     fn test_ollama_provider_supports_streaming() {
         let provider = OllamaProvider::new("http://localhost:11434/api/generate".to_string());
-        assert!(!provider.supports_streaming()); // Default implementation
+        assert!(provider.supports_streaming());
+    }
+
+    #[test]
+    fn test_parse_single_object_body() {
+        let parsed = parse_ollama_body(r#"{"response": "hello", "done": true}"#).unwrap();
+        assert_eq!(parsed.content, "hello");
+        assert_eq!(parsed.aggregated_chunks, None);
+    }
+
+    #[test]
+    fn test_parse_ndjson_body_aggregates_chunks() {
+        let body = concat!(
+            "{\"response\": \"Hel\", \"done\": false}\n",
+            "{\"response\": \"lo\", \"done\": false}\n",
+            "{\"response\": \"\", \"done\": true, \"total_duration\": 123}\n",
+        );
+        let parsed = parse_ollama_body(body).unwrap();
+        assert_eq!(parsed.content, "Hello");
+        assert_eq!(parsed.aggregated_chunks, Some(3));
+        assert_eq!(parsed.usage, None);
+
+        let body = concat!(
+            "{\"response\": \"Hi\", \"done\": false}\n",
+            "{\"response\": \"\", \"done\": true, \"prompt_eval_count\": 12, \"eval_count\": 3}\n",
+        );
+        assert_eq!(
+            parse_ollama_body(body).unwrap().usage,
+            Some(TokenUsage::new(12, 3))
+        );
+    }
+
+    #[test]
+    fn test_parse_ndjson_error_chunk() {
+        let body = "{\"response\": \"a\"}\n{\"error\": \"model not loaded\"}\n";
+        let err = parse_ollama_body(body).unwrap_err();
+        assert!(err.to_string().contains("model not loaded"));
+    }
+
+    #[test]
+    fn test_chat_url_and_tool_calls() {
+        assert_eq!(
+            chat_url("http://localhost:11434/api/generate").unwrap(),
+            "http://localhost:11434/api/chat"
+        );
+        assert_eq!(
+            chat_url("http://localhost:11434/api/chat/").unwrap(),
+            "http://localhost:11434/api/chat"
+        );
+        assert!(chat_url("http://localhost:11434/v1/chat/completions").is_err());
+
+        let parsed = parse_chat_body(
+            r#"{"message": {"role": "assistant", "content": "", "tool_calls": [
+                {"function": {"name": "get_weather", "arguments": {"city": "Rome"}}}
+            ]}, "done": true}"#,
+        )
+        .unwrap();
+        assert_eq!(parsed.content, "");
+        assert_eq!(parsed.tool_calls[0].id, "call_0");
+        assert_eq!(parsed.tool_calls[0].name, "get_weather");
+        assert_eq!(parsed.tool_calls[0].arguments["city"], "Rome");
+    }
+
+    #[test]
+    fn test_model_echo_and_tag_digest() {
+        let parsed =
+            parse_ollama_body(r#"{"model": "llama3", "response": "hi", "done": true}"#).unwrap();
+        assert_eq!(parsed.model_echo.unwrap().model.as_deref(), Some("llama3"));
+
+        assert_eq!(
+            tags_url("http://localhost:11434/api/generate").as_deref(),
+            Some("http://localhost:11434/api/tags")
+        );
+        assert_eq!(tags_url("http://localhost:11434/v1/chat/completions"), None);
+
+        let body = r#"{"models": [
+            {"name": "mistral:7b", "digest": "aaa"},
+            {"name": "llama3:latest", "digest": "bbb"}
+        ]}"#;
+        assert_eq!(tag_digest(body, "llama3").as_deref(), Some("bbb"));
+        assert_eq!(tag_digest(body, "mistral:7b").as_deref(), Some("aaa"));
+        assert_eq!(tag_digest(body, "qwen2"), None);
+    }
+
+    #[test]
+    fn test_parse_invalid_single_line_body_fails() {
+        let err = parse_ollama_body(r#"{"model": "test"}"#).unwrap_err();
+        assert!(err.to_string().contains("Failed to parse response"));
     }
 }
@@ -1,12 +1,25 @@
 use crate::{
+    cassette,
     error::CliError,
-    models::{Message, OpenAIRequest, OpenAIResponse},
-    provider::{InvokeParams, LlmProvider},
+    image::ImageInput,
+    models::{
+        OpenAIContent, OpenAIContentPart, OpenAIImageUrl, OpenAIMessage, OpenAIRequest,
+        OpenAIResponse, OpenAIStreamChunk, OpenAITool, OpenAIToolCall, ToolCall,
+    },
+    provider::{
+        ChunkStream, InvokeParams, LlmProvider, ModelEcho, ProviderResponse, TokenUsage,
+        UpstreamInfo,
+    },
 };
 use async_trait::async_trait;
-use reqwest::Client;
+use reqwest::{Client, RequestBuilder, StatusCode};
 
-use super::logging::{log_request, log_response};
+use super::{
+    content_filter,
+    logging::{log_request, log_response},
+    rate_limit::rate_limited,
+    streaming::{line_stream, StreamLine},
+};
 
 /// OpenAI-compatible provider implementation
 pub struct OpenAIProvider {
@@ -26,22 +39,134 @@ impl OpenAIProvider {
 #[async_trait]
 impl LlmProvider for OpenAIProvider {
     async fn invoke(&self, params: InvokeParams<'_>) -> Result<String, CliError> {
+        self.invoke_detailed(params).await.map(|r| r.content)
+    }
+
+    async fn invoke_detailed(
+        &self,
+        params: InvokeParams<'_>,
+    ) -> Result<ProviderResponse, CliError> {
+        let req = self.request(&params, false);
+        let response = cassette::send(req, params.api_key).await?;
+
+        if !response.status.is_success() {
+            return Err(
+                rate_limited(response.status, response.retry_after, &response.body)
+                    .unwrap_or_else(|| http_error(response.status, response.body)),
+            );
+        }
+
+        // Get response body as text for logging and parsing
+        let response_text = response.body;
+        log_response(&response_text);
+
+        // Parse the response
+        let mut openai_response: OpenAIResponse = serde_json::from_str(&response_text)
+            .map_err(|e| CliError::InvalidResponse(format!("Failed to parse response: {e}")))?;
+
+        let upstream = upstream_info(&openai_response);
+        let usage = openai_response
+            .usage
+            .take()
+            .map(|u| TokenUsage::new(u.prompt_tokens, u.completion_tokens));
+        let model_echo = ModelEcho {
+            model: openai_response.model.clone(),
+            system_fingerprint: openai_response.system_fingerprint.take(),
+            service_tier: openai_response.service_tier.take(),
+            model_digest: None,
+        }
+        .non_empty();
+        if let Some(choice) = openai_response.choices.first() {
+            if let Some(filtered) = content_filter::from_finish_reason(
+                choice.finish_reason.as_deref(),
+                &choice.content_filter_results,
+            ) {
+                return Err(filtered);
+            }
+        }
+        let message = openai_response
+            .choices
+            .into_iter()
+            .next()
+            .map(|c| c.message)
+            .ok_or_else(|| CliError::InvalidResponse("No choices in response".to_string()))?;
+        let tool_calls = message
+            .tool_calls
+            .into_iter()
+            .enumerate()
+            .map(tool_call)
+            .collect::<Result<Vec<_>, _>>()?;
+        let images = message
+            .images
+            .into_iter()
+            .filter_map(response_image)
+            .collect::<Result<Vec<_>, _>>()?;
+        // Content is only optional when the model calls tools or returns images
+        let content = match message.content {
+            Some(content) => content,
+            None if !tool_calls.is_empty() || !images.is_empty() => String::new(),
+            None => {
+                return Err(CliError::InvalidResponse(
+                    "No content in response message".to_string(),
+                ))
+            }
+        };
+
+        Ok(ProviderResponse {
+            content,
+            upstream,
+            model_echo,
+            usage,
+            tool_calls,
+            images,
+            ..Default::default()
+        })
+    }
+
+    async fn invoke_stream(&self, params: InvokeParams<'_>) -> Result<ChunkStream, CliError> {
+        let req = self.request(&params, true);
+        let reply = cassette::send_streaming(req, params.api_key).await?;
+
+        if !reply.status().is_success() {
+            let (status, retry_after) = (reply.status(), reply.retry_after());
+            let body = reply.text().await?;
+            return Err(rate_limited(status, retry_after, &body)
+                .unwrap_or_else(|| http_error(status, body)));
+        }
+        Ok(line_stream(reply, decode_sse_line))
+    }
+
+    fn name(&self) -> &str {
+        "OpenAI"
+    }
+
+    fn supports_streaming(&self) -> bool {
+        true
+    }
+}
+
+impl OpenAIProvider {
+    /// Build the chat completions request (`stream` asks for SSE events)
+    fn request(&self, params: &InvokeParams<'_>, stream: bool) -> RequestBuilder {
         let request = OpenAIRequest {
             model: params.model.to_string(),
             messages: vec![
-                Message {
+                OpenAIMessage {
                     role: "system".to_string(),
-                    content: params.system_prompt.to_string(),
+                    content: OpenAIContent::Text(params.system_prompt.to_string()),
                 },
-                Message {
+                OpenAIMessage {
                     role: "user".to_string(),
-                    content: params.user_prompt.to_string(),
+                    content: user_content(params),
                 },
             ],
             temperature: params.temperature,
+            top_p: params.top_p,
             max_tokens: params.max_tokens,
             seed: params.seed,
             response_format: params.response_format.cloned(),
+            tools: params.tools.iter().map(OpenAITool::function).collect(),
+            stream: stream.then_some(true),
         };
 
         log_request(&request);
@@ -56,60 +181,219 @@ impl LlmProvider for OpenAIProvider {
             req = req.header("Authorization", format!("Bearer {key}"));
             log::debug!("Authorization header: Bearer [REDACTED]");
         }
+        req
+    }
+}
 
-        let response = req.send().await?;
-
-        if !response.status().is_success() {
-            let status = response.status();
-
-            // Special case: 401 authentication error
-            if status == 401 {
-                return Err(CliError::AuthenticationFailed(
-                    "Invalid or missing API key".to_string(),
-                ));
-            }
+/// User message content: plain text, or text followed by `image_url` parts
+fn user_content(params: &InvokeParams<'_>) -> OpenAIContent {
+    let text = params.user_prompt.to_string();
+    if params.images.is_empty() {
+        return OpenAIContent::Text(text);
+    }
+    let mut parts = vec![OpenAIContentPart::Text { text }];
+    parts.extend(
+        params
+            .images
+            .iter()
+            .map(|image| OpenAIContentPart::ImageUrl {
+                image_url: OpenAIImageUrl {
+                    url: image.data_url(),
+                },
+            }),
+    );
+    OpenAIContent::Parts(parts)
+}
 
-            let error_body = response.text().await.unwrap_or_default();
-
-            // Let the API's error message speak for itself
-            let error_msg = format!(
-                "HTTP {} error: {}\nResponse from API: {}",
-                status.as_u16(),
-                status.canonical_reason().unwrap_or("Unknown error"),
-                if error_body.is_empty() {
-                    "No details provided"
-                } else {
-                    &error_body
-                }
-            );
+/// Map a non-success HTTP status to an error
+fn http_error(status: StatusCode, error_body: String) -> CliError {
+    // Special case: 401 authentication error
+    if status == 401 {
+        return CliError::AuthenticationFailed("Invalid or missing API key".to_string());
+    }
+    if let Some(filtered) = content_filter::from_error_body(&error_body) {
+        return filtered;
+    }
 
-            return Err(CliError::InvalidResponse(error_msg));
+    // Let the API's error message speak for itself
+    CliError::InvalidResponse(format!(
+        "HTTP {} error: {}\nResponse from API: {}",
+        status.as_u16(),
+        status.canonical_reason().unwrap_or("Unknown error"),
+        if error_body.is_empty() {
+            "No details provided"
+        } else {
+            &error_body
         }
+    ))
+}
 
-        // Get response body as text for logging and parsing
-        let response_text = response.text().await?;
-        log_response(&response_text);
+/// Tool call with its JSON-encoded arguments parsed (`call_{index}` when the
+/// provider sent no id)
+fn tool_call((index, call): (usize, OpenAIToolCall)) -> Result<ToolCall, CliError> {
+    let arguments = match call.function.arguments.trim() {
+        "" => serde_json::json!({}),
+        encoded => serde_json::from_str(encoded).map_err(|e| {
+            CliError::InvalidResponse(format!(
+                "Invalid arguments for tool call '{}': {e}",
+                call.function.name
+            ))
+        })?,
+    };
+    Ok(ToolCall {
+        id: if call.id.is_empty() {
+            format!("call_{index}")
+        } else {
+            call.id
+        },
+        name: call.function.name,
+        arguments,
+    })
+}
 
-        // Parse the response
-        let openai_response: OpenAIResponse = serde_json::from_str(&response_text)
-            .map_err(|e| CliError::InvalidResponse(format!("Failed to parse response: {e}")))?;
+/// Decode one line of a server-sent events body
+fn decode_sse_line(line: &str) -> Result<StreamLine, CliError> {
+    let Some(data) = line.strip_prefix("data:") else {
+        // Comments (": keep-alive") and other fields (event:, id:)
+        return Ok(StreamLine::Skip);
+    };
+    let data = data.trim();
+    if data == "[DONE]" {
+        return Ok(StreamLine::Done);
+    }
 
-        openai_response
-            .choices
-            .first()
-            .map(|c| c.message.content.clone())
-            .ok_or_else(|| CliError::InvalidResponse("No choices in response".to_string()))
+    let chunk: OpenAIStreamChunk = serde_json::from_str(data)
+        .map_err(|e| CliError::InvalidResponse(format!("Failed to parse streaming event: {e}")))?;
+    let Some(choice) = chunk.choices.into_iter().next() else {
+        return Ok(StreamLine::Text(String::new()));
+    };
+    // Ends the stream with an error after the text received so far
+    if let Some(filtered) = content_filter::from_finish_reason(
+        choice.finish_reason.as_deref(),
+        &choice.content_filter_results,
+    ) {
+        return Err(filtered);
     }
+    Ok(StreamLine::Text(choice.delta.content.unwrap_or_default()))
+}
 
-    fn name(&self) -> &str {
-        "OpenAI"
+/// Decode a generated image; remote URLs are skipped (never fetched)
+fn response_image(part: OpenAIContentPart) -> Option<Result<ImageInput, CliError>> {
+    let OpenAIContentPart::ImageUrl { image_url } = part else {
+        return None;
+    };
+    if !image_url.url.starts_with("data:") {
+        log::warn!("Ignoring generated image returned by URL (only data: URLs are supported)");
+        return None;
     }
+    Some(
+        ImageInput::from_data_url(&image_url.url)
+            .map_err(|e| CliError::InvalidResponse(format!("Invalid image in response: {e}"))),
+    )
+}
+
+/// Extract gateway attribution from a response
+///
+/// Only reported when the response carries gateway-specific fields
+/// (`provider`, `native_finish_reason`), so direct OpenAI calls stay
+/// unchanged; a resolved model alias alone is recorded in `model_echo`.
+fn upstream_info(response: &OpenAIResponse) -> Option<UpstreamInfo> {
+    let choice = response.choices.first();
+    let native_finish_reason = choice.and_then(|c| c.native_finish_reason.clone());
+
+    if response.provider.is_none() && native_finish_reason.is_none() {
+        return None;
+    }
+
+    Some(UpstreamInfo {
+        provider: response.provider.clone(),
+        model: response.model.clone(),
+        finish_reason: choice.and_then(|c| c.finish_reason.clone()),
+        native_finish_reason,
+    })
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn parse(body: &str) -> OpenAIResponse {
+        serde_json::from_str(body).unwrap()
+    }
+
+    #[test]
+    fn test_upstream_info_from_openrouter_response() {
+        let response = parse(
+            r#"{
+                "model": "meta-llama/llama-3.1-8b-instruct",
+                "provider": "Together",
+                "choices": [{
+                    "message": {"role": "assistant", "content": "Hi"},
+                    "finish_reason": "stop",
+                    "native_finish_reason": "eos"
+                }]
+            }"#,
+        );
+
+        let upstream = upstream_info(&response).unwrap();
+        assert_eq!(upstream.provider.as_deref(), Some("Together"));
+        assert_eq!(
+            upstream.model.as_deref(),
+            Some("meta-llama/llama-3.1-8b-instruct")
+        );
+        assert_eq!(upstream.finish_reason.as_deref(), Some("stop"));
+        assert_eq!(upstream.native_finish_reason.as_deref(), Some("eos"));
+    }
+
+    #[test]
+    fn test_upstream_info_absent_for_plain_openai_response() {
+        let response = parse(
+            r#"{"model": "gpt-4", "choices": [{"message": {"role": "assistant", "content": "Hi"}, "finish_reason": "stop"}]}"#,
+        );
+        assert!(upstream_info(&response).is_none());
+    }
+
+    #[test]
+    fn test_upstream_info_absent_for_resolved_model_alias() {
+        // Direct OpenAI call for "gpt-4" served by a dated snapshot
+        let response = parse(
+            r#"{"model": "gpt-4-0613", "choices": [{"message": {"role": "assistant", "content": "Hi"}}]}"#,
+        );
+        assert!(upstream_info(&response).is_none());
+    }
+
+    #[test]
+    fn test_tool_call_arguments_parsed() {
+        let response = parse(
+            r#"{"choices": [{"message": {"role": "assistant", "content": null, "tool_calls": [
+                {"id": "call_abc", "type": "function",
+                 "function": {"name": "get_weather", "arguments": "{\"city\": \"Rome\"}"}},
+                {"type": "function", "function": {"name": "now", "arguments": ""}}
+            ]}, "finish_reason": "tool_calls"}]}"#,
+        );
+        let message = response.choices.into_iter().next().unwrap().message;
+        assert!(message.content.is_none());
+        let calls: Vec<ToolCall> = message
+            .tool_calls
+            .into_iter()
+            .enumerate()
+            .map(|call| tool_call(call).unwrap())
+            .collect();
+        assert_eq!(calls[0].id, "call_abc");
+        assert_eq!(calls[0].arguments["city"], "Rome");
+        assert_eq!(calls[1].id, "call_1");
+        assert_eq!(calls[1].arguments, serde_json::json!({}));
+
+        let malformed = OpenAIToolCall {
+            id: String::new(),
+            function: crate::models::OpenAIFunctionCall {
+                name: "get_weather".to_string(),
+                arguments: "{city".to_string(),
+            },
+        };
+        assert!(tool_call((0, malformed)).is_err());
+    }
+
     #[test]
     fn test_openai_provider_new() {
         let provider =
@@ -132,6 +416,58 @@ mod tests {
     fn test_openai_provider_supports_streaming() {
         let provider =
             OpenAIProvider::new("https://api.openai.com/v1/chat/completions".to_string());
-        assert!(!provider.supports_streaming()); // Default implementation
+        assert!(provider.supports_streaming());
+    }
+
+    #[test]
+    fn test_decode_sse_lines() {
+        let text = |line| match decode_sse_line(line).unwrap() {
+            StreamLine::Text(t) => t,
+            _ => panic!("Expected text for {line}"),
+        };
+        assert_eq!(
+            text(r#"data: {"choices":[{"delta":{"content":"Hel"}}]}"#),
+            "Hel"
+        );
+        assert_eq!(
+            text(r#"data: {"choices":[{"delta":{"role":"assistant"}}]}"#),
+            ""
+        );
+        assert!(matches!(
+            decode_sse_line("data: [DONE]").unwrap(),
+            StreamLine::Done
+        ));
+        assert!(matches!(
+            decode_sse_line(": keep-alive").unwrap(),
+            StreamLine::Skip
+        ));
+        assert!(decode_sse_line("data: {oops").is_err());
+    }
+
+    #[test]
+    fn test_response_images_decoded() {
+        let response = parse(
+            r#"{"choices": [{"message": {"role": "assistant", "content": null, "images": [
+                {"type": "image_url", "image_url": {"url": "data:image/gif;base64,R0lGODlhAQABAA=="}},
+                {"type": "image_url", "image_url": {"url": "https://cdn.example.com/cat.png"}}
+            ]}}]}"#,
+        );
+        let message = response.choices.into_iter().next().unwrap().message;
+        let images: Vec<ImageInput> = message
+            .images
+            .into_iter()
+            .filter_map(response_image)
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(images.len(), 1);
+        assert_eq!(images[0].media_type, "image/gif");
+
+        let svg = OpenAIContentPart::ImageUrl {
+            image_url: OpenAIImageUrl {
+                url: "data:image/svg+xml;base64,PHN2Zy8+".to_string(),
+            },
+        };
+        let err = response_image(svg).unwrap().unwrap_err();
+        assert_eq!(err.code(), "INVALID_RESPONSE");
     }
 }
@@ -0,0 +1,56 @@
+//! Rate-limited (HTTP 429) responses
+//!
+//! Every HTTP provider reports a 429 as [`CliError::RateLimited`], carrying
+//! the delay of the `Retry-After` header when the API sent one, so callers
+//! such as the batch scheduler can wait out the window instead of failing.
+
+use crate::error::CliError;
+use reqwest::StatusCode;
+use std::time::Duration;
+
+/// Error for a rate-limited response (None for any other status)
+pub(crate) fn rate_limited(
+    status: StatusCode,
+    retry_after: Option<Duration>,
+    body: &str,
+) -> Option<CliError> {
+    if status != StatusCode::TOO_MANY_REQUESTS {
+        return None;
+    }
+    let details = if body.is_empty() {
+        "No details provided"
+    } else {
+        body
+    };
+    let message = match retry_after {
+        Some(delay) => format!("HTTP 429 (retry after {}s): {details}", delay.as_secs()),
+        None => format!("HTTP 429: {details}"),
+    };
+    Some(CliError::RateLimited {
+        message,
+        retry_after,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rate_limited() {
+        let err = rate_limited(
+            StatusCode::TOO_MANY_REQUESTS,
+            Some(Duration::from_secs(30)),
+            "slow down",
+        )
+        .unwrap();
+        assert_eq!(err.code(), "RATE_LIMITED");
+        assert!(err.to_string().contains("retry after 30s"), "{err}");
+        assert!(matches!(
+            err,
+            CliError::RateLimited { retry_after: Some(d), .. } if d.as_secs() == 30
+        ));
+
+        assert!(rate_limited(StatusCode::SERVICE_UNAVAILABLE, None, "").is_none());
+    }
+}
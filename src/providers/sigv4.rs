@@ -0,0 +1,200 @@
+//! AWS Signature Version 4 request signing
+//!
+//! Implements the subset needed for JSON POST requests to AWS service
+//! endpoints: no query string, and `host` plus `x-amz-*` as the signed
+//! headers. See <https://docs.aws.amazon.com/IAM/latest/UserGuide/reference_sigv-create-signed-request.html>.
+
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use reqwest::Url;
+use sha2::{Digest, Sha256};
+
+const ALGORITHM: &str = "AWS4-HMAC-SHA256";
+
+/// AWS credentials used for signing
+#[derive(Clone)]
+pub(crate) struct AwsCredentials {
+    pub access_key_id: String,
+    pub secret_access_key: String,
+    pub session_token: Option<String>,
+}
+
+/// Headers to add to a signed request (lowercase names)
+pub(crate) fn sign(
+    method: &str,
+    url: &Url,
+    body: &[u8],
+    credentials: &AwsCredentials,
+    region: &str,
+    service: &str,
+    time: DateTime<Utc>,
+) -> Vec<(&'static str, String)> {
+    let amz_date = time.format("%Y%m%dT%H%M%SZ").to_string();
+    let date = &amz_date[..8];
+
+    let mut headers = vec![("host", host(url)), ("x-amz-date", amz_date.clone())];
+    if let Some(token) = &credentials.session_token {
+        headers.push(("x-amz-security-token", token.clone()));
+    }
+    let signed_headers = headers
+        .iter()
+        .map(|(name, _)| *name)
+        .collect::<Vec<_>>()
+        .join(";");
+    let canonical_headers: String = headers
+        .iter()
+        .map(|(name, value)| format!("{name}:{}\n", value.trim()))
+        .collect();
+
+    let canonical_request = format!(
+        "{method}\n{}\n\n{canonical_headers}\n{signed_headers}\n{}",
+        canonical_uri(url.path()),
+        hex_sha256(body)
+    );
+    let scope = format!("{date}/{region}/{service}/aws4_request");
+    let string_to_sign = format!(
+        "{ALGORITHM}\n{amz_date}\n{scope}\n{}",
+        hex_sha256(canonical_request.as_bytes())
+    );
+
+    let key = [date, region, service, "aws4_request"].iter().fold(
+        format!("AWS4{}", credentials.secret_access_key).into_bytes(),
+        |key, part| hmac_sha256(&key, part.as_bytes()),
+    );
+    let signature = hex(&hmac_sha256(&key, string_to_sign.as_bytes()));
+
+    headers.remove(0); // reqwest sets Host itself
+    headers.push((
+        "authorization",
+        format!(
+            "{ALGORITHM} Credential={}/{scope}, SignedHeaders={signed_headers}, Signature={signature}",
+            credentials.access_key_id
+        ),
+    ));
+    headers
+}
+
+/// Percent-encode everything except RFC 3986 unreserved characters
+pub(crate) fn uri_encode(value: &str) -> String {
+    value
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{b:02X}"),
+        })
+        .collect()
+}
+
+/// Each path segment encoded again (non-S3 services sign the encoded path)
+fn canonical_uri(path: &str) -> String {
+    if path.is_empty() {
+        return "/".to_string();
+    }
+    path.split('/')
+        .map(uri_encode)
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+fn host(url: &Url) -> String {
+    let host = url.host_str().unwrap_or_default();
+    match url.port() {
+        Some(port) => format!("{host}:{port}"),
+        None => host.to_string(),
+    }
+}
+
+fn hmac_sha256(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts any key length");
+    mac.update(data);
+    mac.finalize().into_bytes().to_vec()
+}
+
+fn hex_sha256(data: &[u8]) -> String {
+    hex(&Sha256::digest(data))
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn example_credentials() -> AwsCredentials {
+        AwsCredentials {
+            access_key_id: "AKIDEXAMPLE".to_string(),
+            secret_access_key: "wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY".to_string(),
+            session_token: None,
+        }
+    }
+
+    #[test]
+    fn test_aws_test_suite_get_vanilla() {
+        // "get-vanilla" from the AWS SigV4 test suite
+        let url = Url::parse("https://example.amazonaws.com/").unwrap();
+        let time = Utc.with_ymd_and_hms(2015, 8, 30, 12, 36, 0).unwrap();
+        let headers = sign(
+            "GET",
+            &url,
+            b"",
+            &example_credentials(),
+            "us-east-1",
+            "service",
+            time,
+        );
+
+        assert_eq!(
+            headers,
+            vec![
+                ("x-amz-date", "20150830T123600Z".to_string()),
+                (
+                    "authorization",
+                    "AWS4-HMAC-SHA256 Credential=AKIDEXAMPLE/20150830/us-east-1/service/aws4_request, \
+                     SignedHeaders=host;x-amz-date, \
+                     Signature=5fa00fa31553b73ebf1942676e86291e8372ff2a2260956d9b8aae1d763fbf31"
+                        .to_string()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_session_token_is_signed() {
+        let url = Url::parse("https://bedrock-runtime.us-east-1.amazonaws.com/").unwrap();
+        let credentials = AwsCredentials {
+            session_token: Some("token".to_string()),
+            ..example_credentials()
+        };
+        let headers = sign(
+            "POST",
+            &url,
+            b"{}",
+            &credentials,
+            "us-east-1",
+            "bedrock",
+            Utc::now(),
+        );
+
+        assert!(headers.contains(&("x-amz-security-token", "token".to_string())));
+        let (_, authorization) = headers.last().unwrap();
+        assert!(authorization.contains("SignedHeaders=host;x-amz-date;x-amz-security-token"));
+    }
+
+    #[test]
+    fn test_canonical_uri_double_encodes() {
+        assert_eq!(
+            uri_encode("anthropic.claude-v2:1"),
+            "anthropic.claude-v2%3A1"
+        );
+        assert_eq!(
+            canonical_uri("/model/anthropic.claude-v2%3A1/converse"),
+            "/model/anthropic.claude-v2%253A1/converse"
+        );
+        assert_eq!(canonical_uri(""), "/");
+    }
+}
@@ -0,0 +1,145 @@
+//! Line-oriented streaming bodies (OpenAI SSE, Ollama NDJSON)
+
+use crate::{cassette::StreamReply, error::CliError, provider::ChunkStream};
+use futures::stream;
+use std::collections::VecDeque;
+
+/// Meaning of one non-empty body line
+pub(crate) enum StreamLine {
+    /// Generated text (may be empty, e.g. a role-only delta)
+    Text(String),
+    /// Keep-alive, comment or metadata line
+    Skip,
+    /// End of the generation
+    Done,
+}
+
+struct LineState<F> {
+    reply: StreamReply,
+    buffer: Vec<u8>,
+    pending: VecDeque<Result<String, CliError>>,
+    decode: F,
+    finished: bool,
+}
+
+impl<F> LineState<F>
+where
+    F: FnMut(&str) -> Result<StreamLine, CliError>,
+{
+    /// Decode the complete lines in the buffer (all of it at end of body)
+    fn drain_lines(&mut self, end_of_body: bool) {
+        while !self.finished {
+            let line: Vec<u8> = match self.buffer.iter().position(|&b| b == b'\n') {
+                Some(pos) => self.buffer.drain(..=pos).collect(),
+                None if end_of_body && !self.buffer.is_empty() => std::mem::take(&mut self.buffer),
+                None => break,
+            };
+            let line = String::from_utf8_lossy(&line);
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            match (self.decode)(line) {
+                Ok(StreamLine::Text(text)) if !text.is_empty() => self.pending.push_back(Ok(text)),
+                Ok(StreamLine::Text(_) | StreamLine::Skip) => {}
+                Ok(StreamLine::Done) => self.finished = true,
+                Err(e) => {
+                    self.pending.push_back(Err(e));
+                    self.finished = true;
+                }
+            }
+        }
+        if end_of_body {
+            self.finished = true;
+        }
+    }
+}
+
+/// Stream the text decoded from each line of `reply`'s body
+///
+/// Lines are split on `\n` across network chunks, so multi-byte characters
+/// and JSON objects split between reads are reassembled before decoding.
+pub(crate) fn line_stream<F>(reply: StreamReply, decode: F) -> ChunkStream
+where
+    F: FnMut(&str) -> Result<StreamLine, CliError> + Send + 'static,
+{
+    let state = LineState {
+        reply,
+        buffer: Vec::new(),
+        pending: VecDeque::new(),
+        decode,
+        finished: false,
+    };
+
+    Box::pin(stream::unfold(state, |mut state| async move {
+        loop {
+            if let Some(item) = state.pending.pop_front() {
+                return Some((item, state));
+            }
+            if state.finished {
+                return None;
+            }
+            match state.reply.next_bytes().await {
+                Ok(Some(bytes)) => {
+                    state.buffer.extend_from_slice(&bytes);
+                    state.drain_lines(false);
+                }
+                Ok(None) => state.drain_lines(true),
+                Err(e) => {
+                    state.finished = true;
+                    return Some((Err(e), state));
+                }
+            }
+        }
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cassette::HttpReply;
+    use futures::StreamExt;
+    use reqwest::StatusCode;
+
+    fn buffered(body: &str) -> StreamReply {
+        StreamReply::Buffered(HttpReply {
+            status: StatusCode::OK,
+            body: body.to_string(),
+            retry_after: None,
+        })
+    }
+
+    fn decode(line: &str) -> Result<StreamLine, CliError> {
+        match line {
+            "done" => Ok(StreamLine::Done),
+            "bad" => Err(CliError::InvalidResponse("bad line".to_string())),
+            l if l.starts_with(':') => Ok(StreamLine::Skip),
+            l => Ok(StreamLine::Text(l.to_string())),
+        }
+    }
+
+    async fn collect(body: &str) -> Vec<Result<String, CliError>> {
+        line_stream(buffered(body), decode).collect().await
+    }
+
+    #[tokio::test]
+    async fn test_lines_until_done() {
+        let chunks = collect("a\n: ping\n\nb\ndone\nc\n").await;
+        let texts: Vec<String> = chunks.into_iter().map(Result::unwrap).collect();
+        assert_eq!(texts, vec!["a", "b"]);
+    }
+
+    #[tokio::test]
+    async fn test_last_line_without_newline() {
+        let chunks = collect("a\nb").await;
+        assert_eq!(chunks.len(), 2);
+        assert_eq!(chunks[1].as_ref().unwrap(), "b");
+    }
+
+    #[tokio::test]
+    async fn test_decode_error_ends_stream() {
+        let chunks = collect("a\nbad\nb\n").await;
+        assert_eq!(chunks.len(), 2);
+        assert!(chunks[1].is_err());
+    }
+}
@@ -0,0 +1,297 @@
+//! Client-side rate limiting
+//!
+//! Keeps evaluations under a provider's request and token rate limits (e.g.
+//! OpenAI organization limits during batch runs) instead of relying on HTTP
+//! 429 responses. One token bucket per limit and provider refills
+//! continuously; [`LlmClient`](crate::LlmClient) waits for both buckets
+//! before each call:
+//!
+//! ```toml
+//! [rate_limit.openai]
+//! requests_per_minute = 500
+//! tokens_per_minute = 150000   # Prompt estimate + max_tokens per call
+//! burst_requests = 20          # Bucket capacities (default: the per-minute rate)
+//! burst_tokens = 30000
+//!
+//! [rate_limit.anthropic]
+//! requests_per_minute = 50
+//! ```
+//!
+//! A call reserves its estimated prompt tokens plus `max_tokens`, as providers
+//! count them against the limit; once the response reports its actual usage,
+//! the unused part of the reservation goes back to the bucket. Reservations
+//! are served in arrival order, so concurrent evaluations sharing the
+//! [`RateLimiter`] (through an `Arc`) queue instead of racing.
+
+use crate::{error::CliError, provider::InvokeParams, token_estimator::estimate_tokens, Provider};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+/// Limits of one provider (`[rate_limit.<provider>]`)
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ProviderRateLimit {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub requests_per_minute: Option<usize>,
+
+    /// Refill rate of the token bucket (prompt estimate + `max_tokens`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tokens_per_minute: Option<usize>,
+
+    /// Request bucket capacity (default: `requests_per_minute`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub burst_requests: Option<usize>,
+
+    /// Token bucket capacity (default: `tokens_per_minute`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub burst_tokens: Option<usize>,
+}
+
+/// Rate limits per provider name (`[rate_limit]`)
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct RateLimitConfig {
+    /// Keyed by `ollama`, `openai`, `anthropic`, `bedrock` or `mock`
+    pub providers: BTreeMap<String, ProviderRateLimit>,
+}
+
+impl RateLimitConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        for (name, limit) in &self.providers {
+            if !Provider::NAMES.contains(&name.as_str()) {
+                return Err(format!(
+                    "rate_limit: unknown provider '{name}'. Valid values: {}",
+                    Provider::NAMES.join(", ")
+                ));
+            }
+            if limit.requests_per_minute.is_none() && limit.tokens_per_minute.is_none() {
+                return Err(format!(
+                    "rate_limit.{name}: set requests_per_minute and/or tokens_per_minute"
+                ));
+            }
+            let values = [
+                limit.requests_per_minute,
+                limit.tokens_per_minute,
+                limit.burst_requests,
+                limit.burst_tokens,
+            ];
+            if values.contains(&Some(0)) {
+                return Err(format!("rate_limit.{name}: limits must be >= 1"));
+            }
+        }
+        Ok(())
+    }
+}
+
+struct Bucket {
+    capacity: f64,
+    per_second: f64,
+    /// Negative while reservations wait for the refill
+    level: f64,
+    updated: Instant,
+}
+
+impl Bucket {
+    fn new(per_minute: usize, burst: Option<usize>) -> Self {
+        let capacity = burst.unwrap_or(per_minute) as f64;
+        Self {
+            capacity,
+            per_second: per_minute as f64 / 60.0,
+            level: capacity,
+            updated: Instant::now(),
+        }
+    }
+
+    fn refill(&mut self) {
+        let now = Instant::now();
+        let elapsed = now.duration_since(self.updated).as_secs_f64();
+        self.level = (self.level + elapsed * self.per_second).min(self.capacity);
+        self.updated = now;
+    }
+
+    /// Take `amount` and return how long until the bucket covers it
+    ///
+    /// Amounts above the capacity take a full bucket, so oversized calls are
+    /// delayed rather than blocked forever.
+    fn reserve(&mut self, amount: f64) -> Duration {
+        self.refill();
+        self.level -= amount.min(self.capacity);
+        if self.level >= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(-self.level / self.per_second)
+        }
+    }
+
+    fn refund(&mut self, amount: f64) {
+        self.refill();
+        self.level = (self.level + amount).min(self.capacity);
+    }
+}
+
+struct ProviderBuckets {
+    requests: Option<Bucket>,
+    tokens: Option<Bucket>,
+}
+
+/// Request and token buckets per provider, shared across evaluations
+pub struct RateLimiter {
+    config: RateLimitConfig,
+    buckets: HashMap<Provider, Mutex<ProviderBuckets>>,
+}
+
+impl std::fmt::Debug for RateLimiter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RateLimiter")
+            .field("config", &self.config)
+            .finish()
+    }
+}
+
+impl RateLimiter {
+    pub fn from_config(config: &RateLimitConfig) -> Result<Self, CliError> {
+        config.validate().map_err(CliError::InvalidArguments)?;
+        let buckets = config
+            .providers
+            .iter()
+            .filter_map(|(name, limit)| {
+                let provider = Provider::from_name(name)?;
+                let buckets = ProviderBuckets {
+                    requests: limit
+                        .requests_per_minute
+                        .map(|rate| Bucket::new(rate, limit.burst_requests)),
+                    tokens: limit
+                        .tokens_per_minute
+                        .map(|rate| Bucket::new(rate, limit.burst_tokens)),
+                };
+                Some((provider, Mutex::new(buckets)))
+            })
+            .collect();
+        Ok(Self {
+            config: config.clone(),
+            buckets,
+        })
+    }
+
+    pub fn config(&self) -> &RateLimitConfig {
+        &self.config
+    }
+
+    /// Tokens a call counts against the limit: prompt estimate + `max_tokens`
+    pub fn reserved_tokens(params: &InvokeParams<'_>) -> usize {
+        estimate_tokens(params.system_prompt)
+            + estimate_tokens(params.user_prompt)
+            + params.max_tokens.unwrap_or(0) as usize
+    }
+
+    /// Reserve one request and `tokens` for `provider`, waiting until the
+    /// buckets cover them; returns the time waited
+    pub async fn acquire(&self, provider: Provider, tokens: usize) -> Duration {
+        let Some(buckets) = self.buckets.get(&provider) else {
+            return Duration::ZERO;
+        };
+        let wait = {
+            let mut buckets = buckets.lock().unwrap_or_else(|e| e.into_inner());
+            let requests = buckets
+                .requests
+                .as_mut()
+                .map_or(Duration::ZERO, |bucket| bucket.reserve(1.0));
+            let tokens = buckets
+                .tokens
+                .as_mut()
+                .map_or(Duration::ZERO, |bucket| bucket.reserve(tokens as f64));
+            requests.max(tokens)
+        };
+        if !wait.is_zero() {
+            log::info!(
+                "Rate limit of {} reached; waiting {}ms",
+                provider.as_str(),
+                wait.as_millis()
+            );
+            tokio::time::sleep(wait).await;
+        }
+        wait
+    }
+
+    /// Return the unused part of a reservation once the actual usage is known
+    pub fn settle(&self, provider: Provider, reserved: usize, actual: u64) {
+        let Some(buckets) = self.buckets.get(&provider) else {
+            return;
+        };
+        let unused = reserved as f64 - actual as f64;
+        if unused > 0.0 {
+            if let Some(bucket) = buckets
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .tokens
+                .as_mut()
+            {
+                bucket.refund(unused);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limiter(toml: &str) -> RateLimiter {
+        let config: RateLimitConfig = toml::from_str(toml).unwrap();
+        RateLimiter::from_config(&config).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_request_bucket_delays_beyond_burst() {
+        // 10 requests/s, burst of 2
+        let limiter = limiter("[openai]\nrequests_per_minute = 600\nburst_requests = 2");
+        assert!(limiter.acquire(Provider::OpenAI, 0).await.is_zero());
+        assert!(limiter.acquire(Provider::OpenAI, 0).await.is_zero());
+        let waited = limiter.acquire(Provider::OpenAI, 0).await;
+        assert!(waited > Duration::from_millis(50), "{waited:?}");
+
+        // Providers without limits never wait
+        assert!(limiter.acquire(Provider::Ollama, 1_000_000).await.is_zero());
+    }
+
+    #[test]
+    fn test_token_bucket_reserves_and_refunds() {
+        let limiter = limiter("[openai]\ntokens_per_minute = 6000");
+        let mut buckets = limiter.buckets[&Provider::OpenAI].lock().unwrap();
+        let bucket = buckets.tokens.as_mut().unwrap();
+        assert!(bucket.reserve(5000.0).is_zero());
+        // 2000 more leaves a 1000-token deficit at 100 tokens/s
+        let wait = bucket.reserve(2000.0);
+        assert!(wait > Duration::from_millis(9900) && wait <= Duration::from_secs(10));
+        drop(buckets);
+
+        limiter.settle(Provider::OpenAI, 2000, 500);
+        let buckets = limiter.buckets[&Provider::OpenAI].lock().unwrap();
+        assert!(buckets.tokens.as_ref().unwrap().level >= 499.0);
+
+        // Calls larger than the bucket take it whole instead of waiting forever
+        let mut buckets = buckets;
+        let bucket = buckets.tokens.as_mut().unwrap();
+        bucket.level = bucket.capacity;
+        assert!(bucket.reserve(1_000_000.0).is_zero());
+    }
+
+    #[test]
+    fn test_validate() {
+        let config: RateLimitConfig = toml::from_str("[openia]\nrequests_per_minute = 1").unwrap();
+        assert!(config
+            .validate()
+            .unwrap_err()
+            .contains("unknown provider 'openia'"));
+        let config: RateLimitConfig = toml::from_str("[openai]\nburst_requests = 5").unwrap();
+        assert!(config
+            .validate()
+            .unwrap_err()
+            .contains("requests_per_minute"));
+        let config: RateLimitConfig = toml::from_str("[openai]\ntokens_per_minute = 0").unwrap();
+        assert!(config.validate().unwrap_err().contains(">= 1"));
+    }
+}
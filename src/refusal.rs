@@ -0,0 +1,153 @@
+//! Refusal detection
+//!
+//! Flags responses in which the model declines the request ("I can't help
+//! with that") so evaluation pipelines can tell refusals from genuine answers.
+//! Only the start of the response is scanned: refusals lead with the
+//! decline, while long answers may quote such phrases further down.
+//!
+//! ```toml
+//! [refusal_detection]
+//! on_refusal = "outcome"   # "warn" (default) or "outcome" (REFUSAL status)
+//! patterns = ["(?i)^unable to comply"]  # Extra regexes
+//! builtin_patterns = true  # Common English refusal phrasings (default)
+//! scan_chars = 400         # Characters scanned from the start (default)
+//! ```
+
+use crate::error::CliError;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+/// Common refusal phrasings (straight or curly apostrophes)
+static BUILTIN_PATTERNS: Lazy<Vec<Regex>> = Lazy::new(|| {
+    [
+        r"(?i)^\W*(?:i['’]m|i am)\s+(?:sorry|afraid)\b",
+        r"(?i)\bi\s*(?:can(?:no|['’])t|cannot|won['’]t|will not|(?:am|['’]m) (?:not able|unable) to)\s+(?:help|assist|provide|comply|do that|fulfill|answer|support|create|generate|share|engage)\b",
+        r"(?i)\bas an ai\b.{0,60}\b(?:can(?:no|['’])t|cannot|unable|not able)\b",
+        r"(?i)\bi must (?:decline|refuse)\b",
+        r"(?i)\b(?:this|that|your) request (?:violates|goes against|is against)\b",
+    ]
+    .iter()
+    .map(|p| Regex::new(p).expect("valid refusal pattern"))
+    .collect()
+});
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_scan_chars() -> usize {
+    400
+}
+
+/// What to do when a response is a refusal
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RefusalAction {
+    /// Keep the success output and add a `REFUSAL_DETECTED` warning
+    #[default]
+    Warn,
+    /// Report a distinct `REFUSAL` outcome (status `"refusal"`, response kept)
+    Outcome,
+}
+
+/// Refusal detection settings (`[refusal_detection]`)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RefusalConfig {
+    #[serde(default)]
+    pub on_refusal: RefusalAction,
+
+    /// Additional regexes matched against the scanned text
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub patterns: Vec<String>,
+
+    /// Include the built-in English phrasings (default: true)
+    #[serde(default = "default_true")]
+    pub builtin_patterns: bool,
+
+    /// Characters scanned from the start of the response (default: 400)
+    #[serde(default = "default_scan_chars")]
+    pub scan_chars: usize,
+}
+
+impl RefusalConfig {
+    pub fn new(on_refusal: RefusalAction) -> Self {
+        Self {
+            on_refusal,
+            patterns: Vec::new(),
+            builtin_patterns: true,
+            scan_chars: default_scan_chars(),
+        }
+    }
+
+    /// Matched refusal phrase, if the response is a refusal
+    pub fn detect(&self, response: &str) -> Result<Option<String>, CliError> {
+        let scanned = match response.char_indices().nth(self.scan_chars) {
+            Some((end, _)) => &response[..end],
+            None => response,
+        };
+
+        for pattern in &self.patterns {
+            let regex = Regex::new(pattern).map_err(|e| {
+                CliError::InvalidArguments(format!("Invalid refusal pattern '{pattern}': {e}"))
+            })?;
+            if let Some(m) = regex.find(scanned) {
+                return Ok(Some(m.as_str().to_string()));
+            }
+        }
+        if self.builtin_patterns {
+            if let Some(m) = BUILTIN_PATTERNS.iter().find_map(|re| re.find(scanned)) {
+                return Ok(Some(m.as_str().to_string()));
+            }
+        }
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builtin_patterns() {
+        let config = RefusalConfig::new(RefusalAction::Warn);
+        for refusal in [
+            "I'm sorry, but I can't help with that.",
+            "I can’t assist with creating malware.",
+            "As an AI language model, I cannot provide medical advice.",
+            "I must decline this request.",
+        ] {
+            assert!(config.detect(refusal).unwrap().is_some(), "{refusal}");
+        }
+        for answer in [
+            "Refunds take 5 days.",
+            "The error says it cannot connect to the database.",
+        ] {
+            assert!(config.detect(answer).unwrap().is_none(), "{answer}");
+        }
+    }
+
+    #[test]
+    fn test_only_start_is_scanned() {
+        let mut config = RefusalConfig::new(RefusalAction::Warn);
+        let response = format!("{} I can't help with that.", "Answer. ".repeat(10));
+        assert!(config.detect(&response).unwrap().is_some());
+        config.scan_chars = 20;
+        assert!(config.detect(&response).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_custom_patterns() {
+        let mut config = RefusalConfig::new(RefusalAction::Outcome);
+        config.builtin_patterns = false;
+        config.patterns = vec!["(?i)^unable to comply".to_string()];
+        assert_eq!(
+            config.detect("Unable to comply, sorry").unwrap().as_deref(),
+            Some("Unable to comply")
+        );
+        assert!(config.detect("I'm sorry, no").unwrap().is_none());
+
+        config.patterns = vec!["[unclosed".to_string()];
+        assert!(config.detect("x").is_err());
+    }
+}
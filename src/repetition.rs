@@ -0,0 +1,139 @@
+//! Repetition detection
+//!
+//! Flags degenerate responses in which the model loops over the same phrase,
+//! a common failure of local models at low temperature. The score is the
+//! share of word n-grams that repeat an earlier n-gram: ordinary prose stays
+//! near 0, a loop approaches 1.
+//!
+//! ```toml
+//! [repetition_detection]
+//! ngram_size = 4               # Words per n-gram (default)
+//! threshold = 0.5              # Repeated n-gram ratio that flags a loop (default)
+//! min_words = 20               # Shorter responses are not checked (default)
+//! max_retries = 1              # Re-invoke with adjusted sampling (default: 0)
+//! retry_temperature_step = 0.3 # Temperature added per retry (default)
+//! ```
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+fn default_ngram_size() -> usize {
+    4
+}
+
+fn default_threshold() -> f64 {
+    0.5
+}
+
+fn default_min_words() -> usize {
+    20
+}
+
+fn default_retry_temperature_step() -> f32 {
+    0.3
+}
+
+/// Highest temperature a retry will use
+const MAX_RETRY_TEMPERATURE: f32 = 2.0;
+
+/// Repetition detection settings (`[repetition_detection]`)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RepetitionConfig {
+    /// Words per n-gram (default: 4)
+    #[serde(default = "default_ngram_size")]
+    pub ngram_size: usize,
+
+    /// Repeated n-gram ratio above which a response is flagged (default: 0.5)
+    #[serde(default = "default_threshold")]
+    pub threshold: f64,
+
+    /// Responses with fewer words are never flagged (default: 20)
+    #[serde(default = "default_min_words")]
+    pub min_words: usize,
+
+    /// Re-invocations with adjusted sampling after a flagged response (default: 0)
+    #[serde(default)]
+    pub max_retries: u32,
+
+    /// Temperature added on each retry, capped at 2.0 (default: 0.3)
+    #[serde(default = "default_retry_temperature_step")]
+    pub retry_temperature_step: f32,
+}
+
+impl Default for RepetitionConfig {
+    fn default() -> Self {
+        Self {
+            ngram_size: default_ngram_size(),
+            threshold: default_threshold(),
+            min_words: default_min_words(),
+            max_retries: 0,
+            retry_temperature_step: default_retry_temperature_step(),
+        }
+    }
+}
+
+impl RepetitionConfig {
+    /// Repeated n-gram ratio, if the response is degenerate
+    pub fn detect(&self, response: &str) -> Option<f64> {
+        let words: Vec<String> = response
+            .split_whitespace()
+            .map(|w| w.to_lowercase())
+            .collect();
+        if words.len() < self.min_words.max(self.ngram_size) {
+            return None;
+        }
+        let ratio = repetition_ratio(&words, self.ngram_size);
+        (ratio > self.threshold).then_some(ratio)
+    }
+
+    /// Temperature for the given retry (1-based)
+    pub fn retry_temperature(&self, base: f32, attempt: u32) -> f32 {
+        (base + self.retry_temperature_step * attempt as f32).min(MAX_RETRY_TEMPERATURE)
+    }
+}
+
+/// Share of n-grams that duplicate an earlier n-gram
+fn repetition_ratio(words: &[String], n: usize) -> f64 {
+    let total = words.len() + 1 - n;
+    let unique: HashSet<&[String]> = words.windows(n).collect();
+    (total - unique.len()) as f64 / total as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detects_loop() {
+        let config = RepetitionConfig::default();
+        let looping = format!("Sure. {}", "The answer is the answer is ".repeat(15));
+        let ratio = config.detect(&looping).expect("loop not detected");
+        assert!(ratio > 0.9, "{ratio}");
+
+        let prose = "Refunds are processed within five business days of receiving the \
+            returned item. Contact support if the amount has not arrived after two \
+            weeks, and include your order number so the team can trace the payment.";
+        assert_eq!(config.detect(prose), None);
+    }
+
+    #[test]
+    fn test_short_responses_ignored() {
+        let config = RepetitionConfig::default();
+        assert_eq!(config.detect("yes yes yes yes yes yes"), None);
+
+        let config = RepetitionConfig {
+            min_words: 0,
+            ..Default::default()
+        };
+        assert!(config.detect("yes yes yes yes yes yes").is_some());
+        assert_eq!(config.detect("yes"), None);
+    }
+
+    #[test]
+    fn test_retry_temperature_capped() {
+        let config = RepetitionConfig::default();
+        assert!((config.retry_temperature(0.0, 1) - 0.3).abs() < f32::EPSILON);
+        assert!((config.retry_temperature(0.2, 2) - 0.8).abs() < 1e-6);
+        assert_eq!(config.retry_temperature(1.9, 1), 2.0);
+    }
+}
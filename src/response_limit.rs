@@ -0,0 +1,119 @@
+//! Size limit of HTTP response bodies
+//!
+//! Every response from a provider, LLM-based guardrail or context probe is
+//! read through [`crate::cassette`], which counts the body bytes as they
+//! arrive and stops reading once they exceed the evaluation's
+//! `max_response_bytes`. A misbehaving endpoint returning unbounded data
+//! fails the call with `CliError::ResponseTooLarge` instead of exhausting
+//! memory before output guardrails see the response:
+//!
+//! ```toml
+//! max_response_bytes = 20971520     # Default: 20MB
+//! ```
+//!
+//! A `Content-Length` above the limit is refused before the body is read.
+//! Streamed responses count every chunk, so a stream is cut off mid-way.
+//!
+//! The limit is scoped to the task running an evaluation (see [`scope`]), so
+//! concurrent evaluations (batch items, tenants) can use different limits.
+
+use crate::{constants::http::DEFAULT_MAX_RESPONSE_BYTES, error::CliError};
+use std::future::Future;
+
+tokio::task_local! {
+    static MAX_RESPONSE_BYTES: usize;
+}
+
+/// Run `future` with `limit` applying to the HTTP responses it reads
+pub(crate) async fn scope<F: Future>(limit: usize, future: F) -> F::Output {
+    MAX_RESPONSE_BYTES.scope(limit, future).await
+}
+
+/// Response body limit of the current evaluation (the default outside one)
+fn max_response_bytes() -> usize {
+    MAX_RESPONSE_BYTES
+        .try_with(|limit| *limit)
+        .unwrap_or(DEFAULT_MAX_RESPONSE_BYTES)
+}
+
+/// Body bytes read from one response, checked against the limit
+#[derive(Debug)]
+pub(crate) struct BodyLimit {
+    url: String,
+    limit: usize,
+    read: usize,
+}
+
+impl BodyLimit {
+    /// Start counting the body of `response`, refusing it if its
+    /// `Content-Length` already exceeds the limit
+    pub fn start(response: &reqwest::Response) -> Result<Self, CliError> {
+        let limit = Self {
+            url: response.url().to_string(),
+            limit: max_response_bytes(),
+            read: 0,
+        };
+        match response.content_length() {
+            Some(length) if length > limit.limit as u64 => Err(limit.exceeded(length)),
+            _ => Ok(limit),
+        }
+    }
+
+    /// Count a chunk of `len` bytes
+    pub fn add(&mut self, len: usize) -> Result<(), CliError> {
+        self.read += len;
+        if self.read > self.limit {
+            return Err(self.exceeded(self.read as u64));
+        }
+        Ok(())
+    }
+
+    fn exceeded(&self, size: u64) -> CliError {
+        CliError::ResponseTooLarge(format!(
+            "response from {} exceeds the {} byte limit (max_response_bytes) at {size} bytes",
+            self.url, self.limit
+        ))
+    }
+}
+
+/// Read the whole body of `response` within the limit
+///
+/// The body is decoded as UTF-8, with invalid sequences replaced.
+pub(crate) async fn read_text(mut response: reqwest::Response) -> Result<String, CliError> {
+    let mut limit = BodyLimit::start(&response)?;
+    let mut body = Vec::new();
+    while let Some(chunk) = response.chunk().await? {
+        limit.add(chunk.len())?;
+        body.extend_from_slice(&chunk);
+    }
+    Ok(String::from_utf8_lossy(&body).into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_body_limit_counts_chunks() {
+        let mut limit = BodyLimit {
+            url: "http://localhost/v1/chat/completions".to_string(),
+            limit: 10,
+            read: 0,
+        };
+        assert!(limit.add(6).is_ok());
+        assert!(limit.add(4).is_ok());
+        let err = limit.add(1).unwrap_err();
+        assert!(matches!(err, CliError::ResponseTooLarge(_)), "{err}");
+        assert!(err.to_string().contains("10 byte limit"), "{err}");
+        assert!(err.to_string().contains("at 11 bytes"), "{err}");
+    }
+
+    #[tokio::test]
+    async fn test_limit_scoped_to_future() {
+        assert_eq!(max_response_bytes(), DEFAULT_MAX_RESPONSE_BYTES);
+        let inner = scope(10, async { max_response_bytes() });
+        let other = scope(20, async { max_response_bytes() });
+        assert_eq!(futures::join!(inner, other), (10, 20));
+        assert_eq!(max_response_bytes(), DEFAULT_MAX_RESPONSE_BYTES);
+    }
+}
@@ -0,0 +1,258 @@
+//! Secret resolution for API keys
+//!
+//! `api_key_secret` (main provider, LLM-based guardrails, experiment judge)
+//! names where a key is read from instead of holding it:
+//!
+//! ```toml
+//! api_key_secret = { env = "OPENAI_API_KEY" }
+//! api_key_secret = { file = "/var/run/secrets/llm/api-key" }   # Kubernetes secret mount
+//! api_key_secret = { keyring = "fortified-llm-client/openai" } # service/account
+//! api_key_secret = { vault = "secret/data/llm#api_key" }       # path#field
+//! ```
+//!
+//! Secrets are resolved once, when the configuration is built (guardrail
+//! providers, created per evaluation, reuse the key), and never logged
+//! ([`SecretSource`]'s `Display` shows only the reference).
+//!
+//! - `file`: the file content without surrounding whitespace
+//! - `keyring`: the OS credential store through `security` (macOS) or
+//!   `secret-tool` (Linux Secret Service), using the `service`/`username`
+//!   attributes the `keyring` crate writes
+//! - `vault`: HashiCorp Vault HTTP API at `VAULT_ADDR` with `VAULT_TOKEN`
+//!   (and `VAULT_NAMESPACE` when set); KV v2 (`data.data.<field>`) and KV v1
+//!   (`data.<field>`) responses are both accepted
+
+use crate::error::CliError;
+use serde::{Deserialize, Serialize};
+use std::{fmt, path::PathBuf, process::Command, time::Duration};
+
+/// Timeout of a Vault read
+const VAULT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Where an API key is read from
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SecretSource {
+    /// Environment variable name
+    Env(String),
+    /// File holding the secret (e.g. a mounted Kubernetes secret)
+    File(PathBuf),
+    /// OS credential store entry as `service/account`
+    Keyring(String),
+    /// Vault secret as `path#field` (e.g. `secret/data/llm#api_key`)
+    Vault(String),
+}
+
+impl SecretSource {
+    /// Check the reference syntax without reading the secret
+    pub fn validate(&self) -> Result<(), String> {
+        match self {
+            Self::Env(name) if name.trim().is_empty() => {
+                Err("secret env variable name must not be empty".to_string())
+            }
+            Self::File(path) if path.as_os_str().is_empty() => {
+                Err("secret file path must not be empty".to_string())
+            }
+            Self::Keyring(reference) => split_ref(reference, '/')
+                .map(|_| ())
+                .ok_or_else(|| format!("keyring reference '{reference}' must be service/account")),
+            Self::Vault(reference) => split_ref(reference, '#')
+                .map(|_| ())
+                .ok_or_else(|| format!("vault reference '{reference}' must be path#field")),
+            _ => Ok(()),
+        }
+    }
+
+    /// Read the secret
+    pub fn resolve(&self) -> Result<String, CliError> {
+        self.validate().map_err(CliError::InvalidArguments)?;
+        let secret = match self {
+            Self::Env(name) => std::env::var(name).map_err(|_| {
+                CliError::InvalidArguments(format!("Environment variable '{name}' does not exist"))
+            })?,
+            Self::File(path) => std::fs::read_to_string(path)
+                .map_err(|e| {
+                    CliError::FileNotFound(format!(
+                        "Failed to read secret file '{}': {e}",
+                        path.display()
+                    ))
+                })?
+                .trim()
+                .to_string(),
+            Self::Keyring(reference) => {
+                let (service, account) = split_ref(reference, '/').expect("validated");
+                read_keyring(service, account)?
+            }
+            Self::Vault(reference) => {
+                let (path, field) = split_ref(reference, '#').expect("validated");
+                read_vault(path, field)?
+            }
+        };
+        if secret.is_empty() {
+            return Err(CliError::InvalidArguments(format!(
+                "Secret {self} is empty"
+            )));
+        }
+        Ok(secret)
+    }
+}
+
+impl fmt::Display for SecretSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Env(name) => write!(f, "env '{name}'"),
+            Self::File(path) => write!(f, "file '{}'", path.display()),
+            Self::Keyring(reference) => write!(f, "keyring '{reference}'"),
+            Self::Vault(reference) => write!(f, "vault '{reference}'"),
+        }
+    }
+}
+
+/// Split `reference` at the last `separator` into two non-empty parts
+fn split_ref(reference: &str, separator: char) -> Option<(&str, &str)> {
+    reference
+        .rsplit_once(separator)
+        .filter(|(a, b)| !a.trim().is_empty() && !b.trim().is_empty())
+}
+
+fn read_keyring(service: &str, account: &str) -> Result<String, CliError> {
+    let mut command = if cfg!(target_os = "macos") {
+        let mut command = Command::new("security");
+        command.args(["find-generic-password", "-s", service, "-a", account, "-w"]);
+        command
+    } else {
+        let mut command = Command::new("secret-tool");
+        command.args(["lookup", "service", service, "username", account]);
+        command
+    };
+    let output = command.output().map_err(|e| {
+        CliError::InvalidArguments(format!(
+            "Keyring lookup for '{service}/{account}' failed to start: {e}"
+        ))
+    })?;
+    if !output.status.success() {
+        return Err(CliError::InvalidArguments(format!(
+            "No keyring entry for '{service}/{account}'"
+        )));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+fn read_vault(path: &str, field: &str) -> Result<String, CliError> {
+    let address = std::env::var("VAULT_ADDR")
+        .map_err(|_| CliError::InvalidArguments("Vault secrets require VAULT_ADDR".to_string()))?;
+    let token = std::env::var("VAULT_TOKEN").map_err(|_| {
+        CliError::AuthenticationFailed("Vault secrets require VAULT_TOKEN".to_string())
+    })?;
+    let url = format!(
+        "{}/v1/{}",
+        address.trim_end_matches('/'),
+        path.trim_start_matches('/')
+    );
+    let mut request = reqwest::Client::new()
+        .get(&url)
+        .timeout(VAULT_TIMEOUT)
+        .header("X-Vault-Token", token);
+    if let Ok(namespace) = std::env::var("VAULT_NAMESPACE") {
+        request = request.header("X-Vault-Namespace", namespace);
+    }
+
+    // Secrets are resolved from sync code, possibly inside a runtime: run the
+    // request on its own thread and runtime. Never through the cassette, so
+    // the response is not recorded.
+    let body = std::thread::scope(|scope| {
+        scope
+            .spawn(move || {
+                tokio::runtime::Builder::new_current_thread()
+                    .enable_all()
+                    .build()
+                    .map_err(|e| CliError::InvalidArguments(format!("Vault read failed: {e}")))?
+                    .block_on(async {
+                        let response = request.send().await?;
+                        let status = response.status();
+                        if status == reqwest::StatusCode::FORBIDDEN {
+                            return Err(CliError::AuthenticationFailed(format!(
+                                "Vault denied access to '{path}'"
+                            )));
+                        }
+                        if !status.is_success() {
+                            return Err(CliError::InvalidResponse(format!(
+                                "Vault returned HTTP {} for '{path}'",
+                                status.as_u16()
+                            )));
+                        }
+                        Ok(response.json::<serde_json::Value>().await?)
+                    })
+            })
+            .join()
+            .unwrap_or_else(|_| {
+                Err(CliError::InvalidArguments(
+                    "Vault read thread panicked".to_string(),
+                ))
+            })
+    })?;
+
+    // KV v2 nests the secret under data.data, KV v1 under data
+    let data = &body["data"];
+    data["data"][field]
+        .as_str()
+        .or_else(|| data[field].as_str())
+        .map(str::to_string)
+        .ok_or_else(|| {
+            CliError::InvalidResponse(format!("Vault secret '{path}' has no field '{field}'"))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_and_display_references() {
+        let source: SecretSource =
+            toml::from_str::<toml::Table>(r#"secret = { vault = "secret/data/llm#api_key" }"#)
+                .unwrap()["secret"]
+                .clone()
+                .try_into()
+                .unwrap();
+        assert_eq!(
+            source,
+            SecretSource::Vault("secret/data/llm#api_key".to_string())
+        );
+        assert!(source.validate().is_ok());
+        assert_eq!(source.to_string(), "vault 'secret/data/llm#api_key'");
+
+        for (source, reason) in [
+            (SecretSource::Vault("secret/data/llm".into()), "path#field"),
+            (
+                SecretSource::Keyring("service-only".into()),
+                "service/account",
+            ),
+            (SecretSource::Keyring("/account".into()), "service/account"),
+            (SecretSource::Env(" ".into()), "must not be empty"),
+        ] {
+            let err = source.validate().unwrap_err();
+            assert!(err.contains(reason), "{err}");
+        }
+    }
+
+    #[test]
+    fn test_resolve_file_trims_whitespace() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("api-key");
+        std::fs::write(&path, "sk-mounted\n").unwrap();
+        assert_eq!(
+            SecretSource::File(path.clone()).resolve().unwrap(),
+            "sk-mounted"
+        );
+
+        std::fs::write(&path, "\n").unwrap();
+        let err = SecretSource::File(path).resolve().unwrap_err();
+        assert!(err.to_string().contains("is empty"), "{err}");
+
+        let err = SecretSource::File(dir.path().join("missing"))
+            .resolve()
+            .unwrap_err();
+        assert!(matches!(err, CliError::FileNotFound(_)), "{err}");
+    }
+}
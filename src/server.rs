@@ -0,0 +1,383 @@
+//! HTTP server mode
+//!
+//! Serves the evaluation pipeline over HTTP so the client can run as a
+//! sidecar gateway. The server owns the configuration (endpoint, key,
+//! guardrails); requests only carry prompts, tags and sampling parameters,
+//! so a caller can never redirect the endpoint or weaken the guardrails.
+//!
+//! | Route | Response |
+//! |-------|----------|
+//! | `POST /evaluate` | The usual JSON output of an evaluation |
+//! | `GET /health` | `{"status": "ok", "version": "..."}` |
+//! | `GET /metrics` | Prometheus text exposition (when a [`MetricsRegistry`](crate::MetricsRegistry) is attached) |
+//!
+//! With [`Server::with_events`], every evaluated request is also reported to
+//! an [`EventWriter`], indexed in arrival order.
+
+use crate::{
+    constants::{llm_defaults, server::MAX_REQUEST_BYTES},
+    evaluate,
+    events::{Event, EventWriter},
+    BatchItem, CliError, CliOutput, EvaluationConfig, EvaluationContext,
+};
+use http_body_util::{BodyExt, Full, Limited};
+use hyper::{
+    body::{Bytes, Incoming},
+    header::CONTENT_TYPE,
+    server::conn::http1,
+    service::service_fn,
+    Method, Request, Response, StatusCode,
+};
+use hyper_util::rt::TokioIo;
+use serde::Deserialize;
+use std::{
+    convert::Infallible,
+    net::SocketAddr,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+};
+use tokio::net::TcpListener;
+
+/// Body of `POST /evaluate`
+///
+/// Uses the config file's field names. Everything else comes from the
+/// server's configuration; unknown fields are rejected rather than ignored.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct EvaluateRequest {
+    /// Caller-supplied identifier echoed in progress events
+    #[serde(default)]
+    pub id: Option<String>,
+    pub user_prompt: String,
+    /// Replaces the configured system prompt
+    #[serde(default)]
+    pub system_prompt: Option<String>,
+    /// Added to the configured tags
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub temperature: Option<f32>,
+    #[serde(default)]
+    pub top_p: Option<f32>,
+    #[serde(default)]
+    pub max_tokens: Option<u32>,
+    #[serde(default)]
+    pub seed: Option<u64>,
+}
+
+impl EvaluateRequest {
+    /// `base` with this request's prompts, tags and sampling parameters
+    pub fn apply(&self, base: &EvaluationConfig) -> Result<EvaluationConfig, CliError> {
+        if self.user_prompt.trim().is_empty() {
+            return Err(CliError::InvalidArguments(
+                "user_prompt must not be empty".to_string(),
+            ));
+        }
+        if let Some(t) = self.temperature.filter(|t| {
+            !(llm_defaults::MIN_TEMPERATURE..=llm_defaults::MAX_TEMPERATURE).contains(t)
+        }) {
+            return Err(CliError::InvalidArguments(format!(
+                "temperature must be between {} and {}, got {t}",
+                llm_defaults::MIN_TEMPERATURE,
+                llm_defaults::MAX_TEMPERATURE
+            )));
+        }
+        if let Some(p) = self
+            .top_p
+            .filter(|p| !(llm_defaults::MIN_TOP_P..=llm_defaults::MAX_TOP_P).contains(p))
+        {
+            return Err(CliError::InvalidArguments(format!(
+                "top_p must be between {} and {}, got {p}",
+                llm_defaults::MIN_TOP_P,
+                llm_defaults::MAX_TOP_P
+            )));
+        }
+        if self.max_tokens == Some(0) {
+            return Err(CliError::InvalidArguments(
+                "max_tokens must be >= 1, got 0".to_string(),
+            ));
+        }
+
+        let item = BatchItem {
+            id: None,
+            user_prompt: self.user_prompt.clone(),
+            system_prompt: self.system_prompt.clone(),
+            tags: self.tags.clone(),
+        };
+        let mut config = item.apply(base);
+        if let Some(temperature) = self.temperature {
+            config.temperature = temperature;
+        }
+        if self.top_p.is_some() {
+            config.top_p = self.top_p;
+        }
+        if self.max_tokens.is_some() {
+            config.max_tokens = self.max_tokens;
+        }
+        if self.seed.is_some() {
+            config.seed = self.seed;
+        }
+        Ok(config)
+    }
+}
+
+/// HTTP API bound to a local address
+///
+/// ```no_run
+/// # use fortified_llm_client::{config_builder::ConfigBuilder, Server};
+/// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+/// let base = ConfigBuilder::new()
+///     .api_url("http://localhost:11434/v1/chat/completions")
+///     .model("llama3")
+///     .system_prompt("You are a helpful assistant")
+///     .user_prompt("")
+///     .build()?;
+/// let server = Server::bind("127.0.0.1:8080".parse()?, base).await?;
+/// server.run().await;
+/// # Ok(())
+/// # }
+/// ```
+pub struct Server {
+    listener: TcpListener,
+    state: State,
+}
+
+struct State {
+    base: EvaluationConfig,
+    events: Option<Arc<EventWriter>>,
+    /// Index of the next evaluated request in events
+    next_index: AtomicUsize,
+}
+
+impl Server {
+    /// Bind `addr` (port 0 picks a free port, see [`Server::local_addr`])
+    ///
+    /// The base config's `user_prompt` is replaced by every request.
+    pub async fn bind(addr: SocketAddr, base: EvaluationConfig) -> Result<Self, CliError> {
+        let listener = TcpListener::bind(addr)
+            .await
+            .map_err(|e| CliError::InvalidArguments(format!("Failed to listen on {addr}: {e}")))?;
+        Ok(Self {
+            listener,
+            state: State {
+                base,
+                events: None,
+                next_index: AtomicUsize::new(0),
+            },
+        })
+    }
+
+    /// Report `item_started`, `guardrail_blocked` and `item_completed` events
+    pub fn with_events(mut self, events: Arc<EventWriter>) -> Self {
+        self.state.events = Some(events);
+        self
+    }
+
+    pub fn local_addr(&self) -> SocketAddr {
+        self.listener
+            .local_addr()
+            .expect("bound listener has a local address")
+    }
+
+    /// Accept connections until the returned future is dropped
+    pub async fn run(self) {
+        let state = Arc::new(self.state);
+        loop {
+            let stream = match self.listener.accept().await {
+                Ok((stream, _)) => stream,
+                Err(e) => {
+                    // Transient (e.g. out of file descriptors): keep serving
+                    log::warn!("Failed to accept connection: {e}");
+                    continue;
+                }
+            };
+            let state = Arc::clone(&state);
+            tokio::spawn(async move {
+                let service = service_fn(move |request| handle(Arc::clone(&state), request));
+                if let Err(e) = http1::Builder::new()
+                    .serve_connection(TokioIo::new(stream), service)
+                    .await
+                {
+                    log::debug!("Connection closed with error: {e}");
+                }
+            });
+        }
+    }
+}
+
+async fn handle(
+    state: Arc<State>,
+    request: Request<Incoming>,
+) -> Result<Response<Full<Bytes>>, Infallible> {
+    let response = match (request.method(), request.uri().path()) {
+        (&Method::POST, "/evaluate") => evaluate_request(&state, request.into_body()).await,
+        (&Method::GET, "/health") => json_response(
+            StatusCode::OK,
+            &serde_json::json!({"status": "ok", "version": env!("CARGO_PKG_VERSION")}),
+        ),
+        (&Method::GET, "/metrics") => match &state.base.metrics {
+            Some(metrics) => response(
+                StatusCode::OK,
+                "text/plain; version=0.0.4",
+                metrics.render_prometheus(),
+            ),
+            None => route_error(StatusCode::NOT_FOUND, "Metrics are not enabled"),
+        },
+        (_, "/evaluate" | "/health" | "/metrics") => {
+            route_error(StatusCode::METHOD_NOT_ALLOWED, "Method not allowed")
+        }
+        (_, path) => route_error(StatusCode::NOT_FOUND, &format!("No route for {path}")),
+    };
+    Ok(response)
+}
+
+async fn evaluate_request(state: &State, body: Incoming) -> Response<Full<Bytes>> {
+    let base = &state.base;
+    let body = match Limited::new(body, MAX_REQUEST_BYTES).collect().await {
+        Ok(collected) => collected.to_bytes(),
+        Err(e) => {
+            return route_error(
+                StatusCode::PAYLOAD_TOO_LARGE,
+                &format!("Failed to read request body (limit {MAX_REQUEST_BYTES} bytes): {e}"),
+            )
+        }
+    };
+
+    let prepared = serde_json::from_slice::<EvaluateRequest>(&body)
+        .map_err(|e| CliError::InvalidArguments(format!("Invalid request body: {e}")))
+        .and_then(|request| Ok((request.apply(base)?, request.id)));
+    let (config, id) = match prepared {
+        Ok(prepared) => prepared,
+        Err(e) => {
+            let metadata = EvaluationContext::new(base.clone()).metadata();
+            return json_response(
+                error_status(&e),
+                &CliOutput::error(&e, e.to_string(), metadata),
+            );
+        }
+    };
+
+    let index = state.next_index.fetch_add(1, Ordering::Relaxed);
+    if let Some(events) = &state.events {
+        events.emit(&Event::ItemStarted {
+            index,
+            id: id.clone(),
+        });
+    }
+    let fallback = EvaluationContext::new(config.clone());
+    let (status, output) = match evaluate(config).await {
+        Ok(output) if output.status == "error" => (StatusCode::UNPROCESSABLE_ENTITY, output),
+        Ok(output) => (StatusCode::OK, output),
+        Err(e) => {
+            log::warn!("Evaluation failed: {e}");
+            let output = CliOutput::error(&e, e.to_string(), fallback.metadata());
+            (error_status(&e), output)
+        }
+    };
+    if let Some(events) = &state.events {
+        events.emit_all(&Event::completed(index, id.as_deref(), &output));
+    }
+    json_response(status, &output)
+}
+
+/// HTTP status of an evaluation that failed with `error`
+fn error_status(error: &CliError) -> StatusCode {
+    match error {
+        CliError::InvalidArguments(_) => StatusCode::BAD_REQUEST,
+        CliError::ContextLimitExceeded { .. } => StatusCode::UNPROCESSABLE_ENTITY,
+        CliError::QuotaExceeded(_) | CliError::RateLimited { .. } => StatusCode::TOO_MANY_REQUESTS,
+        CliError::ContentFiltered { .. } => StatusCode::UNPROCESSABLE_ENTITY,
+        CliError::EndpointNotAllowed(_) | CliError::RequestDeclined(_) => StatusCode::FORBIDDEN,
+        CliError::HttpError(_)
+        | CliError::InvalidResponse(_)
+        | CliError::ResponseTooLarge(_)
+        | CliError::AuthenticationFailed(_)
+        | CliError::FetchFailed(_) => StatusCode::BAD_GATEWAY,
+        CliError::FileNotFound(_)
+        | CliError::PdfProcessingFailed(_)
+        | CliError::ImageProcessingFailed(_)
+        | CliError::SelfTestFailed(_) => StatusCode::INTERNAL_SERVER_ERROR,
+    }
+}
+
+fn route_error(status: StatusCode, message: &str) -> Response<Full<Bytes>> {
+    json_response(status, &serde_json::json!({"error": {"message": message}}))
+}
+
+fn json_response(status: StatusCode, body: &impl serde::Serialize) -> Response<Full<Bytes>> {
+    match serde_json::to_string(body) {
+        Ok(json) => response(status, "application/json", json),
+        Err(e) => response(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "text/plain",
+            format!("Failed to serialize response: {e}"),
+        ),
+    }
+}
+
+fn response(status: StatusCode, content_type: &str, body: String) -> Response<Full<Bytes>> {
+    let mut response = Response::new(Full::new(Bytes::from(body)));
+    *response.status_mut() = status;
+    if let Ok(value) = content_type.parse() {
+        response.headers_mut().insert(CONTENT_TYPE, value);
+    }
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config_builder::ConfigBuilder;
+
+    fn base() -> EvaluationConfig {
+        ConfigBuilder::new()
+            .api_url("mock://")
+            .model("mock-model")
+            .system_prompt("You are a helpful assistant")
+            .user_prompt("")
+            .temperature(0.3)
+            .tag("gateway")
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_request_overrides_prompts_and_sampling() {
+        let request: EvaluateRequest = serde_json::from_str(
+            r#"{"user_prompt": "Hi", "system_prompt": "Be brief", "tags": ["team=a"], "seed": 7}"#,
+        )
+        .unwrap();
+        let config = request.apply(&base()).unwrap();
+
+        assert_eq!(config.user_prompt, "Hi");
+        assert_eq!(config.system_prompt, "Be brief");
+        assert_eq!(config.tags, vec!["gateway", "team=a"]);
+        assert_eq!(config.seed, Some(7));
+        assert_eq!(config.temperature, 0.3);
+        assert_eq!(config.api_url, "mock://");
+    }
+
+    #[test]
+    fn test_request_rejects_server_owned_fields_and_bad_ranges() {
+        let err = serde_json::from_str::<EvaluateRequest>(
+            r#"{"user_prompt": "Hi", "api_url": "http://attacker.example"}"#,
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("unknown field `api_url`"));
+
+        for body in [
+            r#"{"user_prompt": "  "}"#,
+            r#"{"user_prompt": "Hi", "temperature": 3.0}"#,
+            r#"{"user_prompt": "Hi", "top_p": 1.5}"#,
+            r#"{"user_prompt": "Hi", "max_tokens": 0}"#,
+        ] {
+            let request: EvaluateRequest = serde_json::from_str(body).unwrap();
+            assert!(
+                matches!(request.apply(&base()), Err(CliError::InvalidArguments(_))),
+                "{body}"
+            );
+        }
+    }
+}
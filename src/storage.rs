@@ -0,0 +1,384 @@
+//! Pluggable key-value storage
+//!
+//! A small [`Storage`] abstraction shared by the subsystems that persist data
+//! (audit payload blobs, caches, checkpoints). Two implementations ship with
+//! the crate: [`FsStorage`] for single-host deployments and [`MemoryStorage`]
+//! for tests and ephemeral embedding. Embedders can implement the trait on
+//! top of Redis, S3, etc.
+//!
+//! Keys are `/`-separated paths of `[A-Za-z0-9._-]` segments; segments may not
+//! start with `.` (reserved for implementation metadata).
+
+use crate::error::CliError;
+use std::{
+    collections::BTreeMap,
+    fs,
+    io::Write,
+    path::{Path, PathBuf},
+    sync::Mutex,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+use tempfile::NamedTempFile;
+
+/// Key-value store with optional per-entry expiry
+///
+/// Expired entries behave as absent: `get` returns `None` and `list` skips
+/// them. Implementations may reclaim their space lazily.
+pub trait Storage: Send + Sync {
+    /// Read a value (None if missing or expired)
+    fn get(&self, key: &str) -> Result<Option<Vec<u8>>, CliError>;
+
+    /// Write a value, replacing any existing one. `ttl: None` never expires.
+    fn put(&self, key: &str, value: &[u8], ttl: Option<Duration>) -> Result<(), CliError>;
+
+    /// Live keys starting with `prefix`, sorted
+    fn list(&self, prefix: &str) -> Result<Vec<String>, CliError>;
+
+    /// Delete a value, returning whether it existed
+    fn delete(&self, key: &str) -> Result<bool, CliError>;
+
+    fn contains(&self, key: &str) -> Result<bool, CliError> {
+        Ok(self.get(key)?.is_some())
+    }
+
+    /// Size of a value in bytes (None if missing or expired)
+    ///
+    /// The default reads the value; implementations should override it with
+    /// a metadata lookup.
+    fn size(&self, key: &str) -> Result<Option<u64>, CliError> {
+        Ok(self.get(key)?.map(|value| value.len() as u64))
+    }
+}
+
+/// Validate a storage key (see module docs for the format)
+pub fn validate_key(key: &str) -> Result<(), CliError> {
+    let valid = !key.is_empty()
+        && key.split('/').all(|segment| {
+            !segment.is_empty()
+                && !segment.starts_with('.')
+                && segment
+                    .bytes()
+                    .all(|b| b.is_ascii_alphanumeric() || matches!(b, b'.' | b'_' | b'-'))
+        });
+    if valid {
+        Ok(())
+    } else {
+        Err(CliError::InvalidArguments(format!(
+            "Invalid storage key '{key}'"
+        )))
+    }
+}
+
+fn expiry_from(ttl: Option<Duration>) -> Option<SystemTime> {
+    ttl.map(|ttl| SystemTime::now() + ttl)
+}
+
+fn is_expired(expires_at: Option<SystemTime>) -> bool {
+    expires_at.is_some_and(|t| t <= SystemTime::now())
+}
+
+/// Stored value and its expiry time
+type MemoryEntry = (Vec<u8>, Option<SystemTime>);
+
+/// In-memory storage (for tests and ephemeral embedding)
+#[derive(Debug, Default)]
+pub struct MemoryStorage {
+    entries: Mutex<BTreeMap<String, MemoryEntry>>,
+}
+
+impl MemoryStorage {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Storage for MemoryStorage {
+    fn get(&self, key: &str) -> Result<Option<Vec<u8>>, CliError> {
+        validate_key(key)?;
+        let entries = self.entries.lock().expect("storage lock poisoned");
+        Ok(entries
+            .get(key)
+            .filter(|(_, expires_at)| !is_expired(*expires_at))
+            .map(|(value, _)| value.clone()))
+    }
+
+    fn put(&self, key: &str, value: &[u8], ttl: Option<Duration>) -> Result<(), CliError> {
+        validate_key(key)?;
+        self.entries
+            .lock()
+            .expect("storage lock poisoned")
+            .insert(key.to_string(), (value.to_vec(), expiry_from(ttl)));
+        Ok(())
+    }
+
+    fn list(&self, prefix: &str) -> Result<Vec<String>, CliError> {
+        let mut entries = self.entries.lock().expect("storage lock poisoned");
+        entries.retain(|_, (_, expires_at)| !is_expired(*expires_at));
+        Ok(entries
+            .keys()
+            .filter(|k| k.starts_with(prefix))
+            .cloned()
+            .collect())
+    }
+
+    fn delete(&self, key: &str) -> Result<bool, CliError> {
+        validate_key(key)?;
+        Ok(self
+            .entries
+            .lock()
+            .expect("storage lock poisoned")
+            .remove(key)
+            .is_some_and(|(_, expires_at)| !is_expired(expires_at)))
+    }
+
+    fn size(&self, key: &str) -> Result<Option<u64>, CliError> {
+        validate_key(key)?;
+        let entries = self.entries.lock().expect("storage lock poisoned");
+        Ok(entries
+            .get(key)
+            .filter(|(_, expires_at)| !is_expired(*expires_at))
+            .map(|(value, _)| value.len() as u64))
+    }
+}
+
+/// Directory holding expiry timestamps, parallel to the key layout
+const TTL_DIR: &str = ".ttl";
+
+/// Filesystem storage: each key is a file under `root`
+///
+/// Writes are atomic (temp file + rename). Expiry times live in a parallel
+/// `.ttl/` tree, so values are stored byte-for-byte at `<root>/<key>`.
+#[derive(Debug, Clone)]
+pub struct FsStorage {
+    root: PathBuf,
+}
+
+impl FsStorage {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    fn value_path(&self, key: &str) -> PathBuf {
+        self.root.join(key)
+    }
+
+    fn ttl_path(&self, key: &str) -> PathBuf {
+        self.root.join(TTL_DIR).join(key)
+    }
+
+    fn expires_at(&self, key: &str) -> Option<SystemTime> {
+        let secs = fs::read_to_string(self.ttl_path(key)).ok()?;
+        let secs: u64 = secs.trim().parse().ok()?;
+        Some(UNIX_EPOCH + Duration::from_secs(secs))
+    }
+
+    /// Remove an expired entry, returning true if it was expired
+    fn purge_if_expired(&self, key: &str) -> bool {
+        if !is_expired(self.expires_at(key)) {
+            return false;
+        }
+        let _ = fs::remove_file(self.value_path(key));
+        let _ = fs::remove_file(self.ttl_path(key));
+        true
+    }
+
+    fn collect_keys(
+        &self,
+        dir: &Path,
+        prefix: &str,
+        keys: &mut Vec<String>,
+    ) -> Result<(), CliError> {
+        let entries = match fs::read_dir(dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+            Err(e) => {
+                return Err(CliError::FileNotFound(format!(
+                    "Failed to read storage directory '{}': {e}",
+                    dir.display()
+                )))
+            }
+        };
+
+        for entry in entries.flatten() {
+            let Some(name) = entry.file_name().to_str().map(str::to_string) else {
+                continue;
+            };
+            // Skip metadata and in-flight temp files
+            if name.starts_with('.') {
+                continue;
+            }
+            let key = if prefix.is_empty() {
+                name
+            } else {
+                format!("{prefix}/{name}")
+            };
+            if entry.path().is_dir() {
+                self.collect_keys(&entry.path(), &key, keys)?;
+            } else {
+                keys.push(key);
+            }
+        }
+        Ok(())
+    }
+}
+
+fn write_atomic(path: &Path, content: &[u8]) -> Result<(), CliError> {
+    let write_err = |e: std::io::Error| {
+        CliError::InvalidArguments(format!("Failed to write '{}': {e}", path.display()))
+    };
+    let dir = path.parent().expect("storage paths always have a parent");
+    fs::create_dir_all(dir).map_err(write_err)?;
+    let mut temp = NamedTempFile::new_in(dir).map_err(write_err)?;
+    temp.write_all(content).map_err(write_err)?;
+    temp.persist(path).map_err(|e| write_err(e.error))?;
+    Ok(())
+}
+
+impl Storage for FsStorage {
+    fn get(&self, key: &str) -> Result<Option<Vec<u8>>, CliError> {
+        validate_key(key)?;
+        if self.purge_if_expired(key) {
+            return Ok(None);
+        }
+        let path = self.value_path(key);
+        match fs::read(&path) {
+            Ok(value) => Ok(Some(value)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(CliError::FileNotFound(format!(
+                "Failed to read '{}': {e}",
+                path.display()
+            ))),
+        }
+    }
+
+    fn put(&self, key: &str, value: &[u8], ttl: Option<Duration>) -> Result<(), CliError> {
+        validate_key(key)?;
+        match expiry_from(ttl) {
+            Some(expires_at) => {
+                let secs = expires_at
+                    .duration_since(UNIX_EPOCH)
+                    .unwrap_or_default()
+                    .as_secs();
+                write_atomic(&self.ttl_path(key), secs.to_string().as_bytes())?;
+            }
+            None => {
+                let _ = fs::remove_file(self.ttl_path(key));
+            }
+        }
+        write_atomic(&self.value_path(key), value)
+    }
+
+    fn list(&self, prefix: &str) -> Result<Vec<String>, CliError> {
+        let mut keys = Vec::new();
+        self.collect_keys(&self.root, "", &mut keys)?;
+        keys.retain(|k| k.starts_with(prefix) && !self.purge_if_expired(k));
+        keys.sort();
+        Ok(keys)
+    }
+
+    fn delete(&self, key: &str) -> Result<bool, CliError> {
+        validate_key(key)?;
+        let expired = self.purge_if_expired(key);
+        let _ = fs::remove_file(self.ttl_path(key));
+        let path = self.value_path(key);
+        match fs::remove_file(&path) {
+            Ok(()) => Ok(!expired),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(false),
+            Err(e) => Err(CliError::InvalidArguments(format!(
+                "Failed to delete '{}': {e}",
+                path.display()
+            ))),
+        }
+    }
+
+    fn size(&self, key: &str) -> Result<Option<u64>, CliError> {
+        validate_key(key)?;
+        if self.purge_if_expired(key) {
+            return Ok(None);
+        }
+        let path = self.value_path(key);
+        match fs::metadata(&path) {
+            Ok(metadata) => Ok(Some(metadata.len())),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(CliError::FileNotFound(format!(
+                "Failed to read '{}': {e}",
+                path.display()
+            ))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn exercise(storage: &dyn Storage) {
+        storage.put("a/one", b"1", None).unwrap();
+        storage.put("a/two", b"2", None).unwrap();
+        storage.put("b/three", b"3", None).unwrap();
+
+        assert_eq!(storage.get("a/one").unwrap().unwrap(), b"1");
+        assert!(storage.get("a/missing").unwrap().is_none());
+        assert_eq!(storage.list("a/").unwrap(), vec!["a/one", "a/two"]);
+        assert_eq!(storage.list("").unwrap().len(), 3);
+
+        storage.put("a/one", b"updated", None).unwrap();
+        assert_eq!(storage.get("a/one").unwrap().unwrap(), b"updated");
+        assert_eq!(storage.size("a/one").unwrap(), Some(7));
+        assert_eq!(storage.size("a/missing").unwrap(), None);
+
+        assert!(storage.delete("a/one").unwrap());
+        assert!(!storage.delete("a/one").unwrap());
+        assert!(!storage.contains("a/one").unwrap());
+
+        // Zero TTL expires immediately
+        storage
+            .put("b/ephemeral", b"x", Some(Duration::ZERO))
+            .unwrap();
+        assert!(storage.get("b/ephemeral").unwrap().is_none());
+        assert_eq!(storage.size("b/ephemeral").unwrap(), None);
+        assert_eq!(storage.list("b/").unwrap(), vec!["b/three"]);
+
+        storage
+            .put("b/lasting", b"y", Some(Duration::from_secs(3600)))
+            .unwrap();
+        assert_eq!(storage.get("b/lasting").unwrap().unwrap(), b"y");
+    }
+
+    #[test]
+    fn test_memory_storage_contract() {
+        exercise(&MemoryStorage::new());
+    }
+
+    #[test]
+    fn test_fs_storage_contract() {
+        let dir = TempDir::new().unwrap();
+        let storage = FsStorage::new(dir.path());
+        exercise(&storage);
+
+        // Values are stored verbatim at <root>/<key>
+        assert_eq!(fs::read(dir.path().join("b/three")).unwrap(), b"3");
+    }
+
+    #[test]
+    fn test_fs_storage_put_without_ttl_clears_expiry() {
+        let dir = TempDir::new().unwrap();
+        let storage = FsStorage::new(dir.path());
+        storage.put("k", b"old", Some(Duration::ZERO)).unwrap();
+        storage.put("k", b"new", None).unwrap();
+        assert_eq!(storage.get("k").unwrap().unwrap(), b"new");
+    }
+
+    #[test]
+    fn test_invalid_keys_rejected() {
+        let storage = MemoryStorage::new();
+        for key in ["", "../etc/passwd", "a//b", ".ttl/x", "a/b c", "/abs"] {
+            assert!(storage.put(key, b"x", None).is_err(), "accepted '{key}'");
+        }
+    }
+}
@@ -0,0 +1,412 @@
+//! Sampling parameter sweeps
+//!
+//! Runs one prompt across a grid of temperatures, top_p values and seeds,
+//! repeating each point to measure how much the response varies, and
+//! reports guardrail pass rates and quality scores per point:
+//!
+//! ```toml
+//! [sweep]
+//! temperatures = [0.0, 0.7, 1.2]
+//! top_p = [0.9, 1.0]   # Optional: omit to keep the configured top_p
+//! seeds = [1, 2]       # Optional: omit to keep the configured seed
+//! repeats = 3          # Runs per grid point
+//! max_runs = 100       # Budget: the sweep is rejected if the grid needs more
+//!
+//! [[sweep.assertions]] # Optional, same checks as [experiment]
+//! type = "max_chars"
+//! max = 400
+//! ```
+//!
+//! Each run is tagged `sweep_point=<index>`, so per-point counts come from the
+//! batch summary. [`SweepReport::to_csv`] flattens the points for analysis.
+
+use crate::{
+    evaluate_batch,
+    experiment::{mean, prepare_judge, ratio, response_text, round, score},
+    response_limit, Assertion, CliError, EvaluationConfig, JudgeConfig,
+};
+use futures::stream::{self, StreamExt};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+/// Default budget of evaluations per sweep
+pub const DEFAULT_MAX_RUNS: usize = 100;
+
+/// Sweep settings as written in config files (`[sweep]`)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SweepConfig {
+    /// Temperatures to try (empty = the configured temperature)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub temperatures: Vec<f32>,
+    /// top_p values to try (empty = the configured top_p)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub top_p: Vec<f32>,
+    /// Seeds to try (empty = the configured seed)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub seeds: Vec<u64>,
+    /// Runs per grid point
+    #[serde(default = "default_repeats")]
+    pub repeats: usize,
+    /// Maximum number of evaluations (grid points x repeats)
+    #[serde(default = "default_max_runs")]
+    pub max_runs: usize,
+    /// Checks applied to every successful response
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub assertions: Vec<Assertion>,
+    /// LLM grading responses from 0 to 10
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub judge: Option<JudgeConfig>,
+}
+
+fn default_repeats() -> usize {
+    1
+}
+
+fn default_max_runs() -> usize {
+    DEFAULT_MAX_RUNS
+}
+
+impl Default for SweepConfig {
+    fn default() -> Self {
+        Self {
+            temperatures: Vec::new(),
+            top_p: Vec::new(),
+            seeds: Vec::new(),
+            repeats: default_repeats(),
+            max_runs: DEFAULT_MAX_RUNS,
+            assertions: Vec::new(),
+            judge: None,
+        }
+    }
+}
+
+/// One combination of sampling parameters
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct SweepPoint {
+    pub temperature: f32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub top_p: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub seed: Option<u64>,
+}
+
+impl SweepConfig {
+    /// Values in range, at least one repeat, and a grid within the budget
+    pub fn validate(&self) -> Result<(), CliError> {
+        if let Some(t) = self.temperatures.iter().find(|t| !(0.0..=2.0).contains(*t)) {
+            return Err(CliError::InvalidArguments(format!(
+                "Sweep temperature {t} is out of range (0.0-2.0)"
+            )));
+        }
+        if let Some(p) = self.top_p.iter().find(|p| !(**p > 0.0 && **p <= 1.0)) {
+            return Err(CliError::InvalidArguments(format!(
+                "Sweep top_p {p} is out of range (0.0 exclusive - 1.0)"
+            )));
+        }
+        if self.repeats == 0 {
+            return Err(CliError::InvalidArguments(
+                "Sweep 'repeats' must be at least 1".to_string(),
+            ));
+        }
+        let points =
+            self.temperatures.len().max(1) * self.top_p.len().max(1) * self.seeds.len().max(1);
+        let runs = points * self.repeats;
+        if runs > self.max_runs {
+            return Err(CliError::InvalidArguments(format!(
+                "Sweep needs {runs} run(s) ({points} point(s) x {} repeat(s)), \
+                 over the max_runs budget of {}",
+                self.repeats, self.max_runs
+            )));
+        }
+        self.assertions.iter().try_for_each(Assertion::validate)
+    }
+
+    /// Grid points in temperature, top_p, seed order (unset axes use `base`)
+    pub fn points(&self, base: &EvaluationConfig) -> Vec<SweepPoint> {
+        let temperatures = if self.temperatures.is_empty() {
+            vec![base.temperature]
+        } else {
+            self.temperatures.clone()
+        };
+        let top_ps: Vec<Option<f32>> = if self.top_p.is_empty() {
+            vec![base.top_p]
+        } else {
+            self.top_p.iter().copied().map(Some).collect()
+        };
+        let seeds: Vec<Option<u64>> = if self.seeds.is_empty() {
+            vec![base.seed]
+        } else {
+            self.seeds.iter().copied().map(Some).collect()
+        };
+
+        let mut points = Vec::new();
+        for &temperature in &temperatures {
+            for &top_p in &top_ps {
+                for &seed in &seeds {
+                    points.push(SweepPoint {
+                        temperature,
+                        top_p,
+                        seed,
+                    });
+                }
+            }
+        }
+        points
+    }
+}
+
+/// Aggregates for one grid point
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PointReport {
+    #[serde(flatten)]
+    pub point: SweepPoint,
+    pub runs: usize,
+    pub succeeded: usize,
+    pub blocked: usize,
+    pub errors: usize,
+    /// Share of runs not blocked by input or output guardrails
+    pub guardrail_pass_rate: f64,
+    /// Share of runs that succeeded and passed every assertion
+    pub pass_rate: f64,
+    /// Mean judge score, else share of assertions passed (0.0-1.0)
+    pub mean_score: f64,
+    /// Distinct response texts among the successful runs
+    pub distinct_responses: usize,
+    /// Mean pairwise word-set distance of the responses (0 = identical, 1 = disjoint)
+    pub response_divergence: f64,
+    pub mean_response_chars: f64,
+    /// Standard deviation of the response length in characters
+    pub response_chars_stddev: f64,
+    pub mean_latency_ms: u64,
+}
+
+/// Result of [`run_sweep`]
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SweepReport {
+    pub repeats: usize,
+    pub points: Vec<PointReport>,
+}
+
+impl SweepReport {
+    /// One CSV row per point (empty cells for unset top_p/seed)
+    pub fn to_csv(&self) -> Result<String, CliError> {
+        let mut writer = csv::Writer::from_writer(Vec::new());
+        let csv_error = |e: csv::Error| CliError::InvalidArguments(format!("CSV error: {e}"));
+        writer
+            .write_record([
+                "temperature",
+                "top_p",
+                "seed",
+                "runs",
+                "succeeded",
+                "blocked",
+                "errors",
+                "guardrail_pass_rate",
+                "pass_rate",
+                "mean_score",
+                "distinct_responses",
+                "response_divergence",
+                "mean_response_chars",
+                "response_chars_stddev",
+                "mean_latency_ms",
+            ])
+            .map_err(csv_error)?;
+        for p in &self.points {
+            writer
+                .write_record([
+                    p.point.temperature.to_string(),
+                    p.point.top_p.map(|v| v.to_string()).unwrap_or_default(),
+                    p.point.seed.map(|v| v.to_string()).unwrap_or_default(),
+                    p.runs.to_string(),
+                    p.succeeded.to_string(),
+                    p.blocked.to_string(),
+                    p.errors.to_string(),
+                    p.guardrail_pass_rate.to_string(),
+                    p.pass_rate.to_string(),
+                    p.mean_score.to_string(),
+                    p.distinct_responses.to_string(),
+                    p.response_divergence.to_string(),
+                    p.mean_response_chars.to_string(),
+                    p.response_chars_stddev.to_string(),
+                    p.mean_latency_ms.to_string(),
+                ])
+                .map_err(csv_error)?;
+        }
+        let bytes = writer
+            .into_inner()
+            .map_err(|e| CliError::InvalidArguments(format!("CSV error: {e}")))?;
+        String::from_utf8(bytes).map_err(|e| CliError::InvalidArguments(format!("CSV error: {e}")))
+    }
+}
+
+/// Run `base`'s prompt at every grid point of `sweep`
+///
+/// Evaluations and judge calls run with at most `concurrency` in flight.
+pub async fn run_sweep(
+    base: &EvaluationConfig,
+    sweep: &SweepConfig,
+    concurrency: usize,
+) -> Result<SweepReport, CliError> {
+    sweep.validate()?;
+    let judge = prepare_judge(sweep.judge.as_ref(), base)?;
+    let points = sweep.points(base);
+
+    let plan: Vec<usize> = (0..points.len())
+        .flat_map(|point| std::iter::repeat_n(point, sweep.repeats))
+        .collect();
+    let configs = plan
+        .iter()
+        .map(|&index| {
+            let point = &points[index];
+            let mut config = base.clone();
+            config.temperature = point.temperature;
+            config.top_p = point.top_p;
+            config.seed = point.seed;
+            config.tags.push(point_tag(index));
+            config
+        })
+        .collect();
+
+    log::info!(
+        "Sweep: {} point(s) x {} repeat(s), {} run(s)",
+        points.len(),
+        sweep.repeats,
+        plan.len()
+    );
+    let batch = evaluate_batch(configs, concurrency).await;
+
+    let judge = judge.as_ref();
+    let scores: Vec<(f64, bool)> = response_limit::scope(
+        base.max_response_bytes,
+        stream::iter(&batch.results)
+            .map(|output| async move {
+                score(output, &base.user_prompt, &sweep.assertions, judge).await
+            })
+            .buffered(concurrency.max(1))
+            .collect(),
+    )
+    .await;
+
+    let reports = points
+        .iter()
+        .enumerate()
+        .map(|(index, point)| {
+            let counts = batch
+                .summary
+                .by_tag
+                .get(&point_tag(index))
+                .cloned()
+                .unwrap_or_default();
+            let runs: Vec<usize> = (0..plan.len()).filter(|&i| plan[i] == index).collect();
+            let guardrail_passed = runs
+                .iter()
+                .filter(|&&i| batch.results[i].blocked_direction().is_none())
+                .count();
+            let responses: Vec<String> = runs
+                .iter()
+                .filter(|&&i| batch.results[i].status == "success")
+                .filter_map(|&i| response_text(&batch.results[i]))
+                .collect();
+            let lengths: Vec<f64> = responses.iter().map(|r| r.chars().count() as f64).collect();
+            let mean_chars = mean(lengths.iter().copied());
+
+            PointReport {
+                point: *point,
+                runs: counts.total,
+                succeeded: counts.succeeded,
+                blocked: counts.blocked,
+                errors: counts.errors,
+                guardrail_pass_rate: round(ratio(guardrail_passed, runs.len())),
+                pass_rate: round(ratio(
+                    runs.iter().filter(|&&i| scores[i].1).count(),
+                    runs.len(),
+                )),
+                mean_score: round(mean(runs.iter().map(|&i| scores[i].0))),
+                distinct_responses: responses.iter().collect::<HashSet<_>>().len(),
+                response_divergence: round(divergence(&responses)),
+                mean_response_chars: round(mean_chars),
+                response_chars_stddev: round(
+                    mean(lengths.iter().map(|l| (l - mean_chars).powi(2))).sqrt(),
+                ),
+                mean_latency_ms: counts
+                    .total_latency_ms
+                    .checked_div(counts.total as u64)
+                    .unwrap_or(0),
+            }
+        })
+        .collect();
+
+    Ok(SweepReport {
+        repeats: sweep.repeats,
+        points: reports,
+    })
+}
+
+fn point_tag(index: usize) -> String {
+    format!("sweep_point={index}")
+}
+
+/// Mean pairwise Jaccard distance between the responses' word sets
+fn divergence(responses: &[String]) -> f64 {
+    let words: Vec<HashSet<String>> = responses
+        .iter()
+        .map(|r| r.split_whitespace().map(str::to_lowercase).collect())
+        .collect();
+    let mut distances = Vec::new();
+    for a in 0..words.len() {
+        for b in a + 1..words.len() {
+            let union = words[a].union(&words[b]).count();
+            let shared = words[a].intersection(&words[b]).count();
+            distances.push(if union == 0 {
+                0.0
+            } else {
+                1.0 - shared as f64 / union as f64
+            });
+        }
+    }
+    mean(distances.into_iter())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_enforces_budget() {
+        let sweep = SweepConfig {
+            temperatures: vec![0.0, 0.5, 1.0],
+            seeds: vec![1, 2],
+            repeats: 5,
+            max_runs: 20,
+            ..Default::default()
+        };
+        let err = sweep.validate().unwrap_err().to_string();
+        assert!(err.contains("30 run(s)"), "{err}");
+        assert!(err.contains("budget of 20"), "{err}");
+
+        let within = SweepConfig {
+            max_runs: 30,
+            ..sweep
+        };
+        assert!(within.validate().is_ok());
+
+        let bad_temperature = SweepConfig {
+            temperatures: vec![3.0],
+            ..Default::default()
+        };
+        assert!(bad_temperature.validate().is_err());
+    }
+
+    #[test]
+    fn test_divergence() {
+        let same = vec!["Paris is nice".to_string(), "paris is NICE".to_string()];
+        assert_eq!(divergence(&same), 0.0);
+
+        let disjoint = vec!["a b".to_string(), "c d".to_string()];
+        assert_eq!(divergence(&disjoint), 1.0);
+
+        let half = vec!["a b c".to_string(), "a b d".to_string()];
+        assert_eq!(divergence(&half), 0.5);
+        assert_eq!(divergence(&[]), 0.0);
+    }
+}
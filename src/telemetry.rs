@@ -0,0 +1,624 @@
+//! Tracing spans and OTLP export
+//!
+//! The evaluation pipeline is instrumented with [`tracing`] spans, so the
+//! latency of PDF extraction, guardrails and the LLM call can be inspected
+//! in any tracing backend:
+//!
+//! | Span | Attributes |
+//! |------|------------|
+//! | `evaluate` | `model`, `provider`, `outcome` |
+//! | `stage` | `stage` (one per pipeline stage) |
+//! | `pdf.extract` | `path`, `chars` |
+//! | `guardrail` | `provider`, `direction`, `passed`, `violations`, `warnings` |
+//! | `llm.invoke` | `model`, `provider`, `streaming` |
+//!
+//! Spans set `otel.name` (e.g. `stage invoke`), which `tracing-opentelemetry`
+//! and [`SpanCollector`] use as the exported span name. Applications with
+//! their own subscriber see the spans as is; [`SpanCollector`] is a minimal
+//! subscriber that buffers finished spans and posts them to an
+//! OTLP/HTTP endpoint (JSON encoding) such as Jaeger or Tempo on port 4318.
+//!
+//! The buffer is bounded ([`DEFAULT_MAX_BUFFERED_SPANS`]; the oldest spans
+//! are dropped with a warning when it is full), so long-running processes
+//! such as `serve` export it periodically with
+//! [`SpanCollector::spawn_exporter`]: every [`DEFAULT_EXPORT_INTERVAL`], or
+//! as soon as [`EXPORT_BATCH_SIZE`] spans are buffered.
+
+use crate::{error::CliError, guardrails::GuardrailResult};
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    thread::ThreadId,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+use tokio::{
+    sync::{oneshot, Notify},
+    task::JoinHandle,
+};
+use tracing::{
+    field::{Field, Visit},
+    span, Event, Metadata, Span, Subscriber,
+};
+
+/// Target prefix of the spans emitted by this crate
+const TARGET: &str = "fortified_llm_client";
+
+/// Default `service.name` of exported spans
+pub const DEFAULT_SERVICE_NAME: &str = "fortified-llm-client";
+
+/// Default cap on buffered spans (see [`SpanCollector::with_capacity`])
+pub const DEFAULT_MAX_BUFFERED_SPANS: usize = 2048;
+
+/// Default interval between background exports
+pub const DEFAULT_EXPORT_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Spans per OTLP request; a background exporter also exports early once
+/// this many spans are buffered
+pub const EXPORT_BATCH_SIZE: usize = 512;
+
+/// Span around a guardrail provider call; fill it with [`record_guardrail`]
+pub(crate) fn guardrail_span(provider: &str, direction: &str) -> Span {
+    tracing::info_span!(
+        "guardrail",
+        otel.name = %format!("guardrail {provider}"),
+        provider,
+        direction,
+        passed = tracing::field::Empty,
+        violations = tracing::field::Empty,
+        warnings = tracing::field::Empty,
+    )
+}
+
+/// Record the outcome of a guardrail call on its span
+pub(crate) fn record_guardrail(span: &Span, result: &Result<GuardrailResult, CliError>) {
+    if let Ok(result) = result {
+        span.record("passed", result.passed);
+        span.record("violations", result.violations.len());
+        span.record("warnings", result.warnings.len());
+    }
+}
+
+/// Value of a span attribute
+#[derive(Debug, Clone, PartialEq)]
+pub enum AttributeValue {
+    String(String),
+    Int(i64),
+    Double(f64),
+    Bool(bool),
+}
+
+impl AttributeValue {
+    fn to_otlp(&self) -> Value {
+        match self {
+            // OTLP JSON encodes 64-bit integers as strings
+            Self::String(s) => json!({ "stringValue": s }),
+            Self::Int(i) => json!({ "intValue": i.to_string() }),
+            Self::Double(d) => json!({ "doubleValue": d }),
+            Self::Bool(b) => json!({ "boolValue": b }),
+        }
+    }
+}
+
+/// A closed span, ready for export
+#[derive(Debug, Clone)]
+pub struct FinishedSpan {
+    /// 32 hex characters, shared by all spans of one trace
+    pub trace_id: String,
+    /// 16 hex characters
+    pub span_id: String,
+    pub parent_span_id: Option<String>,
+    pub name: String,
+    pub start: SystemTime,
+    pub end: SystemTime,
+    pub attributes: Vec<(String, AttributeValue)>,
+}
+
+impl FinishedSpan {
+    pub fn duration(&self) -> Duration {
+        self.end.duration_since(self.start).unwrap_or_default()
+    }
+
+    pub fn attribute(&self, key: &str) -> Option<&AttributeValue> {
+        self.attributes
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v)
+    }
+}
+
+struct OpenSpan {
+    span: FinishedSpan,
+    refs: usize,
+}
+
+#[derive(Default)]
+struct CollectorState {
+    open: HashMap<u64, OpenSpan>,
+    finished: VecDeque<FinishedSpan>,
+    /// Spans dropped because the buffer was full
+    dropped: u64,
+    /// Whether the buffer overflowed since the last take (warned once)
+    overflowed: bool,
+    /// Entered spans per thread, innermost last
+    stacks: HashMap<ThreadId, Vec<u64>>,
+}
+
+/// Subscriber buffering this crate's spans for OTLP export
+///
+/// Events and spans of other crates are ignored.
+///
+/// ```no_run
+/// # use fortified_llm_client::telemetry::SpanCollector;
+/// # use std::sync::Arc;
+/// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+/// let collector = Arc::new(SpanCollector::new());
+/// tracing::subscriber::set_global_default(collector.clone())?;
+///
+/// // ... run evaluations ...
+///
+/// collector.export_otlp("http://localhost:4318", "my-service").await?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct SpanCollector {
+    next_id: AtomicU64,
+    /// Per-process seed making exported ids unique across runs
+    seed: [u8; 32],
+    max_buffered: usize,
+    state: Mutex<CollectorState>,
+    /// Signals a background exporter that a full batch is buffered
+    batch_ready: Notify,
+}
+
+impl Default for SpanCollector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl std::fmt::Debug for SpanCollector {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SpanCollector").finish_non_exhaustive()
+    }
+}
+
+impl SpanCollector {
+    pub fn new() -> Self {
+        Self::with_capacity(DEFAULT_MAX_BUFFERED_SPANS)
+    }
+
+    /// Collector keeping at most `max_buffered` finished spans
+    ///
+    /// When the buffer is full, the oldest span is dropped for each new one
+    /// (with a warning the first time between two exports).
+    pub fn with_capacity(max_buffered: usize) -> Self {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos();
+        let seed = Sha256::digest(format!("{now}:{}", std::process::id()).as_bytes());
+        Self {
+            next_id: AtomicU64::new(1),
+            seed: seed.into(),
+            max_buffered: max_buffered.max(1),
+            state: Mutex::new(CollectorState::default()),
+            batch_ready: Notify::new(),
+        }
+    }
+
+    fn hex_id(&self, id: u64, kind: &str, len: usize) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(self.seed);
+        hasher.update(kind.as_bytes());
+        hasher.update(id.to_be_bytes());
+        hasher.finalize()[..len]
+            .iter()
+            .map(|b| format!("{b:02x}"))
+            .collect()
+    }
+
+    /// Spans closed so far, in closing order
+    pub fn finished_spans(&self) -> Vec<FinishedSpan> {
+        self.lock().finished.iter().cloned().collect()
+    }
+
+    /// Remove and return the spans closed so far
+    pub fn take_finished(&self) -> Vec<FinishedSpan> {
+        let mut state = self.lock();
+        state.overflowed = false;
+        std::mem::take(&mut state.finished).into()
+    }
+
+    /// Number of spans dropped so far because the buffer was full
+    pub fn dropped_spans(&self) -> u64 {
+        self.lock().dropped
+    }
+
+    /// OTLP/JSON `ExportTraceServiceRequest` for `spans`
+    pub fn otlp_payload(spans: &[FinishedSpan], service_name: &str) -> Value {
+        let nanos = |t: SystemTime| {
+            t.duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_nanos()
+                .to_string()
+        };
+        let spans: Vec<Value> = spans
+            .iter()
+            .map(|s| {
+                let mut span = json!({
+                    "traceId": s.trace_id,
+                    "spanId": s.span_id,
+                    "name": s.name,
+                    "kind": 1,
+                    "startTimeUnixNano": nanos(s.start),
+                    "endTimeUnixNano": nanos(s.end),
+                    "attributes": s.attributes.iter().map(|(k, v)| {
+                        json!({ "key": k, "value": v.to_otlp() })
+                    }).collect::<Vec<_>>(),
+                });
+                if let Some(parent) = &s.parent_span_id {
+                    span["parentSpanId"] = json!(parent);
+                }
+                span
+            })
+            .collect();
+        json!({
+            "resourceSpans": [{
+                "resource": {
+                    "attributes": [
+                        { "key": "service.name", "value": { "stringValue": service_name } },
+                        { "key": "service.version", "value": { "stringValue": env!("CARGO_PKG_VERSION") } },
+                    ]
+                },
+                "scopeSpans": [{
+                    "scope": { "name": TARGET, "version": env!("CARGO_PKG_VERSION") },
+                    "spans": spans,
+                }]
+            }]
+        })
+    }
+
+    /// Post the finished spans to an OTLP/HTTP endpoint and clear them
+    ///
+    /// `endpoint` is the collector base URL (`/v1/traces` is appended unless
+    /// already present). Spans are sent in requests of at most
+    /// [`EXPORT_BATCH_SIZE`]. Returns the number of exported spans.
+    pub async fn export_otlp(&self, endpoint: &str, service_name: &str) -> Result<usize, CliError> {
+        let spans = self.take_finished();
+        if spans.is_empty() {
+            return Ok(0);
+        }
+        let endpoint = endpoint.trim_end_matches('/');
+        let url = if endpoint.ends_with("/v1/traces") {
+            endpoint.to_string()
+        } else {
+            format!("{endpoint}/v1/traces")
+        };
+
+        let client = reqwest::Client::new();
+        for batch in spans.chunks(EXPORT_BATCH_SIZE) {
+            let response = client
+                .post(&url)
+                .timeout(Duration::from_secs(10))
+                .json(&Self::otlp_payload(batch, service_name))
+                .send()
+                .await?;
+            if !response.status().is_success() {
+                return Err(CliError::InvalidResponse(format!(
+                    "OTLP export to {url} failed: HTTP {}",
+                    response.status()
+                )));
+            }
+        }
+        Ok(spans.len())
+    }
+
+    /// Export the buffered spans from a background task until
+    /// [`OtlpExporter::shutdown`]
+    ///
+    /// Exports run every `interval`, and as soon as [`EXPORT_BATCH_SIZE`]
+    /// spans are buffered. Failures are logged as warnings (the failed
+    /// batch is lost). Must be called within a Tokio runtime.
+    pub fn spawn_exporter(
+        self: &Arc<Self>,
+        endpoint: &str,
+        service_name: &str,
+        interval: Duration,
+    ) -> OtlpExporter {
+        let (shutdown, mut stop) = oneshot::channel();
+        let collector = self.clone();
+        let (endpoint, service_name) = (endpoint.to_string(), service_name.to_string());
+        let task = tokio::spawn(async move {
+            loop {
+                let last = tokio::select! {
+                    _ = &mut stop => true,
+                    _ = tokio::time::sleep(interval) => false,
+                    _ = collector.batch_ready.notified() => false,
+                };
+                match collector.export_otlp(&endpoint, &service_name).await {
+                    Ok(0) => {}
+                    Ok(count) => log::debug!("Exported {count} trace span(s) to {endpoint}"),
+                    Err(e) => log::warn!("Trace export failed: {e}"),
+                }
+                if last {
+                    break;
+                }
+            }
+        });
+        OtlpExporter { shutdown, task }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, CollectorState> {
+        self.state.lock().expect("span collector lock poisoned")
+    }
+}
+
+/// Copies span fields into attributes (`otel.name` renames the span)
+struct AttributeVisitor<'a>(&'a mut FinishedSpan);
+
+impl AttributeVisitor<'_> {
+    fn set(&mut self, field: &Field, value: AttributeValue) {
+        if field.name() == "otel.name" {
+            if let AttributeValue::String(name) = value {
+                self.0.name = name;
+            }
+            return;
+        }
+        match self
+            .0
+            .attributes
+            .iter_mut()
+            .find(|(k, _)| k == field.name())
+        {
+            Some((_, existing)) => *existing = value,
+            None => self.0.attributes.push((field.name().to_string(), value)),
+        }
+    }
+}
+
+impl Visit for AttributeVisitor<'_> {
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        self.set(field, AttributeValue::String(format!("{value:?}")));
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.set(field, AttributeValue::String(value.to_string()));
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.set(field, AttributeValue::Int(value));
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.set(field, AttributeValue::Int(value as i64));
+    }
+
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        self.set(field, AttributeValue::Double(value));
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.set(field, AttributeValue::Bool(value));
+    }
+}
+
+impl Subscriber for SpanCollector {
+    fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+        metadata.is_span() && metadata.target().starts_with(TARGET)
+    }
+
+    fn new_span(&self, attrs: &span::Attributes<'_>) -> span::Id {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let mut state = self.lock();
+
+        let parent = if attrs.is_root() {
+            None
+        } else if let Some(parent) = attrs.parent() {
+            Some(parent.into_u64())
+        } else {
+            state
+                .stacks
+                .get(&std::thread::current().id())
+                .and_then(|stack| stack.last().copied())
+        };
+        let parent = parent.and_then(|p| state.open.get(&p));
+        let (trace_id, parent_span_id) = match parent {
+            Some(parent) => (
+                parent.span.trace_id.clone(),
+                Some(parent.span.span_id.clone()),
+            ),
+            None => (self.hex_id(id, "trace", 16), None),
+        };
+
+        let now = SystemTime::now();
+        let mut span = FinishedSpan {
+            trace_id,
+            span_id: self.hex_id(id, "span", 8),
+            parent_span_id,
+            name: attrs.metadata().name().to_string(),
+            start: now,
+            end: now,
+            attributes: Vec::new(),
+        };
+        attrs.record(&mut AttributeVisitor(&mut span));
+        state.open.insert(id, OpenSpan { span, refs: 1 });
+        span::Id::from_u64(id)
+    }
+
+    fn record(&self, id: &span::Id, values: &span::Record<'_>) {
+        if let Some(open) = self.lock().open.get_mut(&id.into_u64()) {
+            values.record(&mut AttributeVisitor(&mut open.span));
+        }
+    }
+
+    fn record_follows_from(&self, _span: &span::Id, _follows: &span::Id) {}
+
+    fn event(&self, _event: &Event<'_>) {}
+
+    fn enter(&self, id: &span::Id) {
+        self.lock()
+            .stacks
+            .entry(std::thread::current().id())
+            .or_default()
+            .push(id.into_u64());
+    }
+
+    fn exit(&self, id: &span::Id) {
+        let mut state = self.lock();
+        let thread = std::thread::current().id();
+        if let Some(stack) = state.stacks.get_mut(&thread) {
+            if let Some(pos) = stack.iter().rposition(|&s| s == id.into_u64()) {
+                stack.remove(pos);
+            }
+            if stack.is_empty() {
+                state.stacks.remove(&thread);
+            }
+        }
+    }
+
+    fn clone_span(&self, id: &span::Id) -> span::Id {
+        if let Some(open) = self.lock().open.get_mut(&id.into_u64()) {
+            open.refs += 1;
+        }
+        id.clone()
+    }
+
+    fn try_close(&self, id: span::Id) -> bool {
+        let mut state = self.lock();
+        let key = id.into_u64();
+        let Some(open) = state.open.get_mut(&key) else {
+            return false;
+        };
+        open.refs -= 1;
+        if open.refs > 0 {
+            return false;
+        }
+        let mut open = state.open.remove(&key).expect("span is open");
+        open.span.end = SystemTime::now();
+        let mut first_overflow = false;
+        if state.finished.len() >= self.max_buffered {
+            state.finished.pop_front();
+            state.dropped += 1;
+            first_overflow = !std::mem::replace(&mut state.overflowed, true);
+        }
+        state.finished.push_back(open.span);
+        let batch_ready = state.finished.len() == EXPORT_BATCH_SIZE;
+        drop(state);
+
+        if first_overflow {
+            log::warn!(
+                "Trace span buffer full ({} spans); dropping the oldest spans until the next export",
+                self.max_buffered
+            );
+        }
+        if batch_ready {
+            self.batch_ready.notify_one();
+        }
+        true
+    }
+}
+
+/// Background OTLP export task (see [`SpanCollector::spawn_exporter`])
+#[derive(Debug)]
+pub struct OtlpExporter {
+    shutdown: oneshot::Sender<()>,
+    task: JoinHandle<()>,
+}
+
+impl OtlpExporter {
+    /// Export the remaining spans and stop the task
+    pub async fn shutdown(self) {
+        let _ = self.shutdown.send(());
+        let _ = self.task.await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_collector_links_children_and_records_fields() {
+        let collector = Arc::new(SpanCollector::new());
+        tracing::subscriber::with_default(collector.clone(), || {
+            let parent = tracing::info_span!("evaluate", model = "m");
+            let _entered = parent.enter();
+            let child = guardrail_span("RegexGuardrail", "input");
+            child.record("violations", 2usize);
+            drop(child);
+            // Spans of other crates are ignored
+            let _other = tracing::info_span!(target: "hyper", "connect");
+        });
+
+        let spans = collector.finished_spans();
+        assert_eq!(spans.len(), 2);
+        let (guardrail, evaluate) = (&spans[0], &spans[1]);
+        assert_eq!(guardrail.name, "guardrail RegexGuardrail");
+        assert_eq!(
+            guardrail.attribute("provider"),
+            Some(&AttributeValue::String("RegexGuardrail".to_string()))
+        );
+        assert_eq!(
+            guardrail.attribute("violations"),
+            Some(&AttributeValue::Int(2))
+        );
+        assert_eq!(guardrail.attribute("passed"), None);
+        assert_eq!(guardrail.trace_id, evaluate.trace_id);
+        assert_eq!(guardrail.parent_span_id.as_ref(), Some(&evaluate.span_id));
+        assert_eq!(evaluate.parent_span_id, None);
+        assert_eq!(evaluate.trace_id.len(), 32);
+        assert_eq!(evaluate.span_id.len(), 16);
+    }
+
+    #[test]
+    fn test_full_buffer_drops_oldest_spans() {
+        let collector = Arc::new(SpanCollector::with_capacity(2));
+        tracing::subscriber::with_default(collector.clone(), || {
+            for stage in ["a", "b", "c"] {
+                drop(tracing::info_span!("stage", otel.name = stage));
+            }
+        });
+
+        let names: Vec<String> = collector
+            .take_finished()
+            .into_iter()
+            .map(|s| s.name)
+            .collect();
+        assert_eq!(names, ["b", "c"]);
+        assert_eq!(collector.dropped_spans(), 1);
+    }
+
+    #[test]
+    fn test_otlp_payload_shape() {
+        let start = UNIX_EPOCH + Duration::from_millis(1500);
+        let span = FinishedSpan {
+            trace_id: "0".repeat(32),
+            span_id: "1".repeat(16),
+            parent_span_id: None,
+            name: "evaluate".to_string(),
+            start,
+            end: start + Duration::from_millis(20),
+            attributes: vec![
+                ("model".to_string(), AttributeValue::String("m".to_string())),
+                ("violations".to_string(), AttributeValue::Int(3)),
+            ],
+        };
+        let payload = SpanCollector::otlp_payload(&[span], "svc");
+        let resource = &payload["resourceSpans"][0];
+        assert_eq!(
+            resource["resource"]["attributes"][0]["value"]["stringValue"],
+            "svc"
+        );
+        let exported = &resource["scopeSpans"][0]["spans"][0];
+        assert_eq!(exported["startTimeUnixNano"], "1500000000");
+        assert_eq!(exported["endTimeUnixNano"], "1520000000");
+        assert_eq!(exported["attributes"][1]["value"]["intValue"], "3");
+        assert!(exported.get("parentSpanId").is_none());
+    }
+}
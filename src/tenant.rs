@@ -0,0 +1,246 @@
+//! Multi-tenant configuration registry
+//!
+//! Lets a server embedder register one configuration per tenant (endpoint,
+//! API key, model parameters, guardrail policy) and evaluate requests against
+//! it by tenant ID. Requests only carry prompts, so a caller can never swap in
+//! another tenant's key or weaken the tenant's guardrail policy.
+
+use crate::{accounting::UsageAccountant, evaluate, CliError, CliOutput, EvaluationConfig};
+use serde::Serialize;
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex, RwLock},
+};
+
+/// Per-request input for a tenant evaluation
+///
+/// Everything else (endpoint, key, sampling parameters, guardrails) comes from
+/// the tenant's registered configuration.
+#[derive(Debug, Clone, Default)]
+pub struct TenantRequest {
+    pub user_prompt: String,
+    /// Optional system prompt override (falls back to the tenant's system prompt)
+    pub system_prompt: Option<String>,
+    /// Optional override token (only effective if the tenant has an override policy)
+    pub override_token: Option<String>,
+}
+
+impl TenantRequest {
+    pub fn new(user_prompt: impl Into<String>) -> Self {
+        Self {
+            user_prompt: user_prompt.into(),
+            ..Default::default()
+        }
+    }
+}
+
+/// Cumulative per-tenant evaluation metrics
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct TenantMetrics {
+    /// Total evaluations attempted
+    pub requests: u64,
+    /// Evaluations that returned a successful output
+    pub successes: u64,
+    /// Evaluations blocked by the pipeline (guardrails, context limit, ...)
+    pub blocked: u64,
+    /// Evaluations that failed with a hard error (HTTP, auth, ...)
+    pub errors: u64,
+    /// Sum of estimated tokens across completed evaluations
+    pub tokens_estimated: u64,
+    /// Sum of latency across completed evaluations
+    pub total_latency_ms: u64,
+}
+
+impl TenantMetrics {
+    fn record(&mut self, result: &Result<CliOutput, CliError>) {
+        self.requests += 1;
+        match result {
+            Ok(output) => {
+                if output.status == "success" {
+                    self.successes += 1;
+                } else {
+                    self.blocked += 1;
+                }
+                self.tokens_estimated += output.metadata.tokens_estimated as u64;
+                self.total_latency_ms += output.metadata.latency_ms;
+            }
+            Err(_) => self.errors += 1,
+        }
+    }
+}
+
+struct TenantEntry {
+    config: EvaluationConfig,
+    metrics: Mutex<TenantMetrics>,
+}
+
+/// Registry of isolated per-tenant configurations
+///
+/// Cheap to share across tasks: wrap in an `Arc` and call
+/// [`TenantRegistry::evaluate_for_tenant`] concurrently.
+#[derive(Default)]
+pub struct TenantRegistry {
+    tenants: RwLock<HashMap<String, Arc<TenantEntry>>>,
+    accountant: Option<Arc<UsageAccountant>>,
+}
+
+impl TenantRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record usage per tenant ID and enforce the accountant's quotas
+    pub fn with_accountant(mut self, accountant: Arc<UsageAccountant>) -> Self {
+        self.accountant = Some(accountant);
+        self
+    }
+
+    /// Register (or replace) a tenant's configuration
+    ///
+    /// The prompt fields of `config` act as defaults; `user_prompt` is always
+    /// replaced by the request. Replacing a tenant resets its metrics.
+    pub fn register(&self, tenant_id: impl Into<String>, config: EvaluationConfig) {
+        let entry = Arc::new(TenantEntry {
+            config,
+            metrics: Mutex::new(TenantMetrics::default()),
+        });
+        self.tenants
+            .write()
+            .expect("tenant registry lock poisoned")
+            .insert(tenant_id.into(), entry);
+    }
+
+    /// Remove a tenant, returning `true` if it was registered
+    pub fn unregister(&self, tenant_id: &str) -> bool {
+        self.tenants
+            .write()
+            .expect("tenant registry lock poisoned")
+            .remove(tenant_id)
+            .is_some()
+    }
+
+    pub fn contains(&self, tenant_id: &str) -> bool {
+        self.tenants
+            .read()
+            .expect("tenant registry lock poisoned")
+            .contains_key(tenant_id)
+    }
+
+    /// Registered tenant IDs, sorted
+    pub fn tenant_ids(&self) -> Vec<String> {
+        let mut ids: Vec<String> = self
+            .tenants
+            .read()
+            .expect("tenant registry lock poisoned")
+            .keys()
+            .cloned()
+            .collect();
+        ids.sort();
+        ids
+    }
+
+    /// Snapshot of a tenant's metrics
+    pub fn metrics(&self, tenant_id: &str) -> Option<TenantMetrics> {
+        self.entry(tenant_id)
+            .map(|entry| entry.metrics.lock().expect("metrics lock poisoned").clone())
+    }
+
+    /// Evaluate a request using the tenant's registered configuration
+    ///
+    /// Returns `InvalidArguments` if the tenant is not registered, and
+    /// `QuotaExceeded` if an attached accountant reports the tenant over quota.
+    pub async fn evaluate_for_tenant(
+        &self,
+        tenant_id: &str,
+        request: TenantRequest,
+    ) -> Result<CliOutput, CliError> {
+        let entry = self
+            .entry(tenant_id)
+            .ok_or_else(|| CliError::InvalidArguments(format!("Unknown tenant: '{tenant_id}'")))?;
+
+        if let Some(accountant) = &self.accountant {
+            accountant.reserve(tenant_id)?;
+        }
+
+        let mut config = entry.config.clone();
+        config.user_prompt = request.user_prompt;
+        config.user_prompt_file = None;
+        config.pdf_input = None;
+        config.url_input = None;
+        config.override_token = request.override_token;
+        if let Some(system_prompt) = request.system_prompt {
+            config.system_prompt = system_prompt;
+            config.system_prompt_file = None;
+            config.system_prompt_url = None;
+        }
+
+        log::debug!("Evaluating request for tenant '{tenant_id}'");
+        let result = evaluate(config).await;
+
+        entry
+            .metrics
+            .lock()
+            .expect("metrics lock poisoned")
+            .record(&result);
+        if let Some(accountant) = &self.accountant {
+            accountant.settle_result(tenant_id, &result);
+        }
+
+        result
+    }
+
+    fn entry(&self, tenant_id: &str) -> Option<Arc<TenantEntry>> {
+        self.tenants
+            .read()
+            .expect("tenant registry lock poisoned")
+            .get(tenant_id)
+            .cloned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config_builder::ConfigBuilder;
+
+    fn test_config(api_url: &str) -> EvaluationConfig {
+        ConfigBuilder::new()
+            .api_url(api_url)
+            .model("test-model")
+            .system_prompt("You are helpful.")
+            .user_prompt("")
+            .timeout_secs(5)
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_register_and_list_tenants() {
+        let registry = TenantRegistry::new();
+        registry.register("beta", test_config("http://beta.example.com"));
+        registry.register("alpha", test_config("http://alpha.example.com"));
+
+        assert!(registry.contains("alpha"));
+        assert_eq!(registry.tenant_ids(), vec!["alpha", "beta"]);
+        assert_eq!(registry.metrics("alpha"), Some(TenantMetrics::default()));
+        assert!(registry.metrics("gamma").is_none());
+
+        assert!(registry.unregister("alpha"));
+        assert!(!registry.unregister("alpha"));
+        assert_eq!(registry.tenant_ids(), vec!["beta"]);
+    }
+
+    #[tokio::test]
+    async fn test_unknown_tenant_is_rejected() {
+        let registry = TenantRegistry::new();
+        let Err(err) = registry
+            .evaluate_for_tenant("missing", TenantRequest::new("hello"))
+            .await
+        else {
+            panic!("Unknown tenant should be rejected");
+        };
+
+        assert!(matches!(err, CliError::InvalidArguments(_)));
+        assert!(err.to_string().contains("missing"));
+    }
+}
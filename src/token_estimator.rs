@@ -1,10 +1,12 @@
-use crate::{constants::token_estimation, model_registry};
+use crate::{constants::token_estimation, tokenizer};
 
 /// Token estimator for calculating context requirements
 pub struct TokenEstimator {
     system_tokens: usize,
     user_tokens: usize,
     response_buffer: usize,
+    /// Counts come from an exact tokenizer backend
+    exact: bool,
 }
 
 impl TokenEstimator {
@@ -14,28 +16,28 @@ impl TokenEstimator {
             system_tokens: estimate_tokens(system_prompt),
             user_tokens: estimate_tokens(user_prompt),
             response_buffer: response_buffer as usize,
+            exact: false,
         }
     }
 
-    /// Create a new token estimator using model-specific tokenizer characteristics
+    /// Create a new token estimator using the model's tokenizer backend
     ///
-    /// Falls back to generic estimation if model is not recognized.
+    /// Uses an exact tokenizer when one is available (see [`crate::tokenizer`]),
+    /// the model's chars/token heuristic otherwise, and generic estimation if
+    /// the model is not recognized.
     pub fn new_for_model(
         system_prompt: &str,
         user_prompt: &str,
         response_buffer: u32,
         model_name: &str,
     ) -> Self {
-        if let Some(model_info) = model_registry::lookup_model(model_name) {
-            log::debug!(
-                "Using model-specific token estimation for {} ({} chars/token)",
-                model_name,
-                model_info.tokenizer.chars_per_token()
-            );
+        if let Some(backend) = tokenizer::tokenizer_for_model(model_name) {
+            log::debug!("Using {} token estimation for {model_name}", backend.name());
             Self {
-                system_tokens: model_info.estimate_tokens(system_prompt),
-                user_tokens: model_info.estimate_tokens(user_prompt),
+                system_tokens: backend.count_tokens(system_prompt),
+                user_tokens: backend.count_tokens(user_prompt),
                 response_buffer: response_buffer as usize,
+                exact: backend.is_exact(),
             }
         } else {
             log::debug!("Model '{model_name}' not in registry, using generic token estimation");
@@ -43,8 +45,17 @@ impl TokenEstimator {
         }
     }
 
+    /// Whether prompt counts come from an exact tokenizer
+    pub fn is_exact(&self) -> bool {
+        self.exact
+    }
+
     pub fn total_tokens_required(&self) -> usize {
         let base_tokens = self.system_tokens + self.user_tokens + self.response_buffer;
+        if self.exact {
+            // Exact counts only miss the chat template around the messages
+            return base_tokens + token_estimation::CHAT_TEMPLATE_TOKENS;
+        }
         let safety_tokens =
             (base_tokens as f64 * (token_estimation::SAFETY_MARGIN - 1.0)).ceil() as usize;
         base_tokens + safety_tokens
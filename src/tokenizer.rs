@@ -0,0 +1,316 @@
+//! Tokenizer backends for token estimation
+//!
+//! [`TokenEstimator::new_for_model`](crate::TokenEstimator::new_for_model)
+//! counts tokens with the backend selected for the model:
+//!
+//! 1. A backend registered with [`register_tokenizer`]
+//! 2. The model registry's [`TokenizerSource`], when its feature is enabled:
+//!    - `tiktoken`: OpenAI BPE encodings (`cl100k_base`, `o200k_base`) for GPT models
+//!    - `hf-tokenizers`: the `tokenizer.json` of Llama, Qwen and Mistral models,
+//!      read from the local Hugging Face hub cache (`$HF_HUB_CACHE`,
+//!      `$HF_HOME/hub` or `~/.cache/huggingface/hub`); nothing is downloaded
+//! 3. The chars-per-token heuristic of the model's tokenizer family
+//!
+//! Exact backends make estimates match actual prompt usage within a few
+//! tokens, so the estimator drops its safety margin for them.
+
+use crate::model_registry::{self, TokenizerSource};
+#[cfg(feature = "hf-tokenizers")]
+use crate::CliError;
+use once_cell::sync::Lazy;
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex, RwLock},
+};
+
+/// Counts the tokens of a text for one model
+pub trait TokenizerBackend: Send + Sync {
+    fn count_tokens(&self, text: &str) -> usize;
+
+    /// Whether counts are exact rather than approximations
+    fn is_exact(&self) -> bool {
+        true
+    }
+
+    /// Backend name for logging (e.g. `tiktoken:cl100k_base`)
+    fn name(&self) -> &str;
+}
+
+/// Chars-per-token approximation
+pub struct HeuristicTokenizer {
+    chars_per_token: f64,
+}
+
+impl HeuristicTokenizer {
+    pub fn new(chars_per_token: f64) -> Self {
+        Self { chars_per_token }
+    }
+}
+
+impl TokenizerBackend for HeuristicTokenizer {
+    fn count_tokens(&self, text: &str) -> usize {
+        (text.len() as f64 / self.chars_per_token).ceil() as usize
+    }
+
+    fn is_exact(&self) -> bool {
+        false
+    }
+
+    fn name(&self) -> &str {
+        "heuristic"
+    }
+}
+
+/// OpenAI BPE encoding (feature `tiktoken`)
+#[cfg(feature = "tiktoken")]
+pub struct TiktokenTokenizer {
+    bpe: &'static tiktoken_rs::CoreBPE,
+    name: String,
+}
+
+#[cfg(feature = "tiktoken")]
+impl TiktokenTokenizer {
+    /// Encoding by name: `cl100k_base`, `o200k_base`, `p50k_base` or `r50k_base`
+    pub fn new(encoding: &str) -> Option<Self> {
+        let bpe = match encoding {
+            "cl100k_base" => tiktoken_rs::cl100k_base_singleton(),
+            "o200k_base" => tiktoken_rs::o200k_base_singleton(),
+            "p50k_base" => tiktoken_rs::p50k_base_singleton(),
+            "r50k_base" => tiktoken_rs::r50k_base_singleton(),
+            _ => return None,
+        };
+        Some(Self {
+            bpe,
+            name: format!("tiktoken:{encoding}"),
+        })
+    }
+}
+
+#[cfg(feature = "tiktoken")]
+impl TokenizerBackend for TiktokenTokenizer {
+    fn count_tokens(&self, text: &str) -> usize {
+        // Special token markup in prompts is sent as plain text
+        self.bpe.encode_ordinary(text).len()
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+/// Hugging Face `tokenizer.json` (feature `hf-tokenizers`)
+#[cfg(feature = "hf-tokenizers")]
+pub struct HuggingFaceTokenizer {
+    tokenizer: tokenizers::Tokenizer,
+    name: String,
+}
+
+#[cfg(feature = "hf-tokenizers")]
+impl HuggingFaceTokenizer {
+    pub fn from_file(path: impl AsRef<std::path::Path>) -> Result<Self, CliError> {
+        let path = path.as_ref();
+        let tokenizer = tokenizers::Tokenizer::from_file(path).map_err(|e| {
+            CliError::InvalidArguments(format!(
+                "Failed to load tokenizer '{}': {e}",
+                path.display()
+            ))
+        })?;
+        Ok(Self {
+            tokenizer,
+            name: format!("hf:{}", path.display()),
+        })
+    }
+
+    /// Tokenizer of `repo` (e.g. `meta-llama/Llama-3.1-8B-Instruct`) from the
+    /// local hub cache, if it was downloaded
+    pub fn from_cache(repo: &str) -> Option<Self> {
+        let path = hub_cache_file(repo, "tokenizer.json")?;
+        match Self::from_file(&path) {
+            Ok(tokenizer) => Some(Self {
+                name: format!("hf:{repo}"),
+                ..tokenizer
+            }),
+            Err(e) => {
+                log::warn!("{e}");
+                None
+            }
+        }
+    }
+}
+
+#[cfg(feature = "hf-tokenizers")]
+impl TokenizerBackend for HuggingFaceTokenizer {
+    fn count_tokens(&self, text: &str) -> usize {
+        match self.tokenizer.encode(text, false) {
+            Ok(encoding) => encoding.len(),
+            Err(e) => {
+                log::debug!("{} failed to encode text ({e}); estimating", self.name);
+                HeuristicTokenizer::new(crate::constants::token_estimation::CHARS_PER_TOKEN)
+                    .count_tokens(text)
+            }
+        }
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+/// `file` of the cached snapshot of `repo` (the `main` ref, else any snapshot)
+#[cfg(feature = "hf-tokenizers")]
+fn hub_cache_file(repo: &str, file: &str) -> Option<std::path::PathBuf> {
+    use std::path::PathBuf;
+
+    let hub = std::env::var_os("HF_HUB_CACHE")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HF_HOME").map(|home| PathBuf::from(home).join("hub")))
+        .or_else(|| {
+            std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".cache/huggingface/hub"))
+        })?;
+    let model_dir = hub.join(format!("models--{}", repo.replace('/', "--")));
+    let snapshots = model_dir.join("snapshots");
+    if let Ok(revision) = std::fs::read_to_string(model_dir.join("refs/main")) {
+        let path = snapshots.join(revision.trim()).join(file);
+        if path.is_file() {
+            return Some(path);
+        }
+    }
+    std::fs::read_dir(&snapshots)
+        .ok()?
+        .filter_map(Result::ok)
+        .map(|entry| entry.path().join(file))
+        .find(|path| path.is_file())
+}
+
+static REGISTERED: Lazy<RwLock<HashMap<String, Arc<dyn TokenizerBackend>>>> =
+    Lazy::new(Default::default);
+
+/// Exact backends loaded per registry source (None = unavailable)
+type LoadedBackends = HashMap<TokenizerSource, Option<Arc<dyn TokenizerBackend>>>;
+
+static LOADED: Lazy<Mutex<LoadedBackends>> = Lazy::new(Default::default);
+
+/// Use `backend` for `model` (case-insensitive), ahead of the model registry
+pub fn register_tokenizer(model: &str, backend: Arc<dyn TokenizerBackend>) {
+    REGISTERED
+        .write()
+        .unwrap_or_else(|e| e.into_inner())
+        .insert(model.to_lowercase(), backend);
+}
+
+/// Backend counting tokens for `model`, or None for unknown models
+pub fn tokenizer_for_model(model: &str) -> Option<Arc<dyn TokenizerBackend>> {
+    let registered = REGISTERED
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .get(&model.to_lowercase())
+        .cloned();
+    if registered.is_some() {
+        return registered;
+    }
+
+    let info = model_registry::lookup_model(model)?;
+    let exact = info.tokenizer_source.and_then(|source| {
+        LOADED
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .entry(source)
+            .or_insert_with(|| load(source))
+            .clone()
+    });
+    Some(
+        exact
+            .unwrap_or_else(|| Arc::new(HeuristicTokenizer::new(info.tokenizer.chars_per_token()))),
+    )
+}
+
+/// Exact backend for `source`, if its feature is enabled and it loads
+fn load(source: TokenizerSource) -> Option<Arc<dyn TokenizerBackend>> {
+    let backend: Option<Arc<dyn TokenizerBackend>> = match source {
+        #[cfg(feature = "tiktoken")]
+        TokenizerSource::Tiktoken(encoding) => {
+            TiktokenTokenizer::new(encoding).map(|t| Arc::new(t) as _)
+        }
+        #[cfg(feature = "hf-tokenizers")]
+        TokenizerSource::HuggingFace(repo) => {
+            HuggingFaceTokenizer::from_cache(repo).map(|t| Arc::new(t) as _)
+        }
+        #[allow(unreachable_patterns)]
+        _ => None,
+    };
+    match &backend {
+        Some(backend) => log::debug!("Loaded tokenizer {}", backend.name()),
+        None => log::debug!("No exact tokenizer for {source:?}; using the heuristic"),
+    }
+    backend
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct WordTokenizer;
+
+    impl TokenizerBackend for WordTokenizer {
+        fn count_tokens(&self, text: &str) -> usize {
+            text.split_whitespace().count()
+        }
+
+        fn name(&self) -> &str {
+            "words"
+        }
+    }
+
+    #[test]
+    fn test_registered_backend_takes_precedence() {
+        register_tokenizer("Word-Model", Arc::new(WordTokenizer));
+        let backend = tokenizer_for_model("word-model").unwrap();
+        assert_eq!(backend.count_tokens("one two three"), 3);
+        assert!(backend.is_exact());
+
+        assert!(tokenizer_for_model("unknown-model-xyz").is_none());
+    }
+
+    #[test]
+    fn test_registry_models_fall_back_to_heuristic() {
+        let backend = tokenizer_for_model("mistral-7b").unwrap();
+        if !cfg!(feature = "hf-tokenizers") {
+            assert_eq!(backend.name(), "heuristic");
+            assert!(!backend.is_exact());
+            assert_eq!(backend.count_tokens("Hello, world!"), 4); // ceil(13 / 3.8)
+        }
+    }
+
+    #[cfg(feature = "tiktoken")]
+    #[test]
+    fn test_tiktoken_counts_gpt_tokens() {
+        let backend = tokenizer_for_model("gpt-4").unwrap();
+        assert_eq!(backend.name(), "tiktoken:cl100k_base");
+        assert_eq!(backend.count_tokens("Hello, world!"), 4);
+        let backend = tokenizer_for_model("gpt-4o").unwrap();
+        assert_eq!(backend.name(), "tiktoken:o200k_base");
+    }
+
+    #[cfg(feature = "hf-tokenizers")]
+    #[test]
+    fn test_huggingface_tokenizer_from_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("tokenizer.json");
+        std::fs::write(
+            &path,
+            r#"{"version": "1.0", "truncation": null, "padding": null, "added_tokens": [],
+                "normalizer": null, "pre_tokenizer": {"type": "Whitespace"},
+                "post_processor": null, "decoder": null,
+                "model": {"type": "WordLevel", "vocab": {"hello": 0, "[UNK]": 1},
+                          "unk_token": "[UNK]"}}"#,
+        )
+        .unwrap();
+        let backend = HuggingFaceTokenizer::from_file(&path).unwrap();
+        assert_eq!(backend.count_tokens("hello unknown world !"), 4);
+
+        let err = HuggingFaceTokenizer::from_file(dir.path().join("missing.json"))
+            .err()
+            .unwrap();
+        assert!(matches!(err, CliError::InvalidArguments(_)), "{err}");
+    }
+}
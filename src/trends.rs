@@ -0,0 +1,460 @@
+//! Quality and safety trends across runs
+//!
+//! Records per-day counters for every evaluation, keyed by a fingerprint of
+//! the configuration (model, endpoint, prompts, sampling and guardrails), so
+//! silent model or provider regressions show up as shifts in refusal rate,
+//! guardrail violation rate or mean quality score between periods.
+//!
+//! ```toml
+//! [trend_stats]
+//! dir = "./trends"   # Local stats store
+//! ```
+//!
+//! `fortified-llm-client trends --dir ./trends` prints the report.
+
+use crate::{
+    storage::{FsStorage, Storage},
+    CliError, CliOutput, EvaluationConfig, EvaluationOutcome,
+};
+use chrono::{NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::{
+    path::PathBuf,
+    sync::{Arc, Mutex},
+};
+
+const KEY_PREFIX: &str = "trends";
+
+/// Rate increase (percentage points) flagged as a regression
+const RATE_REGRESSION: f64 = 0.10;
+
+/// Mean quality score drop flagged as a regression
+const QUALITY_REGRESSION: f64 = 1.0;
+
+/// Trend store settings as written in config files (`[trend_stats]`)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TrendStatsConfig {
+    /// Directory of the local stats store
+    pub dir: PathBuf,
+}
+
+/// Identity of a fingerprinted configuration, shown in reports
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TrendSubject {
+    pub model: String,
+    pub api_url: String,
+}
+
+/// Counters for one configuration on one day
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TrendCounters {
+    pub runs: u64,
+    pub refusals: u64,
+    pub input_violations: u64,
+    pub output_violations: u64,
+    #[serde(default)]
+    pub quality_sum: f64,
+    #[serde(default)]
+    pub quality_count: u64,
+}
+
+impl TrendCounters {
+    fn add(&mut self, other: &TrendCounters) {
+        self.runs += other.runs;
+        self.refusals += other.refusals;
+        self.input_violations += other.input_violations;
+        self.output_violations += other.output_violations;
+        self.quality_sum += other.quality_sum;
+        self.quality_count += other.quality_count;
+    }
+
+    fn rate(&self, count: u64) -> Option<f64> {
+        (self.runs > 0).then(|| round(count as f64 / self.runs as f64))
+    }
+
+    fn summary(&self) -> PeriodSummary {
+        PeriodSummary {
+            runs: self.runs,
+            refusal_rate: self.rate(self.refusals),
+            violation_rate: self.rate(self.input_violations + self.output_violations),
+            mean_quality: (self.quality_count > 0)
+                .then(|| round(self.quality_sum / self.quality_count as f64)),
+        }
+    }
+}
+
+/// Rates over a period (None = no runs / no quality scores)
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PeriodSummary {
+    pub runs: u64,
+    pub refusal_rate: Option<f64>,
+    /// Input and output guardrail blocks per run
+    pub violation_rate: Option<f64>,
+    pub mean_quality: Option<f64>,
+}
+
+/// Trend report for one configuration
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TrendReport {
+    pub fingerprint: String,
+    #[serde(flatten)]
+    pub subject: TrendSubject,
+    /// Days before the recent period, within the report window
+    pub baseline: PeriodSummary,
+    /// Most recent days
+    pub recent: PeriodSummary,
+    /// Regressions of the recent period against the baseline
+    pub regressions: Vec<String>,
+}
+
+/// Fingerprint of the settings that shape model behavior
+///
+/// The system prompt is part of the fingerprint; the user prompt, API keys
+/// (guardrail keys and key sources included), timeouts and other per-request
+/// settings are not.
+pub fn config_fingerprint(config: &EvaluationConfig) -> String {
+    let mut identity = serde_json::json!({
+        "api_url": config.api_url,
+        "model": config.model,
+        "provider": config.provider.map(|p| format!("{p:?}")),
+        "system_prompt": config.system_prompt,
+        "temperature": config.temperature,
+        "top_p": config.top_p,
+        "max_tokens": config.max_tokens,
+        "seed": config.seed,
+        "response_format": config.response_format,
+        "input_guardrails": without_credentials(serde_json::json!(config.input_guardrails)),
+        "output_guardrails": without_credentials(serde_json::json!(config.output_guardrails)),
+    });
+    // Only when set, so fingerprints recorded before conditions existed stay valid
+    if !config.guardrail_conditions.is_empty() {
+        identity["guardrail_conditions"] = serde_json::json!(config.guardrail_conditions);
+    }
+    let digest = Sha256::digest(identity.to_string().as_bytes());
+    digest[..8].iter().map(|b| format!("{b:02x}")).collect()
+}
+
+/// Guardrail key fields, left out of the fingerprint
+const CREDENTIAL_FIELDS: [&str; 3] = ["api_key", "api_key_name", "api_key_secret"];
+
+/// Remove [`CREDENTIAL_FIELDS`] at any depth (composite members included)
+fn without_credentials(value: serde_json::Value) -> serde_json::Value {
+    use serde_json::Value;
+    match value {
+        Value::Object(map) => Value::Object(
+            map.into_iter()
+                .filter(|(k, _)| !CREDENTIAL_FIELDS.contains(&k.as_str()))
+                .map(|(k, v)| (k, without_credentials(v)))
+                .collect(),
+        ),
+        Value::Array(items) => Value::Array(items.into_iter().map(without_credentials).collect()),
+        other => other,
+    }
+}
+
+/// Per-configuration daily counters kept in a [`Storage`]
+pub struct TrendStore {
+    storage: Arc<dyn Storage>,
+    /// Serializes read-modify-write updates within the process
+    lock: Mutex<()>,
+}
+
+impl std::fmt::Debug for TrendStore {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TrendStore").finish_non_exhaustive()
+    }
+}
+
+impl TrendStore {
+    pub fn new(storage: Arc<dyn Storage>) -> Self {
+        Self {
+            storage,
+            lock: Mutex::new(()),
+        }
+    }
+
+    pub fn from_config(config: &TrendStatsConfig) -> Self {
+        Self::new(Arc::new(FsStorage::new(&config.dir)))
+    }
+
+    /// Record the output of one evaluation
+    ///
+    /// `quality_score` is the output guardrail's score, when it reports one.
+    pub fn record(
+        &self,
+        config: &EvaluationConfig,
+        output: &CliOutput,
+        quality_score: Option<f32>,
+    ) -> Result<(), CliError> {
+        let fingerprint = config_fingerprint(config);
+        let mut sample = TrendCounters {
+            runs: 1,
+            ..Default::default()
+        };
+        let refusal_warning = output
+            .metadata
+            .warnings
+            .iter()
+            .any(|w| w.code == "REFUSAL_DETECTED");
+        match (output.outcome(), output.blocked_direction()) {
+            (Some(EvaluationOutcome::Refusal), _) => sample.refusals = 1,
+            (_, Some("input")) => sample.input_violations = 1,
+            (_, Some("output")) => sample.output_violations = 1,
+            _ if refusal_warning => sample.refusals = 1,
+            _ => {}
+        }
+        if let Some(score) = quality_score {
+            sample.quality_sum = f64::from(score);
+            sample.quality_count = 1;
+        }
+
+        let _guard = self.lock.lock().expect("trend store lock poisoned");
+        let subject_key = format!("{KEY_PREFIX}/{fingerprint}/subject");
+        if !self.storage.contains(&subject_key)? {
+            let subject = TrendSubject {
+                model: config.model.clone(),
+                api_url: config.api_url.clone(),
+            };
+            self.storage.put(&subject_key, &to_json(&subject)?, None)?;
+        }
+
+        let day_key = format!(
+            "{KEY_PREFIX}/{fingerprint}/days/{}",
+            Utc::now().date_naive()
+        );
+        let mut counters: TrendCounters = self.read(&day_key)?.unwrap_or_default();
+        counters.add(&sample);
+        self.storage.put(&day_key, &to_json(&counters)?, None)
+    }
+
+    /// Fingerprints with recorded runs, sorted
+    pub fn fingerprints(&self) -> Result<Vec<String>, CliError> {
+        let mut fingerprints: Vec<String> = self
+            .storage
+            .list(&format!("{KEY_PREFIX}/"))?
+            .iter()
+            .filter_map(|key| key.split('/').nth(1).map(str::to_string))
+            .collect();
+        fingerprints.dedup();
+        Ok(fingerprints)
+    }
+
+    /// Daily counters for a fingerprint, oldest first
+    pub fn daily(&self, fingerprint: &str) -> Result<Vec<(NaiveDate, TrendCounters)>, CliError> {
+        let prefix = format!("{KEY_PREFIX}/{fingerprint}/days/");
+        let mut days = Vec::new();
+        for key in self.storage.list(&prefix)? {
+            let Ok(date) = key[prefix.len()..].parse::<NaiveDate>() else {
+                continue;
+            };
+            if let Some(counters) = self.read(&key)? {
+                days.push((date, counters));
+            }
+        }
+        Ok(days)
+    }
+
+    /// Compare the last `recent_days` against the preceding days of a
+    /// `window_days` window ending today
+    pub fn report(
+        &self,
+        fingerprint: &str,
+        window_days: u32,
+        recent_days: u32,
+    ) -> Result<TrendReport, CliError> {
+        if recent_days == 0 || recent_days >= window_days {
+            return Err(CliError::InvalidArguments(format!(
+                "Recent period ({recent_days} days) must be shorter than the window \
+                ({window_days} days) and non-empty"
+            )));
+        }
+        let today = Utc::now().date_naive();
+        let mut baseline = TrendCounters::default();
+        let mut recent = TrendCounters::default();
+        for (date, counters) in self.daily(fingerprint)? {
+            let age = (today - date).num_days();
+            if age < 0 || age >= i64::from(window_days) {
+                continue;
+            }
+            if age < i64::from(recent_days) {
+                recent.add(&counters);
+            } else {
+                baseline.add(&counters);
+            }
+        }
+
+        let baseline = baseline.summary();
+        let recent = recent.summary();
+        let regressions = regressions(&baseline, &recent);
+        let subject = self
+            .read(&format!("{KEY_PREFIX}/{fingerprint}/subject"))?
+            .unwrap_or_default();
+        Ok(TrendReport {
+            fingerprint: fingerprint.to_string(),
+            subject,
+            baseline,
+            recent,
+            regressions,
+        })
+    }
+
+    fn read<T: serde::de::DeserializeOwned>(&self, key: &str) -> Result<Option<T>, CliError> {
+        self.storage
+            .get(key)?
+            .map(|bytes| {
+                serde_json::from_slice(&bytes).map_err(|e| {
+                    CliError::InvalidResponse(format!("Corrupt trend entry '{key}': {e}"))
+                })
+            })
+            .transpose()
+    }
+}
+
+fn regressions(baseline: &PeriodSummary, recent: &PeriodSummary) -> Vec<String> {
+    let mut found = Vec::new();
+    let rates = [
+        ("refusal_rate", baseline.refusal_rate, recent.refusal_rate),
+        (
+            "violation_rate",
+            baseline.violation_rate,
+            recent.violation_rate,
+        ),
+    ];
+    for (name, before, after) in rates {
+        if let (Some(before), Some(after)) = (before, after) {
+            if after - before >= RATE_REGRESSION {
+                found.push(format!("{name} rose from {before} to {after}"));
+            }
+        }
+    }
+    if let (Some(before), Some(after)) = (baseline.mean_quality, recent.mean_quality) {
+        if before - after >= QUALITY_REGRESSION {
+            found.push(format!("mean_quality fell from {before} to {after}"));
+        }
+    }
+    found
+}
+
+fn round(value: f64) -> f64 {
+    (value * 1000.0).round() / 1000.0
+}
+
+fn to_json<T: Serialize>(value: &T) -> Result<Vec<u8>, CliError> {
+    serde_json::to_vec(value)
+        .map_err(|e| CliError::InvalidArguments(format!("Failed to serialize trend entry: {e}")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        config_builder::ConfigBuilder,
+        guardrails::{AggregationMode, ExecutionMode, GuardrailProviderConfig},
+        storage::MemoryStorage,
+        EvaluationContext,
+    };
+
+    fn config(model: &str) -> EvaluationConfig {
+        ConfigBuilder::new()
+            .api_url("http://localhost:11434/v1/chat/completions")
+            .model(model)
+            .system_prompt("s")
+            .user_prompt("u")
+            .build()
+            .unwrap()
+    }
+
+    fn output(outcome: Option<EvaluationOutcome>) -> CliOutput {
+        let metadata = EvaluationContext::new(config("m")).metadata();
+        match outcome {
+            None => CliOutput::success("ok".to_string(), metadata, None),
+            Some(outcome) => CliOutput::error(outcome, "blocked".to_string(), metadata),
+        }
+    }
+
+    #[test]
+    fn test_fingerprint_ignores_user_prompt_and_key() {
+        let a = config("m");
+        let mut b = config("m");
+        b.user_prompt = "other".to_string();
+        b.api_key = Some("sk-1".to_string());
+        assert_eq!(config_fingerprint(&a), config_fingerprint(&b));
+        assert_ne!(config_fingerprint(&a), config_fingerprint(&config("m2")));
+    }
+
+    #[test]
+    fn test_fingerprint_ignores_guardrail_keys() {
+        let guarded = |api_key: &str| {
+            let nemo = GuardrailProviderConfig::NemoGuardrails {
+                api_url: "http://localhost:8000/v1/chat/completions".to_string(),
+                config_id: "default".to_string(),
+                timeout_secs: 5,
+                api_key: Some(api_key.to_string()),
+                api_key_name: None,
+                api_key_secret: None,
+                resolved_api_key: None,
+            };
+            let mut config = config("m");
+            config.input_guardrails = Some(GuardrailProviderConfig::Composite {
+                providers: vec![nemo],
+                execution: ExecutionMode::Sequential,
+                aggregation: AggregationMode::AllMustPass,
+            });
+            config
+        };
+        let fingerprint = config_fingerprint(&guarded("key-1"));
+        assert_eq!(fingerprint, config_fingerprint(&guarded("key-2")));
+        assert_ne!(fingerprint, config_fingerprint(&config("m")));
+    }
+
+    #[test]
+    fn test_record_and_report() {
+        let store = TrendStore::new(Arc::new(MemoryStorage::new()));
+        let config = config("m");
+        store.record(&config, &output(None), Some(8.0)).unwrap();
+        store
+            .record(&config, &output(Some(EvaluationOutcome::Refusal)), None)
+            .unwrap();
+        store
+            .record(
+                &config,
+                &output(Some(EvaluationOutcome::OutputValidationFailed)),
+                Some(2.0),
+            )
+            .unwrap();
+
+        let fingerprint = config_fingerprint(&config);
+        assert_eq!(store.fingerprints().unwrap(), vec![fingerprint.clone()]);
+
+        let report = store.report(&fingerprint, 14, 7).unwrap();
+        assert_eq!(report.subject.model, "m");
+        assert_eq!(report.recent.runs, 3);
+        assert_eq!(report.recent.refusal_rate, Some(0.333));
+        assert_eq!(report.recent.violation_rate, Some(0.333));
+        assert_eq!(report.recent.mean_quality, Some(5.0));
+        assert_eq!(report.baseline.runs, 0);
+        assert!(report.regressions.is_empty());
+    }
+
+    #[test]
+    fn test_regressions() {
+        let summary = |refusal, quality| PeriodSummary {
+            runs: 10,
+            refusal_rate: Some(refusal),
+            violation_rate: Some(0.0),
+            mean_quality: Some(quality),
+        };
+        let found = regressions(&summary(0.05, 8.0), &summary(0.3, 6.5));
+        assert_eq!(found.len(), 2);
+        assert!(found[0].starts_with("refusal_rate rose"));
+        assert!(regressions(&summary(0.05, 8.0), &summary(0.1, 7.5)).is_empty());
+    }
+
+    #[test]
+    fn test_invalid_periods() {
+        let store = TrendStore::new(Arc::new(MemoryStorage::new()));
+        assert!(store.report("abc", 7, 7).is_err());
+        assert!(store.report("abc", 7, 0).is_err());
+    }
+}
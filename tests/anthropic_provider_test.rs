@@ -0,0 +1,137 @@
+// Anthropic provider tests
+//
+// Verifies the Messages API request shape (top-level system prompt, required
+// max_tokens, x-api-key/anthropic-version headers), response parsing, error
+// messages, and SSE streaming against a mock server.
+
+use fortified_llm_client::{config_builder::ConfigBuilder, evaluate, evaluate_streaming, Provider};
+use mockito::{Matcher, Server};
+use std::sync::{Arc, Mutex};
+
+fn config(server: &Server) -> ConfigBuilder {
+    ConfigBuilder::new()
+        .api_url(format!("{}/v1/messages", server.url()))
+        .provider(Provider::Anthropic)
+        .model("claude-sonnet-4-5")
+        .system_prompt("Be brief.")
+        .user_prompt("Hello")
+        .api_key("sk-ant-test")
+}
+
+#[tokio::test]
+async fn test_anthropic_messages_request_and_response() {
+    let mut server = Server::new_async().await;
+    let mock = server
+        .mock("POST", "/v1/messages")
+        .match_header("x-api-key", "sk-ant-test")
+        .match_header("anthropic-version", "2023-06-01")
+        .match_body(Matcher::PartialJsonString(
+            r#"{
+                "model": "claude-sonnet-4-5",
+                "max_tokens": 4000,
+                "system": "Be brief.",
+                "messages": [{"role": "user", "content": "Hello"}]
+            }"#
+            .to_string(),
+        ))
+        .with_status(200)
+        .with_body(
+            r#"{
+                "id": "msg_01",
+                "type": "message",
+                "role": "assistant",
+                "model": "claude-sonnet-4-5",
+                "content": [{"type": "text", "text": "Hi"}, {"type": "text", "text": " there"}],
+                "stop_reason": "end_turn"
+            }"#,
+        )
+        .create_async()
+        .await;
+
+    let output = evaluate(config(&server).build().unwrap()).await.unwrap();
+
+    mock.assert_async().await;
+    assert_eq!(output.status, "success");
+    assert_eq!(
+        output.response,
+        Some(serde_json::Value::String("Hi there".to_string()))
+    );
+    assert_eq!(output.metadata.provider.as_deref(), Some("Anthropic"));
+}
+
+#[tokio::test]
+async fn test_anthropic_error_message() {
+    let mut server = Server::new_async().await;
+    let _mock = server
+        .mock("POST", "/v1/messages")
+        .with_status(529)
+        .with_body(
+            r#"{"type": "error", "error": {"type": "overloaded_error", "message": "Overloaded"}}"#,
+        )
+        .create_async()
+        .await;
+
+    let Err(err) = evaluate(config(&server).build().unwrap()).await else {
+        panic!("Expected HTTP error");
+    };
+    assert!(err.to_string().contains("HTTP 529"), "{err}");
+    assert!(err.to_string().contains("overloaded_error: Overloaded"));
+}
+
+#[tokio::test]
+async fn test_anthropic_streaming() {
+    let mut server = Server::new_async().await;
+    let _mock = server
+        .mock("POST", "/v1/messages")
+        .match_body(Matcher::PartialJsonString(r#"{"stream": true}"#.to_string()))
+        .with_status(200)
+        .with_header("content-type", "text/event-stream")
+        .with_body(
+            "event: message_start\n\
+             data: {\"type\": \"message_start\", \"message\": {\"id\": \"msg_01\"}}\n\n\
+             event: content_block_delta\n\
+             data: {\"type\": \"content_block_delta\", \"index\": 0, \"delta\": {\"type\": \"text_delta\", \"text\": \"Hi\"}}\n\n\
+             event: ping\n\
+             data: {\"type\": \"ping\"}\n\n\
+             event: content_block_delta\n\
+             data: {\"type\": \"content_block_delta\", \"index\": 0, \"delta\": {\"type\": \"text_delta\", \"text\": \" there\"}}\n\n\
+             event: message_stop\n\
+             data: {\"type\": \"message_stop\"}\n\n",
+        )
+        .create_async()
+        .await;
+
+    let chunks = Arc::new(Mutex::new(Vec::new()));
+    let sink = chunks.clone();
+    let output = evaluate_streaming(config(&server).build().unwrap(), move |chunk: &str| {
+        sink.lock().unwrap().push(chunk.to_string())
+    })
+    .await
+    .unwrap();
+
+    assert_eq!(*chunks.lock().unwrap(), vec!["Hi", " there"]);
+    assert_eq!(
+        output.response,
+        Some(serde_json::Value::String("Hi there".to_string()))
+    );
+}
+
+#[test]
+fn test_cli_provider_anthropic() {
+    let mut server = mockito::Server::new();
+    let mock = server
+        .mock("POST", "/custom/endpoint")
+        .match_header("anthropic-version", "2023-06-01")
+        .with_status(200)
+        .with_body(r#"{"content": [{"type": "text", "text": "Hi"}], "stop_reason": "end_turn"}"#)
+        .create();
+
+    assert_cmd::cargo::cargo_bin_cmd!("fortified-llm-client")
+        .args(["--api-url", &format!("{}/custom/endpoint", server.url())])
+        .args(["--provider", "anthropic", "--model", "claude-haiku-4-5"])
+        .args(["--system-text", "Be brief.", "--user-text", "Hello"])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains(r#""response": "Hi""#));
+    mock.assert();
+}
@@ -2,7 +2,10 @@
 //
 // Tests how the client handles malformed, incomplete, or unexpected API responses
 
-use fortified_llm_client::{evaluate, EvaluationConfig, Provider};
+use fortified_llm_client::{
+    constants::http::DEFAULT_MAX_RESPONSE_BYTES, evaluate, EvaluationConfig, EvaluationOutcome,
+    Provider,
+};
 use mockito::Server;
 
 async fn create_test_config(api_url: String) -> EvaluationConfig {
@@ -13,18 +16,54 @@ async fn create_test_config(api_url: String) -> EvaluationConfig {
         user_prompt: "Test user".to_string(),
         provider: Some(Provider::OpenAI),
         temperature: 0.0,
+        top_p: None,
         max_tokens: Some(100),
         seed: None,
         api_key: Some("test-key".to_string()),
         timeout_secs: 5,
+        max_response_bytes: DEFAULT_MAX_RESPONSE_BYTES,
         validate_tokens: false,
         context_limit: None,
+        context_probe: None,
         response_format: None,
         pdf_input: None,
+        user_prompt_prefix: None,
+        user_prompt_suffix: None,
+        untrusted_content: Vec::new(),
+        spotlight: None,
         input_guardrails: None,
         output_guardrails: None,
+        refusal_detection: None,
+        repetition_detection: None,
+        deadline: None,
+        tools: Vec::new(),
+        context_files: Vec::new(),
+        input_sources: None,
+        escalation: None,
+        url_input: None,
+        system_prompt_url: None,
+        fetch: None,
+        image_input: None,
+        image_mode: Default::default(),
+        guard_budget: None,
+        chunking: None,
+        bedrock: None,
+        metrics: None,
+        response_cache: None,
+        rate_limiter: None,
+        data_policy: None,
+        metadata_fields: None,
+        tags: Vec::new(),
+        guardrail_conditions: Vec::new(),
         system_prompt_file: None,
         user_prompt_file: None,
+        guardrail_override: None,
+        override_token: None,
+        prompt_audit: None,
+        trend_stats: None,
+        config_warnings: Vec::new(),
+        context_warning_percent: None,
+        prompt_pins: Vec::new(),
     }
 }
 
@@ -386,6 +425,38 @@ async fn test_ollama_api_format() {
     mock.assert_async().await;
 }
 
+#[tokio::test]
+async fn test_ollama_ndjson_streaming_body_is_aggregated() {
+    let mut server = Server::new_async().await;
+    let mock = server
+        .mock("POST", "/api/generate")
+        .with_status(200)
+        .with_header("content-type", "application/x-ndjson")
+        .with_body(concat!(
+            r#"{"model": "test", "response": "Ollama ", "done": false}"#,
+            "\n",
+            r#"{"model": "test", "response": "stream", "done": false}"#,
+            "\n",
+            r#"{"model": "test", "response": "", "done": true}"#,
+            "\n",
+        ))
+        .create_async()
+        .await;
+
+    let mut config = create_test_config(server.url() + "/api/generate").await;
+    config.provider = Some(Provider::Ollama);
+
+    let output = evaluate(config).await.unwrap();
+
+    assert_eq!(
+        output.response,
+        Some(serde_json::Value::String("Ollama stream".to_string()))
+    );
+    assert_eq!(output.metadata.response_chunks_aggregated, Some(3));
+
+    mock.assert_async().await;
+}
+
 #[tokio::test]
 async fn test_ollama_missing_response_field() {
     let mut server = Server::new_async().await;
@@ -409,3 +480,236 @@ async fn test_ollama_missing_response_field() {
 
     mock.assert_async().await;
 }
+
+#[tokio::test]
+async fn test_openrouter_upstream_fields_captured_in_metadata() {
+    let mut server = Server::new_async().await;
+    let mock = server
+        .mock("POST", "/v1/chat/completions")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(
+            r#"{
+                "model": "test-model",
+                "provider": "Fireworks",
+                "choices": [{
+                    "message": {"role": "assistant", "content": "Routed"},
+                    "finish_reason": "length",
+                    "native_finish_reason": "max_tokens"
+                }]
+            }"#,
+        )
+        .create_async()
+        .await;
+
+    let config = create_test_config(server.url() + "/v1/chat/completions").await;
+    let output = evaluate(config).await.unwrap();
+
+    let upstream = output.metadata.upstream.as_ref().unwrap();
+    assert_eq!(upstream.provider.as_deref(), Some("Fireworks"));
+    assert_eq!(upstream.native_finish_reason.as_deref(), Some("max_tokens"));
+
+    let json = serde_json::to_value(&output).unwrap();
+    assert_eq!(json["metadata"]["upstream"]["provider"], "Fireworks");
+    assert_eq!(json["metadata"]["upstream"]["finish_reason"], "length");
+
+    mock.assert_async().await;
+}
+
+#[tokio::test]
+async fn test_openai_model_echo_captured_in_metadata() {
+    let mut server = Server::new_async().await;
+    let mock = server
+        .mock("POST", "/v1/chat/completions")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(
+            r#"{
+                "model": "gpt-4o-2024-08-06",
+                "system_fingerprint": "fp_abc123",
+                "service_tier": "default",
+                "choices": [{"message": {"role": "assistant", "content": "Hi"}}]
+            }"#,
+        )
+        .create_async()
+        .await;
+
+    let config = create_test_config(server.url() + "/v1/chat/completions").await;
+    let output = evaluate(config).await.unwrap();
+
+    let echo = output.metadata.model_echo.as_ref().unwrap();
+    assert_eq!(echo.model.as_deref(), Some("gpt-4o-2024-08-06"));
+    assert_eq!(echo.system_fingerprint.as_deref(), Some("fp_abc123"));
+    assert_eq!(echo.service_tier.as_deref(), Some("default"));
+
+    let json = serde_json::to_value(&output).unwrap();
+    assert_eq!(
+        json["metadata"]["model_echo"]["system_fingerprint"],
+        "fp_abc123"
+    );
+    assert!(json["metadata"]["model_echo"].get("model_digest").is_none());
+
+    mock.assert_async().await;
+}
+
+#[tokio::test]
+async fn test_ollama_model_digest_captured_in_metadata() {
+    let mut server = Server::new_async().await;
+    let generate = server
+        .mock("POST", "/api/generate")
+        .with_status(200)
+        .with_body(r#"{"model": "test-model", "response": "Hi", "done": true}"#)
+        .create_async()
+        .await;
+    let tags = server
+        .mock("GET", "/api/tags")
+        .with_status(200)
+        .with_body(
+            r#"{"models": [
+                {"name": "other:latest", "digest": "0000"},
+                {"name": "test-model:latest", "digest": "sha256:feedbeef"}
+            ]}"#,
+        )
+        .expect(1)
+        .create_async()
+        .await;
+
+    let mut config = create_test_config(server.url() + "/api/generate").await;
+    config.provider = Some(Provider::Ollama);
+    let output = evaluate(config).await.unwrap();
+
+    let echo = output.metadata.model_echo.unwrap();
+    assert_eq!(echo.model.as_deref(), Some("test-model"));
+    assert_eq!(echo.model_digest.as_deref(), Some("sha256:feedbeef"));
+
+    generate.assert_async().await;
+    tags.assert_async().await;
+}
+
+#[tokio::test]
+async fn test_openai_usage_reported_alongside_estimate() {
+    let mut server = Server::new_async().await;
+    let mock = server
+        .mock("POST", "/v1/chat/completions")
+        .with_status(200)
+        .with_body(
+            r#"{
+                "choices": [{"message": {"role": "assistant", "content": "Hi"}}],
+                "usage": {"prompt_tokens": 21, "completion_tokens": 2, "total_tokens": 23}
+            }"#,
+        )
+        .create_async()
+        .await;
+
+    let mut config = create_test_config(server.url() + "/v1/chat/completions").await;
+    config.validate_tokens = true;
+    let output = evaluate(config).await.unwrap();
+
+    assert!(output.metadata.tokens_estimated > 0);
+    assert_eq!(output.metadata.tokens_prompt_actual, Some(21));
+    assert_eq!(output.metadata.tokens_completion_actual, Some(2));
+
+    mock.assert_async().await;
+}
+
+#[tokio::test]
+async fn test_ollama_usage_counts_captured() {
+    let mut server = Server::new_async().await;
+    let mock = server
+        .mock("POST", "/api/generate")
+        .with_status(200)
+        .with_body(r#"{"response": "Hi", "done": true, "prompt_eval_count": 26, "eval_count": 5}"#)
+        .create_async()
+        .await;
+
+    let mut config = create_test_config(server.url() + "/api/generate").await;
+    config.provider = Some(Provider::Ollama);
+    let output = evaluate(config).await.unwrap();
+
+    let json = serde_json::to_value(&output.metadata).unwrap();
+    assert_eq!(json["tokens_prompt_actual"], 26);
+    assert_eq!(json["tokens_completion_actual"], 5);
+
+    mock.assert_async().await;
+}
+
+#[tokio::test]
+async fn test_azure_prompt_filter_reported_as_content_filtered_outcome() {
+    let mut server = Server::new_async().await;
+    let mock = server
+        .mock("POST", "/v1/chat/completions")
+        .with_status(400)
+        .with_header("content-type", "application/json")
+        .with_body(
+            r#"{"error": {
+                "message": "The response was filtered due to the prompt triggering content management policy.",
+                "code": "content_filter",
+                "status": 400,
+                "innererror": {
+                    "code": "ResponsibleAIPolicyViolation",
+                    "content_filter_result": {
+                        "hate": {"filtered": false, "severity": "safe"},
+                        "violence": {"filtered": true, "severity": "high"}
+                    }
+                }
+            }}"#,
+        )
+        .create_async()
+        .await;
+
+    let config = create_test_config(server.url() + "/v1/chat/completions").await;
+    let output = evaluate(config).await.unwrap();
+
+    let error = output.error.as_ref().unwrap();
+    assert_eq!(error.code, EvaluationOutcome::ProviderContentFiltered);
+    assert!(error.message.contains("violence:high"), "{}", error.message);
+    assert_eq!(
+        output.metadata.violation_rules,
+        vec!["content_filter.VIOLENCE"]
+    );
+    assert_eq!(output.blocked_direction(), Some("input"));
+
+    let result = &output.metadata.guardrail_results[0];
+    assert_eq!(result.provider, "content_filter");
+    assert!(!result.passed);
+
+    mock.assert_async().await;
+}
+
+#[tokio::test]
+async fn test_content_filter_finish_reason_reported_as_output_block() {
+    let mut server = Server::new_async().await;
+    let mock = server
+        .mock("POST", "/v1/chat/completions")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(
+            r#"{"choices": [{
+                "message": {"role": "assistant", "content": "Partial"},
+                "finish_reason": "content_filter",
+                "content_filter_results": {"self_harm": {"filtered": true, "severity": "medium"}}
+            }]}"#,
+        )
+        .create_async()
+        .await;
+
+    let config = create_test_config(server.url() + "/v1/chat/completions").await;
+    let output = evaluate(config).await.unwrap();
+
+    assert_eq!(output.status, "error");
+    assert!(
+        output.response.is_none(),
+        "Filtered text must not be returned"
+    );
+    assert_eq!(
+        output.outcome(),
+        Some(&EvaluationOutcome::ProviderContentFiltered)
+    );
+    assert_eq!(
+        output.metadata.violation_rules,
+        vec!["content_filter.SELF_HARM"]
+    );
+    assert_eq!(output.blocked_direction(), Some("output"));
+
+    mock.assert_async().await;
+}
@@ -0,0 +1,104 @@
+// Bedrock provider tests
+//
+// Verifies the Converse API request shape (encoded model id in the path,
+// system blocks, inferenceConfig), SigV4 Authorization headers, response
+// parsing, and error mapping against a mock server.
+
+use fortified_llm_client::{config_builder::ConfigBuilder, evaluate, BedrockConfig, Provider};
+use mockito::{Matcher, Server};
+
+const CONVERSE_PATH: &str = "/model/anthropic.claude-3-haiku-20240307-v1%3A0/converse";
+
+fn config(server: &Server) -> ConfigBuilder {
+    ConfigBuilder::new()
+        .api_url(server.url())
+        .provider(Provider::Bedrock)
+        .model("anthropic.claude-3-haiku-20240307-v1:0")
+        .system_prompt("Be brief.")
+        .user_prompt("Hello")
+        .max_tokens(256)
+        .bedrock(BedrockConfig {
+            region: Some("us-west-2".to_string()),
+            access_key_id: Some("AKIDEXAMPLE".to_string()),
+            secret_access_key: Some("wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY".to_string()),
+            session_token: Some("session-token".to_string()),
+        })
+}
+
+#[tokio::test]
+async fn test_bedrock_converse_signed_request_and_response() {
+    let mut server = Server::new_async().await;
+    let mock = server
+        .mock("POST", CONVERSE_PATH)
+        .match_header(
+            "authorization",
+            Matcher::Regex(
+                r"^AWS4-HMAC-SHA256 Credential=AKIDEXAMPLE/\d{8}/us-west-2/bedrock/aws4_request, SignedHeaders=host;x-amz-date;x-amz-security-token, Signature=[0-9a-f]{64}$"
+                    .to_string(),
+            ),
+        )
+        .match_header("x-amz-date", Matcher::Regex(r"^\d{8}T\d{6}Z$".to_string()))
+        .match_header("x-amz-security-token", "session-token")
+        .match_body(Matcher::PartialJsonString(
+            r#"{
+                "messages": [{"role": "user", "content": [{"text": "Hello"}]}],
+                "system": [{"text": "Be brief."}],
+                "inferenceConfig": {"maxTokens": 256}
+            }"#
+            .to_string(),
+        ))
+        .with_status(200)
+        .with_body(
+            r#"{
+                "output": {"message": {"role": "assistant", "content": [{"text": "Hi"}, {"text": " there"}]}},
+                "stopReason": "end_turn",
+                "usage": {"inputTokens": 8, "outputTokens": 2, "totalTokens": 10}
+            }"#,
+        )
+        .create_async()
+        .await;
+
+    let output = evaluate(config(&server).build().unwrap()).await.unwrap();
+
+    mock.assert_async().await;
+    assert_eq!(output.status, "success");
+    assert_eq!(
+        output.response,
+        Some(serde_json::Value::String("Hi there".to_string()))
+    );
+    assert_eq!(output.metadata.provider.as_deref(), Some("Bedrock"));
+}
+
+#[tokio::test]
+async fn test_bedrock_rejected_signature_is_auth_error() {
+    let mut server = Server::new_async().await;
+    let _mock = server
+        .mock("POST", CONVERSE_PATH)
+        .with_status(403)
+        .with_body(r#"{"message": "The request signature we calculated does not match the signature you provided."}"#)
+        .create_async()
+        .await;
+
+    let Err(err) = evaluate(config(&server).build().unwrap()).await else {
+        panic!("Expected authentication error");
+    };
+    assert_eq!(err.code(), "AUTH_FAILED");
+    assert!(err.to_string().contains("signature we calculated"), "{err}");
+}
+
+#[tokio::test]
+async fn test_bedrock_error_message() {
+    let mut server = Server::new_async().await;
+    let _mock = server
+        .mock("POST", CONVERSE_PATH)
+        .with_status(429)
+        .with_body(r#"{"message": "Too many requests, please wait before trying again."}"#)
+        .create_async()
+        .await;
+
+    let Err(err) = evaluate(config(&server).build().unwrap()).await else {
+        panic!("Expected HTTP error");
+    };
+    assert!(err.to_string().contains("HTTP 429"), "{err}");
+    assert!(err.to_string().contains("Too many requests"));
+}
@@ -0,0 +1,138 @@
+// Record/replay cassette tests
+//
+// Verifies that HTTP interactions are recorded with secrets scrubbed and that
+// replay answers from the cassette without touching the network. The library
+// cassette is process-wide, so the library flow lives in a single test.
+
+use fortified_llm_client::{
+    cassette::{self, Cassette},
+    config_builder::ConfigBuilder,
+    evaluate, log_content_policy, run_from_args, LogContentPolicy, Provider,
+};
+use mockito::Server;
+use predicates::prelude::*;
+use std::sync::Arc;
+use tempfile::TempDir;
+
+const SECRET: &str = "sk-cassette-secret";
+
+async fn mock_llm(server: &mut Server) -> mockito::Mock {
+    server
+        .mock("POST", "/v1/chat/completions")
+        .with_status(200)
+        .with_body(r#"{"choices": [{"message": {"role": "assistant", "content": "recorded"}}]}"#)
+        .expect(1)
+        .create_async()
+        .await
+}
+
+#[tokio::test]
+async fn test_record_then_replay() {
+    let mut server = Server::new_async().await;
+    let mock = mock_llm(&mut server).await;
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("cassette.json");
+
+    let config = || {
+        ConfigBuilder::new()
+            .api_url(format!("{}/v1/chat/completions", server.url()))
+            .provider(Provider::OpenAI)
+            .model("test-model")
+            .system_prompt("System")
+            .user_prompt(format!("Echo {SECRET} please"))
+            .api_key(SECRET)
+            .build()
+            .unwrap()
+    };
+
+    // Record against the live (mock) server
+    let recorder = Arc::new(Cassette::record(&path));
+    cassette::install(recorder.clone());
+    let recorded = evaluate(config()).await.unwrap();
+    cassette::uninstall();
+    recorder.save().unwrap();
+
+    let content = std::fs::read_to_string(&path).unwrap();
+    assert!(!content.contains(SECRET), "secret leaked: {content}");
+    assert!(content.contains("[REDACTED]"));
+    assert_eq!(recorder.interactions().len(), 1);
+
+    // Replay: the server is not hit again (mock expects exactly one call)
+    cassette::install(Arc::new(Cassette::replay(&path).unwrap()));
+    let replayed = evaluate(config()).await.unwrap();
+    cassette::uninstall();
+
+    assert_eq!(replayed.response, recorded.response);
+    mock.assert_async().await;
+
+    // Requests missing from the cassette fail instead of going to the network
+    cassette::install(Arc::new(Cassette::replay(&path).unwrap()));
+    let mut other = config();
+    other.user_prompt = "Something else".to_string();
+    let result = evaluate(other).await;
+    cassette::uninstall();
+    let Err(err) = result else {
+        panic!("Expected replay miss");
+    };
+    assert!(err.to_string().contains("No recorded interaction"));
+
+    // run_from_args restores the process-wide state after replaying
+    let args = |api_url: &str, extra: &[&str]| -> Vec<String> {
+        [
+            "--api-url",
+            api_url,
+            "--model",
+            "test-model",
+            "--system-text",
+            "System",
+        ]
+        .iter()
+        .chain(&["--user-text", &format!("Echo {SECRET} please")])
+        .chain(&["--api-key", SECRET])
+        .chain(extra)
+        .map(|arg| arg.to_string())
+        .collect()
+    };
+    let api_url = format!("{}/v1/chat/completions", server.url());
+    let path = path.to_str().unwrap();
+    let replayed = run_from_args(&args(
+        &api_url,
+        &["--replay", path, "--log-content-policy", "never"],
+    ))
+    .await
+    .unwrap();
+    assert_eq!(replayed.response, recorded.response);
+    assert!(cassette::installed().is_none());
+    assert_eq!(log_content_policy(), LogContentPolicy::Full);
+
+    let mut live = Server::new_async().await;
+    let live_mock = mock_llm(&mut live).await;
+    let live_url = format!("{}/v1/chat/completions", live.url());
+    run_from_args(&args(&live_url, &[])).await.unwrap();
+    live_mock.assert_async().await;
+}
+
+#[tokio::test]
+async fn test_cli_record_and_replay() {
+    let mut server = Server::new_async().await;
+    let mock = mock_llm(&mut server).await;
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("cli.json");
+    let api_url = format!("{}/v1/chat/completions", server.url());
+    let run = |flag: &str| {
+        assert_cmd::cargo::cargo_bin_cmd!("fortified-llm-client")
+            .args(["--api-url", &api_url, "--model", "test-model"])
+            .args(["--system-text", "System", "--user-text", "Hello"])
+            .args(["--api-key", SECRET, flag])
+            .arg(&path)
+            .assert()
+            .success()
+            .stdout(predicate::str::contains(r#""response": "recorded""#));
+    };
+
+    run("--record");
+    run("--replay");
+
+    assert!(!std::fs::read_to_string(&path).unwrap().contains(SECRET));
+    mock.assert_async().await;
+}
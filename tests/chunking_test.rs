@@ -0,0 +1,122 @@
+//! Integration tests for chunked evaluation
+//!
+//! These tests verify that a prompt exceeding the context window is split
+//! into chunks that are answered separately, that map_reduce combines the
+//! chunk answers and refine carries the answer from chunk to chunk, and that
+//! documents needing too many chunks still fail with CONTEXT_LIMIT_EXCEEDED.
+
+mod common;
+
+use fortified_llm_client::{evaluate, ChunkStrategy, ChunkingConfig, EvaluationConfig};
+use mockito::{Matcher, Server, ServerGuard};
+use serde_json::json;
+
+/// 20 paragraphs of exactly 100 bytes (2000 bytes, ~500 tokens)
+fn document() -> String {
+    (1..=20)
+        .map(|i| {
+            format!(
+                "{:<98}\n\n",
+                format!("Paragraph {i:02}: lorem ipsum dolor sit amet.")
+            )
+        })
+        .collect()
+}
+
+fn config(server_url: &str, chunking: ChunkingConfig) -> EvaluationConfig {
+    common::base_builder(format!("{server_url}/v1/chat/completions"))
+        .user_prompt(document())
+        .context_limit(600)
+        .chunking(chunking)
+        .build()
+        .unwrap()
+}
+
+/// Chunks of 100 tokens (400 bytes, four paragraphs) without overlap
+fn chunking(strategy: ChunkStrategy) -> ChunkingConfig {
+    ChunkingConfig {
+        chunk_tokens: Some(100),
+        overlap_tokens: 0,
+        ..ChunkingConfig::new(strategy)
+    }
+}
+
+async fn llm_mock(
+    server: &mut ServerGuard,
+    body: &str,
+    answer: &str,
+    calls: usize,
+) -> mockito::Mock {
+    server
+        .mock("POST", "/v1/chat/completions")
+        .match_body(Matcher::Regex(body.to_string()))
+        .with_status(200)
+        .with_body(
+            json!({"choices": [{"message": {"role": "assistant", "content": answer}}]}).to_string(),
+        )
+        .expect(calls)
+        .create_async()
+        .await
+}
+
+#[tokio::test]
+async fn test_map_reduce_combines_chunk_answers() {
+    let mut server = Server::new_async().await;
+    let chunks = llm_mock(&mut server, r"Paragraph \d+", "Partial notes", 5).await;
+    let combine = llm_mock(&mut server, "Partial answer 5 of 5", "Final summary", 1).await;
+
+    let output = evaluate(config(&server.url(), chunking(ChunkStrategy::MapReduce)))
+        .await
+        .unwrap();
+    assert_eq!(output.status, "success");
+    assert_eq!(output.response, Some(json!("Final summary")));
+
+    let record = output.metadata.chunking.expect("chunking metadata");
+    assert_eq!(record.strategy, ChunkStrategy::MapReduce);
+    assert_eq!(record.source, "user_prompt");
+    assert_eq!(record.chunks.len(), 5);
+    assert_eq!(record.combine_calls, 1);
+    assert_eq!((record.chunks[1].start, record.chunks[1].end), (400, 800));
+    assert!(record.chunks.iter().all(|c| c.response_bytes == 13));
+    assert!(output.metadata.tokens_estimated <= 600);
+    chunks.assert_async().await;
+    combine.assert_async().await;
+}
+
+#[tokio::test]
+async fn test_refine_carries_answer_across_chunks() {
+    let mut server = Server::new_async().await;
+    let first = llm_mock(&mut server, "Paragraph 01", "Draft", 1).await;
+    let refine = llm_mock(&mut server, "Existing answer:\\\\nDraft", "Draft", 4).await;
+
+    let output = evaluate(config(&server.url(), chunking(ChunkStrategy::Refine)))
+        .await
+        .unwrap();
+    assert_eq!(output.status, "success");
+    let record = output.metadata.chunking.expect("chunking metadata");
+    assert_eq!(record.chunks.len(), 5);
+    assert_eq!(record.combine_calls, 0);
+    first.assert_async().await;
+    refine.assert_async().await;
+}
+
+#[tokio::test]
+async fn test_too_many_chunks_exceeds_context_limit() {
+    let mut server = Server::new_async().await;
+    let llm = server
+        .mock("POST", "/v1/chat/completions")
+        .expect(0)
+        .create_async()
+        .await;
+
+    let chunking = ChunkingConfig {
+        max_chunks: 4,
+        ..chunking(ChunkStrategy::MapReduce)
+    };
+    let output = evaluate(config(&server.url(), chunking)).await.unwrap();
+    assert_eq!(output.status, "error");
+    let error = output.error.unwrap();
+    assert_eq!(error.code, "CONTEXT_LIMIT_EXCEEDED");
+    assert!(error.message.contains("5 chunks"), "{}", error.message);
+    llm.assert_async().await;
+}
@@ -0,0 +1,17 @@
+// Shared integration test fixtures
+
+use fortified_llm_client::{config_builder::ConfigBuilder, Provider};
+
+/// OpenAI-compatible config against a mock server, without a user prompt
+///
+/// Tests add the settings under test (prompt, metrics, cache, guardrails).
+pub fn base_builder(api_url: String) -> ConfigBuilder {
+    ConfigBuilder::new()
+        .api_url(api_url)
+        .model("test-model")
+        .system_prompt("Test system")
+        .user_prompt("")
+        .provider(Provider::OpenAI)
+        .max_tokens(100)
+        .timeout_secs(5)
+}
@@ -0,0 +1,106 @@
+// Multi-model comparison tests
+//
+// Verifies that one prompt runs on every model, that per-model responses,
+// token counts and guardrail blocks are reported in the requested order, and
+// that one model failing doesn't stop the others.
+
+use fortified_llm_client::{
+    config_builder::ConfigBuilder, evaluate_compare, CliError, EvaluationConfig, EvaluationOutcome,
+    GuardrailConfigBuilder, Provider,
+};
+use mockito::{Matcher, Server, ServerGuard};
+use serde_json::json;
+
+async fn mock_model(server: &mut ServerGuard, model: &str, content: &str) -> mockito::Mock {
+    server
+        .mock("POST", "/v1/chat/completions")
+        .match_body(Matcher::PartialJson(json!({ "model": model })))
+        .with_status(200)
+        .with_body(
+            json!({
+                "choices": [{"message": {"role": "assistant", "content": content}}],
+                "usage": {"prompt_tokens": 12, "completion_tokens": 3, "total_tokens": 15}
+            })
+            .to_string(),
+        )
+        .expect(1)
+        .create_async()
+        .await
+}
+
+fn config(server_url: &str) -> EvaluationConfig {
+    ConfigBuilder::new()
+        .api_url(format!("{server_url}/v1/chat/completions"))
+        .provider(Provider::OpenAI)
+        .model("llama3")
+        .system_prompt("Answer briefly")
+        .user_prompt("Who handles refunds?")
+        .output_guardrails(
+            GuardrailConfigBuilder::regex()
+                .with_pii(true)
+                .build()
+                .unwrap(),
+        )
+        .build()
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_compare_reports_each_model_in_order() {
+    let mut server = Server::new_async().await;
+    let llama = mock_model(&mut server, "llama3", "The billing team").await;
+    let mistral = mock_model(&mut server, "mistral", "Mail jane@example.com").await;
+    let missing = server
+        .mock("POST", "/v1/chat/completions")
+        .match_body(Matcher::PartialJson(json!({ "model": "qwen2" })))
+        .with_status(404)
+        .with_body(r#"{"error": {"message": "model 'qwen2' not found"}}"#)
+        .create_async()
+        .await;
+
+    let models = ["qwen2", "mistral", "llama3"].map(String::from);
+    let report = evaluate_compare(&config(&server.url()), &models)
+        .await
+        .unwrap();
+
+    let names: Vec<_> = report.models.iter().map(|m| m.model.as_str()).collect();
+    assert_eq!(names, ["qwen2", "mistral", "llama3"]);
+
+    let qwen = &report.models[0];
+    assert_eq!(qwen.status, "error");
+    assert!(qwen.error.as_ref().unwrap().contains("404"), "{qwen:?}");
+
+    let mistral_result = &report.models[1];
+    assert_eq!(
+        mistral_result.outcome,
+        Some(EvaluationOutcome::OutputValidationFailed)
+    );
+    assert_eq!(mistral_result.blocked.as_deref(), Some("output"));
+    assert_eq!(mistral_result.violation_rules, ["regex.EMAIL_ADDRESS"]);
+    assert_eq!(mistral_result.response, None);
+
+    let llama_result = &report.models[2];
+    assert_eq!(llama_result.status, "success");
+    assert_eq!(llama_result.response, Some(json!("The billing team")));
+    assert_eq!(llama_result.response_chars, 16);
+    assert_eq!(llama_result.tokens_prompt_actual, Some(12));
+    assert_eq!(llama_result.tokens_completion_actual, Some(3));
+    assert_eq!(report.fastest.as_deref(), Some("llama3"));
+
+    let json = serde_json::to_value(&report).unwrap();
+    assert_eq!(json["models"][1]["outcome"], "OUTPUT_VALIDATION_FAILED");
+
+    llama.assert_async().await;
+    mistral.assert_async().await;
+    missing.assert_async().await;
+}
+
+#[tokio::test]
+async fn test_compare_rejects_invalid_model_lists() {
+    let base = config("http://127.0.0.1:9");
+    for models in [vec!["llama3"], vec!["llama3", "llama3"]] {
+        let models: Vec<String> = models.into_iter().map(String::from).collect();
+        let err = evaluate_compare(&base, &models).await.unwrap_err();
+        assert!(matches!(err, CliError::InvalidArguments(_)), "{err}");
+    }
+}
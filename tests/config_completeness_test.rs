@@ -6,7 +6,10 @@
 // IMPORTANT: When adding new fields to ConfigFileRequest, you MUST update this test!
 // This test should include EVERY field defined in ConfigFileRequest struct.
 
-use fortified_llm_client::{config_builder::ConfigBuilder, load_config_file};
+use fortified_llm_client::{
+    config_builder::ConfigBuilder, load_config_file, DataClassification, DataPolicy,
+    LogContentPolicy, MetadataFields, Provider, RefusalAction,
+};
 use std::fs;
 use tempfile::NamedTempFile;
 
@@ -20,7 +23,9 @@ fn test_all_config_file_parameters_are_used() {
         "provider": "openai",
         "system_prompt": "System prompt from config",
         "user_prompt": "User prompt from config",
+        "preset": "creative",
         "temperature": 0.7,
+        "top_p": 0.8,
         "max_tokens": 8192,
         "timeout_secs": 600,
         "validate_tokens": true,
@@ -28,7 +33,10 @@ fn test_all_config_file_parameters_are_used() {
         "api_key": "test-api-key-12345",
         "response_format": "json-object",
         "response_format_schema": null,
-        "response_format_schema_strict": null
+        "response_format_schema_strict": null,
+        "self_test": true,
+        "log_content_policy": "truncated:80",
+        "max_response_bytes": 1048576
     }"#;
 
     let file = NamedTempFile::new().unwrap();
@@ -38,6 +46,18 @@ fn test_all_config_file_parameters_are_used() {
     // Load config file
     let file_config = load_config_file(&path).unwrap();
 
+    // self_test gates evaluation in main.rs (not part of EvaluationConfig)
+    assert!(
+        file_config.self_test,
+        "self_test not loaded from config file"
+    );
+    // log_content_policy is process-wide, applied in main.rs
+    assert_eq!(
+        file_config.log_content_policy,
+        Some(LogContentPolicy::Truncated(80)),
+        "log_content_policy not loaded from config file"
+    );
+
     // Build configuration using ConfigBuilder (simulating main.rs flow)
     let builder = ConfigBuilder::new();
     let builder = builder.merge_file_config(&file_config);
@@ -64,6 +84,11 @@ fn test_all_config_file_parameters_are_used() {
         config.temperature, 0.7,
         "temperature not applied from config file"
     );
+    assert_eq!(
+        config.top_p,
+        Some(0.8),
+        "top_p not applied from config file"
+    );
     assert_eq!(
         config.max_tokens,
         Some(8192),
@@ -73,6 +98,10 @@ fn test_all_config_file_parameters_are_used() {
         config.timeout_secs, 600,
         "timeout_secs not applied from config file"
     );
+    assert_eq!(
+        config.max_response_bytes, 1_048_576,
+        "max_response_bytes not applied from config file"
+    );
     assert!(
         config.validate_tokens,
         "validate_tokens not applied from config file"
@@ -269,6 +298,12 @@ fn test_toml_config_all_parameters() {
         context_limit = 200000
         api_key = "toml-api-key"
         response_format = "text"
+        tags = ["experiment=A", "suite=nightly"]
+        data_classification = "restricted"
+        allowed_external_hosts = ["*.example.com"]
+
+        [metadata_fields]
+        exclude = ["api_url", "prompts"]
     "#;
 
     let file = NamedTempFile::new().unwrap();
@@ -292,6 +327,15 @@ fn test_toml_config_all_parameters() {
     assert!(config.validate_tokens);
     assert_eq!(config.context_limit, Some(200000));
     assert_eq!(config.api_key, Some("toml-api-key".to_string()));
+    assert_eq!(config.tags, vec!["experiment=A", "suite=nightly"]);
+    assert_eq!(
+        config.data_policy,
+        Some(DataPolicy::restricted().allow_host("*.example.com"))
+    );
+    assert_eq!(
+        config.metadata_fields,
+        Some(MetadataFields::default().exclude(["api_url", "prompts"]))
+    );
 
     // Verify response_format is Text variant
     assert!(config.response_format.is_some());
@@ -356,3 +400,475 @@ fn test_cli_args_override_config_file() {
 
     fs::remove_file(&path).ok();
 }
+
+#[test]
+fn test_guardrail_override_from_config_file() {
+    // guardrail_override is turned into a controller in main.rs (it needs the
+    // secret from the environment); here we verify it deserializes completely
+    let toml = r#"
+        api_url = "http://test.example.com/api"
+        model = "test-model"
+        system_prompt = "System"
+        user_prompt = "User"
+
+        [guardrail_override]
+        secret_env = "TEST_OVERRIDE_SECRET"
+        allowed_rules = ["PII_*", "MAX_LENGTH"]
+        max_per_hour = 3
+        audit_log = "/var/log/fortified/audit.jsonl"
+    "#;
+
+    let file = NamedTempFile::new().unwrap();
+    let path = file.path().with_extension("toml");
+    fs::write(&path, toml).unwrap();
+
+    let file_config = load_config_file(&path).unwrap();
+    let policy = file_config
+        .guardrail_override
+        .expect("guardrail_override not loaded from config file");
+
+    assert_eq!(policy.secret_env, "TEST_OVERRIDE_SECRET");
+    assert_eq!(policy.allowed_rules, vec!["PII_*", "MAX_LENGTH"]);
+    assert_eq!(policy.max_per_hour, 3);
+    assert_eq!(
+        policy.audit_log,
+        std::path::PathBuf::from("/var/log/fortified/audit.jsonl")
+    );
+
+    fs::remove_file(&path).ok();
+}
+
+#[test]
+fn test_prompt_audit_from_config_file() {
+    // prompt_audit is turned into a PromptAuditor in main.rs
+    let toml = r#"
+        api_url = "http://test.example.com/api"
+        model = "test-model"
+        system_prompt = "System"
+        user_prompt = "User"
+
+        [prompt_audit]
+        log = "/var/log/fortified/prompts.jsonl"
+        blob_dir = "/var/lib/fortified/blobs"
+    "#;
+
+    let file = NamedTempFile::new().unwrap();
+    let path = file.path().with_extension("toml");
+    fs::write(&path, toml).unwrap();
+
+    let file_config = load_config_file(&path).unwrap();
+    let audit = file_config
+        .prompt_audit
+        .expect("prompt_audit not loaded from config file");
+
+    assert_eq!(
+        audit.log,
+        std::path::PathBuf::from("/var/log/fortified/prompts.jsonl")
+    );
+    assert_eq!(
+        audit.blob_dir,
+        Some(std::path::PathBuf::from("/var/lib/fortified/blobs"))
+    );
+
+    fs::remove_file(&path).ok();
+}
+
+#[test]
+fn test_parameter_preset_from_config_file() {
+    // Preset fills unset sampling values; explicit values win
+    let toml = r#"
+        api_url = "http://test.example.com/api"
+        model = "test-model"
+        system_prompt = "System"
+        user_prompt = "User"
+        preset = "deterministic"
+        seed = 7
+    "#;
+
+    let file = NamedTempFile::new().unwrap();
+    let path = file.path().with_extension("toml");
+    fs::write(&path, toml).unwrap();
+
+    let file_config = load_config_file(&path).unwrap();
+    let config = ConfigBuilder::new()
+        .merge_file_config(&file_config)
+        .build()
+        .unwrap();
+
+    assert_eq!(config.temperature, 0.0);
+    assert_eq!(config.top_p, Some(1.0));
+    assert_eq!(config.seed, Some(7));
+    assert!(config.config_warnings.is_empty());
+
+    fs::remove_file(&path).ok();
+}
+
+#[test]
+fn test_guardrail_preset_from_config_file() {
+    let toml = r#"
+        api_url = "http://test.example.com/api"
+        model = "test-model"
+        system_prompt = "System"
+        user_prompt = "User"
+
+        [guardrails]
+        preset = "injection_only"
+    "#;
+
+    let file = NamedTempFile::new().unwrap();
+    let path = file.path().with_extension("toml");
+    fs::write(&path, toml).unwrap();
+
+    let file_config = load_config_file(&path).unwrap();
+    let config = ConfigBuilder::new()
+        .merge_file_config(&file_config)
+        .build()
+        .unwrap();
+
+    assert!(matches!(
+        config.input_guardrails,
+        Some(fortified_llm_client::GuardrailProviderConfig::Composite { .. })
+    ));
+    assert!(
+        config.output_guardrails.is_none(),
+        "injection_only preset has no output guardrails"
+    );
+
+    fs::remove_file(&path).ok();
+}
+
+#[test]
+fn test_refusal_detection_from_config_file() {
+    let toml = r#"
+        api_url = "http://test.example.com/api"
+        model = "test-model"
+        system_prompt = "System"
+        user_prompt = "User"
+
+        [refusal_detection]
+        on_refusal = "outcome"
+        patterns = ["(?i)^unable to comply"]
+    "#;
+
+    let file = NamedTempFile::new().unwrap();
+    let path = file.path().with_extension("toml");
+    fs::write(&path, toml).unwrap();
+
+    let file_config = load_config_file(&path).unwrap();
+    let config = ConfigBuilder::new()
+        .merge_file_config(&file_config)
+        .build()
+        .unwrap();
+
+    let refusal = config
+        .refusal_detection
+        .expect("refusal_detection not loaded from config file");
+    assert_eq!(refusal.on_refusal, RefusalAction::Outcome);
+    assert_eq!(refusal.patterns, vec!["(?i)^unable to comply"]);
+    assert!(refusal.builtin_patterns);
+    assert_eq!(refusal.scan_chars, 400);
+
+    fs::remove_file(&path).ok();
+}
+
+#[test]
+fn test_repetition_detection_from_config_file() {
+    let toml = r#"
+        api_url = "http://test.example.com/api"
+        model = "test-model"
+        system_prompt = "System"
+        user_prompt = "User"
+
+        [repetition_detection]
+        threshold = 0.3
+        max_retries = 2
+    "#;
+
+    let file = NamedTempFile::new().unwrap();
+    let path = file.path().with_extension("toml");
+    fs::write(&path, toml).unwrap();
+
+    let file_config = load_config_file(&path).unwrap();
+    let config = ConfigBuilder::new()
+        .merge_file_config(&file_config)
+        .build()
+        .unwrap();
+
+    let repetition = config
+        .repetition_detection
+        .expect("repetition_detection not loaded from config file");
+    assert_eq!(repetition.threshold, 0.3);
+    assert_eq!(repetition.max_retries, 2);
+    assert_eq!(repetition.ngram_size, 4);
+    assert_eq!(repetition.min_words, 20);
+
+    fs::write(&path, toml.replace("0.3", "1.5")).unwrap();
+    let file_config = load_config_file(&path).unwrap();
+    let err = ConfigBuilder::new()
+        .merge_file_config(&file_config)
+        .build()
+        .unwrap_err();
+    assert!(err.to_string().contains("repetition_detection.threshold"));
+
+    fs::remove_file(&path).ok();
+}
+
+#[test]
+fn test_chunking_from_config_file() {
+    let toml = r#"
+        api_url = "http://test.example.com/api"
+        model = "test-model"
+        system_prompt = "System"
+        user_prompt = "User"
+
+        [chunking]
+        strategy = "refine"
+        chunk_tokens = 4000
+    "#;
+
+    let file = NamedTempFile::new().unwrap();
+    let path = file.path().with_extension("toml");
+    fs::write(&path, toml).unwrap();
+
+    let file_config = load_config_file(&path).unwrap();
+    let config = ConfigBuilder::new()
+        .merge_file_config(&file_config)
+        .build()
+        .unwrap();
+
+    let chunking = config
+        .chunking
+        .expect("chunking not loaded from config file");
+    assert_eq!(
+        chunking.strategy,
+        fortified_llm_client::ChunkStrategy::Refine
+    );
+    assert_eq!(chunking.chunk_tokens, Some(4000));
+    assert_eq!(chunking.overlap_tokens, 200);
+    assert_eq!(chunking.max_chunks, 16);
+
+    fs::write(&path, toml.replace("4000", "100")).unwrap();
+    let file_config = load_config_file(&path).unwrap();
+    let err = ConfigBuilder::new()
+        .merge_file_config(&file_config)
+        .build()
+        .unwrap_err();
+    assert!(err.to_string().contains("chunking.overlap_tokens"));
+
+    fs::remove_file(&path).ok();
+}
+
+#[test]
+fn test_deadline_from_config_file() {
+    let toml = r#"
+        api_url = "http://test.example.com/api"
+        model = "test-model"
+        system_prompt = "System"
+        user_prompt = "User"
+
+        [deadline]
+        total_ms = 8000
+        skippable = ["llama_guard", "gpt_oss_safeguard"]
+    "#;
+
+    let file = NamedTempFile::new().unwrap();
+    let path = file.path().with_extension("toml");
+    fs::write(&path, toml).unwrap();
+
+    let file_config = load_config_file(&path).unwrap();
+    let config = ConfigBuilder::new()
+        .merge_file_config(&file_config)
+        .build()
+        .unwrap();
+
+    let deadline = config
+        .deadline
+        .expect("deadline not loaded from config file");
+    assert_eq!(deadline.total_ms, 8000);
+    assert_eq!(deadline.guardrail_reserve_ms, 1000);
+    assert_eq!(deadline.skippable, vec!["llama_guard", "gpt_oss_safeguard"]);
+
+    fs::write(&path, toml.replace("\"llama_guard\"", "\"llamaguard\"")).unwrap();
+    let file_config = load_config_file(&path).unwrap();
+    let err = ConfigBuilder::new()
+        .merge_file_config(&file_config)
+        .build()
+        .unwrap_err();
+    assert!(err.to_string().contains("deadline.skippable"), "{err}");
+
+    fs::remove_file(&path).ok();
+}
+
+#[test]
+fn test_bedrock_from_config_file() {
+    let toml = r#"
+        api_url = "https://bedrock-runtime.us-east-1.amazonaws.com"
+        model = "anthropic.claude-3-haiku-20240307-v1:0"
+        provider = "bedrock"
+        system_prompt = "System"
+        user_prompt = "User"
+
+        [bedrock]
+        region = "us-west-2"
+    "#;
+
+    let file = NamedTempFile::new().unwrap();
+    let path = file.path().with_extension("toml");
+    fs::write(&path, toml).unwrap();
+
+    let file_config = load_config_file(&path).unwrap();
+    let config = ConfigBuilder::new()
+        .merge_file_config(&file_config)
+        .build()
+        .unwrap();
+
+    assert_eq!(config.provider, Some(Provider::Bedrock));
+    let bedrock = config.bedrock.expect("bedrock not loaded from config file");
+    assert_eq!(bedrock.region.as_deref(), Some("us-west-2"));
+    assert_eq!(bedrock.access_key_id, None);
+
+    fs::remove_file(&path).ok();
+}
+
+#[test]
+fn test_guardrail_conditions_from_config_file() {
+    let toml = r#"
+        api_url = "http://test.example.com/api"
+        model = "test-model"
+        system_prompt = "System"
+        user_prompt = "User"
+
+        [guardrails]
+        preset = "injection_only"
+
+        [[guardrails.conditional]]
+        when = "input_tokens > 50k"
+        stage = "input"
+        skip = ["llama_prompt_guard"]
+
+        [[guardrails.conditional]]
+        when = "input_source == pdf"
+        stage = "output"
+        add = { type = "numeric_consistency" }
+    "#;
+
+    let file = NamedTempFile::new().unwrap();
+    let path = file.path().with_extension("toml");
+    fs::write(&path, toml).unwrap();
+
+    let file_config = load_config_file(&path).unwrap();
+    let config = ConfigBuilder::new()
+        .merge_file_config(&file_config)
+        .build()
+        .unwrap();
+
+    assert!(
+        config.input_guardrails.is_some(),
+        "preset must still apply alongside conditions"
+    );
+    assert_eq!(config.guardrail_conditions.len(), 2);
+    assert_eq!(
+        config.guardrail_conditions[0].when.to_string(),
+        "input_tokens > 50k"
+    );
+    assert_eq!(
+        config.guardrail_conditions[0].skip,
+        vec!["llama_prompt_guard"]
+    );
+
+    // Unknown provider types are rejected when building
+    fs::write(
+        &path,
+        toml.replace("\"llama_prompt_guard\"", "\"prompt_guard\""),
+    )
+    .unwrap();
+    let file_config = load_config_file(&path).unwrap();
+    let err = ConfigBuilder::new()
+        .merge_file_config(&file_config)
+        .build()
+        .unwrap_err();
+    assert!(err
+        .to_string()
+        .contains("unknown provider type 'prompt_guard'"));
+
+    // Malformed conditions fail when the file is loaded
+    fs::write(&path, toml.replace("input_tokens > 50k", "tokens > 50k")).unwrap();
+    let err = load_config_file(&path).unwrap_err();
+    assert!(err.to_string().contains("unknown fact 'tokens'"), "{err}");
+
+    fs::remove_file(&path).ok();
+}
+
+#[test]
+fn test_data_classification_from_config_file() {
+    let toml = r#"
+        api_url = "http://localhost:11434/api/generate"
+        model = "test-model"
+        system_prompt = "System"
+        user_prompt = "User"
+        data_classification = "restricted"
+        allowed_external_hosts = ["llm.corp.example.com"]
+    "#;
+
+    let file = NamedTempFile::new().unwrap();
+    let path = file.path().with_extension("toml");
+    fs::write(&path, toml).unwrap();
+
+    // CLI hosts replace the file's; the classification comes from the file
+    let file_config = load_config_file(&path).unwrap();
+    let config = ConfigBuilder::new()
+        .allow_external_host("*.openai.azure.com")
+        .merge_file_config(&file_config)
+        .build()
+        .unwrap();
+    let policy = config
+        .data_policy
+        .expect("data_classification not loaded from config file");
+    assert_eq!(policy.classification, DataClassification::Restricted);
+    assert_eq!(policy.allowed_external_hosts, vec!["*.openai.azure.com"]);
+
+    fs::write(
+        &path,
+        toml.replace("llm.corp.example.com", "https://llm.corp.example.com"),
+    )
+    .unwrap();
+    let file_config = load_config_file(&path).unwrap();
+    let err = ConfigBuilder::new()
+        .merge_file_config(&file_config)
+        .build()
+        .unwrap_err();
+    assert!(err.to_string().contains("allowed_external_hosts"), "{err}");
+
+    fs::remove_file(&path).ok();
+}
+
+#[test]
+fn test_metadata_fields_validated_from_config_file() {
+    let toml = r#"
+        api_url = "http://localhost:11434/api/generate"
+        model = "test-model"
+        system_prompt = "System"
+        user_prompt = "User"
+
+        [metadata_fields]
+        include = ["model", "tokens"]
+        exclude = ["promts"]
+    "#;
+
+    let file = NamedTempFile::new().unwrap();
+    let path = file.path().with_extension("toml");
+    fs::write(&path, toml).unwrap();
+
+    let file_config = load_config_file(&path).unwrap();
+    let err = ConfigBuilder::new()
+        .merge_file_config(&file_config)
+        .build()
+        .unwrap_err();
+    assert!(
+        err.to_string()
+            .contains("metadata_fields.exclude: unknown field or group 'promts'"),
+        "{err}"
+    );
+
+    fs::remove_file(&path).ok();
+}
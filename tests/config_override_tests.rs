@@ -21,7 +21,7 @@ fn test_config_file_temperature_default() {
     std::fs::write(&path, json).unwrap();
 
     let config = load_config_file(&path).unwrap();
-    assert_eq!(config.temperature, 0.0); // Default temperature
+    assert_eq!(config.temperature, None); // Default applied by ConfigBuilder
 
     std::fs::remove_file(&path).ok();
 }
@@ -42,7 +42,7 @@ fn test_config_file_temperature_explicit() {
     std::fs::write(&path, json).unwrap();
 
     let config = load_config_file(&path).unwrap();
-    assert_eq!(config.temperature, 0.7);
+    assert_eq!(config.temperature, Some(0.7));
 
     std::fs::remove_file(&path).ok();
 }
@@ -174,7 +174,7 @@ fn test_config_file_all_parameters() {
     assert_eq!(config.model, "llama3:70b");
     assert_eq!(config.system_prompt, Some("You are an expert.".to_string()));
     assert_eq!(config.user_prompt, Some("Analyze this.".to_string()));
-    assert_eq!(config.temperature, 0.5);
+    assert_eq!(config.temperature, Some(0.5));
     assert_eq!(config.max_tokens, Some(8000));
     assert_eq!(config.timeout_secs, 600);
     assert!(config.validate_tokens);
@@ -201,7 +201,7 @@ fn test_toml_config_format() {
     std::fs::write(&path, toml).unwrap();
 
     let config = load_config_file(&path).unwrap();
-    assert_eq!(config.temperature, 0.7);
+    assert_eq!(config.temperature, Some(0.7));
     assert_eq!(config.max_tokens, Some(2000));
     assert_eq!(config.user_prompt, Some("Hello".to_string()));
 
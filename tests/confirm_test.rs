@@ -0,0 +1,107 @@
+//! Integration tests for outbound request confirmation
+//!
+//! These tests verify that `evaluate_with_confirmation` previews the request
+//! before any endpoint receives content, and that a declined preview sends
+//! nothing.
+
+mod common;
+
+use fortified_llm_client::{
+    evaluate_with_confirmation, CliError, EndpointClass, EvaluationConfig, GuardrailProviderConfig,
+    LlamaGuardCategory, RequestPreview,
+};
+use mockito::Server;
+use serde_json::json;
+use std::sync::{Arc, Mutex};
+
+fn config(server: &Server) -> EvaluationConfig {
+    common::base_builder(format!("{}/v1/chat/completions", server.url()))
+        .user_prompt("Contact alice@example.com about SSN 123-45-6789")
+        .build()
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_declined_request_sends_nothing() {
+    let mut server = Server::new_async().await;
+    let llm = server
+        .mock("POST", "/v1/chat/completions")
+        .expect(0)
+        .create_async()
+        .await;
+    let guard = server
+        .mock("POST", "/guard/v1/chat/completions")
+        .expect(0)
+        .create_async()
+        .await;
+
+    let mut config = config(&server);
+    config.input_guardrails = Some(GuardrailProviderConfig::LlamaGuard {
+        api_url: format!("{}/guard/v1/chat/completions", server.url()),
+        model: "llama-guard3".to_string(),
+        timeout_secs: 5,
+        enabled_categories: vec![LlamaGuardCategory::S1],
+        custom_categories: Vec::new(),
+        api_key: None,
+        api_key_name: None,
+        api_key_secret: None,
+        resolved_api_key: None,
+    });
+    let seen: Arc<Mutex<Option<RequestPreview>>> = Arc::default();
+    let recorded = Arc::clone(&seen);
+    let result = evaluate_with_confirmation(config, move |preview| {
+        *recorded.lock().unwrap() = Some(preview.clone());
+        false
+    })
+    .await;
+
+    match result {
+        Err(CliError::RequestDeclined(_)) => {}
+        Err(e) => panic!("expected RequestDeclined, got {e:?}"),
+        Ok(output) => panic!("expected RequestDeclined, got status {}", output.status),
+    }
+    llm.assert_async().await;
+    guard.assert_async().await;
+
+    let preview = seen.lock().unwrap().take().unwrap();
+    let roles: Vec<_> = preview.endpoints.iter().map(|e| e.role).collect();
+    assert_eq!(roles, ["llm", "guardrail"]);
+    assert_eq!(preview.endpoints[0].class, EndpointClass::Local);
+    assert_eq!(preview.model, "test-model");
+    assert_eq!(preview.system_prompt_bytes, "Test system".len());
+    assert_eq!(
+        preview.user_prompt_bytes,
+        "Contact alice@example.com about SSN 123-45-6789".len()
+    );
+    let categories: Vec<_> = preview
+        .sensitive
+        .iter()
+        .map(|s| (s.category.as_str(), s.count))
+        .collect();
+    assert_eq!(categories, [("EMAIL", 1), ("SSN", 1)]);
+    assert!(!preview.to_string().contains("alice@example.com"));
+}
+
+#[tokio::test]
+async fn test_confirmed_request_is_sent() {
+    let mut server = Server::new_async().await;
+    let llm = server
+        .mock("POST", "/v1/chat/completions")
+        .with_status(200)
+        .with_body(
+            json!({"choices": [{"message": {"role": "assistant", "content": "Done"}}]}).to_string(),
+        )
+        .create_async()
+        .await;
+
+    let output = evaluate_with_confirmation(config(&server), |_| true)
+        .await
+        .unwrap();
+    assert_eq!(output.status, "success");
+    assert!(output
+        .metadata
+        .stage_latency
+        .iter()
+        .any(|s| s.stage == "confirm"));
+    llm.assert_async().await;
+}
@@ -0,0 +1,193 @@
+// Context window probing tests
+//
+// Verifies that unknown models get their context window from the provider
+// (Ollama /api/show, OpenAI-compatible /models) and that results are cached.
+
+use fortified_llm_client::{
+    config_builder::ConfigBuilder, evaluate, ContextProber, EvaluationConfig, EvaluationOutcome,
+    MemoryStorage, Provider, Storage,
+};
+use mockito::{Matcher, Server};
+use std::{sync::Arc, time::Duration};
+
+fn test_config(api_url: String, prober: Arc<ContextProber>) -> EvaluationConfig {
+    ConfigBuilder::new()
+        .api_url(api_url)
+        .model("custom-model")
+        .system_prompt("Test system")
+        .user_prompt("Test user")
+        .provider(Provider::OpenAI)
+        .max_tokens(100)
+        .timeout_secs(5)
+        .validate_tokens(true)
+        .context_probe(prober)
+        .build()
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_probe_ollama_show() {
+    let mut server = Server::new_async().await;
+    let mock = server
+        .mock("POST", "/api/show")
+        .match_body(Matcher::PartialJsonString(
+            r#"{"model": "custom-model"}"#.to_string(),
+        ))
+        .with_status(200)
+        .with_body(
+            r#"{"parameters": "num_ctx 16384", "model_info": {"llama.context_length": 131072}}"#,
+        )
+        .create_async()
+        .await;
+
+    let limit = ContextProber::new()
+        .probe(
+            &format!("{}/api/generate", server.url()),
+            "custom-model",
+            Some(Provider::Ollama),
+            None,
+            5,
+        )
+        .await;
+
+    assert_eq!(limit, Some(16384));
+    mock.assert_async().await;
+}
+
+#[tokio::test]
+async fn test_probe_openai_model_endpoint() {
+    let mut server = Server::new_async().await;
+    let mock = server
+        .mock("GET", "/v1/models/custom-model")
+        .match_header("authorization", "Bearer sk-test")
+        .with_status(200)
+        .with_body(r#"{"id": "custom-model", "object": "model", "max_model_len": 32768}"#)
+        .create_async()
+        .await;
+
+    let limit = ContextProber::new()
+        .probe(
+            &format!("{}/v1/chat/completions", server.url()),
+            "custom-model",
+            Some(Provider::OpenAI),
+            Some("sk-test"),
+            5,
+        )
+        .await;
+
+    assert_eq!(limit, Some(32768));
+    mock.assert_async().await;
+}
+
+#[tokio::test]
+async fn test_probe_openai_falls_back_to_model_list() {
+    let mut server = Server::new_async().await;
+    let _model = server
+        .mock("GET", "/v1/models/custom-model")
+        .with_status(404)
+        .create_async()
+        .await;
+    let _list = server
+        .mock("GET", "/v1/models")
+        .with_status(200)
+        .with_body(
+            r#"{"data": [{"id": "other", "context_length": 1000},
+                         {"id": "custom-model", "context_length": 200000}]}"#,
+        )
+        .create_async()
+        .await;
+
+    let limit = ContextProber::new()
+        .probe(
+            &format!("{}/v1/chat/completions", server.url()),
+            "custom-model",
+            Some(Provider::OpenAI),
+            None,
+            5,
+        )
+        .await;
+
+    assert_eq!(limit, Some(200000));
+}
+
+#[tokio::test]
+async fn test_probe_failure_is_not_fatal() {
+    let mut server = Server::new_async().await;
+    let _mock = server
+        .mock("POST", "/api/show")
+        .with_status(500)
+        .create_async()
+        .await;
+
+    let limit = ContextProber::new()
+        .probe(
+            &format!("{}/api/generate", server.url()),
+            "custom-model",
+            Some(Provider::Ollama),
+            None,
+            5,
+        )
+        .await;
+
+    assert_eq!(limit, None);
+}
+
+#[tokio::test]
+async fn test_probe_result_persisted_in_storage() {
+    let mut server = Server::new_async().await;
+    let mock = server
+        .mock("POST", "/api/show")
+        .with_status(200)
+        .with_body(r#"{"model_info": {"qwen2.context_length": 32768}}"#)
+        .expect(1)
+        .create_async()
+        .await;
+    let api_url = format!("{}/api/generate", server.url());
+    let storage: Arc<dyn Storage> = Arc::new(MemoryStorage::new());
+
+    let first = ContextProber::new().with_storage(storage.clone(), Duration::from_secs(60));
+    let probed = first
+        .probe(&api_url, "custom-model", Some(Provider::Ollama), None, 5)
+        .await;
+    assert_eq!(probed, Some(32768));
+    assert_eq!(storage.list("context-probe/").unwrap().len(), 1);
+
+    // A fresh prober (e.g. the next CLI run) reads the persisted result
+    let second = ContextProber::new().with_storage(storage, Duration::from_secs(60));
+    let cached = second
+        .probe(&api_url, "custom-model", Some(Provider::Ollama), None, 5)
+        .await;
+    assert_eq!(cached, Some(32768));
+    mock.assert_async().await;
+}
+
+#[tokio::test]
+async fn test_evaluate_uses_probed_context_limit() {
+    let mut server = Server::new_async().await;
+    let _probe = server
+        .mock("GET", "/v1/models/custom-model")
+        .with_status(200)
+        .with_body(r#"{"id": "custom-model", "context_window": 50}"#)
+        .create_async()
+        .await;
+    let llm = server
+        .mock("POST", "/v1/chat/completions")
+        .expect(0)
+        .create_async()
+        .await;
+
+    let config = test_config(
+        format!("{}/v1/chat/completions", server.url()),
+        Arc::new(ContextProber::new()),
+    );
+    let output = evaluate(config).await.unwrap();
+
+    // The probed limit is enforced and reported like a configured one
+    assert_eq!(output.status, "error");
+    assert_eq!(
+        output.error.unwrap().code,
+        EvaluationOutcome::ContextLimitExceeded
+    );
+    assert_eq!(output.metadata.context_limit, Some(50));
+    llm.assert_async().await;
+}
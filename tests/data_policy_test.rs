@@ -0,0 +1,142 @@
+//! Integration tests for the data classification pre-flight check
+//!
+//! These tests verify that restricted evaluations are refused before any
+//! request when the LLM, a model-backed guardrail or an experiment/sweep judge
+//! endpoint is external and not allowlisted, and that local endpoints are used
+//! as usual.
+
+use fortified_llm_client::{
+    config_builder::ConfigBuilder, evaluate, run_experiment, run_sweep, BatchItem, CliError,
+    DataClassification, EvaluationConfig, ExperimentConfig, GuardrailProviderConfig,
+    LlamaGuardCategory, Provider, SweepConfig,
+};
+use mockito::Server;
+use serde_json::json;
+
+fn builder(api_url: String) -> ConfigBuilder {
+    ConfigBuilder::new()
+        .api_url(api_url)
+        .provider(Provider::OpenAI)
+        .model("test-model")
+        .system_prompt("Answer briefly")
+        .user_prompt("Summarize the attached patient record")
+        .data_classification(DataClassification::Restricted)
+}
+
+fn external_guard() -> GuardrailProviderConfig {
+    GuardrailProviderConfig::LlamaGuard {
+        api_url: "https://guard.example.com/v1/chat/completions".to_string(),
+        model: "llama-guard3".to_string(),
+        timeout_secs: 5,
+        enabled_categories: vec![LlamaGuardCategory::S1],
+        custom_categories: Vec::new(),
+        api_key: None,
+        api_key_name: None,
+        api_key_secret: None,
+        resolved_api_key: None,
+    }
+}
+
+async fn assert_refused(config: EvaluationConfig, host: &str) {
+    match evaluate(config).await {
+        Err(CliError::EndpointNotAllowed(message)) => {
+            assert!(message.contains(&format!("'{host}'")), "{message}")
+        }
+        Err(e) => panic!("expected EndpointNotAllowed, got {e:?}"),
+        Ok(output) => panic!("expected EndpointNotAllowed, got status {}", output.status),
+    }
+}
+
+#[tokio::test]
+async fn test_restricted_refuses_external_llm() {
+    let config = builder("https://8.8.8.8/v1/chat/completions".to_string())
+        .build()
+        .unwrap();
+    assert_refused(config, "8.8.8.8").await;
+}
+
+#[tokio::test]
+async fn test_restricted_refuses_external_guardrail_before_llm_call() {
+    let mut server = Server::new_async().await;
+    let llm = server
+        .mock("POST", "/v1/chat/completions")
+        .expect(0)
+        .create_async()
+        .await;
+
+    let mut config = builder(format!("{}/v1/chat/completions", server.url()))
+        .build()
+        .unwrap();
+    config.output_guardrails = Some(external_guard());
+    assert_refused(config, "guard.example.com").await;
+    llm.assert_async().await;
+}
+
+#[tokio::test]
+async fn test_restricted_refuses_external_judge_before_any_call() {
+    let mut server = Server::new_async().await;
+    let llm = server
+        .mock("POST", "/v1/chat/completions")
+        .expect(0)
+        .create_async()
+        .await;
+    let config = builder(format!("{}/v1/chat/completions", server.url()))
+        .build()
+        .unwrap();
+    let judge = json!({
+        "api_url": "https://judge.example.com/v1/chat/completions",
+        "model": "judge-model",
+        "criteria": "Accurate"
+    });
+
+    let experiment: ExperimentConfig = serde_json::from_value(json!({
+        "variants": [
+            {"name": "a", "system_prompt": "A"},
+            {"name": "b", "system_prompt": "B"}
+        ],
+        "judge": judge
+    }))
+    .unwrap();
+    let items = [BatchItem {
+        user_prompt: "Summarize the attached patient record".to_string(),
+        ..Default::default()
+    }];
+    let err = run_experiment(&config, &items, &experiment, 1)
+        .await
+        .err()
+        .unwrap();
+    assert!(matches!(err, CliError::EndpointNotAllowed(_)), "{err:?}");
+    assert!(err.to_string().contains("'judge.example.com'"), "{err}");
+
+    let sweep = SweepConfig {
+        seeds: vec![1, 2],
+        judge: Some(serde_json::from_value(judge).unwrap()),
+        ..Default::default()
+    };
+    let err = run_sweep(&config, &sweep, 1).await.err().unwrap();
+    assert!(matches!(err, CliError::EndpointNotAllowed(_)), "{err:?}");
+    llm.assert_async().await;
+}
+
+#[tokio::test]
+async fn test_restricted_allows_local_endpoints() {
+    let mut server = Server::new_async().await;
+    let llm = server
+        .mock("POST", "/v1/chat/completions")
+        .with_status(200)
+        .with_body(
+            json!({"choices": [{"message": {"role": "assistant", "content": "Summary"}}]})
+                .to_string(),
+        )
+        .create_async()
+        .await;
+
+    // The allowlist only matters for external endpoints
+    let config = builder(format!("{}/v1/chat/completions", server.url()))
+        .allow_external_host("guard.example.com")
+        .build()
+        .unwrap();
+    let output = evaluate(config).await.unwrap();
+    assert_eq!(output.status, "success");
+    llm.assert_async().await;
+}
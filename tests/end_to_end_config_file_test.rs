@@ -7,7 +7,10 @@
 //
 // REGRESSION: This would have caught the bug where file paths were sent to API
 
-use fortified_llm_client::{config::load_config_file, evaluate, EvaluationConfig};
+use fortified_llm_client::{
+    config::load_config_file, constants::http::DEFAULT_MAX_RESPONSE_BYTES, evaluate,
+    EvaluationConfig,
+};
 use mockito::Server;
 use std::fs;
 use tempfile::NamedTempFile;
@@ -93,19 +96,55 @@ temperature = 0.0
         system_prompt: file_config.system_prompt.unwrap(),
         user_prompt: file_config.user_prompt.unwrap(),
         provider: None,
-        temperature: file_config.temperature,
+        temperature: file_config.temperature.unwrap_or_default(),
+        top_p: None,
         max_tokens: file_config.max_tokens,
         seed: file_config.seed,
         api_key: None,
         timeout_secs: file_config.timeout_secs,
+        max_response_bytes: DEFAULT_MAX_RESPONSE_BYTES,
         validate_tokens: file_config.validate_tokens,
         context_limit: file_config.context_limit,
+        context_probe: None,
         response_format: None,
         pdf_input: None,
+        user_prompt_prefix: None,
+        user_prompt_suffix: None,
+        untrusted_content: Vec::new(),
+        spotlight: None,
         input_guardrails: None,
         output_guardrails: None,
+        refusal_detection: None,
+        repetition_detection: None,
+        deadline: None,
+        tools: Vec::new(),
+        context_files: Vec::new(),
+        input_sources: None,
+        escalation: None,
+        url_input: None,
+        system_prompt_url: None,
+        fetch: None,
+        image_input: None,
+        image_mode: Default::default(),
+        guard_budget: None,
+        chunking: None,
+        bedrock: None,
+        metrics: None,
+        response_cache: None,
+        rate_limiter: None,
+        data_policy: None,
+        metadata_fields: None,
+        tags: Vec::new(),
+        guardrail_conditions: Vec::new(),
         system_prompt_file: None,
         user_prompt_file: None,
+        guardrail_override: None,
+        override_token: None,
+        prompt_audit: None,
+        trend_stats: None,
+        config_warnings: Vec::new(),
+        context_warning_percent: None,
+        prompt_pins: Vec::new(),
     };
 
     // Step 6: Execute evaluation (makes actual HTTP request to mock server)
@@ -174,19 +213,55 @@ async fn test_end_to_end_config_with_inline_text() {
         system_prompt: file_config.system_prompt.unwrap(),
         user_prompt: file_config.user_prompt.unwrap(),
         provider: None,
-        temperature: file_config.temperature,
+        temperature: file_config.temperature.unwrap_or_default(),
+        top_p: None,
         max_tokens: file_config.max_tokens,
         seed: file_config.seed,
         api_key: None,
         timeout_secs: file_config.timeout_secs,
+        max_response_bytes: DEFAULT_MAX_RESPONSE_BYTES,
         validate_tokens: file_config.validate_tokens,
         context_limit: file_config.context_limit,
+        context_probe: None,
         response_format: None,
         pdf_input: None,
+        user_prompt_prefix: None,
+        user_prompt_suffix: None,
+        untrusted_content: Vec::new(),
+        spotlight: None,
         input_guardrails: None,
         output_guardrails: None,
+        refusal_detection: None,
+        repetition_detection: None,
+        deadline: None,
+        tools: Vec::new(),
+        context_files: Vec::new(),
+        input_sources: None,
+        escalation: None,
+        url_input: None,
+        system_prompt_url: None,
+        fetch: None,
+        image_input: None,
+        image_mode: Default::default(),
+        guard_budget: None,
+        chunking: None,
+        bedrock: None,
+        metrics: None,
+        response_cache: None,
+        rate_limiter: None,
+        data_policy: None,
+        metadata_fields: None,
+        tags: Vec::new(),
+        guardrail_conditions: Vec::new(),
         system_prompt_file: None,
         user_prompt_file: None,
+        guardrail_override: None,
+        override_token: None,
+        prompt_audit: None,
+        trend_stats: None,
+        config_warnings: Vec::new(),
+        context_warning_percent: None,
+        prompt_pins: Vec::new(),
     };
 
     let result = evaluate(eval_config).await;
@@ -310,19 +385,55 @@ temperature = 0.0
         system_prompt: file_config.system_prompt.unwrap(),
         user_prompt: file_config.user_prompt.unwrap(),
         provider: None,
-        temperature: file_config.temperature,
+        temperature: file_config.temperature.unwrap_or_default(),
+        top_p: None,
         max_tokens: file_config.max_tokens,
         seed: file_config.seed,
         api_key: None,
         timeout_secs: file_config.timeout_secs,
+        max_response_bytes: DEFAULT_MAX_RESPONSE_BYTES,
         validate_tokens: file_config.validate_tokens,
         context_limit: file_config.context_limit,
+        context_probe: None,
         response_format: None,
         pdf_input: None,
+        user_prompt_prefix: None,
+        user_prompt_suffix: None,
+        untrusted_content: Vec::new(),
+        spotlight: None,
         input_guardrails: None,
         output_guardrails: None,
+        refusal_detection: None,
+        repetition_detection: None,
+        deadline: None,
+        tools: Vec::new(),
+        context_files: Vec::new(),
+        input_sources: None,
+        escalation: None,
+        url_input: None,
+        system_prompt_url: None,
+        fetch: None,
+        image_input: None,
+        image_mode: Default::default(),
+        guard_budget: None,
+        chunking: None,
+        bedrock: None,
+        metrics: None,
+        response_cache: None,
+        rate_limiter: None,
+        data_policy: None,
+        metadata_fields: None,
+        tags: Vec::new(),
+        guardrail_conditions: Vec::new(),
         system_prompt_file: None,
         user_prompt_file: None,
+        guardrail_override: None,
+        override_token: None,
+        prompt_audit: None,
+        trend_stats: None,
+        config_warnings: Vec::new(),
+        context_warning_percent: None,
+        prompt_pins: Vec::new(),
     };
 
     let result = evaluate(eval_config).await;
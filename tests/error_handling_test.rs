@@ -17,6 +17,8 @@ fn test_all_error_variants_have_non_empty_messages() {
             excess: 91808,
         },
         CliError::PdfProcessingFailed("Failed to extract PDF".to_string()),
+        CliError::QuotaExceeded("token quota of 1000 reached".to_string()),
+        CliError::SelfTestFailed("injection_is_blocked: not blocked".to_string()),
     ];
 
     for error in errors {
@@ -76,6 +78,8 @@ fn test_error_exit_codes_are_unique() {
             excess: 50,
         },
         CliError::PdfProcessingFailed("test".to_string()),
+        CliError::QuotaExceeded("test".to_string()),
+        CliError::SelfTestFailed("test".to_string()),
     ];
 
     let mut codes = HashSet::new();
@@ -120,6 +124,14 @@ fn test_error_code_strings() {
             CliError::PdfProcessingFailed("test".to_string()),
             "PDF_PROCESSING_FAILED",
         ),
+        (
+            CliError::QuotaExceeded("test".to_string()),
+            "QUOTA_EXCEEDED",
+        ),
+        (
+            CliError::SelfTestFailed("test".to_string()),
+            "SELF_TEST_FAILED",
+        ),
     ];
 
     for (error, expected_code) in errors {
@@ -0,0 +1,155 @@
+// A/B experiment tests
+//
+// Verifies variants run on the dataset items, responses are scored with
+// assertions or an LLM judge, and the report compares variants pairwise.
+
+mod common;
+
+use fortified_llm_client::{
+    run_experiment, Assignment, BatchItem, EvaluationConfig, ExperimentConfig,
+};
+use mockito::{Matcher, Server};
+
+fn base_config(api_url: String) -> EvaluationConfig {
+    common::base_builder(api_url).build().unwrap()
+}
+
+fn completion(content: &str) -> String {
+    serde_json::json!({"choices": [{"message": {"role": "assistant", "content": content}}]})
+        .to_string()
+}
+
+fn items(count: usize) -> Vec<BatchItem> {
+    (0..count)
+        .map(|i| BatchItem {
+            id: Some(format!("q{i}")),
+            user_prompt: format!("Question {i} about refunds"),
+            ..Default::default()
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn test_experiment_with_assertions_compares_variants() {
+    let mut server = Server::new_async().await;
+    let concise = server
+        .mock("POST", "/v1/chat/completions")
+        .match_body(Matcher::Regex("Be concise".to_string()))
+        .with_status(200)
+        .with_body(completion("Refunds take 5 days."))
+        .expect(6)
+        .create_async()
+        .await;
+    let vague = server
+        .mock("POST", "/v1/chat/completions")
+        .match_body(Matcher::Regex("Be vague".to_string()))
+        .with_status(200)
+        .with_body(completion("It depends."))
+        .expect(6)
+        .create_async()
+        .await;
+
+    let experiment: ExperimentConfig = toml::from_str(
+        r#"
+        variants = [
+          { name = "concise", system_prompt = "Be concise" },
+          { name = "vague", system_prompt = "Be vague" },
+        ]
+
+        [[assertions]]
+        type = "contains"
+        value = "refund"
+        "#,
+    )
+    .unwrap();
+    let base = base_config(server.url() + "/v1/chat/completions");
+
+    let report = run_experiment(&base, &items(6), &experiment, 3)
+        .await
+        .unwrap();
+
+    concise.assert_async().await;
+    vague.assert_async().await;
+    assert_eq!(report.assignment, Assignment::All);
+    assert_eq!(report.runs.len(), 12);
+    assert_eq!(report.runs[1].id.as_deref(), Some("q0"));
+    assert_eq!(report.runs[1].variant, "vague");
+
+    assert_eq!(report.variants[0].name, "concise");
+    assert_eq!(report.variants[0].runs, 6);
+    assert_eq!(report.variants[0].pass_rate, 1.0);
+    assert_eq!(report.variants[1].pass_rate, 0.0);
+
+    let comparison = &report.comparisons[0];
+    assert_eq!(comparison.a_wins, Some(6));
+    assert_eq!(comparison.win_rate, Some(1.0));
+    assert_eq!(comparison.score_delta, 1.0);
+    assert!(comparison.p_value.unwrap() < 0.05);
+    assert!(comparison.hint.starts_with("'concise' outperforms 'vague'"));
+}
+
+#[tokio::test]
+async fn test_experiment_split_assignment_with_judge() {
+    let mut server = Server::new_async().await;
+    let answers = server
+        .mock("POST", "/v1/chat/completions")
+        .match_body(Matcher::Regex("Variant".to_string()))
+        .with_status(200)
+        .with_body(completion("Refunds take 5 days."))
+        .expect(4)
+        .create_async()
+        .await;
+    let judge = server
+        .mock("POST", "/judge")
+        .match_body(Matcher::Regex("Criteria: Mentions a duration".to_string()))
+        .with_status(200)
+        .with_body(completion("8"))
+        .expect(4)
+        .create_async()
+        .await;
+
+    let experiment: ExperimentConfig = serde_json::from_value(serde_json::json!({
+        "assignment": "split",
+        "variants": [
+            {"name": "a", "system_prompt": "Variant A"},
+            {"name": "b", "system_prompt": "Variant B"}
+        ],
+        "judge": {
+            "api_url": server.url() + "/judge",
+            "model": "judge-model",
+            "criteria": "Mentions a duration"
+        }
+    }))
+    .unwrap();
+    let base = base_config(server.url() + "/v1/chat/completions");
+
+    let report = run_experiment(&base, &items(4), &experiment, 2)
+        .await
+        .unwrap();
+
+    answers.assert_async().await;
+    judge.assert_async().await;
+    let variants: Vec<&str> = report.runs.iter().map(|r| r.variant.as_str()).collect();
+    assert_eq!(variants, vec!["a", "b", "a", "b"]);
+    assert!(report.runs.iter().all(|r| r.score == 0.8));
+    assert_eq!(report.variants[1].mean_score, 0.8);
+    let comparison = &report.comparisons[0];
+    assert_eq!(comparison.a_wins, None);
+    assert!(comparison.hint.starts_with("Not enough data"));
+}
+
+#[tokio::test]
+async fn test_experiment_requires_two_variants() {
+    let experiment: ExperimentConfig = toml::from_str(
+        r#"
+        variants = [{ name = "only", system_prompt = "Solo" }]
+        "#,
+    )
+    .unwrap();
+    let base = base_config("http://localhost:1/v1/chat/completions".to_string());
+
+    let err = run_experiment(&base, &items(1), &experiment, 1)
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("at least 2 variants"));
+}
@@ -0,0 +1,50 @@
+//! Integration test for URL fetching with proxy environment variables
+//!
+//! Kept in its own test binary: it sets `HTTP_PROXY` for the whole process,
+//! which would route the other tests' mock requests through the proxy.
+
+use fortified_llm_client::{fetch_url, FetchConfig};
+use mockito::{Matcher, Server};
+
+#[tokio::test]
+async fn test_fetch_ignores_proxy_environment() {
+    let mut proxy = Server::new_async().await;
+    let proxied = proxy
+        .mock("GET", Matcher::Any)
+        .with_status(200)
+        .with_header("content-type", "text/plain")
+        .with_body("via proxy")
+        .expect(0)
+        .create_async()
+        .await;
+    let mut server = Server::new_async().await;
+    let page = server
+        .mock("GET", "/page")
+        .with_status(200)
+        .with_header("content-type", "text/plain")
+        .with_body("direct")
+        .expect(1)
+        .create_async()
+        .await;
+    std::env::set_var("HTTP_PROXY", proxy.url());
+    std::env::set_var("http_proxy", proxy.url());
+
+    // Private hosts are still refused, although the proxy could reach them
+    let err = fetch_url(&format!("{}/page", server.url()), &FetchConfig::default())
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("non-public address"), "{err}");
+
+    // Allowed private hosts are fetched directly, not through the proxy
+    let config = FetchConfig {
+        allow_private_networks: true,
+        ..FetchConfig::default()
+    };
+    let fetched = fetch_url(&format!("{}/page", server.url()), &config)
+        .await
+        .unwrap();
+    assert_eq!(fetched.markdown, "direct");
+
+    proxied.assert_async().await;
+    page.assert_async().await;
+}
@@ -1,9 +1,10 @@
 // Integration tests for figment-based config merging (BUG #2 fix)
 //
 // These tests verify that the merge_config() function correctly merges
-// CLI arguments with config files (JSON and TOML formats).
+// CLI arguments with SLLM_* environment variables and config files (JSON and
+// TOML formats).
 //
-// Priority: CLI args > Config file
+// Priority: CLI args > Environment > Config file
 // Supports: JSON (.json) and TOML (.toml) formats
 
 use std::{fs, process::Command};
@@ -570,3 +571,72 @@ fn test_api_key_fields_merge() {
     // CLI should override config api_key
     assert!(!output.is_empty());
 }
+
+// =============================================================================
+// 6. ENVIRONMENT VARIABLE TESTS (Priority: CLI > SLLM_* > Config)
+// =============================================================================
+
+// Run the CLI with SLLM_* variables and return the output JSON
+fn run_cli_with_env(
+    config: Option<&std::path::Path>,
+    env: &[(&str, &str)],
+    extra_args: &[&str],
+) -> serde_json::Value {
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("fortified-llm-client"));
+    if let Some(path) = config {
+        cmd.arg("--config-file").arg(path);
+    }
+    cmd.envs(env.iter().copied())
+        .args(extra_args)
+        .arg("--quiet");
+    let output = cmd.output().unwrap();
+    serde_json::from_slice(&output.stdout).unwrap()
+}
+
+#[test]
+fn test_env_overrides_config_and_cli_overrides_env() {
+    let file = NamedTempFile::new().unwrap();
+    let path = file.path().with_extension("toml");
+    fs::write(
+        &path,
+        r#"
+api_url = "mock://"
+model = "config-model"
+system_prompt = "Echo"
+user_prompt = "ping"
+temperature = 0.5
+"#,
+    )
+    .unwrap();
+
+    let env = [("SLLM_MODEL", "env-model"), ("SLLM_TEMPERATURE", "0.25")];
+    let output = run_cli_with_env(Some(&path), &env, &[]);
+    assert_eq!(output["status"], "success");
+    assert_eq!(output["metadata"]["model"], "env-model");
+    assert_eq!(output["metadata"]["temperature"], 0.25);
+
+    let output = run_cli_with_env(Some(&path), &env, &["--model", "cli-model"]);
+    assert_eq!(output["metadata"]["model"], "cli-model");
+    assert_eq!(output["metadata"]["temperature"], 0.25);
+
+    fs::remove_file(&path).ok();
+}
+
+#[test]
+fn test_env_sets_nested_keys_without_config_file() {
+    // Guardrails have no CLI flags: `__` nests keys like config file tables
+    let env = [
+        ("SLLM_API_URL", "mock://"),
+        ("SLLM_MODEL", "env-model"),
+        ("SLLM_SYSTEM_PROMPT", "Echo"),
+        ("SLLM_GUARDRAILS__INPUT__TYPE", "regex"),
+        ("SLLM_GUARDRAILS__INPUT__MAX_LENGTH_BYTES", "8"),
+    ];
+    let output = run_cli_with_env(None, &env, &["--user-text", "ping"]);
+    assert_eq!(output["status"], "success");
+    assert_eq!(output["metadata"]["model"], "env-model");
+
+    let output = run_cli_with_env(None, &env, &["--user-text", "a longer prompt"]);
+    assert_eq!(output["status"], "error");
+    assert_eq!(output["metadata"]["violation_rules"][0], "regex.MAX_LENGTH");
+}
@@ -0,0 +1,184 @@
+//! Integration tests for guardrail token accounting and budgets
+//!
+//! These tests verify that model-backed guardrail calls are recorded per
+//! direction in `metadata.guard_tokens`, that an exhausted per-evaluation
+//! budget blocks before the guard model is called, that an endpoint token
+//! bucket shared across evaluations skips calls with `on_exhausted = "skip"`,
+//! and that `[guard_budget]` loads from config files.
+
+mod common;
+
+use fortified_llm_client::{
+    evaluate, load_config_file, BudgetAction, EndpointBudgetConfig, EvaluationConfig, GuardBudget,
+    GuardBudgetConfig, GuardrailConfigBuilder,
+};
+use mockito::{Server, ServerGuard};
+use serde_json::json;
+use std::{io::Write, sync::Arc};
+
+async fn llm_mock(server: &mut ServerGuard, calls: usize) -> mockito::Mock {
+    server
+        .mock("POST", "/v1/chat/completions")
+        .with_status(200)
+        .with_body(
+            json!({"choices": [{"message": {"role": "assistant", "content": "Paris"}}]})
+                .to_string(),
+        )
+        .expect(calls)
+        .create_async()
+        .await
+}
+
+async fn guard_mock(server: &mut ServerGuard, calls: usize) -> mockito::Mock {
+    server
+        .mock("POST", "/api/generate")
+        .with_status(200)
+        .with_body(r#"{"response": "safe", "done": true}"#)
+        .expect(calls)
+        .create_async()
+        .await
+}
+
+fn config(server_url: &str, budget: Option<Arc<GuardBudget>>) -> EvaluationConfig {
+    let guard = || {
+        GuardrailConfigBuilder::llama_guard(format!("{server_url}/api/generate"), "llama-guard3:8b")
+            .build()
+            .unwrap()
+    };
+    let mut builder = common::base_builder(format!("{server_url}/v1/chat/completions"))
+        .user_prompt("What is the capital of France?")
+        .input_guardrails(guard())
+        .output_guardrails(guard());
+    if let Some(budget) = budget {
+        builder = builder.guard_budget(budget);
+    }
+    builder.build().unwrap()
+}
+
+#[tokio::test]
+async fn test_guard_tokens_recorded_per_direction() {
+    let mut server = Server::new_async().await;
+    let llm = llm_mock(&mut server, 1).await;
+    let guard = guard_mock(&mut server, 2).await;
+
+    let output = evaluate(config(&server.url(), None)).await.unwrap();
+    assert_eq!(output.status, "success");
+    let usage = output.metadata.guard_tokens;
+    assert_eq!(usage.len(), 2);
+    assert_eq!(usage[0].provider, "llama_guard");
+    assert_eq!(usage[0].direction, "input");
+    assert_eq!(usage[1].direction, "output");
+    assert_eq!(usage[0].endpoint, format!("{}/api/generate", server.url()));
+    // Prompt plus the 100-token response buffer
+    assert!(usage
+        .iter()
+        .all(|u| u.calls == 1 && u.tokens_estimated > 100));
+    assert!(usage.iter().all(|u| u.denied == 0));
+    llm.assert_async().await;
+    guard.assert_async().await;
+}
+
+#[tokio::test]
+async fn test_evaluation_budget_blocks_before_guard_call() {
+    let mut server = Server::new_async().await;
+    let llm = llm_mock(&mut server, 0).await;
+    let guard = guard_mock(&mut server, 0).await;
+
+    let budget = GuardBudget::from_config(&GuardBudgetConfig {
+        max_tokens_per_evaluation: Some(50),
+        ..Default::default()
+    })
+    .unwrap();
+    let output = evaluate(config(&server.url(), Some(Arc::new(budget))))
+        .await
+        .unwrap();
+    assert_eq!(output.status, "error");
+    assert_eq!(output.error.unwrap().code, "INPUT_VALIDATION_FAILED");
+    assert_eq!(
+        output.metadata.violation_rules,
+        vec!["guard_budget.BUDGET_EXHAUSTED"]
+    );
+    let usage = &output.metadata.guard_tokens[0];
+    assert_eq!((usage.calls, usage.denied), (0, 1));
+    llm.assert_async().await;
+    guard.assert_async().await;
+}
+
+#[tokio::test]
+async fn test_endpoint_bucket_shared_across_evaluations() {
+    let mut server = Server::new_async().await;
+    let llm = llm_mock(&mut server, 2).await;
+    // Only the first evaluation's input check fits the bucket
+    let guard = guard_mock(&mut server, 1).await;
+
+    let budget = Arc::new(
+        GuardBudget::from_config(&GuardBudgetConfig {
+            endpoints: vec![EndpointBudgetConfig {
+                api_url: format!("{}/api/generate", server.url()),
+                tokens_per_minute: 1,
+                burst_tokens: Some(150),
+            }],
+            on_exhausted: BudgetAction::Skip,
+            ..Default::default()
+        })
+        .unwrap(),
+    );
+
+    let first = evaluate(config(&server.url(), Some(Arc::clone(&budget))))
+        .await
+        .unwrap();
+    assert_eq!(first.status, "success");
+    assert_eq!(first.metadata.guard_tokens[0].calls, 1);
+    assert_eq!(first.metadata.guard_tokens[1].denied, 1);
+
+    let second = evaluate(config(&server.url(), Some(Arc::clone(&budget))))
+        .await
+        .unwrap();
+    assert_eq!(second.status, "success");
+    assert!(second.metadata.guard_tokens.iter().all(|u| u.calls == 0));
+    assert_eq!(
+        budget.tokens_used(),
+        first.metadata.guard_tokens[0].tokens_estimated
+    );
+    llm.assert_async().await;
+    guard.assert_async().await;
+}
+
+#[test]
+fn test_guard_budget_loads_from_config() {
+    let config_content = r#"
+api_url = "http://localhost:11434/v1/chat/completions"
+model = "test-model"
+system_prompt = "Test system"
+user_prompt = "Test user"
+
+[guard_budget]
+max_tokens_per_evaluation = 4000
+max_tokens_per_batch = 500000
+on_exhausted = "skip"
+
+[[guard_budget.endpoints]]
+api_url = "http://guard:11434/api/generate"
+tokens_per_minute = 60000
+"#;
+
+    let mut temp_file = tempfile::Builder::new().suffix(".toml").tempfile().unwrap();
+    temp_file.write_all(config_content.as_bytes()).unwrap();
+    temp_file.flush().unwrap();
+
+    let config = load_config_file(temp_file.path())
+        .unwrap()
+        .guard_budget
+        .unwrap();
+    assert_eq!(config.max_tokens_per_evaluation, Some(4000));
+    assert_eq!(config.on_exhausted, BudgetAction::Skip);
+    assert_eq!(config.endpoints[0].burst_tokens, None);
+    GuardBudget::from_config(&config).unwrap();
+
+    let duplicate = GuardBudgetConfig {
+        endpoints: vec![config.endpoints[0].clone(), config.endpoints[0].clone()],
+        ..config
+    };
+    let err = GuardBudget::from_config(&duplicate).unwrap_err();
+    assert!(err.to_string().contains("duplicate api_url"), "{err}");
+}
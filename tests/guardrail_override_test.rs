@@ -0,0 +1,107 @@
+// Guardrail override token tests
+//
+// Verifies that a valid override token lets an allowlisted guardrail block
+// through as warnings, that invalid tokens keep the block, and that every
+// attempt lands in the audit log.
+
+use fortified_llm_client::{
+    config_builder::ConfigBuilder, evaluate, AuditEventKind, EvaluationConfig, GuardrailOverride,
+    GuardrailProviderConfig, MemoryAuditLog, Provider, RegexGuardrailConfig,
+};
+use mockito::Server;
+use std::sync::Arc;
+
+fn config_with_override(
+    api_url: String,
+    controller: Arc<GuardrailOverride>,
+    token: Option<&str>,
+) -> EvaluationConfig {
+    let mut builder = ConfigBuilder::new()
+        .api_url(api_url)
+        .model("test-model")
+        .system_prompt("Test system")
+        .user_prompt("This prompt is longer than the tiny limit")
+        .provider(Provider::OpenAI)
+        .max_tokens(100)
+        .timeout_secs(5)
+        .input_guardrails(GuardrailProviderConfig::Regex(RegexGuardrailConfig {
+            max_length_bytes: 10,
+            ..Default::default()
+        }))
+        .guardrail_override(controller);
+    if let Some(token) = token {
+        builder = builder.override_token(token);
+    }
+    builder.build().unwrap()
+}
+
+#[tokio::test]
+async fn test_valid_override_token_lets_request_through() {
+    let mut server = Server::new_async().await;
+    let mock = server
+        .mock("POST", "/v1/chat/completions")
+        .with_status(200)
+        .with_body(r#"{"choices": [{"message": {"role": "assistant", "content": "ok"}}]}"#)
+        .expect(1)
+        .create_async()
+        .await;
+
+    let audit = Arc::new(MemoryAuditLog::new());
+    let controller = Arc::new(
+        GuardrailOverride::new("s3cret", vec!["MAX_LENGTH".to_string()], 5, audit.clone()).unwrap(),
+    );
+
+    let config = config_with_override(
+        server.url() + "/v1/chat/completions",
+        controller,
+        Some("s3cret"),
+    );
+    let output = evaluate(config).await.unwrap();
+
+    assert_eq!(output.status, "success");
+    mock.assert_async().await;
+
+    let events = audit.events();
+    assert_eq!(events.len(), 1);
+    match &events[0].kind {
+        AuditEventKind::OverrideApplied { stage, rules, .. } => {
+            assert_eq!(stage, "input");
+            assert_eq!(rules, &vec!["MAX_LENGTH".to_string()]);
+        }
+        other => panic!("Expected override_applied, got {other:?}"),
+    }
+}
+
+#[tokio::test]
+async fn test_invalid_override_token_keeps_block() {
+    let audit = Arc::new(MemoryAuditLog::new());
+    let controller = Arc::new(GuardrailOverride::new("s3cret", vec![], 5, audit.clone()).unwrap());
+
+    // Blocked before invocation, so no server is needed
+    let config = config_with_override(
+        "http://127.0.0.1:9/v1/chat/completions".to_string(),
+        controller,
+        Some("guess"),
+    );
+    let output = evaluate(config).await.unwrap();
+
+    assert_eq!(output.status, "error");
+    assert_eq!(output.error.unwrap().code, "INPUT_VALIDATION_FAILED");
+    assert_eq!(audit.events()[0].kind.name(), "override_denied");
+}
+
+#[tokio::test]
+async fn test_no_token_means_no_audit_entry() {
+    let audit = Arc::new(MemoryAuditLog::new());
+    let controller = Arc::new(GuardrailOverride::new("s3cret", vec![], 5, audit.clone()).unwrap());
+
+    let config = config_with_override(
+        "http://127.0.0.1:9/v1/chat/completions".to_string(),
+        controller,
+        None,
+    );
+    let output = evaluate(config).await.unwrap();
+
+    assert_eq!(output.status, "error");
+    assert!(audit.events().is_empty());
+}
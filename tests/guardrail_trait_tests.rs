@@ -1,5 +1,10 @@
-use fortified_llm_client::guardrails::{
-    config::RegexGuardrailConfig, GuardrailProvider, RegexGuardrail, Severity,
+use fortified_llm_client::{
+    guardrails::{
+        config::{RegexAction, RegexGuardrailConfig},
+        AggregationMode, ExecutionMode, GuardrailProvider, GuardrailResult, HybridGuardrail,
+        OutputContext, RegexGuardrail, Severity, Violation,
+    },
+    CliError,
 };
 
 #[tokio::test]
@@ -20,6 +25,9 @@ async fn test_input_guardrail_max_length() {
         max_length_bytes: 10,
         patterns_file: None,
         severity_threshold: Severity::Medium,
+        builtin_patterns: Vec::new(),
+        action: RegexAction::Block,
+        preview_chars: None,
     };
     let guardrail = RegexGuardrail::new(config);
 
@@ -49,6 +57,9 @@ async fn test_output_guardrail_max_length() {
         max_length_bytes: 10,
         patterns_file: None,
         severity_threshold: Severity::High,
+        builtin_patterns: Vec::new(),
+        action: RegexAction::Block,
+        preview_chars: None,
     };
     let guardrail = RegexGuardrail::new(config);
 
@@ -87,9 +98,86 @@ async fn test_severity_threshold() {
         max_length_bytes: 100000,
         patterns_file: None,
         severity_threshold: Severity::Critical, // Very high threshold
+        builtin_patterns: Vec::new(),
+        action: RegexAction::Block,
+        preview_chars: None,
     };
     let guardrail = RegexGuardrail::new(config);
 
     let result = guardrail.validate("test").await.unwrap();
     assert!(result.passed);
 }
+
+/// Flags responses that merely repeat the prompt (needs the output context)
+struct EchoDetector;
+
+#[async_trait::async_trait]
+impl GuardrailProvider for EchoDetector {
+    async fn validate(&self, _content: &str) -> Result<GuardrailResult, CliError> {
+        Ok(GuardrailResult::without_quality_score(true, vec![], vec![]))
+    }
+
+    async fn validate_output(
+        &self,
+        response: &str,
+        context: &OutputContext,
+    ) -> Result<GuardrailResult, CliError> {
+        let echoed = context.user_prompt.as_deref() == Some(response);
+        let violations = if echoed {
+            vec![Violation {
+                namespace: String::new(),
+                rule: "ECHO".to_string(),
+                severity: Severity::Low,
+                message: "Response repeats the prompt".to_string(),
+                location: None,
+                source: None,
+                preview: None,
+            }]
+        } else {
+            vec![]
+        };
+        Ok(GuardrailResult::without_quality_score(
+            !echoed,
+            violations,
+            vec![],
+        ))
+    }
+
+    fn name(&self) -> &str {
+        "EchoDetector"
+    }
+}
+
+#[tokio::test]
+async fn test_validate_output_defaults_to_validate() {
+    let guardrail = RegexGuardrail::new(RegexGuardrailConfig {
+        max_length_bytes: 10,
+        ..Default::default()
+    });
+    let context = OutputContext::new("System", "Prompt", None);
+
+    let result = guardrail
+        .validate_output("A response longer than ten bytes", &context)
+        .await
+        .unwrap();
+    assert!(!result.passed);
+    assert!(context.system_prompt_hash.unwrap().starts_with("sha256:"));
+}
+
+#[tokio::test]
+async fn test_composite_forwards_output_context() {
+    let composite = HybridGuardrail::new(
+        vec![
+            Box::new(RegexGuardrail::new(RegexGuardrailConfig::default())),
+            Box::new(EchoDetector),
+        ],
+        ExecutionMode::Parallel,
+        AggregationMode::AllMustPass,
+    );
+    let context = OutputContext::new("System", "ping", None);
+
+    assert!(composite.validate("ping").await.unwrap().passed);
+    let result = composite.validate_output("ping", &context).await.unwrap();
+    assert!(!result.passed);
+    assert_eq!(result.violations[0].rule, "ECHO");
+}
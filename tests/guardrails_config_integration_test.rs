@@ -5,7 +5,8 @@
 //! config parsing succeeds but guardrails are silently ignored.
 
 use fortified_llm_client::{
-    config_builder::ConfigBuilder, load_config_file, ConfigFileRequest, GuardrailProviderConfig,
+    config_builder::ConfigBuilder, create_guardrail_provider, load_config_file, ConfigFileRequest,
+    GuardrailProviderConfig, OutputContext,
 };
 use std::io::Write;
 
@@ -468,3 +469,143 @@ max_length_bytes = 1000
         _ => panic!("Expected Regex variant for output"),
     }
 }
+
+/// Test that custom Llama Guard categories reach the guard prompt and its verdicts
+#[tokio::test]
+async fn test_llama_guard_custom_categories_from_config() {
+    let mut server = mockito::Server::new_async().await;
+    let guard = server
+        .mock("POST", "/api/generate")
+        .match_body(mockito::Matcher::Regex(
+            r"S15: Export Control\.\\nDual-use items".to_string(),
+        ))
+        .with_status(200)
+        .with_body(r#"{"response": "unsafe\nS15", "done": true}"#)
+        .expect(1)
+        .create_async()
+        .await;
+
+    let config_content = format!(
+        r#"
+api_url = "http://localhost:11434/api/generate"
+model = "test-model"
+system_prompt = "Test system"
+user_prompt = "Test user"
+
+[guardrails]
+type = "llama_guard"
+api_url = "{}/api/generate"
+model = "llama-guard3:8b"
+timeout_secs = 5
+enabled_categories = ["S1"]
+
+[[guardrails.custom_categories]]
+code = "S15"
+title = "Export Control"
+description = "Dual-use items shipped to embargoed destinations."
+"#,
+        server.url()
+    );
+
+    let mut temp_file = tempfile::Builder::new().suffix(".toml").tempfile().unwrap();
+    temp_file.write_all(config_content.as_bytes()).unwrap();
+    temp_file.flush().unwrap();
+
+    let config: ConfigFileRequest = load_config_file(temp_file.path().to_str().unwrap()).unwrap();
+    let provider_config = config.guardrails.unwrap().input_config().unwrap();
+    let provider = create_guardrail_provider(&provider_config).unwrap();
+
+    let result = provider.validate("Ship the lathe to X").await.unwrap();
+    guard.assert_async().await;
+    assert!(!result.passed);
+    assert_eq!(result.violations[0].rule, "S15");
+    assert!(result.violations[0].message.contains("Export Control"));
+}
+
+/// Test that custom categories may not shadow built-in codes
+#[test]
+fn test_llama_guard_custom_category_conflict_rejected() {
+    let config: GuardrailProviderConfig = toml::from_str(
+        r#"
+type = "llama_guard"
+api_url = "http://localhost:11434/api/generate"
+model = "llama-guard3:8b"
+timeout_secs = 5
+custom_categories = [{ code = "S2", title = "Fraud" }]
+"#,
+    )
+    .unwrap();
+
+    let Err(err) = create_guardrail_provider(&config) else {
+        panic!("Expected conflicting category to be rejected");
+    };
+    assert!(err
+        .to_string()
+        .contains("conflicts with a built-in category"));
+}
+
+/// Test that a numeric consistency check loads from TOML and compares against the source
+#[tokio::test]
+async fn test_numeric_consistency_output_guardrail_from_config() {
+    let config: GuardrailProviderConfig = toml::from_str(
+        r#"
+type = "numeric_consistency"
+severity_threshold = "Medium"
+"#,
+    )
+    .unwrap();
+    config.validate().unwrap();
+    let provider = create_guardrail_provider(&config).unwrap();
+
+    let source = "Summarize: the lease starts on 2025-01-01 at EUR 4,500 per month.";
+    let context = OutputContext::new("Test system", source, None);
+
+    let grounded = provider
+        .validate_output("Rent is 4500 per month from January 1, 2025.", &context)
+        .await
+        .unwrap();
+    assert!(grounded.passed);
+
+    let invented = provider
+        .validate_output("Rent is 5,400 per month from January 1, 2025.", &context)
+        .await
+        .unwrap();
+    assert!(!invented.passed);
+    assert_eq!(invented.violations[0].id(), "numeric.UNSUPPORTED_NUMBER");
+}
+
+/// Test that a language check loads from TOML and blocks responses in another language
+#[tokio::test]
+async fn test_language_output_guardrail_from_config() {
+    let config: GuardrailProviderConfig = toml::from_str(
+        r#"
+type = "language"
+target = "de"
+severity_threshold = "Medium"
+"#,
+    )
+    .unwrap();
+    config.validate().unwrap();
+    let provider = create_guardrail_provider(&config).unwrap();
+
+    let context = OutputContext::new("Test system", "Wie hoch ist die Miete?", None);
+
+    let german = provider
+        .validate_output(
+            "Die Miete beträgt viertausendfünfhundert Euro pro Monat und ist jeweils im Voraus zu zahlen.",
+            &context,
+        )
+        .await
+        .unwrap();
+    assert!(german.passed);
+
+    let english = provider
+        .validate_output(
+            "The rent is four thousand five hundred euros per month, payable in advance.",
+            &context,
+        )
+        .await
+        .unwrap();
+    assert!(!english.passed);
+    assert_eq!(english.violations[0].id(), "language.LANGUAGE_MISMATCH");
+}
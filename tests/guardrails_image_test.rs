@@ -0,0 +1,152 @@
+//! Integration tests for the image output guardrail
+//!
+//! These tests verify that images returned by the model (OpenRouter-style
+//! `images` parts) reach output guardrails, that the scanner webhook can block
+//! them, that allowed images are returned as data URLs, and that the
+//! guardrail loads from config files.
+
+mod common;
+
+use fortified_llm_client::{
+    evaluate, load_config_file, EvaluationConfig, GuardrailConfigBuilder, GuardrailProviderConfig,
+    ImageScannerConfig,
+};
+use mockito::{Matcher, Server, ServerGuard};
+use serde_json::json;
+use std::io::Write;
+
+/// 1x1 GIF
+const PIXEL_DATA_URL: &str =
+    "data:image/gif;base64,R0lGODlhAQABAIAAAAAAAP///yH5BAEAAAAALAAAAAABAAEAAAICRAEAOw==";
+
+async fn llm_with_image(server: &mut ServerGuard) -> mockito::Mock {
+    server
+        .mock("POST", "/v1/chat/completions")
+        .with_status(200)
+        .with_body(
+            json!({"choices": [{"message": {
+                "role": "assistant",
+                "content": "Here is your image",
+                "images": [{"type": "image_url", "image_url": {"url": PIXEL_DATA_URL}}]
+            }}]})
+            .to_string(),
+        )
+        .expect(1)
+        .create_async()
+        .await
+}
+
+fn config(server_url: &str, guardrail: GuardrailProviderConfig) -> EvaluationConfig {
+    common::base_builder(format!("{server_url}/v1/chat/completions"))
+        .user_prompt("Draw a pixel")
+        .output_guardrails(guardrail)
+        .build()
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_scanner_flags_generated_image() {
+    let mut server = Server::new_async().await;
+    let llm = llm_with_image(&mut server).await;
+    let scanner = server
+        .mock("POST", "/scan")
+        .match_body(Matcher::PartialJson(json!({
+            "media_type": "image/gif",
+            "width": 1,
+            "height": 1
+        })))
+        .with_status(200)
+        .with_body(json!({"flagged": true, "categories": ["nsfw"], "reason": "test"}).to_string())
+        .expect(1)
+        .create_async()
+        .await;
+
+    let guardrail = GuardrailConfigBuilder::image()
+        .scanner(ImageScannerConfig::new(format!("{}/scan", server.url())))
+        .build()
+        .unwrap();
+    let output = evaluate(config(&server.url(), guardrail)).await.unwrap();
+    assert_eq!(output.status, "error");
+    assert!(output.images.is_empty());
+    assert_eq!(output.error.unwrap().code, "OUTPUT_VALIDATION_FAILED");
+    assert_eq!(output.metadata.violation_rules, vec!["image.IMAGE_FLAGGED"]);
+    llm.assert_async().await;
+    scanner.assert_async().await;
+}
+
+#[tokio::test]
+async fn test_allowed_image_returned() {
+    let mut server = Server::new_async().await;
+    let llm = llm_with_image(&mut server).await;
+
+    let guardrail = GuardrailConfigBuilder::image()
+        .allowed_formats(["gif", "png"])
+        .max_dimensions(64, 64)
+        .build()
+        .unwrap();
+    let output = evaluate(config(&server.url(), guardrail)).await.unwrap();
+    assert_eq!(output.status, "success");
+    assert_eq!(output.images, vec![PIXEL_DATA_URL]);
+    llm.assert_async().await;
+
+    // The same image fails a stricter allowlist
+    let mut server = Server::new_async().await;
+    let llm = llm_with_image(&mut server).await;
+    let guardrail = GuardrailConfigBuilder::image()
+        .allowed_formats(["png"])
+        .build()
+        .unwrap();
+    let output = evaluate(config(&server.url(), guardrail)).await.unwrap();
+    assert_eq!(output.status, "error");
+    assert_eq!(
+        output.metadata.violation_rules,
+        vec!["image.IMAGE_FORMAT_NOT_ALLOWED"]
+    );
+    llm.assert_async().await;
+}
+
+#[test]
+fn test_image_guardrail_loads_from_config() {
+    let config_content = r#"
+api_url = "http://localhost:11434/v1/chat/completions"
+model = "test-model"
+system_prompt = "Test system"
+user_prompt = "Test user"
+
+[guardrails.output]
+type = "image"
+allowed_formats = ["png", "jpeg"]
+max_bytes = 5242880
+
+[guardrails.output.scanner]
+url = "https://scanner.internal/v1/scan"
+api_key_name = "SCANNER_API_KEY"
+"#;
+
+    let mut temp_file = tempfile::Builder::new().suffix(".toml").tempfile().unwrap();
+    temp_file.write_all(config_content.as_bytes()).unwrap();
+    temp_file.flush().unwrap();
+
+    let config = load_config_file(temp_file.path()).unwrap();
+    let output = config.guardrails.unwrap().output.unwrap();
+    output.validate().unwrap();
+    match output {
+        GuardrailProviderConfig::Image(image) => {
+            assert_eq!(image.allowed_formats, vec!["png", "jpeg"]);
+            assert_eq!(image.max_bytes, Some(5_242_880));
+            let scanner = image.scanner.unwrap();
+            assert_eq!(scanner.timeout_secs, 30);
+            assert_eq!(scanner.api_key_name.as_deref(), Some("SCANNER_API_KEY"));
+        }
+        other => panic!("Expected Image config, got {other:?}"),
+    }
+
+    let err = GuardrailConfigBuilder::image()
+        .allowed_formats(["bmp"])
+        .build()
+        .unwrap_err();
+    assert!(
+        err.to_string().contains("unknown image format 'bmp'"),
+        "{err}"
+    );
+}
@@ -0,0 +1,116 @@
+//! Integration tests for the JSON schema guardrail
+//!
+//! These tests verify that the guardrail can be configured via TOML files,
+//! that it falls back to the request's `response_format` schema and that
+//! schema violations fail output validation with JSON pointers.
+
+use fortified_llm_client::{
+    config_builder::ConfigBuilder, evaluate, load_config_file, EvaluationOutcome,
+    GuardrailConfigBuilder, GuardrailProviderConfig, Provider, ResponseFormat,
+};
+use mockito::Server;
+use serde_json::json;
+use std::io::Write;
+
+/// Test that JsonSchema can be loaded from config file
+#[test]
+fn test_json_schema_loads_from_config() {
+    let config_content = r#"
+api_url = "http://localhost:11434/api/generate"
+model = "test-model"
+system_prompt = "Test system"
+user_prompt = "Test user"
+
+[guardrails.output]
+type = "json_schema"
+schema_file = "schemas/invoice.json"
+strict = true
+"#;
+
+    let mut temp_file = tempfile::Builder::new().suffix(".toml").tempfile().unwrap();
+    temp_file.write_all(config_content.as_bytes()).unwrap();
+    temp_file.flush().unwrap();
+
+    let config = load_config_file(temp_file.path()).unwrap();
+    match config
+        .guardrails
+        .unwrap()
+        .output
+        .expect("Should have output config")
+    {
+        GuardrailProviderConfig::JsonSchema(json_schema) => {
+            assert_eq!(
+                json_schema.schema_file.unwrap().to_str(),
+                Some("schemas/invoice.json")
+            );
+            assert!(json_schema.strict);
+        }
+        other => panic!("Expected JsonSchema config, got {other:?}"),
+    }
+
+    let err = GuardrailConfigBuilder::json_schema()
+        .schema_file("missing.json")
+        .build()
+        .unwrap_err();
+    assert!(err.to_string().contains("does not exist"), "{err}");
+}
+
+#[tokio::test]
+async fn test_response_validated_against_response_format_schema() {
+    let mut server = Server::new_async().await;
+    let llm = server
+        .mock("POST", "/v1/chat/completions")
+        .with_status(200)
+        .with_body(
+            json!({"choices": [{"message": {
+                "role": "assistant",
+                "content": r#"{"total": "12.50", "currency": "EUR"}"#
+            }}]})
+            .to_string(),
+        )
+        .expect(2)
+        .create_async()
+        .await;
+
+    let schema = json!({
+        "type": "object",
+        "properties": {"total": {"type": "number"}},
+        "required": ["total"]
+    });
+    let config = |strict: bool, guardrail: GuardrailProviderConfig| {
+        ConfigBuilder::new()
+            .api_url(format!("{}/v1/chat/completions", server.url()))
+            .provider(Provider::OpenAI)
+            .model("test-model")
+            .system_prompt("Extract the invoice total")
+            .user_prompt("Total due: 12.50 EUR")
+            .response_format(ResponseFormat::json_schema(
+                "invoice".to_string(),
+                schema.clone(),
+                strict,
+            ))
+            .output_guardrails(guardrail)
+            .build()
+            .unwrap()
+    };
+    let guardrail = || GuardrailConfigBuilder::json_schema().build().unwrap();
+
+    // The response_format strict flag also rejects the undeclared "currency"
+    let output = evaluate(config(true, guardrail())).await.unwrap();
+    let error = output.error.unwrap();
+    assert_eq!(error.code, EvaluationOutcome::OutputValidationFailed);
+    assert!(error.message.contains("json_schema.SCHEMA_VIOLATION"));
+    assert!(error.message.contains("/total"), "{}", error.message);
+
+    // An output schema_file replaces the response_format schema
+    let dir = tempfile::TempDir::new().unwrap();
+    let schema_file = dir.path().join("lenient.json");
+    std::fs::write(&schema_file, r#"{"type": "object"}"#).unwrap();
+    let lenient = GuardrailConfigBuilder::json_schema()
+        .schema_file(&schema_file)
+        .build()
+        .unwrap();
+    let output = evaluate(config(false, lenient)).await.unwrap();
+    assert_eq!(output.status, "success");
+    llm.assert_async().await;
+}
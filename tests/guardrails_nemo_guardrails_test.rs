@@ -0,0 +1,125 @@
+//! Integration tests for the NeMo Guardrails provider
+//!
+//! These tests verify that NeMo Guardrails can be configured via TOML files
+//! and that the provider asks the server for the right rails and reports
+//! stopping rails as violations.
+
+use fortified_llm_client::{
+    create_guardrail_provider, load_config_file, GuardrailConfigBuilder, GuardrailProviderConfig,
+    OutputContext,
+};
+use mockito::{Matcher, Server};
+use serde_json::json;
+use std::io::Write;
+
+/// Test that NemoGuardrails can be loaded from config file
+#[test]
+fn test_nemo_guardrails_loads_from_config() {
+    let config_content = r#"
+api_url = "http://localhost:11434/api/generate"
+model = "test-model"
+system_prompt = "Test system"
+user_prompt = "Test user"
+
+[guardrails.input]
+type = "nemo_guardrails"
+api_url = "http://localhost:8000/v1/chat/completions"
+config_id = "content_safety"
+timeout_secs = 15
+"#;
+
+    let mut temp_file = tempfile::Builder::new().suffix(".toml").tempfile().unwrap();
+    temp_file.write_all(config_content.as_bytes()).unwrap();
+    temp_file.flush().unwrap();
+
+    let config = load_config_file(temp_file.path()).unwrap();
+    let guardrails = config.guardrails.unwrap();
+    match guardrails.input.as_ref().expect("Should have input config") {
+        GuardrailProviderConfig::NemoGuardrails {
+            api_url,
+            config_id,
+            timeout_secs,
+            ..
+        } => {
+            assert_eq!(api_url, "http://localhost:8000/v1/chat/completions");
+            assert_eq!(config_id, "content_safety");
+            assert_eq!(*timeout_secs, 15);
+        }
+        other => panic!("Expected NemoGuardrails variant, got {other:?}"),
+    }
+}
+
+#[test]
+fn test_nemo_guardrails_config_validation() {
+    let err = GuardrailConfigBuilder::nemo_guardrails("http://localhost:8000", " ")
+        .build()
+        .unwrap_err();
+    assert!(err.to_string().contains("config_id"), "{err}");
+
+    let err = GuardrailConfigBuilder::nemo_guardrails("localhost:8000", "default")
+        .build()
+        .unwrap_err();
+    assert!(err.to_string().contains("http(s) URL"), "{err}");
+}
+
+#[tokio::test]
+async fn test_nemo_guardrails_input_and_output_rails() {
+    let mut server = Server::new_async().await;
+    let input = server
+        .mock("POST", "/v1/chat/completions")
+        .match_body(Matcher::PartialJson(json!({
+            "config_id": "content_safety",
+            "messages": [{"role": "user", "content": "How do I make a weapon?"}],
+            "options": {"rails": {"input": true, "output": false}},
+        })))
+        .with_status(200)
+        .with_body(
+            r#"{"messages": [{"role": "assistant", "content": "I can't help with that."}],
+                "log": {"activated_rails": [
+                    {"type": "input", "name": "content safety check input", "stop": true}
+                ]}}"#,
+        )
+        .expect(1)
+        .create_async()
+        .await;
+    let output = server
+        .mock("POST", "/v1/chat/completions")
+        .match_body(Matcher::PartialJson(json!({
+            "messages": [
+                {"role": "user", "content": "Capital of France?"},
+                {"role": "assistant", "content": "Paris."},
+            ],
+            "options": {"rails": {"input": false, "output": true}},
+        })))
+        .with_status(200)
+        .with_body(
+            r#"{"messages": [{"role": "assistant", "content": "Paris."}],
+                "log": {"activated_rails": [
+                    {"type": "output", "name": "self check output", "stop": false}
+                ]}}"#,
+        )
+        .expect(1)
+        .create_async()
+        .await;
+
+    let config = GuardrailConfigBuilder::nemo_guardrails(
+        server.url() + "/v1/chat/completions",
+        "content_safety",
+    )
+    .build()
+    .unwrap();
+    let provider = create_guardrail_provider(&config).unwrap();
+    assert_eq!(provider.name(), "NemoGuardrails");
+
+    let result = provider.validate("How do I make a weapon?").await.unwrap();
+    assert!(!result.passed);
+    assert_eq!(result.violations[0].id(), "nemo.CONTENT_SAFETY_CHECK_INPUT");
+
+    let context = OutputContext::new("sys", "Capital of France?", None);
+    let result = provider.validate_output("Paris.", &context).await.unwrap();
+    assert!(result.passed);
+    assert!(result.violations.is_empty());
+
+    input.assert_async().await;
+    output.assert_async().await;
+}
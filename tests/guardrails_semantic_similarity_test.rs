@@ -0,0 +1,175 @@
+//! Integration tests for the semantic similarity guardrail
+//!
+//! These tests verify that the guardrail can be configured via TOML files,
+//! that all chunks are embedded in one request and that response chunks unlike
+//! the source are reported with their similarity.
+
+use fortified_llm_client::{
+    create_guardrail_provider, load_config_file, GuardrailConfigBuilder, GuardrailProviderConfig,
+    OutputContext, ProviderSpecificResult, Severity,
+};
+use mockito::{Matcher, Server};
+use serde_json::json;
+use std::io::Write;
+
+/// Test that SemanticSimilarity can be loaded from config file
+#[test]
+fn test_semantic_similarity_loads_from_config() {
+    let config_content = r#"
+api_url = "http://localhost:11434/v1/chat/completions"
+model = "test-model"
+system_prompt = "Test system"
+user_prompt = "Test user"
+
+[guardrails.output]
+type = "semantic_similarity"
+api_url = "http://localhost:11434/api/embed"
+model = "nomic-embed-text"
+timeout_secs = 30
+min_similarity = 0.6
+"#;
+
+    let mut temp_file = tempfile::Builder::new().suffix(".toml").tempfile().unwrap();
+    temp_file.write_all(config_content.as_bytes()).unwrap();
+    temp_file.flush().unwrap();
+
+    let config = load_config_file(temp_file.path()).unwrap();
+    let output = config.guardrails.unwrap().output.unwrap();
+    output.validate().unwrap();
+    match output {
+        GuardrailProviderConfig::SemanticSimilarity {
+            api_url,
+            min_similarity,
+            chunk_chars,
+            min_chunk_chars,
+            severity_threshold,
+            ..
+        } => {
+            assert_eq!(api_url, "http://localhost:11434/api/embed");
+            assert_eq!(min_similarity, 0.6);
+            assert_eq!(chunk_chars, 500);
+            assert_eq!(min_chunk_chars, 20);
+            assert_eq!(severity_threshold, Severity::High);
+        }
+        other => panic!("Expected SemanticSimilarity config, got {other:?}"),
+    }
+
+    let builder = || {
+        GuardrailConfigBuilder::semantic_similarity(
+            "http://localhost:11434/v1/embeddings",
+            "nomic-embed-text",
+        )
+    };
+    let err = builder().min_similarity(1.5).build().unwrap_err();
+    assert!(err.to_string().contains("min_similarity 1.5"), "{err}");
+    let err = builder().chunk_chars(10, 20).build().unwrap_err();
+    assert!(err.to_string().contains("min_chunk_chars (20)"), "{err}");
+}
+
+#[tokio::test]
+async fn test_low_similarity_chunks_reported() {
+    let mut server = Server::new_async().await;
+    // One source chunk, then two response chunks: the second is unrelated
+    let embeddings = server
+        .mock("POST", "/v1/embeddings")
+        .match_header("authorization", "Bearer embed-key")
+        .match_body(Matcher::PartialJson(json!({
+            "model": "nomic-embed-text",
+            "input": [
+                "The lease starts in January at EUR 4,500 per month.",
+                "Rent is EUR 4,500 per month.",
+                "The landlord also owns a vineyard in Tuscany."
+            ]
+        })))
+        .with_status(200)
+        .with_body(
+            json!({"data": [
+                {"index": 0, "embedding": [1.0, 0.0, 0.0]},
+                {"index": 1, "embedding": [0.9, 0.1, 0.0]},
+                {"index": 2, "embedding": [0.1, 0.0, 1.0]}
+            ]})
+            .to_string(),
+        )
+        .expect(2)
+        .create_async()
+        .await;
+
+    let config = |severity: Severity| {
+        GuardrailConfigBuilder::semantic_similarity(
+            format!("{}/v1/embeddings", server.url()),
+            "nomic-embed-text",
+        )
+        .chunk_chars(40, 10)
+        .severity_threshold(severity)
+        .api_key("embed-key")
+        .build()
+        .unwrap()
+    };
+    let context = OutputContext::new(
+        "Test system",
+        "The lease starts in January at EUR 4,500 per month.",
+        None,
+    );
+    let response = "Rent is EUR 4,500 per month. The landlord also owns a vineyard in Tuscany.";
+
+    // Findings are warnings by default
+    let provider = create_guardrail_provider(&config(Severity::High)).unwrap();
+    let result = provider.validate_output(response, &context).await.unwrap();
+    assert!(result.passed);
+    assert_eq!(result.warnings.len(), 1);
+    assert_eq!(result.warnings[0].id(), "semantic.LOW_SIMILARITY");
+    assert_eq!(
+        result.warnings[0].location.as_deref(),
+        Some("Line 1, column 30")
+    );
+    match result.provider_specific {
+        Some(ProviderSpecificResult::SemanticSimilarity(similarity)) => {
+            assert_eq!(similarity.source_chunks, 1);
+            assert_eq!(similarity.chunks.len(), 2);
+            assert!(similarity.chunks[0].similarity > 0.9);
+            assert!(similarity.min_similarity < 0.2);
+        }
+        other => panic!("Expected SemanticSimilarity result, got {other:?}"),
+    }
+
+    let provider = create_guardrail_provider(&config(Severity::Medium)).unwrap();
+    let result = provider.validate_output(response, &context).await.unwrap();
+    assert!(!result.passed);
+    assert!(result.violations[0].message.contains("vineyard"));
+    embeddings.assert_async().await;
+}
+
+#[tokio::test]
+async fn test_ollama_embed_response() {
+    let mut server = Server::new_async().await;
+    let embed = server
+        .mock("POST", "/api/embed")
+        .with_status(200)
+        .with_body(
+            json!({"model": "nomic-embed-text", "embeddings": [[0.0, 1.0], [0.0, 2.0]]})
+                .to_string(),
+        )
+        .expect(1)
+        .create_async()
+        .await;
+
+    let config = GuardrailConfigBuilder::semantic_similarity(
+        format!("{}/api/embed", server.url()),
+        "nomic-embed-text",
+    )
+    .severity_threshold(Severity::Medium)
+    .build()
+    .unwrap();
+    let provider = create_guardrail_provider(&config).unwrap();
+    let context = OutputContext::new("Test system", "Summarize the quarterly report.", None);
+    let result = provider
+        .validate_output("The quarterly report shows growth.", &context)
+        .await
+        .unwrap();
+    assert!(result.passed);
+    assert!(result.warnings.is_empty());
+    embed.assert_async().await;
+
+    // Input guardrails have no source to compare against
+    assert!(provider.validate("Any input").await.unwrap().passed);
+}
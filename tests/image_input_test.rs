@@ -0,0 +1,132 @@
+//! Integration tests for image input (`--image-file`)
+//!
+//! These tests verify that vision mode sends the image as an OpenAI
+//! `image_url` content part, that unsupported formats are refused before any
+//! LLM call, and that image settings load from config files. OCR mode needs
+//! Docling or Tesseract and is not exercised here.
+
+mod common;
+
+use fortified_llm_client::{
+    config_builder::ConfigBuilder, evaluate, load_config_file, CliError, EvaluationConfig,
+    ImageMode, Provider,
+};
+use mockito::{Matcher, Server};
+use serde_json::json;
+use std::io::Write;
+
+/// 1x1 transparent GIF
+const PIXEL_GIF: &[u8] = b"GIF89a\x01\x00\x01\x00\x80\x00\x00\x00\x00\x00\xff\xff\xff\x21\xf9\x04\x01\x00\x00\x00\x00\x2c\x00\x00\x00\x00\x01\x00\x01\x00\x00\x02\x02\x44\x01\x00\x3b";
+
+fn image_file(bytes: &[u8], suffix: &str) -> tempfile::NamedTempFile {
+    let mut file = tempfile::Builder::new().suffix(suffix).tempfile().unwrap();
+    file.write_all(bytes).unwrap();
+    file.flush().unwrap();
+    file
+}
+
+fn config(server_url: &str, image: &tempfile::NamedTempFile) -> EvaluationConfig {
+    common::base_builder(format!("{server_url}/v1/chat/completions"))
+        .user_prompt("What is in this image?")
+        .image_input(image.path())
+        .image_mode(ImageMode::Vision)
+        .build()
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_vision_image_sent_as_content_part() {
+    let mut server = Server::new_async().await;
+    let data_url =
+        "data:image/gif;base64,R0lGODlhAQABAIAAAAAAAP///yH5BAEAAAAALAAAAAABAAEAAAICRAEAOw==";
+    let llm = server
+        .mock("POST", "/v1/chat/completions")
+        .match_body(Matcher::PartialJson(json!({
+            "messages": [
+                {"role": "system", "content": "Test system"},
+                {"role": "user", "content": [
+                    {"type": "text", "text": "What is in this image?"},
+                    {"type": "image_url", "image_url": {"url": data_url}}
+                ]}
+            ]
+        })))
+        .with_status(200)
+        .with_body(
+            json!({"choices": [{"message": {"role": "assistant", "content": "A pixel"}}]})
+                .to_string(),
+        )
+        .expect(1)
+        .create_async()
+        .await;
+
+    let image = image_file(PIXEL_GIF, ".gif");
+    let output = evaluate(config(&server.url(), &image)).await.unwrap();
+    assert_eq!(output.status, "success");
+    assert_eq!(
+        output.metadata.image_input,
+        Some(image.path().display().to_string())
+    );
+    assert_eq!(output.metadata.image_mode, Some(ImageMode::Vision));
+    assert_eq!(
+        output.metadata.user_prompt_text.as_deref(),
+        Some("What is in this image?")
+    );
+    llm.assert_async().await;
+}
+
+#[tokio::test]
+async fn test_unsupported_image_refused() {
+    let mut server = Server::new_async().await;
+    let llm = server
+        .mock("POST", "/v1/chat/completions")
+        .expect(0)
+        .create_async()
+        .await;
+
+    let image = image_file(b"<svg xmlns=\"http://www.w3.org/2000/svg\"/>", ".svg");
+    let err = match evaluate(config(&server.url(), &image)).await {
+        Err(e) => e,
+        Ok(output) => panic!("Expected an image error, got status {}", output.status),
+    };
+    assert!(matches!(err, CliError::ImageProcessingFailed(_)), "{err}");
+    assert_eq!(err.exit_code(), 14);
+    llm.assert_async().await;
+}
+
+#[test]
+fn test_image_from_config_file() {
+    let config_content = r#"
+api_url = "http://localhost:11434/v1/chat/completions"
+model = "test-model"
+system_prompt = "Test system"
+image_file = "scan.png"
+image_mode = "vision"
+"#;
+
+    let mut temp_file = tempfile::Builder::new().suffix(".toml").tempfile().unwrap();
+    temp_file.write_all(config_content.as_bytes()).unwrap();
+    temp_file.flush().unwrap();
+
+    // No user prompt needed with an image
+    let file_config = load_config_file(temp_file.path()).unwrap();
+    let config = ConfigBuilder::new()
+        .merge_file_config(&file_config)
+        .build()
+        .unwrap();
+    assert_eq!(
+        config.image_input.as_deref(),
+        Some(std::path::Path::new("scan.png"))
+    );
+    assert_eq!(config.image_mode, ImageMode::Vision);
+    assert_eq!(config.user_prompt, "");
+
+    let err = ConfigBuilder::new()
+        .merge_file_config(&file_config)
+        .provider(Provider::Anthropic)
+        .build()
+        .unwrap_err();
+    assert!(
+        err.to_string().contains("Vision image input requires"),
+        "{err}"
+    );
+}
@@ -5,7 +5,9 @@
 //! config fields are added but not included in metadata.
 
 use fortified_llm_client::{
-    config_builder::ConfigBuilder, evaluate, guardrails::config::RegexGuardrailConfig,
+    config_builder::ConfigBuilder,
+    evaluate,
+    guardrails::config::{RegexAction, RegexGuardrailConfig},
     GuardrailProviderConfig, Provider, ResponseFormat, Severity,
 };
 use mockito::Server;
@@ -70,6 +72,8 @@ async fn test_metadata_contains_all_config_fields_success() {
     assert_eq!(result.metadata.max_tokens, Some(1000));
     assert_eq!(result.metadata.timeout_secs, 60);
     assert_eq!(result.metadata.context_limit, Some(128000));
+    assert!(result.metadata.context_usage_ratio.unwrap() < 0.1);
+    assert!(result.metadata.warnings.is_empty());
     assert_eq!(result.metadata.response_format, None);
     assert!(result.metadata.validate_tokens);
 
@@ -117,6 +121,9 @@ async fn test_metadata_with_input_guardrails() {
         max_length_bytes: 100000,
         patterns_file: None,
         severity_threshold: Severity::Medium,
+        builtin_patterns: Vec::new(),
+        action: RegexAction::Block,
+        preview_chars: None,
     });
 
     let config = ConfigBuilder::new()
@@ -164,6 +171,9 @@ async fn test_metadata_with_output_guardrails() {
         max_length_bytes: 100000,
         patterns_file: None,
         severity_threshold: Severity::Medium,
+        builtin_patterns: Vec::new(),
+        action: RegexAction::Block,
+        preview_chars: None,
     });
 
     let config = ConfigBuilder::new()
@@ -316,11 +326,15 @@ async fn test_metadata_with_pdf_input() {
     let metadata = Metadata {
         model: "test".to_string(),
         tokens_estimated: 100,
+        tokens_prompt_actual: None,
+        tokens_completion_actual: None,
+        context_usage_ratio: None,
         latency_ms: 200,
         timestamp: "2025-01-01T00:00:00Z".to_string(),
         api_url: "http://test".to_string(),
         provider: None,
         temperature: 0.7,
+        top_p: None,
         max_tokens: Some(1000),
         seed: None,
         timeout_secs: 30,
@@ -332,8 +346,34 @@ async fn test_metadata_with_pdf_input() {
         user_prompt_text: None, // PDF replaces user prompt
         user_prompt_file: None,
         pdf_input: Some("/path/to/file.pdf".to_string()),
+        user_prompt_prefix: None,
+        user_prompt_suffix: None,
+        spotlighting: None,
+        tags: Vec::new(),
         input_guardrails_enabled: None,
         output_guardrails_enabled: None,
+        violation_rules: Vec::new(),
+        violation_previews: Vec::new(),
+        redactions: Vec::new(),
+        guardrail_results: Vec::new(),
+        guardrail_explanations: Default::default(),
+        escalation: None,
+        url_input: None,
+        system_prompt_url: None,
+        prompt_pins: Vec::new(),
+        image_input: None,
+        image_mode: None,
+        chunking: None,
+        guard_tokens: Vec::new(),
+        quality_score: None,
+        response_chunks_aggregated: None,
+        upstream: None,
+        model_echo: None,
+        cache_hit: None,
+        stage_latency: Vec::new(),
+        warnings: Vec::new(),
+        config_warnings: Vec::new(),
+        field_filter: None,
     };
 
     // Verify pdf_input field exists and can be set
@@ -472,12 +512,16 @@ fn test_metadata_struct_completeness_compile_check() {
         // Execution results
         model: String::new(),
         tokens_estimated: 0,
+        tokens_prompt_actual: None,
+        tokens_completion_actual: None,
+        context_usage_ratio: None,
         latency_ms: 0,
         timestamp: String::new(),
         // Input configuration
         api_url: String::new(),
         provider: None,
         temperature: 0.0,
+        top_p: None,
         max_tokens: None,
         seed: None,
         timeout_secs: 0,
@@ -490,11 +534,72 @@ fn test_metadata_struct_completeness_compile_check() {
         user_prompt_text: None,
         user_prompt_file: None,
         pdf_input: None,
+        url_input: None,
+        system_prompt_url: None,
+        prompt_pins: Vec::new(),
+        image_input: None,
+        image_mode: None,
+        chunking: None,
+        guard_tokens: Vec::new(),
+        user_prompt_prefix: None,
+        user_prompt_suffix: None,
+        spotlighting: None,
+        tags: Vec::new(),
         // Guardrails
         input_guardrails_enabled: None,
         output_guardrails_enabled: None,
+        violation_rules: Vec::new(),
+        violation_previews: Vec::new(),
+        redactions: Vec::new(),
+        guardrail_results: Vec::new(),
+        guardrail_explanations: Default::default(),
+        escalation: None,
+        quality_score: None,
+        // Transport
+        response_chunks_aggregated: None,
+        upstream: None,
+        model_echo: None,
+        cache_hit: None,
+        stage_latency: Vec::new(),
+        warnings: Vec::new(),
+        config_warnings: Vec::new(),
+        field_filter: None,
     };
 
     // If this compiles, all expected fields are present
     // If a field is missing, this will fail to compile
 }
+
+/// Near-limit requests succeed but carry a structured headroom warning
+#[tokio::test]
+async fn test_metadata_context_headroom_warning() {
+    let mut server = Server::new_async().await;
+    let mock = server
+        .mock("POST", "/v1/chat/completions")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(r#"{"choices": [{"message": {"role": "assistant", "content": "ok"}}]}"#)
+        .create_async()
+        .await;
+
+    // 100-token response buffer + prompt + 10% safety margin in a 120-token window
+    let config = ConfigBuilder::new()
+        .api_url(server.url() + "/v1/chat/completions")
+        .model("test-model")
+        .system_prompt("Sys")
+        .user_prompt("Hi")
+        .provider(Provider::OpenAI)
+        .max_tokens(100)
+        .validate_tokens(true)
+        .context_limit(120)
+        .build()
+        .unwrap();
+
+    let result = evaluate(config).await.unwrap();
+    mock.assert_async().await;
+
+    assert_eq!(result.status, "success");
+    assert!(result.metadata.context_usage_ratio.unwrap() >= 0.9);
+    assert_eq!(result.metadata.warnings.len(), 1);
+    assert_eq!(result.metadata.warnings[0].code, "CONTEXT_HEADROOM_LOW");
+}
@@ -0,0 +1,136 @@
+//! Integration tests for `[metadata_fields]` output filtering
+//!
+//! These tests verify that excluded fields and groups are left out of the
+//! serialized output while the `Metadata` struct stays complete, that every
+//! serialized field belongs to a group, and that the prompt audit log keeps
+//! the unfiltered metadata.
+
+mod common;
+
+use fortified_llm_client::{
+    config_builder::ConfigBuilder, evaluate, AuditEventKind, BlobStore, EvaluationConfig,
+    MemoryAuditLog, MetadataFields, PromptAuditor, METADATA_FIELD_GROUPS,
+};
+use mockito::{Server, ServerGuard};
+use std::sync::Arc;
+use tempfile::TempDir;
+
+async fn llm_mock(server: &mut ServerGuard) -> mockito::Mock {
+    server
+        .mock("POST", "/v1/chat/completions")
+        .with_status(200)
+        .with_body(
+            r#"{
+                "choices": [{"message": {"role": "assistant", "content": "Paris"}}],
+                "usage": {"prompt_tokens": 20, "completion_tokens": 1, "total_tokens": 21}
+            }"#,
+        )
+        .create_async()
+        .await
+}
+
+fn builder(server_url: &str) -> ConfigBuilder {
+    common::base_builder(format!("{server_url}/v1/chat/completions"))
+        .user_prompt("What is the capital of France?")
+        .tag("suite=metadata")
+}
+
+fn config(server_url: &str, fields: MetadataFields) -> EvaluationConfig {
+    builder(server_url).metadata_fields(fields).build().unwrap()
+}
+
+#[tokio::test]
+async fn test_excluded_fields_left_out_of_output() {
+    let mut server = Server::new_async().await;
+    let _llm = llm_mock(&mut server).await;
+
+    let fields = MetadataFields::default().exclude(["api_url", "prompts"]);
+    let output = evaluate(config(&server.url(), fields)).await.unwrap();
+    let json = serde_json::to_value(&output).unwrap();
+    let metadata = json["metadata"].as_object().unwrap();
+
+    assert_eq!(json["status"], "success");
+    assert_eq!(metadata["model"], "test-model");
+    assert_eq!(metadata["provider"], "OpenAI");
+    for field in ["api_url", "system_prompt_text", "user_prompt_text"] {
+        assert!(!metadata.contains_key(field), "{field} not filtered");
+    }
+    // The struct itself is not filtered
+    assert!(output.metadata.api_url.ends_with("/v1/chat/completions"));
+    assert!(output.metadata.user_prompt_text.is_some());
+
+    let fields = MetadataFields::default().include(["model", "tokens"]);
+    let output = evaluate(config(&server.url(), fields)).await.unwrap();
+    let json = serde_json::to_value(&output).unwrap();
+    let mut keys: Vec<&String> = json["metadata"].as_object().unwrap().keys().collect();
+    keys.sort();
+    assert_eq!(
+        keys,
+        [
+            "model",
+            "tokens_completion_actual",
+            "tokens_estimated",
+            "tokens_prompt_actual"
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_serialized_fields_all_belong_to_a_group() {
+    let mut server = Server::new_async().await;
+    let _llm = llm_mock(&mut server).await;
+
+    let output = evaluate(builder(&server.url()).seed(7).build().unwrap())
+        .await
+        .unwrap();
+    let json = serde_json::to_value(&output.metadata).unwrap();
+    for field in json.as_object().unwrap().keys() {
+        assert!(
+            METADATA_FIELD_GROUPS
+                .iter()
+                .any(|(_, fields)| fields.contains(&field.as_str())),
+            "metadata field '{field}' is missing from METADATA_FIELD_GROUPS"
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_audit_log_keeps_full_metadata() {
+    let mut server = Server::new_async().await;
+    let _llm = llm_mock(&mut server).await;
+
+    let dir = TempDir::new().unwrap();
+    let log = Arc::new(MemoryAuditLog::new());
+    let auditor = Arc::new(PromptAuditor::new(
+        log.clone(),
+        BlobStore::new(dir.path().join("blobs")),
+    ));
+    let config = builder(&server.url())
+        .metadata_fields(MetadataFields::default().exclude(["endpoint", "prompts"]))
+        .prompt_audit(auditor.clone())
+        .build()
+        .unwrap();
+    let output = evaluate(config).await.unwrap();
+    assert!(serde_json::to_value(&output).unwrap()["metadata"]
+        .get("api_url")
+        .is_none());
+
+    let events = log.events();
+    let Some(AuditEventKind::EvaluationCompleted { status, metadata }) =
+        events.last().map(|e| &e.kind)
+    else {
+        panic!("no evaluation_completed event: {events:?}");
+    };
+    assert_eq!(status, "success");
+    assert_eq!(metadata["api_url"], output.metadata.api_url.as_str());
+    assert_eq!(metadata["provider"], "OpenAI");
+
+    // Prompt texts are blob references, like in prompt_submitted events
+    let reference = metadata["user_prompt_text"].as_str().unwrap();
+    let stored = auditor.blobs().get(reference).unwrap().unwrap();
+    assert_eq!(stored, b"What is the capital of France?");
+    assert_eq!(
+        events.last().unwrap().kind.blob_refs(),
+        vec![metadata["system_prompt_text"].as_str().unwrap(), reference]
+    );
+}
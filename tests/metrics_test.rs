@@ -0,0 +1,81 @@
+// Evaluation metrics tests
+//
+// Verifies a shared registry counts requests by outcome, guardrail blocks by
+// rule, tokens and latency, and renders them in the Prometheus text format.
+
+mod common;
+
+use fortified_llm_client::{evaluate, EvaluationConfig, GuardrailConfigBuilder, MetricsRegistry};
+use mockito::Server;
+use std::sync::Arc;
+
+fn base_config(api_url: String, metrics: Arc<MetricsRegistry>) -> EvaluationConfig {
+    common::base_builder(api_url)
+        .user_prompt("What is the capital of France?")
+        .metrics(metrics)
+        .build()
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_registry_counts_requests_and_guardrail_blocks() {
+    let mut server = Server::new_async().await;
+    let mock = server
+        .mock("POST", "/v1/chat/completions")
+        .with_status(200)
+        .with_body(r#"{"choices": [{"message": {"role": "assistant", "content": "Paris."}}]}"#)
+        .expect(2)
+        .create_async()
+        .await;
+    let metrics = Arc::new(MetricsRegistry::new());
+    let config = base_config(server.url() + "/v1/chat/completions", metrics.clone());
+
+    evaluate(config.clone()).await.unwrap();
+    evaluate(config.clone()).await.unwrap();
+    let mut blocked = config;
+    blocked.input_guardrails = Some(
+        GuardrailConfigBuilder::regex()
+            .max_length_bytes(3)
+            .build()
+            .unwrap(),
+    );
+    let output = evaluate(blocked).await.unwrap();
+    assert_eq!(output.status, "error");
+    mock.assert_async().await;
+
+    let snapshot = metrics.snapshot();
+    assert_eq!(snapshot.total_requests(), 3);
+    let count = |status: &str, outcome: &str| {
+        snapshot
+            .requests
+            .iter()
+            .find(|r| r.model == "test-model" && r.status == status && r.outcome == outcome)
+            .map(|r| r.count)
+    };
+    assert_eq!(count("success", "none"), Some(2));
+    assert_eq!(count("error", "INPUT_VALIDATION_FAILED"), Some(1));
+
+    assert_eq!(snapshot.guardrail_blocks.len(), 1);
+    assert_eq!(snapshot.guardrail_blocks[0].direction, "input");
+    assert_eq!(snapshot.guardrail_blocks[0].rule, "regex.MAX_LENGTH");
+    assert!(snapshot.tokens_estimated["test-model"] > 0);
+    assert_eq!(snapshot.latency["test-model"].count, 3);
+    assert_eq!(snapshot.stage_latency["invoke"].count, 2);
+    assert_eq!(snapshot.stage_latency["input_guard"].count, 3);
+}
+
+#[tokio::test]
+async fn test_hard_errors_are_counted() {
+    let metrics = Arc::new(MetricsRegistry::new());
+    let config = base_config(
+        "http://127.0.0.1:1/v1/chat/completions".to_string(),
+        metrics.clone(),
+    );
+    assert!(evaluate(config).await.is_err());
+
+    let text = metrics.render_prometheus();
+    assert!(text.contains(
+        r#"fortified_llm_requests_total{model="test-model",status="error",outcome="HTTP_ERROR"} 1"#
+    ));
+    assert!(text.contains(r#"fortified_llm_request_duration_seconds_count{model="test-model"} 1"#));
+}
@@ -0,0 +1,80 @@
+// Mock provider tests
+//
+// Verifies that embedders can run full evaluations offline against fixtures
+// files, including scripted failures, without any HTTP server.
+
+use fortified_llm_client::{config_builder::ConfigBuilder, evaluate, Provider};
+use predicates::prelude::*;
+use std::io::Write;
+use tempfile::NamedTempFile;
+
+fn fixtures(content: &str, suffix: &str) -> NamedTempFile {
+    let mut file = tempfile::Builder::new().suffix(suffix).tempfile().unwrap();
+    file.write_all(content.as_bytes()).unwrap();
+    file
+}
+
+#[tokio::test]
+async fn test_evaluate_with_mock_fixtures() {
+    let file = fixtures(
+        r#"
+[[responses]]
+when = "(?i)refund"
+content = "Refunds take 5 days."
+
+[[responses]]
+content = "I can only help with refunds."
+"#,
+        ".toml",
+    );
+
+    let config = ConfigBuilder::new()
+        .api_url(file.path().display().to_string())
+        .provider(Provider::Mock)
+        .model("mock-model")
+        .system_prompt("You are a support bot.")
+        .user_prompt("How long does a REFUND take?")
+        .build()
+        .unwrap();
+    let output = evaluate(config).await.unwrap();
+
+    assert_eq!(output.status, "success");
+    assert_eq!(
+        output.response,
+        Some(serde_json::Value::String(
+            "Refunds take 5 days.".to_string()
+        ))
+    );
+}
+
+#[tokio::test]
+async fn test_mock_error_surfaces_like_http_error() {
+    let file = fixtures(
+        r#"{"responses": [{"error": {"status": 500, "message": "boom"}}]}"#,
+        ".json",
+    );
+
+    let config = ConfigBuilder::new()
+        .api_url(format!("mock://{}", file.path().display()))
+        .model("mock-model")
+        .system_prompt("s")
+        .user_prompt("u")
+        .build()
+        .unwrap();
+    let Err(err) = evaluate(config).await else {
+        panic!("Expected mock error");
+    };
+
+    assert!(err.to_string().contains("HTTP 500"), "{err}");
+    assert!(err.to_string().contains("boom"));
+}
+
+#[test]
+fn test_cli_mock_echo() {
+    assert_cmd::cargo::cargo_bin_cmd!("fortified-llm-client")
+        .args(["--api-url", "mock://", "--model", "mock-model"])
+        .args(["--system-text", "Echo", "--user-text", "ping"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(r#""response": "ping""#));
+}
@@ -0,0 +1,164 @@
+// Model discovery tests
+//
+// Verifies that model lists are read from Ollama /api/tags, OpenAI-compatible
+// /models and Anthropic /v1/models, cross-referenced with the model registry,
+// and printed by the models subcommand.
+
+use fortified_llm_client::{discover_models, CliError, ContextProber, Provider};
+use mockito::{Matcher, Server};
+use predicates::prelude::*;
+
+#[tokio::test]
+async fn test_ollama_tags_cross_referenced_with_registry() {
+    let mut server = Server::new_async().await;
+    let tags = server
+        .mock("GET", "/api/tags")
+        .with_status(200)
+        .with_body(
+            r#"{"models": [
+                {"name": "my-finetune:latest", "size": 1200000000},
+                {"name": "llama3.1:8b", "size": 4661224676}
+            ]}"#,
+        )
+        .create_async()
+        .await;
+
+    let listing = discover_models(
+        &format!("{}/api/generate", server.url()),
+        Some(Provider::Ollama),
+        None,
+        5,
+        None,
+    )
+    .await
+    .unwrap();
+
+    tags.assert_async().await;
+    let ids: Vec<&str> = listing.models.iter().map(|m| m.id.as_str()).collect();
+    assert_eq!(ids, ["llama3.1:8b", "my-finetune:latest"]);
+    assert_eq!(listing.models[0].context_source, Some("registry"));
+    assert!(listing.models[0].context_window.is_some());
+    assert_eq!(listing.models[1].context_window, None);
+    assert_eq!(listing.models[1].size_bytes, Some(1_200_000_000));
+}
+
+#[tokio::test]
+async fn test_openai_models_sends_key_and_probes_unknown_models() {
+    let mut server = Server::new_async().await;
+    let models = server
+        .mock("GET", "/v1/models")
+        .match_header("authorization", "Bearer sk-test")
+        .with_status(200)
+        .with_body(r#"{"object": "list", "data": [{"id": "gpt-4o"}, {"id": "in-house"}]}"#)
+        .create_async()
+        .await;
+    let probe = server
+        .mock("GET", "/v1/models/in-house")
+        .with_status(200)
+        .with_body(r#"{"id": "in-house", "context_length": 16384}"#)
+        .create_async()
+        .await;
+
+    let prober = ContextProber::new();
+    let listing = discover_models(
+        &format!("{}/v1/chat/completions", server.url()),
+        Some(Provider::OpenAI),
+        Some("sk-test"),
+        5,
+        Some(&prober),
+    )
+    .await
+    .unwrap();
+
+    models.assert_async().await;
+    probe.assert_async().await;
+    assert_eq!(listing.models[0].registry_name, Some("gpt-4o"));
+    let in_house = &listing.models[1];
+    assert_eq!(in_house.id, "in-house");
+    assert_eq!(in_house.context_window, Some(16384));
+    assert_eq!(in_house.context_source, Some("probe"));
+}
+
+#[tokio::test]
+async fn test_anthropic_models_uses_api_key_header() {
+    let mut server = Server::new_async().await;
+    let models = server
+        .mock("GET", "/v1/models")
+        .match_query(Matcher::UrlEncoded("limit".into(), "1000".into()))
+        .match_header("x-api-key", "sk-ant-test")
+        .match_header("anthropic-version", Matcher::Any)
+        .with_status(200)
+        .with_body(r#"{"data": [{"id": "claude-3-5-sonnet-20241022", "type": "model"}]}"#)
+        .create_async()
+        .await;
+
+    let listing = discover_models(
+        &format!("{}/v1/messages", server.url()),
+        Some(Provider::Anthropic),
+        Some("sk-ant-test"),
+        5,
+        None,
+    )
+    .await
+    .unwrap();
+
+    models.assert_async().await;
+    assert_eq!(listing.provider, "Anthropic");
+    assert_eq!(listing.models.len(), 1);
+}
+
+#[tokio::test]
+async fn test_unauthorized_model_list_is_authentication_error() {
+    let mut server = Server::new_async().await;
+    server
+        .mock("GET", "/v1/models")
+        .with_status(401)
+        .with_body(r#"{"error": "invalid key"}"#)
+        .create_async()
+        .await;
+
+    let result = discover_models(
+        &format!("{}/v1/chat/completions", server.url()),
+        Some(Provider::OpenAI),
+        Some("wrong"),
+        5,
+        None,
+    )
+    .await;
+
+    assert!(matches!(result, Err(CliError::AuthenticationFailed(_))));
+}
+
+#[tokio::test]
+async fn test_mock_endpoint_has_no_model_list() {
+    let result = discover_models("mock://", Some(Provider::Mock), None, 5, None).await;
+    assert!(matches!(result, Err(CliError::InvalidArguments(_))));
+}
+
+#[test]
+fn test_cli_models_prints_table_and_json() {
+    let mut server = Server::new();
+    server
+        .mock("GET", "/api/tags")
+        .with_status(200)
+        .with_body(r#"{"models": [{"name": "llama3.1:8b", "size": 4661224676}]}"#)
+        .expect(2)
+        .create();
+    let api_url = format!("{}/api/generate", server.url());
+
+    assert_cmd::cargo::cargo_bin_cmd!("fortified-llm-client")
+        .args(["models", "--api-url", &api_url, "--provider", "ollama"])
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with("MODEL"))
+        .stdout(predicate::str::contains("llama3.1:8b"))
+        .stdout(predicate::str::contains("4.7GB"));
+
+    assert_cmd::cargo::cargo_bin_cmd!("fortified-llm-client")
+        .args(["models", "--api-url", &api_url, "--provider", "ollama"])
+        .args(["--format", "json"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("\"id\": \"llama3.1:8b\""))
+        .stdout(predicate::str::contains("\"context_source\": \"registry\""));
+}
@@ -0,0 +1,64 @@
+// Prompt audit integration tests
+//
+// Verifies that evaluations record submitted prompts with content-addressed
+// payload storage, so repeated documents are stored once.
+
+use fortified_llm_client::{
+    config_builder::ConfigBuilder, evaluate, AuditSink, BlobStore, EvaluationConfig, JsonlAuditLog,
+    PromptAuditor, Provider,
+};
+use mockito::Server;
+use std::sync::Arc;
+use tempfile::TempDir;
+
+fn audited_config(api_url: String, auditor: Arc<PromptAuditor>) -> EvaluationConfig {
+    ConfigBuilder::new()
+        .api_url(api_url)
+        .model("test-model")
+        .system_prompt("Test system")
+        .user_prompt("Summarize this long document")
+        .provider(Provider::OpenAI)
+        .max_tokens(100)
+        .timeout_secs(5)
+        .prompt_audit(auditor)
+        .build()
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_repeated_prompts_are_stored_once() {
+    let mut server = Server::new_async().await;
+    let mock = server
+        .mock("POST", "/v1/chat/completions")
+        .with_status(200)
+        .with_body(r#"{"choices": [{"message": {"role": "assistant", "content": "ok"}}]}"#)
+        .expect(3)
+        .create_async()
+        .await;
+
+    let dir = TempDir::new().unwrap();
+    let log = JsonlAuditLog::new(dir.path().join("audit.jsonl"));
+    let blobs = BlobStore::new(dir.path().join("audit.blobs"));
+    let auditor = Arc::new(PromptAuditor::new(Arc::new(log.clone()), blobs.clone()));
+
+    for _ in 0..3 {
+        let config = audited_config(server.url() + "/v1/chat/completions", auditor.clone());
+        evaluate(config).await.unwrap();
+    }
+
+    let events = log
+        .events_since(chrono::Utc::now() - chrono::Duration::hours(1))
+        .unwrap();
+    assert_eq!(events.len(), 3);
+    assert!(events.iter().all(|e| e.kind.name() == "prompt_submitted"));
+
+    // System + user prompt, each stored once
+    assert_eq!(blobs.list().unwrap().len(), 2);
+    let user_ref = events[0].kind.blob_refs()[1];
+    assert_eq!(
+        blobs.get(user_ref).unwrap().unwrap(),
+        b"Summarize this long document"
+    );
+
+    mock.assert_async().await;
+}
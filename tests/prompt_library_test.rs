@@ -0,0 +1,349 @@
+// Prompt library tests
+//
+// Verifies that prompt files with TOML front matter are listed by name and
+// version, resolved to their latest version, linted for undeclared and unused
+// variables and duplicate versions, and exposed by the prompts subcommand.
+// Config files reference library prompts by name and version, the resolved
+// hash is recorded in the metadata and pinned hashes catch prompt drift.
+
+use fortified_llm_client::{
+    audit::blob_ref, config::load_config_file, config_builder::ConfigBuilder, CliError,
+    PromptLibrary,
+};
+use predicates::prelude::*;
+use std::fs;
+use tempfile::TempDir;
+
+fn library(files: &[(&str, &str)]) -> TempDir {
+    let dir = TempDir::new().unwrap();
+    for (name, content) in files {
+        fs::write(dir.path().join(name), content).unwrap();
+    }
+    dir
+}
+
+const SUMMARIZE_V1: &str = "+++
+name = \"summarize\"
+version = \"1.9\"
+variables = [\"document\"]
+models = [\"gpt-4o\"]
++++
+Summarize this:
+
+{{document}}
+";
+
+const SUMMARIZE_V2: &str = "+++
+name = \"summarize\"
+version = \"1.10\"
+description = \"Summaries tailored to a reader\"
+variables = [\"document\", \"audience\"]
++++
+Summarize this for {{ audience }}:
+
+{{document}}
+";
+
+#[test]
+fn test_list_sorts_by_name_and_version() {
+    let dir = library(&[
+        ("summarize-v2.md", SUMMARIZE_V2),
+        ("summarize-v1.md", SUMMARIZE_V1),
+        ("classify.txt", "Classify the ticket."),
+        ("notes.json", "{}"),
+    ]);
+
+    let prompts = PromptLibrary::new(dir.path()).list().unwrap();
+
+    let listed: Vec<(&str, Option<&str>)> = prompts
+        .iter()
+        .map(|p| (p.metadata.name.as_str(), p.metadata.version.as_deref()))
+        .collect();
+    assert_eq!(
+        listed,
+        [
+            ("classify", None),
+            ("summarize", Some("1.9")),
+            ("summarize", Some("1.10")),
+        ]
+    );
+}
+
+#[test]
+fn test_get_resolves_latest_or_pinned_version() {
+    let dir = library(&[
+        ("summarize-v1.md", SUMMARIZE_V1),
+        ("summarize-v2.md", SUMMARIZE_V2),
+    ]);
+    let prompts = PromptLibrary::new(dir.path());
+
+    let latest = prompts.get("summarize", None).unwrap();
+    assert_eq!(latest.metadata.version.as_deref(), Some("1.10"));
+    assert!(latest.text.starts_with("Summarize this for"));
+
+    let pinned = prompts.get("summarize", Some("1.9")).unwrap();
+    assert_eq!(pinned.metadata.models, ["gpt-4o"]);
+
+    let err = prompts.get("summarize", Some("3.0")).unwrap_err();
+    assert!(matches!(err, CliError::InvalidArguments(_)));
+    assert!(err.to_string().contains("available: 1.9, 1.10"), "{err}");
+    assert!(prompts.get("translate", None).is_err());
+}
+
+#[test]
+fn test_lint_reports_errors_and_warnings() {
+    let dir = library(&[
+        ("summarize-v1.md", SUMMARIZE_V1),
+        ("summarize-copy.md", SUMMARIZE_V1),
+        (
+            "greet.md",
+            "+++\nname = \"greet\"\nvariables = [\"name\", \"tone\"]\n+++\nHello {{name}} from {{team}}\n",
+        ),
+        ("broken.md", "+++\nname = \"broken\"\nversion = 1\n+++\nText\n"),
+        ("plain.txt", "No front matter"),
+    ]);
+
+    let report = PromptLibrary::new(dir.path()).lint().unwrap();
+
+    assert_eq!(report.prompts_checked, 5);
+    let messages: Vec<String> = report
+        .issues
+        .iter()
+        .map(|i| {
+            format!(
+                "{} {}: {}",
+                i.level,
+                i.file.file_name().unwrap().to_string_lossy(),
+                i.message
+            )
+        })
+        .collect();
+    let has = |expected: &str| messages.iter().any(|m| m.starts_with(expected));
+    assert!(has("error broken.md: invalid front matter"), "{messages:?}");
+    assert!(
+        has("error summarize-v1.md: duplicate prompt 'summarize' version 1.9"),
+        "{messages:?}"
+    );
+    assert!(
+        has("error greet.md: placeholder '{{team}}' is not listed"),
+        "{messages:?}"
+    );
+    assert!(
+        has("warning greet.md: variable 'tone' is not used"),
+        "{messages:?}"
+    );
+    assert!(has("warning greet.md: no version"), "{messages:?}");
+    assert!(has("warning plain.txt: no front matter"), "{messages:?}");
+    assert_eq!(report.errors, 3);
+    assert_eq!(report.warnings, 3);
+}
+
+#[test]
+fn test_cli_prompts_list_show_and_lint() {
+    let dir = library(&[
+        ("summarize-v1.md", SUMMARIZE_V1),
+        ("summarize-v2.md", SUMMARIZE_V2),
+    ]);
+    let dir_arg = dir.path().to_str().unwrap();
+
+    assert_cmd::cargo::cargo_bin_cmd!("fortified-llm-client")
+        .args(["prompts", "list", "--dir", dir_arg])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("\"version\": \"1.10\""))
+        .stdout(predicate::str::contains("\"hash\": \"sha256:"))
+        .stdout(predicate::str::contains("Summarize this").not());
+
+    assert_cmd::cargo::cargo_bin_cmd!("fortified-llm-client")
+        .args(["prompts", "show", "summarize", "--version", "1.9"])
+        .args(["--dir", dir_arg])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Summarize this:"));
+
+    assert_cmd::cargo::cargo_bin_cmd!("fortified-llm-client")
+        .args(["prompts", "lint", "--dir", dir_arg])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("\"errors\": 0"));
+
+    fs::write(dir.path().join("bad.md"), "Hi {{name}}").unwrap();
+    assert_cmd::cargo::cargo_bin_cmd!("fortified-llm-client")
+        .args(["prompts", "lint", "--dir", dir_arg])
+        .assert()
+        .code(6)
+        .stderr(predicate::str::contains(
+            "placeholder '{{name}}' is not listed in variables",
+        ));
+}
+
+const REVIEWER_V1: &str = "+++
+name = \"reviewer\"
+version = \"1.0\"
++++
+You review pull requests.
+";
+
+const REVIEWER_V2: &str = "+++
+name = \"reviewer\"
+version = \"2.0\"
++++
+You review pull requests strictly.
+";
+
+fn write_ref_config(dir: &TempDir, refs: &str) -> std::path::PathBuf {
+    let path = dir.path().join("config.toml");
+    fs::write(
+        &path,
+        format!(
+            "api_url = \"mock://\"\nmodel = \"mock\"\n{refs}\n[prompts]\ndir = \"{}\"\n",
+            dir.path().display()
+        ),
+    )
+    .unwrap();
+    path
+}
+
+#[test]
+fn test_config_prompt_refs_pin_versions_and_detect_drift() {
+    let dir = library(&[
+        ("reviewer-v1.md", REVIEWER_V1),
+        ("reviewer-v2.md", REVIEWER_V2),
+        ("ticket.txt", "Review this change."),
+    ]);
+    let ticket_hash = blob_ref(b"Review this change.");
+    let config_path = write_ref_config(
+        &dir,
+        &format!(
+            "system_prompt_ref = \"reviewer@1.0\"\nuser_prompt_ref = {{ name = \"ticket\", hash = \"{ticket_hash}\" }}"
+        ),
+    );
+
+    let file_config = load_config_file(&config_path).unwrap();
+    assert_eq!(
+        file_config.system_prompt.as_deref(),
+        Some("You review pull requests.\n")
+    );
+    assert_eq!(
+        file_config.user_prompt.as_deref(),
+        Some("Review this change.")
+    );
+    let config = ConfigBuilder::new()
+        .merge_file_config(&file_config)
+        .build()
+        .unwrap();
+    let pins: Vec<(&str, &str, Option<&str>)> = config
+        .prompt_pins
+        .iter()
+        .map(|p| (p.role, p.name.as_str(), p.version.as_deref()))
+        .collect();
+    assert_eq!(
+        pins,
+        [
+            ("system", "reviewer", Some("1.0")),
+            ("user", "ticket", None)
+        ]
+    );
+    assert_eq!(config.prompt_pins[1].hash, ticket_hash);
+    assert!(config.config_warnings.is_empty());
+
+    // The prompt is edited after its hash was pinned
+    fs::write(
+        dir.path().join("ticket.txt"),
+        "Review this change carefully.",
+    )
+    .unwrap();
+    let file_config = load_config_file(&config_path).unwrap();
+    let config = ConfigBuilder::new()
+        .merge_file_config(&file_config)
+        .build()
+        .unwrap();
+    assert!(config.prompt_pins[1].drifted());
+    assert_eq!(config.config_warnings.len(), 1);
+    assert_eq!(config.config_warnings[0].field, "user_prompt_ref");
+    assert!(
+        config.config_warnings[0]
+            .message
+            .contains(&format!("pinned {ticket_hash}")),
+        "{}",
+        config.config_warnings[0].message
+    );
+
+    // A system prompt given by the caller replaces the referenced one
+    let config = ConfigBuilder::new()
+        .system_prompt("Inline")
+        .merge_file_config(&file_config)
+        .build()
+        .unwrap();
+    assert_eq!(config.prompt_pins.len(), 1);
+    assert_eq!(config.prompt_pins[0].role, "user");
+}
+
+#[test]
+fn test_config_prompt_refs_rejected() {
+    let dir = library(&[
+        ("reviewer-v1.md", REVIEWER_V1),
+        ("summarize-v1.md", SUMMARIZE_V1),
+    ]);
+    for (refs, expected) in [
+        (
+            "system_prompt_ref = \"reviewer@3.0\"",
+            "No version '3.0' of prompt 'reviewer' (available: 1.0)",
+        ),
+        (
+            "system_prompt_ref = \"summarize\"",
+            "declares variables, which system_prompt_ref can't fill in",
+        ),
+        (
+            "system_prompt_ref = \"reviewer\"\nsystem_prompt = \"Inline\"",
+            "cannot specify 'system_prompt_ref' with",
+        ),
+        (
+            "system_prompt = \"Inline\"\nuser_prompt = \"Hi\"\nuser_prompt_ref = \"reviewer\"",
+            "more than one of",
+        ),
+    ] {
+        let config_path = write_ref_config(&dir, refs);
+        let err = load_config_file(&config_path).unwrap_err();
+        assert!(err.to_string().contains(expected), "{refs}: {err}");
+    }
+
+    let path = dir.path().join("no-library.toml");
+    fs::write(
+        &path,
+        "api_url = \"mock://\"\nmodel = \"mock\"\nsystem_prompt_ref = \"reviewer\"\n",
+    )
+    .unwrap();
+    let err = load_config_file(&path).unwrap_err();
+    assert!(err.to_string().contains("[prompts]"), "{err}");
+}
+
+#[test]
+fn test_cli_records_prompt_pins_in_metadata() {
+    let dir = library(&[("reviewer-v2.md", REVIEWER_V2)]);
+    let config_path = write_ref_config(
+        &dir,
+        "system_prompt_ref = { name = \"reviewer\", version = \"2.0\", hash = \"sha256:0000\" }",
+    );
+
+    let output = assert_cmd::cargo::cargo_bin_cmd!("fortified-llm-client")
+        .arg("-c")
+        .arg(&config_path)
+        .args(["--user-text", "Review this change.", "--quiet"])
+        .output()
+        .unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(json["status"], "success", "{json}");
+    let pin = &json["metadata"]["prompt_pins"][0];
+    assert_eq!(pin["role"], "system");
+    assert_eq!(pin["version"], "2.0");
+    assert_eq!(
+        pin["hash"],
+        blob_ref(b"You review pull requests strictly.\n")
+    );
+    assert_eq!(pin["pinned_hash"], "sha256:0000");
+    assert_eq!(
+        json["metadata"]["config_warnings"][0]["field"],
+        "system_prompt_ref"
+    );
+}
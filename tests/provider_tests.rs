@@ -36,11 +36,14 @@ async fn test_mock_provider() {
             system_prompt: "system",
             user_prompt: "user",
             temperature: 0.1,
+            top_p: None,
             max_tokens: Some(1000),
             seed: None,
             api_key: None,
             timeout_secs: 300,
             response_format: None,
+            tools: &[],
+            images: &[],
         })
         .await;
 
@@ -124,3 +127,17 @@ fn test_provider_detection_path_takes_precedence() {
         "Path /v1/chat/completions should override port 11434 detection"
     );
 }
+
+#[test]
+fn test_provider_detection_anthropic() {
+    let url = "https://api.anthropic.com/v1/messages";
+    let provider_type = detect_provider_type(url);
+    assert!(matches!(provider_type, ProviderType::Anthropic));
+}
+
+#[test]
+fn test_provider_detection_bedrock() {
+    let url = "https://bedrock-runtime.us-east-1.amazonaws.com";
+    let provider_type = detect_provider_type(url);
+    assert!(matches!(provider_type, ProviderType::Bedrock));
+}
@@ -0,0 +1,125 @@
+//! Integration tests for client-side rate limiting
+//!
+//! These tests verify that a rate limiter shared across a batch spaces out
+//! requests beyond the burst, that the unused part of a token reservation is
+//! returned once the provider reports its usage, and that `[rate_limit]`
+//! loads and validates from config files.
+
+mod common;
+
+use fortified_llm_client::{
+    evaluate, evaluate_batch, load_config_file, CliError, EvaluationConfig, RateLimiter,
+};
+use mockito::{Server, ServerGuard};
+use serde_json::json;
+use std::{
+    io::Write,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+async fn llm_mock(server: &mut ServerGuard, calls: usize) -> mockito::Mock {
+    server
+        .mock("POST", "/v1/chat/completions")
+        .with_status(200)
+        .with_body(
+            json!({
+                "choices": [{"message": {"role": "assistant", "content": "Paris"}}],
+                "usage": {"prompt_tokens": 20, "completion_tokens": 1, "total_tokens": 21}
+            })
+            .to_string(),
+        )
+        .expect(calls)
+        .create_async()
+        .await
+}
+
+fn limiter(toml: &str) -> Arc<RateLimiter> {
+    Arc::new(RateLimiter::from_config(&toml::from_str(toml).unwrap()).unwrap())
+}
+
+fn config(server_url: &str, limiter: Arc<RateLimiter>) -> EvaluationConfig {
+    common::base_builder(format!("{server_url}/v1/chat/completions"))
+        .user_prompt("What is the capital of France?")
+        .max_tokens(900)
+        .rate_limiter(limiter)
+        .build()
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_shared_limiter_spaces_batch_requests() {
+    let mut server = Server::new_async().await;
+    let llm = llm_mock(&mut server, 3).await;
+
+    // 10 requests/s with a burst of 1: the 2nd and 3rd calls wait ~100ms each
+    let limiter = limiter("[openai]\nrequests_per_minute = 600\nburst_requests = 1");
+    let configs = (0..3)
+        .map(|_| config(&server.url(), limiter.clone()))
+        .collect();
+    let started = Instant::now();
+    let output = evaluate_batch(configs, 3).await;
+    let elapsed = started.elapsed();
+
+    assert!(output.results.iter().all(|o| o.status == "success"));
+    assert!(elapsed >= Duration::from_millis(180), "{elapsed:?}");
+    llm.assert_async().await;
+}
+
+#[tokio::test]
+async fn test_unused_token_reservation_is_returned() {
+    let mut server = Server::new_async().await;
+    let llm = llm_mock(&mut server, 2).await;
+
+    // A call reserves ~910 tokens (prompt + max_tokens) of a 1000-token bucket
+    // refilling at 100 tokens/s. Without the refund of the 21 tokens actually
+    // used, the second call would wait ~8s.
+    let limiter = limiter("[openai]\ntokens_per_minute = 6000\nburst_tokens = 1000");
+    let started = Instant::now();
+    for _ in 0..2 {
+        let output = evaluate(config(&server.url(), limiter.clone()))
+            .await
+            .unwrap();
+        assert_eq!(output.status, "success");
+    }
+    assert!(started.elapsed() < Duration::from_secs(2));
+    llm.assert_async().await;
+}
+
+#[test]
+fn test_rate_limit_loads_from_config_file() {
+    let mut file = tempfile::Builder::new().suffix(".toml").tempfile().unwrap();
+    write!(
+        file,
+        r#"
+api_url = "https://api.openai.com/v1/chat/completions"
+model = "gpt-4"
+system_prompt = "Answer briefly"
+user_prompt = "What is the capital of France?"
+
+[rate_limit.openai]
+requests_per_minute = 500
+tokens_per_minute = 150000
+burst_tokens = 30000
+
+[rate_limit.anthropic]
+requests_per_minute = 50
+"#
+    )
+    .unwrap();
+    let config = load_config_file(file.path()).unwrap().rate_limit.unwrap();
+    let openai = &config.providers["openai"];
+    assert_eq!(openai.requests_per_minute, Some(500));
+    assert_eq!(openai.tokens_per_minute, Some(150000));
+    assert_eq!(openai.burst_tokens, Some(30000));
+    assert_eq!(config.providers["anthropic"].tokens_per_minute, None);
+    assert!(RateLimiter::from_config(&config).is_ok());
+
+    let invalid = toml::from_str("[openia]\nrequests_per_minute = 500").unwrap();
+    let err = RateLimiter::from_config(&invalid).unwrap_err();
+    assert!(matches!(err, CliError::InvalidArguments(_)), "{err}");
+    assert!(
+        err.to_string().contains("unknown provider 'openia'"),
+        "{err}"
+    );
+}
@@ -0,0 +1,182 @@
+// Response cache tests
+//
+// Verifies repeated requests are answered from the cache without calling the
+// LLM, that the key covers the prompt, that output guardrails still run
+// on cached responses, that stochastic requests are only cached on opt-in,
+// and that a model build change invalidates earlier entries.
+
+mod common;
+
+use fortified_llm_client::{
+    evaluate, storage::MemoryStorage, EvaluationConfig, GuardrailConfigBuilder, ResponseCache,
+    StorageCache,
+};
+use mockito::{Matcher, Server};
+use std::sync::Arc;
+
+fn base_config(api_url: String, cache: Arc<ResponseCache>) -> EvaluationConfig {
+    common::base_builder(api_url)
+        .user_prompt("What is the capital of France?")
+        .seed(42)
+        .response_cache(cache)
+        .build()
+        .unwrap()
+}
+
+async fn mock_completion(server: &mut Server, hits: usize) -> mockito::Mock {
+    server
+        .mock("POST", "/v1/chat/completions")
+        .with_status(200)
+        .with_body(r#"{"choices": [{"message": {"role": "assistant", "content": "Paris."}}]}"#)
+        .expect(hits)
+        .create_async()
+        .await
+}
+
+#[tokio::test]
+async fn test_repeated_request_served_from_cache() {
+    let mut server = Server::new_async().await;
+    let mock = mock_completion(&mut server, 2).await;
+    let cache = Arc::new(ResponseCache::memory(10));
+    let config = base_config(server.url() + "/v1/chat/completions", cache);
+
+    let first = evaluate(config.clone()).await.unwrap();
+    let second = evaluate(config.clone()).await.unwrap();
+    let mut other_prompt = config;
+    other_prompt.user_prompt = "What is the capital of Spain?".to_string();
+    let third = evaluate(other_prompt).await.unwrap();
+
+    mock.assert_async().await;
+    assert_eq!(first.metadata.cache_hit, Some(false));
+    assert_eq!(second.metadata.cache_hit, Some(true));
+    assert_eq!(second.response, first.response);
+    assert_eq!(third.metadata.cache_hit, Some(false));
+}
+
+#[tokio::test]
+async fn test_output_guardrails_run_on_cache_hit() {
+    let mut server = Server::new_async().await;
+    let mock = mock_completion(&mut server, 1).await;
+    let cache = Arc::new(ResponseCache::new(Arc::new(StorageCache::new(
+        Arc::new(MemoryStorage::new()),
+        None,
+    ))));
+    let config = base_config(server.url() + "/v1/chat/completions", cache);
+
+    assert_eq!(evaluate(config.clone()).await.unwrap().status, "success");
+
+    let mut strict = config;
+    strict.output_guardrails = Some(
+        GuardrailConfigBuilder::regex()
+            .max_length_bytes(3)
+            .build()
+            .unwrap(),
+    );
+    let output = evaluate(strict).await.unwrap();
+
+    mock.assert_async().await;
+    assert_eq!(output.metadata.cache_hit, Some(true));
+    assert_eq!(output.status, "error");
+}
+
+#[tokio::test]
+async fn test_no_cache_hit_metadata_without_cache() {
+    let mut server = Server::new_async().await;
+    let mock = mock_completion(&mut server, 1).await;
+    let mut config = base_config(
+        server.url() + "/v1/chat/completions",
+        Arc::new(ResponseCache::memory(1)),
+    );
+    config.response_cache = None;
+
+    let output = evaluate(config).await.unwrap();
+
+    mock.assert_async().await;
+    assert_eq!(output.metadata.cache_hit, None);
+}
+
+#[tokio::test]
+async fn test_stochastic_requests_bypass_cache_unless_enabled() {
+    let mut server = Server::new_async().await;
+    let mock = mock_completion(&mut server, 3).await;
+    let url = server.url() + "/v1/chat/completions";
+
+    // temperature > 0 without a seed: every call reaches the LLM
+    let mut config = base_config(url.clone(), Arc::new(ResponseCache::memory(10)));
+    config.temperature = 0.7;
+    config.seed = None;
+    for _ in 0..2 {
+        let output = evaluate(config.clone()).await.unwrap();
+        assert_eq!(output.metadata.cache_hit, None);
+    }
+
+    // Opting in caches them like deterministic requests
+    config.response_cache = Some(Arc::new(ResponseCache::memory(10).stochastic(true)));
+    let first = evaluate(config.clone()).await.unwrap();
+    let second = evaluate(config).await.unwrap();
+
+    mock.assert_async().await;
+    assert_eq!(first.metadata.cache_hit, Some(false));
+    assert_eq!(second.metadata.cache_hit, Some(true));
+}
+
+async fn mock_build(
+    server: &mut Server,
+    prompt: &str,
+    fingerprint: &str,
+    hits: usize,
+) -> mockito::Mock {
+    server
+        .mock("POST", "/v1/chat/completions")
+        .match_body(Matcher::Regex(prompt.to_string()))
+        .with_status(200)
+        .with_body(format!(
+            r#"{{"system_fingerprint": "{fingerprint}",
+                "choices": [{{"message": {{"role": "assistant", "content": "Paris."}}}}]}}"#
+        ))
+        .expect(hits)
+        .create_async()
+        .await
+}
+
+#[tokio::test]
+async fn test_model_build_change_invalidates_cached_responses() {
+    let mut server = Server::new_async().await;
+    let france = mock_build(&mut server, "France", "fp_1", 2).await;
+    let spain = mock_build(&mut server, "Spain", "fp_2", 1).await;
+
+    let cache = Arc::new(ResponseCache::memory(10));
+    let config = base_config(server.url() + "/v1/chat/completions", cache);
+    let mut other_prompt = config.clone();
+    other_prompt.user_prompt = "What is the capital of Spain?".to_string();
+
+    assert_eq!(
+        evaluate(config.clone()).await.unwrap().metadata.cache_hit,
+        Some(false)
+    );
+    assert_eq!(
+        evaluate(config.clone()).await.unwrap().metadata.cache_hit,
+        Some(true)
+    );
+
+    // A live call served by another build invalidates the France entry
+    tokio::time::sleep(std::time::Duration::from_millis(2)).await;
+    assert_eq!(
+        evaluate(other_prompt).await.unwrap().metadata.cache_hit,
+        Some(false)
+    );
+    let after_change = evaluate(config).await.unwrap();
+    assert_eq!(after_change.metadata.cache_hit, Some(false));
+    assert_eq!(
+        after_change
+            .metadata
+            .model_echo
+            .unwrap()
+            .system_fingerprint
+            .as_deref(),
+        Some("fp_1")
+    );
+
+    france.assert_async().await;
+    spain.assert_async().await;
+}
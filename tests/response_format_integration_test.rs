@@ -4,7 +4,8 @@
 // formats and wrapped as string for text format.
 
 use fortified_llm_client::{
-    evaluate, EvaluationConfig, JsonSchemaDefinition, Provider, ResponseFormat,
+    constants::http::DEFAULT_MAX_RESPONSE_BYTES, evaluate, EvaluationConfig, JsonSchemaDefinition,
+    Provider, ResponseFormat,
 };
 use mockito::Server;
 use serde_json::json;
@@ -17,18 +18,54 @@ async fn create_test_config(api_url: String) -> EvaluationConfig {
         user_prompt: "Test user".to_string(),
         provider: Some(Provider::OpenAI),
         temperature: 0.0,
+        top_p: None,
         max_tokens: Some(100),
         seed: None,
         api_key: Some("test-key".to_string()),
         timeout_secs: 5,
+        max_response_bytes: DEFAULT_MAX_RESPONSE_BYTES,
         validate_tokens: false,
         context_limit: None,
+        context_probe: None,
         response_format: None,
         pdf_input: None,
+        user_prompt_prefix: None,
+        user_prompt_suffix: None,
+        untrusted_content: Vec::new(),
+        spotlight: None,
         input_guardrails: None,
         output_guardrails: None,
+        refusal_detection: None,
+        repetition_detection: None,
+        deadline: None,
+        tools: Vec::new(),
+        context_files: Vec::new(),
+        input_sources: None,
+        escalation: None,
+        url_input: None,
+        system_prompt_url: None,
+        fetch: None,
+        image_input: None,
+        image_mode: Default::default(),
+        guard_budget: None,
+        chunking: None,
+        bedrock: None,
+        metrics: None,
+        response_cache: None,
+        rate_limiter: None,
+        data_policy: None,
+        metadata_fields: None,
+        tags: Vec::new(),
+        guardrail_conditions: Vec::new(),
         system_prompt_file: None,
         user_prompt_file: None,
+        guardrail_override: None,
+        override_token: None,
+        prompt_audit: None,
+        trend_stats: None,
+        config_warnings: Vec::new(),
+        context_warning_percent: None,
+        prompt_pins: Vec::new(),
     }
 }
 
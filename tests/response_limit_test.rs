@@ -0,0 +1,125 @@
+//! Integration tests for the HTTP response size limit
+//!
+//! The limit is set per evaluation (`max_response_bytes`). Bodies are sent
+//! chunked (no `Content-Length`) unless a test checks the early refusal of a
+//! declared oversized body.
+
+mod common;
+
+use fortified_llm_client::{evaluate, evaluate_streaming, EvaluationConfig};
+use mockito::{Matcher, Server};
+
+const LIMIT: usize = 1024;
+
+fn config(server_url: &str) -> EvaluationConfig {
+    common::base_builder(format!("{server_url}/v1/chat/completions"))
+        .user_prompt("Greet")
+        .max_response_bytes(LIMIT)
+        .build()
+        .unwrap()
+}
+
+fn completion(content: &str) -> String {
+    serde_json::json!({
+        "choices": [{"message": {"role": "assistant", "content": content}}]
+    })
+    .to_string()
+}
+
+#[tokio::test]
+async fn test_chunked_body_cut_off_at_limit() {
+    let mut server = Server::new_async().await;
+    let _mock = server
+        .mock("POST", "/v1/chat/completions")
+        .with_status(200)
+        .with_chunked_body(|w| {
+            for _ in 0..64 {
+                w.write_all(&[b' '; 256])?;
+            }
+            w.write_all(completion("Hello").as_bytes())
+        })
+        .create_async()
+        .await;
+
+    let Err(err) = evaluate(config(&server.url())).await else {
+        panic!("Expected the response to be refused");
+    };
+    assert_eq!(err.code(), "RESPONSE_TOO_LARGE", "{err}");
+    assert_eq!(err.exit_code(), 16);
+    assert!(err.to_string().contains("1024 byte limit"), "{err}");
+}
+
+#[tokio::test]
+async fn test_declared_oversized_body_refused() {
+    let mut server = Server::new_async().await;
+    let _mock = server
+        .mock("POST", "/v1/chat/completions")
+        .with_status(200)
+        .with_body(completion(&"a".repeat(2 * LIMIT)))
+        .create_async()
+        .await;
+
+    let Err(err) = evaluate(config(&server.url())).await else {
+        panic!("Expected the response to be refused");
+    };
+    assert_eq!(err.code(), "RESPONSE_TOO_LARGE", "{err}");
+    assert!(err.to_string().contains("/v1/chat/completions"), "{err}");
+}
+
+#[tokio::test]
+async fn test_limit_applies_per_evaluation() {
+    let mut server = Server::new_async().await;
+    let _mock = server
+        .mock("POST", "/v1/chat/completions")
+        .with_status(200)
+        .with_body(completion(&"a".repeat(2 * LIMIT)))
+        .expect(2)
+        .create_async()
+        .await;
+
+    let mut larger = config(&server.url());
+    larger.max_response_bytes = 4 * LIMIT;
+    let (refused, accepted) = tokio::join!(evaluate(config(&server.url())), evaluate(larger));
+    assert_eq!(refused.err().unwrap().code(), "RESPONSE_TOO_LARGE");
+    assert_eq!(accepted.unwrap().status, "success");
+}
+
+#[tokio::test]
+async fn test_body_within_limit_accepted() {
+    let mut server = Server::new_async().await;
+    let _mock = server
+        .mock("POST", "/v1/chat/completions")
+        .with_status(200)
+        .with_chunked_body(|w| w.write_all(completion("Hello").as_bytes()))
+        .create_async()
+        .await;
+
+    let output = evaluate(config(&server.url())).await.unwrap();
+    assert_eq!(output.status, "success");
+    assert_eq!(output.response, Some(serde_json::json!("Hello")));
+}
+
+#[tokio::test]
+async fn test_stream_cut_off_at_limit() {
+    let mut server = Server::new_async().await;
+    let _mock = server
+        .mock("POST", "/v1/chat/completions")
+        .match_body(Matcher::PartialJsonString(
+            r#"{"stream": true}"#.to_string(),
+        ))
+        .with_status(200)
+        .with_header("content-type", "text/event-stream")
+        .with_chunked_body(|w| {
+            for _ in 0..64 {
+                w.write_all(b"data: {\"choices\":[{\"delta\":{\"content\":\"spam \"}}]}\n\n")?;
+            }
+            w.write_all(b"data: [DONE]\n\n")
+        })
+        .create_async()
+        .await;
+
+    let Err(err) = evaluate_streaming(config(&server.url()), |_: &str| {}).await else {
+        panic!("Expected the stream to be cut off");
+    };
+    assert_eq!(err.code(), "RESPONSE_TOO_LARGE", "{err}");
+}
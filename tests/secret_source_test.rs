@@ -0,0 +1,146 @@
+//! Integration tests for API key secret sources
+//!
+//! These tests verify that `api_key_secret` references are parsed from config
+//! files, validated, and resolved into the Authorization header of guardrail
+//! requests (mounted secret files and Vault KV reads).
+
+use fortified_llm_client::{
+    create_guardrail_provider, load_config_file, run_from_args, GuardrailConfigBuilder,
+    LlamaGuardCategory, SecretSource,
+};
+use mockito::{Matcher, Server};
+use std::fs;
+
+#[tokio::test]
+async fn test_guardrail_api_key_from_mounted_file() {
+    let mut server = Server::new_async().await;
+    let guard = server
+        .mock("POST", "/v1/chat/completions")
+        .match_header("authorization", "Bearer sk-guard-from-file")
+        .with_status(200)
+        .with_body(r#"{"choices": [{"message": {"role": "assistant", "content": "safe"}}]}"#)
+        .expect(1)
+        .create_async()
+        .await;
+
+    let dir = tempfile::TempDir::new().unwrap();
+    let secret = dir.path().join("api-key");
+    fs::write(&secret, "sk-guard-from-file\n").unwrap();
+    let config_path = dir.path().join("config.toml");
+    fs::write(
+        &config_path,
+        format!(
+            r#"
+api_url = "mock://"
+model = "mock-model"
+system_prompt = "Echo"
+user_prompt = "ping"
+
+[guardrails.input]
+type = "llama_guard"
+api_url = "{}/v1/chat/completions"
+model = "llama-guard3:8b"
+timeout_secs = 5
+api_key_secret = {{ file = "{}" }}
+"#,
+            server.url(),
+            secret.display()
+        ),
+    )
+    .unwrap();
+
+    let config = load_config_file(&config_path).unwrap();
+    let input = config.guardrails.unwrap().input.unwrap();
+    let guardrail = create_guardrail_provider(&input).unwrap();
+    assert!(guardrail.validate("hello").await.unwrap().passed);
+    guard.assert_async().await;
+
+    // Conflicting sources and malformed references fail validation
+    let err = GuardrailConfigBuilder::llama_guard(server.url(), "llama-guard3:8b")
+        .categories([LlamaGuardCategory::S1])
+        .api_key_name("GUARD_KEY")
+        .api_key_secret(SecretSource::File(secret.clone()))
+        .build()
+        .unwrap_err();
+    assert!(
+        err.to_string()
+            .contains("cannot specify both 'api_key_name' and 'api_key_secret'"),
+        "{err}"
+    );
+    let err = GuardrailConfigBuilder::llama_guard(server.url(), "llama-guard3:8b")
+        .categories([LlamaGuardCategory::S1])
+        .api_key_secret(SecretSource::Vault("secret/data/guard".to_string()))
+        .build()
+        .unwrap_err();
+    assert!(err.to_string().contains("path#field"), "{err}");
+
+    // The main provider accepts the same sources
+    fs::write(
+        &config_path,
+        format!(
+            "api_url = \"mock://\"\nmodel = \"mock-model\"\nsystem_prompt = \"Echo\"\nuser_prompt = \"ping\"\napi_key_secret = {{ file = \"{}\" }}\n",
+            dir.path().join("missing").display()
+        ),
+    )
+    .unwrap();
+    let args = vec![
+        "--config-file".to_string(),
+        config_path.display().to_string(),
+    ];
+    let err = run_from_args(&args).await.err().unwrap();
+    assert!(
+        err.to_string().contains("Failed to read secret file"),
+        "{err}"
+    );
+    fs::write(dir.path().join("missing"), "sk-main").unwrap();
+    assert_eq!(run_from_args(&args).await.unwrap().status, "success");
+}
+
+#[tokio::test]
+async fn test_guardrail_api_key_from_vault() {
+    let mut server = Server::new_async().await;
+    let vault = server
+        .mock("GET", "/v1/secret/data/guard")
+        .match_header("x-vault-token", "vault-test-token")
+        .with_status(200)
+        .with_body(r#"{"data": {"data": {"api_key": "sk-from-vault"}, "metadata": {}}}"#)
+        .expect(1)
+        .create_async()
+        .await;
+    let guard = server
+        .mock("POST", "/v1/chat/completions")
+        .match_header("authorization", "Bearer sk-from-vault")
+        .match_body(Matcher::Any)
+        .with_status(200)
+        .with_body(r#"{"choices": [{"message": {"role": "assistant", "content": "safe"}}]}"#)
+        .create_async()
+        .await;
+
+    // Only test in this binary that touches the Vault variables
+    std::env::set_var("VAULT_ADDR", server.url());
+    std::env::set_var("VAULT_TOKEN", "vault-test-token");
+
+    let config = GuardrailConfigBuilder::llama_guard(
+        server.url() + "/v1/chat/completions",
+        "llama-guard3:8b",
+    )
+    .categories([LlamaGuardCategory::S1])
+    .api_key_secret(SecretSource::Vault("secret/data/guard#api_key".to_string()))
+    .build()
+    .unwrap();
+    let guardrail = create_guardrail_provider(&config).unwrap();
+    assert!(guardrail.validate("hello").await.unwrap().passed);
+    vault.assert_async().await;
+    guard.assert_async().await;
+
+    let missing = GuardrailConfigBuilder::llama_guard(server.url(), "llama-guard3:8b")
+        .categories([LlamaGuardCategory::S1])
+        .api_key_secret(SecretSource::Vault("secret/data/guard#token".to_string()))
+        .build()
+        .unwrap();
+    let err = create_guardrail_provider(&missing).err().unwrap();
+    assert!(err.to_string().contains("has no field 'token'"), "{err}");
+
+    std::env::remove_var("VAULT_ADDR");
+    std::env::remove_var("VAULT_TOKEN");
+}
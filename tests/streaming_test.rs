@@ -0,0 +1,204 @@
+// Streaming tests
+//
+// Verifies that OpenAI SSE and Ollama NDJSON bodies are streamed chunk by
+// chunk through evaluate_streaming(), and that output guardrails run on the
+// accumulated response, aborting once it exceeds the output size limit.
+
+use fortified_llm_client::{
+    config_builder::ConfigBuilder,
+    evaluate_streaming,
+    guardrails::config::{RegexAction, RegexGuardrailConfig},
+    GuardrailProviderConfig, InvokeParams, LlmClient, Provider, Severity,
+};
+use futures::StreamExt;
+use mockito::{Matcher, Server};
+use std::sync::{Arc, Mutex};
+
+const SSE_BODY: &str = "data: {\"choices\":[{\"delta\":{\"role\":\"assistant\"}}]}\n\n\
+data: {\"choices\":[{\"delta\":{\"content\":\"Hello\"}}]}\n\n\
+: keep-alive\n\n\
+data: {\"choices\":[{\"delta\":{\"content\":\", world\"}}]}\n\n\
+data: [DONE]\n\n";
+
+async fn mock_sse(server: &mut Server) -> mockito::Mock {
+    server
+        .mock("POST", "/v1/chat/completions")
+        .match_body(Matcher::PartialJsonString(
+            r#"{"stream": true}"#.to_string(),
+        ))
+        .with_status(200)
+        .with_header("content-type", "text/event-stream")
+        .with_body(SSE_BODY)
+        .create_async()
+        .await
+}
+
+fn collector() -> (
+    Arc<Mutex<Vec<String>>>,
+    impl Fn(&str) + Send + Sync + 'static,
+) {
+    let chunks = Arc::new(Mutex::new(Vec::new()));
+    let sink = chunks.clone();
+    (chunks, move |chunk: &str| {
+        sink.lock().unwrap().push(chunk.to_string())
+    })
+}
+
+#[tokio::test]
+async fn test_evaluate_streaming_openai_sse() {
+    let mut server = Server::new_async().await;
+    let mock = mock_sse(&mut server).await;
+
+    let config = ConfigBuilder::new()
+        .api_url(format!("{}/v1/chat/completions", server.url()))
+        .provider(Provider::OpenAI)
+        .model("test-model")
+        .system_prompt("System")
+        .user_prompt("Greet")
+        .build()
+        .unwrap();
+    let (chunks, on_chunk) = collector();
+    let output = evaluate_streaming(config, on_chunk).await.unwrap();
+
+    mock.assert_async().await;
+    assert_eq!(output.status, "success");
+    assert_eq!(*chunks.lock().unwrap(), vec!["Hello", ", world"]);
+    assert_eq!(
+        output.response,
+        Some(serde_json::Value::String("Hello, world".to_string()))
+    );
+}
+
+#[tokio::test]
+async fn test_invoke_stream_ollama_ndjson() {
+    let mut server = Server::new_async().await;
+    let mock = server
+        .mock("POST", "/api/generate")
+        .match_body(Matcher::PartialJsonString(
+            r#"{"stream": true}"#.to_string(),
+        ))
+        .with_status(200)
+        .with_body(
+            "{\"response\": \"Hel\", \"done\": false}\n\
+             {\"response\": \"lo\", \"done\": false}\n\
+             {\"response\": \"\", \"done\": true}\n",
+        )
+        .create_async()
+        .await;
+
+    let client = LlmClient::new(
+        format!("{}/api/generate", server.url()),
+        Some(Provider::Ollama),
+    );
+    let chunks: Vec<String> = client
+        .invoke_stream(InvokeParams {
+            model: "llama3",
+            system_prompt: "System",
+            user_prompt: "Greet",
+            temperature: 0.0,
+            top_p: None,
+            max_tokens: None,
+            seed: None,
+            api_key: None,
+            timeout_secs: 30,
+            response_format: None,
+            tools: &[],
+            images: &[],
+        })
+        .await
+        .unwrap()
+        .map(Result::unwrap)
+        .collect()
+        .await;
+
+    mock.assert_async().await;
+    assert_eq!(chunks, vec!["Hel", "lo"]);
+}
+
+fn regex_output_guard(
+    server: &Server,
+    max_length_bytes: usize,
+    patterns_file: Option<std::path::PathBuf>,
+) -> fortified_llm_client::EvaluationConfig {
+    ConfigBuilder::new()
+        .api_url(format!("{}/v1/chat/completions", server.url()))
+        .provider(Provider::OpenAI)
+        .model("test-model")
+        .system_prompt("System")
+        .user_prompt("Greet")
+        .output_guardrails(GuardrailProviderConfig::Regex(RegexGuardrailConfig {
+            max_length_bytes,
+            patterns_file,
+            severity_threshold: Severity::Medium,
+            builtin_patterns: Vec::new(),
+            action: RegexAction::Block,
+            preview_chars: None,
+        }))
+        .build()
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_stream_aborted_when_output_exceeds_max_length() {
+    let mut server = Server::new_async().await;
+    let _mock = mock_sse(&mut server).await;
+
+    // The first chunk fits, the second pushes the response past the limit
+    let config = regex_output_guard(&server, 8, None);
+    let (chunks, on_chunk) = collector();
+    let output = evaluate_streaming(config, on_chunk).await.unwrap();
+
+    assert_eq!(*chunks.lock().unwrap(), vec!["Hello"]);
+    assert_eq!(output.status, "error");
+    let error = output.error.unwrap();
+    assert_eq!(error.code, "OUTPUT_VALIDATION_FAILED");
+    assert!(error.message.contains("12 > 8 bytes"), "{}", error.message);
+    assert!(error.message.contains("stream aborted"));
+}
+
+#[tokio::test]
+async fn test_output_guardrails_run_on_accumulated_stream() {
+    let mut server = Server::new_async().await;
+    let _mock = mock_sse(&mut server).await;
+
+    // The pattern spans both chunks, so only the accumulated response matches
+    let dir = tempfile::TempDir::new().unwrap();
+    let patterns = dir.path().join("patterns.txt");
+    std::fs::write(&patterns, "output\tHello, world\tGreeting\thigh\n").unwrap();
+    let config = regex_output_guard(&server, 1024, Some(patterns));
+    let (chunks, on_chunk) = collector();
+    let output = evaluate_streaming(config, on_chunk).await.unwrap();
+
+    assert_eq!(chunks.lock().unwrap().len(), 2);
+    assert_eq!(output.status, "error");
+    assert_eq!(
+        output.error.unwrap().code,
+        "OUTPUT_VALIDATION_FAILED",
+        "streamed chunks must be discarded when the output guard blocks"
+    );
+}
+
+#[tokio::test]
+async fn test_stream_http_error() {
+    let mut server = Server::new_async().await;
+    let _mock = server
+        .mock("POST", "/v1/chat/completions")
+        .with_status(500)
+        .with_body("overloaded")
+        .create_async()
+        .await;
+
+    let config = ConfigBuilder::new()
+        .api_url(format!("{}/v1/chat/completions", server.url()))
+        .provider(Provider::OpenAI)
+        .model("test-model")
+        .system_prompt("System")
+        .user_prompt("Greet")
+        .build()
+        .unwrap();
+    let Err(err) = evaluate_streaming(config, |_: &str| {}).await else {
+        panic!("Expected HTTP error");
+    };
+    assert!(err.to_string().contains("HTTP 500"), "{err}");
+    assert!(err.to_string().contains("overloaded"));
+}
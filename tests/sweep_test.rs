@@ -0,0 +1,126 @@
+// Sampling parameter sweep tests
+//
+// Verifies every grid point is run with its parameters and repeats, and that
+// per-point pass rates, scores and response variance are reported.
+
+mod common;
+
+use fortified_llm_client::{
+    run_sweep, Assertion, EvaluationConfig, GuardrailConfigBuilder, SweepConfig,
+};
+use mockito::{Matcher, Server};
+
+fn base_config(api_url: String) -> EvaluationConfig {
+    common::base_builder(api_url)
+        .user_prompt("What is the capital of France?")
+        .build()
+        .unwrap()
+}
+
+async fn mock_at_temperature(
+    server: &mut Server,
+    temperature: f64,
+    content: &str,
+) -> mockito::Mock {
+    server
+        .mock("POST", "/v1/chat/completions")
+        .match_body(Matcher::PartialJson(
+            serde_json::json!({ "temperature": temperature }),
+        ))
+        .with_status(200)
+        .with_body(
+            serde_json::json!({
+                "choices": [{"message": {"role": "assistant", "content": content}}]
+            })
+            .to_string(),
+        )
+        .expect(2)
+        .create_async()
+        .await
+}
+
+#[tokio::test]
+async fn test_sweep_runs_every_point_with_repeats() {
+    let mut server = Server::new_async().await;
+    let cold = mock_at_temperature(&mut server, 0.0, "Paris.").await;
+    let hot = mock_at_temperature(&mut server, 1.5, "Paris is the capital of France.").await;
+
+    let sweep = SweepConfig {
+        temperatures: vec![0.0, 1.5],
+        repeats: 2,
+        assertions: vec![Assertion::MaxChars { max: 10 }],
+        ..Default::default()
+    };
+    let config = base_config(server.url() + "/v1/chat/completions");
+    let report = run_sweep(&config, &sweep, 2).await.unwrap();
+
+    cold.assert_async().await;
+    hot.assert_async().await;
+    assert_eq!(report.repeats, 2);
+    assert_eq!(report.points.len(), 2);
+
+    let (cold, hot) = (&report.points[0], &report.points[1]);
+    assert_eq!(cold.point.temperature, 0.0);
+    assert_eq!(cold.point.seed, None);
+    assert_eq!(cold.runs, 2);
+    assert_eq!(cold.succeeded, 2);
+    assert_eq!(cold.guardrail_pass_rate, 1.0);
+    assert_eq!(cold.pass_rate, 1.0);
+    assert_eq!(cold.distinct_responses, 1);
+    assert_eq!(cold.response_divergence, 0.0);
+    assert_eq!(cold.mean_response_chars, 6.0);
+    assert_eq!(cold.response_chars_stddev, 0.0);
+
+    assert_eq!(hot.point.temperature, 1.5);
+    assert_eq!(hot.pass_rate, 0.0);
+    assert_eq!(hot.mean_score, 0.0);
+
+    let csv = report.to_csv().unwrap();
+    let mut lines = csv.lines();
+    assert!(lines
+        .next()
+        .unwrap()
+        .starts_with("temperature,top_p,seed,runs"));
+    assert!(lines
+        .next()
+        .unwrap()
+        .starts_with("0,,,2,2,0,0,1,1,1,1,0,6,0,"));
+    assert_eq!(lines.count(), 1);
+}
+
+#[tokio::test]
+async fn test_sweep_reports_guardrail_blocks_per_point() {
+    let mut config = base_config("http://127.0.0.1:1/v1/chat/completions".to_string());
+    config.input_guardrails = Some(
+        GuardrailConfigBuilder::regex()
+            .max_length_bytes(3)
+            .build()
+            .unwrap(),
+    );
+    let sweep = SweepConfig {
+        seeds: vec![1, 2, 3],
+        ..Default::default()
+    };
+
+    let report = run_sweep(&config, &sweep, 1).await.unwrap();
+    assert_eq!(report.points.len(), 3);
+    for (point, seed) in report.points.iter().zip([1, 2, 3]) {
+        assert_eq!(point.point.seed, Some(seed));
+        assert_eq!(point.blocked, 1);
+        assert_eq!(point.guardrail_pass_rate, 0.0);
+        assert_eq!(point.distinct_responses, 0);
+    }
+}
+
+#[tokio::test]
+async fn test_sweep_over_budget_is_rejected() {
+    let sweep = SweepConfig {
+        temperatures: vec![0.0, 0.5, 1.0],
+        repeats: 10,
+        max_runs: 20,
+        ..Default::default()
+    };
+    let config = base_config("http://127.0.0.1:1/v1/chat/completions".to_string());
+    let err = run_sweep(&config, &sweep, 1).await.unwrap_err();
+    assert!(err.to_string().contains("max_runs budget of 20"));
+}
@@ -0,0 +1,226 @@
+// Multi-tenant registry tests
+//
+// Verifies that each tenant's endpoint, API key and limits stay isolated and
+// that metrics are tracked per tenant.
+
+use fortified_llm_client::{
+    config_builder::ConfigBuilder, EvaluationConfig, Provider, TenantRegistry, TenantRequest,
+};
+use mockito::Server;
+
+fn tenant_config(api_url: String, api_key: &str) -> EvaluationConfig {
+    ConfigBuilder::new()
+        .api_url(api_url)
+        .model("test-model")
+        .system_prompt("Tenant system prompt")
+        .user_prompt("")
+        .provider(Provider::OpenAI)
+        .max_tokens(100)
+        .api_key(api_key)
+        .timeout_secs(5)
+        .build()
+        .unwrap()
+}
+
+fn completion(content: &str) -> String {
+    format!(r#"{{"choices": [{{"message": {{"role": "assistant", "content": "{content}"}}}}]}}"#)
+}
+
+#[tokio::test]
+async fn test_tenants_use_their_own_endpoint_and_key() {
+    let mut server_a = Server::new_async().await;
+    let mut server_b = Server::new_async().await;
+
+    let mock_a = server_a
+        .mock("POST", "/v1/chat/completions")
+        .match_header("authorization", "Bearer key-a")
+        .with_status(200)
+        .with_body(completion("from tenant a"))
+        .expect(2)
+        .create_async()
+        .await;
+    let mock_b = server_b
+        .mock("POST", "/v1/chat/completions")
+        .match_header("authorization", "Bearer key-b")
+        .with_status(200)
+        .with_body(completion("from tenant b"))
+        .expect(1)
+        .create_async()
+        .await;
+
+    let registry = TenantRegistry::new();
+    registry.register(
+        "a",
+        tenant_config(server_a.url() + "/v1/chat/completions", "key-a"),
+    );
+    registry.register(
+        "b",
+        tenant_config(server_b.url() + "/v1/chat/completions", "key-b"),
+    );
+
+    for _ in 0..2 {
+        let output = registry
+            .evaluate_for_tenant("a", TenantRequest::new("hello"))
+            .await
+            .unwrap();
+        assert_eq!(
+            output.response,
+            Some(serde_json::Value::String("from tenant a".to_string()))
+        );
+    }
+    let output = registry
+        .evaluate_for_tenant("b", TenantRequest::new("hello"))
+        .await
+        .unwrap();
+    assert_eq!(
+        output.response,
+        Some(serde_json::Value::String("from tenant b".to_string()))
+    );
+
+    mock_a.assert_async().await;
+    mock_b.assert_async().await;
+
+    let metrics_a = registry.metrics("a").unwrap();
+    assert_eq!(metrics_a.requests, 2);
+    assert_eq!(metrics_a.successes, 2);
+    let metrics_b = registry.metrics("b").unwrap();
+    assert_eq!(metrics_b.requests, 1);
+    assert_eq!(metrics_b.successes, 1);
+}
+
+#[tokio::test]
+async fn test_tenant_metrics_track_blocked_and_failed_requests() {
+    let mut server = Server::new_async().await;
+    let mock = server
+        .mock("POST", "/v1/chat/completions")
+        .with_status(500)
+        .with_body(r#"{"error": {"message": "boom"}}"#)
+        .expect(1)
+        .create_async()
+        .await;
+
+    let registry = TenantRegistry::new();
+
+    // Tenant with a tiny context limit: every request is blocked before invocation
+    let mut strict = tenant_config(server.url() + "/v1/chat/completions", "key");
+    strict.validate_tokens = true;
+    strict.context_limit = Some(10);
+    registry.register("strict", strict);
+    registry.register(
+        "failing",
+        tenant_config(server.url() + "/v1/chat/completions", "key"),
+    );
+
+    let output = registry
+        .evaluate_for_tenant("strict", TenantRequest::new("hello"))
+        .await
+        .unwrap();
+    assert_eq!(output.status, "error");
+
+    let result = registry
+        .evaluate_for_tenant("failing", TenantRequest::new("hello"))
+        .await;
+    assert!(result.is_err());
+
+    mock.assert_async().await;
+
+    let strict = registry.metrics("strict").unwrap();
+    assert_eq!((strict.requests, strict.blocked, strict.errors), (1, 1, 0));
+    let failing = registry.metrics("failing").unwrap();
+    assert_eq!(
+        (failing.requests, failing.blocked, failing.errors),
+        (1, 0, 1)
+    );
+}
+
+#[tokio::test]
+async fn test_tenant_quota_blocks_further_evaluations() {
+    use fortified_llm_client::{Quota, UsageAccountant};
+    use std::sync::Arc;
+
+    let mut server = Server::new_async().await;
+    let mock = server
+        .mock("POST", "/v1/chat/completions")
+        .with_status(200)
+        .with_body(completion("ok"))
+        .expect(1)
+        .create_async()
+        .await;
+
+    let accountant = Arc::new(UsageAccountant::new());
+    accountant.set_quota(
+        "a",
+        Quota {
+            max_requests: Some(1),
+            ..Default::default()
+        },
+    );
+    let registry = TenantRegistry::new().with_accountant(Arc::clone(&accountant));
+    registry.register(
+        "a",
+        tenant_config(server.url() + "/v1/chat/completions", "key"),
+    );
+
+    let output = registry
+        .evaluate_for_tenant("a", TenantRequest::new("hello"))
+        .await
+        .unwrap();
+    assert_eq!(output.status, "success");
+
+    let Err(err) = registry
+        .evaluate_for_tenant("a", TenantRequest::new("hello again"))
+        .await
+    else {
+        panic!("Second request should exceed the quota");
+    };
+    assert_eq!(err.code(), "QUOTA_EXCEEDED");
+
+    mock.assert_async().await;
+    let usage = accountant.usage("a").unwrap();
+    assert_eq!(usage.requests, 1);
+    assert!(usage.tokens > 0);
+}
+
+#[tokio::test]
+async fn test_failed_evaluations_count_against_the_quota() {
+    use fortified_llm_client::{Quota, UsageAccountant};
+    use std::sync::Arc;
+
+    let mut server = Server::new_async().await;
+    let mock = server
+        .mock("POST", "/v1/chat/completions")
+        .with_status(500)
+        .with_body(r#"{"error": {"message": "boom"}}"#)
+        .expect(1)
+        .create_async()
+        .await;
+
+    let accountant = Arc::new(UsageAccountant::new());
+    accountant.set_quota(
+        "a",
+        Quota {
+            max_requests: Some(1),
+            ..Default::default()
+        },
+    );
+    let registry = TenantRegistry::new().with_accountant(Arc::clone(&accountant));
+    registry.register(
+        "a",
+        tenant_config(server.url() + "/v1/chat/completions", "key"),
+    );
+
+    let result = registry
+        .evaluate_for_tenant("a", TenantRequest::new("hello"))
+        .await;
+    assert!(result.is_err());
+    assert_eq!(accountant.usage("a").unwrap().requests, 1);
+
+    let Err(err) = registry
+        .evaluate_for_tenant("a", TenantRequest::new("hello again"))
+        .await
+    else {
+        panic!("Second request should exceed the quota");
+    };
+    assert_eq!(err.code(), "QUOTA_EXCEEDED");
+    mock.assert_async().await;
+}
@@ -0,0 +1,203 @@
+//! Integration tests for tool/function calling
+//!
+//! These tests verify that tool definitions reach the provider (OpenAI tools
+//! API, Ollama /api/chat), that requested calls come back typed in
+//! `CliOutput::tool_calls`, and that output guardrails see their arguments.
+
+use fortified_llm_client::{
+    config_builder::ConfigBuilder, evaluate, load_config_file, EvaluationOutcome,
+    GuardrailConfigBuilder, Provider, Severity, ToolDefinition,
+};
+use mockito::{Matcher, Server};
+use serde_json::json;
+use std::io::Write;
+
+fn run_sql_tool() -> ToolDefinition {
+    ToolDefinition::new(
+        "run_sql",
+        "Run a read-only SQL query",
+        json!({
+            "type": "object",
+            "properties": {"query": {"type": "string"}},
+            "required": ["query"]
+        }),
+    )
+}
+
+fn tool_call_body(query: &str) -> String {
+    json!({"choices": [{
+        "message": {
+            "role": "assistant",
+            "content": null,
+            "tool_calls": [{
+                "id": "call_1",
+                "type": "function",
+                "function": {
+                    "name": "run_sql",
+                    "arguments": json!({"query": query}).to_string()
+                }
+            }]
+        },
+        "finish_reason": "tool_calls"
+    }]})
+    .to_string()
+}
+
+#[tokio::test]
+async fn test_openai_tool_calls_returned_and_guarded() {
+    let mut server = Server::new_async().await;
+    let safe = server
+        .mock("POST", "/v1/chat/completions")
+        .match_body(Matcher::AllOf(vec![
+            Matcher::PartialJson(json!({
+                "tools": [{"type": "function", "function": {"name": "run_sql"}}]
+            })),
+            Matcher::Regex("How many orders".to_string()),
+        ]))
+        .with_status(200)
+        .with_body(tool_call_body("SELECT count(*) FROM orders"))
+        .expect(1)
+        .create_async()
+        .await;
+    let unsafe_call = server
+        .mock("POST", "/v1/chat/completions")
+        .match_body(Matcher::Regex("Clean up".to_string()))
+        .with_status(200)
+        .with_body(tool_call_body("DROP TABLE orders"))
+        .expect(1)
+        .create_async()
+        .await;
+
+    let config = |user_prompt: &str| {
+        ConfigBuilder::new()
+            .api_url(format!("{}/v1/chat/completions", server.url()))
+            .provider(Provider::OpenAI)
+            .model("test-model")
+            .system_prompt("Answer with the database")
+            .user_prompt(user_prompt)
+            .tool(run_sql_tool())
+            .output_guardrails(
+                GuardrailConfigBuilder::terminology()
+                    .forbid("DROP TABLE")
+                    .severity_threshold(Severity::Medium)
+                    .build()
+                    .unwrap(),
+            )
+            .build()
+            .unwrap()
+    };
+
+    let output = evaluate(config("How many orders are there?"))
+        .await
+        .unwrap();
+    assert_eq!(output.status, "success");
+    assert_eq!(output.response, Some(json!("")));
+    assert_eq!(output.tool_calls.len(), 1);
+    assert_eq!(output.tool_calls[0].id, "call_1");
+    assert_eq!(output.tool_calls[0].name, "run_sql");
+    assert_eq!(
+        output.tool_calls[0].arguments["query"],
+        "SELECT count(*) FROM orders"
+    );
+
+    // Output guardrails check tool arguments like response text
+    let output = evaluate(config("Clean up the orders")).await.unwrap();
+    assert_eq!(
+        output.error.unwrap().code,
+        EvaluationOutcome::OutputValidationFailed
+    );
+    assert!(output.tool_calls.is_empty());
+
+    safe.assert_async().await;
+    unsafe_call.assert_async().await;
+}
+
+#[tokio::test]
+async fn test_ollama_tools_use_chat_endpoint() {
+    let mut server = Server::new_async().await;
+    let chat = server
+        .mock("POST", "/api/chat")
+        .match_body(Matcher::PartialJson(json!({
+            "stream": false,
+            "tools": [{"type": "function", "function": {"name": "run_sql"}}]
+        })))
+        .with_status(200)
+        .with_body(
+            json!({"message": {"role": "assistant", "content": "", "tool_calls": [
+                {"function": {"name": "run_sql", "arguments": {"query": "SELECT 1"}}}
+            ]}, "done": true})
+            .to_string(),
+        )
+        .expect(1)
+        .create_async()
+        .await;
+
+    let config = ConfigBuilder::new()
+        .api_url(format!("{}/api/generate", server.url()))
+        .provider(Provider::Ollama)
+        .model("llama3.1")
+        .system_prompt("Answer with the database")
+        .user_prompt("Ping the database")
+        .tool(run_sql_tool())
+        .build()
+        .unwrap();
+
+    let output = evaluate(config).await.unwrap();
+    assert_eq!(output.status, "success");
+    assert_eq!(output.tool_calls[0].id, "call_0");
+    assert_eq!(output.tool_calls[0].arguments, json!({"query": "SELECT 1"}));
+    chat.assert_async().await;
+}
+
+#[test]
+fn test_tools_from_config_file() {
+    let config_content = r#"
+api_url = "http://localhost:11434/v1/chat/completions"
+model = "test-model"
+system_prompt = "Test system"
+user_prompt = "Test user"
+
+[[tools]]
+name = "get_weather"
+description = "Current weather for a city"
+parameters = { type = "object", properties = { city = { type = "string" } }, required = ["city"] }
+
+[[tools]]
+name = "now"
+"#;
+
+    let mut temp_file = tempfile::Builder::new().suffix(".toml").tempfile().unwrap();
+    temp_file.write_all(config_content.as_bytes()).unwrap();
+    temp_file.flush().unwrap();
+
+    let file_config = load_config_file(temp_file.path()).unwrap();
+    let config = ConfigBuilder::new()
+        .merge_file_config(&file_config)
+        .build()
+        .unwrap();
+    assert_eq!(config.tools.len(), 2);
+    assert_eq!(config.tools[0].parameters["required"], json!(["city"]));
+    assert_eq!(config.tools[1].description, None);
+    assert_eq!(config.tools[1].parameters["type"], "object");
+
+    let build = |tools: Vec<ToolDefinition>| {
+        tools
+            .into_iter()
+            .fold(
+                ConfigBuilder::new()
+                    .api_url("http://localhost:11434/v1/chat/completions")
+                    .model("test-model")
+                    .system_prompt("Test system")
+                    .user_prompt("Test user"),
+                ConfigBuilder::tool,
+            )
+            .build()
+    };
+    let err = build(vec![run_sql_tool(), run_sql_tool()]).unwrap_err();
+    assert!(
+        err.to_string().contains("duplicate tool name 'run_sql'"),
+        "{err}"
+    );
+    let err = build(vec![ToolDefinition::new("run sql", "", json!({}))]).unwrap_err();
+    assert!(err.to_string().contains("tool name 'run sql'"), "{err}");
+}
@@ -0,0 +1,175 @@
+// Tracing span tests
+//
+// Verifies the pipeline emits nested spans for stages, guardrails and the
+// LLM call, with model, provider and violation attributes, and that a
+// background exporter ships them while the server is running.
+
+mod common;
+
+use fortified_llm_client::{
+    evaluate,
+    telemetry::{AttributeValue, FinishedSpan},
+    EvaluationConfig, GuardrailConfigBuilder, SpanCollector,
+};
+use mockito::{Matcher, Server};
+use std::{sync::Arc, time::Duration};
+
+fn base_config(api_url: String) -> EvaluationConfig {
+    common::base_builder(api_url)
+        .user_prompt("What is the capital of France?")
+        .build()
+        .unwrap()
+}
+
+fn find<'a>(spans: &'a [FinishedSpan], name: &str) -> &'a FinishedSpan {
+    spans
+        .iter()
+        .find(|s| s.name == name)
+        .unwrap_or_else(|| panic!("no '{name}' span"))
+}
+
+fn string(value: &str) -> Option<AttributeValue> {
+    Some(AttributeValue::String(value.to_string()))
+}
+
+#[tokio::test]
+async fn test_evaluation_emits_nested_spans() {
+    let mut server = Server::new_async().await;
+    server
+        .mock("POST", "/v1/chat/completions")
+        .with_status(200)
+        .with_body(r#"{"choices": [{"message": {"role": "assistant", "content": "Paris."}}]}"#)
+        .create_async()
+        .await;
+
+    let mut config = base_config(server.url() + "/v1/chat/completions");
+    config.input_guardrails = Some(GuardrailConfigBuilder::regex().build().unwrap());
+
+    let collector = Arc::new(SpanCollector::new());
+    let output = {
+        let _guard = tracing::subscriber::set_default(collector.clone());
+        evaluate(config).await.unwrap()
+    };
+    assert_eq!(output.status, "success");
+
+    let spans = collector.finished_spans();
+    let root = find(&spans, "evaluate");
+    assert_eq!(root.parent_span_id, None);
+    assert_eq!(root.attribute("model").cloned(), string("test-model"));
+    assert_eq!(root.attribute("provider").cloned(), string("OpenAI"));
+    assert_eq!(root.attribute("status").cloned(), string("success"));
+    assert!(spans.iter().all(|s| s.trace_id == root.trace_id));
+
+    let input_guard = find(&spans, "stage input_guard");
+    assert_eq!(input_guard.parent_span_id.as_ref(), Some(&root.span_id));
+    let guardrail = spans
+        .iter()
+        .find(|s| s.attribute("direction") == string("input").as_ref())
+        .expect("guardrail span");
+    assert_eq!(
+        guardrail.parent_span_id.as_ref(),
+        Some(&input_guard.span_id)
+    );
+    assert_eq!(
+        guardrail.attribute("violations"),
+        Some(&AttributeValue::Int(0))
+    );
+    assert_eq!(
+        guardrail.attribute("passed"),
+        Some(&AttributeValue::Bool(true))
+    );
+
+    let invoke = find(&spans, "llm.invoke");
+    assert_eq!(
+        invoke.parent_span_id.as_ref(),
+        Some(&find(&spans, "stage invoke").span_id)
+    );
+    assert_eq!(invoke.attribute("model").cloned(), string("test-model"));
+}
+
+#[tokio::test]
+async fn test_blocked_evaluation_records_violations() {
+    let mut config = base_config("http://localhost:1/v1/chat/completions".to_string());
+    config.input_guardrails = Some(
+        GuardrailConfigBuilder::regex()
+            .max_length_bytes(3)
+            .build()
+            .unwrap(),
+    );
+
+    let collector = Arc::new(SpanCollector::new());
+    let output = {
+        let _guard = tracing::subscriber::set_default(collector.clone());
+        evaluate(config).await.unwrap()
+    };
+    assert_eq!(output.status, "error");
+
+    let spans = collector.finished_spans();
+    let root = find(&spans, "evaluate");
+    assert_eq!(
+        root.attribute("error_code").cloned(),
+        string("INPUT_VALIDATION_FAILED")
+    );
+    let guardrail = spans
+        .iter()
+        .find(|s| s.attribute("passed") == Some(&AttributeValue::Bool(false)))
+        .expect("failed guardrail span");
+    assert!(matches!(
+        guardrail.attribute("violations"),
+        Some(AttributeValue::Int(n)) if *n > 0
+    ));
+    // The LLM is never called
+    assert!(spans.iter().all(|s| s.name != "llm.invoke"));
+}
+
+#[tokio::test]
+async fn test_exporter_sends_spans_while_server_runs() {
+    let mut upstream = Server::new_async().await;
+    upstream
+        .mock("POST", "/v1/chat/completions")
+        .with_status(200)
+        .with_body(r#"{"choices": [{"message": {"role": "assistant", "content": "Paris."}}]}"#)
+        .create_async()
+        .await;
+    let mut otlp = Server::new_async().await;
+    let traces = otlp
+        .mock("POST", "/v1/traces")
+        .match_body(Matcher::Regex(r#""name":"evaluate""#.to_string()))
+        .with_status(200)
+        .expect_at_least(1)
+        .create_async()
+        .await;
+
+    let collector = Arc::new(SpanCollector::new());
+    let _guard = tracing::subscriber::set_default(collector.clone());
+    let exporter = collector.spawn_exporter(&otlp.url(), "svc", Duration::from_millis(50));
+
+    let base = base_config(upstream.url() + "/v1/chat/completions");
+    let server = fortified_llm_client::Server::bind("127.0.0.1:0".parse().unwrap(), base)
+        .await
+        .unwrap();
+    let url = format!("http://{}/evaluate", server.local_addr());
+    let handle = tokio::spawn(server.run());
+    let response = reqwest::Client::new()
+        .post(url)
+        .header("content-type", "application/json")
+        .body(r#"{"user_prompt": "What is the capital of France?"}"#)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 200);
+
+    // Spans reach the collector without stopping the server
+    for _ in 0..100 {
+        if traces.matched_async().await {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    traces.assert_async().await;
+    assert!(!handle.is_finished());
+    assert!(collector.finished_spans().is_empty());
+
+    handle.abort();
+    exporter.shutdown().await;
+}
@@ -0,0 +1,75 @@
+// Trend tracking tests
+//
+// Verifies that evaluations are recorded per configuration fingerprint and
+// that the `trends` subcommand reports the recorded rates.
+
+use fortified_llm_client::{
+    config_builder::ConfigBuilder, config_fingerprint, evaluate, RefusalAction, RefusalConfig,
+    TrendStatsConfig, TrendStore,
+};
+use predicates::prelude::*;
+use std::sync::Arc;
+use tempfile::TempDir;
+
+#[tokio::test]
+async fn test_trends_recorded_and_reported() {
+    let dir = TempDir::new().unwrap();
+    let store = Arc::new(TrendStore::from_config(&TrendStatsConfig {
+        dir: dir.path().to_path_buf(),
+    }));
+
+    // The mock:// provider echoes the user prompt back as the response
+    let config = |user_prompt: &str| {
+        ConfigBuilder::new()
+            .api_url("mock://")
+            .model("mock-model")
+            .system_prompt("Echo")
+            .user_prompt(user_prompt)
+            .refusal_detection(RefusalConfig::new(RefusalAction::Warn))
+            .trend_stats(store.clone())
+            .build()
+            .unwrap()
+    };
+    let fingerprint = config_fingerprint(&config("x"));
+
+    evaluate(config("Refunds take 5 days.")).await.unwrap();
+    evaluate(config("I'm sorry, but I can't help with that."))
+        .await
+        .unwrap();
+
+    let report = store.report(&fingerprint, 28, 7).unwrap();
+    assert_eq!(report.recent.runs, 2);
+    assert_eq!(report.recent.refusal_rate, Some(0.5));
+    assert_eq!(report.recent.violation_rate, Some(0.0));
+
+    assert_cmd::cargo::cargo_bin_cmd!("fortified-llm-client")
+        .args(["trends", "--dir"])
+        .arg(dir.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(format!(
+            r#""fingerprint": "{fingerprint}""#
+        )))
+        .stdout(predicate::str::contains(r#""model": "mock-model""#))
+        .stdout(predicate::str::contains(r#""refusal_rate": 0.5"#));
+}
+
+#[test]
+fn test_cli_trends_rejects_recent_period_covering_window() {
+    let dir = TempDir::new().unwrap();
+    assert_cmd::cargo::cargo_bin_cmd!("fortified-llm-client")
+        .args([
+            "trends",
+            "--fingerprint",
+            "abc",
+            "--days",
+            "7",
+            "--recent-days",
+            "7",
+        ])
+        .arg("--dir")
+        .arg(dir.path())
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("must be shorter than the window"));
+}
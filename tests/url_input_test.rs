@@ -0,0 +1,363 @@
+//! Integration tests for URL ingestion (`--url`)
+//!
+//! These tests verify that fetched pages reach the LLM as markdown, that
+//! redirects are followed, and that private hosts, unsupported content types
+//! and oversized bodies are refused. System prompts fetched from prompt
+//! library URLs (`system_prompt_url`) must come from allowlisted hosts.
+
+use fortified_llm_client::{
+    config_builder::ConfigBuilder, evaluate, load_config_file, CliError, EvaluationConfig,
+    FetchConfig, Provider,
+};
+use mockito::{Matcher, Server};
+use serde_json::json;
+use std::io::Write;
+
+const ARTICLE: &str = r#"<html><head><title>Q3 Results</title></head>
+<body>
+  <nav><a href="/">Home</a></nav>
+  <article><h1>Results</h1><p>Revenue grew by <b>4%</b>.</p></article>
+  <footer>Contact us</footer>
+</body></html>"#;
+
+fn config(server_url: &str, page: &str, fetch: FetchConfig) -> EvaluationConfig {
+    ConfigBuilder::new()
+        .api_url(format!("{server_url}/v1/chat/completions"))
+        .provider(Provider::OpenAI)
+        .model("test-model")
+        .system_prompt("Summarize the page")
+        .url_input(format!("{server_url}{page}"))
+        .fetch(fetch)
+        .build()
+        .unwrap()
+}
+
+async fn fetch_error(config: EvaluationConfig) -> CliError {
+    match evaluate(config).await {
+        Err(e) => e,
+        Ok(output) => panic!("Expected a fetch error, got status {}", output.status),
+    }
+}
+
+fn allow_private() -> FetchConfig {
+    FetchConfig {
+        allow_private_networks: true,
+        ..FetchConfig::default()
+    }
+}
+
+#[tokio::test]
+async fn test_page_sent_as_markdown() {
+    let mut server = Server::new_async().await;
+    let redirect = server
+        .mock("GET", "/old")
+        .with_status(301)
+        .with_header("location", "/article")
+        .expect(1)
+        .create_async()
+        .await;
+    let page = server
+        .mock("GET", "/article")
+        .with_status(200)
+        .with_header("content-type", "text/html; charset=utf-8")
+        .with_body(ARTICLE)
+        .expect(1)
+        .create_async()
+        .await;
+    let llm = server
+        .mock("POST", "/v1/chat/completions")
+        .match_body(Matcher::PartialJson(json!({
+            "messages": [
+                {"role": "system"},
+                {"role": "user", "content": "# Results\n\nRevenue grew by **4%**."}
+            ]
+        })))
+        .with_status(200)
+        .with_body(
+            json!({"choices": [{"message": {"role": "assistant", "content": "Growth"}}]})
+                .to_string(),
+        )
+        .expect(1)
+        .create_async()
+        .await;
+
+    let output = evaluate(config(&server.url(), "/old", allow_private()))
+        .await
+        .unwrap();
+    assert_eq!(output.status, "success");
+    assert_eq!(
+        output.metadata.url_input,
+        Some(format!("{}/old", server.url()))
+    );
+    assert_eq!(output.metadata.user_prompt_text, None);
+    redirect.assert_async().await;
+    page.assert_async().await;
+    llm.assert_async().await;
+}
+
+#[tokio::test]
+async fn test_unsafe_fetches_refused() {
+    let mut server = Server::new_async().await;
+    let pdf = server
+        .mock("GET", "/report.pdf")
+        .with_status(200)
+        .with_header("content-type", "application/pdf")
+        .with_body("%PDF-1.7")
+        .create_async()
+        .await;
+    let article = server
+        .mock("GET", "/article")
+        .with_status(200)
+        .with_header("content-type", "text/html")
+        .with_body(ARTICLE)
+        .create_async()
+        .await;
+
+    // The mock server listens on loopback, which is refused by default
+    let err = fetch_error(config(&server.url(), "/article", FetchConfig::default())).await;
+    assert_eq!(err.code(), "FETCH_FAILED");
+    assert!(
+        err.to_string().contains("non-public address 127.0.0.1"),
+        "{err}"
+    );
+
+    let err = fetch_error(config(&server.url(), "/report.pdf", allow_private())).await;
+    assert!(
+        err.to_string()
+            .contains("unsupported content type 'application/pdf'"),
+        "{err}"
+    );
+
+    let limited = FetchConfig {
+        max_bytes: 64,
+        ..allow_private()
+    };
+    let err = fetch_error(config(&server.url(), "/article", limited)).await;
+    assert!(err.to_string().contains("64 byte limit"), "{err}");
+
+    pdf.assert_async().await;
+    article.assert_async().await;
+}
+
+#[test]
+fn test_url_from_config_file() {
+    let config_content = r#"
+api_url = "http://localhost:11434/v1/chat/completions"
+model = "test-model"
+system_prompt = "Test system"
+user_prompt_url = "https://example.com/article"
+
+[fetch]
+max_bytes = 65536
+"#;
+
+    let mut temp_file = tempfile::Builder::new().suffix(".toml").tempfile().unwrap();
+    temp_file.write_all(config_content.as_bytes()).unwrap();
+    temp_file.flush().unwrap();
+
+    let file_config = load_config_file(temp_file.path()).unwrap();
+    let config = ConfigBuilder::new()
+        .merge_file_config(&file_config)
+        .build()
+        .unwrap();
+    assert_eq!(
+        config.url_input.as_deref(),
+        Some("https://example.com/article")
+    );
+    let fetch = config.fetch.unwrap();
+    assert_eq!(fetch.max_bytes, 65536);
+    assert!(!fetch.allow_private_networks);
+
+    let err = ConfigBuilder::new()
+        .merge_file_config(&file_config)
+        .url_input("ftp://example.com/file")
+        .build()
+        .unwrap_err();
+    assert!(
+        err.to_string().contains("unsupported URL scheme 'ftp'"),
+        "{err}"
+    );
+}
+
+fn allow_hosts(hosts: &[&str]) -> FetchConfig {
+    FetchConfig {
+        allowed_hosts: hosts.iter().map(|h| h.to_string()).collect(),
+        ..FetchConfig::default()
+    }
+}
+
+fn prompt_url_config(server_url: &str, path: &str, fetch: FetchConfig) -> EvaluationConfig {
+    ConfigBuilder::new()
+        .api_url(format!("{server_url}/v1/chat/completions"))
+        .provider(Provider::OpenAI)
+        .model("test-model")
+        .system_prompt_url(format!("{server_url}{path}"))
+        .user_prompt("Where is my order?")
+        .fetch(fetch)
+        .build()
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_system_prompt_fetched_from_allowlisted_host() {
+    let mut server = Server::new_async().await;
+    let prompt = server
+        .mock("GET", "/prompts/support/v3.md")
+        .with_status(200)
+        .with_header("content-type", "text/markdown")
+        .with_body("You are a support agent. Be brief.\n")
+        .expect(1)
+        .create_async()
+        .await;
+    let llm = server
+        .mock("POST", "/v1/chat/completions")
+        .match_body(Matcher::PartialJson(json!({
+            "messages": [
+                {"role": "system", "content": "You are a support agent. Be brief."},
+                {"role": "user", "content": "Where is my order?"}
+            ]
+        })))
+        .with_status(200)
+        .with_body(
+            json!({"choices": [{"message": {"role": "assistant", "content": "Shipped"}}]})
+                .to_string(),
+        )
+        .expect(1)
+        .create_async()
+        .await;
+
+    // The mock server is on loopback, where prompts may be fetched over http
+    let config = prompt_url_config(
+        &server.url(),
+        "/prompts/support/v3.md",
+        allow_hosts(&["127.0.0.1"]),
+    );
+    let output = evaluate(config).await.unwrap();
+    assert_eq!(output.status, "success");
+    assert_eq!(
+        output.metadata.system_prompt_url,
+        Some(format!("{}/prompts/support/v3.md", server.url()))
+    );
+    assert_eq!(output.metadata.system_prompt_text, None);
+    prompt.assert_async().await;
+    llm.assert_async().await;
+}
+
+#[tokio::test]
+async fn test_prompt_and_page_fetches_limited_to_allowed_hosts() {
+    let mut server = Server::new_async().await;
+    let html = server
+        .mock("GET", "/prompts/page.html")
+        .with_status(200)
+        .with_header("content-type", "text/html")
+        .with_body(ARTICLE)
+        .create_async()
+        .await;
+    let moved = server
+        .mock("GET", "/moved")
+        .with_status(302)
+        .with_header(
+            "location",
+            &format!(
+                "http://localhost:{}/article",
+                server.socket_address().port()
+            ),
+        )
+        .create_async()
+        .await;
+
+    // Prompts are plain text or markdown only
+    let prompt_config = prompt_url_config(
+        &server.url(),
+        "/prompts/page.html",
+        allow_hosts(&["127.0.0.1"]),
+    );
+    let err = fetch_error(prompt_config).await;
+    assert!(
+        err.to_string()
+            .contains("unsupported content type 'text/html' for a prompt"),
+        "{err}"
+    );
+
+    // Redirects to a host missing from the allowlist are refused
+    let err = fetch_error(config(&server.url(), "/moved", allow_hosts(&["127.0.0.1"]))).await;
+    assert!(
+        err.to_string().contains("host 'localhost'")
+            && err.to_string().contains("fetch.allowed_hosts"),
+        "{err}"
+    );
+
+    let https_only = FetchConfig {
+        https_only: true,
+        ..allow_hosts(&["127.0.0.1"])
+    };
+    let err = fetch_error(config(&server.url(), "/article", https_only)).await;
+    assert!(err.to_string().contains("not an https URL"), "{err}");
+
+    // The host must be allowlisted before anything is fetched
+    let err = ConfigBuilder::new()
+        .api_url("http://localhost:11434/v1/chat/completions")
+        .model("test-model")
+        .system_prompt_url("https://prompts.example.com/v1.md")
+        .user_prompt("Hi")
+        .fetch(allow_hosts(&["artifacts.example.com"]))
+        .build()
+        .unwrap_err();
+    assert!(
+        err.to_string()
+            .contains("must be listed in fetch.allowed_hosts"),
+        "{err}"
+    );
+
+    html.assert_async().await;
+    moved.assert_async().await;
+}
+
+#[test]
+fn test_system_prompt_url_from_config_file() {
+    let config_content = r#"
+api_url = "http://localhost:11434/v1/chat/completions"
+model = "test-model"
+system_prompt_url = "https://artifacts.corp.example/prompts/support/v3.md"
+user_prompt = "Hi"
+
+[fetch]
+allowed_hosts = ["artifacts.corp.example"]
+https_only = true
+"#;
+
+    let mut temp_file = tempfile::Builder::new().suffix(".toml").tempfile().unwrap();
+    temp_file.write_all(config_content.as_bytes()).unwrap();
+    temp_file.flush().unwrap();
+
+    let file_config = load_config_file(temp_file.path()).unwrap();
+    let config = ConfigBuilder::new()
+        .merge_file_config(&file_config)
+        .build()
+        .unwrap();
+    assert_eq!(
+        config.system_prompt_url.as_deref(),
+        Some("https://artifacts.corp.example/prompts/support/v3.md")
+    );
+    assert!(config.system_prompt.is_empty());
+
+    // A system prompt given directly takes precedence over the file's URL
+    let config = ConfigBuilder::new()
+        .system_prompt("Inline")
+        .merge_file_config(&file_config)
+        .build()
+        .unwrap();
+    assert_eq!(config.system_prompt, "Inline");
+    assert_eq!(config.system_prompt_url, None);
+
+    let mut conflicting = tempfile::Builder::new().suffix(".toml").tempfile().unwrap();
+    conflicting
+        .write_all(format!("system_prompt = \"Also inline\"\n{config_content}").as_bytes())
+        .unwrap();
+    conflicting.flush().unwrap();
+    let err = load_config_file(conflicting.path()).unwrap_err();
+    assert!(
+        err.to_string().contains("'system_prompt_url' with"),
+        "{err}"
+    );
+}